@@ -0,0 +1,110 @@
+"""
+# Advent of Code
+
+Solutions to Advent of Code puzzles, one module per year (`y2019.py`),
+sharing `harness.py` for registration, inputs, timing, and answers.
+
+    list        the registered days and parts
+    run         solve a day on its input, and check the recorded answers
+    test        check every part against the examples from its puzzle
+
+`run` prints each part's answer and time, and marks it `ok` if it matches
+the answer recorded with `--record`, or `WRONG` if it does not (and then
+exits with an error, so a refactor that breaks an old day is noticed).
+
+
+## Usage
+
+    python3 aoc.py list
+    python3 aoc.py test
+    python3 aoc.py run 2019 7
+    python3 aoc.py run 2019 7 --input my_input.txt
+    python3 aoc.py run 2019 7 --record
+"""
+
+import argparse
+import importlib
+from typing import List
+
+import harness
+
+
+YEARS: List[str] = ["y2019"]
+
+
+def run_day(year: int, day: int, input_file: str, record: bool) -> bool:
+    """Solve every part of the day, and return whether none were wrong."""
+    solutions = harness.parts(year, day)
+    if not solutions:
+        raise ValueError(f"there is no solution for {year} day {day}")
+    if input_file is None:
+        text = harness.load_input(year, day)
+    else:
+        with open(input_file) as f:
+            text = f.read()
+    all_right = True
+    for solution in solutions:
+        answer, elapsed = harness.timed(solution.function, text)
+        expected = harness.expected_answer(year, day, solution.part)
+        if record:
+            harness.record_answer(year, day, solution.part, answer)
+            verdict = "recorded"
+        elif expected is None:
+            verdict = ""
+        elif str(answer) == expected:
+            verdict = "ok"
+        else:
+            verdict = f"WRONG (the recorded answer is {expected})"
+            all_right = False
+        print(f"{year} day {day} part {solution.part}: {answer}    {elapsed * 1000:.1f} ms    {verdict}".rstrip())
+    return all_right
+
+
+def run_tests() -> bool:
+    passed = failed = 0
+    for solution in harness.SOLUTIONS.values():
+        for text, expected in solution.examples:
+            answer = solution.function(text)
+            name = f"{solution.year} day {solution.day} part {solution.part}"
+            if answer == expected:
+                passed += 1
+            else:
+                failed += 1
+                print(f"FAIL  {name}: expected {expected}, got {answer}, for {text[:40]!r}")
+    print(f"{passed} passed, {failed} failed")
+    return failed == 0
+
+
+def main():
+    for year in YEARS:
+        importlib.import_module(year)
+
+    parser = argparse.ArgumentParser(description="Advent of Code solutions")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    subparsers.add_parser("list", help="list the registered days")
+    subparsers.add_parser("test", help="check every part against its examples")
+    run_parser = subparsers.add_parser("run", help="solve a day")
+    run_parser.add_argument("year", type=int)
+    run_parser.add_argument("day", type=int)
+    run_parser.add_argument("--input", type=str, default=None, help="use this file instead of the cached input")
+    run_parser.add_argument("--record", action="store_true", help="save the answers as the right ones")
+    args = parser.parse_args()
+
+    if args.command == "list":
+        days = sorted({(s.year, s.day) for s in harness.SOLUTIONS.values()})
+        for year, day in days:
+            parts = ", ".join(str(s.part) for s in harness.parts(year, day))
+            print(f"{year} day {day:>2}: part {parts}")
+    elif args.command == "test":
+        if not run_tests():
+            parser.exit(1)
+    else:
+        try:
+            if not run_day(args.year, args.day, args.input, args.record):
+                parser.exit(1)
+        except (OSError, ValueError) as e:
+            parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,130 @@
+"""
+# Advent of Code Harness
+
+What every day's solution needs besides the solution itself: registering
+it, getting the input, timing it, and noticing when a change breaks an
+answer that used to be right.
+
+    @solution(2019, 1, part=1, examples=[("12", 2), ("1969", 654)])
+    def fuel(text: str) -> int:
+        ...
+
+A part takes the puzzle input as text and returns its answer. Its
+examples (from the puzzle's description) are what `aoc.py test` checks.
+
+
+## Inputs and Answers
+
+Everyone's input is different, and Advent of Code asks that inputs are
+not shared, so they are not in the repository: they are downloaded once,
+with your session cookie (from `AOC_SESSION`, or the file
+`~/.config/aoc/session`), and cached in `inputs/` (which git ignores).
+
+Once an answer has been accepted on the site, `--record` saves it in
+`inputs/answers.json`, and every later run checks against it.
+"""
+
+import json
+import os
+import time
+import urllib.request
+from typing import Any, Callable, Dict, List, NamedTuple, Optional, Tuple
+
+
+DIRECTORY: str = os.path.dirname(os.path.abspath(__file__))
+INPUTS: str = os.path.join(DIRECTORY, "inputs")
+ANSWERS: str = os.path.join(INPUTS, "answers.json")
+SESSION_FILE: str = os.path.expanduser("~/.config/aoc/session")
+USER_AGENT: str = "github.com/thedavidchu/puzzles aoc harness"
+
+Part = Callable[[str], Any]
+
+
+class Solution(NamedTuple):
+    year: int
+    day: int
+    part: int
+    function: Part
+    examples: List[Tuple[str, Any]]
+
+
+SOLUTIONS: Dict[Tuple[int, int, int], Solution] = {}
+
+
+def solution(year: int, day: int, part: int, examples: Optional[List[Tuple[str, Any]]] = None):
+    """Register a function as the solution to one part of a day."""
+    def register(function: Part) -> Part:
+        key = (year, day, part)
+        if key in SOLUTIONS:
+            raise ValueError(f"{year} day {day} part {part} is already registered")
+        SOLUTIONS[key] = Solution(year, day, part, function, list(examples or []))
+        return function
+    return register
+
+
+def parts(year: int, day: int) -> List[Solution]:
+    return [SOLUTIONS[key] for key in sorted(SOLUTIONS) if key[:2] == (year, day)]
+
+
+def input_path(year: int, day: int) -> str:
+    return os.path.join(INPUTS, str(year), f"{day:02}.txt")
+
+
+def session_token() -> Optional[str]:
+    token = os.environ.get("AOC_SESSION")
+    if token:
+        return token.strip()
+    if os.path.exists(SESSION_FILE):
+        with open(SESSION_FILE) as f:
+            return f.read().strip()
+    return None
+
+
+def load_input(year: int, day: int) -> str:
+    """The cached input, downloading it the first time."""
+    path = input_path(year, day)
+    if not os.path.exists(path):
+        token = session_token()
+        if token is None:
+            raise ValueError(f"there is no input at {path}, and no session token to download it "
+                             f"(set AOC_SESSION or write it to {SESSION_FILE})")
+        request = urllib.request.Request(
+            f"https://adventofcode.com/{year}/day/{day}/input",
+            headers={"Cookie": f"session={token}", "User-Agent": USER_AGENT},
+        )
+        try:
+            with urllib.request.urlopen(request) as response:
+                text = response.read().decode()
+        except OSError as e:
+            raise ValueError(f"could not download the input for {year} day {day}: {e}") from None
+        os.makedirs(os.path.dirname(path), exist_ok=True)
+        with open(path, "w") as f:
+            f.write(text)
+    with open(path) as f:
+        return f.read()
+
+
+def load_answers() -> Dict[str, str]:
+    if not os.path.exists(ANSWERS):
+        return {}
+    with open(ANSWERS) as f:
+        return json.load(f)
+
+
+def record_answer(year: int, day: int, part: int, answer: Any):
+    answers = load_answers()
+    answers[f"{year}/{day}/{part}"] = str(answer)
+    os.makedirs(INPUTS, exist_ok=True)
+    with open(ANSWERS, "w") as f:
+        json.dump(answers, f, indent=4, sort_keys=True)
+        f.write("\n")
+
+
+def expected_answer(year: int, day: int, part: int) -> Optional[str]:
+    return load_answers().get(f"{year}/{day}/{part}")
+
+
+def timed(function: Part, text: str) -> Tuple[Any, float]:
+    start = time.perf_counter()
+    answer = function(text)
+    return answer, time.perf_counter() - start
@@ -0,0 +1,148 @@
+"""
+# Advent of Code 2019
+
+The Intcode days lean on `intcode/intcode.py`.
+"""
+
+import itertools
+import os
+import sys
+
+from harness import solution
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from intcode.intcode import Intcode, parse, run_feedback_loop
+
+
+################################################################################
+###                      Day 1: The Tyranny of the Rocket Equation           ###
+################################################################################
+
+
+def fuel(mass: int) -> int:
+    return mass // 3 - 2
+
+
+def total_fuel(mass: int) -> int:
+    """The fuel for the mass, and for that fuel, and so on."""
+    total = 0
+    while fuel(mass) > 0:
+        mass = fuel(mass)
+        total += mass
+    return total
+
+
+@solution(2019, 1, part=1, examples=[("12", 2), ("14", 2), ("1969", 654), ("100756", 33583)])
+def day1_part1(text: str) -> int:
+    return sum(fuel(int(line)) for line in text.split())
+
+
+@solution(2019, 1, part=2, examples=[("14", 2), ("1969", 966), ("100756", 50346)])
+def day1_part2(text: str) -> int:
+    return sum(total_fuel(int(line)) for line in text.split())
+
+
+################################################################################
+###                          Day 2: 1202 Program Alarm                       ###
+################################################################################
+
+
+def run_with(program, noun: int, verb: int) -> int:
+    intcode = Intcode(program)
+    intcode.memory[1], intcode.memory[2] = noun, verb
+    intcode.run()
+    return intcode.memory[0]
+
+
+@solution(2019, 2, part=1)
+def day2_part1(text: str) -> int:
+    return run_with(parse(text), 12, 2)
+
+
+@solution(2019, 2, part=2)
+def day2_part2(text: str) -> int:
+    program = parse(text)
+    for noun, verb in itertools.product(range(100), repeat=2):
+        if run_with(program, noun, verb) == 19690720:
+            return 100 * noun + verb
+    raise ValueError("no noun and verb give 19690720")
+
+
+################################################################################
+###                 Day 5: Sunny with a Chance of Asteroids                  ###
+################################################################################
+
+
+def diagnostic(text: str, system: int) -> int:
+    intcode = Intcode(parse(text), [system])
+    intcode.run()
+    outputs = intcode.receive()
+    if any(outputs[:-1]):
+        raise ValueError(f"a diagnostic test failed: {outputs}")
+    return outputs[-1]
+
+
+COMPARE_TO_8 = ("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,"
+                "1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99")
+
+
+@solution(2019, 5, part=1, examples=[("3,0,4,0,99", 1)])
+def day5_part1(text: str) -> int:
+    return diagnostic(text, 1)
+
+
+@solution(2019, 5, part=2, examples=[(COMPARE_TO_8, 999)])
+def day5_part2(text: str) -> int:
+    return diagnostic(text, 5)
+
+
+################################################################################
+###                     Day 7: Amplification Circuit                         ###
+################################################################################
+
+
+def chain(program, phases) -> int:
+    signal = 0
+    for phase in phases:
+        intcode = Intcode(program, [phase, signal])
+        intcode.run()
+        signal = intcode.receive()[-1]
+    return signal
+
+
+@solution(2019, 7, part=1, examples=[("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0", 43210)])
+def day7_part1(text: str) -> int:
+    program = parse(text)
+    return max(chain(program, phases) for phases in itertools.permutations(range(5)))
+
+
+@solution(2019, 7, part=2, examples=[
+    ("3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5", 139629729),
+])
+def day7_part2(text: str) -> int:
+    program = parse(text)
+    return max(run_feedback_loop(program, list(phases)) for phases in itertools.permutations(range(5, 10)))
+
+
+################################################################################
+###                         Day 9: Sensor Boost                              ###
+################################################################################
+
+
+def boost(text: str, mode: int) -> int:
+    intcode = Intcode(parse(text), [mode])
+    intcode.run()
+    outputs = intcode.receive()
+    if len(outputs) != 1:
+        raise ValueError(f"these opcodes are broken: {outputs}")
+    return outputs[0]
+
+
+@solution(2019, 9, part=1, examples=[("104,1125899906842624,99", 1125899906842624)])
+def day9_part1(text: str) -> int:
+    return boost(text, 1)
+
+
+@solution(2019, 9, part=2)
+def day9_part2(text: str) -> int:
+    return boost(text, 2)
@@ -0,0 +1,248 @@
+"""
+# Boggle
+
+A grid of lettered dice. A word is spelt by a path of tiles, each step
+to one of the eight around it, no tile used twice in the same word. The
+Q die reads Qu, since a Q alone is hardly any use in English, so the Qu
+tile gives two letters of the word.
+
+
+## Searching
+
+Every word on the board is found by a depth-first search from each
+tile, which is far too many paths to follow to the end: on a 4x4 board
+there are about 12 million. The dictionary is kept in a trie, a tree
+with one letter an edge, so a path's letters are a walk down it, and
+the search turns back as soon as they are not the start of any word.
+Most paths die after two or three letters.
+
+A word can only be found if all its letters are on the board, so only
+those words go in the trie, which is much quicker than loading all of a
+big dictionary for each board.
+
+
+## Scoring
+
+Words shorter than --min letters do not count: 3 by default, and 4 in
+Big Boggle, on 5x5. The rest score by length, with Qu as two letters:
+
+    3, 4        1 point
+    5           2
+    6           3
+    7           5
+    8 or more   11
+
+
+## The Format
+
+One row of tiles a line, separated by spaces, with Qu (or Q) for the Qu
+tile. Case is ignored, and lines starting with # are comments. A board
+on the command line has its rows separated by /.
+
+    R E A D
+    T Qu I E
+    S N O W
+    L A M P
+
+`random` rolls the 16 dice of the 4x4 game; on any other size, each
+tile rolls a die picked at random from those 16.
+
+
+## Usage
+
+    python3 boggle.py solve boards/classic.txt
+    python3 boggle.py solve "cat/ode/gsk"
+    python3 boggle.py solve boards/big.txt --min 4 --top 20
+    python3 boggle.py random --seed 1
+    python3 boggle.py random --size 6x3 --seed 2 --words ../wordle/words_length_5.txt
+"""
+
+import argparse
+import os
+import random
+import sys
+import textwrap
+from typing import Dict, List, Optional, Set
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from rng.rng import add_seed_argument, make_rng
+
+
+DEFAULT_WORDS: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "..", "wordle", "words.txt")
+# The dice of the 4x4 game (the 1987 set); the Q face is the Qu tile
+DICE: List[str] = [
+    "AAEEGN", "ABBJOO", "ACHOPS", "AFFKPS", "AOOTTW", "CIMOTU", "DEILRX", "DELRVY",
+    "DISTTY", "EEGHNW", "EEINSU", "EHRTVW", "EIOSST", "ELRTTY", "HIMNQU", "HLNNRZ",
+]
+# The score of a word by its length; longer than the last scores the last
+SCORES: List[int] = [0, 0, 0, 1, 1, 2, 3, 5, 11]
+END: str = "$"      # the key in a trie node that holds the word ending there
+
+
+class Trie:
+    """The words, as nested dicts of letters, with END in the node where a word ends."""
+
+    def __init__(self, words: List[str] = ()):
+        self.root: Dict[str, dict] = {}
+        self.size = 0
+        for word in words:
+            self.add(word)
+
+    def add(self, word: str):
+        node = self.root
+        for letter in word:
+            node = node.setdefault(letter, {})
+        if END not in node:
+            node[END] = word
+            self.size += 1
+
+    def __contains__(self, word: str) -> bool:
+        node = self.walk(self.root, word)
+        return node is not None and END in node
+
+    @staticmethod
+    def walk(node: dict, letters: str) -> Optional[dict]:
+        """The node below node along letters, or None if no word goes that way."""
+        for letter in letters:
+            node = node.get(letter)
+            if node is None:
+                return None
+        return node
+
+
+def score(word: str) -> int:
+    return SCORES[min(len(word), len(SCORES) - 1)]
+
+
+def find_words(board: Grid, trie: Trie, shortest: int = 3) -> Dict[str, List[Position]]:
+    """Every word of the trie at least shortest letters long on the board, with a path that spells it."""
+    found: Dict[str, List[Position]] = {}
+    path: List[Position] = []
+    used: Set[Position] = set()
+
+    def visit(position: Position, node: dict):
+        node = Trie.walk(node, board[position])
+        if node is None:
+            return
+        path.append(position)
+        used.add(position)
+        word = node.get(END)
+        if word is not None and len(word) >= shortest and word not in found:
+            found[word] = list(path)
+        for neighbour in board.neighbours(position, diagonal=True):
+            if neighbour not in used:
+                visit(neighbour, node)
+        path.pop()
+        used.remove(position)
+
+    for position in board.positions():
+        visit(position, trie.root)
+    return found
+
+
+def roll(width: int, height: int, rng: random.Random) -> Grid:
+    if (width, height) == (4, 4):
+        dice = list(DICE)
+        rng.shuffle(dice)
+    else:
+        dice = [rng.choice(DICE) for _ in range(width * height)]
+    faces = [rng.choice(die).lower() for die in dice]
+    return Grid([[tile(face) for face in faces[y * width:(y + 1) * width]] for y in range(height)])
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def tile(text: str) -> str:
+    """The letters a tile gives a word: q is the Qu tile."""
+    text = text.lower()
+    if text in ("q", "qu"):
+        return "qu"
+    if len(text) != 1 or not text.isalpha() or not text.isascii():
+        raise ValueError(f"a tile is a letter or Qu, not {text!r}")
+    return text
+
+
+def parse(text: str) -> Grid:
+    rows = [line for line in text.split("\n") if line.strip() and not line.lstrip().startswith("#")]
+    if not rows:
+        raise ValueError("the board is empty")
+    return Grid.parse("\n".join(rows), tile, separator=" ")
+
+
+def read_board(argument: str) -> Grid:
+    if os.path.exists(argument):
+        with open(argument) as f:
+            return parse(f.read())
+    rows = argument.lower().replace("qu", "q").split("/")
+    return parse("\n".join(" ".join(row) for row in rows))
+
+
+def read_words(path: str, letters: Set[str]) -> Trie:
+    """The words of the list spelt only with these letters, in a trie."""
+    trie = Trie()
+    with open(path) as f:
+        for line in f:
+            word = line.strip().lower()
+            if word.isalpha() and word.isascii() and set(word) <= letters:
+                trie.add(word)
+    return trie
+
+
+def render(board: Grid) -> str:
+    return "\n".join(" ".join(t.capitalize().ljust(2) for t in row).rstrip() for row in board.rows())
+
+
+def report(board: Grid, words: str, shortest: int, top: Optional[int]):
+    print(render(board))
+    print()
+    trie = read_words(words, set("".join(t for _, t in board.items())))
+    found = find_words(board, trie, shortest)
+    ranked = sorted(found, key=lambda word: (-len(word), word))
+    for length in sorted({len(word) for word in ranked}, reverse=True):
+        same = [word for word in ranked if len(word) == length]
+        points = score(same[0])
+        heading = f"{length} letters, {points} point{'s' * (points != 1)} each: "
+        print(textwrap.fill(", ".join(same), width=100, initial_indent=heading,
+                            subsequent_indent=" " * 4))
+        if top is not None and len([word for word in ranked if len(word) >= length]) >= top:
+            break
+    total = sum(score(word) for word in found)
+    print(f"{len(found)} word{'s' * (len(found) != 1)}, {total} point{'s' * (total != 1)}")
+    if ranked:
+        longest = ranked[0]
+        print(f"The longest, {longest}: " + " ".join(f"({x + 1},{y + 1})" for x, y in found[longest]))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Find every word on a Boggle board")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="find the words on a board")
+    solve_parser.add_argument("board", type=str, help="a file, or the rows of tiles separated by /")
+    random_parser = subparsers.add_parser("random", help="roll the dice and find the words")
+    random_parser.add_argument("--size", type=str, default="4x4", help="WxH (default 4x4)")
+    add_seed_argument(random_parser)
+    for p in (solve_parser, random_parser):
+        p.add_argument("--words", type=str, default=DEFAULT_WORDS, help="the word list, one word a line")
+        p.add_argument("--min", type=int, default=3, help="the fewest letters a word can have (default 3)")
+        p.add_argument("--top", type=int, default=None, help="list only the longest words, at least this many")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "random":
+            width, x, height = args.size.partition("x")
+            if not x or not width.isdigit() or not height.isdigit() or int(width) < 1 or int(height) < 1:
+                raise ValueError("the size must be WxH, e.g. 5x5")
+            board = roll(int(width), int(height), make_rng(args.seed))
+        else:
+            board = read_board(args.board)
+        report(board, args.words, args.min, args.top)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
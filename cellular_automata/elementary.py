@@ -0,0 +1,121 @@
+"""
+# Elementary Cellular Automata
+
+A row of cells, each 0 or 1. Every generation, each cell looks at itself
+and its two neighbours, and the rule number says what it becomes: the
+neighbourhood (left, centre, right) read as a binary number 0 to 7
+picks that bit of the rule. For example, rule 110 is 01101110 in binary,
+so the neighbourhood 111 (bit 7) becomes 0, 110 (bit 6) becomes 1, and
+so on. Rule 30 looks random, rule 90 draws a Sierpinski triangle, and
+rule 110 is Turing-complete.
+
+Printing every generation under the last draws the space-time diagram.
+
+
+## Boundaries
+
+The cells beyond either end of the row are
+
+    zero      always 0 (the default)
+    one       always 1
+    wrap      the other end of the row (the row is a ring)
+    reflect   the end cell itself, mirrored
+
+
+## Usage
+
+    python3 elementary.py --rule 30 --width 79 --generations 40
+    python3 elementary.py --rule 110 --start right --generations 60
+    python3 elementary.py --rule 90 --width 255 --generations 128 --png rule90.png --scale 3
+    python3 elementary.py --rule 184 --start random --seed 1 --boundary wrap
+    python3 elementary.py --rule 54 --start 0001101000
+"""
+
+import argparse
+import os
+import sys
+from typing import List, Optional
+
+from png import scale, write_png
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+BOUNDARIES: List[str] = ["zero", "one", "wrap", "reflect"]
+ALIVE: str = "#"
+DEAD: str = " "
+PALETTE = [(255, 255, 255), (0, 0, 0)]
+
+
+def step(row: List[int], rule: int, boundary: str) -> List[int]:
+    if boundary == "wrap":
+        left, right = row[-1], row[0]
+    elif boundary == "reflect":
+        left, right = row[0], row[-1]
+    else:
+        left = right = 1 if boundary == "one" else 0
+    padded = [left] + row + [right]
+    return [
+        (rule >> (padded[i - 1] << 2 | padded[i] << 1 | padded[i + 1])) & 1
+        for i in range(1, len(padded) - 1)
+    ]
+
+
+def run(row: List[int], rule: int, generations: int, boundary: str) -> List[List[int]]:
+    """Return the first `generations` rows, starting with `row`."""
+    rows = [row]
+    for _ in range(generations - 1):
+        rows.append(step(rows[-1], rule, boundary))
+    return rows
+
+
+def initial_row(start: str, width: int, seed: Optional[int]) -> List[int]:
+    if start == "centre" or start == "center":
+        row = [0] * width
+        row[width // 2] = 1
+        return row
+    if start == "right":
+        return [0] * (width - 1) + [1]
+    if start == "random":
+        rng = make_rng(seed)
+        return [rng.randint(0, 1) for _ in range(width)]
+    if start and set(start) <= {"0", "1"}:
+        return [int(c) for c in start]
+    raise ValueError(f"the start must be centre, right, random, or a row of 0s and 1s, not '{start}'")
+
+
+def render(rows: List[List[int]]) -> str:
+    return "\n".join("".join(ALIVE if c else DEAD for c in row).rstrip() for row in rows)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Elementary cellular automata")
+    parser.add_argument("--rule", type=int, default=110, help="0 to 255")
+    parser.add_argument("--width", type=int, default=79, help="ignored if --start gives the row")
+    parser.add_argument("--generations", type=int, default=40)
+    parser.add_argument("--start", type=str, default="centre", help="centre, right, random, or e.g. 0110")
+    add_seed_argument(parser, help="for --start random")
+    parser.add_argument("--boundary", choices=BOUNDARIES, default="zero")
+    parser.add_argument("--png", type=str, default=None, help="save the space-time diagram here")
+    parser.add_argument("--scale", type=int, default=1, help="pixels per cell in the PNG")
+    parser.add_argument("--quiet", action="store_true", help="do not print the diagram")
+    args = parser.parse_args()
+
+    if not 0 <= args.rule <= 255:
+        parser.error("the rule must be between 0 and 255")
+    if args.width < 1 or args.generations < 1 or args.scale < 1:
+        parser.error("the width, generations, and scale must be positive")
+    try:
+        row = initial_row(args.start, args.width, args.seed)
+    except ValueError as e:
+        parser.error(str(e))
+    rows = run(row, args.rule, args.generations, args.boundary)
+    if not args.quiet:
+        print(render(rows))
+    if args.png is not None:
+        write_png(args.png, scale(rows, PALETTE, args.scale))
+
+
+if __name__ == "__main__":
+    main()
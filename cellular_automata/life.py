@@ -0,0 +1,529 @@
+"""
+# Conway's Game of Life
+
+An infinite grid of cells, each alive or dead. Every generation, a dead
+cell with exactly 3 live neighbours is born, a live cell with 2 or 3
+live neighbours survives, and every other cell dies. That is the rule
+B3/S23; other "Life-like" rules (e.g. HighLife, B36/S23) work too.
+
+
+## Universes
+
+    unbounded   the infinite grid, stored as the set of live cells
+    torus       a --width by --height grid whose edges wrap around
+    bitwise     the same torus, as one big integer with a bit per cell
+    hashlife    the infinite grid, using Bill Gosper's HashLife
+
+The bitwise torus steps every cell at once, with no loop over the cells
+in Python: the eight neighbours are the board shifted eight ways (with
+the shifts wrapped around within each row), and their sum is kept as
+four bit-planes, each an integer whose bit for a cell is one binary
+digit of that cell's count, added up with the logic of a ripple-carry
+adder. The rule then picks the cells whose count (and state) it wants.
+It is the trick that SIMD Life uses, with the whole board as one wide
+register, and is much faster than the set on a busy torus.
+
+HashLife stores the universe as a quadtree in which identical squares
+are the same node, and memoizes how each node evolves, so a pattern
+with a lot of repetition in space and time (e.g. a glider gun) can be
+run for billions of generations. It jumps 2^j generations at once, and
+runs n generations by jumping by each power of two in n.
+
+
+## Patterns
+
+Patterns are read from RLE files, the standard format of e.g. the
+LifeWiki and Golly. `patterns/` has a few classics.
+
+
+## Usage
+
+    python3 life.py patterns/glider_gun.rle --animate
+    python3 life.py patterns/r_pentomino.rle --generations 1103
+    python3 life.py patterns/acorn.rle --universe torus --width 120 --height 60 --animate
+    python3 life.py patterns/acorn.rle --universe bitwise --width 120 --height 60 --generations 5000
+    python3 life.py patterns/glider_gun.rle --universe hashlife --generations 1000000000
+    python3 life.py patterns/r_pentomino.rle --benchmark 1000
+    python3 life.py patterns/acorn.rle --benchmark 1000 --width 256 --height 256
+"""
+
+import argparse
+import re
+import time
+from typing import Dict, Iterable, List, NamedTuple, Optional, Set, Tuple
+
+
+Cell = Tuple[int, int]
+UNIVERSES: List[str] = ["unbounded", "torus", "bitwise", "hashlife"]
+ALIVE: str = "#"
+DEAD: str = "."
+CLEAR_SCREEN: str = "\x1b[H\x1b[2J"
+NEIGHBOURS: List[Cell] = [(dx, dy) for dy in (-1, 0, 1) for dx in (-1, 0, 1) if (dx, dy) != (0, 0)]
+
+
+class Rule(NamedTuple):
+    birth: Set[int]
+    survival: Set[int]
+
+
+CONWAY: Rule = Rule({3}, {2, 3})
+
+
+def parse_rule(text: str) -> Rule:
+    match = re.fullmatch(r"[Bb](\d*)/[Ss](\d*)", text.strip())
+    if match is None:
+        # The older S/B notation, e.g. 23/3
+        match = re.fullmatch(r"(\d*)/(\d*)", text.strip())
+        if match is None:
+            raise ValueError(f"cannot read the rule '{text}'")
+        return Rule({int(c) for c in match.group(2)}, {int(c) for c in match.group(1)})
+    return Rule({int(c) for c in match.group(1)}, {int(c) for c in match.group(2)})
+
+
+def parse_rle(text: str) -> Tuple[Set[Cell], Rule]:
+    cells: Set[Cell] = set()
+    rule = CONWAY
+    body: List[str] = []
+    for line in text.split("\n"):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        if line.startswith("x"):
+            match = re.search(r"rule\s*=\s*([^,\s]+)", line)
+            if match is not None:
+                rule = parse_rule(match.group(1))
+            continue
+        body.append(line)
+    x, y = 0, 0
+    for count, tag in re.findall(r"(\d*)([bo$!]|[A-Za-z])", "".join(body)):
+        n = int(count) if count else 1
+        if tag == "!":
+            break
+        if tag == "$":
+            x, y = 0, y + n
+        elif tag == "b":
+            x += n
+        else:   # `o`, or any other letter in multi-state files
+            cells.update((x + i, y) for i in range(n))
+            x += n
+    return cells, rule
+
+
+def load_rle(path: str) -> Tuple[Set[Cell], Rule]:
+    with open(path) as f:
+        return parse_rle(f.read())
+
+
+def bounding_box(cells: Iterable[Cell]) -> Optional[Tuple[int, int, int, int]]:
+    cells = list(cells)
+    if not cells:
+        return None
+    xs = [x for x, _ in cells]
+    ys = [y for _, y in cells]
+    return min(xs), min(ys), max(xs), max(ys)
+
+
+################################################################################
+###                            Unbounded and Torus                           ###
+################################################################################
+
+
+class SetLife:
+    """The live cells as a set. With a width and height, the grid is a torus."""
+
+    def __init__(self, cells: Set[Cell], rule: Rule, size: Optional[Tuple[int, int]] = None):
+        self.rule = rule
+        self.size = size
+        self.generation = 0
+        self.cells = {self.wrap(c) for c in cells}
+
+    def wrap(self, cell: Cell) -> Cell:
+        if self.size is None:
+            return cell
+        return cell[0] % self.size[0], cell[1] % self.size[1]
+
+    def step(self):
+        counts: Dict[Cell, int] = {}
+        for x, y in self.cells:
+            for dx, dy in NEIGHBOURS:
+                neighbour = self.wrap((x + dx, y + dy))
+                counts[neighbour] = counts.get(neighbour, 0) + 1
+        self.cells = {
+            cell for cell, n in counts.items()
+            if n in (self.rule.survival if cell in self.cells else self.rule.birth)
+        }
+        self.generation += 1
+
+    def advance(self, generations: int):
+        for _ in range(generations):
+            self.step()
+
+    def live_cells(self) -> Set[Cell]:
+        return self.cells
+
+
+################################################################################
+###                               Bitwise Torus                              ###
+################################################################################
+
+
+class BitLife:
+    """A width x height torus as one integer: bit y * width + x is the cell (x, y)."""
+
+    def __init__(self, cells: Set[Cell], rule: Rule, size: Tuple[int, int]):
+        self.rule = rule
+        self.width, self.height = size
+        self.generation = 0
+        self.cells_mask = (1 << (self.width * self.height)) - 1
+        first_column = sum(1 << (y * self.width) for y in range(self.height))
+        self.first_column = first_column
+        self.last_column = first_column << (self.width - 1)
+        self.board = 0
+        for x, y in cells:
+            self.board |= 1 << ((y % self.height) * self.width + x % self.width)
+
+    def shift_rows(self, board: int, dy: int) -> int:
+        """The board moved down by dy rows (up if negative), wrapping around."""
+        bits = (dy % self.height) * self.width
+        total = self.width * self.height
+        return ((board << bits) | (board >> (total - bits))) & self.cells_mask
+
+    def shift_columns(self, board: int, dx: int) -> int:
+        """The board moved right by one column (dx = 1) or left (dx = -1), wrapping within each row."""
+        if dx == 1:
+            return (((board << 1) & ~self.first_column & self.cells_mask)
+                    | ((board & self.last_column) >> (self.width - 1)))
+        return ((board >> 1) & ~self.last_column) | ((board & self.first_column) << (self.width - 1))
+
+    def step(self):
+        board = self.board
+        rows = [self.shift_rows(board, -1), board, self.shift_rows(board, 1)]
+        neighbours = [rows[0], rows[2]]
+        for row in rows:
+            neighbours += [self.shift_columns(row, -1), self.shift_columns(row, 1)]
+        planes: List[int] = []      # planes[i] has bit i of every cell's count
+        for neighbour in neighbours:
+            carry = neighbour
+            for i in range(len(planes)):
+                planes[i], carry = planes[i] ^ carry, planes[i] & carry
+                if not carry:
+                    break
+            if carry:
+                planes.append(carry)
+
+        def count_is(n: int) -> int:
+            if n >> len(planes):
+                return 0
+            mask = self.cells_mask
+            for i, plane in enumerate(planes):
+                mask &= plane if n >> i & 1 else ~plane
+            return mask
+
+        born = survive = 0
+        for n in self.rule.birth:
+            born |= count_is(n)
+        for n in self.rule.survival:
+            survive |= count_is(n)
+        self.board = ((born & ~board) | (survive & board)) & self.cells_mask
+        self.generation += 1
+
+    def advance(self, generations: int):
+        for _ in range(generations):
+            self.step()
+
+    def live_cells(self) -> Set[Cell]:
+        cells: Set[Cell] = set()
+        board = self.board
+        while board:
+            low = board & -board
+            index = low.bit_length() - 1
+            cells.add((index % self.width, index // self.width))
+            board ^= low
+        return cells
+
+    @property
+    def population(self) -> int:
+        return bin(self.board).count("1")
+
+
+################################################################################
+###                                 HashLife                                 ###
+################################################################################
+
+
+class Node:
+    """A square of 2^level cells a side, split into four quadrants.
+
+    Nodes are interned, so two squares with the same contents are the
+    same node, and the memo tables can key on identity.
+    """
+
+    __slots__ = ("level", "a", "b", "c", "d", "population")
+
+    def __init__(self, level: int, a, b, c, d, population: int):
+        self.level = level
+        # a b
+        # c d
+        self.a, self.b, self.c, self.d = a, b, c, d
+        self.population = population
+
+
+class HashLife:
+    def __init__(self, cells: Set[Cell], rule: Rule):
+        self.rule = rule
+        self.generation = 0
+        self.on = Node(0, None, None, None, None, 1)
+        self.off = Node(0, None, None, None, None, 0)
+        self.nodes: Dict[Tuple[int, int, int, int], Node] = {}
+        self.zeros: List[Node] = [self.off]
+        self.successors: Dict[Tuple[int, int], Node] = {}
+        box = bounding_box(cells)
+        if box is None:
+            self.x, self.y, self.root = 0, 0, self.zero(3)
+            return
+        x0, y0, x1, y1 = box
+        level = 3
+        while 1 << level <= max(x1 - x0, y1 - y0):
+            level += 1
+        self.x, self.y = x0, y0
+        self.root = self.build({(x - x0, y - y0) for x, y in cells}, 0, 0, level)
+
+    def join(self, a: Node, b: Node, c: Node, d: Node) -> Node:
+        key = (id(a), id(b), id(c), id(d))
+        node = self.nodes.get(key)
+        if node is None:
+            node = Node(a.level + 1, a, b, c, d, a.population + b.population + c.population + d.population)
+            self.nodes[key] = node
+        return node
+
+    def zero(self, level: int) -> Node:
+        while len(self.zeros) <= level:
+            z = self.zeros[-1]
+            self.zeros.append(self.join(z, z, z, z))
+        return self.zeros[level]
+
+    def build(self, cells: Set[Cell], x: int, y: int, level: int) -> Node:
+        if not cells:
+            return self.zero(level)
+        if level == 0:
+            return self.on
+        half = 1 << (level - 1)
+        quadrants: List[Set[Cell]] = [set(), set(), set(), set()]
+        for cx, cy in cells:
+            quadrants[(cx >= x + half) + 2 * (cy >= y + half)].add((cx, cy))
+        return self.join(
+            self.build(quadrants[0], x, y, level - 1),
+            self.build(quadrants[1], x + half, y, level - 1),
+            self.build(quadrants[2], x, y + half, level - 1),
+            self.build(quadrants[3], x + half, y + half, level - 1),
+        )
+
+    def pad(self, node: Node) -> Node:
+        """The node one level up with `node` in the middle."""
+        z = self.zero(node.level - 1)
+        return self.join(
+            self.join(z, z, z, node.a), self.join(z, z, node.b, z),
+            self.join(z, node.c, z, z), self.join(node.d, z, z, z),
+        )
+
+    def centre(self, node: Node) -> Node:
+        """The middle half of the node."""
+        return self.join(node.a.d, node.b.c, node.c.b, node.d.a)
+
+    def is_centred(self, node: Node) -> bool:
+        """Whether every live cell is in the middle half."""
+        return node.population == self.centre(node).population
+
+    def life_4x4(self, node: Node) -> Node:
+        """The middle 2x2 of a 4x4 node, one generation on."""
+        grid = [[0] * 4 for _ in range(4)]
+        for qy, row in enumerate(((node.a, node.b), (node.c, node.d))):
+            for qx, quadrant in enumerate(row):
+                for sy, sub_row in enumerate(((quadrant.a, quadrant.b), (quadrant.c, quadrant.d))):
+                    for sx, cell in enumerate(sub_row):
+                        grid[2 * qy + sy][2 * qx + sx] = cell.population
+
+        def cell(x: int, y: int) -> Node:
+            n = sum(grid[y + dy][x + dx] for dx, dy in NEIGHBOURS)
+            alive = n in (self.rule.survival if grid[y][x] else self.rule.birth)
+            return self.on if alive else self.off
+
+        return self.join(cell(1, 1), cell(2, 1), cell(1, 2), cell(2, 2))
+
+    def successor(self, node: Node, j: int) -> Node:
+        """The middle half of the node, 2^j generations on (j <= level - 2)."""
+        key = (id(node), j)
+        result = self.successors.get(key)
+        if result is not None:
+            return result
+        if node.population == 0:
+            result = node.a
+        elif node.level == 2:
+            result = self.life_4x4(node)
+        else:
+            a, b, c, d, join = node.a, node.b, node.c, node.d, self.join
+            # The nine overlapping sub-squares, each half the size of the node
+            squares = [
+                a, join(a.b, b.a, a.d, b.c), b,
+                join(a.c, a.d, c.a, c.b), join(a.d, b.c, c.b, d.a), join(b.c, b.d, d.a, d.b),
+                c, join(c.b, d.a, c.d, d.c), d,
+            ]
+            if j < node.level - 2:
+                # Step each of the nine by 2^j, then stitch their middles together
+                s = [self.successor(square, j) for square in squares]
+                result = join(
+                    join(s[0].d, s[1].c, s[3].b, s[4].a), join(s[1].d, s[2].c, s[4].b, s[5].a),
+                    join(s[3].d, s[4].c, s[6].b, s[7].a), join(s[4].d, s[5].c, s[7].b, s[8].a),
+                )
+            else:
+                # Step the nine by half of 2^j, then the four overlaps by the other half
+                s = [self.successor(square, j - 1) for square in squares]
+                result = join(
+                    self.successor(join(s[0], s[1], s[3], s[4]), j - 1),
+                    self.successor(join(s[1], s[2], s[4], s[5]), j - 1),
+                    self.successor(join(s[3], s[4], s[6], s[7]), j - 1),
+                    self.successor(join(s[4], s[5], s[7], s[8]), j - 1),
+                )
+        self.successors[key] = result
+        return result
+
+    def advance(self, generations: int):
+        j = 0
+        while generations:
+            if generations & 1:
+                self.jump(j)
+            generations >>= 1
+            j += 1
+
+    def jump(self, j: int):
+        """Run 2^j generations."""
+        node, x, y = self.root, self.x, self.y
+        # Crop, then pad until nothing can reach the edge of the middle half
+        while node.level > 3 and self.is_centred(node):
+            x, y = x + (1 << (node.level - 2)), y + (1 << (node.level - 2))
+            node = self.centre(node)
+        while node.level < j + 3 or not (self.is_centred(node) and self.is_centred(self.centre(node))):
+            x, y = x - (1 << (node.level - 1)), y - (1 << (node.level - 1))
+            node = self.pad(node)
+        self.root = self.successor(node, j)
+        self.x, self.y = x + (1 << (node.level - 2)), y + (1 << (node.level - 2))
+        self.generation += 1 << j
+
+    def live_cells(self) -> Set[Cell]:
+        cells: Set[Cell] = set()
+        stack = [(self.root, self.x, self.y)]
+        while stack:
+            node, x, y = stack.pop()
+            if node.population == 0:
+                continue
+            if node.level == 0:
+                cells.add((x, y))
+                continue
+            half = 1 << (node.level - 1)
+            stack += [(node.a, x, y), (node.b, x + half, y), (node.c, x, y + half), (node.d, x + half, y + half)]
+        return cells
+
+    @property
+    def population(self) -> int:
+        return self.root.population
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def render(cells: Set[Cell], window: Optional[Tuple[int, int, int, int]]) -> str:
+    if window is None:
+        return "(empty)"
+    x0, y0, x1, y1 = window
+    return "\n".join(
+        "".join(ALIVE if (x, y) in cells else DEAD for x in range(x0, x1 + 1))
+        for y in range(y0, y1 + 1)
+    )
+
+
+def make_universe(args, cells: Set[Cell], rule: Rule):
+    if args.universe == "torus":
+        return SetLife(cells, rule, (args.width, args.height))
+    if args.universe == "bitwise":
+        return BitLife(cells, rule, (args.width, args.height))
+    if args.universe == "hashlife":
+        return HashLife(cells, rule)
+    return SetLife(cells, rule)
+
+
+def population(universe) -> int:
+    if isinstance(universe, (HashLife, BitLife)):
+        return universe.population
+    return len(universe.cells)
+
+
+def animate(universe, args):
+    if args.universe in ("torus", "bitwise"):
+        window = (0, 0, args.width - 1, args.height - 1)
+    else:
+        box = bounding_box(universe.live_cells()) or (0, 0, 0, 0)
+        cx, cy = (box[0] + box[2]) // 2, (box[1] + box[3]) // 2
+        window = (cx - args.width // 2, cy - args.height // 2,
+                  cx - args.width // 2 + args.width - 1, cy - args.height // 2 + args.height - 1)
+    try:
+        while args.generations is None or universe.generation < args.generations:
+            print(CLEAR_SCREEN + f"Generation {universe.generation}    Population {population(universe)}")
+            print(render(universe.live_cells(), window))
+            time.sleep(args.delay)
+            universe.advance(args.step)
+    except KeyboardInterrupt:
+        pass
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Conway's Game of Life")
+    parser.add_argument("pattern", type=str, help="path to an RLE pattern")
+    parser.add_argument("--universe", choices=UNIVERSES, default="unbounded")
+    parser.add_argument("--rule", type=str, default=None, help="e.g. B36/S23 (overrides the file's rule)")
+    parser.add_argument("--generations", type=int, default=None)
+    parser.add_argument("--width", type=int, default=79, help="of the tori, or of the animation window")
+    parser.add_argument("--height", type=int, default=40, help="of the tori, or of the animation window")
+    parser.add_argument("--animate", action="store_true", help="show every --step generations")
+    parser.add_argument("--step", type=int, default=1, help="generations per animation frame")
+    parser.add_argument("--delay", type=float, default=0.05, help="seconds between frames")
+    parser.add_argument("--benchmark", type=int, default=None, metavar="N",
+                        help="time N generations in every universe")
+    args = parser.parse_args()
+
+    try:
+        cells, rule = load_rle(args.pattern)
+        if args.rule is not None:
+            rule = parse_rule(args.rule)
+    except ValueError as e:
+        parser.error(str(e))
+
+    if args.benchmark is not None:
+        for name in UNIVERSES:
+            args.universe = name
+            universe = make_universe(args, cells, rule)
+            start = time.perf_counter()
+            universe.advance(args.benchmark)
+            elapsed = time.perf_counter() - start
+            rate = args.benchmark / elapsed if elapsed else float("inf")
+            print(f"{name:<10} {args.benchmark} generations in {elapsed:.3f}s "
+                  f"({rate:.0f} per second), population {population(universe)}")
+        return
+
+    universe = make_universe(args, cells, rule)
+    if args.animate:
+        animate(universe, args)
+        return
+    universe.advance(args.generations or 0)
+    print(f"Generation {universe.generation}    Population {population(universe)}")
+    # Only draw patterns that fit on the screen
+    if population(universe) <= args.width * args.height:
+        live = universe.live_cells()
+        box = bounding_box(live)
+        if box is None or (box[2] - box[0] < args.width and box[3] - box[1] < args.height):
+            print(render(live, box))
+
+
+if __name__ == "__main__":
+    main()
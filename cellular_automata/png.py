@@ -0,0 +1,48 @@
+"""
+# PNG Writer
+
+Just enough of PNG to save the automata as images without any
+dependencies: 8-bit RGB, no interlacing, and every row filtered with
+filter type 0 (none), compressed with zlib.
+"""
+
+import struct
+import zlib
+from typing import List, Sequence, Tuple
+
+
+Colour = Tuple[int, int, int]
+PNG_SIGNATURE: bytes = b"\x89PNG\r\n\x1a\n"
+
+
+def _chunk(kind: bytes, data: bytes) -> bytes:
+    crc = zlib.crc32(kind + data) & 0xFFFFFFFF
+    return struct.pack(">I", len(data)) + kind + data + struct.pack(">I", crc)
+
+
+def write_png(path: str, pixels: Sequence[Sequence[Colour]]):
+    """Save rows of (r, g, b) pixels. Every row must be the same width."""
+    height = len(pixels)
+    width = len(pixels[0]) if height else 0
+    if width == 0:
+        raise ValueError("cannot save an empty image")
+    raw: List[bytes] = []
+    for row in pixels:
+        if len(row) != width:
+            raise ValueError("every row must be the same width")
+        raw.append(b"\x00" + bytes(channel for pixel in row for channel in pixel))
+    header = struct.pack(">IIBBBBB", width, height, 8, 2, 0, 0, 0)
+    with open(path, "wb") as f:
+        f.write(PNG_SIGNATURE)
+        f.write(_chunk(b"IHDR", header))
+        f.write(_chunk(b"IDAT", zlib.compress(b"".join(raw), 9)))
+        f.write(_chunk(b"IEND", b""))
+
+
+def scale(cells: Sequence[Sequence[int]], palette: Sequence[Colour], factor: int) -> List[List[Colour]]:
+    """Turn a grid of palette indices into pixels, each cell factor x factor."""
+    pixels: List[List[Colour]] = []
+    for row in cells:
+        line = [palette[c] for c in row for _ in range(factor)]
+        pixels += [line] * factor
+    return pixels
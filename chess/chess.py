@@ -0,0 +1,597 @@
+"""
+# Chess Problems: Mate in N
+
+Find the move that forces checkmate in N moves against any defence, as
+in the problems printed in newspapers: "White to play and mate in 2".
+
+
+## The Board
+
+0x88: 128 squares, rank * 16 + file, of which only those with the 0x88
+bits clear are on the board, so a step off any edge is caught by one
+and. Pieces are letters, as in FEN: PNBRQK for White, pnbrqk for Black.
+
+Moves are generated for each piece and then checked for legality by
+making them and seeing whether the mover's king is attacked, with
+castling (not out of, through, or into check), en passant, and
+promotion. The generator is checked by perft, the number of positions
+reached in exactly d moves, against the published counts for six
+positions that between them hit every kind of move (`test`).
+
+
+## Mate in N
+
+A depth-first search over the attacker's moves: a move wins in n if
+every reply loses in n - 1, and a move wins in 1 if it mates. Checks
+are tried first, then captures, and positions already shown not to be
+won in n are remembered. The line printed is the key move, the reply
+that holds out longest, and so on, in standard algebraic notation.
+
+
+## Usage
+
+    python3 chess.py mate "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1" 1
+    python3 chess.py mate "r6k/6pp/8/6N1/8/1Q6/6PP/6K1 w - - 0 1" 4 --stats
+    python3 chess.py mate --file puzzles/mates.txt
+    python3 chess.py perft "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1" 3 --divide
+    python3 chess.py test
+"""
+
+import argparse
+import os
+from typing import Dict, List, NamedTuple, Optional, Set, Tuple
+
+
+PUZZLE_DIRECTORY: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "puzzles")
+START: str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+EMPTY: str = "."
+FILES: str = "abcdefgh"
+
+KNIGHT_STEPS: List[int] = [33, 31, 18, 14, -14, -18, -31, -33]
+KING_STEPS: List[int] = [1, -1, 16, -16, 15, 17, -15, -17]
+BISHOP_STEPS: List[int] = [15, 17, -15, -17]
+ROOK_STEPS: List[int] = [1, -1, 16, -16]
+SLIDES: Dict[str, List[int]] = {"B": BISHOP_STEPS, "R": ROOK_STEPS, "Q": BISHOP_STEPS + ROOK_STEPS}
+SQUARES: List[int] = [rank * 16 + file for rank in range(8) for file in range(8)]
+
+# (the king's from and to, the rook's from and to, the squares that must be empty)
+CASTLING: Dict[str, Tuple[int, int, int, int, List[int]]] = {
+    "K": (4, 6, 7, 5, [5, 6]),
+    "Q": (4, 2, 0, 3, [1, 2, 3]),
+    "k": (116, 118, 119, 117, [117, 118]),
+    "q": (116, 114, 112, 115, [113, 114, 115]),
+}
+# moving from or capturing on these squares loses the castling rights
+CASTLING_LOST: Dict[int, str] = {4: "KQ", 0: "Q", 7: "K", 116: "kq", 112: "q", 119: "k"}
+
+
+class Move(NamedTuple):
+    start: int
+    end: int
+    promotion: str = ""     # the piece a pawn becomes, in the mover's case
+    kind: str = ""          # "castle", "en passant", "double" (a pawn's two steps), or ""
+
+
+def square_name(square: int) -> str:
+    return f"{FILES[square & 7]}{(square >> 4) + 1}"
+
+
+def is_white(piece: str) -> bool:
+    return piece.isupper()
+
+
+class Position:
+    def __init__(self, fen: str = START):
+        fields = fen.split()
+        if len(fields) == 4:
+            fields += ["0", "1"]
+        if len(fields) != 6:
+            raise ValueError(f"a FEN has six fields (or the first four), not {len(fields)}")
+        placement, side, castling, en_passant, halfmove, fullmove = fields
+        self.board = [EMPTY] * 128
+        ranks = placement.split("/")
+        if len(ranks) != 8:
+            raise ValueError(f"a FEN has 8 ranks, not {len(ranks)}")
+        for i, text in enumerate(ranks):
+            rank, file = 7 - i, 0
+            for c in text:
+                if c.isdigit():
+                    file += int(c)
+                elif c.upper() in "PNBRQK":
+                    if file < 8:
+                        self.board[rank * 16 + file] = c
+                    file += 1
+                else:
+                    raise ValueError(f"rank {rank + 1}: {c!r} is not a piece")
+            if file != 8:
+                raise ValueError(f"rank {rank + 1} has {file} squares, not 8")
+        if side not in ("w", "b"):
+            raise ValueError(f"the side to move is w or b, not {side!r}")
+        self.white = side == "w"
+        if castling != "-" and (not set(castling) <= set("KQkq")):
+            raise ValueError(f"castling rights are some of KQkq, or -, not {castling!r}")
+        self.castling = "" if castling == "-" else "".join(c for c in "KQkq" if c in castling)
+        if en_passant == "-":
+            self.en_passant: Optional[int] = None
+        elif len(en_passant) == 2 and en_passant[0] in FILES and en_passant[1] in "36":
+            self.en_passant = (int(en_passant[1]) - 1) * 16 + FILES.index(en_passant[0])
+        else:
+            raise ValueError(f"the en passant square is like e3, or -, not {en_passant!r}")
+        if not halfmove.isdigit() or not fullmove.isdigit():
+            raise ValueError("the move counters are numbers")
+        self.halfmove, self.fullmove = int(halfmove), int(fullmove)
+        for king in "Kk":
+            if self.board.count(king) != 1:
+                raise ValueError(f"there should be one {'white' if king == 'K' else 'black'} king")
+        self.kings = {True: self.board.index("K"), False: self.board.index("k")}
+        if self.attacked(self.kings[not self.white], self.white):
+            raise ValueError("the side not to move is in check")
+
+    def fen(self) -> str:
+        ranks = []
+        for rank in range(7, -1, -1):
+            text, empty = "", 0
+            for file in range(8):
+                piece = self.board[rank * 16 + file]
+                if piece == EMPTY:
+                    empty += 1
+                    continue
+                text += (str(empty) if empty else "") + piece
+                empty = 0
+            ranks.append(text + (str(empty) if empty else ""))
+        en_passant = square_name(self.en_passant) if self.en_passant is not None else "-"
+        return (f"{'/'.join(ranks)} {'w' if self.white else 'b'} {self.castling or '-'} {en_passant} "
+                f"{self.halfmove} {self.fullmove}")
+
+    def key(self) -> Tuple:
+        return "".join(self.board), self.white, self.castling, self.en_passant
+
+    def attacked(self, square: int, by_white: bool) -> bool:
+        """Whether a piece of the given side attacks the square."""
+        board = self.board
+        pawn, knight, king = ("P", "N", "K") if by_white else ("p", "n", "k")
+        for step in ((-15, -17) if by_white else (15, 17)):
+            s = square + step
+            if not s & 0x88 and board[s] == pawn:
+                return True
+        for step in KNIGHT_STEPS:
+            s = square + step
+            if not s & 0x88 and board[s] == knight:
+                return True
+        for step in KING_STEPS:
+            s = square + step
+            if not s & 0x88 and board[s] == king:
+                return True
+        for steps, pieces in ((BISHOP_STEPS, "BQ"), (ROOK_STEPS, "RQ")):
+            if not by_white:
+                pieces = pieces.lower()
+            for step in steps:
+                s = square + step
+                while not s & 0x88:
+                    if board[s] != EMPTY:
+                        if board[s] in pieces:
+                            return True
+                        break
+                    s += step
+        return False
+
+    def in_check(self) -> bool:
+        return self.attacked(self.kings[self.white], not self.white)
+
+    def pseudo_legal(self) -> List[Move]:
+        board, white = self.board, self.white
+        moves = []
+        for square in SQUARES:
+            piece = board[square]
+            if piece == EMPTY or is_white(piece) != white:
+                continue
+            kind = piece.upper()
+            if kind == "P":
+                forward = 16 if white else -16
+                last = 7 if white else 0
+                first = 1 if white else 6
+                promotions = "QRBN" if white else "qrbn"
+                one = square + forward
+                targets = []
+                if not one & 0x88 and board[one] == EMPTY:
+                    targets.append((one, ""))
+                    two = one + forward
+                    if square >> 4 == first and board[two] == EMPTY:
+                        moves.append(Move(square, two, "", "double"))
+                for side in (forward - 1, forward + 1):
+                    s = square + side
+                    if s & 0x88:
+                        continue
+                    if board[s] != EMPTY and is_white(board[s]) != white:
+                        targets.append((s, ""))
+                    elif s == self.en_passant:
+                        moves.append(Move(square, s, "", "en passant"))
+                for s, _ in targets:
+                    if s >> 4 == last:
+                        moves += [Move(square, s, p) for p in promotions]
+                    else:
+                        moves.append(Move(square, s))
+            elif kind in ("N", "K"):
+                for step in (KNIGHT_STEPS if kind == "N" else KING_STEPS):
+                    s = square + step
+                    if not s & 0x88 and (board[s] == EMPTY or is_white(board[s]) != white):
+                        moves.append(Move(square, s))
+            else:
+                for step in SLIDES[kind]:
+                    s = square + step
+                    while not s & 0x88:
+                        if board[s] == EMPTY:
+                            moves.append(Move(square, s))
+                        else:
+                            if is_white(board[s]) != white:
+                                moves.append(Move(square, s))
+                            break
+                        s += step
+        for right in (("K", "Q") if white else ("k", "q")):
+            if right not in self.castling:
+                continue
+            king_from, king_to, rook_from, _, between = CASTLING[right]
+            if (board[king_from] == ("K" if white else "k") and board[rook_from] == ("R" if white else "r")
+                    and all(board[s] == EMPTY for s in between)
+                    and not any(self.attacked(s, not white) for s in (king_from, (king_from + king_to) // 2, king_to))):
+                moves.append(Move(king_from, king_to, "", "castle"))
+        return moves
+
+    def make(self, move: Move) -> Tuple:
+        """Play the move, and return what `unmake` needs to take it back."""
+        board = self.board
+        undo = (move, board[move.end], self.castling, self.en_passant, self.halfmove)
+        piece = board[move.start]
+        board[move.start] = EMPTY
+        board[move.end] = move.promotion or piece
+        if move.kind == "en passant":
+            board[move.end + (-16 if self.white else 16)] = EMPTY
+        elif move.kind == "castle":
+            _, _, rook_from, rook_to, _ = next(c for c in CASTLING.values() if c[1] == move.end)
+            board[rook_to], board[rook_from] = board[rook_from], EMPTY
+        if piece in "Kk":
+            self.kings[self.white] = move.end
+        for square in (move.start, move.end):
+            lost = CASTLING_LOST.get(square)
+            if lost and self.castling:
+                self.castling = "".join(c for c in self.castling if c not in lost)
+        self.en_passant = (move.start + move.end) // 2 if move.kind == "double" else None
+        self.halfmove = 0 if piece in "Pp" or undo[1] != EMPTY else self.halfmove + 1
+        if not self.white:
+            self.fullmove += 1
+        self.white = not self.white
+        return undo
+
+    def unmake(self, undo: Tuple):
+        move, captured, self.castling, self.en_passant, self.halfmove = undo
+        self.white = not self.white
+        if not self.white:
+            self.fullmove -= 1
+        board = self.board
+        piece = board[move.end]
+        if move.promotion:
+            piece = "P" if self.white else "p"
+        board[move.start] = piece
+        board[move.end] = captured
+        if move.kind == "en passant":
+            board[move.end + (-16 if self.white else 16)] = "p" if self.white else "P"
+        elif move.kind == "castle":
+            _, _, rook_from, rook_to, _ = next(c for c in CASTLING.values() if c[1] == move.end)
+            board[rook_from], board[rook_to] = board[rook_to], EMPTY
+        if piece in "Kk":
+            self.kings[self.white] = move.start
+
+    def legal(self) -> List[Move]:
+        moves = []
+        for move in self.pseudo_legal():
+            undo = self.make(move)
+            if not self.attacked(self.kings[not self.white], self.white):
+                moves.append(move)
+            self.unmake(undo)
+        return moves
+
+    def gives_check(self, move: Move) -> bool:
+        undo = self.make(move)
+        check = self.in_check()
+        self.unmake(undo)
+        return check
+
+    def san(self, move: Move) -> str:
+        """The move in standard algebraic notation, like Nbd7, exf8=Q+, or O-O#."""
+        if move.kind == "castle":
+            text = "O-O" if move.end & 7 == 6 else "O-O-O"
+        else:
+            piece = self.board[move.start].upper()
+            capture = self.board[move.end] != EMPTY or move.kind == "en passant"
+            if piece == "P":
+                text = (FILES[move.start & 7] + "x" if capture else "") + square_name(move.end)
+                if move.promotion:
+                    text += "=" + move.promotion.upper()
+            else:
+                rivals = [m for m in self.legal() if m.end == move.end and m.start != move.start
+                          and self.board[m.start] == self.board[move.start]]
+                where = ""
+                if rivals:
+                    if all(m.start & 7 != move.start & 7 for m in rivals):
+                        where = FILES[move.start & 7]
+                    elif all(m.start >> 4 != move.start >> 4 for m in rivals):
+                        where = str((move.start >> 4) + 1)
+                    else:
+                        where = square_name(move.start)
+                text = piece + where + ("x" if capture else "") + square_name(move.end)
+        undo = self.make(move)
+        if self.in_check():
+            text += "#" if not self.legal() else "+"
+        self.unmake(undo)
+        return text
+
+    def render(self) -> str:
+        rows = [f"{rank + 1} " + " ".join(self.board[rank * 16 + file] for file in range(8))
+                for rank in range(7, -1, -1)]
+        rows.append("  " + " ".join(FILES))
+        return "\n".join(rows)
+
+
+################################################################################
+###                                  Perft                                   ###
+################################################################################
+
+
+def perft(position: Position, depth: int) -> int:
+    if depth == 0:
+        return 1
+    moves = position.legal()
+    if depth == 1:
+        return len(moves)
+    total = 0
+    for move in moves:
+        undo = position.make(move)
+        total += perft(position, depth - 1)
+        position.unmake(undo)
+    return total
+
+
+def divide(position: Position, depth: int) -> List[Tuple[str, int]]:
+    """perft, split by the first move, for finding which move a bug is under."""
+    counts = []
+    for move in position.legal():
+        name = square_name(move.start) + square_name(move.end) + move.promotion.lower()
+        undo = position.make(move)
+        counts.append((name, perft(position, depth - 1)))
+        position.unmake(undo)
+    return sorted(counts)
+
+
+# (FEN, the counts at depths 1, 2, 3, ...), from the Chess Programming Wiki's perft results
+PERFT_POSITIONS: List[Tuple[str, List[int]]] = [
+    (START, [20, 400, 8902, 197281]),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", [48, 2039, 97862]),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", [14, 191, 2812, 43238]),
+    ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", [6, 264, 9467]),
+    ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", [44, 1486, 62379]),
+    ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", [46, 2079, 89890]),
+]
+
+
+################################################################################
+###                                Mate in N                                 ###
+################################################################################
+
+
+class Statistics:
+    def __init__(self):
+        self.nodes = 0
+
+    def __str__(self) -> str:
+        return f"{self.nodes} positions searched"
+
+
+class MateSolver:
+    def __init__(self):
+        self.failed: Set[Tuple] = set()     # (key, n): not won in n
+        self.statistics = Statistics()
+
+    def ordered(self, position: Position, moves: List[Move]) -> List[Move]:
+        def rank(move: Move) -> int:
+            if position.gives_check(move):
+                return 0
+            return 1 if position.board[move.end] != EMPTY or move.promotion else 2
+        return sorted(moves, key=rank)
+
+    def wins(self, position: Position, n: int) -> Optional[Move]:
+        """A move that mates in at most n against any defence, for the side to move."""
+        self.statistics.nodes += 1
+        key = (position.key(), n)
+        if key in self.failed:
+            return None
+        moves = position.legal()
+        if n == 1:
+            moves = [move for move in moves if position.gives_check(move)]
+        for move in self.ordered(position, moves):
+            undo = position.make(move)
+            won = self.loses(position, n - 1)
+            position.unmake(undo)
+            if won:
+                return move
+        self.failed.add(key)
+        return None
+
+    def loses(self, position: Position, n: int) -> bool:
+        """Whether the side to move is mated now, or in at most n whatever it plays."""
+        self.statistics.nodes += 1
+        replies = position.legal()
+        if not replies:
+            return position.in_check()
+        if n == 0:
+            return False
+        for reply in replies:
+            undo = position.make(reply)
+            held = self.wins(position, n) is None
+            position.unmake(undo)
+            if held:
+                return False
+        return True
+
+    def fastest(self, position: Position, n: int) -> Optional[int]:
+        """The fewest moves to mate, up to n, if there is one."""
+        for m in range(1, n + 1):
+            if self.wins(position, m) is not None:
+                return m
+        return None
+
+    def line(self, position: Position, n: int) -> List[str]:
+        """The forcing line, in algebraic notation: the fastest mate, against the longest defence."""
+        sans = []
+        undos = []
+        while True:
+            move = self.wins(position, n)
+            sans.append(position.san(move))
+            undos.append(position.make(move))
+            replies = position.legal()
+            if not replies:
+                break
+            lengths = []
+            for reply in replies:
+                undo = position.make(reply)
+                lengths.append(self.fastest(position, n - 1))
+                position.unmake(undo)
+            n = max(lengths)
+            reply = replies[lengths.index(n)]
+            sans.append(position.san(reply))
+            undos.append(position.make(reply))
+        for undo in reversed(undos):
+            position.unmake(undo)
+        return sans
+
+
+def solve(fen: str, n: int, statistics: Optional[Statistics] = None) -> Optional[Tuple[int, List[str]]]:
+    """The fewest moves to mate, up to n, and the line; None if there is no mate in n."""
+    position = Position(fen)
+    solver = MateSolver()
+    m = solver.fastest(position, n)
+    if statistics is not None:
+        statistics.nodes = solver.statistics.nodes
+    if m is None:
+        return None
+    return m, solver.line(position, m)
+
+
+def format_line(position: Position, sans: List[str]) -> str:
+    """Numbered: 1. Qh5+ Kd7 2. Qd5#, or from Black, 1... Qxf2+ 2. Kh1 Qg1#."""
+    number, white = position.fullmove, position.white
+    text = [] if white else [f"{number}..."]
+    for san in sans:
+        if white:
+            text.append(f"{number}.")
+        else:
+            number += 1
+        text.append(san)
+        white = not white
+    return " ".join(text)
+
+
+def read_puzzles(path: str) -> List[Tuple[int, str, int, Optional[str]]]:
+    """(line, FEN, n, the recorded key move) for each line: FEN; n; key move (optional)."""
+    puzzles = []
+    with open(path) as f:
+        for number, line in enumerate(f, start=1):
+            line = line.strip()
+            if not line or line.startswith("#"):
+                continue
+            parts = [part.strip() for part in line.split(";")]
+            if len(parts) not in (2, 3) or not parts[1].isdigit():
+                raise ValueError(f"line {number}: expected FEN; n; key move (optional)")
+            puzzles.append((number, parts[0], int(parts[1]), parts[2] if len(parts) == 3 else None))
+    return puzzles
+
+
+def mate_command(fen: str, n: int, stats: bool) -> bool:
+    position = Position(fen)
+    print(position.render())
+    statistics = Statistics()
+    found = solve(fen, n, statistics)
+    if found is None:
+        print(f"No mate in {n}")
+    else:
+        print(f"Mate in {found[0]}: {format_line(position, found[1])}")
+    if stats:
+        print(statistics)
+    return found is not None
+
+
+def file_command(path: str) -> bool:
+    passed = True
+    for number, fen, n, key in read_puzzles(path):
+        found = solve(fen, n)
+        if found is None:
+            verdict = f"wrong: no mate in {n}"
+        elif found[0] != n:
+            verdict = f"wrong: mate in {found[0]}, not {n}: {format_line(Position(fen), found[1])}"
+        elif key is not None and key != found[1][0]:
+            verdict = f"wrong: the key is {found[1][0]}, not {key}"
+        else:
+            verdict = f"ok: {format_line(Position(fen), found[1])}"
+        passed &= verdict.startswith("ok")
+        print(f"line {number}: {verdict}")
+    return passed
+
+
+def test_command(max_nodes: int) -> bool:
+    passed = True
+    for fen, counts in PERFT_POSITIONS:
+        position = Position(fen)
+        for depth, expected in enumerate(counts, start=1):
+            if expected > max_nodes:
+                break
+            found = perft(position, depth)
+            ok = found == expected
+            passed &= ok
+            print(f"{fen}: depth {depth}: {found}" + ("" if ok else f", expected {expected}"))
+    mates = os.path.join(PUZZLE_DIRECTORY, "mates.txt")
+    if os.path.exists(mates):
+        passed &= file_command(mates)
+    return passed
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve mate-in-N chess problems")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    mate_parser = subparsers.add_parser("mate", help="find a forced mate")
+    mate_parser.add_argument("fen", type=str, nargs="?", default=None)
+    mate_parser.add_argument("n", type=int, nargs="?", default=2, help="the most moves to mate in")
+    mate_parser.add_argument("--file", type=str, default=None, help="solve each line: FEN; n; key move")
+    mate_parser.add_argument("--stats", action="store_true", help="print the positions searched")
+    perft_parser = subparsers.add_parser("perft", help="count the positions a number of moves ahead")
+    perft_parser.add_argument("fen", type=str, nargs="?", default=START)
+    perft_parser.add_argument("depth", type=int, nargs="?", default=3)
+    perft_parser.add_argument("--divide", action="store_true", help="the count under each first move")
+    test_parser = subparsers.add_parser("test", help="the perft counts, and the recorded mates")
+    test_parser.add_argument("--max-nodes", type=int, default=100000,
+                             help="skip the perft depths with more positions than this")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "mate":
+            if (args.fen is None) == (args.file is None):
+                raise ValueError("give a FEN, or a --file of them")
+            ok = file_command(args.file) if args.file else mate_command(args.fen, args.n, args.stats)
+        elif args.command == "perft":
+            position = Position(args.fen)
+            if args.divide:
+                counts = divide(position, args.depth)
+                for name, count in counts:
+                    print(f"{name}: {count}")
+                print(f"Total: {sum(count for _, count in counts)}")
+            else:
+                print(perft(position, args.depth))
+            ok = True
+        else:
+            ok = test_command(args.max_nodes)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if not ok:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
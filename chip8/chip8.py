@@ -0,0 +1,471 @@
+"""
+# CHIP-8
+
+Joseph Weisbecker's CHIP-8 is the virtual machine behind the games of
+the late-1970s COSMAC VIP, and the usual first emulator to write. It has
+
+    4 KiB of memory     the program is loaded at 0x200, and a font of the
+                        hex digits (5 rows each) sits below it
+    V0 to VF            sixteen 8-bit registers, where VF is the flag
+    I                   a 12-bit address register
+    a stack             of return addresses, for subroutine calls
+    two timers          the delay and sound timers count down at 60 Hz,
+                        and the sound plays while its timer is nonzero
+    a 64x32 display     of monochrome pixels, drawn by XORing sprites,
+                        with VF set when a pixel is erased (a collision)
+    a hex keypad        of keys 0 to F
+
+and 35 two-byte instructions (see `disassemble` for their names).
+
+
+## The Keypad
+
+The keypad is mapped onto the left of a QWERTY keyboard:
+
+    1 2 3 C         1 2 3 4
+    4 5 6 D   <-    Q W E R
+    7 8 9 E         A S D F
+    A 0 B F         Z X C V
+
+A terminal only sees keys being typed, not held down or let go, so a key
+counts as held for a few frames after it is typed. Ctrl-C quits.
+
+
+## Quirks
+
+The later interpreters changed a few instructions, and games are written
+for one or the other:
+
+    original    (COSMAC VIP) 8XY6 and 8XYE shift VY into VX, FX55 and
+                FX65 leave I after the last register, 8XY1/2/3 reset VF,
+                and BNNN jumps to NNN + V0
+    modern      (CHIP-48 and SUPER-CHIP) shifts act on VX, I is left
+                alone, VF is left alone, and BXNN jumps to XNN + VX
+
+
+## Headless Mode
+
+`--headless` runs a fixed number of frames (`--frames`), without the
+terminal, and then prints the display and the registers, so a program
+can be checked instruction by instruction alongside `--trace`. The keys
+are scripted with `--keys`, e.g. `--keys 5@10 5@30` types 5 in frames 10
+and 30.
+
+
+## ROMs
+
+A `.ch8` file is a raw ROM. A `.hex` file is the same bytes written in
+hex, with `#` comments, which is how the examples are written:
+
+    digits.hex      draws the font
+    counter.hex     counts the keys typed
+
+
+## Usage
+
+    python3 chip8.py examples/digits.hex
+    python3 chip8.py examples/counter.hex
+    python3 chip8.py examples/counter.hex --headless --frames 60 --keys 1@10 1@20 1@30
+    python3 chip8.py examples/digits.hex --headless --frames 1 --trace
+    python3 chip8.py examples/counter.hex --disassemble
+    python3 chip8.py game.ch8 --quirks modern --speed 15
+"""
+
+import argparse
+import os
+import re
+import select
+import sys
+import time
+from typing import Dict, List, NamedTuple, Optional, Set
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+MEMORY_SIZE: int = 4096
+PROGRAM_START: int = 0x200
+FONT_START: int = 0x50
+WIDTH, HEIGHT = 64, 32
+FRAME_RATE: int = 60
+DEFAULT_SPEED: int = 10         # instructions per frame
+DEFAULT_FRAMES: int = 600
+KEY_HOLD_FRAMES: int = 6
+CLEAR_SCREEN: str = "\x1b[H\x1b[2J"
+HOME: str = "\x1b[H"
+
+FONT: List[int] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0,   # 0
+    0x20, 0x60, 0x20, 0x20, 0x70,   # 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0,   # 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0,   # 3
+    0x90, 0x90, 0xF0, 0x10, 0x10,   # 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0,   # 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0,   # 6
+    0xF0, 0x10, 0x20, 0x40, 0x40,   # 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0,   # 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0,   # 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90,   # A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0,   # B
+    0xF0, 0x80, 0x80, 0x80, 0xF0,   # C
+    0xE0, 0x90, 0x90, 0x90, 0xE0,   # D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0,   # E
+    0xF0, 0x80, 0xF0, 0x80, 0x80,   # F
+]
+
+KEYBOARD: Dict[str, int] = {
+    "1": 0x1, "2": 0x2, "3": 0x3, "4": 0xC,
+    "q": 0x4, "w": 0x5, "e": 0x6, "r": 0xD,
+    "a": 0x7, "s": 0x8, "d": 0x9, "f": 0xE,
+    "z": 0xA, "x": 0x0, "c": 0xB, "v": 0xF,
+}
+
+
+class Quirks(NamedTuple):
+    shift_vy: bool
+    increment_i: bool
+    reset_vf: bool
+    jump_vx: bool
+
+
+QUIRKS: Dict[str, Quirks] = {
+    "original": Quirks(shift_vy=True, increment_i=True, reset_vf=True, jump_vx=False),
+    "modern": Quirks(shift_vy=False, increment_i=False, reset_vf=False, jump_vx=True),
+}
+
+
+class MachineError(Exception):
+    """The program did something illegal while running."""
+
+
+def load_rom(path: str) -> bytes:
+    if not path.endswith(".hex"):
+        with open(path, "rb") as f:
+            return f.read()
+    with open(path) as f:
+        text = f.read()
+    digits = "".join(line.split("#", 1)[0] for line in text.split("\n"))
+    digits = re.sub(r"\s+", "", digits)
+    if not re.fullmatch(r"([0-9A-Fa-f]{2})*", digits):
+        raise ValueError(f"{path}: expected pairs of hex digits")
+    return bytes.fromhex(digits)
+
+
+def disassemble_instruction(opcode: int) -> str:
+    """In the mnemonics of Cowgod's technical reference."""
+    x, y, n = (opcode >> 8) & 0xF, (opcode >> 4) & 0xF, opcode & 0xF
+    kk, nnn = opcode & 0xFF, opcode & 0xFFF
+    top = opcode >> 12
+    if opcode == 0x00E0:
+        return "CLS"
+    if opcode == 0x00EE:
+        return "RET"
+    simple = {
+        0x0: f"SYS {nnn:03X}", 0x1: f"JP {nnn:03X}", 0x2: f"CALL {nnn:03X}",
+        0x3: f"SE V{x:X}, {kk:02X}", 0x4: f"SNE V{x:X}, {kk:02X}", 0x6: f"LD V{x:X}, {kk:02X}",
+        0x7: f"ADD V{x:X}, {kk:02X}", 0xA: f"LD I, {nnn:03X}", 0xB: f"JP V0, {nnn:03X}",
+        0xC: f"RND V{x:X}, {kk:02X}", 0xD: f"DRW V{x:X}, V{y:X}, {n:X}",
+    }
+    if top in simple:
+        return simple[top]
+    if top in (0x5, 0x9) and n == 0:
+        return f"{'SE' if top == 0x5 else 'SNE'} V{x:X}, V{y:X}"
+    if top == 0x8 and n in (0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0xE):
+        name = {0x0: "LD", 0x1: "OR", 0x2: "AND", 0x3: "XOR", 0x4: "ADD", 0x5: "SUB",
+                0x6: "SHR", 0x7: "SUBN", 0xE: "SHL"}[n]
+        return f"{name} V{x:X}, V{y:X}"
+    if top == 0xE and kk in (0x9E, 0xA1):
+        return f"{'SKP' if kk == 0x9E else 'SKNP'} V{x:X}"
+    if top == 0xF:
+        forms = {
+            0x07: f"LD V{x:X}, DT", 0x0A: f"LD V{x:X}, K", 0x15: f"LD DT, V{x:X}",
+            0x18: f"LD ST, V{x:X}", 0x1E: f"ADD I, V{x:X}", 0x29: f"LD F, V{x:X}",
+            0x33: f"LD B, V{x:X}", 0x55: f"LD [I], V{x:X}", 0x65: f"LD V{x:X}, [I]",
+        }
+        if kk in forms:
+            return forms[kk]
+    return f"DW {opcode:04X}"
+
+
+def disassemble(rom: bytes) -> str:
+    lines: List[str] = []
+    for offset in range(0, len(rom) - 1, 2):
+        opcode = (rom[offset] << 8) | rom[offset + 1]
+        lines.append(f"{PROGRAM_START + offset:03X}  {opcode:04X}  {disassemble_instruction(opcode)}")
+    if len(rom) % 2:
+        lines.append(f"{PROGRAM_START + len(rom) - 1:03X}  {rom[-1]:02X}    DB {rom[-1]:02X}")
+    return "\n".join(lines)
+
+
+class Chip8:
+    def __init__(self, rom: bytes, quirks: Quirks = QUIRKS["original"], seed: Optional[int] = None):
+        if len(rom) > MEMORY_SIZE - PROGRAM_START:
+            raise ValueError(f"the ROM is {len(rom)} bytes, but only {MEMORY_SIZE - PROGRAM_START} fit")
+        self.memory = bytearray(MEMORY_SIZE)
+        self.memory[FONT_START:FONT_START + len(FONT)] = bytes(FONT)
+        self.memory[PROGRAM_START:PROGRAM_START + len(rom)] = rom
+        self.v = [0] * 16
+        self.i = 0
+        self.pc = PROGRAM_START
+        self.stack: List[int] = []
+        self.delay_timer = 0
+        self.sound_timer = 0
+        self.display = [[False] * WIDTH for _ in range(HEIGHT)]
+        self.keys: Set[int] = set()     # the keys held down
+        self.quirks = quirks
+        self.rng = make_rng(seed)
+        self.steps = 0
+        self.waiting_for_key: Optional[int] = None     # the register for FX0A
+
+    def press(self, key: int):
+        self.keys.add(key)
+        if self.waiting_for_key is not None:
+            self.v[self.waiting_for_key] = key
+            self.waiting_for_key = None
+
+    def release(self, key: int):
+        self.keys.discard(key)
+
+    def tick_timers(self):
+        """Once per frame."""
+        self.delay_timer = max(0, self.delay_timer - 1)
+        self.sound_timer = max(0, self.sound_timer - 1)
+
+    def draw(self, x: int, y: int, rows: int) -> bool:
+        """XOR a sprite onto the display, clipped at the edges, and return whether it erased a pixel."""
+        erased = False
+        x, y = x % WIDTH, y % HEIGHT
+        for row in range(rows):
+            if y + row >= HEIGHT:
+                break
+            bits = self.memory[(self.i + row) % MEMORY_SIZE]
+            for column in range(8):
+                if x + column >= WIDTH or not bits & (0x80 >> column):
+                    continue
+                erased |= self.display[y + row][x + column]
+                self.display[y + row][x + column] ^= True
+        return erased
+
+    def step(self):
+        if self.waiting_for_key is not None:
+            return
+        if self.pc > MEMORY_SIZE - 2:
+            raise MachineError(f"address {self.pc:03X}: the program counter ran off the end of memory")
+        opcode = (self.memory[self.pc] << 8) | self.memory[self.pc + 1]
+        address = self.pc
+        self.pc += 2
+        self.steps += 1
+        x, y, n = (opcode >> 8) & 0xF, (opcode >> 4) & 0xF, opcode & 0xF
+        kk, nnn = opcode & 0xFF, opcode & 0xFFF
+        v = self.v
+        top = opcode >> 12
+
+        if opcode == 0x00E0:
+            self.display = [[False] * WIDTH for _ in range(HEIGHT)]
+        elif opcode == 0x00EE:
+            if not self.stack:
+                raise MachineError(f"address {address:03X}: RET with an empty stack")
+            self.pc = self.stack.pop()
+        elif top == 0x0:
+            pass    # SYS, a call to the original machine code, which every modern interpreter ignores
+        elif top == 0x1:
+            self.pc = nnn
+        elif top == 0x2:
+            if len(self.stack) >= 16:
+                raise MachineError(f"address {address:03X}: the stack overflowed")
+            self.stack.append(self.pc)
+            self.pc = nnn
+        elif top == 0x3:
+            self.pc += 2 * (v[x] == kk)
+        elif top == 0x4:
+            self.pc += 2 * (v[x] != kk)
+        elif top == 0x5 and n == 0:
+            self.pc += 2 * (v[x] == v[y])
+        elif top == 0x6:
+            v[x] = kk
+        elif top == 0x7:
+            v[x] = (v[x] + kk) & 0xFF
+        elif top == 0x8 and n in (0x0, 0x1, 0x2, 0x3):
+            v[x] = [v[y], v[x] | v[y], v[x] & v[y], v[x] ^ v[y]][n]
+            if n and self.quirks.reset_vf:
+                v[0xF] = 0
+        elif top == 0x8 and n == 0x4:
+            total = v[x] + v[y]
+            v[x], v[0xF] = total & 0xFF, int(total > 0xFF)
+        elif top == 0x8 and n in (0x5, 0x7):
+            a, b = (v[x], v[y]) if n == 0x5 else (v[y], v[x])
+            v[x], v[0xF] = (a - b) & 0xFF, int(a >= b)
+        elif top == 0x8 and n in (0x6, 0xE):
+            value = v[y] if self.quirks.shift_vy else v[x]
+            if n == 0x6:
+                v[x], v[0xF] = value >> 1, value & 1
+            else:
+                v[x], v[0xF] = (value << 1) & 0xFF, value >> 7
+        elif top == 0x9 and n == 0:
+            self.pc += 2 * (v[x] != v[y])
+        elif top == 0xA:
+            self.i = nnn
+        elif top == 0xB:
+            self.pc = (nnn + (v[x] if self.quirks.jump_vx else v[0])) & 0xFFF
+        elif top == 0xC:
+            v[x] = self.rng.randrange(256) & kk
+        elif top == 0xD:
+            v[0xF] = int(self.draw(v[x], v[y], n))
+        elif top == 0xE and kk == 0x9E:
+            self.pc += 2 * (v[x] in self.keys)
+        elif top == 0xE and kk == 0xA1:
+            self.pc += 2 * (v[x] not in self.keys)
+        elif top == 0xF and kk == 0x07:
+            v[x] = self.delay_timer
+        elif top == 0xF and kk == 0x0A:
+            self.waiting_for_key = x
+        elif top == 0xF and kk == 0x15:
+            self.delay_timer = v[x]
+        elif top == 0xF and kk == 0x18:
+            self.sound_timer = v[x]
+        elif top == 0xF and kk == 0x1E:
+            self.i = (self.i + v[x]) & 0xFFF
+        elif top == 0xF and kk == 0x29:
+            self.i = FONT_START + 5 * (v[x] & 0xF)
+        elif top == 0xF and kk == 0x33:
+            for offset, digit in enumerate(f"{v[x]:03d}"):
+                self.memory[(self.i + offset) % MEMORY_SIZE] = int(digit)
+        elif top == 0xF and kk in (0x55, 0x65):
+            for register in range(x + 1):
+                if kk == 0x55:
+                    self.memory[(self.i + register) % MEMORY_SIZE] = v[register]
+                else:
+                    v[register] = self.memory[(self.i + register) % MEMORY_SIZE]
+            if self.quirks.increment_i:
+                self.i = (self.i + x + 1) & 0xFFF
+        else:
+            raise MachineError(f"address {address:03X}: unknown instruction {opcode:04X}")
+
+    def frame(self, speed: int, trace: bool = False):
+        """One 60th of a second: `speed` instructions, then the timers."""
+        for _ in range(speed):
+            if self.waiting_for_key is not None:
+                break
+            if trace:
+                opcode = (self.memory[self.pc] << 8) | self.memory[(self.pc + 1) % MEMORY_SIZE]
+                registers = " ".join(f"{value:02X}" for value in self.v)
+                print(f"{self.steps:>8}  {self.pc:03X}  {disassemble_instruction(opcode):<18} "
+                      f"I={self.i:03X}  V={registers}")
+            self.step()
+        self.tick_timers()
+
+
+def render(display: List[List[bool]]) -> str:
+    """Two rows of pixels per line of text, with half blocks."""
+    blocks = {(False, False): " ", (True, False): "▀", (False, True): "▄", (True, True): "█"}
+    lines = []
+    for y in range(0, HEIGHT, 2):
+        lines.append("".join(blocks[display[y][x], display[y + 1][x]] for x in range(WIDTH)))
+    border = "+" + "-" * WIDTH + "+"
+    return "\n".join([border] + [f"|{line}|" for line in lines] + [border])
+
+
+def format_state(chip8: Chip8) -> str:
+    registers = " ".join(f"V{r:X}={value:02X}" for r, value in enumerate(chip8.v))
+    stack = " ".join(f"{address:03X}" for address in chip8.stack) or "(empty)"
+    return "\n".join([
+        f"PC={chip8.pc:03X}  I={chip8.i:03X}  DT={chip8.delay_timer}  ST={chip8.sound_timer}  steps={chip8.steps}",
+        registers,
+        f"stack: {stack}",
+    ])
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def parse_key_events(words: List[str]) -> Dict[int, List[int]]:
+    """`key@frame` words, as the keys typed in each frame."""
+    events: Dict[int, List[int]] = {}
+    for word in words:
+        match = re.fullmatch(r"([0-9A-Fa-f])@(\d+)", word)
+        if match is None:
+            raise ValueError(f"bad key event '{word}': expected e.g. 5@10 for key 5 in frame 10")
+        events.setdefault(int(match.group(2)), []).append(int(match.group(1), 16))
+    return events
+
+
+def run_headless(chip8: Chip8, frames: int, speed: int, events: Dict[int, List[int]], trace: bool):
+    held: Dict[int, int] = {}   # key -> the frame it is let go
+    for frame in range(frames):
+        for key in events.get(frame, []):
+            chip8.press(key)
+            held[key] = frame + KEY_HOLD_FRAMES
+        for key, until in list(held.items()):
+            if frame >= until:
+                chip8.release(key)
+                del held[key]
+        chip8.frame(speed, trace)
+    print(render(chip8.display))
+    print(format_state(chip8))
+    if chip8.waiting_for_key is not None:
+        print(f"waiting for a key into V{chip8.waiting_for_key:X}")
+
+
+def run_terminal(chip8: Chip8, speed: int):
+    import termios
+    import tty
+
+    held: Dict[int, float] = {}     # key -> the time it is let go
+    settings = termios.tcgetattr(sys.stdin)
+    try:
+        tty.setcbreak(sys.stdin.fileno())
+        print(CLEAR_SCREEN, end="")
+        while True:
+            start = time.monotonic()
+            while select.select([sys.stdin], [], [], 0)[0]:
+                key = KEYBOARD.get(sys.stdin.read(1).lower())
+                if key is not None:
+                    chip8.press(key)
+                    held[key] = start + KEY_HOLD_FRAMES / FRAME_RATE
+            for key, until in list(held.items()):
+                if start >= until:
+                    chip8.release(key)
+                    del held[key]
+            sounding = chip8.sound_timer > 0
+            chip8.frame(speed)
+            print(HOME + render(chip8.display) + ("\a" if chip8.sound_timer > 0 and not sounding else ""))
+            time.sleep(max(0.0, 1 / FRAME_RATE - (time.monotonic() - start)))
+    except KeyboardInterrupt:
+        pass
+    finally:
+        termios.tcsetattr(sys.stdin, termios.TCSADRAIN, settings)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="CHIP-8 emulator")
+    parser.add_argument("rom", type=str, help="path to a .ch8 ROM, or a .hex file")
+    parser.add_argument("--quirks", choices=QUIRKS, default="original", help="which interpreter to behave like")
+    parser.add_argument("--speed", type=int, default=DEFAULT_SPEED, help="instructions per frame")
+    add_seed_argument(parser, help="for RND")
+    parser.add_argument("--headless", action="store_true", help="run without the terminal, and print the final state")
+    parser.add_argument("--frames", type=int, default=DEFAULT_FRAMES, help="how many frames to run headless")
+    parser.add_argument("--keys", type=str, nargs="*", default=[], help="key@frame events for headless mode")
+    parser.add_argument("--trace", action="store_true", help="print every instruction (headless only)")
+    parser.add_argument("--disassemble", action="store_true", help="print the ROM as instructions and stop")
+    args = parser.parse_args()
+
+    try:
+        rom = load_rom(args.rom)
+        if args.disassemble:
+            print(disassemble(rom))
+            return
+        chip8 = Chip8(rom, QUIRKS[args.quirks], args.seed)
+        if args.headless:
+            run_headless(chip8, args.frames, args.speed, parse_key_events(args.keys), args.trace)
+        elif not sys.stdin.isatty():
+            parser.error("the terminal display needs a terminal (or use --headless)")
+        else:
+            run_terminal(chip8, args.speed)
+    except (ValueError, MachineError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,262 @@
+"""
+# Ciphers
+
+The Vigenère cipher, and how to break it. Each letter of the message is
+shifted along the alphabet by a letter of the key, A by 0 to Z by 25,
+the key repeating for as long as the message runs; letters are the only
+thing enciphered, and the key only moves on at a letter.
+
+    plain   ATTACKATDAWN
+    key     LEMONLEMONLE
+    cipher  LXFOPVEFRNHR
+
+Every letter is enciphered by one of only as many Caesar shifts as the
+key has letters, so once the length of the key is known, the message
+falls apart into that many columns, each a Caesar cipher, and each of
+those is easy.
+
+
+## The Length of the Key
+
+Two ways to guess it, which are shown side by side:
+
+    Kasiski     a run of letters (three or more) that comes round twice
+                in the cipher text was most likely the same plain text
+                under the same part of the key, so the distance between
+                them is a multiple of the key's length: count, for each
+                length, the repeats whose distance it divides
+    coincidence the index of coincidence of a column, the chance that two
+                of its letters picked at random are the same, is about
+                0.066 in English and 0.038 when the letters are spread
+                evenly, as they are in a column that mixes shifts; so
+                for each length, take the average over its columns
+
+The coincidence of a multiple of the key's length is as high as the
+key's own, so the length is the shortest whose coincidence is within 10%
+of the highest (or give --length).
+
+
+## The Key
+
+For each column, the shift whose letters look most like English: the
+smallest chi-squared statistic of the column's counts against English
+letter frequencies. A key that came out as a word repeated (LEMONLEMON)
+is cut down to the word.
+
+
+## Usage
+
+    python3 cipher.py vigenere "Attack at dawn" --key LEMON
+    python3 cipher.py vigenere "LXFOP VEFRNHR" --key LEMON --decrypt
+    python3 cipher.py vigenere texts/pride.txt --key DARCY | python3 cipher.py crack-vigenere -
+    python3 cipher.py check
+
+`check` runs the test vectors: the example above, and each classic text
+in `texts/` enciphered under the keys in `vectors.txt` and cracked back.
+"""
+
+import argparse
+import os
+import string
+import sys
+from typing import Dict, List, Optional, Tuple
+
+
+ALPHABET: str = string.ascii_uppercase
+DIRECTORY: str = os.path.dirname(os.path.abspath(__file__))
+VECTORS: str = os.path.join(DIRECTORY, "vectors.txt")
+# The letter frequencies of English, in percent
+ENGLISH: List[float] = [8.2, 1.5, 2.8, 4.3, 12.7, 2.2, 2.0, 6.1, 7.0, 0.15, 0.77, 4.0, 2.4,
+                        6.7, 7.5, 1.9, 0.095, 6.0, 6.3, 9.1, 2.8, 0.98, 2.4, 0.15, 2.0, 0.074]
+ENGLISH_COINCIDENCE: float = 0.066
+RANDOM_COINCIDENCE: float = 1 / 26
+SHORTEST_REPEAT: int = 3
+# (plain text, key, cipher text) that enciphering must match exactly
+KNOWN_ANSWERS: List[Tuple[str, str, str]] = [("ATTACKATDAWN", "LEMON", "LXFOPVEFRNHR")]
+
+
+def letters(text: str) -> str:
+    return "".join(c for c in text.upper() if c in ALPHABET)
+
+
+def vigenere(text: str, key: str, decrypt: bool = False) -> str:
+    """The text enciphered (or deciphered) under the key, keeping its case, and everything but letters as is."""
+    shifts = [ALPHABET.index(c) * (-1 if decrypt else 1) for c in letters(key)]
+    if not shifts:
+        raise ValueError("the key has no letters")
+    result = []
+    i = 0
+    for c in text:
+        if c.upper() in ALPHABET:
+            shifted = ALPHABET[(ALPHABET.index(c.upper()) + shifts[i % len(shifts)]) % 26]
+            result.append(shifted if c.isupper() else shifted.lower())
+            i += 1
+        else:
+            result.append(c)
+    return "".join(result)
+
+
+################################################################################
+###                                 Cracking                                 ###
+################################################################################
+
+
+def coincidence(column: str) -> float:
+    n = len(column)
+    if n < 2:
+        return 0.0
+    return sum(k * (k - 1) for k in (column.count(c) for c in ALPHABET)) / (n * (n - 1))
+
+
+def average_coincidence(cipher: str, length: int) -> float:
+    return sum(coincidence(cipher[i::length]) for i in range(length)) / length
+
+
+def kasiski(cipher: str, longest_key: int) -> Dict[int, int]:
+    """For each key length, how many distances between repeated runs of letters it divides."""
+    seen: Dict[str, int] = {}
+    distances = []
+    for i in range(len(cipher) - SHORTEST_REPEAT + 1):
+        run = cipher[i:i + SHORTEST_REPEAT]
+        if run in seen:
+            distances.append(i - seen[run])
+        seen[run] = i
+    return {length: sum(1 for distance in distances if distance % length == 0)
+            for length in range(2, longest_key + 1)}
+
+
+def key_length(cipher: str, longest_key: int) -> int:
+    scores = {length: average_coincidence(cipher, length) for length in range(1, longest_key + 1)}
+    best = max(scores.values())
+    return min(length for length, score in scores.items() if score >= 0.9 * best)
+
+
+def chi_squared(column: str, shift: int) -> float:
+    """How far the column, shifted back, is from English."""
+    counts = [0] * 26
+    for c in column:
+        counts[(ALPHABET.index(c) - shift) % 26] += 1
+    n = len(column)
+    return sum((count - n * share / 100) ** 2 / (n * share / 100) for count, share in zip(counts, ENGLISH))
+
+
+def shortest_period(key: str) -> str:
+    """The key as a word repeated, cut down to the word."""
+    for length in range(1, len(key)):
+        if len(key) % length == 0 and key[:length] * (len(key) // length) == key:
+            return key[:length]
+    return key
+
+
+def crack(cipher: str, length: int) -> str:
+    """The key, given its length."""
+    key = "".join(ALPHABET[min(range(26), key=lambda shift: chi_squared(cipher[i::length], shift))]
+                  for i in range(length))
+    return shortest_period(key)
+
+
+def report(text: str, longest_key: int, length: Optional[int]) -> Tuple[str, str]:
+    """A table of the guesses at the key's length, and the key."""
+    cipher = letters(text)
+    if len(cipher) < 2:
+        raise ValueError("there are too few letters to crack")
+    if length is not None and length > len(cipher):
+        raise ValueError(f"a key of {length} letters is longer than the {len(cipher)} letters of the cipher")
+    longest_key = min(longest_key, max(1, len(cipher) // 2))
+    repeats = kasiski(cipher, longest_key)
+    chosen = length if length is not None else key_length(cipher, longest_key)
+    lines = ["length  coincidence  Kasiski"]
+    for n in range(1, longest_key + 1):
+        mark = "  <-" if n == chosen else ""
+        lines.append(f"{n:>6}  {average_coincidence(cipher, n):>11.4f}  {repeats.get(n, ''):>7}{mark}")
+    lines.append(f"(English {ENGLISH_COINCIDENCE}, even {RANDOM_COINCIDENCE:.3f})")
+    return "\n".join(lines), crack(cipher, chosen)
+
+
+################################################################################
+###                               Test Vectors                               ###
+################################################################################
+
+
+def read_vectors(path: str) -> List[Tuple[str, str]]:
+    """The texts and the keys to encipher them with, one pair a line."""
+    vectors = []
+    with open(path) as f:
+        for number, line in enumerate(f, start=1):
+            words = line.split()
+            if not words or words[0].startswith("#"):
+                continue
+            if len(words) != 2 or not letters(words[1]):
+                raise ValueError(f"{path}, line {number}: expected a text and a key")
+            vectors.append((os.path.join(os.path.dirname(path), words[0]), words[1]))
+    return vectors
+
+
+def check(path: str) -> List[str]:
+    """Run the test vectors; what went wrong."""
+    problems = []
+    for plain, key, cipher in KNOWN_ANSWERS:
+        if vigenere(plain, key) != cipher:
+            problems.append(f"{plain} under {key} is {vigenere(plain, key)}, not {cipher}")
+        if vigenere(cipher, key, decrypt=True) != plain:
+            problems.append(f"{cipher} deciphered under {key} is not {plain}")
+    for text_path, key in read_vectors(path):
+        text = read_text(text_path)
+        _, found = report(vigenere(text, key), 20, None)
+        print(f"{os.path.basename(text_path)} under {key}: {found}", file=sys.stderr)
+        if found != letters(key):
+            problems.append(f"{os.path.basename(text_path)} under {key} cracked as {found}")
+    return problems
+
+
+def read_text(text: str) -> str:
+    """The text itself, a file of it, or - for stdin; lines starting with # are comments."""
+    if text == "-":
+        lines = sys.stdin.readlines()
+    elif os.path.exists(text):
+        with open(text) as f:
+            lines = f.readlines()
+    else:
+        return text
+    return "".join(line for line in lines if not line.startswith("#")).strip()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Encipher with Vigenère, and crack it")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    vigenere_parser = subparsers.add_parser("vigenere", help="encipher or decipher with a key")
+    vigenere_parser.add_argument("text", type=str, help="the text, a file of it, or - for stdin")
+    vigenere_parser.add_argument("--key", type=str, required=True)
+    vigenere_parser.add_argument("--decrypt", action="store_true")
+    crack_parser = subparsers.add_parser("crack-vigenere", help="find the key, and decipher")
+    crack_parser.add_argument("text", type=str, help="the cipher text, a file of it, or - for stdin")
+    crack_parser.add_argument("--max-length", type=int, default=20, help="the longest key to consider")
+    crack_parser.add_argument("--length", type=int, default=None, help="the key's length, if known")
+    check_parser = subparsers.add_parser("check", help="run the test vectors")
+    check_parser.add_argument("--vectors", type=str, default=VECTORS)
+    args = parser.parse_args()
+
+    try:
+        if args.command == "vigenere":
+            print(vigenere(read_text(args.text), args.key, args.decrypt))
+        elif args.command == "crack-vigenere":
+            if args.length is not None and args.length < 1:
+                raise ValueError("the key's length must be at least 1")
+            text = read_text(args.text)
+            table, key = report(text, args.max_length, args.length)
+            print(table)
+            print(f"Key: {key}")
+            print(vigenere(text, key, decrypt=True))
+        else:
+            problems = check(args.vectors)
+            for problem in problems:
+                print(problem)
+            print("All the test vectors pass" if not problems else f"{len(problems)} failed")
+            if problems:
+                parser.exit(1)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,223 @@
+"""
+# Collatz Trajectories
+
+Take a positive whole number n. If it is even, halve it; if it is odd,
+make it 3n + 1. Repeat. The Collatz conjecture is that every n gets to
+1 eventually, and it has been checked far beyond anything here, but
+never proved. The trajectory of 27 takes 111 steps and climbs to 9232
+on the way.
+
+    length      the steps it takes to get to 1 (the total stopping time)
+    peak        the highest number on the way
+
+Python's integers have no fixed width, so trajectories that climb past
+2^64 (as they do from numbers that large) are followed exactly, with no
+overflow to check for.
+
+
+## Scanning
+
+`scan` measures every n in a range. The lengths and peaks of the numbers
+below `--cache` are remembered as they are found, so a trajectory stops
+as soon as it falls to a number already measured, and the rest of it is
+filled in on the way back; most trajectories fall below their start
+within a few steps. The range is cut into chunks scanned by separate
+processes, each with its own cache, and the results are merged.
+
+A record holder is a number whose trajectory is longer (or climbs
+higher) than that of every smaller number in the range; from 1, these
+are the classic delay and path records. Within a chunk the records are
+found in order, and merging the chunks in order keeps only those that
+beat everything before them.
+
+
+## Usage
+
+    python3 collatz.py trajectory 27
+    python3 collatz.py trajectory 2^100-1 --quiet
+    python3 collatz.py scan 1 1000000 --records
+    python3 collatz.py scan 1 100000 --histogram --bins 20 --workers 1 --stats
+"""
+
+import argparse
+import collections
+import math
+import multiprocessing
+import os
+import re
+from typing import Counter, List, NamedTuple, Tuple
+
+
+DEFAULT_CACHE: int = 1 << 20
+CHUNK: int = 1 << 16
+HISTOGRAM_WIDTH: int = 60
+NUMBER = re.compile(r"(\d+)(?:\^(\d+))?([+-]\d+)?")
+
+
+class Chunk(NamedTuple):
+    start: int
+    end: int
+    lengths: Counter[int]               # how many numbers have each length
+    length_records: List[Tuple[int, int]]   # (n, length), each longer than those before it in the chunk
+    peak_records: List[Tuple[int, int]]     # (n, peak), each higher than those before it in the chunk
+    steps: int                          # taken, rather than looked up in the cache
+
+
+class Scan(NamedTuple):
+    lengths: Counter[int]
+    length_records: List[Tuple[int, int]]
+    peak_records: List[Tuple[int, int]]
+    steps: int
+
+
+def parse_number(text: str) -> int:
+    """A positive whole number, written out or as a power, like 2^100-1."""
+    match = NUMBER.fullmatch(text.replace(" ", ""))
+    if match is None:
+        raise argparse.ArgumentTypeError(f"expected a number like 27 or 2^100-1, not {text!r}")
+    base, exponent, offset = match.groups()
+    n = int(base) ** int(exponent or 1) + int(offset or 0)
+    if n < 1:
+        raise argparse.ArgumentTypeError(f"{text} is not positive")
+    return n
+
+
+def step(n: int) -> int:
+    return n // 2 if n % 2 == 0 else 3 * n + 1
+
+
+def trajectory(n: int) -> List[int]:
+    numbers = [n]
+    while n != 1:
+        n = step(n)
+        numbers.append(n)
+    return numbers
+
+
+class Cache:
+    """The lengths and peaks of the numbers below size, as far as they have been measured."""
+
+    def __init__(self, size: int):
+        self.size = max(size, 2)
+        self.lengths = [-1] * self.size
+        self.peaks = [0] * self.size
+        self.lengths[1], self.peaks[1] = 0, 1
+        self.steps = 0
+
+    def measure(self, n: int) -> Tuple[int, int]:
+        path = []
+        while n >= self.size or self.lengths[n] < 0:
+            path.append(n)
+            n = step(n)
+        self.steps += len(path)
+        length, peak = self.lengths[n], self.peaks[n]
+        for m in reversed(path):
+            length += 1
+            peak = max(peak, m)
+            if m < self.size:
+                self.lengths[m], self.peaks[m] = length, peak
+        return length, peak
+
+
+def scan_chunk(bounds: Tuple[int, int, int]) -> Chunk:
+    start, end, cache_size = bounds
+    cache = Cache(min(cache_size, end))
+    lengths: Counter[int] = collections.Counter()
+    length_records: List[Tuple[int, int]] = []
+    peak_records: List[Tuple[int, int]] = []
+    for n in range(start, end):
+        length, peak = cache.measure(n)
+        lengths[length] += 1
+        if not length_records or length > length_records[-1][1]:
+            length_records.append((n, length))
+        if not peak_records or peak > peak_records[-1][1]:
+            peak_records.append((n, peak))
+    return Chunk(start, end, lengths, length_records, peak_records, cache.steps)
+
+
+def merge_records(chunks: List[Chunk], records: str) -> List[Tuple[int, int]]:
+    merged: List[Tuple[int, int]] = []
+    for chunk in chunks:
+        for n, value in getattr(chunk, records):
+            if not merged or value > merged[-1][1]:
+                merged.append((n, value))
+    return merged
+
+
+def scan(start: int, end: int, workers: int, cache_size: int) -> Scan:
+    """Measure every n from start to end, inclusive."""
+    bounds = [(lo, min(lo + CHUNK, end + 1), cache_size) for lo in range(start, end + 1, CHUNK)]
+    if workers == 1:
+        chunks = list(map(scan_chunk, bounds))
+    else:
+        with multiprocessing.Pool(workers) as pool:
+            chunks = pool.map(scan_chunk, bounds)
+    lengths: Counter[int] = collections.Counter()
+    for chunk in chunks:
+        lengths.update(chunk.lengths)
+    return Scan(lengths, merge_records(chunks, "length_records"), merge_records(chunks, "peak_records"),
+                sum(chunk.steps for chunk in chunks))
+
+
+def histogram(lengths: Counter[int], bins: int) -> str:
+    """The numbers of each length, in bins of equal width, as bars of #s."""
+    low, high = min(lengths), max(lengths)
+    width = max(1, math.ceil((high - low + 1) / bins))
+    counts = [0] * ((high - low) // width + 1)
+    for length, count in lengths.items():
+        counts[(length - low) // width] += count
+    labels = [f"{low + i * width}-{low + (i + 1) * width - 1}" for i in range(len(counts))]
+    label_width, count_width, most = max(map(len, labels)), len(str(max(counts))), max(counts)
+    return "\n".join(f"{label:>{label_width}} {count:>{count_width}} {'#' * round(count / most * HISTOGRAM_WIDTH)}"
+                     for label, count in zip(labels, counts))
+
+
+def render_records(title: str, records: List[Tuple[int, int]]) -> str:
+    width = len(str(records[-1][0]))
+    return "\n".join([title] + [f"  {n:>{width}}  {value}" for n, value in records])
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Explore Collatz trajectories")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    trajectory_parser = subparsers.add_parser("trajectory", help="follow one number to 1")
+    trajectory_parser.add_argument("n", type=parse_number, help="like 27, or 2^100-1")
+    trajectory_parser.add_argument("--quiet", action="store_true", help="print only the length and peak")
+    scan_parser = subparsers.add_parser("scan", help="measure every number in a range")
+    scan_parser.add_argument("start", type=parse_number)
+    scan_parser.add_argument("end", type=parse_number, help="inclusive")
+    scan_parser.add_argument("--workers", type=int, default=os.cpu_count() or 1, help="processes to scan with")
+    scan_parser.add_argument("--cache", type=int, default=DEFAULT_CACHE, help="remember the numbers below this")
+    scan_parser.add_argument("--records", action="store_true", help="print the record holders")
+    scan_parser.add_argument("--histogram", action="store_true", help="plot how many numbers have each length")
+    scan_parser.add_argument("--bins", type=int, default=30)
+    scan_parser.add_argument("--stats", action="store_true", help="print the steps the cache saved")
+    args = parser.parse_args()
+
+    if args.command == "trajectory":
+        numbers = trajectory(args.n)
+        if not args.quiet:
+            print(" ".join(map(str, numbers)))
+        print(f"{len(numbers) - 1} steps, peak {max(numbers)}")
+        return
+
+    if args.end < args.start:
+        parser.exit(1, "error: the range is empty\n")
+    if args.workers < 1 or args.bins < 1:
+        parser.exit(1, "error: --workers and --bins must be at least 1\n")
+    result = scan(args.start, args.end, args.workers, args.cache)
+    longest, highest = result.length_records[-1], result.peak_records[-1]
+    print(f"{args.start} to {args.end}: longest {longest[0]} ({longest[1]} steps), "
+          f"highest {highest[0]} (peak {highest[1]})")
+    if args.records:
+        print(render_records("length records:", result.length_records))
+        print(render_records("peak records:", result.peak_records))
+    if args.histogram:
+        print(histogram(result.lengths, args.bins))
+    if args.stats:
+        total = sum(length * count for length, count in result.lengths.items())
+        print(f"{result.steps} steps taken of {total}; the cache saved the rest")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,325 @@
+"""
+# Graph Colouring
+
+Give every vertex of a graph a colour, so that no edge joins two
+vertices of the same colour, using as few colours as possible. The
+fewest there can be is the graph's chromatic number.
+
+
+## Methods
+
+    greedy      the vertices in order of degree, most first, each given
+                the first colour none of its neighbours has (Welsh and
+                Powell). Fast, and no promises.
+    dsatur      the same, but always colouring next the vertex whose
+                neighbours have the most different colours already, the
+                most constrained (Brélaz). Fast, and often optimal on
+                small graphs.
+    exact       DSATUR's order, but backtracking over every colour a
+                vertex could have, and cutting off a branch once it uses
+                as many colours as the best found: branch and bound,
+                from DSATUR's colouring down. It stops early when it
+                gets down to the size of a clique it found, which needs
+                that many colours anyway. Exact, and for small graphs:
+                the search grows exponentially.
+    sat         k-colourability as a CNF formula, solved by the in-repo
+                SAT solver (`sat/sat.py`): for every vertex and colour, a
+                variable for whether the vertex has the colour; every
+                vertex has a colour; no edge has the same colour at both
+                ends. The clique's vertices are fixed to colours 1, 2,
+                ..., since any colouring can be renamed to fit. Starting
+                from DSATUR's count, k goes down one at a time until the
+                formula is unsatisfiable. Exact.
+
+
+## The Format
+
+DIMACS graph files: lines starting with `c` are comments, a `p edge
+VERTICES EDGES` line gives the size, and an `e U V` line is an edge,
+with the vertices numbered from 1. Comments of the form `c chromatic: N`
+record the chromatic number, which `--test` checks with the exact
+methods.
+
+    c a triangle
+    p edge 3 3
+    e 1 2
+    e 2 3
+    e 1 3
+
+
+## Graphs
+
+    petersen.col    the Petersen graph, 10 vertices (3 colours)
+    myciel3.col     Mycielski's graph on 11 vertices, the Grötzsch graph:
+                    no triangles, but 4 colours
+    myciel4.col     Mycielski's graph on 23 vertices (5 colours)
+    queen5_5.col    the squares of a 5x5 board, joined when a queen on one
+                    attacks the other (5 colours)
+
+
+## Usage
+
+    python3 colouring.py graphs/petersen.col
+    python3 colouring.py graphs/myciel3.col --method exact --stats
+    python3 colouring.py graphs/queen5_5.col --method greedy
+    python3 colouring.py graphs/myciel4.col --method sat --stats
+    python3 colouring.py graphs/myciel3.col --dimacs 3
+    python3 colouring.py graphs/*.col --test
+"""
+
+import argparse
+import os
+import re
+import sys
+from typing import List, Optional, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from sat.sat import CNF, format_dimacs, solve as solve_sat
+
+
+METHODS: List[str] = ["greedy", "dsatur", "exact", "sat"]
+EXACT_METHODS: List[str] = ["exact", "sat"]
+
+
+class Graph:
+    def __init__(self, vertices: int):
+        self.neighbours: List[Set[int]] = [set() for _ in range(vertices)]
+        self.answers: List[str] = []
+
+    def __len__(self) -> int:
+        return len(self.neighbours)
+
+    def edges(self) -> List[Tuple[int, int]]:
+        return [(u, v) for u in range(len(self)) for v in self.neighbours[u] if u < v]
+
+    def degree(self, vertex: int) -> int:
+        return len(self.neighbours[vertex])
+
+
+class Statistics:
+    def __init__(self):
+        self.clique = 0         # the size of the clique found, the exact methods' lower bound
+        self.nodes = 0          # of the exact search
+        self.formulas = 0       # solved by the SAT solver
+        self.clauses = 0        # in them, all told
+
+    def __str__(self) -> str:
+        parts = [f"a clique of {self.clique}"] if self.clique else []
+        if self.nodes:
+            parts.append(f"{self.nodes} node{'s' * (self.nodes != 1)} searched")
+        if self.formulas:
+            parts.append(f"{self.formulas} formula{'s' * (self.formulas != 1)} solved, {self.clauses} clauses in all")
+        return ", ".join(parts) if parts else "nothing searched"
+
+
+def parse(text: str) -> Graph:
+    graph: Optional[Graph] = None
+    answers = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        words = line.split()
+        if not words:
+            continue
+        if words[0] == "c":
+            answer = re.fullmatch(r"c\s+chromatic:\s*(.*?)\s*", line.strip())
+            if answer is not None:
+                answers.append(answer.group(1))
+            continue
+        if words[0] == "p":
+            if graph is not None or len(words) != 4 or words[1] not in ("edge", "col") \
+                    or not words[2].isdigit() or not words[3].isdigit():
+                raise ValueError(f"line {line_number}: expected one 'p edge VERTICES EDGES'")
+            graph = Graph(int(words[2]))
+        elif words[0] == "e":
+            if graph is None:
+                raise ValueError(f"line {line_number}: an edge before the 'p edge VERTICES EDGES' line")
+            if len(words) != 3 or not words[1].isdigit() or not words[2].isdigit():
+                raise ValueError(f"line {line_number}: expected 'e U V'")
+            u, v = int(words[1]) - 1, int(words[2]) - 1
+            if not (0 <= u < len(graph) and 0 <= v < len(graph)):
+                raise ValueError(f"line {line_number}: the vertices are numbered 1 to {len(graph)}")
+            if u == v:
+                raise ValueError(f"line {line_number}: vertex {u + 1} is joined to itself, so has no colour")
+            graph.neighbours[u].add(v)
+            graph.neighbours[v].add(u)
+        else:
+            raise ValueError(f"line {line_number}: expected a 'c', 'p' or 'e' line")
+    if graph is None:
+        raise ValueError("no 'p edge VERTICES EDGES' line")
+    graph.answers = answers
+    return graph
+
+
+def is_proper(graph: Graph, colours: List[int]) -> bool:
+    return all(colours[u] != colours[v] for u, v in graph.edges())
+
+
+def clique(graph: Graph) -> List[int]:
+    """A large clique, grown greedily from each vertex in turn, keeping the largest."""
+    best: List[int] = []
+    for start in range(len(graph)):
+        members, candidates = [start], set(graph.neighbours[start])
+        while candidates:
+            vertex = max(candidates, key=lambda v: (len(graph.neighbours[v] & candidates), -v))
+            members.append(vertex)
+            candidates &= graph.neighbours[vertex]
+        if len(members) > len(best):
+            best = members
+    return best
+
+
+################################################################################
+###                                 Methods                                  ###
+################################################################################
+
+
+def greedy(graph: Graph) -> List[int]:
+    colours = [-1] * len(graph)
+    for vertex in sorted(range(len(graph)), key=lambda v: -graph.degree(v)):
+        taken = {colours[n] for n in graph.neighbours[vertex]}
+        colours[vertex] = next(c for c in range(len(graph)) if c not in taken)
+    return colours
+
+
+def saturation_order(graph: Graph, colours: List[int], uncoloured: Set[int]) -> int:
+    """The uncoloured vertex with the most different colours around it, then the most uncoloured neighbours."""
+    return max(uncoloured, key=lambda v: (len({colours[n] for n in graph.neighbours[v]} - {-1}),
+                                          len(graph.neighbours[v] & uncoloured), -v))
+
+
+def dsatur(graph: Graph) -> List[int]:
+    colours = [-1] * len(graph)
+    uncoloured = set(range(len(graph)))
+    while uncoloured:
+        vertex = saturation_order(graph, colours, uncoloured)
+        taken = {colours[n] for n in graph.neighbours[vertex]}
+        colours[vertex] = next(c for c in range(len(graph)) if c not in taken)
+        uncoloured.remove(vertex)
+    return colours
+
+
+def exact(graph: Graph, statistics: Statistics) -> List[int]:
+    best = dsatur(graph)
+    bound = statistics.clique = len(clique(graph))
+    colours = [-1] * len(graph)
+    uncoloured = set(range(len(graph)))
+
+    def search(used: int) -> bool:
+        """Whether to stop, with best down to the clique's size."""
+        nonlocal best
+        statistics.nodes += 1
+        if not uncoloured:
+            best = list(colours)
+            return max(best) + 1 == bound
+        vertex = saturation_order(graph, colours, uncoloured)
+        taken = {colours[n] for n in graph.neighbours[vertex]}
+        uncoloured.remove(vertex)
+        for colour in range(used + 1):
+            if colour >= max(best):     # no better than the best found
+                break
+            if colour in taken:
+                continue
+            colours[vertex] = colour
+            if search(max(used, colour + 1)):
+                return True
+        colours[vertex] = -1
+        uncoloured.add(vertex)
+        return False
+
+    if max(best, default=-1) + 1 > bound:
+        search(0)
+    return best
+
+
+def to_cnf(graph: Graph, k: int, fixed: List[int]) -> CNF:
+    """Whether the graph has a k-colouring, with the fixed vertices given colours 1, 2, ...: vertex v has
+    colour c when variable v * k + c + 1 is true."""
+    def variable(vertex: int, colour: int) -> int:
+        return vertex * k + colour + 1
+
+    clauses = [[variable(v, c) for c in range(k)] for v in range(len(graph))]
+    clauses += [[-variable(u, c), -variable(v, c)] for u, v in graph.edges() for c in range(k)]
+    clauses += [[variable(v, c)] for c, v in enumerate(fixed[:k])]
+    return CNF(len(graph) * k, clauses)
+
+
+def colours_from_model(graph: Graph, k: int, model: List[int]) -> List[int]:
+    true = {literal for literal in model if literal > 0}
+    return [next(c for c in range(k) if v * k + c + 1 in true) for v in range(len(graph))]
+
+
+def by_sat(graph: Graph, statistics: Statistics) -> List[int]:
+    best = dsatur(graph)
+    fixed = clique(graph)
+    statistics.clique = len(fixed)
+    for k in range(max(best, default=-1), len(fixed) - 1, -1):
+        cnf = to_cnf(graph, k, fixed)
+        statistics.formulas += 1
+        statistics.clauses += len(cnf.clauses)
+        model = solve_sat(cnf.variables, cnf.clauses)
+        if model is None:
+            break
+        best = colours_from_model(graph, k, model)
+    return best
+
+
+def colour(graph: Graph, method: str, statistics: Statistics) -> List[int]:
+    if method == "greedy":
+        return greedy(graph)
+    if method == "dsatur":
+        return dsatur(graph)
+    if method == "exact":
+        return exact(graph, statistics)
+    return by_sat(graph, statistics)
+
+
+def render(colours: List[int]) -> str:
+    count = max(colours, default=-1) + 1
+    lines = [f"{c + 1}: {' '.join(str(v + 1) for v, vc in enumerate(colours) if vc == c)}" for c in range(count)]
+    return "\n".join(lines + [f"{count} colour{'s' * (count != 1)}"])
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Colour graphs with as few colours as possible")
+    parser.add_argument("files", type=str, nargs="+", help="graphs, in DIMACS format")
+    parser.add_argument("--method", choices=METHODS, default="dsatur")
+    parser.add_argument("--stats", action="store_true", help="print the work done")
+    parser.add_argument("--dimacs", type=int, metavar="K", default=None,
+                        help="print the CNF for a K-colouring instead of colouring")
+    parser.add_argument("--test", action="store_true",
+                        help="check the 'c chromatic:' comments, with --method exact or sat (exact by default)")
+    args = parser.parse_args()
+
+    failures = 0
+    for path in args.files:
+        try:
+            with open(path) as f:
+                graph = parse(f.read())
+        except (OSError, ValueError) as e:
+            parser.exit(1, f"error: {path}: {e}\n")
+        if args.dimacs is not None:
+            if args.dimacs < 1:
+                parser.exit(1, "error: --dimacs needs at least one colour\n")
+            cnf = to_cnf(graph, args.dimacs, clique(graph))
+            print(format_dimacs(cnf, [f"{args.dimacs}-colouring of {path}"]), end="")
+            continue
+        statistics = Statistics()
+        method = args.method if not args.test or args.method in EXACT_METHODS else "exact"
+        colours = colour(graph, method, statistics)
+        assert is_proper(graph, colours)
+        count = str(max(colours, default=-1) + 1)
+        if args.test:
+            ok = graph.answers == [count]
+            failures += not ok
+            print(f"{'ok' if ok else 'FAIL'}  {path}: {count}" + ("" if ok else f", expected {graph.answers}"))
+            continue
+        if len(args.files) > 1:
+            print(f"{path}:")
+        print(render(colours))
+        if args.stats:
+            print(statistics)
+    if failures:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,459 @@
+"""
+# Connect Four
+
+Two players drop stones in turn into the columns of an upright 7 wide,
+6 high grid; a stone falls to the lowest empty cell, and the first to
+get four in a row, across, down, or diagonally, wins. The first player
+wins, by starting in the middle column (Allis and Allen, 1988).
+
+
+## Bitboards
+
+A position is two numbers used as bit sets: the stones of the player to
+move, and all the stones. Each column is height + 1 bits, bottom up, the
+extra bit always empty so that shifting a row of stones one column over
+never carries into the next. Adding the bottom row to the mask gives
+the cells a stone can be dropped into, and four in a row is three
+shifts and ands, one each for across, down, and the two diagonals. This
+follows Pascal Pons's solver (http://blog.gamesolver.org).
+
+
+## Solving
+
+Negamax with alpha-beta, scored as in Pons's solver: a win is worth 1
+plus the stones the winner has left to play after their winning one, so
+a quicker win is worth more, and a draw is 0.
+
+    moves           only those that do not hand the opponent a win on
+                    their next move; if the opponent has two wins ready,
+                    the position is lost
+    ordering        the moves that make the most new threats first, and
+                    among those, the middle columns first
+    transposition   a table of upper bounds on each position's value
+    null windows    the value is found by asking, again and again, is it
+                    more than v?, each a search with alpha = v, beta =
+                    v + 1, halving the range each time
+
+In Python, a position a dozen stones in solves in seconds to a minute,
+one twenty stones in at once, and the empty board not at all. `check`
+compares the solver with plain minimax on boards small enough for that.
+
+
+## Notation
+
+A position is the columns played, in order, counting from 1 on the left:
+4453 is the first player in the middle, the second on top of them, then
+the first again, and the second in the third column.
+
+
+## Usage
+
+    python3 connect4.py solve 2252576253462244111563365343671351441
+    python3 connect4.py solve 22525762534622441115 --analyze --stats
+    python3 connect4.py solve 4444441213222 --stats
+    python3 connect4.py solve 4455 --width 5 --height 4 --analyze
+    python3 connect4.py check --width 4 --height 4 --positions 200 --seed 1
+    python3 connect4.py play --first engine
+    python3 connect4.py play --no-record
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from repl.repl import Repl
+from rng.rng import add_seed_argument, make_rng
+
+
+STONES: str = "XO"
+
+
+class Board:
+    """The size of the grid, and the bit masks that go with it."""
+
+    def __init__(self, width: int = 7, height: int = 6):
+        if width < 1 or height < 1:
+            raise ValueError(f"a {width}x{height} board has no cells")
+        self.width, self.height = width, height
+        self.cells = width * height
+        self.bottom = sum(1 << (column * (height + 1)) for column in range(width))
+        self.full = self.bottom * ((1 << height) - 1)
+        middle = (width - 1) / 2
+        self.order = sorted(range(width), key=lambda column: abs(column - middle))
+
+    def column_mask(self, column: int) -> int:
+        return ((1 << self.height) - 1) << (column * (self.height + 1))
+
+    def top_mask(self, column: int) -> int:
+        return 1 << (self.height - 1 + column * (self.height + 1))
+
+    def winning_cells(self, stones: int, mask: int) -> int:
+        """The empty cells that would give `stones` four in a row."""
+        h = self.height
+        found = (stones << 1) & (stones << 2) & (stones << 3)   # on top of three
+        for shift in (h + 1, h, h + 2):                         # across, and the two diagonals
+            pair = (stones << shift) & (stones << 2 * shift)
+            found |= pair & (stones << 3 * shift)
+            found |= pair & (stones >> shift)
+            pair = (stones >> shift) & (stones >> 2 * shift)
+            found |= pair & (stones << shift)
+            found |= pair & (stones >> 3 * shift)
+        return found & (self.full ^ mask)
+
+
+class Position:
+    def __init__(self, board: Board, current: int = 0, mask: int = 0, moves: int = 0):
+        self.board = board
+        self.current = current      # the stones of the player to move
+        self.mask = mask            # all the stones
+        self.moves = moves
+
+    def key(self) -> int:
+        """current + mask: the same for two positions only if they are the same."""
+        return self.current + self.mask
+
+    def can_play(self, column: int) -> bool:
+        return not self.mask & self.board.top_mask(column)
+
+    def possible(self) -> int:
+        return (self.mask + self.board.bottom) & self.board.full
+
+    def play(self, move: int) -> "Position":
+        """The position after dropping a stone on the bit `move`, one of possible()."""
+        return Position(self.board, self.current ^ self.mask, self.mask | move, self.moves + 1)
+
+    def play_column(self, column: int) -> "Position":
+        return self.play(self.possible() & self.board.column_mask(column))
+
+    def is_winning_move(self, column: int) -> bool:
+        return bool(self.board.winning_cells(self.current, self.mask) & self.possible()
+                    & self.board.column_mask(column))
+
+    def can_win_next(self) -> bool:
+        return bool(self.board.winning_cells(self.current, self.mask) & self.possible())
+
+    def non_losing_moves(self) -> int:
+        """The moves that do not let the opponent win next; 0 if every move does."""
+        possible = self.possible()
+        threats = self.board.winning_cells(self.current ^ self.mask, self.mask)
+        forced = possible & threats
+        if forced:
+            if forced & (forced - 1):
+                return 0        # two threats to block at once
+            possible = forced
+        return possible & ~(threats >> 1)   # not under one of the opponent's wins
+
+    def threats_made(self, move: int) -> int:
+        return bin(self.board.winning_cells(self.current | move, self.mask)).count("1")
+
+    def render(self) -> str:
+        b = self.board
+        mine = STONES[self.moves % 2]
+        theirs = STONES[1 - self.moves % 2]
+        rows = []
+        for row in range(b.height - 1, -1, -1):
+            cells = []
+            for column in range(b.width):
+                bit = 1 << (column * (b.height + 1) + row)
+                cells.append("." if not self.mask & bit else mine if self.current & bit else theirs)
+            rows.append(" ".join(cells))
+        rows.append(" ".join(str(column + 1) for column in range(b.width)))
+        return "\n".join(rows)
+
+
+class OutOfNodes(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.nodes = 0
+
+    def __str__(self) -> str:
+        return f"{self.nodes} positions searched"
+
+
+def half(n: int) -> int:
+    """n / 2, rounded toward 0."""
+    return n // 2 if n >= 0 else -(-n // 2)
+
+
+class Solver:
+    def __init__(self, board: Board, max_nodes: Optional[int] = None):
+        self.board = board
+        self.max_nodes = max_nodes
+        self.table: Dict[int, int] = {}     # key -> an upper bound on the value
+        self.statistics = Statistics()
+
+    def negamax(self, position: Position, alpha: int, beta: int) -> int:
+        """The value, if it is within (alpha, beta); else a bound beyond the side it fell on."""
+        self.statistics.nodes += 1
+        if self.max_nodes is not None and self.statistics.nodes > self.max_nodes:
+            raise OutOfNodes()
+        cells = self.board.cells
+        moves = position.non_losing_moves()
+        if not moves:
+            return -half(cells - position.moves)
+        if position.moves >= cells - 2:
+            return 0
+        lowest = -half(cells - 2 - position.moves)    # the opponent cannot win on their next move
+        if alpha < lowest:
+            alpha = lowest
+            if alpha >= beta:
+                return alpha
+        highest = self.table.get(position.key(), half(cells - 1 - position.moves))
+        if beta > highest:
+            beta = highest
+            if alpha >= beta:
+                return beta
+        ordered: List[Tuple[int, int]] = []
+        for column in self.board.order:
+            move = moves & self.board.column_mask(column)
+            if move:
+                ordered.append((-position.threats_made(move), len(ordered), move))
+        for _, _, move in sorted(ordered):
+            value = -self.negamax(position.play(move), -beta, -alpha)
+            if value >= beta:
+                return value
+            if value > alpha:
+                alpha = value
+        self.table[position.key()] = alpha
+        return alpha
+
+    def solve(self, position: Position) -> int:
+        """The exact value to the player to move."""
+        cells = self.board.cells
+        if position.can_win_next():
+            return half(cells + 1 - position.moves)
+        low, high = -half(cells - position.moves), half(cells + 1 - position.moves)
+        while low < high:
+            middle = low + (high - low) // 2
+            if middle <= 0 and half(low) < middle:
+                middle = half(low)
+            elif middle >= 0 and half(high) > middle:
+                middle = half(high)
+            value = self.negamax(position, middle, middle + 1)
+            if value <= middle:
+                high = value
+            else:
+                low = value
+        return low
+
+    def analyze(self, position: Position) -> Dict[int, int]:
+        """The value of each playable column, to the player to move."""
+        values = {}
+        for column in range(self.board.width):
+            if position.can_play(column):
+                if position.is_winning_move(column):
+                    values[column] = half(self.board.cells + 1 - position.moves)
+                else:
+                    values[column] = -self.solve(position.play_column(column))
+        return values
+
+
+def describe(position: Position, value: int) -> str:
+    """A value to the player to move, in words."""
+    if value == 0:
+        return "a draw"
+    player = STONES[position.moves % 2]
+    winner = player if value > 0 else STONES[1 - position.moves % 2]
+    plies = position.board.cells + 2 - 2 * abs(value) - position.moves   # up to the winning stone
+    stones = (plies + 1) // 2 if winner == player else plies // 2
+    return f"{winner} wins with {stones} more stone{'s' if stones > 1 else ''}"
+
+
+def parse(board: Board, text: str) -> Position:
+    position = Position(board)
+    for i, c in enumerate(text.strip(), start=1):
+        if not c.isdigit() or not 1 <= int(c) <= board.width:
+            raise ValueError(f"move {i}: a column is 1 to {board.width}, not {c!r}")
+        column = int(c) - 1
+        if not position.can_play(column):
+            raise ValueError(f"move {i}: column {c} is full")
+        if position.is_winning_move(column):
+            raise ValueError(f"move {i}: {STONES[position.moves % 2]} has already won with column {c}")
+        position = position.play_column(column)
+    return position
+
+
+################################################################################
+###                                 Checking                                 ###
+################################################################################
+
+
+def minimax(position: Position) -> int:
+    """The value by trying every move, with no pruning at all."""
+    best = None
+    for column in range(position.board.width):
+        if position.can_play(column):
+            if position.is_winning_move(column):
+                value = half(position.board.cells + 1 - position.moves)
+            else:
+                value = -minimax(position.play_column(column))
+            best = value if best is None else max(best, value)
+    return 0 if best is None else best
+
+
+def check(board: Board, positions: int, rng: random.Random) -> bool:
+    """Solve random positions both ways, and compare."""
+    solver = Solver(board)
+    for _ in range(positions):
+        position, moves = Position(board), ""
+        for _ in range(rng.randrange(board.cells // 3, board.cells)):
+            columns = [c for c in range(board.width) if position.can_play(c) and not position.is_winning_move(c)]
+            if not columns:
+                break
+            column = rng.choice(columns)
+            position, moves = position.play_column(column), moves + str(column + 1)
+        if position.moves == board.cells:
+            continue
+        expected, found = minimax(position), solver.solve(position)
+        if expected != found:
+            print(f"{moves}: the solver says {found}, minimax {expected}")
+            return False
+    print(f"All {positions} positions agree")
+    return True
+
+
+################################################################################
+###                                   Play                                   ###
+################################################################################
+
+
+def engine_move(position: Position, max_nodes: int) -> Tuple[int, str]:
+    """
+    The best column, by solving, if that takes at most max_nodes positions;
+    otherwise one that lets the opponent win next only if all do, making the
+    most threats.
+    """
+    solver = Solver(position.board, max_nodes)
+    try:
+        values = solver.analyze(position)
+        column = max(position.board.order, key=lambda c: values.get(c, -position.board.cells))
+        return column, describe(position, values[column])
+    except OutOfNodes:
+        pass
+    safe = position.non_losing_moves() or position.possible()
+    options = [c for c in position.board.order if safe & position.board.column_mask(c)]
+    for column in options:
+        if position.is_winning_move(column):
+            return column, "a win"
+    column = max(options, key=lambda c: position.threats_made(safe & position.board.column_mask(c)))
+    return column, "too far from the end to solve"
+
+
+def play(board: Board, engine_first: bool, max_nodes: int, record: bool = True):
+    history: List[Position] = [Position(board)]
+    engine_plays = 0 if engine_first else 1
+    finished: List[Optional[str]] = [None]
+    session = Session("connect4", f"{board.width}x{board.height}", record)
+
+    def engine_turn():
+        position = history[-1]
+        if finished[0] is not None or position.moves % 2 != engine_plays:
+            return
+        column, why = engine_move(position, max_nodes)
+        drop(column, f"{STONES[engine_plays]} plays {column + 1} ({why})")
+
+    def drop(column: int, message: str):
+        position = history[-1]
+        won = position.is_winning_move(column)
+        history.append(position.play_column(column))
+        print(message)
+        print(history[-1].render())
+        if won:
+            finished[0] = f"{STONES[position.moves % 2]} wins"
+            session.finish("lost" if position.moves % 2 == engine_plays else "won")
+        elif history[-1].moves == board.cells:
+            finished[0] = "A draw"
+            session.finish("drawn")
+        if finished[0] is not None:
+            print(finished[0])
+
+    def move(text: str):
+        if finished[0] is not None:
+            raise ValueError("the game is over (:new for another)")
+        if not text.isdigit() or not 1 <= int(text) <= board.width:
+            raise ValueError(f"a move is a column, 1 to {board.width}")
+        if not history[-1].can_play(int(text) - 1):
+            raise ValueError(f"column {text} is full")
+        session.move()
+        drop(int(text) - 1, f"{STONES[history[-1].moves % 2]} plays {text}")
+        engine_turn()
+
+    def undo(_: str):
+        while len(history) > 1:
+            history.pop()
+            if history[-1].moves % 2 != engine_plays:
+                break
+        finished[0] = None
+        print(history[-1].render())
+
+    def new(_: str):
+        del history[1:]
+        finished[0] = None
+        session.start()
+        print(history[-1].render())
+        engine_turn()
+
+    repl = Repl("column> ", move)
+    repl.add_command("board", lambda _: print(history[-1].render()), "print the board")
+    repl.add_command("undo", undo, "take back your last move (and the engine's reply)")
+    repl.add_command("new", new, "start again")
+    print(f"You are {STONES[1 - engine_plays]}; a move is a column, and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve and play Connect Four")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="the exact value of a position")
+    solve_parser.add_argument("moves", type=str, nargs="?", default="", help="the columns played, like 4453")
+    solve_parser.add_argument("--analyze", action="store_true", help="the value of each move too")
+    solve_parser.add_argument("--stats", action="store_true", help="print the positions searched")
+    check_parser = subparsers.add_parser("check", help="compare the solver with minimax on random positions")
+    check_parser.add_argument("--positions", type=int, default=100)
+    add_seed_argument(check_parser)
+    play_parser = subparsers.add_parser("play", help="play against the engine")
+    play_parser.add_argument("--first", choices=["human", "engine"], default="human")
+    play_parser.add_argument("--nodes", type=int, default=200000,
+                             help="the most positions the engine searches before it gives up on solving")
+    play_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    for p in (solve_parser, check_parser, play_parser):
+        p.add_argument("--width", type=int, default=7)
+        p.add_argument("--height", type=int, default=6)
+    args = parser.parse_args()
+
+    try:
+        board = Board(args.width, args.height)
+        if args.command == "solve":
+            position = parse(board, args.moves)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "check":
+        if not check(board, args.positions, make_rng(args.seed)):
+            parser.exit(1)
+    elif args.command == "play":
+        play(board, args.first == "engine", args.nodes, not args.no_record)
+    else:
+        print(position.render())
+        if position.moves == board.cells:
+            print("The board is full: a draw")
+            return
+        solver = Solver(board)
+        print(f"{STONES[position.moves % 2]} to move: {describe(position, solver.solve(position))}")
+        if args.analyze:
+            for column, value in solver.analyze(position).items():
+                print(f"  {column + 1}: {describe(position, value)}")
+        if args.stats:
+            print(solver.statistics)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,165 @@
+"""
+# Countdown
+
+The numbers round of the television game: six numbers, a target from
+101 to 999, and thirty seconds to make the target from the numbers with
++, -, * and /. Each number can be used at most once, and every step has
+to give a positive whole number, so no fractions and nothing negative
+along the way. If the target cannot be made, the closest value wins.
+
+    large       25, 50, 75 and 100, one of each
+    small       1 to 10, two of each
+
+
+## Searching
+
+Take any two of the numbers left, replace them with what an operator
+makes of them, and carry on with the shorter list; every value made
+along the way is a candidate. Most combinations are not worth trying:
+
+    a + b, a * b    only with a >= b, since the other order is the same
+    a - b           only with a > b, and not when it gives b back
+    a / b           only when b divides a, b > 1, and it does not give
+                    b back
+    a * 1           never: it gives a back
+
+and a list of numbers already searched from, in any order, is not
+searched again, however it was reached. The answer is the closest value,
+and of those, the one using the fewest numbers. Expressions are built as
+trees for the shared expression module (`expression/expression.py`),
+which writes them out with only the parentheses they need.
+
+
+## Usage
+
+    python3 countdown.py solve 75 50 2 3 8 7 --target 812
+    python3 countdown.py solve 100 75 50 25 6 3 --target 952 --stats
+    python3 countdown.py solve 1 1 2 2 3 3 --target 999
+    python3 countdown.py random --large 2 --seed 1
+"""
+
+import argparse
+import os
+import random
+import sys
+from fractions import Fraction
+from typing import List, NamedTuple, Optional, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from expression.expression import Binary, Node, Number, evaluate, format_value, to_string
+from rng.rng import add_seed_argument, make_rng
+
+
+LARGE: List[int] = [25, 50, 75, 100]
+SMALL: List[int] = [n for n in range(1, 11) for _ in range(2)]
+Item = Tuple[int, Node]     # a value, and how it was made
+
+
+class Statistics:
+    def __init__(self):
+        self.states = 0
+
+    def __str__(self) -> str:
+        return f"{self.states} lists of numbers searched"
+
+
+class Solution(NamedTuple):
+    value: int
+    tree: Node
+
+
+def combinations(a: Item, b: Item) -> List[Item]:
+    """What a and b can make, without the combinations that repeat others; a's value is at least b's."""
+    (x, left), (y, right) = a, b
+    made = [(x + y, Binary("+", left, right))]
+    if y > 1:
+        made.append((x * y, Binary("*", left, right)))
+    if x > y and x - y != y:
+        made.append((x - y, Binary("-", left, right)))
+    if y > 1 and x % y == 0 and x // y != y:
+        made.append((x // y, Binary("/", left, right)))
+    return made
+
+
+def leaves(tree: Node) -> int:
+    return leaves(tree.left) + leaves(tree.right) if isinstance(tree, Binary) else 1
+
+
+def solve(numbers: List[int], target: int, statistics: Statistics) -> Solution:
+    seen: Set[Tuple[int, ...]] = set()
+    best: Optional[Solution] = None
+
+    def rank(solution: Solution) -> Tuple[int, int]:
+        return abs(solution.value - target), leaves(solution.tree)
+
+    def search(items: List[Item]):
+        nonlocal best
+        key = tuple(sorted(value for value, _ in items))
+        if key in seen:
+            return
+        seen.add(key)
+        statistics.states += 1
+        for value, tree in items:
+            if best is None or rank(Solution(value, tree)) < rank(best):
+                best = Solution(value, tree)
+        items = sorted(items, key=lambda item: -item[0])
+        for i in range(len(items)):
+            for j in range(i + 1, len(items)):
+                rest = items[:i] + items[i + 1:j] + items[j + 1:]
+                for made in combinations(items[i], items[j]):
+                    search(rest + [made])
+
+    search([(n, Number(Fraction(n))) for n in numbers])
+    assert best is not None
+    return best
+
+
+def derivation(tree: Node) -> List[str]:
+    """The steps that make the tree, one operator a line, each on values already made."""
+    if not isinstance(tree, Binary):
+        return []
+    steps = derivation(tree.left) + derivation(tree.right)
+    left, right, made = (format_value(evaluate(node)) for node in (tree.left, tree.right, tree))
+    return steps + [f"{left} {tree.operator} {right} = {made}"]
+
+
+def render(solution: Solution, target: int) -> str:
+    lines = derivation(solution.tree)
+    lines.append(f"{to_string(solution.tree)} = {solution.value}")
+    off = abs(solution.value - target)
+    lines.append("Exact" if off == 0 else f"{off} away from {target}")
+    return "\n".join(lines)
+
+
+def deal(large: int, rng: random.Random) -> Tuple[List[int], int]:
+    return rng.sample(LARGE, large) + rng.sample(SMALL, 6 - large), rng.randint(101, 999)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve the Countdown numbers game")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a given game")
+    solve_parser.add_argument("numbers", type=int, nargs="+")
+    solve_parser.add_argument("--target", type=int, required=True)
+    random_parser = subparsers.add_parser("random", help="deal a game, and solve it")
+    random_parser.add_argument("--large", type=int, default=1, choices=range(5), help="how many large numbers")
+    add_seed_argument(random_parser)
+    for p in (solve_parser, random_parser):
+        p.add_argument("--stats", action="store_true", help="print the lists of numbers searched")
+    args = parser.parse_args()
+
+    if args.command == "random":
+        numbers, target = deal(args.large, make_rng(args.seed))
+        print(f"{' '.join(map(str, numbers))}, target {target}")
+    else:
+        numbers, target = args.numbers, args.target
+        if not numbers or any(n < 1 for n in numbers):
+            parser.exit(1, "error: the numbers must be positive\n")
+    statistics = Statistics()
+    print(render(solve(numbers, target, statistics), target))
+    if args.stats:
+        print(statistics)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,339 @@
+"""
+# Counter Machines
+
+A Minsky counter machine has a handful of counters, each holding a
+natural number, and a program with only three instructions:
+
+    inc r [next]      add one to counter r
+    dec r zero        if r is 0, jump to `zero`; otherwise subtract one
+    halt              stop
+
+Two counters are already enough to simulate any Turing machine (given a
+suitable encoding of the input), which makes these the simplest
+universal machines in this repo.
+
+
+## Program Format
+
+One instruction per line, optionally after a `label:`. Execution starts
+at the first instruction and carries on to the following line unless
+told otherwise: `inc` may name the instruction to go to next, and `dec`
+jumps when the counter is zero. Running off the end halts. `#` starts a
+comment. Counters are named by any identifier, and `counters: a b`
+fixes their order (otherwise they are ordered by first use). For
+example, this adds `b` to `a`:
+
+    loop: dec b end
+          inc a loop
+    end:  halt
+
+There is no unconditional jump, but `dec z label` on a counter `z` that
+is always 0 does the job.
+
+
+## Translating to Brainfuck
+
+`to-bf` translates a program into Brainfuck that runs it, with the
+initial counters baked in. When the machine halts, the BF program
+outputs each counter as a byte. Each counter is one BF cell, so the
+translation is only faithful while the counters stay below the cell
+size (256 for most interpreters). The layout is:
+
+    cell 0         1 while the machine is running
+    cells 1, 2     scratch space for testing a counter against 0
+    cells 3...     the counters
+    then           one flag per instruction, set while it is the current one
+
+Each pass of the main loop visits every instruction's flag in turn, and
+runs the instruction whose flag is set.
+
+
+## Usage
+
+    python3 counter_machine.py run programs/add.cm --set a=3 --set b=4
+    python3 counter_machine.py run programs/multiply.cm --set a=6 --set b=7 --trace
+    python3 counter_machine.py to-bf programs/add.cm --set a=3 --set b=4 --output add.bf
+"""
+
+import argparse
+import re
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+
+DEFAULT_MAX_STEPS: int = 1_000_000
+IDENTIFIER: str = r"[A-Za-z_][A-Za-z0-9_]*"
+
+
+class ProgramError(Exception):
+    """The counter machine program is malformed."""
+
+
+class Instruction(NamedTuple):
+    op: str                 # "inc", "dec", or "halt"
+    counter: Optional[str]
+    target: Optional[int]   # inc: the next instruction; dec: where to go on zero
+
+
+class Program(NamedTuple):
+    counters: List[str]
+    instructions: List[Instruction]
+    labels: List[Optional[str]]
+
+
+def parse_program(text: str) -> Program:
+    counters: List[str] = []
+    labels: Dict[str, int] = {}
+    # (op, counter, target label, line number) until the labels are resolved
+    pending: List[Tuple[str, Optional[str], Optional[str], int]] = []
+    errors: List[str] = []
+    fixed_counters = False
+
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        line = line.split("#", 1)[0].strip()
+        if not line:
+            continue
+        if line.startswith("counters:"):
+            if fixed_counters or pending:
+                errors.append(f"line {line_number}: 'counters:' must come once, before the program")
+            fixed_counters = True
+            counters = line[len("counters:"):].split()
+            continue
+        match = re.fullmatch(rf"(?:({IDENTIFIER})\s*:)?\s*(.*)", line)
+        label, rest = match.group(1), match.group(2).split()
+        if label is not None:
+            if label in labels:
+                errors.append(f"line {line_number}: duplicate label '{label}'")
+            labels[label] = len(pending)
+        if not rest:
+            errors.append(f"line {line_number}: label '{label}' has no instruction")
+            continue
+        op, operands = rest[0].lower(), rest[1:]
+        expected = {"inc": (1, 2), "dec": (2, 2), "halt": (0, 0)}
+        if op not in expected:
+            errors.append(f"line {line_number}: unknown instruction '{rest[0]}'")
+            continue
+        lo, hi = expected[op]
+        if not lo <= len(operands) <= hi:
+            errors.append(f"line {line_number}: wrong number of operands for '{op}'")
+            continue
+        counter = operands[0] if operands else None
+        if counter is not None:
+            if not re.fullmatch(IDENTIFIER, counter):
+                errors.append(f"line {line_number}: '{counter}' is not a counter name")
+            elif counter not in counters:
+                if fixed_counters:
+                    errors.append(f"line {line_number}: '{counter}' is not in 'counters:'")
+                else:
+                    counters.append(counter)
+        pending.append((op, counter, operands[1] if len(operands) > 1 else None, line_number))
+
+    instructions: List[Instruction] = []
+    for i, (op, counter, target, line_number) in enumerate(pending):
+        if target is None:
+            resolved = i + 1 if op == "inc" else None
+        elif target in labels:
+            resolved = labels[target]
+        else:
+            errors.append(f"line {line_number}: unknown label '{target}'")
+            resolved = None
+        instructions.append(Instruction(op, counter, resolved))
+    if errors:
+        raise ProgramError("\n".join(errors))
+
+    names: List[Optional[str]] = [None] * len(instructions)
+    for label, i in labels.items():
+        if i < len(names):
+            names[i] = label
+    return Program(counters, instructions, names)
+
+
+def parse_assignments(assignments: List[str], program: Program) -> Dict[str, int]:
+    values = {counter: 0 for counter in program.counters}
+    for assignment in assignments:
+        match = re.fullmatch(rf"({IDENTIFIER})=(\d+)", assignment)
+        if match is None:
+            raise ProgramError(f"expected counter=value, not '{assignment}'")
+        if match.group(1) not in values:
+            raise ProgramError(f"the program has no counter '{match.group(1)}'")
+        values[match.group(1)] = int(match.group(2))
+    return values
+
+
+def format_instruction(program: Program, pc: int) -> str:
+    op, counter, target = program.instructions[pc]
+    name = program.labels[pc] or str(pc)
+    if op == "halt":
+        return f"{name}: halt"
+    if target is None or target >= len(program.instructions):
+        where = "end"
+    else:
+        where = program.labels[target] or str(target)
+    return f"{name}: {op} {counter} {where}"
+
+
+def run(program: Program, counters: Dict[str, int], max_steps: int, trace: bool = False) -> Tuple[bool, int]:
+    """Run the program, updating `counters`. Return (whether it halted, steps)."""
+    pc, steps = 0, 0
+    while 0 <= pc < len(program.instructions):
+        op, counter, target = program.instructions[pc]
+        if op == "halt":
+            return True, steps
+        if steps >= max_steps:
+            return False, steps
+        if trace:
+            state = " ".join(f"{c}={counters[c]}" for c in program.counters)
+            print(f"{steps:>8}  {format_instruction(program, pc):<24} {state}")
+        steps += 1
+        if op == "inc":
+            counters[counter] += 1
+            pc = target
+        elif counters[counter] == 0:
+            pc = target
+        else:
+            counters[counter] -= 1
+            pc += 1
+    return True, steps
+
+
+def to_bf(program: Program, counters: Dict[str, int]) -> str:
+    """Translate the program into Brainfuck (see the module docstring for the layout)."""
+    running, is_nonzero, is_zero = 0, 1, 2
+    counter_cell = {c: 3 + i for i, c in enumerate(program.counters)}
+    flag_base = 3 + len(program.counters)
+    num_instructions = len(program.instructions)
+    position = 0
+    code: List[str] = []
+
+    def go(cell: int):
+        nonlocal position
+        code.append(">" * (cell - position) if cell > position else "<" * (position - cell))
+        position = cell
+
+    def add(cell: int, amount: int = 1):
+        go(cell)
+        code.append("+" * amount)
+
+    def goto(pc: Optional[int]):
+        """Make `pc` the current instruction, halting if it is past the end."""
+        if pc is None or pc >= num_instructions:
+            halt()
+        else:
+            add(flag_base + pc)
+
+    def halt():
+        go(running)
+        code.append("-")
+        for c in program.counters:
+            go(counter_cell[c])
+            code.append(".")
+
+    for c, value in counters.items():
+        add(counter_cell[c], value)
+    add(running)
+    if num_instructions:
+        add(flag_base)
+    else:
+        halt()
+    go(running)
+    code.append("[")
+    for pc, (op, counter, target) in enumerate(program.instructions):
+        flag = flag_base + pc
+        go(flag)
+        code.append("[-")
+        if op == "halt":
+            halt()
+        elif op == "inc":
+            add(counter_cell[counter])
+            goto(target)
+        else:
+            cell = counter_cell[counter]
+            # Copy the counter into is_nonzero (via is_zero), then set is_zero
+            go(cell)
+            code.append("[-")
+            add(is_nonzero)
+            add(is_zero)
+            go(cell)
+            code.append("]")
+            go(is_zero)
+            code.append("[-")
+            add(cell)
+            go(is_zero)
+            code.append("]+")
+            go(is_nonzero)
+            code.append("[[-]")
+            go(is_zero)
+            code.append("-")
+            go(cell)
+            code.append("-")
+            goto(pc + 1)
+            go(is_nonzero)
+            code.append("]")
+            go(is_zero)
+            code.append("[-")
+            goto(target)
+            go(is_zero)
+            code.append("]")
+        go(flag)
+        code.append("]")
+    go(running)
+    code.append("]")
+    text = "".join(code)
+    return "\n".join(text[i:i + 72] for i in range(0, len(text), 72)) + "\n"
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def load(path: str) -> Program:
+    with open(path) as f:
+        return parse_program(f.read())
+
+
+def run_command(args):
+    program = load(args.program)
+    counters = parse_assignments(args.set, program)
+    halted, steps = run(program, counters, args.max_steps, args.trace)
+    print(f"Result: {'halt' if halted else 'step limit exceeded'}")
+    print(f"Steps: {steps}")
+    for c in program.counters:
+        print(f"{c} = {counters[c]}")
+
+
+def to_bf_command(args):
+    program = load(args.program)
+    text = to_bf(program, parse_assignments(args.set, program))
+    if args.output is None:
+        print(text, end="")
+    else:
+        with open(args.output, "w") as f:
+            f.write(text)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Minsky counter machines")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+
+    run_parser = subparsers.add_parser("run", help="run a counter machine")
+    run_parser.add_argument("program", type=str, help="path to the program")
+    run_parser.add_argument("--set", type=str, action="append", default=[], help="initial value, e.g. a=3")
+    run_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    run_parser.add_argument("--trace", action="store_true", help="print every instruction as it runs")
+    run_parser.set_defaults(func=run_command)
+
+    bf_parser = subparsers.add_parser("to-bf", help="translate a counter machine into Brainfuck")
+    bf_parser.add_argument("program", type=str, help="path to the program")
+    bf_parser.add_argument("--set", type=str, action="append", default=[], help="initial value, e.g. a=3")
+    bf_parser.add_argument("--output", type=str, default=None, help="defaults to stdout")
+    bf_parser.set_defaults(func=to_bf_command)
+
+    args = parser.parse_args()
+    try:
+        args.func(args)
+    except ProgramError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,279 @@
+"""
+# Crossword Filling
+
+Fill a crossword grid with words: every run of two or more white cells,
+across or down (a slot), must be a word from the list, no word twice,
+and where two slots cross they share the letter.
+
+
+## Filling
+
+Each slot keeps the words that could still go in it. Arc consistency
+runs after every choice: where slot A crosses slot B, a word of A is
+only kept if some word left in B has the same letter there. It works
+through the set of letters B can still put in the crossing, rather than
+pairs of words, since a slot can start with thousands of them (which is
+also why this does not use the pairwise AC-3 of `csp/csp.py`). A slot
+down to one word, chosen or not, takes it out of every other slot.
+
+The search then picks a slot and tries its words in turn, undoing the
+choice when propagation empties some slot. The order of the slots is
+
+    mrv         the fewest words left, ties to the most crossings (the default)
+    longest     the longest slot first, then the most crossings
+    static      across then down, in the order they are numbered
+
+The words are tried in the order of the list, or shuffled with --seed,
+for a different fill each time.
+
+
+## The Format
+
+A template is rows of cells: # for a black square, . for an empty white
+one, and a letter for one already filled in. A word list has one word a
+line; only letters count, and case is ignored. The default list is the
+one in `wordle/`.
+
+
+## Usage
+
+    python3 crossword.py templates/five.txt
+    python3 crossword.py templates/seven.txt --seed 3
+    python3 crossword.py templates/nine.txt --order longest --stats
+    python3 crossword.py templates/filled.txt --words ../hangman/words.txt
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from rng.rng import add_seed_argument, make_rng
+from search.search import Progress
+
+
+ORDERS: List[str] = ["mrv", "longest", "static"]
+DEFAULT_WORDS: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "..", "wordle", "words.txt")
+BLOCK, EMPTY = "#", "."
+
+
+class Slot(NamedTuple):
+    number: int
+    direction: str      # "across" or "down"
+    cells: Tuple[Position, ...]
+
+    def __str__(self) -> str:
+        return f"{self.number} {self.direction}"
+
+
+class Crossing(NamedTuple):
+    other: int          # the index of the slot crossed
+    here: int           # the letter of this slot that is shared
+    there: int          # and of the other
+
+
+class Template:
+    def __init__(self, grid: Grid):
+        self.grid = grid
+        self.slots: List[Slot] = []
+        self.numbers: Dict[Position, int] = {}
+        for position in grid.positions():
+            if grid[position] == BLOCK:
+                continue
+            for direction, (dx, dy) in (("across", (1, 0)), ("down", (0, 1))):
+                x, y = position
+                if grid.get((x - dx, y - dy), BLOCK) != BLOCK:
+                    continue
+                cells = []
+                while grid.get((x, y), BLOCK) != BLOCK:
+                    cells.append((x, y))
+                    x, y = x + dx, y + dy
+                if len(cells) >= 2:
+                    number = self.numbers.setdefault(position, len(self.numbers) + 1)
+                    self.slots.append(Slot(number, direction, tuple(cells)))
+        if not self.slots:
+            raise ValueError("the template has no slots of two or more cells")
+        self.crossings: List[List[Crossing]] = [[] for _ in self.slots]
+        where: Dict[Position, List[Tuple[int, int]]] = {}
+        for index, slot in enumerate(self.slots):
+            for letter, cell in enumerate(slot.cells):
+                where.setdefault(cell, []).append((index, letter))
+        for places in where.values():
+            if len(places) == 2:
+                (a, i), (b, j) = places
+                self.crossings[a].append(Crossing(b, i, j))
+                self.crossings[b].append(Crossing(a, j, i))
+
+    def pattern(self, slot: Slot) -> str:
+        return "".join(self.grid[cell] for cell in slot.cells)
+
+
+class Statistics:
+    def __init__(self):
+        self.choices = 0
+        self.backtracks = 0
+
+    def __str__(self) -> str:
+        return f"{self.choices} words tried, {self.backtracks} backtracks"
+
+
+Domains = List[List[str]]
+
+
+class Filler:
+    def __init__(self, template: Template, words: List[str], order: str = "mrv",
+                 rng: Optional[random.Random] = None, progress: Optional[Progress] = None):
+        self.template = template
+        self.order = order
+        self.progress = progress
+        self.statistics = Statistics()
+        by_length: Dict[int, List[str]] = {}
+        for word in words:
+            by_length.setdefault(len(word), []).append(word)
+        self.domains: Domains = []
+        for slot in template.slots:
+            pattern = template.pattern(slot)
+            fitting = [word for word in by_length.get(len(pattern), [])
+                       if all(p == EMPTY or p == c for p, c in zip(pattern, word))]
+            if rng is not None:
+                rng.shuffle(fitting)
+            self.domains.append(fitting)
+        self.same_length = [[other for other, them in enumerate(template.slots)
+                             if other != index and len(them.cells) == len(slot.cells)]
+                            for index, slot in enumerate(template.slots)]
+
+    def propagate(self, domains: Domains, changed: List[int]) -> bool:
+        """
+        Arc consistency over the crossings, in place, and a slot down to one
+        word takes it from the others; False if some slot has no words left.
+        """
+        queue = list(changed)
+        queued = set(queue)
+
+        def narrow(index: int, kept: List[str]) -> bool:
+            if not kept:
+                return False
+            domains[index] = kept
+            if index not in queued:
+                queued.add(index)
+                queue.append(index)
+            return True
+
+        while queue:
+            index = queue.pop()
+            queued.discard(index)
+            if len(domains[index]) == 1:
+                word = domains[index][0]
+                for other in self.same_length[index]:
+                    if word in domains[other] and not narrow(other, [w for w in domains[other] if w != word]):
+                        return False
+            for crossing in self.template.crossings[index]:
+                letters = {word[crossing.here] for word in domains[index]}
+                before = domains[crossing.other]
+                kept = [word for word in before if word[crossing.there] in letters]
+                if len(kept) != len(before) and not narrow(crossing.other, kept):
+                    return False
+        return True
+
+    def choose_slot(self, domains: Domains, open_slots: List[int]) -> int:
+        crossings = self.template.crossings
+        if self.order == "mrv":
+            return min(open_slots, key=lambda i: (len(domains[i]), -len(crossings[i])))
+        if self.order == "longest":
+            return min(open_slots, key=lambda i: (-len(self.template.slots[i].cells), -len(crossings[i])))
+        return open_slots[0]
+
+    def fill(self) -> Optional[List[str]]:
+        """A word for each slot, or None if there is no way to fill the grid."""
+        domains = [list(domain) for domain in self.domains]
+        if not all(domains) or not self.propagate(domains, list(range(len(domains)))):
+            return None
+        return self.search(domains)
+
+    def search(self, domains: Domains) -> Optional[List[str]]:
+        open_slots = [i for i in range(len(domains)) if len(domains[i]) > 1]
+        if self.progress is not None:
+            self.progress.update(words=self.statistics.choices, open=len(open_slots))
+        if not open_slots:
+            return [domain[0] for domain in domains]
+        index = self.choose_slot(domains, open_slots)
+        for word in domains[index]:
+            self.statistics.choices += 1
+            trial = list(domains)   # propagation replaces a domain rather than changing it
+            trial[index] = [word]
+            if self.propagate(trial, [index]):
+                found = self.search(trial)
+                if found is not None:
+                    return found
+            self.statistics.backtracks += 1
+        return None
+
+
+def read_words(path: str) -> List[str]:
+    words = []
+    seen = set()
+    with open(path) as f:
+        for line in f:
+            word = line.strip().upper()
+            if len(word) >= 2 and word.isalpha() and word.isascii() and word not in seen:
+                seen.add(word)
+                words.append(word)
+    return words
+
+
+def parse(text: str) -> Template:
+    grid = Grid.parse(text, lambda c: c.upper())
+    for position, cell in grid.items():
+        if cell not in (BLOCK, EMPTY) and not (cell.isalpha() and cell.isascii()):
+            raise ValueError(f"row {position[1] + 1}, column {position[0] + 1}: a cell is #, ., or a letter, "
+                             f"not {cell!r}")
+    return Template(grid)
+
+
+def render(template: Template, words: List[str]) -> str:
+    grid = template.grid.copy()
+    for slot, word in zip(template.slots, words):
+        for cell, letter in zip(slot.cells, word):
+            grid[cell] = letter
+    lines = [grid.render(lambda c: "█" if c == BLOCK else c, separator=" ")]
+    for direction in ("across", "down"):
+        lines.append(f"\n{direction.capitalize()}")
+        lines += [f"{slot.number:>3}. {word}" for slot, word in zip(template.slots, words)
+                  if slot.direction == direction]
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Fill a crossword grid from a word list")
+    parser.add_argument("template", type=str, help="the grid: # for black, . for empty, or letters")
+    parser.add_argument("--words", type=str, default=DEFAULT_WORDS, help="the word list, one word a line")
+    parser.add_argument("--order", choices=ORDERS, default="mrv", help="which slot to fill next")
+    add_seed_argument(parser, help="shuffle the words, for a different fill")
+    parser.add_argument("--stats", action="store_true", help="print the words tried and the backtracks")
+    parser.add_argument("--quiet", action="store_true", help="do not report the progress of the search")
+    args = parser.parse_args()
+
+    try:
+        with open(args.template) as f:
+            template = parse(f.read())
+        words = read_words(args.words)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    rng = make_rng(args.seed) if args.seed is not None else None
+    filler = Filler(template, words, args.order, rng, None if args.quiet else Progress(label="fill"))
+    found = filler.fill()
+    if found is None:
+        print("No fill: the word list cannot fill this grid")
+    else:
+        print(render(template, found))
+    if args.stats:
+        print(filler.statistics)
+    if found is None:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,185 @@
+"""
+# Cryptarithms
+
+Also alphametics: an equation in words, each letter standing for a
+different digit, with no word starting with 0. Find the digits.
+
+    SEND + MORE = MONEY         9567 + 1085 = 10652
+
+Either side can add, subtract and multiply any number of words (and
+plain numbers), with the usual precedence and parentheses: the
+equation's sides are read by the shared expression module
+(`expression/expression.py`), with the words as its variables. − and ×
+may be written as - and *.
+
+
+## Solving
+
+By the shared CSP solver, with a variable for every letter, whose
+domain is the digits, and all-different among the letters. The
+equation itself is one constraint on every letter, but it can only be
+checked at the end, so it is also checked a column at a time: if the
+equation holds, it holds modulo 10, 100, 1000, ..., and modulo 10^k it
+only depends on the last k letters of each word, since +, - and × never
+carry a digit to the right. So there is a constraint for each k on the
+letters of the last k columns, which the solver checks as soon as all
+but one of them are known. The letters in the most of these are the
+units column's, so the solver's choice of variable (fewest values left,
+then most constraints) starts there and works left.
+
+No word starts with 0, but that is checked in the column constraints
+rather than taken out of the first letters' domains: with one value
+fewer, they would be chosen first, and the search would start at the
+wrong end.
+
+The search keeps going after the first solution, to tell whether it is
+the only one; `--all` lists every solution.
+
+
+## Usage
+
+    python3 cryptarithm.py "SEND + MORE = MONEY"
+    python3 cryptarithm.py "TWO + TWO = FOUR" --all
+    python3 cryptarithm.py "COUNT − COIN = SNUB" --stats
+    python3 cryptarithm.py --file puzzles/classics.txt
+"""
+
+import argparse
+import os
+import re
+import sys
+from typing import Dict, Iterator, List, NamedTuple, Optional
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.csp import CSP, Statistics, solutions
+from expression.expression import Binary, Name, Node, Number, Unary, evaluate, parse as parse_expression
+
+
+OPERATORS: Dict[str, str] = {"−": "-", "×": "*", "·": "*"}
+
+
+class Puzzle(NamedTuple):
+    text: str
+    left: Node
+    right: Node
+    words: List[str]
+    letters: List[str]
+
+
+def names(node: Node) -> Iterator[str]:
+    """The words of the expression, checking that it only adds, subtracts and multiplies."""
+    if isinstance(node, Name):
+        yield node.name
+    elif isinstance(node, Unary):
+        yield from names(node.operand)
+    elif isinstance(node, Binary):
+        if node.operator not in "+-*":
+            raise ValueError(f"only +, - and * are allowed, not {node.operator}")
+        yield from names(node.left)
+        yield from names(node.right)
+    elif isinstance(node, Number) and node.value.denominator != 1:
+        raise ValueError("only whole numbers are allowed")
+
+
+def parse(text: str) -> Puzzle:
+    normalized = text.upper()
+    for symbol, operator in OPERATORS.items():
+        normalized = normalized.replace(symbol, operator)
+    if normalized.count("=") != 1:
+        raise ValueError("expected one '=', between the two sides")
+    sides = []
+    for side in normalized.split("="):
+        try:
+            sides.append(parse_expression(side))
+        except ValueError as e:
+            raise ValueError(f"{side.strip()!r}: {e}") from None
+    words = list(dict.fromkeys(word for side in sides for word in names(side)))
+    for word in words:
+        if not re.fullmatch(r"[A-Z]+", word):
+            raise ValueError(f"{word!r} is not a word of letters")
+    letters = list(dict.fromkeys(letter for word in words for letter in reversed(word)))
+    if len(letters) > 10:
+        raise ValueError(f"{len(letters)} letters, but only 10 digits")
+    return Puzzle(text.strip(), sides[0], sides[1], words, letters)
+
+
+def holds(puzzle: Puzzle, digits: Dict[str, int], columns: Optional[int] = None) -> bool:
+    """Whether the equation holds, or only modulo 10^columns, with the words cut to their last columns."""
+    words = {word: int("".join(str(digits[letter]) for letter in word[-columns if columns else 0:]) or "0")
+             for word in puzzle.words}
+    difference = evaluate(puzzle.left, words) - evaluate(puzzle.right, words)
+    return difference == 0 if columns is None else difference % 10 ** columns == 0
+
+
+def to_csp(puzzle: Puzzle) -> CSP:
+    csp = CSP()
+    for letter in puzzle.letters:
+        csp.add_variable(letter, range(10))
+    csp.all_different(puzzle.letters)
+    leading = {word[0] for word in puzzle.words if len(word) > 1}
+    longest = max((len(word) for word in puzzle.words), default=0)
+    for columns in range(1, longest + 1):
+        letters = list(dict.fromkeys(letter for word in puzzle.words for letter in word[-columns:]))
+        firsts = [i for i, letter in enumerate(letters) if letter in leading]
+
+        def check(*values: int, letters=letters, firsts=firsts, columns=columns) -> bool:
+            return all(values[i] for i in firsts) and \
+                holds(puzzle, dict(zip(letters, values)), columns if columns < longest else None)
+
+        csp.add_constraint(letters, check)
+    return csp
+
+
+def substitute(puzzle: Puzzle, digits: Dict[str, int]) -> str:
+    return "".join(str(digits[c.upper()]) if c.upper() in digits else c for c in puzzle.text)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve cryptarithms, like SEND + MORE = MONEY")
+    parser.add_argument("puzzle", type=str, nargs="?", help="the equation, in words")
+    parser.add_argument("--file", type=str, default=None, help="equations, one per line, instead")
+    parser.add_argument("--all", action="store_true", help="list every solution")
+    parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    args = parser.parse_args()
+
+    if (args.puzzle is None) == (args.file is None):
+        parser.error("give an equation, or --file")
+    try:
+        if args.file is not None:
+            with open(args.file) as f:
+                lines = [line.strip() for line in f if line.strip() and not line.lstrip().startswith("#")]
+        else:
+            lines = [args.puzzle]
+        puzzles = [parse(line) for line in lines]
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    unsolved = 0
+    for puzzle in puzzles:
+        if len(puzzles) > 1:
+            print(f"{puzzle.text}")
+        statistics = Statistics()
+        found = []
+        for digits in solutions(to_csp(puzzle), lcv=False, statistics=statistics):
+            found.append(digits)
+            if args.all:
+                print(substitute(puzzle, digits))
+            elif len(found) == 2:
+                break
+        if not found:
+            print("No solution")
+            unsolved += 1
+        elif args.all:
+            print(f"{len(found)} solution{'s' * (len(found) != 1)}")
+        else:
+            print(substitute(puzzle, found[0]))
+            print("  ".join(f"{letter}={found[0][letter]}" for letter in sorted(puzzle.letters)))
+            print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+        if args.stats:
+            print(statistics)
+    if unsolved:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,310 @@
+"""
+# Cryptograms
+
+A message written with each letter swapped for another, the same one
+every time: a monoalphabetic substitution. The key is the plain letter
+for each letter of the cipher, one of 26! of them, far too many to try,
+but English is lopsided enough that a key can be improved a little at a
+time until the message reads.
+
+    cipher  GSV JFRXP YILDM ULC
+    plain   the quick brown fox
+
+
+## Scoring
+
+A candidate key is scored on what it makes of the message, in two ways:
+
+    quadgrams   the log probability of the message as runs of four
+                letters, each as often as it is in English (the words
+                run together: "the fox" is THEF, HEFO, EFOX), with a
+                floor for the ones never seen
+    dictionary  a bonus for each letter in a word (of 3 letters or more)
+                that is in the word list, when the cryptogram keeps its
+                word breaks
+
+The quadgram counts are in `quadgrams.txt`, counted from the prose of
+the Python standard library's docstrings, which is technical (RETURN is
+common) but English enough. `build` counts them again from any text,
+like a book from Project Gutenberg, for --quadgrams:
+
+    python3 cryptogram.py build book.txt --output book_quadgrams.txt
+
+
+## Solving
+
+Hill climbing: try swapping what each pair of cipher letters stands for,
+keep any swap that scores better, and stop when none does. That finds a
+local best, which is often a near miss with a few letters crossed, so
+the best key so far is shaken by a few random swaps and climbed from
+again, --kicks times, keeping whatever scores better. All that is done
+--restarts times, first from the key that matches the cipher's letters
+to English's by frequency (E, T, A, O, I, N, ...), and then from random
+keys, and the best distinct decryptions are printed with their keys.
+
+The confidence of each is the share of its letters that are in
+dictionary words. A cryptogram written in groups of five letters (every
+group but the last), the usual way of hiding the word breaks, has none
+to look up, so there is only the score, in log10 per quadgram, which is
+about -4.5 for English and -7 for nonsense.
+
+
+## Usage
+
+    python3 cryptogram.py solve puzzles/gettysburg.txt
+    python3 cryptogram.py solve puzzles/two_cities.txt --top 3 --seed 1
+    python3 cryptogram.py solve puzzles/fives.txt --seed 1
+    python3 cryptogram.py encrypt "Attack at dawn, the eastern gate" --seed 1
+"""
+
+import argparse
+import math
+import os
+import random
+import re
+import string
+import sys
+from typing import Dict, List, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+ALPHABET: str = string.ascii_uppercase
+DIRECTORY: str = os.path.dirname(os.path.abspath(__file__))
+DEFAULT_QUADGRAMS: str = os.path.join(DIRECTORY, "quadgrams.txt")
+DEFAULT_WORDS: str = os.path.join(DIRECTORY, "..", "wordle", "interactive_helper", "dictionary.txt")
+ENGLISH_ORDER: str = "ETAOINSHRDLCUMWFGYPBVKJXQZ"
+DICTIONARY_BONUS: float = 1.0     # for each letter in a dictionary word, in log10 units
+SHORTEST_WORD: int = 3
+KICK_SWAPS: int = 3
+Key = List[int]     # the plain letter for each cipher letter, 0 to 25
+
+
+class Quadgrams:
+    def __init__(self, counts: Dict[str, int]):
+        total = sum(counts.values())
+        if not total:
+            raise ValueError("there are no quadgrams")
+        self.floor = math.log10(0.01 / total)
+        self.table: List[float] = [self.floor] * 26 ** 4
+        for quadgram, count in counts.items():
+            self.table[index(quadgram)] = math.log10(count / total)
+
+    @classmethod
+    def load(cls, path: str) -> "Quadgrams":
+        counts: Dict[str, int] = {}
+        with open(path) as f:
+            for number, line in enumerate(f, start=1):
+                words = line.split()
+                if not words or words[0].startswith("#"):
+                    continue
+                if len(words) != 2 or len(words[0]) != 4 or not words[0].isalpha() or not words[1].isdigit():
+                    raise ValueError(f"{path}, line {number}: expected four letters and a count")
+                counts[words[0].upper()] = int(words[1])
+        return cls(counts)
+
+
+def index(quadgram: str) -> int:
+    a, b, c, d = (ALPHABET.index(letter) for letter in quadgram)
+    return ((a * 26 + b) * 26 + c) * 26 + d
+
+
+def count_quadgrams(text: str) -> Dict[str, int]:
+    letters = re.sub("[^A-Z]", "", text.upper())
+    counts: Dict[str, int] = {}
+    for i in range(len(letters) - 3):
+        counts[letters[i:i + 4]] = counts.get(letters[i:i + 4], 0) + 1
+    return counts
+
+
+class Cryptogram:
+    def __init__(self, text: str):
+        self.text = text
+        self.letters: List[int] = [ALPHABET.index(c) for c in text.upper() if c in ALPHABET]
+        if len(self.letters) < 4:
+            raise ValueError("the cryptogram needs at least four letters")
+        words = re.findall("[A-Z]+", text.upper())
+        # In groups of five, there are no word breaks to look up
+        if len(words) >= 3 and all(len(word) == 5 for word in words[:-1]):
+            words = []
+        self.words: List[List[int]] = [[ALPHABET.index(c) for c in word]
+                                       for word in words if len(word) >= SHORTEST_WORD]
+        self.present: List[int] = sorted(set(self.letters))
+
+    def decrypt(self, key: Key) -> str:
+        return "".join(ALPHABET[key[ALPHABET.index(c)]].lower() if c in ALPHABET else c for c in self.text.upper())
+
+
+class Scorer:
+    def __init__(self, quadgrams: Quadgrams, words: Set[str]):
+        self.quadgrams = quadgrams
+        self.words = words
+
+    def quadgram_score(self, cryptogram: Cryptogram, key: Key) -> float:
+        table = self.quadgrams.table
+        plain = [key[c] for c in cryptogram.letters]
+        return sum(table[((a * 26 + b) * 26 + c) * 26 + d]
+                   for a, b, c, d in zip(plain, plain[1:], plain[2:], plain[3:]))
+
+    def in_dictionary(self, cryptogram: Cryptogram, key: Key) -> int:
+        """The letters in the words that are in the dictionary."""
+        found = 0
+        for word in cryptogram.words:
+            if "".join(ALPHABET[key[c]] for c in word) in self.words:
+                found += len(word)
+        return found
+
+    def score(self, cryptogram: Cryptogram, key: Key) -> float:
+        return self.quadgram_score(cryptogram, key) + DICTIONARY_BONUS * self.in_dictionary(cryptogram, key)
+
+
+def read_words(path: str) -> Set[str]:
+    with open(path) as f:
+        return {word.strip().upper() for word in f if word.strip().isalpha()}
+
+
+################################################################################
+###                                 Solving                                  ###
+################################################################################
+
+
+def frequency_key(cryptogram: Cryptogram) -> Key:
+    """The cipher's letters, most common first, matched to English's, and the rest to what is left."""
+    counts = [cryptogram.letters.count(c) for c in range(26)]
+    order = sorted(range(26), key=lambda c: -counts[c])
+    key = [0] * 26
+    for c, plain in zip(order, ENGLISH_ORDER):
+        key[c] = ALPHABET.index(plain)
+    return key
+
+
+def climb(cryptogram: Cryptogram, scorer: Scorer, key: Key) -> Tuple[float, Key]:
+    """Swap what pairs of cipher letters stand for while that scores better."""
+    key = list(key)
+    best = scorer.score(cryptogram, key)
+    present = set(cryptogram.present)
+    improved = True
+    while improved:
+        improved = False
+        for i in cryptogram.present:
+            for j in range(26):
+                if j == i or j in present and j < i:
+                    continue
+                key[i], key[j] = key[j], key[i]
+                score = scorer.score(cryptogram, key)
+                if score > best:
+                    best, improved = score, True
+                else:
+                    key[i], key[j] = key[j], key[i]
+    return best, key
+
+
+def kick(cryptogram: Cryptogram, key: Key, rng: random.Random) -> Key:
+    """The key with a few cipher letters swapped at random, to climb again from."""
+    key = list(key)
+    for _ in range(KICK_SWAPS):
+        i, j = rng.choice(cryptogram.present), rng.randrange(26)
+        key[i], key[j] = key[j], key[i]
+    return key
+
+
+def solve(cryptogram: Cryptogram, scorer: Scorer, restarts: int, kicks: int,
+          rng: random.Random) -> List[Tuple[float, Key]]:
+    """The best key of each restart, best first, one for each distinct decryption."""
+    found: Dict[str, Tuple[float, Key]] = {}
+    for attempt in range(restarts):
+        start = frequency_key(cryptogram) if attempt == 0 else rng.sample(range(26), 26)
+        score, key = climb(cryptogram, scorer, start)
+        for _ in range(kicks):
+            trial_score, trial = climb(cryptogram, scorer, kick(cryptogram, key, rng))
+            if trial_score > score:
+                score, key = trial_score, trial
+        plain = cryptogram.decrypt(key)
+        if plain not in found or score > found[plain][0]:
+            found[plain] = (score, key)
+    return sorted(found.values(), key=lambda item: -item[0])
+
+
+def format_key(cryptogram: Cryptogram, key: Key) -> str:
+    present = set(cryptogram.present)
+    plain = "".join(ALPHABET[key[c]].lower() if c in present else "." for c in range(26))
+    return f"cipher  {ALPHABET}\nplain   {plain}"
+
+
+def encrypt(text: str, rng: random.Random) -> str:
+    """The text in capitals, under a random key that leaves no letter as itself."""
+    while True:
+        key = rng.sample(ALPHABET, 26)
+        if all(plain != cipher for plain, cipher in zip(ALPHABET, key)):
+            break
+    return text.upper().translate(str.maketrans(ALPHABET, "".join(key)))
+
+
+def read_text(text: str) -> str:
+    """The text itself, or a file of it; lines starting with # are comments."""
+    if not os.path.exists(text):
+        return text
+    with open(text) as f:
+        return "".join(line for line in f if not line.startswith("#")).strip()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Crack substitution ciphers by hill climbing")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="crack a cryptogram")
+    solve_parser.add_argument("cryptogram", type=str, help="the cipher text, or a file of it")
+    solve_parser.add_argument("--restarts", type=int, default=5, help="the climbs, from different keys")
+    solve_parser.add_argument("--kicks", type=int, default=20, help="the climbs again from each best key, shaken")
+    solve_parser.add_argument("--top", type=int, default=1, help="the best decryptions to print")
+    add_seed_argument(solve_parser)
+    solve_parser.add_argument("--quadgrams", type=str, default=DEFAULT_QUADGRAMS)
+    solve_parser.add_argument("--words", type=str, default=DEFAULT_WORDS)
+    encrypt_parser = subparsers.add_parser("encrypt", help="make a cryptogram, with a random key")
+    encrypt_parser.add_argument("text", type=str, help="the message, or a file of it")
+    add_seed_argument(encrypt_parser)
+    build_parser = subparsers.add_parser("build", help="count the quadgrams of some English")
+    build_parser.add_argument("files", type=str, nargs="+")
+    build_parser.add_argument("--output", type=str, required=True, help="the file to write, for solve --quadgrams")
+    build_parser.add_argument("--min", type=int, default=2, help="leave out quadgrams seen fewer times")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "build":
+            counts: Dict[str, int] = {}
+            for path in args.files:
+                with open(path) as f:
+                    for quadgram, count in count_quadgrams(f.read()).items():
+                        counts[quadgram] = counts.get(quadgram, 0) + count
+            kept = sorted((item for item in counts.items() if item[1] >= args.min), key=lambda item: -item[1])
+            if not kept:
+                raise ValueError(f"no quadgram is seen {args.min} times in {', '.join(args.files)}, "
+                                 f"so nothing is written")
+            with open(args.output, "w") as f:
+                f.write(f"# Quadgram counts from {', '.join(os.path.basename(path) for path in args.files)}\n")
+                f.writelines(f"{quadgram} {count}\n" for quadgram, count in kept)
+            print(f"{len(kept)} quadgrams written to {args.output}")
+            return
+        if args.command == "encrypt":
+            print(encrypt(read_text(args.text), make_rng(args.seed)))
+            return
+        cryptogram = Cryptogram(read_text(args.cryptogram))
+        scorer = Scorer(Quadgrams.load(args.quadgrams), read_words(args.words))
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    results = solve(cryptogram, scorer, args.restarts, args.kicks, make_rng(args.seed))
+    quadgrams = len(cryptogram.letters) - 3
+    letters = sum(len(word) for word in cryptogram.words)
+    for rank, (score, key) in enumerate(results[:args.top], start=1):
+        if rank > 1:
+            print()
+        confidence = f", {100 * scorer.in_dictionary(cryptogram, key) / letters:.0f}% in the dictionary" \
+            if letters else ""
+        print(f"{rank}. score {scorer.quadgram_score(cryptogram, key) / quadgrams:.2f} a quadgram{confidence}")
+        print(format_key(cryptogram, key))
+        print(cryptogram.decrypt(key))
+
+
+if __name__ == "__main__":
+    main()
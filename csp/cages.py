@@ -0,0 +1,85 @@
+"""
+# Cages
+
+A cage is a group of cells whose values must together meet a rule: add
+up to a total, in Killer Sudoku and Kakuro, or make a target with an
+operation, in KenKen. A constraint on the whole group is only checked by
+`csp.py` once all but one of its cells are filled, which is late, so a
+cage is instead a hidden variable of its own, whose values are the ways
+to fill it:
+
+    sum cages       the sets of different digits that add up to the
+                    total (a cage of 3 adding to 23 is {6, 8, 9}), from
+                    the partition table; each cell's value must be in
+                    the set, and they must all differ
+    table cages     the tuples of values that meet the rule, listed in
+                    full; each cell's value must be its place in the
+                    tuple
+
+Either way the cage is tied to its cells by binary constraints, which
+AC-3 works on throughout the search: a way with no value left for some
+cell is crossed off, and so is a value that no way left has.
+
+    csp = CSP()
+    for cell in cells:
+        csp.add_variable(cell, range(1, 10))
+    add_sum_cage(csp, "a", cells, 23)
+
+
+## Usage
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from csp.cages import add_sum_cage, add_table_cage
+"""
+
+import functools
+import itertools
+from typing import Dict, FrozenSet, Hashable, Iterable, List, Sequence, Set, Tuple
+
+from csp.csp import CSP, Value, Variable
+
+
+DIGITS: range = range(1, 10)
+
+# PARTITIONS[size, total]: the sets of `size` different digits from 1 to 9 that add up to `total`
+PARTITIONS: Dict[Tuple[int, int], List[FrozenSet[int]]] = {}
+for _size in range(1, len(DIGITS) + 1):
+    for _digits in itertools.combinations(DIGITS, _size):
+        PARTITIONS.setdefault((_size, sum(_digits)), []).append(frozenset(_digits))
+
+
+def cage_digits(size: int, total: int) -> Set[int]:
+    """The digits that are in some set of `size` different digits adding up to `total`."""
+    return set().union(*PARTITIONS.get((size, total), []))
+
+
+def _in_set(digits: FrozenSet[int], digit: int) -> bool:
+    return digit in digits
+
+
+def add_sum_cage(csp: CSP, name: Hashable, cells: Sequence[Variable], total: int, different: bool = True):
+    """
+    The cells are different digits adding up to total. With different=False
+    the caller has already made them differ (as the units of a Sudoku do).
+    """
+    if (len(cells), total) not in PARTITIONS:
+        raise ValueError(f"{len(cells)} different digits cannot add up to {total}")
+    csp.add_variable(name, PARTITIONS[len(cells), total])
+    for cell in cells:
+        csp.add_constraint([name, cell], _in_set)
+    if different:
+        csp.all_different(cells)
+
+
+def _at(index: int, row: Tuple[Value, ...], value: Value) -> bool:
+    return row[index] == value
+
+
+def add_table_cage(csp: CSP, name: Hashable, cells: Sequence[Variable], rows: Iterable[Tuple[Value, ...]]):
+    """The cells' values, in order, are one of the rows."""
+    rows = list(rows)
+    if not rows:
+        raise ValueError("a cage with no way to fill it")
+    csp.add_variable(name, rows)
+    for index, cell in enumerate(cells):
+        csp.add_constraint([name, cell], functools.partial(_at, index))
@@ -0,0 +1,241 @@
+"""
+# Constraint Satisfaction
+
+A CSP is a set of variables, each with a domain of possible values, and
+constraints on which combinations of values are allowed. The puzzle
+solvers describe their puzzle as one (a Sudoku is 81 variables with
+domain 1 to 9, and all-different constraints on the rows, columns, and
+boxes), and leave the searching to `solve`.
+
+    csp = CSP()
+    for region in ["WA", "NT", "SA", "Q", "NSW", "V"]:
+        csp.add_variable(region, ["red", "green", "blue"])
+    for a, b in [("WA", "NT"), ("WA", "SA"), ("NT", "SA"), ("NT", "Q"),
+                 ("SA", "Q"), ("SA", "NSW"), ("SA", "V"), ("Q", "NSW"), ("NSW", "V")]:
+        csp.add_constraint([a, b], lambda x, y: x != y)
+    solve(csp)          {'SA': 'red', 'NT': 'green', ...}
+
+
+## The Search
+
+Backtracking, one variable at a time, with
+
+    AC-3    after each assignment (and once at the start), every binary
+            constraint is made arc consistent: a value is removed from a
+            variable's domain if no value of the other variable allows it.
+            A constraint on more variables is checked once all but one of
+            them are assigned, and then filters the last one's domain.
+    MRV     the next variable is the one with the fewest values left (the
+            minimum remaining values), breaking ties by the most
+            constraints on unassigned variables
+    LCV     its values are tried in order of how few values they rule out
+            for its neighbours (the least constraining value first)
+
+Each can be turned off, to compare them, and `Statistics` counts the
+assignments tried.
+
+
+## Usage
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from csp.csp import CSP, solve
+"""
+
+from collections import deque
+from typing import Any, Callable, Dict, Hashable, Iterator, List, NamedTuple, Optional, Sequence, Set, Tuple
+
+
+Variable = Hashable
+Value = Any
+Domains = Dict[Variable, List[Value]]
+Assignment = Dict[Variable, Value]
+
+
+class Constraint(NamedTuple):
+    variables: Tuple[Variable, ...]
+    predicate: Callable[..., bool]  # called with the variables' values, in order
+
+
+class Statistics:
+    def __init__(self):
+        self.assignments = 0
+        self.backtracks = 0
+
+    def __str__(self) -> str:
+        return f"{self.assignments} assignments, {self.backtracks} backtracks"
+
+
+class CSP:
+    def __init__(self):
+        self.domains: Domains = {}
+        self.constraints: List[Constraint] = []
+        self.constraints_on: Dict[Variable, List[Constraint]] = {}
+
+    def add_variable(self, variable: Variable, domain: Sequence[Value]):
+        if variable in self.domains:
+            raise ValueError(f"the variable {variable!r} already exists")
+        self.domains[variable] = list(domain)
+        self.constraints_on[variable] = []
+
+    def add_constraint(self, variables: Sequence[Variable], predicate: Callable[..., bool]):
+        for variable in variables:
+            if variable not in self.domains:
+                raise ValueError(f"there is no variable {variable!r}")
+        constraint = Constraint(tuple(variables), predicate)
+        self.constraints.append(constraint)
+        for variable in set(variables):
+            self.constraints_on[variable].append(constraint)
+
+    def all_different(self, variables: Sequence[Variable]):
+        """As pairwise constraints, so that AC-3 can propagate them."""
+        for i, a in enumerate(variables):
+            for b in variables[i + 1:]:
+                self.add_constraint([a, b], _different)
+
+    def neighbours(self, variable: Variable) -> Set[Variable]:
+        return {other for constraint in self.constraints_on[variable] for other in constraint.variables} - {variable}
+
+    def consistent(self, assignment: Assignment) -> bool:
+        """Whether every constraint whose variables are all assigned holds."""
+        for constraint in self.constraints:
+            if all(variable in assignment for variable in constraint.variables):
+                if not constraint.predicate(*(assignment[variable] for variable in constraint.variables)):
+                    return False
+        return True
+
+
+def _different(a: Value, b: Value) -> bool:
+    return a != b
+
+
+def _revise(domains: Domains, x: Variable, y: Variable, constraint: Constraint) -> bool:
+    """Remove the values of x that no value of y allows. Return whether any were removed."""
+    first = constraint.variables[0] == x
+    kept = [
+        value for value in domains[x]
+        if any(constraint.predicate(value, other) if first else constraint.predicate(other, value)
+               for other in domains[y])
+    ]
+    if len(kept) == len(domains[x]):
+        return False
+    domains[x] = kept
+    return True
+
+
+def _filter_last(domains: Domains, constraint: Constraint) -> Optional[Variable]:
+    """
+    If all but one variable of the constraint have one value, remove the last
+    one's values that break it. Return the variable if its domain changed.
+    """
+    open_variables = [variable for variable in set(constraint.variables) if len(domains[variable]) > 1]
+    if len(open_variables) > 1:
+        return None
+    if not open_variables:
+        values = [domains[variable][0] for variable in constraint.variables]
+        if not constraint.predicate(*values):
+            domains[constraint.variables[0]] = []
+            return constraint.variables[0]
+        return None
+    last = open_variables[0]
+    kept = []
+    for value in domains[last]:
+        values = [value if variable == last else domains[variable][0] for variable in constraint.variables]
+        if constraint.predicate(*values):
+            kept.append(value)
+    if len(kept) == len(domains[last]):
+        return None
+    domains[last] = kept
+    return last
+
+
+def ac3(csp: CSP, domains: Domains, changed: Optional[Sequence[Variable]] = None) -> bool:
+    """
+    Make the domains arc consistent, in place, starting from the constraints
+    on the changed variables (or all of them). Return False if a domain
+    becomes empty, which means there is no solution from here.
+    """
+    constraints = csp.constraints if changed is None else [c for v in changed for c in csp.constraints_on[v]]
+    queue = deque(constraints)
+    queued = set(map(id, constraints))
+    while queue:
+        constraint = queue.popleft()
+        queued.discard(id(constraint))
+        if len(constraint.variables) == 2 and constraint.variables[0] != constraint.variables[1]:
+            x, y = constraint.variables
+            revised = [v for v, w in ((x, y), (y, x)) if _revise(domains, v, w, constraint)]
+        else:
+            revised = [v for v in [_filter_last(domains, constraint)] if v is not None]
+        for variable in revised:
+            if not domains[variable]:
+                return False
+            for other in csp.constraints_on[variable]:
+                if other is not constraint and id(other) not in queued:
+                    queue.append(other)
+                    queued.add(id(other))
+    return True
+
+
+def _select_variable(csp: CSP, domains: Domains, unassigned: List[Variable], mrv: bool) -> Variable:
+    if not mrv:
+        return unassigned[0]
+
+    def degree(variable: Variable) -> int:
+        return sum(1 for other in csp.neighbours(variable) if len(domains[other]) > 1)
+
+    return min(unassigned, key=lambda variable: (len(domains[variable]), -degree(variable)))
+
+
+def _order_values(csp: CSP, domains: Domains, variable: Variable, lcv: bool) -> List[Value]:
+    if not lcv or len(domains[variable]) == 1:
+        return list(domains[variable])
+
+    def ruled_out(value: Value) -> int:
+        trial = dict(domains)
+        trial[variable] = [value]
+        count = 0
+        for constraint in csp.constraints_on[variable]:
+            if len(constraint.variables) == 2:
+                other = constraint.variables[1] if constraint.variables[0] == variable else constraint.variables[0]
+                before = len(trial[other])
+                _revise(trial, other, variable, constraint)
+                count += before - len(trial[other])
+        return count
+
+    return sorted(domains[variable], key=ruled_out)
+
+
+def solutions(csp: CSP, mrv: bool = True, lcv: bool = True, propagate: bool = True,
+              statistics: Optional[Statistics] = None, domains: Optional[Domains] = None) -> Iterator[Assignment]:
+    """Every solution, one at a time (searching from `domains`, if given, instead of the CSP's own)."""
+    statistics = statistics if statistics is not None else Statistics()
+    domains = {variable: list(domain) for variable, domain in (domains or csp.domains).items()}
+    if propagate and not ac3(csp, domains):
+        return
+    yield from _backtrack(csp, domains, {}, mrv, lcv, propagate, statistics)
+
+
+def _backtrack(csp: CSP, domains: Domains, assignment: Assignment, mrv: bool, lcv: bool, propagate: bool,
+               statistics: Statistics) -> Iterator[Assignment]:
+    unassigned = [variable for variable in domains if variable not in assignment]
+    if not unassigned:
+        yield dict(assignment)
+        return
+    variable = _select_variable(csp, domains, unassigned, mrv)
+    for value in _order_values(csp, domains, variable, lcv):
+        statistics.assignments += 1
+        assignment[variable] = value
+        if propagate:
+            trial = {v: list(domain) for v, domain in domains.items()}
+            trial[variable] = [value]
+            if ac3(csp, trial, [variable]):
+                yield from _backtrack(csp, trial, assignment, mrv, lcv, propagate, statistics)
+        elif csp.consistent(assignment):
+            yield from _backtrack(csp, domains, assignment, mrv, lcv, propagate, statistics)
+        del assignment[variable]
+        statistics.backtracks += 1
+
+
+def solve(csp: CSP, mrv: bool = True, lcv: bool = True, propagate: bool = True,
+          statistics: Optional[Statistics] = None) -> Optional[Assignment]:
+    """The first solution, or None if there is none."""
+    return next(solutions(csp, mrv, lcv, propagate, statistics), None)
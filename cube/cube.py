@@ -0,0 +1,616 @@
+"""
+# Rubik's Cube
+
+A 3x3x3 cube, its move notation, scrambles, and Kociemba's two-phase
+solver. A move is a face (U, R, F, D, L, B: up, right, front, down,
+left, back) and how far it turns: R a quarter turn clockwise, looking at
+that face, R' anticlockwise, and R2 a half turn.
+
+The cube is kept as its pieces (cubies): where each of the 8 corners and
+12 edges is, and how it is twisted or flipped there. A move is itself a
+cube (the solved cube with that move made), and making a move multiplies
+by it. The 54 stickers (facelets), which are what a person sees, are
+only worked out to draw the cube or read one in.
+
+
+## The Two Phases
+
+    phase 1     reach the subgroup G1 = <U, D, R2, L2, F2, B2>: every
+                corner twisted correctly, every edge flipped correctly,
+                and the four middle-layer edges somewhere in the middle
+                layer. The state is three numbers (coordinates): the
+                twist (3^7), the flip (2^11), and which four places the
+                middle edges are in (12 choose 4).
+    phase 2     solve it with those moves only, which keep all of that
+                true. The state is the order of the corners (8!), of the
+                other eight edges (8!), and of the middle edges (4!).
+
+Each phase is IDA*, and its heuristic is the larger of two pruning
+tables: for each pair of coordinates, the fewest moves to set both
+right, found by a breadth-first search out from solved. A short phase 1
+can leave a long phase 2, so the search does not stop at the first
+solution: it keeps finding longer phase 1s, each with the shortest phase
+2 after it, until the total is at most `--max-length` moves or
+`--seconds` have passed, and then gives the shortest it found.
+
+The move and pruning tables take about twenty seconds to build, the
+first time, and are saved in `tables/` (which git ignores) for later runs.
+
+
+## Checking
+
+A cube read from its facelets is checked before it is solved: each
+colour has nine stickers, each corner and edge is one that exists, and
+it could be solved at all (the twists add up to a multiple of 3, the
+flips to a multiple of 2, and the corners and edges are permuted with
+the same parity). `verify` makes the moves of a solution and says
+whether the cube ends up solved, and `solve` does so before printing.
+
+
+## Usage
+
+    python3 cube.py solve "R U R' U' R' F R2 U' R' U' R U R' F'"
+    python3 cube.py solve --facelets DDUBUFRRLFULURDLLRUFDUFRBFULDBBDDBUUFRBLLBRLDFBRRBFFLD
+    python3 cube.py scramble --seed 1
+    python3 cube.py scramble --moves 25 --seed 1 | python3 cube.py solve - --show
+    python3 cube.py show "R U R' U'"
+    python3 cube.py verify "R U R' U'" "U R U' R'"
+    python3 cube.py build-tables
+"""
+
+import argparse
+import itertools
+import os
+import random
+import sys
+import time
+from array import array
+from typing import Dict, List, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+FACES: str = "URFDLB"
+TABLE_DIRECTORY: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "tables")
+
+# The pieces, in Kociemba's order; a corner is named by its faces clockwise from its U or D one
+URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB = range(8)
+UR, UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR = range(12)
+
+# The facelets of each piece's place, in the same order as its name, as indices into the 54
+# facelets (face by face in the order of FACES, each row by row as the net below shows them)
+CORNER_FACELETS: List[Tuple[int, int, int]] = [
+    (8, 9, 20), (6, 18, 38), (0, 36, 47), (2, 45, 11), (29, 26, 15), (27, 44, 24), (33, 53, 42), (35, 17, 51),
+]
+EDGE_FACELETS: List[Tuple[int, int]] = [
+    (5, 10), (7, 19), (3, 37), (1, 46), (32, 16), (28, 25), (30, 43), (34, 52),
+    (23, 12), (21, 41), (50, 39), (48, 14),
+]
+CORNER_COLOURS: List[str] = ["URF", "UFL", "ULB", "UBR", "DFR", "DLF", "DBL", "DRB"]
+EDGE_COLOURS: List[str] = ["UR", "UF", "UL", "UB", "DR", "DF", "DL", "DB", "FR", "FL", "BL", "BR"]
+
+
+class Cube:
+    """
+    cp[i] is the corner in place i, and co[i] its twist there (0 to 2, how
+    far clockwise its U or D sticker has turned from the U or D face); ep and
+    eo likewise for the edges, with eo[i] 1 if the edge is flipped.
+    """
+
+    def __init__(self, cp: Sequence[int] = range(8), co: Sequence[int] = (0,) * 8,
+                 ep: Sequence[int] = range(12), eo: Sequence[int] = (0,) * 12):
+        self.cp, self.co = list(cp), list(co)
+        self.ep, self.eo = list(ep), list(eo)
+
+    def __eq__(self, other: object) -> bool:
+        return (isinstance(other, Cube) and self.cp == other.cp and self.co == other.co
+                and self.ep == other.ep and self.eo == other.eo)
+
+    def copy(self) -> "Cube":
+        return Cube(self.cp, self.co, self.ep, self.eo)
+
+    def __mul__(self, other: "Cube") -> "Cube":
+        """This cube, then the moves that made `other` from solved."""
+        return Cube([self.cp[other.cp[i]] for i in range(8)],
+                    [(self.co[other.cp[i]] + other.co[i]) % 3 for i in range(8)],
+                    [self.ep[other.ep[i]] for i in range(12)],
+                    [(self.eo[other.ep[i]] + other.eo[i]) % 2 for i in range(12)])
+
+    def inverse(self) -> "Cube":
+        cp, co, ep, eo = [0] * 8, [0] * 8, [0] * 12, [0] * 12
+        for i in range(8):
+            cp[self.cp[i]] = i
+        for i in range(8):
+            co[i] = -self.co[cp[i]] % 3
+        for i in range(12):
+            ep[self.ep[i]] = i
+        for i in range(12):
+            eo[i] = self.eo[ep[i]]
+        return Cube(cp, co, ep, eo)
+
+    def apply(self, moves: Sequence[int]) -> "Cube":
+        cube = self
+        for move in moves:
+            cube = cube * MOVE_CUBES[move]
+        return cube
+
+    def is_solved(self) -> bool:
+        return self == Cube()
+
+    # The coordinates
+
+    def twist(self) -> int:
+        value = 0
+        for twist in self.co[:7]:
+            value = 3 * value + twist
+        return value
+
+    def flip(self) -> int:
+        value = 0
+        for flip in self.eo[:11]:
+            value = 2 * value + flip
+        return value
+
+    def slice(self) -> int:
+        return SLICE_INDEX[tuple(i for i in range(12) if self.ep[i] >= FR)]
+
+    def corners(self) -> int:
+        return PERMUTATION_INDEX[tuple(self.cp)]
+
+    def edges(self) -> int:
+        """Only in G1, where the first eight edges are in the first eight places."""
+        return PERMUTATION_INDEX[tuple(self.ep[:8])]
+
+    def slice_order(self) -> int:
+        """Only in G1, where the middle edges are in the middle layer."""
+        return SLICE_ORDER_INDEX[tuple(edge - FR for edge in self.ep[8:])]
+
+    # The facelets
+
+    def facelets(self) -> str:
+        facelets = [FACES[i // 9] for i in range(54)]   # the centres; the rest are overwritten
+        for i in range(8):
+            for n in range(3):
+                facelets[CORNER_FACELETS[i][(n + self.co[i]) % 3]] = CORNER_COLOURS[self.cp[i]][n]
+        for i in range(12):
+            for n in range(2):
+                facelets[EDGE_FACELETS[i][(n + self.eo[i]) % 2]] = EDGE_COLOURS[self.ep[i]][n]
+        return "".join(facelets)
+
+    @classmethod
+    def from_facelets(cls, facelets: str) -> "Cube":
+        """From the 54 stickers as `facelets` gives them, after checking that they are a cube that can be solved."""
+        facelets = "".join(facelets.split()).upper()
+        if len(facelets) != 54:
+            raise ValueError(f"a cube has 54 facelets, not {len(facelets)}")
+        for face in FACES:
+            if facelets.count(face) != 9:
+                raise ValueError(f"there should be 9 {face} facelets, not {facelets.count(face)}")
+        if any(facelets[9 * i + 4] != face for i, face in enumerate(FACES)):
+            raise ValueError(f"the centres must be {', '.join(FACES)}, in that order")
+        cube = cls([-1] * 8, [0] * 8, [-1] * 12, [0] * 12)
+        for i, places in enumerate(CORNER_FACELETS):
+            colours = [facelets[place] for place in places]
+            twist = next((n for n in range(3) if colours[n] in "UD"), None)
+            turned = None if twist is None else "".join(colours[(twist + n) % 3] for n in range(3))
+            if turned not in CORNER_COLOURS:
+                raise ValueError(f"there is no corner {''.join(colours)}")
+            cube.cp[i], cube.co[i] = CORNER_COLOURS.index(turned), twist
+        for i, places in enumerate(EDGE_FACELETS):
+            colours = "".join(facelets[place] for place in places)
+            for flip in range(2):
+                turned = colours[flip:] + colours[:flip]
+                if turned in EDGE_COLOURS:
+                    cube.ep[i], cube.eo[i] = EDGE_COLOURS.index(turned), flip
+                    break
+            else:
+                raise ValueError(f"there is no edge {colours}")
+        cube.check()
+        return cube
+
+    def check(self):
+        """That this is a cube that can be solved, or a ValueError saying why not."""
+        if sorted(self.cp) != list(range(8)):
+            raise ValueError("some corner is on the cube twice, and so another is missing")
+        if sorted(self.ep) != list(range(12)):
+            raise ValueError("some edge is on the cube twice, and so another is missing")
+        if sum(self.co) % 3:
+            raise ValueError("a corner is twisted in place")
+        if sum(self.eo) % 2:
+            raise ValueError("an edge is flipped in place")
+        if parity(self.cp) != parity(self.ep):
+            raise ValueError("two pieces are swapped")
+
+
+def parity(permutation: Sequence[int]) -> int:
+    return sum(1 for i, a in enumerate(permutation) for b in permutation[i + 1:] if a > b) % 2
+
+
+def _combinations(n: int, k: int) -> Dict[Tuple[int, ...], int]:
+    # From the places at the end, so that the middle edges where they belong are 0
+    return {tuple(sorted(places)): i for i, places in enumerate(itertools.combinations(range(n - 1, -1, -1), k))}
+
+
+SLICE_INDEX: Dict[Tuple[int, ...], int] = _combinations(12, 4)
+SLICES: List[Tuple[int, ...]] = list(SLICE_INDEX)
+PERMUTATIONS: List[Tuple[int, ...]] = list(itertools.permutations(range(8)))
+PERMUTATION_INDEX: Dict[Tuple[int, ...], int] = {p: i for i, p in enumerate(PERMUTATIONS)}
+SLICE_ORDERS: List[Tuple[int, ...]] = list(itertools.permutations(range(4)))
+SLICE_ORDER_INDEX: Dict[Tuple[int, ...], int] = {p: i for i, p in enumerate(SLICE_ORDERS)}
+TWISTS, FLIPS = 3 ** 7, 2 ** 11
+
+
+################################################################################
+###                                  Moves                                   ###
+################################################################################
+
+
+# A quarter turn of each face, clockwise, as where each piece comes from
+_QUARTER_TURNS: List[Cube] = [
+    Cube([UBR, URF, UFL, ULB, DFR, DLF, DBL, DRB], [0] * 8,
+         [UB, UR, UF, UL, DR, DF, DL, DB, FR, FL, BL, BR], [0] * 12),
+    Cube([DFR, UFL, ULB, URF, DRB, DLF, DBL, UBR], [2, 0, 0, 1, 1, 0, 0, 2],
+         [FR, UF, UL, UB, BR, DF, DL, DB, DR, FL, BL, UR], [0] * 12),
+    Cube([UFL, DLF, ULB, UBR, URF, DFR, DBL, DRB], [1, 2, 0, 0, 2, 1, 0, 0],
+         [UR, FL, UL, UB, DR, FR, DL, DB, UF, DF, BL, BR], [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0]),
+    Cube([URF, UFL, ULB, UBR, DLF, DBL, DRB, DFR], [0] * 8,
+         [UR, UF, UL, UB, DF, DL, DB, DR, FR, FL, BL, BR], [0] * 12),
+    Cube([URF, ULB, DBL, UBR, DFR, UFL, DLF, DRB], [0, 1, 2, 0, 0, 2, 1, 0],
+         [UR, UF, BL, UB, DR, DF, FL, DB, FR, UL, DL, BR], [0] * 12),
+    Cube([URF, UFL, UBR, DRB, DFR, DLF, ULB, DBL], [0, 0, 1, 2, 0, 0, 2, 1],
+         [UR, UF, UL, BR, DR, DF, DL, BL, FR, FL, UB, DB], [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1]),
+]
+
+# Move m turns face m // 3, (m % 3 + 1) quarter turns: U U2 U' R R2 R' ...
+MOVE_NAMES: List[str] = [face + suffix for face in FACES for suffix in ("", "2", "'")]
+MOVE_CUBES: List[Cube] = []
+for _turn in _QUARTER_TURNS:
+    MOVE_CUBES += [_turn, _turn * _turn, _turn * _turn * _turn]
+# G1's moves: the U and D turns, and half turns of the rest
+PHASE_2_MOVES: List[int] = [0, 1, 2, 9, 10, 11, 4, 7, 13, 16]
+
+
+def parse_moves(text: str) -> List[int]:
+    """Moves like R U2 F' (spaces optional, and R2' is the same as R2)."""
+    moves = []
+    i = 0
+    text = text.replace("’", "'")
+    while i < len(text):
+        if text[i].isspace() or text[i] in ",()":
+            i += 1
+            continue
+        face = FACES.find(text[i])
+        if face < 0:
+            raise ValueError(f"'{text[i]}' at {i + 1} is not a face: expected one of {', '.join(FACES)}")
+        i += 1
+        turns = 1
+        if i < len(text) and text[i] == "2":
+            turns, i = 2, i + 1
+        if i < len(text) and text[i] == "'":
+            turns, i = (turns * 3) % 4, i + 1
+        moves.append(3 * face + turns - 1)
+    return moves
+
+
+def format_moves(moves: Sequence[int]) -> str:
+    return " ".join(MOVE_NAMES[move] for move in moves)
+
+
+def invert_moves(moves: Sequence[int]) -> List[int]:
+    return [3 * (move // 3) + 2 - move % 3 for move in reversed(moves)]
+
+
+def render(cube: Cube) -> str:
+    """The net: U above, then L F R B, then D below."""
+    facelets = cube.facelets()
+
+    def row(face: str, r: int) -> str:
+        start = 9 * FACES.index(face) + 3 * r
+        return " ".join(facelets[start:start + 3])
+
+    lines = [" " * 8 + row("U", r) for r in range(3)]
+    lines += ["  ".join(row(face, r) for face in "LFRB") for r in range(3)]
+    lines += [" " * 8 + row("D", r) for r in range(3)]
+    return "\n".join(lines)
+
+
+def scramble_moves(count: int, rng: random.Random) -> List[int]:
+    """Random moves, never turning the same face twice in a row, nor U D U and the like."""
+    moves: List[int] = []
+    while len(moves) < count:
+        move = rng.randrange(18)
+        face = move // 3
+        if moves and moves[-1] // 3 == face:
+            continue
+        if len(moves) >= 2 and moves[-1] // 3 % 3 == face % 3 and moves[-2] // 3 == face:
+            continue
+        moves.append(move)
+    return moves
+
+
+def random_cube(rng: random.Random) -> Cube:
+    """Every cube that can be solved is equally likely."""
+    cp, ep = list(range(8)), list(range(12))
+    rng.shuffle(cp)
+    rng.shuffle(ep)
+    if parity(cp) != parity(ep):
+        ep[0], ep[1] = ep[1], ep[0]
+    co = [rng.randrange(3) for _ in range(7)]
+    eo = [rng.randrange(2) for _ in range(11)]
+    return Cube(cp, co + [-sum(co) % 3], ep, eo + [sum(eo) % 2])
+
+
+################################################################################
+###                                  Tables                                  ###
+################################################################################
+
+
+class Tables:
+    """
+    The move tables (for each value of a coordinate and each move, its value
+    after the move), and the pruning tables, which are indexed by one
+    coordinate times the size of the other, plus the other.
+    """
+
+    def __init__(self, progress: bool = True):
+        self.progress = progress
+        os.makedirs(TABLE_DIRECTORY, exist_ok=True)
+        self.twist_moves = self.load("twist_moves", "H", lambda: self.moves(TWISTS, _twisted, Cube.twist, range(18)))
+        self.flip_moves = self.load("flip_moves", "H", lambda: self.moves(FLIPS, _flipped, Cube.flip, range(18)))
+        self.slice_moves = self.load("slice_moves", "H", lambda: self.moves(len(SLICES), _sliced, Cube.slice,
+                                                                            range(18)))
+        self.corner_moves = self.load("corner_moves", "H", lambda: self.moves(
+            len(PERMUTATIONS), lambda i: Cube(cp=PERMUTATIONS[i]), Cube.corners, PHASE_2_MOVES))
+        self.edge_moves = self.load("edge_moves", "H", lambda: self.moves(
+            len(PERMUTATIONS), lambda i: Cube(ep=PERMUTATIONS[i] + (FR, FL, BL, BR)), Cube.edges, PHASE_2_MOVES))
+        self.order_moves = self.load("order_moves", "H", lambda: self.moves(
+            len(SLICE_ORDERS), lambda i: Cube(ep=tuple(range(8)) + tuple(FR + e for e in SLICE_ORDERS[i])),
+            Cube.slice_order, PHASE_2_MOVES))
+        self.slice_twist = self.load("slice_twist", "B", lambda: self.pruning(
+            self.slice_moves, self.twist_moves, len(SLICES), TWISTS, 18))
+        self.slice_flip = self.load("slice_flip", "B", lambda: self.pruning(
+            self.slice_moves, self.flip_moves, len(SLICES), FLIPS, 18))
+        self.order_corners = self.load("order_corners", "B", lambda: self.pruning(
+            self.order_moves, self.corner_moves, len(SLICE_ORDERS), len(PERMUTATIONS), 10))
+        self.order_edges = self.load("order_edges", "B", lambda: self.pruning(
+            self.order_moves, self.edge_moves, len(SLICE_ORDERS), len(PERMUTATIONS), 10))
+
+    def load(self, name: str, typecode: str, build) -> array:
+        path = os.path.join(TABLE_DIRECTORY, name + ".table")
+        table = array(typecode)
+        if os.path.exists(path):
+            with open(path, "rb") as f:
+                table.frombytes(f.read())
+            return table
+        if self.progress:
+            print(f"building the {name.replace('_', ' ')} table ...", file=sys.stderr, flush=True)
+        table = array(typecode, build())
+        with open(path, "wb") as f:
+            f.write(table.tobytes())
+        return table
+
+    @staticmethod
+    def moves(size: int, cube_of, coordinate, moves: Sequence[int]) -> List[int]:
+        """table[value * len(moves) + m] is the coordinate after moves[m]."""
+        table = []
+        for value in range(size):
+            cube = cube_of(value)
+            table += [coordinate(cube * MOVE_CUBES[move]) for move in moves]
+        return table
+
+    @staticmethod
+    def pruning(a_moves: array, b_moves: array, a_size: int, b_size: int, move_count: int) -> bytearray:
+        """Breadth-first from (0, 0), the fewest moves to each (a, b)."""
+        table = bytearray([255]) * (a_size * b_size)
+        table[0] = 0
+        layer = [0]
+        depth = 0
+        while layer:
+            depth += 1
+            following = []
+            for index in layer:
+                a, b = divmod(index, b_size)
+                for m in range(move_count):
+                    after = a_moves[a * move_count + m] * b_size + b_moves[b * move_count + m]
+                    if table[after] == 255:
+                        table[after] = depth
+                        following.append(after)
+            layer = following
+        return table
+
+
+def _twisted(twist: int) -> Cube:
+    co = [0] * 8
+    for i in range(6, -1, -1):
+        twist, co[i] = divmod(twist, 3)
+    co[7] = -sum(co) % 3
+    return Cube(co=co)
+
+
+def _flipped(flip: int) -> Cube:
+    eo = [0] * 12
+    for i in range(10, -1, -1):
+        flip, eo[i] = divmod(flip, 2)
+    eo[11] = sum(eo) % 2
+    return Cube(eo=eo)
+
+
+def _sliced(index: int) -> Cube:
+    places = SLICES[index]
+    middle, others = iter(range(FR, BR + 1)), iter(range(FR))
+    return Cube(ep=[next(middle) if i in places else next(others) for i in range(12)])
+
+
+################################################################################
+###                                 Solving                                  ###
+################################################################################
+
+
+class Solver:
+    def __init__(self, tables: Tables, max_length: int, deadline: float):
+        self.tables = tables
+        self.max_length = max_length
+        self.deadline = deadline
+        self.best: Optional[List[int]] = None
+        self.path: List[int] = []
+        self.nodes = 0
+
+    def done(self) -> bool:
+        return (self.best is not None and len(self.best) <= self.max_length) or time.monotonic() > self.deadline
+
+    def phase_1(self, cube: Cube, twist: int, flip: int, slice_: int, left: int) -> bool:
+        """Every phase 1 of exactly `left` more moves, each handed on to phase 2; True once done."""
+        self.nodes += 1
+        tables = self.tables
+        if left == 0:
+            if twist or flip or slice_:
+                return False
+            # A phase 1 ending in a G1 move was already found, a move shorter
+            if self.path and self.path[-1] in PHASE_2_MOVES:
+                return False
+            self.phase_2_from(cube)
+            return self.done()
+        for move in range(18):
+            if self.path and _redundant(self.path[-1], move):
+                continue
+            t, f, s = tables.twist_moves[twist * 18 + move], tables.flip_moves[flip * 18 + move], \
+                tables.slice_moves[slice_ * 18 + move]
+            if max(tables.slice_twist[s * TWISTS + t], tables.slice_flip[s * FLIPS + f]) >= left:
+                continue
+            self.path.append(move)
+            finished = self.phase_1(cube, t, f, s, left - 1)
+            self.path.pop()
+            if finished:
+                return True
+        return False
+
+    def phase_2_from(self, start: Cube):
+        cube = start.apply(self.path)
+        corners, edges, order = cube.corners(), cube.edges(), cube.slice_order()
+        longest = (len(self.best) if self.best is not None else self.max_length + 10) - len(self.path) - 1
+        phase_1 = len(self.path)
+        for depth in range(longest + 1):
+            if self.phase_2(corners, edges, order, depth):
+                self.best = list(self.path)
+                del self.path[phase_1:]
+                return
+        del self.path[phase_1:]
+
+    def phase_2(self, corners: int, edges: int, order: int, left: int) -> bool:
+        self.nodes += 1
+        if left == 0:
+            return corners == 0 and edges == 0 and order == 0
+        tables = self.tables
+        size = len(PERMUTATIONS)
+        for m, move in enumerate(PHASE_2_MOVES):
+            if self.path and _redundant(self.path[-1], move):
+                continue
+            c, e, o = tables.corner_moves[corners * 10 + m], tables.edge_moves[edges * 10 + m], \
+                tables.order_moves[order * 10 + m]
+            if max(tables.order_corners[o * size + c], tables.order_edges[o * size + e]) >= left:
+                continue
+            self.path.append(move)
+            if self.phase_2(c, e, o, left - 1):
+                return True
+            self.path.pop()
+        return False
+
+
+def _redundant(previous: int, move: int) -> bool:
+    """The same face twice, or opposite faces in the other order (D U for U D), which is no new sequence."""
+    return previous // 3 == move // 3 or previous // 3 == move // 3 + 3
+
+
+def solve(cube: Cube, tables: Tables, max_length: int = 22, seconds: float = 10.0) -> Tuple[List[int], int]:
+    """The shortest moves found, and the nodes searched."""
+    cube.check()
+    solver = Solver(tables, max_length, time.monotonic() + seconds)
+    twist, flip, slice_ = cube.twist(), cube.flip(), cube.slice()
+    depth = max(tables.slice_twist[slice_ * TWISTS + twist], tables.slice_flip[slice_ * FLIPS + flip])
+    while not solver.done() and depth <= 20:
+        if solver.best is not None and depth >= len(solver.best):
+            break
+        solver.phase_1(cube, twist, flip, slice_, depth)
+        depth += 1
+    assert solver.best is not None, "phase 1 never takes more than 12 moves, nor phase 2 18"
+    return solver.best, solver.nodes
+
+
+def verify(cube: Cube, moves: Sequence[int]) -> bool:
+    return cube.apply(moves).is_solved()
+
+
+################################################################################
+###                                   Main                                   ###
+################################################################################
+
+
+def read_cube(argument: Optional[str], facelets: Optional[str]) -> Cube:
+    """The cube that the moves (or - to read them from stdin) make from solved, or that the facelets show."""
+    if facelets is not None:
+        return Cube.from_facelets(facelets)
+    if argument is None:
+        raise ValueError("give the moves of a scramble, or --facelets")
+    text = sys.stdin.read() if argument == "-" else argument
+    return Cube().apply(parse_moves(text))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve the Rubik's cube with Kociemba's two-phase algorithm")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a scrambled cube")
+    solve_parser.add_argument("scramble", type=str, nargs="?", help="the moves that scrambled it, or - for stdin")
+    solve_parser.add_argument("--facelets", type=str, help="the 54 facelets instead, U R F D L B, row by row")
+    solve_parser.add_argument("--max-length", type=int, default=22, help="stop at a solution this short")
+    solve_parser.add_argument("--seconds", type=float, default=10.0, help="or after this long, with the best so far")
+    solve_parser.add_argument("--show", action="store_true", help="draw the cube before it is solved")
+    scramble_parser = subparsers.add_parser("scramble", help="print a scramble")
+    scramble_parser.add_argument("--moves", type=int, default=None,
+                                 help="this many random moves, instead of a random cube's solution backwards")
+    add_seed_argument(scramble_parser)
+    show_parser = subparsers.add_parser("show", help="draw the cube that some moves make")
+    show_parser.add_argument("scramble", type=str, nargs="?", help="the moves, or - for stdin")
+    show_parser.add_argument("--facelets", type=str, help="the 54 facelets instead")
+    verify_parser = subparsers.add_parser("verify", help="check that a solution solves a scramble")
+    verify_parser.add_argument("scramble", type=str)
+    verify_parser.add_argument("solution", type=str)
+    subparsers.add_parser("build-tables", help="build and save the move and pruning tables")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "build-tables":
+            Tables()
+        elif args.command == "scramble":
+            rng = make_rng(args.seed)
+            if args.moves is not None:
+                print(format_moves(scramble_moves(args.moves, rng)))
+            else:
+                moves, _ = solve(random_cube(rng), Tables(), seconds=1.0)
+                print(format_moves(invert_moves(moves)))
+        elif args.command == "show":
+            cube = read_cube(args.scramble, args.facelets)
+            print(render(cube))
+            print(cube.facelets())
+        elif args.command == "verify":
+            solved = verify(Cube().apply(parse_moves(args.scramble)), parse_moves(args.solution))
+            print("Solved" if solved else "Not solved")
+            if not solved:
+                parser.exit(1)
+        else:
+            cube = read_cube(args.scramble, args.facelets)
+            tables = Tables()
+            if args.show:
+                print(render(cube) + "\n")
+            started = time.monotonic()
+            moves, nodes = solve(cube, tables, args.max_length, args.seconds)
+            if not verify(cube, moves):
+                parser.exit(1, f"error: {format_moves(moves)} does not solve the cube\n")
+            print(f"{len(moves)} moves: {format_moves(moves)}")
+            print(f"{nodes} nodes in {time.monotonic() - started:.1f}s")
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
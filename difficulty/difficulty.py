@@ -0,0 +1,164 @@
+"""
+# Difficulty
+
+How hard a puzzle is for a person, the way the puzzle magazines grade
+them: by the hardest kind of reasoning it takes, and then by how much of
+it. A solver that reasons the way a person does (a grader) records, in
+a `Rating`, each time a technique made progress, and each guess it had
+to make when none did. A `Scale` lists the techniques of one kind of
+puzzle, easiest first, and turns a Rating into
+
+    hardest     the hardest technique needed, or guessing if the rest
+                got stuck
+    score       0 to 100: each technique, and guessing after them, has
+                an equal band, and the score is in the band of the
+                hardest, the higher the more it was used (halfway up
+                after its `typical` number of uses, and never quite to
+                the top)
+    level       easy, medium, hard or fiendish, by the quarter of 0 to
+                100 the score is in
+
+The score is comparable between kinds of puzzle only roughly, as their
+techniques are not the same; but within a kind, it orders the puzzles
+that need the same techniques by how much work they are.
+
+A generator asks for a level and makes puzzles until one rates as
+asked (`generate_rated`), giving up after so many tries.
+
+A puzzle written as a CSP (see `csp/csp.py`) can be rated by `rate_csp`,
+which solves the way a person would: arc consistency as far as it goes
+(its uses are the values it crosses off), then probing (a value that arc
+consistency finds leads nowhere, when tried, is crossed off, and then
+arc consistency again), and only when both are stuck, the search, whose
+dead ends are its guesses.
+
+    SCALE = Scale([("singles", 50), ("pairs", 5), ("x-wing", 2)], guesses=10)
+    rating = Rating()
+    rating.use("singles", 30)
+    rating.use("pairs")
+    SCALE.describe(rating)      'medium (score 29): singles 30, pairs 1'
+
+
+## Usage
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from difficulty.difficulty import Rating, Scale, generate_rated
+"""
+
+from typing import Callable, Dict, List, Optional, Sequence, Tuple, TypeVar
+
+from csp.csp import CSP, Domains, Statistics, Variable, ac3, solutions
+
+
+T = TypeVar("T")
+GUESSING: str = "guessing"
+ARC_CONSISTENCY: str = "arc consistency"
+PROBING: str = "probing"
+LEVELS: List[str] = ["easy", "medium", "hard", "fiendish"]
+
+
+class Rating:
+    def __init__(self):
+        self.uses: Dict[str, int] = {}
+        self.guesses = 0
+
+    def use(self, technique: str, times: int = 1):
+        self.uses[technique] = self.uses.get(technique, 0) + times
+
+    def guess(self, times: int = 1):
+        self.guesses += times
+
+    def __str__(self) -> str:
+        used = [f"{technique} {n}" for technique, n in self.uses.items()]
+        if self.guesses:
+            used.append(f"{self.guesses} guess{'es' * (self.guesses != 1)}")
+        return ", ".join(used) or "nothing to do"
+
+
+class Scale:
+    def __init__(self, techniques: Sequence[Tuple[str, float]], guesses: float):
+        """Each technique, easiest first, with its typical uses; and the typical guesses."""
+        self.tiers: List[Tuple[str, float]] = list(techniques) + [(GUESSING, guesses)]
+
+    def hardest(self, rating: Rating) -> str:
+        if rating.guesses:
+            return GUESSING
+        used = [name for name, _ in self.tiers if rating.uses.get(name)]
+        return used[-1] if used else self.tiers[0][0]
+
+    def score(self, rating: Rating) -> float:
+        hardest = self.hardest(rating)
+        tier = [name for name, _ in self.tiers].index(hardest)
+        typical = self.tiers[tier][1]
+        amount = rating.guesses if hardest == GUESSING else rating.uses.get(hardest, 0)
+        return 100 * (tier + amount / (amount + typical)) / len(self.tiers)
+
+    def level(self, rating: Rating) -> str:
+        return level_of(self.score(rating))
+
+    def describe(self, rating: Rating) -> str:
+        """The level and score, and the uses of each technique, easiest first."""
+        ordered = Rating()
+        for name, _ in self.tiers:
+            if rating.uses.get(name):
+                ordered.use(name, rating.uses[name])
+        for name, n in rating.uses.items():
+            if name not in ordered.uses:
+                ordered.use(name, n)
+        ordered.guess(rating.guesses)
+        return f"{self.level(rating)} (score {self.score(rating):.0f}): {ordered}"
+
+
+def level_of(score: float) -> str:
+    return LEVELS[min(len(LEVELS) - 1, int(score * len(LEVELS) / 100))]
+
+
+def generate_rated(make: Callable[[], T], rate: Callable[[T], Rating], scale: Scale, level: Optional[str],
+                   attempts: int) -> Tuple[T, Rating]:
+    """A puzzle from make() that rates at the level (or any, if it is None), giving up after `attempts` tries."""
+    if level is not None and level not in LEVELS:
+        raise ValueError(f"the levels are {', '.join(LEVELS)}, not {level}")
+    for _ in range(attempts):
+        puzzle = make()
+        rating = rate(puzzle)
+        if level is None or scale.level(rating) == level:
+            return puzzle, rating
+    raise ValueError(f"no {level} puzzle in {attempts} tries")
+
+
+def rate_csp(csp: CSP) -> Rating:
+    """The values crossed off by arc consistency and by probing, and the guesses of the search after them."""
+    rating = Rating()
+    domains = {variable: list(domain) for variable, domain in csp.domains.items()}
+    changed: Optional[List[Variable]] = None
+    while True:
+        before = sum(map(len, domains.values()))
+        if not ac3(csp, domains, changed):
+            raise ValueError("there is no solution")
+        if before > sum(map(len, domains.values())):
+            rating.use(ARC_CONSISTENCY, before - sum(map(len, domains.values())))
+        if all(len(domain) == 1 for domain in domains.values()):
+            return rating
+        changed = _probe(csp, domains)
+        if changed is None:
+            break
+        rating.use(PROBING)
+    statistics = Statistics()
+    if next(solutions(csp, statistics=statistics, domains=domains), None) is None:
+        raise ValueError("there is no solution")
+    rating.guess(max(1, statistics.backtracks))
+    return rating
+
+
+def _probe(csp: CSP, domains: Domains) -> Optional[List[Variable]]:
+    """Cross off the first value that arc consistency rules out once it is tried, and return its variable."""
+    for variable, domain in domains.items():
+        if len(domain) == 1:
+            continue
+        for value in domain:
+            trial = {v: list(values) for v, values in domains.items()}
+            trial[variable] = [value]
+            if not ac3(csp, trial, [variable]):
+                domain.remove(value)
+                return [variable]
+    return None
@@ -0,0 +1,342 @@
+"""
+# Dancing Links
+
+Knuth's Algorithm X, with his dancing links, for exact cover: given a
+set of items and a list of options (each a subset of the items), choose
+options that cover every item exactly once. Many puzzles are exact
+cover problems, once the items and options are chosen well:
+
+    Sudoku          an option is "digit d in row r, column c", and covers
+                    the items "r,c is filled", "row r has d", "column c
+                    has d", and "the box has d"
+    N queens        an option is a queen, and covers its row, its column,
+                    and its two diagonals; the diagonals are secondary
+                    items, which may be covered at most once rather than
+                    exactly once
+    polyominoes     an option is a piece in one place and orientation, and
+                    covers the piece's name and the board's cells under it
+
+Algorithm X picks the item with the fewest options left, tries each of
+its options in turn, and removes every option that clashes with it. The
+dancing links make removing and restoring an option cheap: the options
+are a sparse matrix of doubly linked lists, and a removed node keeps its
+own links, so one assignment each way puts it back.
+
+    dlx = DLX(["a", "b", "c"])
+    dlx.add_option("x", ["a", "b"])
+    dlx.add_option("y", ["c"])
+    dlx.add_option("z", ["b", "c"])
+    list(dlx.solutions())       [['x', 'y']]
+
+Counting every tiling of a big board takes a while, so `--count` prints
+how it is going to stderr every second (`--quiet` turns that off).
+
+
+## Usage
+
+    python3 dlx.py queens 8
+    python3 dlx.py queens 10 --count
+    python3 dlx.py sudoku ..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..
+    python3 dlx.py pentominoes 3x20 --count
+    python3 dlx.py pentominoes 6x10
+    python3 dlx.py pentominoes 6x10 --count --quiet
+"""
+
+import argparse
+import functools
+import itertools
+import os
+import sys
+from typing import Dict, Hashable, Iterator, List, Optional, Sequence, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import Progress
+
+
+Item = Hashable
+
+
+class DLX:
+    def __init__(self, primary: Sequence[Item], secondary: Sequence[Item] = ()):
+        """The primary items must be covered exactly once, and the secondary at most once."""
+        items = list(primary) + list(secondary)
+        if len(set(items)) != len(items):
+            raise ValueError("the items must be distinct")
+        self.columns: Dict[Item, int] = {item: i + 1 for i, item in enumerate(items)}
+        # Node 0 is the root, then one header per item, then the options' nodes.
+        n = len(items) + 1
+        self.left = [i - 1 for i in range(n)]
+        self.right = [i + 1 for i in range(n)]
+        self.left[0], self.right[n - 1] = len(primary), 0
+        self.right[len(primary)] = 0
+        for header in range(len(primary) + 1, n):     # secondary items are not in the root's list
+            self.left[header] = self.right[header] = header
+        self.up = list(range(n))
+        self.down = list(range(n))
+        self.column = list(range(n))
+        self.size = [0] * n
+        self.option_of: List[int] = [-1] * n
+        self.options: List[Hashable] = []
+        self.updates = 0
+
+    def add_option(self, name: Hashable, items: Sequence[Item]):
+        if not items:
+            raise ValueError(f"the option {name!r} covers nothing")
+        if len(set(items)) != len(items):
+            raise ValueError(f"the option {name!r} covers an item twice")
+        option = len(self.options)
+        self.options.append(name)
+        first = len(self.left)
+        for k, item in enumerate(items):
+            if item not in self.columns:
+                raise ValueError(f"the option {name!r} covers {item!r}, which is not an item")
+            header = self.columns[item]
+            node = first + k
+            self.left.append(first + (k - 1) % len(items))
+            self.right.append(first + (k + 1) % len(items))
+            self.up.append(self.up[header])
+            self.down.append(header)
+            self.down[self.up[header]] = node
+            self.up[header] = node
+            self.column.append(header)
+            self.size[header] += 1
+            self.option_of.append(option)
+
+    def cover(self, header: int):
+        left, right, up, down = self.left, self.right, self.up, self.down
+        right[left[header]], left[right[header]] = right[header], left[header]
+        row = down[header]
+        while row != header:
+            node = right[row]
+            while node != row:
+                down[up[node]], up[down[node]] = down[node], up[node]
+                self.size[self.column[node]] -= 1
+                self.updates += 1
+                node = right[node]
+            row = down[row]
+
+    def uncover(self, header: int):
+        left, right, up, down = self.left, self.right, self.up, self.down
+        row = up[header]
+        while row != header:
+            node = left[row]
+            while node != row:
+                self.size[self.column[node]] += 1
+                down[up[node]], up[down[node]] = node, node
+                node = left[node]
+            row = up[row]
+        right[left[header]], left[right[header]] = header, header
+
+    def choose(self) -> int:
+        """The primary item with the fewest options left."""
+        best, best_size = 0, None
+        header = self.right[0]
+        while header != 0:
+            if best_size is None or self.size[header] < best_size:
+                best, best_size = header, self.size[header]
+                if best_size <= 1:
+                    break
+            header = self.right[header]
+        return best
+
+    def solutions(self, progress: Optional[Progress] = None) -> Iterator[List[Hashable]]:
+        """Every exact cover, as the names of its options."""
+        chosen: List[int] = []
+        found = 0
+
+        def search() -> Iterator[List[Hashable]]:
+            nonlocal found
+            if progress is not None:
+                progress.update(updates=self.updates, solutions=found, depth=len(chosen))
+            if self.right[0] == 0:
+                found += 1
+                yield [self.options[self.option_of[node]] for node in chosen]
+                return
+            header = self.choose()
+            if self.size[header] == 0:
+                return
+            self.cover(header)
+            row = self.down[header]
+            while row != header:
+                chosen.append(row)
+                node = self.right[row]
+                while node != row:
+                    self.cover(self.column[node])
+                    node = self.right[node]
+                yield from search()
+                node = self.left[row]
+                while node != row:
+                    self.uncover(self.column[node])
+                    node = self.left[node]
+                chosen.pop()
+                row = self.down[row]
+            self.uncover(header)
+
+        yield from search()
+
+    def count(self, progress: Optional[Progress] = None) -> int:
+        return sum(1 for _ in self.solutions(progress))
+
+    def solve(self) -> Optional[List[Hashable]]:
+        return next(self.solutions(), None)
+
+
+################################################################################
+###                                 Puzzles                                  ###
+################################################################################
+
+
+def queens(n: int) -> DLX:
+    """Option (r, c) is a queen in row r and column c."""
+    diagonals = [("/", d) for d in range(2 * n - 1)] + [("\\", d) for d in range(2 * n - 1)]
+    primary = [("row", r) for r in range(n)] + [("column", c) for c in range(n)]
+    dlx = DLX(primary, diagonals)
+    # The middle rows first, which Knuth found makes the search faster.
+    for r in sorted(range(n), key=lambda r: abs(r - (n - 1) / 2)):
+        for c in range(n):
+            dlx.add_option((r, c), [("row", r), ("column", c), ("/", r + c), ("\\", r - c + n - 1)])
+    return dlx
+
+
+def format_queens(n: int, solution: List[Tuple[int, int]]) -> str:
+    placed = set(solution)
+    return "\n".join(" ".join("Q" if (r, c) in placed else "." for c in range(n)) for r in range(n))
+
+
+def sudoku(puzzle: str) -> DLX:
+    """Option (r, c, d) is digit d in row r, column c."""
+    digits = [c for c in puzzle if not c.isspace()]
+    if len(digits) != 81 or any(c not in "0123456789." for c in digits):
+        raise ValueError("a Sudoku is 81 digits, with '.' or '0' for the empty cells")
+    items = [("cell", r, c) for r in range(9) for c in range(9)]
+    items += [(kind, i, d) for kind in ("row", "column", "box") for i in range(9) for d in range(1, 10)]
+    dlx = DLX(items)
+    for r, c in itertools.product(range(9), repeat=2):
+        given = digits[9 * r + c]
+        for d in ([int(given)] if given not in ".0" else range(1, 10)):
+            box = 3 * (r // 3) + c // 3
+            dlx.add_option((r, c, d), [("cell", r, c), ("row", r, d), ("column", c, d), ("box", box, d)])
+    return dlx
+
+
+def format_sudoku(solution: List[Tuple[int, int, int]]) -> str:
+    grid = [[0] * 9 for _ in range(9)]
+    for r, c, d in solution:
+        grid[r][c] = d
+    lines = []
+    for r in range(9):
+        if r and r % 3 == 0:
+            lines.append("------+-------+------")
+        cells = [str(d) for d in grid[r]]
+        lines.append(" | ".join(" ".join(cells[i:i + 3]) for i in range(0, 9, 3)))
+    return "\n".join(lines)
+
+
+PENTOMINOES: Dict[str, List[str]] = {
+    "F": [".##", "##.", ".#."],
+    "I": ["#####"],
+    "L": ["####", "#..."],
+    "N": ["##..", ".###"],
+    "P": ["###", "##."],
+    "T": ["###", ".#.", ".#."],
+    "U": ["#.#", "###"],
+    "V": ["#..", "#..", "###"],
+    "W": ["#..", "##.", ".##"],
+    "X": [".#.", "###", ".#."],
+    "Y": ["####", ".#.."],
+    "Z": ["##.", ".#.", ".##"],
+}
+
+Cells = Tuple[Tuple[int, int], ...]
+
+
+def orientations(rows: List[str]) -> Set[Cells]:
+    """Every rotation and reflection of a piece, as cells (x, y) moved to touch the top left."""
+    cells = [(x, y) for y, row in enumerate(rows) for x, c in enumerate(row) if c == "#"]
+    found: Set[Cells] = set()
+    for _ in range(2):
+        for _ in range(4):
+            cells = [(-y, x) for x, y in cells]     # a quarter turn
+            x0, y0 = min(x for x, _ in cells), min(y for _, y in cells)
+            found.add(tuple(sorted((x - x0, y - y0) for x, y in cells)))
+        cells = [(-x, y) for x, y in cells]         # a reflection
+    return found
+
+
+def pentominoes(width: int, height: int) -> DLX:
+    """
+    Option (name, cells) is a piece covering those cells. To count each
+    tiling once rather than once per symmetry of the board, the X (which has
+    every symmetry itself) is kept to one quarter of the board. On a board
+    with an odd side, the X can sit on the middle line, and then its mirror
+    image is still counted too: 3x20 has 2 tilings, but counts 4.
+    """
+    if width * height != 60:
+        raise ValueError(f"the twelve pentominoes cover 60 cells, not {width}x{height} = {width * height}")
+    board = [(x, y) for y in range(height) for x in range(width)]
+    dlx = DLX(list(PENTOMINOES) + board)
+    for name, rows in PENTOMINOES.items():
+        for shape in orientations(rows):
+            for dx, dy in board:
+                cells = [(x + dx, y + dy) for x, y in shape]
+                if not all(0 <= x < width and 0 <= y < height for x, y in cells):
+                    continue
+                if name == "X":
+                    cx, cy = cells[2]   # the centre, as the middle cell of the sorted plus
+                    if 2 * cx > width - 1 or 2 * cy > height - 1:
+                        continue
+                dlx.add_option((name, tuple(cells)), [name] + cells)
+    return dlx
+
+
+def format_tiling(width: int, height: int, solution: List[Tuple[str, Cells]]) -> str:
+    board = [["."] * width for _ in range(height)]
+    for name, cells in solution:
+        for x, y in cells:
+            board[y][x] = name
+    return "\n".join("".join(row) for row in board)
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Exact cover with Dancing Links")
+    parser.add_argument("puzzle", choices=["queens", "sudoku", "pentominoes"])
+    parser.add_argument("instance", type=str, help="n for queens, the digits for sudoku, WxH for pentominoes")
+    parser.add_argument("--count", action="store_true", help="count the solutions instead of showing one")
+    parser.add_argument("--quiet", action="store_true", help="do not report the progress of --count")
+    args = parser.parse_args()
+
+    try:
+        if args.puzzle == "queens":
+            if not args.instance.isdigit():
+                raise ValueError("the number of queens must be a positive integer")
+            n = int(args.instance)
+            dlx = queens(n)
+            show = functools.partial(format_queens, n)
+        elif args.puzzle == "sudoku":
+            dlx = sudoku(args.instance)
+            show = format_sudoku
+        else:
+            width, _, height = args.instance.partition("x")
+            if not width.isdigit() or not height.isdigit():
+                raise ValueError("the board must be WxH, e.g. 6x10")
+            dlx = pentominoes(int(width), int(height))
+            show = functools.partial(format_tiling, int(width), int(height))
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+    if args.count:
+        count = dlx.count(None if args.quiet else Progress(label='dlx'))
+        print(f"{count} solution{'s' * (count != 1)}")
+    else:
+        solution = dlx.solve()
+        print("No solution" if solution is None else show(solution))
+    print(f"{dlx.updates} updates")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,563 @@
+"""
+# Dots and Boxes
+
+A grid of dots, and two players taking turns to join two neighbouring
+dots with a line. Whoever draws the fourth side of a box takes it, and
+must then draw another line; the player with more boxes at the end
+wins.
+
+      a   b   c         a 2x2 board, of 3x3 dots, where B has taken
+    1 +---+   +         the top left box; a line is named by its two
+      | B |             dots, like a1b1 across the top, or c2c3
+    2 +---+---+
+              |
+    3 +   +   +
+
+
+## Chains and Loops
+
+Late in the game every line either takes a box or gives one away. The
+boxes left then form chains (a row of boxes, each with two sides drawn,
+running from one edge of the board to another) and loops (a ring of
+them). Opening a chain hands it to the opponent, who can take all of it
+and then has to open the next one, or take all but the last two and hand
+those back with one line (a double-dealing move), so that the other
+player opens the next one: keeping control. A loop can be declined in
+the same way, at the cost of four boxes.
+
+When every box left has exactly two sides drawn, the position is only
+chains and loops, and its value is worked out from their lengths alone,
+by trying each one to open and both replies, without any more search:
+
+    value(S)  = max over c in S of -reply(c, S - c)
+    reply     = a chain of n: n + value(rest), or, for n >= 3,
+                n - 4 - value(rest) (all but 2, which go back)
+                a loop of n: n + value(rest), or n - 8 - value(rest)
+
+
+## Search
+
+Before then, negamax with alpha-beta pruning, where a move that takes a
+box is worth the box and keeps the turn, and so is not negated. The
+value of a position is the boxes the player to move will take from the
+rest of it, less the opponent's, so it depends only on the lines drawn:
+a transposition table keeps them, as exact values or bounds. Moves that
+take a box are tried first, then the safe moves that give nothing away.
+
+The whole game is only searched to the end on small boards; past
+--nodes positions, the engine gives up and plays by rule of thumb: take
+any box it can, else a safe move, else the move that gives away the
+fewest. `greedy` plays by that rule always.
+
+Searched to the end, 2x2 is a win for the first player, 3 boxes to 1,
+and 3x3 for the second, 6 to 3, which takes about half a minute.
+
+
+## Usage
+
+    python3 dots_and_boxes.py solve --size 2x2 --stats
+    python3 dots_and_boxes.py solve --size 3x2 --moves "a1b1 c3c2"
+    python3 dots_and_boxes.py play --size 3x3 --first engine --seed 1
+    python3 dots_and_boxes.py play --size 2x2 --no-record
+    python3 dots_and_boxes.py selfplay --size 3x3 --games 5 --second greedy --seed 1
+    python3 dots_and_boxes.py selfplay --size 3x2 --games 10 --seed 1
+"""
+
+import argparse
+import os
+import random
+import re
+import sys
+import time
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from repl.repl import Repl
+from rng.rng import add_seed_argument, make_rng
+
+
+PLAYERS: str = "AB"
+PLAYER_KINDS: List[str] = ["engine", "greedy"]
+EXACT, LOWER, UPPER = 0, 1, 2
+DOT: str = "+"
+MOVE = re.compile(r"([a-z])(\d+)\s*-?\s*([a-z])(\d+)")
+Component = Tuple[str, int]     # "chain" or "loop", and its boxes
+
+
+class Board:
+    def __init__(self, width: int, height: int):
+        """width x height boxes, of (width + 1) x (height + 1) dots."""
+        if not 1 <= width <= 25 or not 1 <= height <= 25:
+            raise ValueError("a board is 1 to 25 boxes each way")
+        self.width, self.height = width, height
+        self.across = width * (height + 1)      # the lines across come first, row by row
+        self.edges = self.across + (width + 1) * height
+        self.boxes = width * height
+        self.full = (1 << self.edges) - 1
+        self.box_edges: List[List[int]] = []
+        self.edge_boxes: List[List[int]] = [[] for _ in range(self.edges)]
+        for y in range(height):
+            for x in range(width):
+                sides = [y * width + x, (y + 1) * width + x,
+                         self.across + y * (width + 1) + x, self.across + y * (width + 1) + x + 1]
+                for e in sides:
+                    self.edge_boxes[e].append(len(self.box_edges))
+                self.box_edges.append(sides)
+        self.box_masks: List[int] = [sum(1 << e for e in sides) for sides in self.box_edges]
+        self.symmetries = self._symmetries()
+
+    def _symmetries(self) -> List[List[List[int]]]:
+        """For each symmetry of the board, and each byte of a mask, where each value of the byte goes: a mask is
+        mapped a byte at a time, rather than a line at a time."""
+        w, h = self.width, self.height
+        maps = [lambda x, y: (x, y), lambda x, y: (w - x, y), lambda x, y: (x, h - y), lambda x, y: (w - x, h - y)]
+        if w == h:
+            maps += [lambda x, y: (y, x), lambda x, y: (w - y, x), lambda x, y: (y, h - x), lambda x, y: (w - y, h - x)]
+        index = {tuple(sorted(self.dots(e))): e for e in range(self.edges)}
+        tables = []
+        for f in maps:
+            image = [index[tuple(sorted(f(*dot) for dot in self.dots(e)))] for e in range(self.edges)]
+            tables.append([[sum(1 << image[8 * k + bit] for bit in range(8)
+                                if value >> bit & 1 and 8 * k + bit < self.edges)
+                            for value in range(256)] for k in range((self.edges + 7) // 8)])
+        return tables
+
+    def key(self, mask: int) -> int:
+        """The same for every reflection or rotation of the lines."""
+        return min(sum(table[k][mask >> (8 * k) & 255] for k in range(len(table))) for table in self.symmetries)
+
+    def dots(self, e: int) -> Tuple[Tuple[int, int], Tuple[int, int]]:
+        if e < self.across:
+            x, y = e % self.width, e // self.width
+            return (x, y), (x + 1, y)
+        x, y = (e - self.across) % (self.width + 1), (e - self.across) // (self.width + 1)
+        return (x, y), (x, y + 1)
+
+    def name(self, e: int) -> str:
+        return "".join(f"{chr(ord('a') + x)}{y + 1}" for x, y in self.dots(e))
+
+    def parse_move(self, text: str) -> int:
+        match = MOVE.fullmatch(text.strip().lower())
+        if match is None:
+            raise ValueError("a move is two neighbouring dots, like a1b1")
+        ends = sorted((ord(match.group(i)) - ord("a"), int(match.group(i + 1)) - 1) for i in (1, 3))
+        for e in range(self.edges):
+            if list(self.dots(e)) == ends:
+                return e
+        raise ValueError(f"{text.strip()} does not join two neighbouring dots on a {self.width}x{self.height} board")
+
+    def sides(self, mask: int, box: int) -> int:
+        return bin(mask & self.box_masks[box]).count("1")
+
+    def completes(self, mask: int, e: int) -> int:
+        """How many boxes the line takes."""
+        after = mask | 1 << e
+        return sum(1 for box in self.edge_boxes[e] if after & self.box_masks[box] == self.box_masks[box])
+
+    def is_safe(self, mask: int, e: int) -> bool:
+        """Whether the line leaves no box with three sides for the opponent."""
+        return all(self.sides(mask, box) <= 1 for box in self.edge_boxes[e])
+
+    def undrawn(self, mask: int) -> List[int]:
+        return [e for e in range(self.edges) if not mask >> e & 1]
+
+    def components(self, mask: int) -> Optional[List[Component]]:
+        """The chains and loops, if every box left has exactly two sides drawn; else None."""
+        left = [box for box in range(self.boxes) if mask & self.box_masks[box] != self.box_masks[box]]
+        if any(self.sides(mask, box) != 2 for box in left):
+            return None
+        # Each box's neighbours through its two undrawn sides, None for the edge of the board
+        links: Dict[int, List[Optional[int]]] = {}
+        for box in left:
+            links[box] = []
+            for e in self.box_edges[box]:
+                if not mask >> e & 1:
+                    others = [b for b in self.edge_boxes[e] if b != box]
+                    links[box].append(others[0] if others else None)
+        found = []
+        seen = set()
+        for first in left:
+            if first in seen:
+                continue
+            seen.add(first)
+            stack = [first]
+            size, ground = 0, 0
+            while stack:
+                box = stack.pop()
+                size += 1
+                for other in links[box]:
+                    if other is None:
+                        ground += 1
+                    elif other not in seen:
+                        seen.add(other)
+                        stack.append(other)
+            found.append(("chain" if ground else "loop", size))
+        return found
+
+    def render(self, mask: int, owners: Tuple[int, ...]) -> str:
+        lines = ["  " + "   ".join(chr(ord("a") + x) for x in range(self.width + 1))]
+        for y in range(self.height + 1):
+            row = "".join(DOT + ("---" if mask >> (y * self.width + x) & 1 else "   ") for x in range(self.width))
+            lines.append(f"{y + 1:<2}{row}{DOT}")
+            if y < self.height:
+                cells = []
+                for x in range(self.width + 1):
+                    cells.append("|" if mask >> (self.across + y * (self.width + 1) + x) & 1 else " ")
+                    if x < self.width:
+                        owner = owners[y * self.width + x]
+                        cells.append(f" {PLAYERS[owner] if owner >= 0 else ' '} ")
+                lines.append(("  " + "".join(cells)).rstrip())
+        return "\n".join(lines)
+
+
+def control_value(components: Tuple[Component, ...], memo: Dict[Tuple[Component, ...], int]) -> int:
+    """The boxes the player to move takes from only chains and loops, less the opponent's: it has to open one."""
+    if not components:
+        return 0
+    if components in memo:
+        return memo[components]
+    best = None
+    for i, (kind, n) in enumerate(components):
+        if i and components[i - 1] == (kind, n):
+            continue
+        rest = control_value(components[:i] + components[i + 1:], memo)
+        reply = n + rest
+        if kind == "chain" and n >= 3:
+            reply = max(reply, n - 4 - rest)
+        elif kind == "loop":
+            reply = max(reply, n - 8 - rest)
+        best = -reply if best is None else max(best, -reply)
+    memo[components] = best
+    return best
+
+
+class State(NamedTuple):
+    mask: int
+    player: int
+    owners: Tuple[int, ...]     # who took each box, or -1
+
+    def play(self, board: Board, e: int) -> "State":
+        mask = self.mask | 1 << e
+        owners = list(self.owners)
+        for box in board.edge_boxes[e]:
+            if mask & board.box_masks[box] == board.box_masks[box]:
+                owners[box] = self.player
+        took = owners != list(self.owners)
+        return State(mask, self.player if took else 1 - self.player, tuple(owners))
+
+    def score(self, player: int) -> int:
+        return self.owners.count(player)
+
+
+def start(board: Board) -> State:
+    return State(0, 0, (-1,) * board.boxes)
+
+
+################################################################################
+###                                  Engine                                  ###
+################################################################################
+
+
+class OutOfNodes(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.nodes = 0
+        self.hits = 0
+        self.endgames = 0
+
+    def __str__(self) -> str:
+        return (f"{self.nodes} positions searched, {self.hits} transposition table hits, "
+                f"{self.endgames} valued as chains and loops")
+
+
+class Engine:
+    def __init__(self, board: Board, max_nodes: Optional[int] = None, rng: Optional[random.Random] = None):
+        self.board = board
+        self.max_nodes = max_nodes
+        self.rng = rng or random.Random(0)
+        self.table: Dict[int, Tuple[int, int]] = {}     # key -> (value, EXACT or LOWER or UPPER)
+        self.memo: Dict[Tuple[Component, ...], int] = {}
+        self.statistics = Statistics()
+        self.budget = 0
+
+    def ordered(self, mask: int) -> List[Tuple[int, int]]:
+        """Each line with the boxes it takes: those that take boxes first, then the safe ones, then the rest."""
+        moves = [(e, self.board.completes(mask, e)) for e in self.board.undrawn(mask)]
+        return sorted(moves, key=lambda move: (-move[1], not self.board.is_safe(mask, move[0])))
+
+    def negamax(self, mask: int, alpha: int, beta: int) -> int:
+        """The boxes the player to move takes from the rest, less the opponent's."""
+        if mask == self.board.full:
+            return 0
+        self.statistics.nodes += 1
+        if self.max_nodes is not None and self.statistics.nodes > self.budget:
+            raise OutOfNodes()
+        key = self.board.key(mask)
+        entry = self.table.get(key)
+        if entry is not None:
+            value, flag = entry
+            if flag == EXACT or (flag == LOWER and value >= beta) or (flag == UPPER and value <= alpha):
+                self.statistics.hits += 1
+                return value
+        components = self.board.components(mask)
+        if components is not None:
+            self.statistics.endgames += 1
+            value = control_value(tuple(sorted(components)), self.memo)
+            self.table[key] = (value, EXACT)
+            return value
+        original_alpha = alpha
+        best = -self.board.boxes - 1
+        for e, took in self.ordered(mask):
+            after = mask | 1 << e
+            value = took + self.negamax(after, alpha - took, beta - took) if took else -self.negamax(after, -beta,
+                                                                                                       -alpha)
+            best = max(best, value)
+            alpha = max(alpha, value)
+            if alpha >= beta:
+                break
+        flag = UPPER if best <= original_alpha else LOWER if best >= beta else EXACT
+        self.table[key] = (best, flag)
+        return best
+
+    def best(self, mask: int) -> Tuple[int, int]:
+        """The best line, and its value: raises OutOfNodes past the limit."""
+        self.budget = self.statistics.nodes + (self.max_nodes or 0)
+        moves = self.ordered(mask)
+        self.rng.shuffle(moves)
+        moves.sort(key=lambda move: (-move[1], not self.board.is_safe(mask, move[0])))
+        best_move, best_value = -1, -self.board.boxes - 2
+        for e, took in moves:
+            after = mask | 1 << e
+            limit = self.board.boxes + 1
+            value = took + self.negamax(after, -limit, limit) if took else -self.negamax(after, -limit, limit)
+            if value > best_value:
+                best_move, best_value = e, value
+        return best_move, best_value
+
+    def evaluate(self, mask: int) -> List[Tuple[int, int]]:
+        """Each line with its exact value to the player drawing it, best first."""
+        self.budget = self.statistics.nodes + (self.max_nodes or 0)
+        limit = self.board.boxes + 1
+        scored = []
+        for e, took in self.ordered(mask):
+            after = mask | 1 << e
+            scored.append((e, took + self.negamax(after, -limit, limit) if took
+                           else -self.negamax(after, -limit, limit)))
+        return sorted(scored, key=lambda move: -move[1])
+
+
+def given_away(board: Board, mask: int) -> int:
+    """How many boxes the opponent can take, one after another, after this line."""
+    taken = 0
+    while True:
+        takes = [e for e in board.undrawn(mask) if board.completes(mask, e)]
+        if not takes:
+            return taken
+        taken += board.completes(mask, takes[0])
+        mask |= 1 << takes[0]
+
+
+def rule_of_thumb(board: Board, mask: int, rng: random.Random) -> int:
+    """Take a box if there is one, else draw a safe line, else give away as few boxes as there are."""
+    undrawn = board.undrawn(mask)
+    takes = [e for e in undrawn if board.completes(mask, e)]
+    if takes:
+        return takes[0]
+    safe = [e for e in undrawn if board.is_safe(mask, e)]
+    if safe:
+        return rng.choice(safe)
+    return min(undrawn, key=lambda e: (given_away(board, mask | 1 << e), e))
+
+
+def choose(kind: str, engine: Engine, mask: int, rng: random.Random) -> Tuple[int, Optional[int]]:
+    """A line, and its value if the engine worked it out."""
+    if kind == "engine":
+        try:
+            return engine.best(mask)
+        except OutOfNodes:
+            pass
+    return rule_of_thumb(engine.board, mask, rng), None
+
+
+def describe(value: int) -> str:
+    if value == 0:
+        return "a draw from here"
+    return f"{'wins' if value > 0 else 'loses'} the rest by {abs(value)}"
+
+
+################################################################################
+###                                   Play                                   ###
+################################################################################
+
+
+def play(board: Board, engine_first: bool, max_nodes: int, rng: random.Random, record: bool = True):
+    engine = Engine(board, max_nodes)
+    history: List[State] = [start(board)]
+    engine_plays = 0 if engine_first else 1
+    session = Session("dots_and_boxes", f"{board.width}x{board.height}", record)
+
+    def render() -> str:
+        state = history[-1]
+        scores = f"{PLAYERS[0]} {state.score(0)}, {PLAYERS[1]} {state.score(1)}"
+        return f"{board.render(state.mask, state.owners)}\n{scores}"
+
+    def over() -> bool:
+        state = history[-1]
+        if state.mask != board.full:
+            return False
+        a, b = state.score(0), state.score(1)
+        print("A draw" if a == b else f"{PLAYERS[0] if a > b else PLAYERS[1]} wins" +
+              (" (the engine)" if (a > b) == (engine_plays == 0) else ""))
+        session.finish("drawn" if a == b else "lost" if (a > b) == (engine_plays == 0) else "won")
+        return True
+
+    def engine_turn():
+        while not over() and history[-1].player == engine_plays:
+            e, value = choose("engine", engine, history[-1].mask, rng)
+            history.append(history[-1].play(board, e))
+            print(f"{PLAYERS[engine_plays]} draws {board.name(e)}" +
+                  (f" ({describe(value)})" if value is not None else ""))
+        print(render())
+
+    def move(text: str):
+        state = history[-1]
+        if state.mask == board.full:
+            raise ValueError("the game is over (:new for another)")
+        e = board.parse_move(text)
+        if state.mask >> e & 1:
+            raise ValueError(f"{board.name(e)} is drawn already")
+        history.append(state.play(board, e))
+        session.move()
+        if history[-1].player == engine_plays:
+            engine_turn()
+        else:
+            print(render())
+            over()
+
+    def hint(_: str):
+        state = history[-1]
+        if state.mask != board.full:
+            e, value = choose("engine", engine, state.mask, rng)
+            print(f"{board.name(e)}" + (f": {describe(value)}" if value is not None else ""))
+
+    def undo(_: str):
+        while len(history) > 1:
+            history.pop()
+            if history[-1].player != engine_plays:
+                break
+        print(render())
+
+    def new(_: str):
+        del history[1:]
+        session.start()
+        engine_turn()
+
+    repl = Repl("line> ", move)
+    repl.add_command("board", lambda _: print(render()), "print the board")
+    repl.add_command("hint", hint, "the engine's line")
+    repl.add_command("undo", undo, "take back your last line (and the engine's reply)")
+    repl.add_command("new", new, "start again")
+    print(f"You are {PLAYERS[1 - engine_plays]}; a line joins two dots, like a1b1, and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+def selfplay(board: Board, kinds: Tuple[str, str], games: int, max_nodes: int, rng: random.Random):
+    """Play the players against each other, and time them."""
+    engines = [Engine(board, max_nodes, rng), Engine(board, max_nodes, rng)]
+    wins = [0, 0]
+    margin = 0
+    seconds = [0.0, 0.0]
+    moves = [0, 0]
+    for game in range(1, games + 1):
+        state = start(board)
+        while state.mask != board.full:
+            player = state.player
+            began = time.perf_counter()
+            e, _ = choose(kinds[player], engines[player], state.mask, rng)
+            seconds[player] += time.perf_counter() - began
+            moves[player] += 1
+            state = state.play(board, e)
+        a, b = state.score(0), state.score(1)
+        margin += a - b
+        if a != b:
+            wins[0 if a > b else 1] += 1
+        print(f"game {game}: {PLAYERS[0]} {a}, {PLAYERS[1]} {b}")
+    draws = games - sum(wins)
+    print(f"{PLAYERS[0]} ({kinds[0]}) won {wins[0]}, {PLAYERS[1]} ({kinds[1]}) won {wins[1]}, {draws} drawn; "
+          f"{PLAYERS[0]} by {margin / games:+.2f} boxes a game")
+    for player in (0, 1):
+        print(f"{PLAYERS[player]}: {seconds[player] / max(1, moves[player]) * 1000:.1f} ms a line"
+              + (f", {engines[player].statistics}" if kinds[player] == "engine" else ""))
+
+
+def parse_size(text: str) -> Board:
+    width, x, height = text.partition("x")
+    if not x or not width.isdigit() or not height.isdigit():
+        raise ValueError(f"expected a size like 3x3 (in boxes), not {text!r}")
+    return Board(int(width), int(height))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve and play Dots and Boxes")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--size", type=str, default="3x3", help="the width and height, in boxes")
+    common.add_argument("--nodes", type=int, default=20000,
+                        help="the most positions the engine searches for a line before it plays by rule of thumb")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", parents=[common], help="the exact value of each line")
+    solve_parser.add_argument("--moves", type=str, default="", help="the lines drawn so far, like \"a1b1 b1b2\"")
+    solve_parser.add_argument("--stats", action="store_true", help="print the positions searched")
+    play_parser = subparsers.add_parser("play", parents=[common], help="play against the engine")
+    play_parser.add_argument("--first", choices=["human", "engine"], default="human")
+    play_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    add_seed_argument(play_parser, help="for the engine's choice among the safe lines")
+    selfplay_parser = subparsers.add_parser("selfplay", parents=[common], help="play players against each other")
+    selfplay_parser.add_argument("--first", choices=PLAYER_KINDS, default="engine")
+    selfplay_parser.add_argument("--second", choices=PLAYER_KINDS, default="engine")
+    selfplay_parser.add_argument("--games", type=int, default=10)
+    add_seed_argument(selfplay_parser)
+    args = parser.parse_args()
+
+    try:
+        board = parse_size(args.size)
+        if args.nodes < 1:
+            raise ValueError("--nodes must be at least 1")
+        state = start(board)
+        for text in (args.moves.split() if args.command == "solve" else []):
+            e = board.parse_move(text)
+            if state.mask >> e & 1:
+                raise ValueError(f"{board.name(e)} is drawn twice")
+            state = state.play(board, e)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "play":
+        play(board, args.first == "engine", args.nodes, make_rng(args.seed), not args.no_record)
+        return
+    if args.command == "selfplay":
+        selfplay(board, (args.first, args.second), max(1, args.games), args.nodes, make_rng(args.seed))
+        return
+    print(board.render(state.mask, state.owners))
+    print(f"{PLAYERS[0]} {state.score(0)}, {PLAYERS[1]} {state.score(1)}")
+    if state.mask == board.full:
+        print("The game is over")
+        return
+    engine = Engine(board)
+    moves = engine.evaluate(state.mask)
+    player = PLAYERS[state.player]
+    final = state.score(state.player) - state.score(1 - state.player) + moves[0][1]
+    print(f"{player} to move: {describe(moves[0][1])}, and " +
+          (f"wins by {final}" if final > 0 else f"loses by {-final}" if final < 0 else "draws"))
+    for e, value in moves:
+        print(f"  {board.name(e)}: {describe(value)}")
+    if args.stats:
+        print(engine.statistics)
+
+
+if __name__ == "__main__":
+    main()
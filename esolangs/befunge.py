@@ -0,0 +1,185 @@
+"""
+# Befunge-93
+
+A program is an 80 by 25 grid of characters, and the instruction
+pointer moves over it in one of four directions, wrapping around the
+edges. There is one stack of integers (popping an empty stack gives 0).
+
+    0-9     push the digit
+    + - * / %   pop a, pop b, push b op a (dividing by 0 gives 0)
+    !       logical not
+    `       pop a, pop b, push 1 if b > a, else 0
+    > < ^ v move right, left, up, down
+    ?       move in a random direction
+    _       pop, move right if 0, else left
+    |       pop, move down if 0, else up
+    "       toggle string mode, which pushes each character's code
+    :       duplicate         \\   swap          $   discard
+    .       output a number followed by a space
+    ,       output a character
+    #       skip the next cell
+    g       pop y, pop x, push the character at (x, y)
+    p       pop y, pop x, pop v, store v at (x, y)
+    &       input a number    ~   input a character (-1 at the end of the input)
+    @       end
+
+Spaces and every other character do nothing. As `p` can change any
+cell, programs can modify themselves; `g` and `p` outside the grid read
+0 and do nothing.
+
+
+## Usage
+
+    python3 befunge.py examples/hello.bf93
+    python3 befunge.py examples/factorial.bf93 --input 10
+    echo "hello" | python3 befunge.py examples/cat.bf93
+    python3 befunge.py examples/hello.bf93 --trace --max-steps 50
+"""
+
+import argparse
+import os
+import sys
+from typing import List, Optional
+
+from esolang_io import BufferIO, ProgramIO, StreamIO
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from interpreters.interpreter import Interpreter
+from rng.rng import add_seed_argument, make_rng
+
+
+WIDTH: int = 80
+HEIGHT: int = 25
+DEFAULT_MAX_STEPS: Optional[int] = None
+DIRECTIONS = {">": (1, 0), "<": (-1, 0), "^": (0, -1), "v": (0, 1)}
+
+
+class Befunge(Interpreter):
+    def __init__(self, source: str, io: ProgramIO, seed: Optional[int] = None):
+        super().__init__(io)
+        lines = source.split("\n")
+        if len(lines) > HEIGHT or any(len(line) > WIDTH for line in lines):
+            raise ValueError(f"the program is larger than {WIDTH}x{HEIGHT}")
+        self.grid: List[List[int]] = [[ord(" ")] * WIDTH for _ in range(HEIGHT)]
+        for y, line in enumerate(lines):
+            for x, c in enumerate(line):
+                self.grid[y][x] = ord(c)
+        self.rng = make_rng(seed)
+        self.stack: List[int] = []
+        self.x, self.y = 0, 0
+        self.dx, self.dy = 1, 0
+        self.string_mode = False
+
+    def pop(self) -> int:
+        return self.stack.pop() if self.stack else 0
+
+    def move(self):
+        self.x = (self.x + self.dx) % WIDTH
+        self.y = (self.y + self.dy) % HEIGHT
+
+    def step(self):
+        c = chr(self.grid[self.y][self.x]) if self.grid[self.y][self.x] < 0x110000 else " "
+        self.steps += 1
+        if self.string_mode:
+            if c == '"':
+                self.string_mode = False
+            else:
+                self.stack.append(self.grid[self.y][self.x])
+        elif c.isdigit():
+            self.stack.append(int(c))
+        elif c in "+-*/%`":
+            a, b = self.pop(), self.pop()
+            if c == "+":
+                self.stack.append(b + a)
+            elif c == "-":
+                self.stack.append(b - a)
+            elif c == "*":
+                self.stack.append(b * a)
+            elif c == "/":
+                # Befunge-93 is written in C, so division rounds towards 0
+                self.stack.append(0 if a == 0 else abs(b) // abs(a) * (1 if (a < 0) == (b < 0) else -1))
+            elif c == "%":
+                self.stack.append(0 if a == 0 else abs(b) % abs(a) * (1 if b >= 0 else -1))
+            else:
+                self.stack.append(1 if b > a else 0)
+        elif c == "!":
+            self.stack.append(1 if self.pop() == 0 else 0)
+        elif c in DIRECTIONS:
+            self.dx, self.dy = DIRECTIONS[c]
+        elif c == "?":
+            self.dx, self.dy = self.rng.choice(list(DIRECTIONS.values()))
+        elif c == "_":
+            self.dx, self.dy = (1, 0) if self.pop() == 0 else (-1, 0)
+        elif c == "|":
+            self.dx, self.dy = (0, 1) if self.pop() == 0 else (0, -1)
+        elif c == '"':
+            self.string_mode = True
+        elif c == ":":
+            value = self.pop()
+            self.stack += [value, value]
+        elif c == "\\":
+            a, b = self.pop(), self.pop()
+            self.stack += [a, b]
+        elif c == "$":
+            self.pop()
+        elif c == ".":
+            self.io.write_text(f"{self.pop()} ")
+        elif c == ",":
+            self.io.write_char(self.pop())
+        elif c == "#":
+            self.move()
+        elif c == "g":
+            y, x = self.pop(), self.pop()
+            self.stack.append(self.grid[y][x] if 0 <= x < WIDTH and 0 <= y < HEIGHT else 0)
+        elif c == "p":
+            y, x, value = self.pop(), self.pop(), self.pop()
+            if 0 <= x < WIDTH and 0 <= y < HEIGHT:
+                self.grid[y][x] = value
+        elif c == "&":
+            number = self.io.read_number()
+            self.stack.append(-1 if number is None else number)
+        elif c == "~":
+            byte = self.io.read_byte()
+            self.stack.append(-1 if byte is None else byte)
+        elif c == "@":
+            self.halted = True
+            return
+        self.move()
+
+    def snapshot(self) -> str:
+        c = self.grid[self.y][self.x]
+        shown = chr(c) if 32 <= c < 127 else f"\\x{c & 0xFF:02x}"
+        return f"({self.x:>2}, {self.y:>2}) {shown}  {self.stack}"
+
+
+def run(source: str, io: ProgramIO, max_steps: Optional[int] = None, seed: Optional[int] = None) -> Befunge:
+    befunge = Befunge(source, io, seed)
+    befunge.run(max_steps)
+    return befunge
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Befunge-93 interpreter")
+    parser.add_argument("program", type=str, help="path to the program")
+    parser.add_argument("--input", type=str, default=None, help="use this as the input instead of stdin")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    add_seed_argument(parser, help="for `?`")
+    parser.add_argument("--trace", action="store_true", help="print the position and stack at every step")
+    args = parser.parse_args()
+
+    with open(args.program) as f:
+        source = f.read()
+    io = StreamIO() if args.input is None else BufferIO(args.input.encode())
+    try:
+        befunge = Befunge(source.rstrip("\n"), io, args.seed)
+    except ValueError as e:
+        parser.error(str(e))
+    befunge.run(args.max_steps, args.trace)
+    if isinstance(io, BufferIO):
+        print(io.output.decode(errors="replace"), end="")
+    if not befunge.halted:
+        print(f"\nstopped after {befunge.steps} steps")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,350 @@
+"""
+# Brainfuck Analysis
+
+What can be known about a Brainfuck program without running it, by
+abstract interpretation: each cell's value is a known byte or unknown,
+and the pointer is a known cell or unknown. Every cell starts at 0 and
+the pointer at cell 0. Straight-line code keeps things known until a `,`
+(the cell becomes unknown) or a move the analysis cannot follow. A loop
+is run to a fixed point: its head joins the state before it with the
+state after its body, a value that differs becoming unknown, until
+nothing changes. A loop whose body moves the pointer (like `[<]`) leaves
+it unknown, and after that every cell is unknown too.
+
+A fixed point of constants forgets how many times a loop went round, so
+a counted loop is run all at once instead: one of only adds and moves,
+ending where it began, whose cell goes up or down by an odd step (so it
+always reaches 0). When its cell is known, the number of times round is
+too, and `++++++++[>+++++++++<-]` leaves 72 in cell 1.
+
+The facts found are
+
+    dead loops      a `[` whose cell is always 0, so its body never runs
+    endless loops   a `]` whose cell is never 0, so once entered the
+                    loop never ends
+    constant output a `.` that always prints the same byte
+    dead stores     a `+`, `-` or `,` whose cell is never read after it
+                    (by `.`, `[` or `]`) before it is written again or
+                    the program ends; a `,` is still needed to consume
+                    its input, but its byte is not
+    the end         the cells known at the end, if it is reached
+
+The dead stores come from a second pass, backwards: which cells might
+still be read (live) before each instruction, with loops again run to a
+fixed point. `analyse` returns all of this as an `Analysis`, by the
+index of each instruction in the program (its commands, without the
+comments), for an optimiser to use: constant propagation can replace a
+run of code ending in a constant `.` with sets of the cell, and store
+elimination can drop the dead `+` and `-`.
+
+Consecutive `+` and `-` are one operation, and so are consecutive `<`
+and `>`, reported at the first of them.
+
+
+## Usage
+
+    python3 bf_analysis.py examples/constants.bf
+    python3 bf_analysis.py examples/hello.bf
+"""
+
+import argparse
+from typing import Dict, List, NamedTuple, Optional, Set, Tuple
+
+
+BF_COMMANDS: str = "+-<>[].,"
+
+
+class Op(NamedTuple):
+    kind: str                   # "+" (add), ">" (move), "[" (loop), "." or ","
+    index: int                  # of its first command
+    amount: int                 # the sum of an add (mod 256) or a move
+    body: List["Op"]            # of a loop
+    end: int                    # the index of a loop's ]
+
+
+class Analysis(NamedTuple):
+    commands: int
+    positions: List[Tuple[int, int]]        # the line and column of each command
+    cells: Dict[int, Optional[int]]         # where each reached op works: the cell, or None if unknown
+    entry: Dict[int, Optional[int]]         # each reached loop's cell at its [, if always the same
+    exit: Dict[int, Optional[int]]          # and at its ], if its body is reached
+    output: Dict[int, Optional[int]]        # the byte each reached . prints, if always the same
+    dead_stores: Set[int]                   # the + - , ops whose value is never read
+    final: Optional[Dict[int, int]]         # the known cells at the end; None if the pointer is lost
+    pointer_known: bool                     # at the end
+
+
+def parse(source: str) -> Tuple[List[Op], List[Tuple[int, int]]]:
+    """The operations, and the line and column of each command."""
+    commands: List[str] = []
+    positions: List[Tuple[int, int]] = []
+    for number, line in enumerate(source.split("\n"), start=1):
+        for column, c in enumerate(line, start=1):
+            if c in BF_COMMANDS:
+                commands.append(c)
+                positions.append((number, column))
+
+    def block(i: int, closing: bool) -> Tuple[List[Op], int]:
+        ops: List[Op] = []
+        while i < len(commands):
+            c = commands[i]
+            if c == "]":
+                if not closing:
+                    raise ValueError(f"line {positions[i][0]}, column {positions[i][1]}: unmatched ]")
+                return ops, i
+            if c == "[":
+                body, end = block(i + 1, True)
+                ops.append(Op("[", i, 0, body, end))
+                i = end + 1
+            elif c in "+-<>":
+                kind = "+" if c in "+-" else ">"
+                start, amount = i, 0
+                while i < len(commands) and commands[i] in ("+-" if kind == "+" else "<>"):
+                    amount += 1 if commands[i] in "+>" else -1
+                    i += 1
+                ops.append(Op(kind, start, amount % 256 if kind == "+" else amount, [], start))
+            else:
+                ops.append(Op(c, i, 0, [], i))
+                i += 1
+        if closing:
+            raise ValueError("unmatched [ at the end of the program")
+        return ops, i
+
+    return block(0, False)[0], positions
+
+
+################################################################################
+###   FORWARD: VALUES   ###
+################################################################################
+
+
+class State(NamedTuple):
+    pointer: Optional[int]
+    values: Dict[int, Optional[int]]        # the cells that may differ from the rest
+    rest: Optional[int]                     # every other cell: 0 at first, None once unknown
+
+    def get(self, cell: Optional[int]) -> Optional[int]:
+        if cell is None:
+            return None
+        return self.values.get(cell, self.rest)
+
+    def set(self, cell: int, value: Optional[int]) -> "State":
+        return State(self.pointer, {**self.values, cell: value}, self.rest)
+
+
+UNKNOWN: State = State(None, {}, None)
+
+
+def join(a: State, b: State) -> State:
+    pointer = a.pointer if a.pointer == b.pointer else None
+    if pointer is None:
+        return UNKNOWN
+    rest = a.rest if a.rest == b.rest else None
+    values = {}
+    for cell in set(a.values) | set(b.values):
+        value = a.get(cell) if a.get(cell) == b.get(cell) else None
+        if value != rest:
+            values[cell] = value
+    return State(pointer, values, rest)
+
+
+def forward(ops: List[Op], state: State, facts: Analysis) -> Optional[State]:
+    """The state after the ops; None if they cannot finish (an endless loop is always entered)."""
+    for op in ops:
+        facts.cells[op.index] = state.pointer
+        if op.kind == "+":
+            if state.pointer is None:
+                state = UNKNOWN
+            else:
+                value = state.get(state.pointer)
+                state = state.set(state.pointer, None if value is None else (value + op.amount) % 256)
+        elif op.kind == ">":
+            state = UNKNOWN if state.pointer is None else State(state.pointer + op.amount, state.values, state.rest)
+        elif op.kind == ",":
+            state = UNKNOWN if state.pointer is None else state.set(state.pointer, None)
+        elif op.kind == ".":
+            facts.output[op.index] = state.get(state.pointer)
+        else:
+            after = loop(op, state, facts)
+            if after is None:
+                return None
+            state = after
+    return state
+
+
+def forget(op: Op, facts: Analysis):
+    """Clear what an earlier pass found inside a loop, so that only the last pass's facts remain."""
+    for i in range(op.index + 1, op.end + 1):
+        for table in (facts.cells, facts.entry, facts.exit, facts.output):
+            table.pop(i, None)
+
+
+def loop(op: Op, state: State, facts: Analysis) -> Optional[State]:
+    """The state after a loop: its head is the fixed point of joining the state before it with that after its body."""
+    forget(op, facts)
+    facts.entry[op.index] = state.get(state.pointer)
+    if state.get(state.pointer) == 0:
+        return state
+    head = state
+    while True:
+        forget(op, facts)
+        after = forward(op.body, head, facts)
+        if after is None:
+            break
+        facts.cells[op.end] = after.pointer
+        facts.exit[op.end] = after.get(after.pointer)
+        joined = join(head, after)
+        if joined == head:
+            break
+        head = joined
+    entered = state.get(state.pointer) is not None
+    if entered and (after is None or facts.exit[op.end] not in (None, 0)):
+        return None
+    deltas = counted(op)
+    if deltas is not None and entered and state.pointer is not None:
+        # Run the whole loop at once: it goes round until its cell reaches 0
+        value = state.get(state.pointer)
+        assert value is not None
+        times = -value * pow(deltas[0], -1, 256) % 256
+        for offset, delta in deltas.items():
+            cell = state.pointer + offset
+            before = state.get(cell)
+            state = state.set(cell, None if before is None else (before + times * delta) % 256)
+        return state
+    return head if head.pointer is None else head.set(head.pointer, 0)
+
+
+def counted(op: Op) -> Optional[Dict[int, int]]:
+    """What one time round a loop of only adds and moves adds to each cell, by its offset from the loop's; None if
+    the loop does anything else, or ends away from where it began, or might not end (its cell's step is even)."""
+    deltas: Dict[int, int] = {0: 0}
+    offset = 0
+    for inner in op.body:
+        if inner.kind == ">":
+            offset += inner.amount
+        elif inner.kind == "+":
+            deltas[offset] = (deltas.get(offset, 0) + inner.amount) % 256
+        else:
+            return None
+    return deltas if offset == 0 and deltas[0] % 2 == 1 else None
+
+
+################################################################################
+###   BACKWARD: LIVE CELLS   ###
+################################################################################
+
+
+Live = Optional[Set[int]]       # the cells that may be read later; None for every cell
+
+
+def union(a: Live, b: Live) -> Live:
+    return None if a is None or b is None else a | b
+
+
+def read(live: Live, cell: Optional[int]) -> Live:
+    return None if cell is None else union(live, {cell})
+
+
+def backward(ops: List[Op], live: Live, facts: Analysis) -> Live:
+    """The cells live before the ops, given those live after them, marking the dead stores on the way."""
+    for op in reversed(ops):
+        if op.index not in facts.cells:
+            continue        # never reached
+        cell = facts.cells[op.index]
+        if op.kind in "+,":
+            facts.dead_stores.discard(op.index)
+            if cell is not None and live is not None and cell not in live:
+                facts.dead_stores.add(op.index)
+            if op.kind == "," and cell is not None and live is not None:
+                live = live - {cell}
+        elif op.kind == ".":
+            live = read(live, cell)
+        elif op.kind == "[":
+            if op.end not in facts.cells:
+                live = read(live, cell)         # the body never runs, or never gets to the ]
+                continue
+            # Before the ] test, the loop may go on to the body again or leave
+            at_end = read(live, facts.cells[op.end])
+            while True:
+                start = backward(op.body, at_end, facts)
+                again = read(union(live, start), facts.cells[op.end])
+                if again == at_end:
+                    break
+                at_end = again
+            live = read(union(live, start), cell)
+    return live
+
+
+def analyse(source: str) -> Analysis:
+    ops, positions = parse(source)
+    facts = Analysis(len(positions), positions, {}, {}, {}, {}, set(), None, False)
+    end = forward(ops, State(0, {}, 0), facts)
+    backward(ops, set(), facts)
+    if end is not None and end.pointer is not None:
+        final = {cell: value for cell, value in end.values.items() if value is not None and value != end.rest}
+        return facts._replace(final=final, pointer_known=True)
+    return facts
+
+
+################################################################################
+###   REPORT   ###
+################################################################################
+
+
+def character(byte: int) -> str:
+    return repr(chr(byte)) if 32 <= byte < 127 else f"byte {byte}"
+
+
+def report(source: str) -> str:
+    ops, _ = parse(source)
+    facts = analyse(source)
+
+    def where(i: int) -> str:
+        line, column = facts.positions[i]
+        return f"{line}:{column}"
+
+    kinds: Dict[int, Op] = {}
+
+    def index(ops: List[Op]):
+        for op in ops:
+            kinds[op.index] = op
+            index(op.body)
+
+    index(ops)
+    lines = [f"{facts.commands} commands in {len(kinds)} operations"]
+    unreached = [i for i in kinds if i not in facts.cells]
+    lost = sorted(i for i, cell in facts.cells.items() if cell is None)
+    if lost:
+        lines.append(f"The pointer is unknown from {where(lost[0])} on (a loop moves it)")
+    dead = sorted(i for i, value in facts.entry.items() if value == 0)
+    endless = sorted(kinds[i].end for i, value in facts.entry.items()
+                     if value != 0 and facts.exit.get(kinds[i].end) not in (None, 0))
+    lines.append("Loops never entered: " + (", ".join(where(i) for i in dead) or "none"))
+    lines.append("Loops that never end once entered: " + (", ".join(where(i) for i in endless) or "none"))
+    constant = sorted(i for i, value in facts.output.items() if value is not None)
+    lines.append(f"Constant output: {len(constant)} of {len(facts.output)} reached '.'")
+    for i in constant:
+        lines.append(f"    {where(i)}  {character(facts.output[i])}")
+    lines.append("Stores never read: " + (", ".join(f"{where(i)} (cell {facts.cells[i]})"
+                                                   for i in sorted(facts.dead_stores)) or "none"))
+    if unreached:
+        lines.append(f"Unreached: {len(unreached)} operations, from {where(min(unreached))}")
+    if facts.final is not None:
+        known = ", ".join(f"cell {cell} = {value}" for cell, value in sorted(facts.final.items()))
+        lines.append("At the end: " + (known or "every cell is 0"))
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Find constant cells, dead loops and dead stores in Brainfuck")
+    parser.add_argument("program", type=str)
+    args = parser.parse_args()
+    try:
+        with open(args.program) as f:
+            print(report(f.read()))
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
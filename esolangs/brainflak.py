@@ -0,0 +1,208 @@
+"""
+# Brain-Flak
+
+DJMcMayhem's Brain-Flak is made of balanced brackets, acting on two
+stacks of integers (only one is active at a time). Every bracket pair
+evaluates to a number, and a run of pairs evaluates to their sum.
+
+    ()      1                   (x)     push x, and evaluate to x
+    []      the stack's height  [x]     -x
+    {}      pop (0 if empty)    {x}     while the top is nonzero, run x, and
+                                        evaluate to the sum of the runs
+    <>      swap stacks, and 0  <x>     run x, and evaluate to 0
+
+Every other character is a comment, apart from these debug flags, which
+print the stacks at that point in the program:
+
+    #dv     the active stack        #dc     the active stack as characters
+    #dl     both stacks
+
+The inputs start on the active stack, the first on top, and when the
+program ends, the active stack is printed from the top down. So the
+empty program prints its input. `--ascii` reads the input as characters
+and prints characters. `--trace` prints both stacks after every bracket
+pair.
+
+
+## Examples
+
+    sum.flak        the sum of the inputs
+    countdown.flak  0 up to n
+    reverse.flak    the inputs in reverse (try it with --ascii)
+
+
+## Usage
+
+    python3 brainflak.py examples/sum.flak --input "1 2 3 4"
+    python3 brainflak.py examples/countdown.flak --input 5 --trace
+    python3 brainflak.py examples/reverse.flak --input "Hello" --ascii
+    python3 brainflak.py --code "#dv({}{})#dv" --input "2 3"
+"""
+
+import argparse
+import re
+import sys
+from typing import List, NamedTuple, Optional, Tuple, Union
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+PAIRS = {"(": ")", "[": "]", "{": "}", "<": ">"}
+
+
+class Pair(NamedTuple):
+    bracket: str
+    body: List["Node"]      # empty for a nilad
+    position: int
+
+
+Node = Union[Pair, str]     # a str is a debug flag
+
+
+class StepLimitExceeded(Exception):
+    pass
+
+
+def parse(source: str) -> List[Node]:
+    """The program as a tree of bracket pairs, with the debug flags in place."""
+    root: List[Node] = []
+    open_pairs: List[Tuple[str, int, List[Node]]] = []
+    body = root
+    position = 0
+    while position < len(source):
+        c = source[position]
+        flag = re.match(r"#(d[vcl])", source[position:])
+        if flag is not None:
+            body.append(flag.group(1))
+            position += len(flag.group(0))
+            continue
+        if c in PAIRS:
+            open_pairs.append((c, position, body))
+            body = []
+        elif c in PAIRS.values():
+            if not open_pairs or PAIRS[open_pairs[-1][0]] != c:
+                raise ValueError(f"character {position}: unmatched '{c}'")
+            bracket, start, outer = open_pairs.pop()
+            outer.append(Pair(bracket, body, start))
+            body = outer
+        position += 1
+    if open_pairs:
+        raise ValueError(f"character {open_pairs[-1][1]}: unmatched '{open_pairs[-1][0]}'")
+    return root
+
+
+class BrainFlak:
+    def __init__(self, program: List[Node], inputs: List[int], trace: bool = False,
+                 max_steps: Optional[int] = None):
+        self.program = program
+        self.active: List[int] = list(reversed(inputs))
+        self.inactive: List[int] = []
+        self.trace = trace
+        self.max_steps = max_steps
+        self.steps = 0
+
+    def pop(self) -> int:
+        return self.active.pop() if self.active else 0
+
+    def evaluate(self, nodes: List[Node]) -> int:
+        total = 0
+        for node in nodes:
+            if isinstance(node, str):
+                self.debug(node)
+                continue
+            total += self.evaluate_pair(node)
+            self.steps += 1
+            if self.trace:
+                shown = node.bracket + ("..." if node.body else "") + PAIRS[node.bracket]
+                print(f"{self.steps:>8}  {node.position:>5}  {shown:<6} {self.format_stacks()}")
+            if self.max_steps is not None and self.steps >= self.max_steps:
+                raise StepLimitExceeded()
+        return total
+
+    def evaluate_pair(self, pair: Pair) -> int:
+        bracket, body = pair.bracket, pair.body
+        nilad = not body or all(isinstance(node, str) for node in body)
+        if nilad:
+            self.evaluate(body)     # just the debug flags
+            if bracket == "(":
+                return 1
+            if bracket == "[":
+                return len(self.active)
+            if bracket == "{":
+                return self.pop()
+            self.active, self.inactive = self.inactive, self.active
+            return 0
+        if bracket == "(":
+            value = self.evaluate(body)
+            self.active.append(value)
+            return value
+        if bracket == "[":
+            return -self.evaluate(body)
+        if bracket == "{":
+            total = 0
+            while self.active and self.active[-1] != 0:
+                total += self.evaluate(body)
+            return total
+        self.evaluate(body)
+        return 0
+
+    def format_stacks(self) -> str:
+        return f"active {self.active[::-1]}  inactive {self.inactive[::-1]}"
+
+    def debug(self, flag: str):
+        if flag == "dv":
+            print(f"#dv {self.active[::-1]}")
+        elif flag == "dc":
+            print(f"#dc {''.join(chr(v % 0x110000) for v in reversed(self.active))!r}")
+        else:
+            print(f"#dl {self.format_stacks()}")
+
+    def run(self) -> bool:
+        """Run the program, and return whether it finished within the step limit."""
+        try:
+            self.evaluate(self.program)
+        except StepLimitExceeded:
+            return False
+        return True
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Brain-Flak interpreter")
+    parser.add_argument("program", type=str, nargs="?", default=None, help="path to the program")
+    parser.add_argument("--code", type=str, default=None, help="run this code instead of a file")
+    parser.add_argument("--input", type=str, default="", help="the inputs: numbers, or text with --ascii")
+    parser.add_argument("--ascii", action="store_true", help="read and print characters instead of numbers")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="print both stacks after every bracket pair")
+    args = parser.parse_args()
+
+    if (args.program is None) == (args.code is None):
+        parser.error("give either a program or --code")
+    source = args.code
+    if args.program is not None:
+        with open(args.program) as f:
+            source = f.read()
+    try:
+        program = parse(source)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.ascii:
+        inputs = [ord(c) for c in args.input]
+    elif re.fullmatch(r"\s*(-?\d+\s*)*", args.input):
+        inputs = [int(word) for word in args.input.split()]
+    else:
+        parser.error("the inputs must be integers (or use --ascii)")
+
+    sys.setrecursionlimit(max(sys.getrecursionlimit(), 10_000))
+    brainflak = BrainFlak(program, inputs, args.trace, args.max_steps)
+    finished = brainflak.run()
+    if args.ascii:
+        print("".join(chr(v % 0x110000) for v in reversed(brainflak.active)))
+    else:
+        for value in reversed(brainflak.active):
+            print(value)
+    if not finished:
+        print(f"stopped after {brainflak.steps} steps")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,93 @@
+"""
+# Deadfish
+
+Jonathan Todd Skinner's Deadfish has one accumulator, starting at 0, and
+four commands:
+
+    i   increment
+    d   decrement
+    s   square
+    o   output the accumulator (and a newline)
+
+Its one quirk: whenever the accumulator becomes -1 or 256, it is reset
+to 0. So `d` at 0 stays at 0, and `iiiissss` (16, squared) is 0, but
+`iiiiisso` prints 625 because 25 squared skips over 256. Every other
+character does nothing.
+
+With no program, Deadfish runs interactively like the original, keeping
+the accumulator from one line to the next (`:reset` sets it back to 0).
+
+
+## Usage
+
+    python3 deadfish.py --code iisiiiisiiiiiiiio
+    python3 deadfish.py --code iiiissssodo
+    python3 deadfish.py program.df
+    python3 deadfish.py
+"""
+
+import argparse
+import os
+import sys
+
+from esolang_io import ProgramIO, StreamIO
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from repl.repl import Repl
+
+
+class Deadfish:
+    def __init__(self, io: ProgramIO):
+        self.io = io
+        self.value = 0
+
+    def run(self, code: str):
+        for c in code:
+            if c == "i":
+                self.value += 1
+            elif c == "d":
+                self.value -= 1
+            elif c == "s":
+                self.value *= self.value
+            elif c == "o":
+                self.io.write_text(f"{self.value}\n")
+            if self.value in (-1, 256):
+                self.value = 0
+        self.io.flush()
+
+
+def run(code: str, io: ProgramIO) -> Deadfish:
+    deadfish = Deadfish(io)
+    deadfish.run(code)
+    return deadfish
+
+
+def start_repl(deadfish: Deadfish):
+    repl = Repl(">> ", deadfish.run)
+
+    def reset(_: str):
+        deadfish.value = 0
+
+    repl.add_command("reset", reset, "set the accumulator back to 0")
+    repl.add_command("value", lambda _: print(deadfish.value), "print the accumulator")
+    repl.run()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Deadfish interpreter")
+    parser.add_argument("program", type=str, nargs="?", default=None, help="path to the program")
+    parser.add_argument("--code", type=str, default=None, help="run this code instead of a file")
+    args = parser.parse_args()
+
+    deadfish = Deadfish(StreamIO())
+    if args.code is not None:
+        deadfish.run(args.code)
+    elif args.program is not None:
+        with open(args.program) as f:
+            deadfish.run(f.read())
+    else:
+        start_repl(deadfish)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,108 @@
+"""
+# Esolang I/O
+
+The interpreters in this directory never touch stdin and stdout
+directly. They read and write through a `ProgramIO`, so the same
+interpreter runs interactively (`StreamIO`) or on a byte buffer
+(`BufferIO`), which is how to check a program's output from Python:
+
+    io = BufferIO(b"5\n")
+    befunge.run(source, io)
+    assert io.output == b"120 "
+
+Reading past the end of the input returns None, and each language
+decides what that means (usually -1 or 0).
+"""
+
+import sys
+from typing import List, Optional
+
+
+class ProgramIO:
+    """Byte-oriented input and output, plus the number I/O many esolangs have."""
+
+    def __init__(self):
+        self._pushback: List[int] = []
+
+    def _read(self) -> Optional[int]:
+        raise NotImplementedError
+
+    def write_byte(self, byte: int):
+        raise NotImplementedError
+
+    def flush(self):
+        pass
+
+    def read_byte(self) -> Optional[int]:
+        if self._pushback:
+            return self._pushback.pop()
+        return self._read()
+
+    def unread_byte(self, byte: int):
+        self._pushback.append(byte)
+
+    def read_number(self) -> Optional[int]:
+        """Skip anything before an (optionally signed) decimal number and read it."""
+        byte = self.read_byte()
+        while byte is not None and not (chr(byte).isdigit() or chr(byte) == "-"):
+            byte = self.read_byte()
+        if byte is None:
+            return None
+        sign = 1
+        if chr(byte) == "-":
+            sign, byte = -1, self.read_byte()
+        digits = ""
+        while byte is not None and chr(byte).isdigit():
+            digits += chr(byte)
+            byte = self.read_byte()
+        if byte is not None:
+            self.unread_byte(byte)
+        if not digits:
+            return self.read_number()
+        return sign * int(digits)
+
+    def write_text(self, text: str):
+        for byte in text.encode():
+            self.write_byte(byte)
+
+    def write_char(self, code: int):
+        """Write a character code (as UTF-8 if it is not a byte)."""
+        if 0 <= code < 256:
+            self.write_byte(code)
+        else:
+            self.write_text(chr(code % 0x110000))
+
+
+class StreamIO(ProgramIO):
+    """The terminal (or whatever stdin and stdout are connected to)."""
+
+    def _read(self) -> Optional[int]:
+        self.flush()
+        data = sys.stdin.buffer.read(1)
+        return data[0] if data else None
+
+    def write_byte(self, byte: int):
+        sys.stdout.buffer.write(bytes([byte & 0xFF]))
+
+    def flush(self):
+        sys.stdout.flush()
+        sys.stdout.buffer.flush()
+
+
+class BufferIO(ProgramIO):
+    """Input from a fixed buffer, and output collected in `output`."""
+
+    def __init__(self, data: bytes = b""):
+        super().__init__()
+        self.data = data
+        self.position = 0
+        self.output = bytearray()
+
+    def _read(self) -> Optional[int]:
+        if self.position >= len(self.data):
+            return None
+        self.position += 1
+        return self.data[self.position - 1]
+
+    def write_byte(self, byte: int):
+        self.output.append(byte & 0xFF)
@@ -0,0 +1,4 @@
+coin::=~heads
+coin::=~tails
+::=
+coin
@@ -0,0 +1,5 @@
+Prints "Hi": the loop sets cell 1 to 72 and cell 2 to 32
+++++++++[>+++++++++>++++<<-]>.
++++++++++++++++++++++++++++++++++.
+>>+++ cell 3 is set and never read
+<<<[>+<-] never entered: cell 0 is 0 after the first loop
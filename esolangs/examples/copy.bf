@@ -0,0 +1,2 @@
+The same as cat but reading each byte into a new cell
+,[.>,]
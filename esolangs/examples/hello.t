@@ -0,0 +1,3 @@
+a::=~Hello, World!
+::=
+a
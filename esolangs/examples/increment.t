@@ -0,0 +1,5 @@
+0+::=1
+1+::=+0
+_+::=_1
+::=
+_1011+
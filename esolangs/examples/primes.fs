@@ -0,0 +1,13 @@
+\ The sieve of Eratosthenes: `n primes` prints the primes below n
+1000 constant size
+create sieve size cells allot
+
+: composite? ( n -- flag )  sieve + @ ;
+: strike ( n -- )  \ mark the multiples of n, from n squared
+    dup dup * begin dup size < while
+        true over sieve + !  over +
+    repeat 2drop ;
+: primes ( n -- )
+    size min 2 do
+        i composite? 0= if i . i strike then
+    loop ;
@@ -0,0 +1,2 @@
+Waits forever unless its input is empty or starts with a zero byte
+,[]
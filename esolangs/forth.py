@@ -0,0 +1,550 @@
+"""
+# Forth
+
+A small Forth: a data stack of integers, a dictionary of words, and
+colon definitions compiled into lists of instructions. Words are not
+case-sensitive, and true is -1.
+
+    : name ... ;            define a word (`recurse` calls the word being defined)
+    variable x  10 x !  x @     a variable (`+!` adds, `?` prints)
+    42 constant answer      a constant
+    create a 10 cells allot     an array (`a 3 +` is the address of a[3])
+    ( comment )  \\ comment
+
+Control flow only works inside definitions:
+
+    cond if ... else ... then
+    limit start do ... loop     (or n +loop; `i` and `j` are the indices, `leave` exits)
+    begin ... cond until
+    begin ... cond while ... repeat
+    begin ... again             (`exit` returns from the word)
+
+The rest of the words:
+
+    stack       dup drop swap over rot -rot nip tuck pick roll ?dup depth
+                2dup 2drop 2swap 2over
+    arithmetic  + - * / mod /mod */ negate abs min max 1+ 1- 2* 2/
+    logic       = <> < > <= >= 0= 0< 0> 0<> and or xor invert lshift rshift
+                true false
+    output      . emit cr space spaces .s ." text"
+    dictionary  words see
+
+`/` and `mod` round towards negative infinity. An error empties the
+stack and abandons any definition in progress, like most Forths.
+
+
+## REPL
+
+With no files (or with `--interactive`), Forth reads lines from the
+terminal and answers "ok" after each. Definitions may span lines. The
+REPL's own commands start with `#`, as `:` is taken: `#trace on` prints
+every word as it runs, with the stack after it, and `#help` lists the
+rest.
+
+
+## Usage
+
+    python3 forth.py examples/fizzbuzz.fs
+    python3 forth.py examples/primes.fs --eval "100 primes cr"
+    python3 forth.py --eval ": square dup * ; 7 square ."
+    python3 forth.py examples/primes.fs --interactive
+"""
+
+import argparse
+import os
+import sys
+from typing import Callable, Dict, List, NamedTuple, Optional, Tuple, Union
+
+from esolang_io import ProgramIO, StreamIO
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from repl.repl import Repl
+
+
+TRUE, FALSE = -1, 0
+
+
+class Instruction(NamedTuple):
+    # "call" (a Word), "literal" (a number), "string" (text to print), "branch" and "0branch"
+    # (where to go), "do" (where its loop ends), "loop" and "+loop" (where to go back to),
+    # "leave", or "exit"
+    kind: str
+    argument: Union["Word", int, str, None] = None
+
+
+class Word(NamedTuple):
+    name: str
+    builtin: Optional[Callable[["Forth"], None]]
+    code: List[Instruction]
+
+
+def binary(operation: Callable[[int, int], int]) -> Callable[["Forth"], None]:
+    def run(forth: "Forth"):
+        b, a = forth.pop(), forth.pop()
+        forth.push(operation(a, b))
+    return run
+
+
+def unary(operation: Callable[[int], int]) -> Callable[["Forth"], None]:
+    return lambda forth: forth.push(operation(forth.pop()))
+
+
+def flag(condition: bool) -> int:
+    return TRUE if condition else FALSE
+
+
+def divide(a: int, b: int) -> int:
+    if b == 0:
+        raise ValueError("division by zero")
+    return a // b
+
+
+def modulo(a: int, b: int) -> int:
+    if b == 0:
+        raise ValueError("division by zero")
+    return a % b
+
+
+def pick(forth: "Forth"):
+    n = forth.pop()
+    if not 0 <= n < len(forth.stack):
+        raise ValueError("stack underflow")
+    forth.push(forth.stack[-1 - n])
+
+
+def roll(forth: "Forth"):
+    n = forth.pop()
+    if not 0 <= n < len(forth.stack):
+        raise ValueError("stack underflow")
+    forth.push(forth.stack.pop(-1 - n))
+
+
+def divide_with_remainder(forth: "Forth"):
+    a, b = forth.pop_many(2)
+    forth.stack += [modulo(a, b), divide(a, b)]
+
+
+def multiply_divide(forth: "Forth"):
+    a, b, c = forth.pop_many(3)
+    forth.push(divide(a * b, c))
+
+
+def add_to(forth: "Forth"):
+    value, address = forth.pop_many(2)
+    forth.store(forth.fetch(address) + value, address)
+
+
+def rearrange(count: int, order: List[int]) -> Callable[["Forth"], None]:
+    """Pop count items and push them back in this order (0 is the deepest)."""
+    def run(forth: "Forth"):
+        items = forth.pop_many(count)
+        forth.stack += [items[i] for i in order]
+    return run
+
+
+BUILTINS: Dict[str, Callable[["Forth"], None]] = {
+    "dup": rearrange(1, [0, 0]),
+    "drop": rearrange(1, []),
+    "swap": rearrange(2, [1, 0]),
+    "over": rearrange(2, [0, 1, 0]),
+    "rot": rearrange(3, [1, 2, 0]),
+    "-rot": rearrange(3, [2, 0, 1]),
+    "nip": rearrange(2, [1]),
+    "tuck": rearrange(2, [1, 0, 1]),
+    "2dup": rearrange(2, [0, 1, 0, 1]),
+    "2drop": rearrange(2, []),
+    "2swap": rearrange(4, [2, 3, 0, 1]),
+    "2over": rearrange(4, [0, 1, 2, 3, 0, 1]),
+    "pick": pick,
+    "roll": roll,
+    "?dup": lambda forth: forth.push(forth.peek()) if forth.peek() != 0 else None,
+    "depth": lambda forth: forth.push(len(forth.stack)),
+    "+": binary(lambda a, b: a + b),
+    "-": binary(lambda a, b: a - b),
+    "*": binary(lambda a, b: a * b),
+    "/": binary(divide),
+    "mod": binary(modulo),
+    "/mod": divide_with_remainder,
+    "*/": multiply_divide,
+    "negate": unary(lambda a: -a),
+    "abs": unary(abs),
+    "min": binary(min),
+    "max": binary(max),
+    "1+": unary(lambda a: a + 1),
+    "1-": unary(lambda a: a - 1),
+    "2*": unary(lambda a: a * 2),
+    "2/": unary(lambda a: a // 2),
+    "=": binary(lambda a, b: flag(a == b)),
+    "<>": binary(lambda a, b: flag(a != b)),
+    "<": binary(lambda a, b: flag(a < b)),
+    ">": binary(lambda a, b: flag(a > b)),
+    "<=": binary(lambda a, b: flag(a <= b)),
+    ">=": binary(lambda a, b: flag(a >= b)),
+    "0=": unary(lambda a: flag(a == 0)),
+    "0<": unary(lambda a: flag(a < 0)),
+    "0>": unary(lambda a: flag(a > 0)),
+    "0<>": unary(lambda a: flag(a != 0)),
+    "and": binary(lambda a, b: a & b),
+    "or": binary(lambda a, b: a | b),
+    "xor": binary(lambda a, b: a ^ b),
+    "invert": unary(lambda a: ~a),
+    "lshift": binary(lambda a, b: a << b),
+    "rshift": binary(lambda a, b: a >> b),
+    "true": lambda forth: forth.push(TRUE),
+    "false": lambda forth: forth.push(FALSE),
+    ".": lambda forth: forth.io.write_text(f"{forth.pop()} "),
+    "emit": lambda forth: forth.io.write_char(forth.pop()),
+    "cr": lambda forth: forth.io.write_text("\n"),
+    "space": lambda forth: forth.io.write_text(" "),
+    "spaces": lambda forth: forth.io.write_text(" " * max(forth.pop(), 0)),
+    ".s": lambda forth: forth.io.write_text(f"<{len(forth.stack)}> " + "".join(f"{v} " for v in forth.stack)),
+    "!": lambda forth: forth.store(*forth.pop_many(2)),
+    "@": lambda forth: forth.push(forth.fetch(forth.pop())),
+    "+!": add_to,
+    "?": lambda forth: forth.io.write_text(f"{forth.fetch(forth.pop())} "),
+    ",": lambda forth: forth.memory.append(forth.pop()),
+    "here": lambda forth: forth.push(len(forth.memory)),
+    "allot": lambda forth: forth.memory.extend([0] * max(forth.pop(), 0)),
+    "cells": lambda forth: None,
+    "i": lambda forth: forth.push(forth.loop_index(0)),
+    "j": lambda forth: forth.push(forth.loop_index(1)),
+    "words": lambda forth: forth.io.write_text(" ".join(reversed(forth.dictionary)) + "\n"),
+}
+
+# Words that read the text after them, or that build definitions, rather than being compiled
+IMMEDIATE = {":", ";", "(", "\\", ".\"", "variable", "constant", "create", "see",
+             "if", "else", "then", "do", "loop", "+loop", "leave", "begin", "until", "while", "repeat", "again",
+             "exit", "recurse"}
+CONTROL = IMMEDIATE - {":", ";", "(", "\\", ".\"", "variable", "constant", "create", "see"}
+
+
+class Forth:
+    def __init__(self, io: ProgramIO, trace: bool = False):
+        self.io = io
+        self.trace = trace
+        self.stack: List[int] = []
+        self.memory: List[int] = []
+        self.loops: List[Tuple[int, int, int]] = []     # (index, limit, exit) of each running do loop
+        self.dictionary: Dict[str, Word] = {name: Word(name, run, []) for name, run in BUILTINS.items()}
+        self.text = ""
+        self.position = 0
+        # The definition being compiled, and its unfinished control structures
+        self.defining: Optional[Word] = None
+        self.control: List[Tuple[str, int]] = []
+
+    ############################################################################
+    ###   STACK AND MEMORY   ###
+    ############################################################################
+
+    def push(self, value: int):
+        self.stack.append(value)
+
+    def pop(self) -> int:
+        if not self.stack:
+            raise ValueError("stack underflow")
+        return self.stack.pop()
+
+    def peek(self) -> int:
+        if not self.stack:
+            raise ValueError("stack underflow")
+        return self.stack[-1]
+
+    def pop_many(self, count: int) -> List[int]:
+        """The top count items, deepest first."""
+        if len(self.stack) < count:
+            raise ValueError("stack underflow")
+        items = self.stack[len(self.stack) - count:]
+        del self.stack[len(self.stack) - count:]
+        return items
+
+    def fetch(self, address: int) -> int:
+        if not 0 <= address < len(self.memory):
+            raise ValueError(f"invalid address {address}")
+        return self.memory[address]
+
+    def store(self, value: int, address: int):
+        self.fetch(address)
+        self.memory[address] = value
+
+    def loop_index(self, depth: int) -> int:
+        if len(self.loops) <= depth:
+            raise ValueError("not inside enough do loops")
+        return self.loops[-1 - depth][0]
+
+    ############################################################################
+    ###   READING   ###
+    ############################################################################
+
+    def next_word(self) -> Optional[str]:
+        text = self.text
+        while self.position < len(text) and text[self.position].isspace():
+            self.position += 1
+        if self.position >= len(text):
+            return None
+        start = self.position
+        while self.position < len(text) and not text[self.position].isspace():
+            self.position += 1
+        return text[start:self.position]
+
+    def read_until(self, end: str) -> str:
+        """The text up to the next `end` (skipping the one space after the word before)."""
+        start = self.position + 1
+        stop = self.text.find(end, start)
+        if stop < 0:
+            stop = len(self.text)
+        self.position = stop + len(end)
+        return self.text[start:stop]
+
+    def read_name(self, after: str) -> str:
+        name = self.next_word()
+        if name is None:
+            raise ValueError(f"'{after}' needs a name after it")
+        return name.lower()
+
+    ############################################################################
+    ###   INTERPRETING AND COMPILING   ###
+    ############################################################################
+
+    def evaluate(self, text: str):
+        self.text, self.position = text, 0
+        try:
+            while True:
+                word = self.next_word()
+                if word is None:
+                    break
+                self.interpret(word)
+        except ValueError:
+            self.stack.clear()
+            self.loops.clear()
+            self.defining = None
+            self.control.clear()
+            raise
+        finally:
+            self.io.flush()
+
+    def interpret(self, token: str):
+        word = token.lower()
+        if word in IMMEDIATE:
+            self.immediate(word)
+        elif word in self.dictionary:
+            if self.defining is not None:
+                self.defining.code.append(Instruction("call", self.dictionary[word]))
+            else:
+                self.execute(self.dictionary[word])
+        else:
+            try:
+                value = int(token)
+            except ValueError:
+                raise ValueError(f"unknown word '{token}'") from None
+            if self.defining is not None:
+                self.defining.code.append(Instruction("literal", value))
+            else:
+                self.push(value)
+
+    def immediate(self, word: str):
+        if word == "(":
+            self.read_until(")")
+            return
+        if word == "\\":
+            self.read_until("\n")
+            return
+        if word in CONTROL or word == ";":
+            if self.defining is None:
+                raise ValueError(f"'{word}' only works inside a definition")
+            if word == ";":
+                if self.control:
+                    raise ValueError(f"'{self.control[-1][0]}' in '{self.defining.name}' is never closed")
+                self.dictionary.pop(self.defining.name, None)
+                self.dictionary[self.defining.name] = self.defining
+                self.defining = None
+            else:
+                self.compile_control(word)
+            return
+        if word == ".\"":
+            text = self.read_until("\"")
+            if self.defining is not None:
+                self.defining.code.append(Instruction("string", text))
+            else:
+                self.io.write_text(text)
+            return
+        if self.defining is not None:
+            raise ValueError(f"'{word}' cannot be used inside a definition")
+        if word == ":":
+            self.defining = Word(self.read_name(":"), None, [])
+        elif word == "variable":
+            self.define_value(self.read_name(word), len(self.memory))
+            self.memory.append(0)
+        elif word == "create":
+            self.define_value(self.read_name(word), len(self.memory))
+        elif word == "constant":
+            name = self.read_name(word)
+            self.define_value(name, self.pop())
+        else:
+            self.see(self.read_name(word))
+
+    def define_value(self, name: str, value: int):
+        self.dictionary.pop(name, None)
+        self.dictionary[name] = Word(name, None, [Instruction("literal", value)])
+
+    def compile_control(self, word: str):
+        code = self.defining.code
+
+        def expect(*kinds: str) -> int:
+            if not self.control or self.control[-1][0] not in kinds:
+                raise ValueError(f"'{word}' without {' or '.join(repr(k) for k in kinds)}")
+            return self.control.pop()[1]
+
+        if word == "if":
+            self.control.append(("if", len(code)))
+            code.append(Instruction("0branch"))
+        elif word == "else":
+            start = expect("if")
+            self.control.append(("else", len(code)))
+            code.append(Instruction("branch"))
+            code[start] = Instruction("0branch", len(code))
+        elif word == "then":
+            start = expect("if", "else")
+            code[start] = Instruction(code[start].kind, len(code))
+        elif word == "do":
+            self.control.append(("do", len(code)))
+            code.append(Instruction("do"))
+        elif word in ("loop", "+loop"):
+            start = expect("do")
+            code.append(Instruction(word, start + 1))
+            code[start] = Instruction("do", len(code))
+        elif word == "leave":
+            if not any(kind == "do" for kind, _ in self.control):
+                raise ValueError("'leave' outside a do loop")
+            code.append(Instruction("leave"))
+        elif word == "begin":
+            self.control.append(("begin", len(code)))
+        elif word == "until":
+            code.append(Instruction("0branch", expect("begin")))
+        elif word == "again":
+            code.append(Instruction("branch", expect("begin")))
+        elif word == "while":
+            start = expect("begin")
+            self.control += [("begin", start), ("while", len(code))]
+            code.append(Instruction("0branch"))
+        elif word == "repeat":
+            exit_branch = expect("while")
+            code.append(Instruction("branch", expect("begin")))
+            code[exit_branch] = Instruction("0branch", len(code))
+        elif word == "exit":
+            code.append(Instruction("exit"))
+        elif word == "recurse":
+            code.append(Instruction("call", self.defining))
+
+    ############################################################################
+    ###   RUNNING   ###
+    ############################################################################
+
+    def execute(self, word: Word):
+        if word.builtin is not None:
+            word.builtin(self)
+            if self.trace:
+                print(f"    {word.name:<12} {self.stack}")
+            return
+        loops = len(self.loops)
+        try:
+            self.run(word.code)
+        finally:
+            del self.loops[loops:]
+        if self.trace:
+            print(f"    {word.name:<12} {self.stack}  (end)")
+
+    def run(self, code: List[Instruction]):
+        pc = 0
+        while pc < len(code):
+            kind, argument = code[pc]
+            pc += 1
+            if kind == "call":
+                self.execute(argument)
+            elif kind == "literal":
+                self.push(argument)
+            elif kind == "string":
+                self.io.write_text(argument)
+            elif kind == "branch":
+                pc = argument
+            elif kind == "0branch":
+                if self.pop() == 0:
+                    pc = argument
+            elif kind == "do":
+                start, limit = self.pop(), self.pop()
+                self.loops.append((start, limit, argument))
+            elif kind in ("loop", "+loop"):
+                index, limit, end = self.loops[-1]
+                step = 1 if kind == "loop" else self.pop()
+                new_index = index + step
+                finished = index < limit <= new_index if step >= 0 else new_index < limit <= index
+                if finished:
+                    self.loops.pop()
+                else:
+                    self.loops[-1] = (new_index, limit, end)
+                    pc = argument
+            elif kind == "leave":
+                pc = self.loops.pop()[2]
+            elif kind == "exit":
+                return
+
+    def see(self, name: str):
+        if name not in self.dictionary:
+            raise ValueError(f"unknown word '{name}'")
+        word = self.dictionary[name]
+        if word.builtin is not None:
+            self.io.write_text(f"{name} is built in\n")
+            return
+        lines = [f": {name}"]
+        for index, (kind, argument) in enumerate(word.code):
+            shown = argument.name if isinstance(argument, Word) else ("" if argument is None else repr(argument))
+            lines.append(f"    {index:>3}  {kind:<8} {shown}")
+        self.io.write_text("\n".join(lines) + "\n;\n")
+
+
+################################################################################
+###   CLI   ###
+################################################################################
+
+
+def start_repl(forth: Forth):
+    def evaluate(line: str):
+        if line.strip().lower() == "bye":
+            repl.stop()
+            return
+        forth.evaluate(line)
+        print(" compiled" if forth.defining is not None else " ok")
+
+    def set_trace(argument: str):
+        forth.trace = argument != "off"
+
+    repl = Repl("", evaluate, command_prefix="#")
+    repl.add_command("trace", set_trace, "'on' or 'off': print every word and the stack as it runs")
+    repl.add_command("stack", lambda _: print(forth.stack), "print the stack")
+    print("Forth. Type bye to leave, or #help for the REPL's commands.")
+    repl.run()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="A small Forth")
+    parser.add_argument("files", type=str, nargs="*", help="source files to run in order")
+    parser.add_argument("--eval", type=str, default=None, help="run this code after the files")
+    parser.add_argument("--interactive", action="store_true", help="start the REPL after the files")
+    parser.add_argument("--trace", action="store_true", help="print every word and the stack as it runs")
+    args = parser.parse_args()
+
+    sys.setrecursionlimit(max(sys.getrecursionlimit(), 10_000))
+    forth = Forth(StreamIO(), args.trace)
+    try:
+        for path in args.files:
+            with open(path) as f:
+                forth.evaluate(f.read())
+        if args.eval is not None:
+            forth.evaluate(args.eval)
+    except ValueError as e:
+        parser.exit(1, f"\nerror: {e}\n")
+    if args.interactive or not args.files and args.eval is None:
+        start_repl(forth)
+
+
+if __name__ == "__main__":
+    main()
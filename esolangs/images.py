@@ -0,0 +1,222 @@
+"""
+# Image Readers
+
+Just enough PNG and GIF decoding to load Piet programs without any
+dependencies. Both return rows of (r, g, b) pixels, ignoring any
+transparency.
+
+PNG: every colour type, at any bit depth (16-bit channels keep their
+high byte), but not interlaced images. GIF: only the first frame, which
+is drawn onto the background colour if it does not cover the screen.
+"""
+
+import struct
+import zlib
+from typing import List, Optional, Tuple
+
+
+Colour = Tuple[int, int, int]
+PNG_SIGNATURE: bytes = b"\x89PNG\r\n\x1a\n"
+CHANNELS = {0: 1, 2: 3, 3: 1, 4: 2, 6: 4}
+
+
+def read_image(path: str) -> List[List[Colour]]:
+    with open(path, "rb") as f:
+        data = f.read()
+    if data.startswith(PNG_SIGNATURE):
+        return read_png(data)
+    if data[:6] in (b"GIF87a", b"GIF89a"):
+        return read_gif(data)
+    raise ValueError(f"{path} is not a PNG or GIF")
+
+
+################################################################################
+###   PNG   ###
+################################################################################
+
+
+def _paeth(a: int, b: int, c: int) -> int:
+    p = a + b - c
+    pa, pb, pc = abs(p - a), abs(p - b), abs(p - c)
+    if pa <= pb and pa <= pc:
+        return a
+    return b if pb <= pc else c
+
+
+def _unfilter(data: bytes, height: int, stride: int, bpp: int) -> List[bytearray]:
+    rows: List[bytearray] = []
+    previous = bytearray(stride)
+    for y in range(height):
+        start = y * (stride + 1)
+        kind, row = data[start], bytearray(data[start + 1:start + 1 + stride])
+        for i in range(stride):
+            left = row[i - bpp] if i >= bpp else 0
+            up = previous[i]
+            if kind == 1:
+                row[i] = (row[i] + left) & 0xFF
+            elif kind == 2:
+                row[i] = (row[i] + up) & 0xFF
+            elif kind == 3:
+                row[i] = (row[i] + (left + up) // 2) & 0xFF
+            elif kind == 4:
+                up_left = previous[i - bpp] if i >= bpp else 0
+                row[i] = (row[i] + _paeth(left, up, up_left)) & 0xFF
+            elif kind != 0:
+                raise ValueError(f"row {y} has an unknown filter type {kind}")
+        rows.append(row)
+        previous = row
+    return rows
+
+
+def _samples(row: bytearray, count: int, depth: int) -> List[int]:
+    """Split a row into count samples of depth bits (16-bit samples keep their high byte)."""
+    if depth == 8:
+        return list(row[:count])
+    if depth == 16:
+        return list(row[0:2 * count:2])
+    per_byte = 8 // depth
+    mask = (1 << depth) - 1
+    return [(row[i // per_byte] >> (8 - depth * (i % per_byte + 1))) & mask for i in range(count)]
+
+
+def read_png(data: bytes) -> List[List[Colour]]:
+    position = len(PNG_SIGNATURE)
+    header: Optional[tuple] = None
+    palette: List[Colour] = []
+    compressed: List[bytes] = []
+    while position < len(data):
+        length, kind = struct.unpack(">I4s", data[position:position + 8])
+        body = data[position + 8:position + 8 + length]
+        position += 12 + length
+        if kind == b"IHDR":
+            header = struct.unpack(">IIBBBBB", body)
+        elif kind == b"PLTE":
+            palette = [tuple(body[i:i + 3]) for i in range(0, len(body) - 2, 3)]
+        elif kind == b"IDAT":
+            compressed.append(body)
+        elif kind == b"IEND":
+            break
+    if header is None:
+        raise ValueError("the PNG has no header")
+    width, height, depth, colour_type, _, _, interlace = header
+    if colour_type not in CHANNELS:
+        raise ValueError(f"unknown PNG colour type {colour_type}")
+    if interlace:
+        raise ValueError("interlaced PNGs are not supported")
+    if colour_type == 3 and not palette:
+        raise ValueError("the PNG has no palette")
+
+    channels = CHANNELS[colour_type]
+    stride = (width * channels * depth + 7) // 8
+    bpp = max(1, channels * depth // 8)
+    rows = _unfilter(zlib.decompress(b"".join(compressed)), height, stride, bpp)
+    top = (1 << min(depth, 8)) - 1
+    pixels: List[List[Colour]] = []
+    for row in rows:
+        samples = _samples(row, width * channels, depth)
+        if colour_type == 3:
+            pixels.append([palette[s] for s in samples])
+            continue
+        if depth < 8:
+            samples = [s * 255 // top for s in samples]
+        line: List[Colour] = []
+        for x in range(width):
+            pixel = samples[x * channels:(x + 1) * channels]
+            line.append((pixel[0],) * 3 if channels <= 2 else tuple(pixel[:3]))
+        pixels.append(line)
+    return pixels
+
+
+################################################################################
+###   GIF   ###
+################################################################################
+
+
+def _sub_blocks(data: bytes, position: int) -> Tuple[bytes, int]:
+    """Join the length-prefixed sub-blocks starting at position, up to the empty one."""
+    parts: List[bytes] = []
+    while data[position] != 0:
+        parts.append(data[position + 1:position + 1 + data[position]])
+        position += 1 + data[position]
+    return b"".join(parts), position + 1
+
+
+def _lzw_decode(data: bytes, minimum_size: int) -> bytearray:
+    clear, end = 1 << minimum_size, (1 << minimum_size) + 1
+    table: List[bytes] = [bytes([i]) for i in range(clear)] + [b"", b""]
+    size = minimum_size + 1
+    output = bytearray()
+    previous: Optional[bytes] = None
+    buffer, bits = 0, 0
+    for byte in data:
+        buffer |= byte << bits
+        bits += 8
+        while bits >= size:
+            code = buffer & ((1 << size) - 1)
+            buffer >>= size
+            bits -= size
+            if code == clear:
+                table, size, previous = table[:clear + 2], minimum_size + 1, None
+                continue
+            if code == end:
+                return output
+            if code < len(table):
+                entry = table[code]
+                if previous is not None:
+                    table.append(previous + entry[:1])
+            elif code == len(table) and previous is not None:
+                entry = previous + previous[:1]
+                table.append(entry)
+            else:
+                raise ValueError("the GIF's image data is corrupt")
+            output += entry
+            previous = entry
+            if len(table) == 1 << size and size < 12:
+                size += 1
+    return output
+
+
+def read_gif(data: bytes) -> List[List[Colour]]:
+    width, height, flags, background = struct.unpack("<HHBB", data[6:12])
+    position = 13
+    global_table: List[Colour] = []
+    if flags & 0x80:
+        count = 2 << (flags & 7)
+        global_table = [tuple(data[position + 3 * i:position + 3 * i + 3]) for i in range(count)]
+        position += 3 * count
+    fill = global_table[background] if background < len(global_table) else (0, 0, 0)
+    pixels = [[fill] * width for _ in range(height)]
+
+    while position < len(data):
+        kind = data[position]
+        if kind == 0x21:            # an extension, which we skip
+            _, position = _sub_blocks(data, position + 2)
+        elif kind == 0x2C:
+            left, top, frame_width, frame_height, frame_flags = \
+                struct.unpack("<HHHHB", data[position + 1:position + 10])
+            position += 10
+            table = global_table
+            if frame_flags & 0x80:
+                count = 2 << (frame_flags & 7)
+                table = [tuple(data[position + 3 * i:position + 3 * i + 3]) for i in range(count)]
+                position += 3 * count
+            if not table:
+                raise ValueError("the GIF has no colour table")
+            minimum_size = data[position]
+            compressed, position = _sub_blocks(data, position + 1)
+            indices = _lzw_decode(compressed, minimum_size)
+            rows = list(range(frame_height))
+            if frame_flags & 0x40:  # interlaced: every 8th row from 0, then from 4, then every 4th from 2, ...
+                rows = list(range(0, frame_height, 8)) + list(range(4, frame_height, 8)) \
+                    + list(range(2, frame_height, 4)) + list(range(1, frame_height, 2))
+            for i, y in enumerate(rows):
+                for x in range(frame_width):
+                    if 0 <= top + y < height and 0 <= left + x < width and i * frame_width + x < len(indices):
+                        index = indices[i * frame_width + x]
+                        pixels[top + y][left + x] = table[index] if index < len(table) else (0, 0, 0)
+            return pixels
+        elif kind == 0x3B:
+            break
+        else:
+            raise ValueError(f"unknown GIF block 0x{kind:02x}")
+    raise ValueError("the GIF has no image")
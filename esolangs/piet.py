@@ -0,0 +1,422 @@
+"""
+# Piet
+
+David Morgan-Mar's Piet: programs are images. The image is split into
+codels (squares of codel-size pixels), and connected codels of the same
+colour form a colour block. There are 18 colours in a cycle of six hues
+(red, yellow, green, cyan, blue, magenta) and three lightnesses (light,
+normal, dark), plus white and black. Other colours count as white (or
+as black, or are an error, with `--unknown`).
+
+The interpreter moves from block to block. Its direction pointer (DP)
+is right, down, left, or up, and its codel chooser (CC) is left or
+right. It leaves a block from the edge furthest in the DP's direction,
+at the codel of that edge furthest to the CC's side (looking along the
+DP). When the next codel is black or off the image, it toggles the CC,
+then rotates the DP clockwise, and so on alternately; after eight
+failures, the program ends. White blocks are passed straight through
+without doing anything.
+
+Moving between two coloured blocks runs the command given by how far
+the hue and the lightness moved on:
+
+    hue \\ lightness  0           1           2
+    0                           push        pop
+    1               add         subtract    multiply
+    2               divide      mod         not
+    3               greater     pointer     switch
+    4               duplicate   roll        in_number
+    5               in_char     out_number  out_char
+
+`push` pushes the size (in codels) of the block being left. A command
+that needs more of the stack than there is, that divides by 0, or that
+reads past the end of the input does nothing. `divide` and `mod` round
+towards negative infinity, so `mod` has the divisor's sign.
+
+
+## Building Images
+
+Images are fiddly to draw by hand, so `build` turns a straight-line
+program (one command per line like `push 72` or `out_char`, `;`
+comments) into a PNG: a single row of blocks ending in a block that
+traps the interpreter. `push` takes any integer, or a character like
+'H'. `pointer` and `switch` turn the interpreter, so a single row cannot
+use them.
+
+
+## Usage
+
+    python3 piet.py run examples/hello.png --codel-size 4
+    python3 piet.py run examples/add.png --codel-size 4 --input "3 4" --trace
+    python3 piet.py build examples/hello.piet -o examples/hello.png --codel-size 4
+"""
+
+import argparse
+import math
+import os
+import re
+import sys
+from typing import Dict, List, NamedTuple, Optional, Set, Tuple, Union
+
+from esolang_io import BufferIO, ProgramIO, StreamIO
+from images import Colour, read_image
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from cellular_automata.png import write_png
+from interpreters.interpreter import Interpreter
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+HUES = ["red", "yellow", "green", "cyan", "blue", "magenta"]
+LIGHTNESSES = ["light", "normal", "dark"]
+PALETTE: List[List[Colour]] = [
+    [(0xFF, 0xC0, 0xC0), (0xFF, 0xFF, 0xC0), (0xC0, 0xFF, 0xC0),
+     (0xC0, 0xFF, 0xFF), (0xC0, 0xC0, 0xFF), (0xFF, 0xC0, 0xFF)],
+    [(0xFF, 0x00, 0x00), (0xFF, 0xFF, 0x00), (0x00, 0xFF, 0x00),
+     (0x00, 0xFF, 0xFF), (0x00, 0x00, 0xFF), (0xFF, 0x00, 0xFF)],
+    [(0xC0, 0x00, 0x00), (0xC0, 0xC0, 0x00), (0x00, 0xC0, 0x00),
+     (0x00, 0xC0, 0xC0), (0x00, 0x00, 0xC0), (0xC0, 0x00, 0xC0)],
+]
+WHITE: Colour = (0xFF, 0xFF, 0xFF)
+BLACK: Colour = (0x00, 0x00, 0x00)
+COMMANDS: List[List[Optional[str]]] = [
+    [None, "push", "pop"],
+    ["add", "subtract", "multiply"],
+    ["divide", "mod", "not"],
+    ["greater", "pointer", "switch"],
+    ["duplicate", "roll", "in_number"],
+    ["in_char", "out_number", "out_char"],
+]
+DIRECTIONS: List[Tuple[int, int]] = [(1, 0), (0, 1), (-1, 0), (0, -1)]     # clockwise from right
+DP_NAMES = ["right", "down", "left", "up"]
+CC_NAMES = ["left", "right"]
+
+# A codel is (hue, lightness), or one of these
+WHITE_CODEL, BLACK_CODEL = "white", "black"
+Codel = Union[Tuple[int, int], str]
+
+
+class Block(NamedTuple):
+    colour: Codel
+    codels: List[Tuple[int, int]]
+
+
+def colour_name(codel: Codel) -> str:
+    if codel in (WHITE_CODEL, BLACK_CODEL):
+        return codel
+    hue, lightness = codel
+    return HUES[hue] if lightness == 1 else f"{LIGHTNESSES[lightness]} {HUES[hue]}"
+
+
+################################################################################
+###   IMAGES TO CODELS   ###
+################################################################################
+
+
+def guess_codel_size(pixels: List[List[Colour]]) -> int:
+    """The largest size that divides every run of one colour, across or down."""
+    size = 0
+    for lines in (pixels, list(zip(*pixels))):
+        for line in lines:
+            run = 1
+            for previous, pixel in zip(line, line[1:]):
+                if pixel == previous:
+                    run += 1
+                else:
+                    size, run = math.gcd(size, run), 1
+            size = math.gcd(size, run)
+    return max(size, 1)
+
+
+def to_codels(pixels: List[List[Colour]], codel_size: int, unknown: str) -> List[List[Codel]]:
+    height, width = len(pixels), len(pixels[0]) if pixels else 0
+    if width % codel_size or height % codel_size:
+        raise ValueError(f"a {width}x{height} image does not split into codels of size {codel_size}")
+    colours: Dict[Colour, Codel] = {WHITE: WHITE_CODEL, BLACK: BLACK_CODEL}
+    for lightness, row in enumerate(PALETTE):
+        for hue, colour in enumerate(row):
+            colours[colour] = (hue, lightness)
+    codels: List[List[Codel]] = []
+    for y in range(0, height, codel_size):
+        line: List[Codel] = []
+        for x in range(0, width, codel_size):
+            colour = pixels[y][x]
+            if colour not in colours and unknown == "error":
+                raise ValueError(f"the pixel at ({x}, {y}) is #{bytes(colour).hex()}, which is not a Piet colour")
+            line.append(colours.get(colour, WHITE_CODEL if unknown == "white" else BLACK_CODEL))
+        codels.append(line)
+    return codels
+
+
+################################################################################
+###   INTERPRETER   ###
+################################################################################
+
+
+class Piet(Interpreter):
+    def __init__(self, codels: List[List[Codel]], io: ProgramIO):
+        super().__init__(io)
+        if not codels or codels[0][0] in (WHITE_CODEL, BLACK_CODEL):
+            raise ValueError("the program must start (at the top left) on a coloured codel")
+        self.codels = codels
+        self.height, self.width = len(codels), len(codels[0])
+        self.blocks: Dict[Tuple[int, int], Block] = {}
+        self.stack: List[int] = []
+        self.x, self.y = 0, 0
+        self.dp, self.cc = 0, 0
+        # Where the last step started, and its command
+        self.last: Optional[Tuple[int, int, Optional[str]]] = None
+
+    def block(self, x: int, y: int) -> Block:
+        if (x, y) not in self.blocks:
+            colour = self.codels[y][x]
+            block = Block(colour, [])
+            seen: Set[Tuple[int, int]] = {(x, y)}
+            frontier = [(x, y)]
+            while frontier:
+                cx, cy = frontier.pop()
+                block.codels.append((cx, cy))
+                for dx, dy in DIRECTIONS:
+                    nx, ny = cx + dx, cy + dy
+                    if (nx, ny) not in seen and self.passable(nx, ny) and self.codels[ny][nx] == colour:
+                        seen.add((nx, ny))
+                        frontier.append((nx, ny))
+            for codel in block.codels:
+                self.blocks[codel] = block
+        return self.blocks[(x, y)]
+
+    def passable(self, x: int, y: int) -> bool:
+        return 0 <= x < self.width and 0 <= y < self.height and self.codels[y][x] != BLACK_CODEL
+
+    def exit_codel(self, block: Block) -> Tuple[int, int]:
+        dx, dy = DIRECTIONS[self.dp]
+        cx, cy = DIRECTIONS[(self.dp + (1 if self.cc else -1)) % 4]
+        return max(block.codels, key=lambda c: (c[0] * dx + c[1] * dy, c[0] * cx + c[1] * cy))
+
+    def turn(self, attempt: int):
+        if attempt % 2 == 0:
+            self.cc ^= 1
+        else:
+            self.dp = (self.dp + 1) % 4
+
+    def slide(self, x: int, y: int) -> Optional[Tuple[int, int]]:
+        """Go straight through white from (x, y), turning when blocked; None if it loops."""
+        seen: Set[Tuple[int, int, int, int]] = set()
+        while self.codels[y][x] == WHITE_CODEL:
+            dx, dy = DIRECTIONS[self.dp]
+            if self.passable(x + dx, y + dy):
+                x, y = x + dx, y + dy
+                continue
+            if (x, y, self.dp, self.cc) in seen:
+                return None
+            seen.add((x, y, self.dp, self.cc))
+            self.cc ^= 1
+            self.dp = (self.dp + 1) % 4
+        return x, y
+
+    def step(self) -> Optional[str]:
+        """Move to the next block and run its command, which is returned."""
+        block = self.block(self.x, self.y)
+        self.steps += 1
+        self.last = (self.x, self.y, None)
+        for attempt in range(8):
+            x, y = self.exit_codel(block)
+            dx, dy = DIRECTIONS[self.dp]
+            if self.passable(x + dx, y + dy):
+                break
+            self.turn(attempt)
+        else:
+            self.halted = True
+            return None
+        x, y = x + dx, y + dy
+        if self.codels[y][x] == WHITE_CODEL:
+            end = self.slide(x, y)
+            if end is None:
+                self.halted = True
+                return None
+            self.x, self.y = end
+            return None
+        self.x, self.y = x, y
+        hue, lightness = block.colour
+        new_hue, new_lightness = self.codels[y][x]
+        command = COMMANDS[(new_hue - hue) % 6][(new_lightness - lightness) % 3]
+        self.last = (self.last[0], self.last[1], command)
+        self.execute(command, len(block.codels))
+        return command
+
+    def execute(self, command: str, size: int):
+        stack = self.stack
+        needs = {"push": 0, "in_number": 0, "in_char": 0,
+                 "pop": 1, "not": 1, "pointer": 1, "switch": 1, "duplicate": 1,
+                 "out_number": 1, "out_char": 1}.get(command, 2)
+        if len(stack) < needs:
+            return
+        if command == "push":
+            stack.append(size)
+        elif command == "pop":
+            stack.pop()
+        elif command in ("add", "subtract", "multiply", "divide", "mod", "greater"):
+            a, b = stack[-1], stack[-2]
+            if command in ("divide", "mod") and a == 0:
+                return
+            del stack[-2:]
+            stack.append({"add": lambda: b + a, "subtract": lambda: b - a, "multiply": lambda: b * a,
+                          "divide": lambda: b // a, "mod": lambda: b % a,
+                          "greater": lambda: int(b > a)}[command]())
+        elif command == "not":
+            stack.append(int(stack.pop() == 0))
+        elif command == "pointer":
+            self.dp = (self.dp + stack.pop()) % 4
+        elif command == "switch":
+            self.cc ^= abs(stack.pop()) % 2
+        elif command == "duplicate":
+            stack.append(stack[-1])
+        elif command == "roll":
+            rolls, depth = stack[-1], stack[-2]
+            if depth < 0 or depth > len(stack) - 2:
+                return
+            del stack[-2:]
+            if depth > 0:
+                rolls %= depth
+                stack[-depth:] = stack[len(stack) - rolls:] + stack[-depth:len(stack) - rolls]
+        elif command == "in_number":
+            number = self.io.read_number()
+            if number is not None:
+                stack.append(number)
+        elif command == "in_char":
+            byte = self.io.read_byte()
+            if byte is not None:
+                stack.append(byte)
+        elif command == "out_number":
+            self.io.write_text(str(stack.pop()))
+        elif command == "out_char":
+            self.io.write_char(stack.pop())
+
+    def snapshot(self) -> str:
+        """The last step: the block it left and the one it came to, and its command."""
+        x, y, command = self.last or (self.x, self.y, None)
+        if self.halted:
+            moved = "end"
+        else:
+            moved = "" if self.last is None else colour_name(self.codels[self.y][self.x])
+        return (f"({x:>3}, {y:>3})  {colour_name(self.codels[y][x]):>13} -> {moved:<13}  {command or '':<10}  "
+                f"dp {DP_NAMES[self.dp]:<5}  cc {CC_NAMES[self.cc]:<5}  {self.stack}")
+
+
+################################################################################
+###   STRAIGHT-LINE PROGRAMS   ###
+################################################################################
+
+
+FLAT_COMMANDS: Dict[str, Tuple[int, int]] = {
+    command: (hue, lightness)
+    for hue, row in enumerate(COMMANDS) for lightness, command in enumerate(row)
+    if command not in (None, "pointer", "switch")
+}
+
+
+def parse_straight_line(text: str) -> List[Tuple[str, int]]:
+    """(command, block size) pairs; each push becomes pushes of at most 10 codels."""
+    commands: List[Tuple[str, int]] = []
+    errors: List[str] = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        line = re.sub(r"('.')|;.*", lambda m: m.group(1) or "", line).strip()
+        if not line:
+            continue
+        words = line.split(None, 1)
+        if words[0] in ("pointer", "switch"):
+            errors.append(f"line {line_number}: {words[0]} turns the interpreter, which a single row cannot follow")
+        elif words[0] not in FLAT_COMMANDS:
+            errors.append(f"line {line_number}: '{words[0]}' is not a Piet command")
+        elif words[0] != "push":
+            if len(words) > 1:
+                errors.append(f"line {line_number}: {words[0]} takes no argument")
+            commands.append((words[0], 1))
+        else:
+            match = re.fullmatch(r"'(.)'|(-?\d+)", words[1].strip()) if len(words) > 1 else None
+            if match is None:
+                errors.append(f"line {line_number}: push needs a number or a character")
+                continue
+            commands += push(ord(match.group(1)) if match.group(1) is not None else int(match.group(2)))
+    if errors:
+        raise ValueError("\n".join(errors))
+    if not commands:
+        raise ValueError("the program is empty")
+    return commands
+
+
+def push(n: int) -> List[Tuple[str, int]]:
+    if n < 0:
+        return push(0) + push(-n) + [("subtract", 1)]
+    if n == 0:
+        return [("push", 1), ("not", 1)]
+    if n <= 10:
+        return [("push", n)]
+    return push(n // 10) + [("push", 10), ("multiply", 1)] + (push(n % 10) + [("add", 1)] if n % 10 else [])
+
+
+def build(commands: List[Tuple[str, int]]) -> List[List[Colour]]:
+    """One row of blocks, and a final block that every direction leads out of into black."""
+    hue, lightness = 0, 0
+    row: List[Colour] = []
+    for command, size in commands:
+        row += [PALETTE[lightness][hue]] * size
+        dh, dl = FLAT_COMMANDS[command]
+        hue, lightness = (hue + dh) % 6, (lightness + dl) % 3
+    last = PALETTE[lightness][hue]
+    # The final block is (x, 0), (x, 1), (x - 1, 1), and (x - 1, 2), where x is the last column
+    width = len(row) + 1
+    pixels = [row + [last], [BLACK] * (width - 2) + [last, last], [BLACK] * (width - 2) + [last, BLACK]]
+    return pixels
+
+
+################################################################################
+###   CLI   ###
+################################################################################
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Piet interpreter")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+
+    run_parser = subparsers.add_parser("run", help="run a PNG or GIF program")
+    run_parser.add_argument("program", type=str)
+    run_parser.add_argument("--codel-size", type=int, default=1, help="pixels per codel side, or 0 to guess")
+    run_parser.add_argument("--unknown", choices=["white", "black", "error"], default="white",
+                            help="what to do with colours that are not Piet's")
+    run_parser.add_argument("--input", type=str, default=None, help="use this as the input instead of stdin")
+    run_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    run_parser.add_argument("--trace", action="store_true", help="print every step with the DP, CC, and stack")
+
+    build_parser = subparsers.add_parser("build", help="draw a straight-line program as a PNG")
+    build_parser.add_argument("program", type=str)
+    build_parser.add_argument("-o", "--output", type=str, required=True)
+    build_parser.add_argument("--codel-size", type=int, default=1)
+    args = parser.parse_args()
+
+    if args.codel_size < 0 or args.command == "build" and args.codel_size == 0:
+        parser.error("the codel size must be positive")
+    try:
+        if args.command == "build":
+            with open(args.program) as f:
+                pixels = build(parse_straight_line(f.read()))
+            size = args.codel_size
+            write_png(args.output, [[p for p in row for _ in range(size)] for row in pixels for _ in range(size)])
+            return
+        pixels = read_image(args.program)
+        codel_size = args.codel_size or guess_codel_size(pixels)
+        piet = Piet(to_codels(pixels, codel_size, args.unknown), StreamIO() if args.input is None
+                    else BufferIO(args.input.encode()))
+        try:
+            piet.run(args.max_steps, args.trace)
+        finally:
+            if isinstance(piet.io, BufferIO):
+                print(piet.io.output.decode(errors="replace"), end="")
+        if not piet.halted:
+            print(f"\nstopped after {piet.steps} steps")
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+
+
+if __name__ == "__main__":
+    main()
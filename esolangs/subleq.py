@@ -0,0 +1,344 @@
+"""
+# Subleq
+
+A one-instruction computer. Memory is a row of integers, and every
+instruction is three of them, a b c, meaning
+
+    mem[b] -= mem[a]; if mem[b] <= 0, jump to c (otherwise go on to the next three)
+
+The program stops when it jumps to a negative address. I/O uses address
+-1: `-1 b c` reads a byte into mem[b] (-1 at the end of the input), and
+`a -1 c` writes mem[a] as a character.
+
+Programs modify themselves to do anything indirect: to use mem[p], copy
+p into an operand of a later instruction.
+
+
+## Assembly
+
+Words are separated by spaces or commas, and `#` starts a comment. A
+line of one to three operands is an instruction: `a b c`, with `a b`
+meaning `a b ?` and `a` meaning `a a ?`, where `?` is the address of the
+next instruction. A line starting with `.data` is just its words. Any
+word may be given a label with `name:` (which may also stand alone, to
+label the next word). Words are numbers, characters ('H'), labels,
+`label+n` or `label-n`, or `?`, and `.data` also takes strings
+("Hello\\n"), which become one word per character. For example, this
+prints a string by moving a pointer along it:
+
+    loop:   Z check:text done   # stop at the 0 after the text
+            print:text -1       # output the character
+            NEG1 print          # move both pointers on
+            NEG1 check
+            Z Z loop
+    done:   Z Z -1
+    Z:      .data 0
+    NEG1:   .data -1
+    text:   .data "Hello, World!\\n" 0
+
+
+## Brainfuck to Subleq
+
+`from-bf` compiles Brainfuck into Subleq assembly. The tape is an array
+at the end of memory (which grows as far as the program goes) with a
+pointer `P` to the current cell, and each command patches P into the
+operands of the few instructions it needs. Cells wrap at 256, and `,` at
+the end of the input stores 0. Each `.` writes its byte as it is, with
+or without `--input`, so a program can write binary data.
+
+
+## Usage
+
+    python3 subleq.py run examples/hello.sq
+    python3 subleq.py run examples/hello.sq --trace --max-steps 20
+    python3 subleq.py from-bf examples/hello.bf -o hello.sq
+    python3 subleq.py run examples/hello.bf
+"""
+
+import argparse
+import ast
+import os
+import re
+import sys
+from typing import Dict, List, Optional, Tuple
+
+from esolang_io import BufferIO, ProgramIO, StreamIO
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from interpreters.interpreter import Interpreter
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+BF_COMMANDS: str = "+-<>[].,"
+TOKEN = re.compile(r"""(?:\w+:)?(?:"(?:\\.|[^"\\])*"|'(?:\\.|[^'\\])')|[^\s,]+""")
+
+
+################################################################################
+###   ASSEMBLER   ###
+################################################################################
+
+
+def assemble(text: str) -> List[int]:
+    # Each word is (expression, line number), with `?` already replaced by the next address
+    words: List[Tuple[str, int]] = []
+    labels: Dict[str, int] = {}
+    errors: List[str] = []
+
+    def define(name: str, line_number: int):
+        if name in labels:
+            errors.append(f"line {line_number}: '{name}' is defined twice")
+        labels[name] = len(words)
+
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        line = re.sub(r"""("(?:\\.|[^"\\])*"|'(?:\\.|[^'\\])')|#.*""", lambda m: m.group(1) or "", line)
+        items: List[str] = []
+        for token in TOKEN.findall(line):
+            match = re.fullmatch(r"(\w+):(.*)", token, re.DOTALL)
+            if match is not None:
+                define(match.group(1), line_number)
+                token = match.group(2)
+            if not token:
+                continue
+            if token == ".data" and not items:
+                items.append(token)
+                continue
+            data = bool(items) and items[0] == ".data"
+            items.append(token)
+            if data and token[0] in "\"'":
+                try:
+                    value = ast.literal_eval(token)
+                except (ValueError, SyntaxError):
+                    errors.append(f"line {line_number}: cannot read {token}")
+                    continue
+                # A label before a string labels its first character
+                words += [(str(ord(c)), line_number) for c in value]
+            elif data or len(items) <= 3:
+                words.append((token, line_number))
+            else:
+                errors.append(f"line {line_number}: an instruction has at most three words")
+                break
+        if not items or items[0] == ".data":
+            continue
+        # Fill in the missing operands: `a` is `a a ?`, and `a b` is `a b ?`
+        count = min(len(items), 3)
+        if count == 1:
+            words.append((items[0], line_number))
+        if count <= 2:
+            words.append(("?", line_number))
+        next_address = len(words)
+        for i in range(next_address - 3, next_address):
+            if words[i][0] == "?":
+                words[i] = (str(next_address), line_number)
+
+    memory: List[int] = []
+    for expression, line_number in words:
+        match = re.fullmatch(r"(-?\d+)|'(\\?.)'|(\w+)(?:([+-])(\d+))?", expression)
+        if match is None:
+            errors.append(f"line {line_number}: cannot read '{expression}'")
+            memory.append(0)
+        elif match.group(1) is not None:
+            memory.append(int(match.group(1)))
+        elif match.group(2) is not None:
+            memory.append(ord(ast.literal_eval(f"'{match.group(2)}'")))
+        elif match.group(3) not in labels:
+            errors.append(f"line {line_number}: no label '{match.group(3)}'")
+            memory.append(0)
+        else:
+            offset = int(match.group(5) or 0) * (-1 if match.group(4) == "-" else 1)
+            memory.append(labels[match.group(3)] + offset)
+    if errors:
+        raise ValueError("\n".join(errors))
+    return memory
+
+
+################################################################################
+###   MACHINE   ###
+################################################################################
+
+
+class Subleq(Interpreter):
+    def __init__(self, memory: List[int], io: ProgramIO):
+        super().__init__(io)
+        self.memory = list(memory)
+        self.pc = 0
+
+    def read(self, address: int) -> int:
+        if address < 0:
+            raise ValueError(f"instruction {self.pc}: cannot read address {address}")
+        return self.memory[address] if address < len(self.memory) else 0
+
+    def write(self, address: int, value: int):
+        if address < 0:
+            raise ValueError(f"instruction {self.pc}: cannot write address {address}")
+        if address >= len(self.memory):
+            self.memory += [0] * (address + 1 - len(self.memory))
+        self.memory[address] = value
+
+    def step(self):
+        a, b, c = self.read(self.pc), self.read(self.pc + 1), self.read(self.pc + 2)
+        self.steps += 1
+        if a == -1:
+            byte = self.io.read_byte()
+            self.write(b, -1 if byte is None else byte)
+            self.pc += 3
+        elif b == -1:
+            self.io.write_char(self.read(a))
+            self.pc += 3
+        else:
+            value = self.read(b) - self.read(a)
+            self.write(b, value)
+            self.pc = c if value <= 0 else self.pc + 3
+        self.halted = self.pc < 0
+
+    def snapshot(self) -> str:
+        a, b, c = self.read(self.pc), self.read(self.pc + 1), self.read(self.pc + 2)
+        values = " ".join(f"[{x}]={self.read(x)}" for x in (a, b) if x >= 0)
+        return f"{self.pc:>6}:  {a} {b} {c}  {values}"
+
+
+################################################################################
+###   BRAINFUCK TO SUBLEQ   ###
+################################################################################
+
+
+def compile_bf(source: str) -> str:
+    """Return Subleq assembly that runs the BF program."""
+    program = "".join(c for c in source if c in BF_COMMANDS)
+    lines: List[str] = []
+    loops: List[int] = []
+    count = 0
+
+    def label(name: str) -> str:
+        return f"{name}{count}"
+
+    def patch(*operands: str):
+        """Copy the pointer into each operand."""
+        for operand in operands:
+            lines.append(f"    {operand} {operand}")
+            lines.append("    P Z")
+            lines.append(f"    Z {operand}")
+            lines.append("    Z Z")
+
+    for pc, op in enumerate(program):
+        count = pc
+        lines.append(f"# {pc}: {op}")
+        if op == ">":
+            lines.append("    NEG1 P")
+        elif op == "<":
+            lines.append("    ONE P")
+        elif op == "+":
+            patch(*(label(f"a{i}_") for i in range(4)))
+            lines.append(f"    NEG1 {label('a0_')}:0")
+            lines.append(f"    C255 {label('a1_')}:0 {label('restore')}     # past 255?")
+            lines.append(f"    ONE {label('a2_')}:0 {label('done')}     # then it was 256, so now 0")
+            lines.append(f"{label('restore')}: NEG255 {label('a3_')}:0")
+            lines.append(f"{label('done')}:")
+        elif op == "-":
+            patch(*(label(f"a{i}_") for i in range(4)))
+            lines.append(f"    ONE {label('a0_')}:0 {label('check')}")
+            lines.append(f"    Z Z {label('done')}")
+            lines.append(f"{label('check')}: NEG1 {label('a1_')}:0 {label('wrap')}     # 0 or -1?")
+            lines.append(f"    ONE {label('a2_')}:0 {label('done')}")
+            lines.append(f"{label('wrap')}: NEG255 {label('a3_')}:0")
+            lines.append(f"{label('done')}:")
+        elif op == "[":
+            loops.append(pc)
+            patch(label("a0_"))
+            lines.append(f"    Z {label('a0_')}:0 end{pc}")
+            # `]` comes back here, past the test, which has the old pointer
+            lines.append(f"loop{pc}:")
+        elif op == "]":
+            if not loops:
+                raise ValueError(f"unmatched ']' at instruction {pc}")
+            start = loops.pop()
+            patch(label("a0_"))
+            lines.append(f"    Z {label('a0_')}:0 end{start}")
+            lines.append(f"    Z Z loop{start}")
+            lines.append(f"end{start}:")
+        elif op == ".":
+            patch(label("a0_"))
+            lines.append(f"    {label('a0_')}:0 -1")
+        elif op == ",":
+            patch(*(label(f"a{i}_") for i in range(4)))
+            lines.append(f"    -1 {label('a0_')}:0")
+            lines.append(f"    Z {label('a1_')}:0 {label('eof')}     # the end of the input is -1")
+            lines.append(f"    Z Z {label('done')}")
+            lines.append(f"{label('eof')}: {label('a2_')}:0 {label('a3_')}:0")
+            lines.append(f"{label('done')}:")
+    if loops:
+        raise ValueError(f"unmatched '[' at instruction {loops[-1]}")
+
+    lines.append("    Z Z -1")
+    lines.append("Z: .data 0")
+    lines.append("ONE: .data 1")
+    lines.append("NEG1: .data -1")
+    lines.append("C255: .data 255")
+    lines.append("NEG255: .data -255")
+    lines.append("P: .data TAPE")
+    lines.append("# The tape runs from here to the end of memory, which grows as needed")
+    lines.append("TAPE: .data 0")
+    return "\n".join(lines) + "\n"
+
+
+################################################################################
+###   CLI   ###
+################################################################################
+
+
+def load(path: str) -> List[int]:
+    with open(path) as f:
+        text = f.read()
+    if path.endswith(".bf"):
+        text = compile_bf(text)
+    return assemble(text)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Subleq machine, assembler, and Brainfuck compiler")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+
+    run_parser = subparsers.add_parser("run", help="run assembly (or Brainfuck, if it ends in .bf)")
+    run_parser.add_argument("program", type=str)
+    run_parser.add_argument("--input", type=str, default=None, help="use this as the input instead of stdin")
+    run_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    run_parser.add_argument("--trace", action="store_true", help="print every instruction")
+
+    asm_parser = subparsers.add_parser("asm", help="print the memory image of a program")
+    asm_parser.add_argument("program", type=str)
+
+    bf_parser = subparsers.add_parser("from-bf", help="compile Brainfuck into Subleq assembly")
+    bf_parser.add_argument("program", type=str)
+    bf_parser.add_argument("-o", "--output", type=str, default=None, help="where to write it (default stdout)")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "from-bf":
+            with open(args.program) as f:
+                text = compile_bf(f.read())
+            if args.output is None:
+                print(text, end="")
+            else:
+                with open(args.output, "w") as f:
+                    f.write(text)
+            return
+        memory = load(args.program)
+        if args.command == "asm":
+            print(" ".join(map(str, memory)))
+            return
+        io = StreamIO() if args.input is None else BufferIO(args.input.encode())
+        subleq = Subleq(memory, io)
+        try:
+            subleq.run(args.max_steps, args.trace)
+        finally:
+            if isinstance(io, BufferIO):
+                sys.stdout.flush()
+                sys.stdout.buffer.write(io.output)
+                sys.stdout.buffer.flush()
+        if not subleq.halted:
+            print(f"\nstopped after {subleq.steps} steps")
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+
+
+if __name__ == "__main__":
+    main()
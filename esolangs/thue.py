@@ -0,0 +1,169 @@
+"""
+# Thue
+
+John Colagioia's Thue is a nondeterministic string-rewriting language.
+A program is a list of rules, a line `::=` on its own, and then the
+initial string:
+
+    lhs::=rhs       replace an occurrence of lhs with rhs
+    lhs::=~text     remove lhs, and output text (and a newline)
+    lhs::=:::       replace lhs with a line of input
+
+At each step, one of the possible rewrites (any rule, at any place in
+the string where it matches) is chosen at random, and the program ends
+when there are none left. Unlike a Markov algorithm, the order of the
+rules means nothing, so a correct program must give the same answer
+whichever order the rewrites happen in (or be meant to be random).
+
+`--seed` makes the choices repeatable, and `--order first` always takes
+the first rule that matches, at its leftmost place, which is handy for
+debugging a program that should not depend on the order.
+
+
+## Examples
+
+    hello.t             prints "Hello, World!"
+    increment.t         adds one to a binary number: _1011+ becomes _1100
+    coin.t              prints heads or tails
+
+
+## Usage
+
+    python3 thue.py examples/hello.t
+    python3 thue.py examples/increment.t --trace
+    python3 thue.py examples/coin.t --seed 1
+"""
+
+import argparse
+import os
+import sys
+from typing import List, NamedTuple, Optional, Tuple
+
+from esolang_io import BufferIO, ProgramIO, StreamIO
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from interpreters.interpreter import Interpreter
+from rng.rng import add_seed_argument, make_rng
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+SEPARATOR: str = "::="
+ORDERS = ["random", "first"]
+
+
+class Rule(NamedTuple):
+    lhs: str
+    rhs: str
+
+
+def parse(text: str) -> Tuple[List[Rule], str]:
+    """Return the rules and the initial string."""
+    rules: List[Rule] = []
+    errors: List[str] = []
+    lines = text.split("\n")
+    for line_number, line in enumerate(lines, start=1):
+        if line.strip() == SEPARATOR:
+            if errors:
+                raise ValueError("\n".join(errors))
+            return rules, "".join(lines[line_number:]).rstrip("\n")
+        if not line.strip():
+            continue
+        lhs, separator, rhs = line.partition(SEPARATOR)
+        if not separator:
+            errors.append(f"line {line_number}: expected 'lhs::=rhs'")
+        elif not lhs:
+            errors.append(f"line {line_number}: the left-hand side cannot be empty")
+        else:
+            rules.append(Rule(lhs, rhs))
+    errors.append(f"there is no '{SEPARATOR}' line before the initial string")
+    raise ValueError("\n".join(errors))
+
+
+def matches(rules: List[Rule], state: str) -> List[Tuple[Rule, int]]:
+    found: List[Tuple[Rule, int]] = []
+    for rule in rules:
+        position = state.find(rule.lhs)
+        while position >= 0:
+            found.append((rule, position))
+            position = state.find(rule.lhs, position + 1)
+    return found
+
+
+def read_line(io: ProgramIO) -> str:
+    line = bytearray()
+    byte = io.read_byte()
+    while byte is not None and byte != ord("\n"):
+        line.append(byte)
+        byte = io.read_byte()
+    return line.decode(errors="replace")
+
+
+class Thue(Interpreter):
+    def __init__(self, rules: List[Rule], state: str, io: ProgramIO,
+                 order: str = "random", seed: Optional[int] = None):
+        super().__init__(io)
+        self.rules = rules
+        self.state = state
+        self.order = order
+        self.rng = make_rng(seed)
+        self.rule: Optional[Rule] = None
+
+    def step(self) -> Optional[Rule]:
+        """Make one rewrite, and return its rule (None once nothing matches)."""
+        if self.order == "first":
+            found = next(((rule, self.state.find(rule.lhs)) for rule in self.rules if rule.lhs in self.state), None)
+        else:
+            candidates = matches(self.rules, self.state)
+            found = self.rng.choice(candidates) if candidates else None
+        if found is None:
+            self.halted = True
+            return None
+        self.steps += 1
+        rule, position = found
+        if rule.rhs.startswith("~"):
+            self.io.write_text(rule.rhs[1:] + "\n")
+            replacement = ""
+        elif rule.rhs == ":::":
+            replacement = read_line(self.io)
+        else:
+            replacement = rule.rhs
+        self.state = self.state[:position] + replacement + self.state[position + len(rule.lhs):]
+        self.rule = rule
+        return rule
+
+    def snapshot(self) -> str:
+        """The last rewrite, and the string it left."""
+        shown = "" if self.rule is None else self.rule.lhs + SEPARATOR + self.rule.rhs
+        return f"{shown:<24} {self.state}"
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Thue interpreter")
+    parser.add_argument("program", type=str, help="path to the program")
+    parser.add_argument("--input", type=str, default=None, help="use this as the input instead of stdin")
+    parser.add_argument("--order", choices=ORDERS, default="random", help="how to choose the next rewrite")
+    add_seed_argument(parser, help="for the random choices")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="print every rewrite, and the final string")
+    args = parser.parse_args()
+
+    try:
+        with open(args.program) as f:
+            rules, state = parse(f.read())
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    io = StreamIO() if args.input is None else BufferIO(args.input.encode())
+    thue = Thue(rules, state, io, args.order, args.seed)
+    try:
+        thue.run(args.max_steps, args.trace)
+    finally:
+        if isinstance(io, BufferIO):
+            print(io.output.decode(errors="replace"), end="")
+    if args.trace:
+        print(f"Final: {thue.state}")
+    if not thue.halted:
+        print(f"stopped after {thue.steps} steps")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,407 @@
+"""
+# Whitespace
+
+Only spaces, tabs, and line feeds mean anything in a Whitespace program;
+every other character is a comment. Below, they are written S, T, and L.
+Each instruction starts with its kind (the IMP), then the command, then
+sometimes an argument:
+
+    S       stack       S n push    LS dup      TS n copy   LT swap
+                        LL drop     TL n slide
+    TS      arithmetic  SS add      ST sub      SL mul      TS div      TT mod
+    TT      heap        S store     T load
+    L       flow        SS l label  ST l call   SL l jump   TS l jz
+                        TT l jn     TL ret      LL end
+    TL      I/O         SS printc   ST printn   TS readc    TT readn
+
+A number is a sign (S for +, T for -) then binary digits (S for 0, T for
+1), ending in L. A label is just the digits, ending in L. `copy n` pushes
+the nth item from the top (0 is the top), `slide n` drops n items from
+under the top, and `readc` and `readn` pop an address and store what
+they read to the heap. Division and modulo round towards negative
+infinity, as in the reference (Haskell) interpreter.
+
+
+## Mnemonics
+
+Invisible source needs tooling, so `asm` turns the readable form into
+Whitespace and `disasm` does the reverse. One instruction per line, with
+its name from the table above; `;` starts a comment. Numbers may be
+written in decimal or as a character ('A', '\\n', '\\t', '\\\\', or '\\''),
+and labels may be any name:
+
+    label loop
+        push 'a'
+        printc
+        jump loop
+
+Labels are numbered in the order they appear, but a label written as L
+followed by binary digits (L0110) keeps exactly those digits, which is
+how `disasm` writes them, so disassembling and reassembling gives back
+the same program. `run` assembles files ending in `.wsa` first.
+
+
+## Usage
+
+    python3 whitespace.py run examples/hello.ws
+    python3 whitespace.py run examples/factorial.wsa --input 10
+    python3 whitespace.py asm examples/hello.wsa -o examples/hello.ws
+    python3 whitespace.py disasm examples/hello.ws
+"""
+
+import argparse
+import operator
+import os
+import re
+import sys
+from typing import Dict, List, NamedTuple, Optional, Union
+
+from esolang_io import BufferIO, ProgramIO, StreamIO
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from interpreters.interpreter import Interpreter
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+CHARACTERS: Dict[str, str] = {" ": "S", "\t": "T", "\n": "L"}
+
+# mnemonic -> (code, argument: None, "number", or "label")
+COMMANDS: Dict[str, tuple] = {
+    "push": ("SS", "number"),
+    "dup": ("SLS", None),
+    "copy": ("STS", "number"),
+    "swap": ("SLT", None),
+    "drop": ("SLL", None),
+    "slide": ("STL", "number"),
+    "add": ("TSSS", None),
+    "sub": ("TSST", None),
+    "mul": ("TSSL", None),
+    "div": ("TSTS", None),
+    "mod": ("TSTT", None),
+    "store": ("TTS", None),
+    "load": ("TTT", None),
+    "label": ("LSS", "label"),
+    "call": ("LST", "label"),
+    "jump": ("LSL", "label"),
+    "jz": ("LTS", "label"),
+    "jn": ("LTT", "label"),
+    "ret": ("LTL", None),
+    "end": ("LLL", None),
+    "printc": ("TLSS", None),
+    "printn": ("TLST", None),
+    "readc": ("TLTS", None),
+    "readn": ("TLTT", None),
+}
+ESCAPES: Dict[str, str] = {"\\n": "\n", "\\t": "\t", "\\\\": "\\", "\\'": "'"}
+ARITHMETIC = {"add": operator.add, "sub": operator.sub, "mul": operator.mul,
+              "div": operator.floordiv, "mod": operator.mod}
+
+
+class Instruction(NamedTuple):
+    command: str
+    argument: Union[int, str, None] = None     # a number, or a label's binary digits
+
+
+################################################################################
+###   WHITESPACE SOURCE   ###
+################################################################################
+
+
+def tokens(source: str) -> str:
+    return "".join(CHARACTERS.get(c, "") for c in source)
+
+
+def parse(source: str) -> List[Instruction]:
+    code = tokens(source)
+    program: List[Instruction] = []
+    position = 0
+    while position < len(code):
+        for command, (prefix, argument) in COMMANDS.items():
+            if code.startswith(prefix, position):
+                break
+        else:
+            raise ValueError(f"instruction {len(program)}: unknown command "
+                             f"{code[position:position + 4]}...")
+        position += len(prefix)
+        if argument is None:
+            program.append(Instruction(command))
+            continue
+        end = code.find("L", position)
+        if end < 0:
+            raise ValueError(f"instruction {len(program)}: {command}'s argument has no end")
+        digits = code[position:end].replace("S", "0").replace("T", "1")
+        position = end + 1
+        if argument == "label":
+            program.append(Instruction(command, digits))
+        elif digits == "":
+            raise ValueError(f"instruction {len(program)}: {command} is missing its sign")
+        else:
+            value = int(digits[1:] or "0", 2)
+            program.append(Instruction(command, -value if digits[0] == "1" else value))
+    return program
+
+
+def unparse(program: List[Instruction]) -> str:
+    """Whitespace source, with the mnemonic of each instruction as a comment."""
+    lines: List[str] = []
+    for instruction in program:
+        code, argument = COMMANDS[instruction.command]
+        if argument == "label":
+            code += instruction.argument.replace("0", "S").replace("1", "T") + "L"
+        elif argument == "number":
+            value = instruction.argument
+            code += ("T" if value < 0 else "S") + (format(abs(value), "b") if value else "") \
+                .replace("0", "S").replace("1", "T") + "L"
+        source = code.replace("S", " ").replace("T", "\t").replace("L", "\n")
+        # The comment goes before the line feeds, as they end the instruction
+        head, tail = source.rstrip("\n"), "\n" * (len(source) - len(source.rstrip("\n")))
+        lines.append(head + format_instruction(instruction).replace(" ", "_") + tail)
+    return "".join(lines)
+
+
+################################################################################
+###   MNEMONICS   ###
+################################################################################
+
+
+def format_instruction(instruction: Instruction) -> str:
+    _, argument = COMMANDS[instruction.command]
+    if argument == "label":
+        return f"{instruction.command} L{instruction.argument}"
+    if argument == "number":
+        return f"{instruction.command} {instruction.argument}"
+    return instruction.command
+
+
+def disassemble(program: List[Instruction]) -> str:
+    lines: List[str] = []
+    for instruction in program:
+        indent = "" if instruction.command == "label" else "    "
+        lines.append(indent + format_instruction(instruction))
+    return "\n".join(lines) + "\n"
+
+
+def parse_number(text: str) -> int:
+    match = re.fullmatch(r"'(\\?.)'|(-?\d+)", text)
+    if match is None or match.group(1) is not None and len(match.group(1)) == 2 \
+            and match.group(1) not in ESCAPES:
+        raise ValueError(f"'{text}' is not a number or a character")
+    if match.group(2) is not None:
+        return int(match.group(2))
+    return ord(ESCAPES.get(match.group(1), match.group(1)))
+
+
+def assemble(text: str) -> List[Instruction]:
+    parsed: List[tuple] = []
+    errors: List[str] = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        # A ';' inside a character literal does not start a comment
+        line = re.sub(r"('\\?.')|;.*", lambda m: m.group(1) or "", line).strip()
+        if not line:
+            continue
+        words = line.split(None, 1)
+        command = words[0].lower()
+        if command not in COMMANDS:
+            errors.append(f"line {line_number}: unknown instruction '{words[0]}'")
+            continue
+        argument = COMMANDS[command][1]
+        if argument is None:
+            if len(words) > 1:
+                errors.append(f"line {line_number}: {command} takes no argument")
+            else:
+                parsed.append((command, None))
+        elif len(words) == 1:
+            errors.append(f"line {line_number}: {command} needs a {argument}")
+        elif argument == "number":
+            try:
+                parsed.append((command, parse_number(words[1].strip())))
+            except ValueError as e:
+                errors.append(f"line {line_number}: {e}")
+        else:
+            parsed.append((command, words[1].strip()))
+    if errors:
+        raise ValueError("\n".join(errors))
+
+    # Named labels get the binary numbers that the L0110-style labels don't use
+    names = [argument for command, argument in parsed if COMMANDS[command][1] == "label"]
+    digits: Dict[str, str] = {name: name[1:] for name in names if re.fullmatch(r"L[01]*", name)}
+    used = set(digits.values())
+    number = 0
+    for name in names:
+        if name not in digits:
+            while format(number, "b") in used:
+                number += 1
+            digits[name] = format(number, "b")
+            used.add(digits[name])
+    return [Instruction(command, digits[argument] if COMMANDS[command][1] == "label" else argument)
+            for command, argument in parsed]
+
+
+################################################################################
+###   INTERPRETER   ###
+################################################################################
+
+
+class Whitespace(Interpreter):
+    def __init__(self, program: List[Instruction], io: ProgramIO):
+        super().__init__(io)
+        self.program = program
+        self.labels: Dict[str, int] = {}
+        for index, instruction in enumerate(program):
+            if instruction.command == "label":
+                if instruction.argument in self.labels:
+                    raise ValueError(f"label L{instruction.argument} is defined twice")
+                self.labels[instruction.argument] = index
+        for instruction in program:
+            if instruction.command in ("call", "jump", "jz", "jn") and instruction.argument not in self.labels:
+                raise ValueError(f"{format_instruction(instruction)}: no such label")
+        self.stack: List[int] = []
+        self.heap: Dict[int, int] = {}
+        self.calls: List[int] = []
+        self.pc = 0
+
+    def pop(self) -> int:
+        if not self.stack:
+            raise ValueError(f"instruction {self.pc} ({format_instruction(self.program[self.pc])}): "
+                             f"the stack is empty")
+        return self.stack.pop()
+
+    def step(self):
+        if self.pc >= len(self.program):
+            raise ValueError("the program ran off its end without `end`")
+        command, argument = self.program[self.pc]
+        self.steps += 1
+        next_pc = self.pc + 1
+        if command == "push":
+            self.stack.append(argument)
+        elif command == "dup":
+            value = self.pop()
+            self.stack += [value, value]
+        elif command == "copy":
+            if not 0 <= argument < len(self.stack):
+                raise ValueError(f"instruction {self.pc} (copy {argument}): the stack is too short")
+            self.stack.append(self.stack[-1 - argument])
+        elif command == "swap":
+            a, b = self.pop(), self.pop()
+            self.stack += [a, b]
+        elif command == "drop":
+            self.pop()
+        elif command == "slide":
+            top = self.pop()
+            if argument > 0:
+                del self.stack[-argument:]
+            self.stack.append(top)
+        elif command in ("add", "sub", "mul", "div", "mod"):
+            a, b = self.pop(), self.pop()
+            if command in ("div", "mod") and a == 0:
+                raise ValueError(f"instruction {self.pc} ({command}): division by zero")
+            self.stack.append(ARITHMETIC[command](b, a))
+        elif command == "store":
+            value, address = self.pop(), self.pop()
+            self.heap[address] = value
+        elif command == "load":
+            self.stack.append(self.heap.get(self.pop(), 0))
+        elif command == "label":
+            pass
+        elif command == "call":
+            self.calls.append(next_pc)
+            next_pc = self.labels[argument]
+        elif command == "jump":
+            next_pc = self.labels[argument]
+        elif command == "jz":
+            if self.pop() == 0:
+                next_pc = self.labels[argument]
+        elif command == "jn":
+            if self.pop() < 0:
+                next_pc = self.labels[argument]
+        elif command == "ret":
+            if not self.calls:
+                raise ValueError(f"instruction {self.pc} (ret): not in a subroutine")
+            next_pc = self.calls.pop()
+        elif command == "end":
+            self.halted = True
+            return
+        elif command == "printc":
+            self.io.write_char(self.pop())
+        elif command == "printn":
+            self.io.write_text(str(self.pop()))
+        elif command == "readc":
+            byte = self.io.read_byte()
+            self.heap[self.pop()] = -1 if byte is None else byte
+        elif command == "readn":
+            number = self.io.read_number()
+            if number is None:
+                raise ValueError(f"instruction {self.pc} (readn): the input has no number")
+            self.heap[self.pop()] = number
+        self.pc = next_pc
+
+    def snapshot(self) -> str:
+        shown = format_instruction(self.program[self.pc]) if self.pc < len(self.program) else "(the end)"
+        return f"{self.pc:>5}  {shown:<16} {self.stack}"
+
+
+def run(program: List[Instruction], io: ProgramIO, max_steps: Optional[int] = None) -> Whitespace:
+    whitespace = Whitespace(program, io)
+    whitespace.run(max_steps)
+    return whitespace
+
+
+################################################################################
+###   CLI   ###
+################################################################################
+
+
+def load(path: str) -> List[Instruction]:
+    with open(path) as f:
+        text = f.read()
+    return assemble(text) if path.endswith(".wsa") else parse(text)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Whitespace interpreter, assembler, and disassembler")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+
+    run_parser = subparsers.add_parser("run", help="run a program (.ws, or .wsa mnemonics)")
+    run_parser.add_argument("program", type=str)
+    run_parser.add_argument("--input", type=str, default=None, help="use this as the input instead of stdin")
+    run_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    run_parser.add_argument("--trace", action="store_true", help="print each instruction and the stack")
+
+    asm_parser = subparsers.add_parser("asm", help="assemble mnemonics into Whitespace")
+    asm_parser.add_argument("program", type=str)
+    asm_parser.add_argument("-o", "--output", type=str, default=None, help="where to write it (default stdout)")
+    asm_parser.add_argument("--bare", action="store_true", help="leave out the mnemonic comments")
+
+    disasm_parser = subparsers.add_parser("disasm", help="print a Whitespace program as mnemonics")
+    disasm_parser.add_argument("program", type=str)
+    args = parser.parse_args()
+
+    try:
+        program = load(args.program)
+        if args.command == "asm":
+            source = unparse(program)
+            if args.bare:
+                source = "".join(c for c in source if c in CHARACTERS)
+            if args.output is None:
+                print(source, end="")
+            else:
+                with open(args.output, "w") as f:
+                    f.write(source)
+        elif args.command == "disasm":
+            print(disassemble(program), end="")
+        else:
+            io = StreamIO() if args.input is None else BufferIO(args.input.encode())
+            whitespace = Whitespace(program, io)
+            try:
+                whitespace.run(args.max_steps, args.trace)
+            finally:
+                if isinstance(io, BufferIO):
+                    print(io.output.decode(errors="replace"), end="")
+            if not whitespace.halted:
+                print(f"\nstopped after {whitespace.steps} steps")
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+
+
+if __name__ == "__main__":
+    main()
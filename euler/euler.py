@@ -0,0 +1,208 @@
+"""
+# Project Euler
+
+Solutions to Project Euler problems. Project Euler asks that answers are
+not published, so each problem is registered with the MD5 hash of its
+answer instead of the answer itself, which is enough to check it:
+
+    @problem(1, "e1edf9d1967ca96767dcc2b2d6df69f4")
+    def multiples_of_3_or_5() -> int:
+        return sum(n for n in range(1000) if n % 3 == 0 or n % 5 == 0)
+
+    list        the problems that have solutions
+    run N       solve problem N, time it, and check it against its hash
+    test        solve and check every problem
+
+
+## Usage
+
+    python3 euler.py list
+    python3 euler.py run 14
+    python3 euler.py test
+"""
+
+import argparse
+import hashlib
+import itertools
+import math
+import os
+import sys
+import time
+from typing import Callable, Dict, NamedTuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from number_theory.number_theory import factorize, primes_below
+
+
+class Problem(NamedTuple):
+    number: int
+    answer_hash: str
+    solve: Callable[[], int]
+
+
+PROBLEMS: Dict[int, Problem] = {}
+
+
+def problem(number: int, answer_hash: str):
+    def register(solve: Callable[[], int]) -> Callable[[], int]:
+        if number in PROBLEMS:
+            raise ValueError(f"problem {number} is already registered")
+        PROBLEMS[number] = Problem(number, answer_hash, solve)
+        return solve
+    return register
+
+
+def answer_hash(answer: int) -> str:
+    return hashlib.md5(str(answer).encode()).hexdigest()
+
+
+################################################################################
+###                                 Problems                                 ###
+################################################################################
+
+
+@problem(1, "e1edf9d1967ca96767dcc2b2d6df69f4")
+def multiples_of_3_or_5() -> int:
+    return sum(n for n in range(1000) if n % 3 == 0 or n % 5 == 0)
+
+
+@problem(2, "4194eb91842c8e7e6df099ca73c38f28")
+def even_fibonacci_numbers() -> int:
+    total, a, b = 0, 1, 2
+    while a <= 4_000_000:
+        total += a if a % 2 == 0 else 0
+        a, b = b, a + b
+    return total
+
+
+@problem(3, "94c4dd41f9dddce696557d3717d98d82")
+def largest_prime_factor() -> int:
+    return max(factorize(600851475143))
+
+
+@problem(4, "d4cfc27d16ea72a96b83d9bdef6ce2ec")
+def largest_palindrome_product() -> int:
+    return max(
+        a * b for a in range(100, 1000) for b in range(a, 1000)
+        if str(a * b) == str(a * b)[::-1]
+    )
+
+
+@problem(5, "bc0d0a22a7a46212135ed0ba77d22f3a")
+def smallest_multiple() -> int:
+    result = 1
+    for n in range(1, 21):
+        result = result * n // math.gcd(result, n)
+    return result
+
+
+@problem(6, "867380888952c39a131fe1d832246ecc")
+def sum_square_difference() -> int:
+    numbers = range(1, 101)
+    return sum(numbers) ** 2 - sum(n * n for n in numbers)
+
+
+@problem(7, "8c32ab09ec0210af60d392e9b2009560")
+def the_10001st_prime() -> int:
+    return primes_below(200_000)[10_000]
+
+
+@problem(9, "24eaa9820350012ff678de47cb85b639")
+def special_pythagorean_triplet() -> int:
+    for a in range(1, 1000):
+        for b in range(a + 1, 1000 - a):
+            c = 1000 - a - b
+            if a * a + b * b == c * c:
+                return a * b * c
+    raise ValueError("there is no triplet")
+
+
+@problem(10, "d915b2a9ac8749a6b837404815f1ae25")
+def summation_of_primes() -> int:
+    return sum(primes_below(2_000_000))
+
+
+@problem(14, "5052c3765262bb2c6be537abd60b305e")
+def longest_collatz_sequence() -> int:
+    lengths = {1: 1}
+
+    def length(n: int) -> int:
+        path = []
+        while n not in lengths:
+            path.append(n)
+            n = n // 2 if n % 2 == 0 else 3 * n + 1
+        total = lengths[n]
+        for m in reversed(path):
+            total += 1
+            lengths[m] = total
+        return total
+
+    return max(range(1, 1_000_000), key=length)
+
+
+@problem(15, "928f3957168ac592c4215dcd04e0b678")
+def lattice_paths() -> int:
+    return math.comb(40, 20)
+
+
+@problem(16, "6a5889bb0190d0211a991f47bb19a777")
+def power_digit_sum() -> int:
+    return sum(map(int, str(2 ** 1000)))
+
+
+@problem(20, "443cb001c138b2561a0d90720d6ce111")
+def factorial_digit_sum() -> int:
+    return sum(map(int, str(math.factorial(100))))
+
+
+@problem(25, "a376802c0811f1b9088828288eb0d3f0")
+def the_1000_digit_fibonacci_number() -> int:
+    a, b = 1, 1
+    for index in itertools.count(1):
+        if len(str(a)) >= 1000:
+            return index
+        a, b = b, a + b
+    raise AssertionError("unreachable")
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def run(number: int) -> bool:
+    entry = PROBLEMS[number]
+    start = time.perf_counter()
+    answer = entry.solve()
+    elapsed = time.perf_counter() - start
+    right = answer_hash(answer) == entry.answer_hash
+    print(f"Problem {number:>3}: {answer:<16} {elapsed * 1000:>9.1f} ms    {'ok' if right else 'WRONG'}")
+    return right
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Project Euler solutions")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    subparsers.add_parser("list", help="list the solved problems")
+    subparsers.add_parser("test", help="solve and check every problem")
+    run_parser = subparsers.add_parser("run", help="solve one problem")
+    run_parser.add_argument("number", type=int)
+    args = parser.parse_args()
+
+    if args.command == "list":
+        for number, entry in sorted(PROBLEMS.items()):
+            print(f"{number:>3}  {entry.solve.__name__.replace('_', ' ')}")
+    elif args.command == "run":
+        if args.number not in PROBLEMS:
+            parser.exit(1, f"error: there is no solution for problem {args.number}\n")
+        if not run(args.number):
+            parser.exit(1)
+    else:
+        results = [run(number) for number in sorted(PROBLEMS)]
+        print(f"{sum(results)} of {len(results)} right")
+        if not all(results):
+            parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
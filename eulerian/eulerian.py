@@ -0,0 +1,278 @@
+"""
+# Eulerian Paths and Circuits
+
+A walk through a graph that uses every edge exactly once: a circuit if
+it ends where it started, a path if not. Euler's answer to the bridges
+of Königsberg (is there a walk over each of the seven once?) was that
+it depends only on the degrees:
+
+    undirected  the edges all join up, and the number of vertices of odd
+                degree is 0 (a circuit, from anywhere) or 2 (a path,
+                from one of them to the other)
+    directed    the edges all join up, and every vertex has as many
+                edges in as out (a circuit), or one has one more out,
+                where the path starts, and one has one more in, where it
+                ends
+
+In Königsberg all four parts of the town had an odd number of bridges.
+
+
+## Hierholzer's Algorithm
+
+Walk from the start along unused edges until stuck, which can only
+happen back at the start (for a circuit) or at the end (for a path).
+Then back up along the walk to the last vertex with an unused edge,
+walk a loop from there in the same way, and splice it in. With a stack
+for the walk, the vertices come off it in the reverse of the final
+order, and each edge is looked at twice: linear time.
+
+
+## The Format
+
+One edge a line: the two vertices, and after them, optionally, a name
+for the edge (like the name of a bridge). A line `directed` makes the
+edges go from the first vertex to the second. Lines starting with # are
+comments; `# expect: circuit`, `path` or `none` records the answer,
+which `--test` checks.
+
+    # a triangle, with a tail
+    A B
+    B C
+    C A
+    C D main road
+
+
+## Graphs
+
+    koenigsberg.txt  the seven bridges (none)
+    nikolaus.txt     Das Haus vom Nikolaus, the house drawn in one stroke
+                     (a path)
+    octahedron.txt   every degree 4 (a circuit)
+    debruijn.txt     the de Bruijn graph of 3-bit words, directed (a
+                     circuit, which spells a de Bruijn sequence)
+    one_way.txt      streets, some one way (a path)
+
+
+## Usage
+
+    python3 eulerian.py graphs/koenigsberg.txt
+    python3 eulerian.py graphs/nikolaus.txt
+    python3 eulerian.py graphs/octahedron.txt --start U
+    python3 eulerian.py graphs/debruijn.txt --start 00
+    python3 eulerian.py graphs/*.txt --test
+"""
+
+import argparse
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+
+ANSWERS: List[str] = ["circuit", "path", "none"]
+
+
+class Edge(NamedTuple):
+    start: str
+    end: str
+    name: str
+
+
+class Graph:
+    def __init__(self, edges: List[Edge], directed: bool):
+        self.edges = edges
+        self.directed = directed
+        self.vertices: List[str] = []
+        self.adjacent: Dict[str, List[Tuple[int, str]]] = {}    # each vertex's edges out, by index, and where to
+        self.out_degree: Dict[str, int] = {}
+        self.in_degree: Dict[str, int] = {}
+        for i, (u, v, _) in enumerate(edges):
+            for vertex in (u, v):
+                if vertex not in self.adjacent:
+                    self.vertices.append(vertex)
+                    self.adjacent[vertex] = []
+                    self.out_degree[vertex] = self.in_degree[vertex] = 0
+            self.adjacent[u].append((i, v))
+            self.out_degree[u] += 1
+            self.in_degree[v] += 1
+            if not directed:
+                self.adjacent[v].append((i, u))
+                self.out_degree[v] += 1
+                self.in_degree[u] += 1
+
+    def degree(self, vertex: str) -> int:
+        """Undirected, the edges at the vertex (a loop counting twice)."""
+        return self.out_degree[vertex]
+
+    def pieces(self) -> int:
+        """How many separate pieces the edges fall into, ignoring which way they go."""
+        links: Dict[str, List[str]] = {vertex: [] for vertex in self.vertices}
+        for u, v, _ in self.edges:
+            links[u].append(v)
+            links[v].append(u)
+        seen = set()
+        count = 0
+        for vertex in self.vertices:
+            if vertex in seen:
+                continue
+            count += 1
+            seen.add(vertex)
+            stack = [vertex]
+            while stack:
+                for other in links[stack.pop()]:
+                    if other not in seen:
+                        seen.add(other)
+                        stack.append(other)
+        return count
+
+
+class Verdict(NamedTuple):
+    kind: str                   # circuit, path or none
+    starts: List[str]           # where the walk may start
+    reason: str                 # why not, for none
+
+
+def classify(graph: Graph) -> Verdict:
+    pieces = graph.pieces()
+    if pieces > 1:
+        return Verdict("none", [], f"the edges are in {pieces} separate pieces")
+    if not graph.directed:
+        odd = [v for v in graph.vertices if graph.degree(v) % 2]
+        if not odd:
+            return Verdict("circuit", graph.vertices, "")
+        if len(odd) == 2:
+            return Verdict("path", odd, "")
+        listed = ", ".join(f"{v} ({graph.degree(v)})" for v in odd)
+        return Verdict("none", [], f"{len(odd)} vertices have odd degree, {listed}, where a path allows 2: "
+                                   f"{(len(odd) - 2) // 2} more edge{'s' * (len(odd) > 4)} between them would do")
+    surplus = {v: graph.out_degree[v] - graph.in_degree[v] for v in graph.vertices}
+    unbalanced = {v: s for v, s in surplus.items() if s}
+    if not unbalanced:
+        return Verdict("circuit", graph.vertices, "")
+    starts = [v for v, s in unbalanced.items() if s == 1]
+    ends = [v for v, s in unbalanced.items() if s == -1]
+    if len(unbalanced) == 2 and len(starts) == 1 and len(ends) == 1:
+        return Verdict("path", starts, "")
+    listed = ", ".join(f"{v} ({s:+d})" for v, s in unbalanced.items())
+    return Verdict("none", [], f"the edges out less those in are {listed}, where a path allows only one +1 "
+                               f"(its start) and one -1 (its end)")
+
+
+def hierholzer(graph: Graph, start: str) -> List[Tuple[str, Optional[int]]]:
+    """The walk, as each vertex and the edge that led to it (None for the start)."""
+    used = [False] * len(graph.edges)
+    tried = {vertex: 0 for vertex in graph.vertices}
+    stack: List[Tuple[str, Optional[int]]] = [(start, None)]
+    walk = []
+    while stack:
+        vertex = stack[-1][0]
+        edges = graph.adjacent[vertex]
+        while tried[vertex] < len(edges) and used[edges[tried[vertex]][0]]:
+            tried[vertex] += 1
+        if tried[vertex] == len(edges):
+            walk.append(stack.pop())
+        else:
+            i, other = edges[tried[vertex]]
+            used[i] = True
+            stack.append((other, i))
+    walk.reverse()
+    if len(walk) != len(graph.edges) + 1:
+        raise AssertionError("the walk missed some edges; the graph should have been classified as having none")
+    return walk
+
+
+def is_eulerian(graph: Graph, walk: List[Tuple[str, Optional[int]]]) -> bool:
+    """Whether the walk follows edges that are there, each once."""
+    used = [i for _, i in walk[1:]]
+    if sorted(used) != list(range(len(graph.edges))):
+        return False
+    for (before, _), (after, i) in zip(walk, walk[1:]):
+        u, v, _ = graph.edges[i]
+        if (before, after) != (u, v) and (graph.directed or (before, after) != (v, u)):
+            return False
+    return True
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Tuple[Graph, Optional[str]]:
+    """The graph, and the answer it expects, if it says."""
+    edges = []
+    directed = False
+    expected = None
+    for number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if line.startswith("#"):
+            key, _, value = line[1:].partition(":")
+            if key.strip() == "expect":
+                expected = value.strip()
+                if expected not in ANSWERS:
+                    raise ValueError(f"line {number}: expected {', '.join(ANSWERS)}, not {expected!r}")
+            continue
+        if not line:
+            continue
+        if line == "directed":
+            directed = True
+            continue
+        words = line.split()
+        if len(words) < 2:
+            raise ValueError(f"line {number}: an edge is two vertices, and maybe a name")
+        edges.append(Edge(words[0], words[1], " ".join(words[2:])))
+    if not edges:
+        raise ValueError("there are no edges")
+    return Graph(edges, directed), expected
+
+
+def render(graph: Graph, walk: List[Tuple[str, Optional[int]]]) -> str:
+    arrow = " -> " if graph.directed else " - "
+    lines = [arrow.join(vertex for vertex, _ in walk)]
+    if any(edge.name for edge in graph.edges):
+        width = len(str(len(graph.edges)))
+        for step, ((before, _), (after, i)) in enumerate(zip(walk, walk[1:]), start=1):
+            name = graph.edges[i].name
+            lines.append(f"  {step:>{width}}. {before} to {after}" + (f" by {name}" if name else ""))
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Find Eulerian paths and circuits, walks over every edge once")
+    parser.add_argument("files", type=str, nargs="+", help="graphs, as edge lists")
+    parser.add_argument("--start", type=str, default=None, help="the vertex to start from, if it can be")
+    parser.add_argument("--test", action="store_true", help="check the '# expect:' comments, and the walks")
+    args = parser.parse_args()
+
+    failures = 0
+    for path in args.files:
+        try:
+            with open(path) as f:
+                graph, expected = parse(f.read())
+        except (OSError, ValueError) as e:
+            parser.exit(1, f"error: {path}: {e}\n")
+        verdict = classify(graph)
+        walk = hierholzer(graph, verdict.starts[0]) if verdict.starts else []
+        if args.test:
+            ok = verdict.kind == expected and (not walk or is_eulerian(graph, walk))
+            failures += not ok
+            print(f"{'ok' if ok else 'FAIL'}  {path}: {verdict.kind}" + ("" if ok else f", expected {expected}"))
+            continue
+        if len(args.files) > 1:
+            print(f"{path}:")
+        if verdict.kind == "none":
+            print(f"No Eulerian path: {verdict.reason}")
+            failures += 1
+            continue
+        start = verdict.starts[0]
+        if args.start is not None:
+            if args.start not in verdict.starts:
+                parser.exit(1, f"error: the walk cannot start at {args.start}, only at "
+                               f"{', '.join(verdict.starts) if len(verdict.starts) < 10 else 'a vertex with edges'}\n")
+            start = args.start
+            walk = hierholzer(graph, start)
+        print(f"An Eulerian {verdict.kind}, {len(graph.edges)} edges from {start} to {walk[-1][0]}:")
+        print(render(graph, walk))
+    if failures:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,308 @@
+"""
+# Arithmetic Expressions
+
+A tokenizer, parser and evaluator for ordinary arithmetic, for the
+puzzles that build or read sums (the 24 game, Countdown, cryptarithms).
+Numbers are exact: every value is a fraction, so 1/3 * 3 is 1, and
+decimals like 0.1 are read as the fractions they stand for.
+
+    +  -            add and subtract, the loosest, from the left
+    *  /  %         multiply, divide and remainder, from the left
+    -x  +x          negate (or not), binding tighter than * and /
+    ^               powers, the tightest, from the right, so 2^3^2 is
+                    2^9 and -2^2 is -4; the power must be whole
+    ( )             grouping
+    names           variables, given values with --let (or by the code
+                    that calls evaluate)
+
+
+## Parsing
+
+Pratt's top-down operator precedence: each operator has a binding power
+on each side, and `expression(power)` keeps folding operators into the
+left operand for as long as they bind tighter than the power it was
+called with. Right-associative operators bind a little less tightly on
+their right, so the same operator on the right is folded in there
+first.
+
+`to_string` writes a tree back out with only the parentheses it needs,
+which is how the solvers print the expressions they find.
+
+
+## Usage
+
+    python3 expression.py calc "2 * (3 + 4) - 10 / 4"
+    python3 expression.py calc --format -- "-2^2" "2^3^2" "1/3 + 1/6" "(2^3)^2 - (-1)"
+    python3 expression.py calc "x^2 - 2*x*y + y^2" --let x=7 --let y=3 --tree
+    echo "(1 + 2) * 3" | python3 expression.py calc
+"""
+
+import argparse
+import re
+import sys
+from fractions import Fraction
+from typing import Dict, List, NamedTuple, Optional, Tuple, Union
+
+
+MAX_EXPONENT: int = 10_000
+TOKEN = re.compile(r"\s*(?:(\d+(?:\.\d*)?|\.\d+)|([A-Za-z_]\w*)|(\S))")
+BINARY: Dict[str, Tuple[int, int]] = {     # operator: its binding powers on the left and on the right
+    "+": (10, 11), "-": (10, 11),
+    "*": (20, 21), "/": (20, 21), "%": (20, 21),
+    "^": (41, 40),
+}
+UNARY_POWER: int = 30
+# (operator, operator on its right at the same precedence) that need no parentheses between: a + (b - c) is a + b - c
+ASSOCIATIVE: List[Tuple[str, str]] = [("+", "+"), ("+", "-"), ("*", "*"), ("*", "/")]
+
+
+class Token(NamedTuple):
+    kind: str       # "number", "name", "operator", or "end"
+    text: str
+    column: int
+
+
+class Number(NamedTuple):
+    value: Fraction
+
+
+class Name(NamedTuple):
+    name: str
+
+
+class Unary(NamedTuple):
+    operator: str
+    operand: "Node"
+
+
+class Binary(NamedTuple):
+    operator: str
+    left: "Node"
+    right: "Node"
+
+
+Node = Union[Number, Name, Unary, Binary]
+
+
+def tokenize(text: str) -> List[Token]:
+    tokens = []
+    position = 0
+    while text[position:].strip():
+        match = TOKEN.match(text, position)
+        number, name, operator = match.groups()
+        column = match.start(match.lastindex) + 1
+        if number is not None:
+            tokens.append(Token("number", number, column))
+        elif name is not None:
+            tokens.append(Token("name", name, column))
+        elif operator in BINARY or operator in "()":
+            tokens.append(Token("operator", operator, column))
+        else:
+            raise ValueError(f"column {column}: unexpected {operator!r}")
+        position = match.end()
+    tokens.append(Token("end", "", len(text) + 1))
+    return tokens
+
+
+class Parser:
+    def __init__(self, text: str):
+        self.tokens = tokenize(text)
+        self.position = 0
+
+    def peek(self) -> Token:
+        return self.tokens[self.position]
+
+    def next(self) -> Token:
+        token = self.tokens[self.position]
+        self.position += 1
+        return token
+
+    def expect(self, text: str):
+        token = self.next()
+        if token.text != text or token.kind != "operator":
+            raise ValueError(f"column {token.column}: expected {text!r}, not {describe(token)}")
+
+    def expression(self, power: int = 0) -> Node:
+        left = self.operand()
+        while True:
+            token = self.peek()
+            if token.kind != "operator" or token.text not in BINARY or BINARY[token.text][0] <= power:
+                return left
+            self.next()
+            left = Binary(token.text, left, self.expression(BINARY[token.text][1]))
+
+    def operand(self) -> Node:
+        token = self.next()
+        if token.kind == "number":
+            return Number(Fraction(token.text))
+        if token.kind == "name":
+            return Name(token.text)
+        if token.text in ("-", "+"):
+            return Unary(token.text, self.expression(UNARY_POWER))
+        if token.text == "(":
+            inner = self.expression()
+            self.expect(")")
+            return inner
+        raise ValueError(f"column {token.column}: expected a number, a name, or '(', not {describe(token)}")
+
+
+def describe(token: Token) -> str:
+    return "the end" if token.kind == "end" else repr(token.text)
+
+
+def parse(text: str) -> Node:
+    parser = Parser(text)
+    tree = parser.expression()
+    token = parser.peek()
+    if token.kind != "end":
+        raise ValueError(f"column {token.column}: expected an operator, not {describe(token)}")
+    return tree
+
+
+################################################################################
+###                                Evaluation                                ###
+################################################################################
+
+
+def evaluate(node: Node, variables: Optional[Dict[str, Fraction]] = None) -> Fraction:
+    if isinstance(node, Number):
+        return node.value
+    if isinstance(node, Name):
+        if variables is None or node.name not in variables:
+            raise ValueError(f"{node.name} has no value")
+        return Fraction(variables[node.name])
+    if isinstance(node, Unary):
+        value = evaluate(node.operand, variables)
+        return -value if node.operator == "-" else value
+    return apply(node.operator, evaluate(node.left, variables), evaluate(node.right, variables))
+
+
+def apply(operator: str, a: Fraction, b: Fraction) -> Fraction:
+    if operator == "+":
+        return a + b
+    if operator == "-":
+        return a - b
+    if operator == "*":
+        return a * b
+    if operator in ("/", "%") and b == 0:
+        raise ValueError("division by zero")
+    if operator == "/":
+        return a / b
+    if operator == "%":
+        return a % b
+    if b.denominator != 1:
+        raise ValueError(f"the power {format_value(b)} is not whole")
+    if abs(b) > MAX_EXPONENT:
+        raise ValueError(f"the power {format_value(b)} is too big (at most {MAX_EXPONENT})")
+    if a == 0 and b < 0:
+        raise ValueError("division by zero")
+    return a ** int(b)
+
+
+################################################################################
+###                                Formatting                                ###
+################################################################################
+
+
+def format_value(value: Fraction) -> str:
+    return str(value.numerator) if value.denominator == 1 else f"{value.numerator}/{value.denominator}"
+
+
+def precedence(node: Node) -> int:
+    """How tightly the node holds together when written out: a fraction is a division, a negative number
+    a negation."""
+    if isinstance(node, Binary):
+        return BINARY[node.operator][0]
+    if isinstance(node, Number) and node.value.denominator != 1:
+        return BINARY["/"][0]
+    if isinstance(node, Unary) or isinstance(node, Number) and node.value < 0:
+        return UNARY_POWER
+    return 100
+
+
+def spine(node: Node, power: int) -> List[str]:
+    """The operators down the left of the node at the given precedence: a - b + c has + then -."""
+    operators = []
+    while isinstance(node, Binary) and BINARY[node.operator][0] == power:
+        operators.append(node.operator)
+        node = node.left
+    if precedence(node) == power:   # a fraction
+        operators.append("/")
+    return operators
+
+
+def to_string(node: Node) -> str:
+    """The expression, with only the parentheses it needs."""
+    if isinstance(node, Number):
+        return format_value(node.value)
+    if isinstance(node, Name):
+        return node.name
+    if isinstance(node, Unary):
+        operand = to_string(node.operand)
+        return node.operator + (f"({operand})" if precedence(node.operand) < UNARY_POWER else operand)
+    power = BINARY[node.operator][0]
+    left, right = to_string(node.left), to_string(node.right)
+    if precedence(node.left) < power or precedence(node.left) == power and node.operator == "^":
+        left = f"({left})"
+    if precedence(node.right) < power or precedence(node.right) == power and node.operator != "^" \
+            and not all((node.operator, operator) in ASSOCIATIVE for operator in spine(node.right, power)):
+        right = f"({right})"
+    spaced = f" {node.operator} " if node.operator in "+-" else node.operator
+    return f"{left}{spaced}{right}"
+
+
+def render_tree(node: Node, indent: str = "") -> str:
+    if isinstance(node, Number):
+        return f"{indent}{format_value(node.value)}"
+    if isinstance(node, Name):
+        return f"{indent}{node.name}"
+    children = [node.operand] if isinstance(node, Unary) else [node.left, node.right]
+    label = f"{node.operator} (unary)" if isinstance(node, Unary) else node.operator
+    return "\n".join([f"{indent}{label}"] + [render_tree(child, indent + "    ") for child in children])
+
+
+def parse_binding(text: str) -> Tuple[str, Fraction]:
+    name, equals, value = text.partition("=")
+    if not equals or not re.fullmatch(r"[A-Za-z_]\w*", name.strip()):
+        raise argparse.ArgumentTypeError(f"expected NAME=VALUE, not {text!r}")
+    try:
+        return name.strip(), evaluate(parse(value))
+    except ValueError as e:
+        raise argparse.ArgumentTypeError(f"{text}: {e}")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Parse and evaluate arithmetic expressions")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    calc_parser = subparsers.add_parser("calc", help="evaluate expressions exactly")
+    calc_parser.add_argument("expressions", type=str, nargs="*", help="expressions (or one per line on stdin)")
+    calc_parser.add_argument("--let", type=parse_binding, action="append", default=[], metavar="NAME=VALUE",
+                             help="give a variable a value")
+    calc_parser.add_argument("--format", action="store_true", help="print each expression, reparenthesized")
+    calc_parser.add_argument("--tree", action="store_true", help="print each expression's parse tree")
+    calc_parser.add_argument("--decimal", action="store_true", help="print fractions as decimals too")
+    args = parser.parse_args()
+
+    variables = dict(args.let)
+    expressions = args.expressions or [line for line in sys.stdin.read().split("\n") if line.strip()]
+    failures = 0
+    for text in expressions:
+        try:
+            tree = parse(text)
+            value = evaluate(tree, variables)
+        except ValueError as e:
+            print(f"error: {text}: {e}")
+            failures += 1
+            continue
+        if args.tree:
+            print(render_tree(tree))
+        result = format_value(value)
+        if args.decimal and value.denominator != 1:
+            result += f" = {float(value):g}"
+        print(f"{to_string(tree)} = {result}" if args.format else result)
+    if failures:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,273 @@
+"""
+# Flood-It
+
+A grid of coloured cells. The flood starts as the piece of one colour in
+the top left corner; each move picks a colour, and the flood takes it,
+swallowing the pieces of that colour beside it. The aim is to flood the
+whole grid in as few moves as possible.
+
+
+## Pieces
+
+The grid is boiled down to its pieces, the largest runs of one colour
+joined side to side, and which pieces are beside which. Two pieces of
+the same colour are never beside each other, so a move only swallows
+the pieces of its colour on the flood's edge, and never more after
+them. The flood and its edge are bit masks of pieces, so a move is a few
+operations on integers.
+
+
+## Lower Bounds
+
+Two things a solution cannot do faster than:
+
+    colours     each move brings in one colour, so there are at least as
+                many moves as colours left outside the flood
+    distance    a move only swallows pieces on the edge, so a piece that
+                is d pieces away from the flood (in steps between pieces
+                beside each other) is d moves away
+
+Each move takes one off at most, from each, so the larger is a lower
+bound on the moves left.
+
+
+## Searches
+
+    greedy      the colour that floods the most cells next
+    beam        breadth first, keeping the --width best floods after
+                each move: the smallest lower bound on the moves left,
+                then the most cells (the default)
+    astar       A*, on moves so far plus the lower bound, which is
+                admissible, so the first flood to finish is the shortest;
+                it gives up after --limit floods
+
+`random` boards are 14x14 with 6 colours by default, as in the usual
+game, which allows 25 moves; `astar` is only practical on small boards.
+
+
+## The Format
+
+One row a line, each cell a colour, a digit from 1. Lines starting with
+# are comments.
+
+
+## Usage
+
+    python3 flood_it.py solve boards/small.txt --search astar
+    python3 flood_it.py solve boards/small.txt --search greedy
+    python3 flood_it.py random --seed 1
+    python3 flood_it.py random --seed 1 --search greedy
+    python3 flood_it.py random --size 6x6 --colours 4 --seed 2 --search astar
+"""
+
+import argparse
+import heapq
+import os
+import sys
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from rng.rng import add_seed_argument, make_rng
+
+
+SEARCHES: List[str] = ["greedy", "beam", "astar"]
+
+
+class Flood(NamedTuple):
+    flooded: int    # a bit mask of pieces
+    edge: int       # the pieces beside the flood, not in it
+
+
+class Board:
+    def __init__(self, grid: Grid):
+        self.grid = grid
+        self.piece: Dict[Position, int] = {}
+        self.colours: List[int] = []    # each piece's colour
+        self.sizes: List[int] = []      # each piece's number of cells
+        for start in grid.positions():
+            if start in self.piece:
+                continue
+            index = len(self.colours)
+            self.piece[start] = index
+            stack, size = [start], 0
+            while stack:
+                cell = stack.pop()
+                size += 1
+                for n in grid.neighbours(cell):
+                    if n not in self.piece and grid[n] == grid[start]:
+                        self.piece[n] = index
+                        stack.append(n)
+            self.colours.append(grid[start])
+            self.sizes.append(size)
+        self.beside: List[int] = [0] * len(self.colours)
+        for cell, index in self.piece.items():
+            for n in grid.neighbours(cell):
+                if self.piece[n] != index:
+                    self.beside[index] |= 1 << self.piece[n]
+        self.palette: List[int] = sorted(set(self.colours))
+        self.of_colour: Dict[int, int] = {colour: 0 for colour in self.palette}
+        for index, colour in enumerate(self.colours):
+            self.of_colour[colour] |= 1 << index
+        self.everything = (1 << len(self.colours)) - 1
+
+    def start(self) -> Flood:
+        return Flood(1, self.beside[0])
+
+    def move(self, flood: Flood, colour: int) -> Flood:
+        added = flood.edge & self.of_colour[colour]
+        flooded = flood.flooded | added
+        edge = flood.edge
+        for index in pieces(added):
+            edge |= self.beside[index]
+        return Flood(flooded, edge & ~flooded)
+
+    def moves(self, flood: Flood) -> List[int]:
+        """The colours that would swallow something."""
+        return [colour for colour in self.palette if flood.edge & self.of_colour[colour]]
+
+    def cells(self, flood: Flood) -> int:
+        return sum(self.sizes[index] for index in pieces(flood.flooded))
+
+    def bound(self, flood: Flood) -> int:
+        """A lower bound on the moves left: the larger of the colours left and the farthest piece."""
+        colours = sum(1 for colour in self.palette if self.of_colour[colour] & ~flood.flooded)
+        reached, frontier, distance = flood.flooded, flood.edge, 0
+        while reached != self.everything:
+            distance += 1
+            reached |= frontier
+            grown = 0
+            for index in pieces(frontier):
+                grown |= self.beside[index]
+            frontier = grown & ~reached
+        return max(colours, distance)
+
+
+def pieces(mask: int) -> List[int]:
+    found = []
+    while mask:
+        low = mask & -mask
+        found.append(low.bit_length() - 1)
+        mask ^= low
+    return found
+
+
+################################################################################
+###                                 Searching                                ###
+################################################################################
+
+
+def greedy(board: Board) -> List[int]:
+    flood, moves = board.start(), []
+    while flood.flooded != board.everything:
+        colour = max(board.moves(flood), key=lambda colour: board.cells(board.move(flood, colour)))
+        flood = board.move(flood, colour)
+        moves.append(colour)
+    return moves
+
+
+def beam(board: Board, width: int) -> List[int]:
+    layer: List[Tuple[Flood, List[int]]] = [(board.start(), [])]
+    while True:
+        done = [moves for flood, moves in layer if flood.flooded == board.everything]
+        if done:
+            return done[0]
+        following: Dict[int, Tuple[Flood, List[int]]] = {}
+        for flood, moves in layer:
+            for colour in board.moves(flood):
+                after = board.move(flood, colour)
+                following.setdefault(after.flooded, (after, moves + [colour]))
+        layer = sorted(following.values(), key=lambda item: (board.bound(item[0]), -board.cells(item[0])))[:width]
+
+
+def astar(board: Board, limit: int) -> Optional[List[int]]:
+    """The fewest moves, or None if that takes more than limit floods."""
+    start = board.start()
+    queue = [(board.bound(start), 0, start.flooded, start, [])]
+    best = {start.flooded: 0}
+    expanded = 0
+    while queue:
+        _, cost, _, flood, moves = heapq.heappop(queue)
+        if flood.flooded == board.everything:
+            return moves
+        if best.get(flood.flooded, cost) < cost:
+            continue
+        expanded += 1
+        if expanded > limit:
+            return None
+        for colour in board.moves(flood):
+            after = board.move(flood, colour)
+            if after.flooded not in best or cost + 1 < best[after.flooded]:
+                best[after.flooded] = cost + 1
+                heapq.heappush(queue, (cost + 1 + board.bound(after), cost + 1, after.flooded, after,
+                                       moves + [colour]))
+    return None
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Grid:
+    rows = []
+    for number, line in enumerate(text.split("\n"), start=1):
+        stripped = line.strip()
+        if not stripped or stripped.startswith("#"):
+            continue
+        if not stripped.isdigit() or "0" in stripped:
+            raise ValueError(f"line {number}: expected a colour for each cell, a digit from 1")
+        if rows and len(stripped) != len(rows[0]):
+            raise ValueError(f"line {number}: {len(stripped)} cells, but the first row has {len(rows[0])}")
+        rows.append([int(c) for c in stripped])
+    if not rows:
+        raise ValueError("the board is empty")
+    return Grid(rows)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Flood the grid in few moves")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="flood a board")
+    solve_parser.add_argument("file", type=str, help="the board, in the format above")
+    random_parser = subparsers.add_parser("random", help="flood a random board")
+    random_parser.add_argument("--size", type=str, default="14x14", help="WxH (default 14x14)")
+    random_parser.add_argument("--colours", type=int, default=6, choices=range(2, 10))
+    add_seed_argument(random_parser)
+    for p in (solve_parser, random_parser):
+        p.add_argument("--search", choices=SEARCHES, default="beam")
+        p.add_argument("--width", type=int, default=100, help="the floods kept after each move by beam")
+        p.add_argument("--limit", type=int, default=200000, help="the most floods A* expands")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "random":
+            width, x, height = args.size.partition("x")
+            if not x or not width.isdigit() or not height.isdigit() or int(width) < 1 or int(height) < 1:
+                raise ValueError("the size must be WxH, e.g. 14x14")
+            rng = make_rng(args.seed)
+            grid = Grid([[rng.randint(1, args.colours) for _ in range(int(width))] for _ in range(int(height))])
+        else:
+            with open(args.file) as f:
+                grid = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    board = Board(grid)
+    print(grid)
+    print()
+    if args.search == "greedy":
+        moves = greedy(board)
+    elif args.search == "beam":
+        moves = beam(board, args.width)
+    else:
+        moves = astar(board, args.limit)
+        if moves is None:
+            parser.exit(1, f"A* gave up after {args.limit} floods; try a smaller board, or --search beam\n")
+    bound = board.bound(board.start())
+    print(" ".join(map(str, moves)))
+    print(f"{len(moves)} moves" + (", the fewest" if args.search == "astar" or len(moves) == bound
+                                   else f"; at least {bound} are needed"))
+
+
+if __name__ == "__main__":
+    main()
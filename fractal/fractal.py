@@ -0,0 +1,232 @@
+"""
+# Mandelbrot and Julia Sets
+
+Take a complex number z, square it and add c, over and over. For some
+starting points the values stay near the origin for ever; for the rest
+they run off to infinity, and once |z| passes 2 they never come back.
+
+    Mandelbrot  z starts at 0 and c is the point: the set is the points
+                c that stay
+    Julia       c is fixed (--c) and z starts at the point: each c has
+                its own set, joined up exactly when c is in the
+                Mandelbrot set
+
+A point that has not escaped after --iterations squarings is counted as
+in the set, and drawn black; the rest are shaded by how soon they
+escaped. The count is smoothed (n + 1 - log2 log|z|, with a larger
+escape radius so that it is accurate) so that the shades run into each
+other rather than falling into bands.
+
+
+## The View
+
+--centre is the point in the middle of the picture, and at --zoom 1 the
+picture is 3.5 wide, which fits the whole Mandelbrot set; each doubling
+of the zoom halves that. In the terminal, each character is one point,
+and twice as tall as it is wide, so the rows are twice as far apart; a
+--png has square pixels.
+
+
+## In Parallel
+
+Every point is worked out on its own, so the rows are shared among
+--workers processes, a few rows to each task so that the slow rows
+through the set do not all land on one worker. `bench` draws the same
+view with 1, 2, 4, ... workers up to --workers and prints how much
+faster each is.
+
+
+## Usage
+
+    python3 fractal.py mandelbrot
+    python3 fractal.py mandelbrot --centre=-0.745+0.113i --zoom 60 --iterations 300
+    python3 fractal.py julia --c=-0.8+0.156i
+    python3 fractal.py julia --c 0.285+0.01i --png julia.png --width 600 --height 400
+    python3 fractal.py mandelbrot --png mandelbrot.png --width 700 --height 400 --workers 4
+    python3 fractal.py bench --width 300 --height 200 --workers 4
+"""
+
+import argparse
+import math
+import multiprocessing
+import os
+import sys
+import time
+from typing import List, NamedTuple, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from cellular_automata.png import write_png
+
+
+SHADES: str = " .:-=+*#%@"
+INSIDE: float = -1.0
+# Escaping at |z| > 2 is enough to know, but a larger radius makes the smoothed count accurate
+ESCAPE_RADIUS: float = 256.0
+FULL_WIDTH: float = 3.5
+ROWS_PER_TASK: int = 4
+Colour = Tuple[int, int, int]
+
+
+class View(NamedTuple):
+    kind: str
+    c: complex              # for a Julia set
+    centre: complex
+    zoom: float
+    width: int
+    height: int
+    aspect: float           # how much farther apart the rows are than the columns
+    iterations: int
+
+    def point(self, x: int, y: int) -> complex:
+        step = FULL_WIDTH / self.zoom / self.width
+        return self.centre + complex((x - (self.width - 1) / 2) * step,
+                                     ((self.height - 1) / 2 - y) * step * self.aspect)
+
+
+def escape(z: complex, c: complex, iterations: int) -> float:
+    """The smoothed count of squarings before z escapes, or INSIDE if it never does."""
+    limit = ESCAPE_RADIUS * ESCAPE_RADIUS
+    for n in range(iterations):
+        z = z * z + c
+        size = z.real * z.real + z.imag * z.imag
+        if size > limit:
+            return n + 1 - math.log2(math.log(size) / 2)
+    return INSIDE
+
+
+def in_bulbs(c: complex) -> bool:
+    """Whether c is in the main cardioid or the disc to its left, which are in the Mandelbrot set for certain."""
+    q = (c.real - 0.25) ** 2 + c.imag ** 2
+    return q * (q + (c.real - 0.25)) <= c.imag ** 2 / 4 or (c.real + 1) ** 2 + c.imag ** 2 <= 1 / 16
+
+
+def render_rows(task: Tuple[View, int, int]) -> List[List[float]]:
+    """The escape counts of the rows from start to end."""
+    view, start, end = task
+    rows = []
+    for y in range(start, end):
+        row = []
+        for x in range(view.width):
+            point = view.point(x, y)
+            if view.kind == "julia":
+                row.append(escape(point, view.c, view.iterations))
+            elif in_bulbs(point):
+                row.append(INSIDE)
+            else:
+                row.append(escape(0j, point, view.iterations))
+        rows.append(row)
+    return rows
+
+
+def render(view: View, workers: int) -> List[List[float]]:
+    tasks = [(view, y, min(y + ROWS_PER_TASK, view.height)) for y in range(0, view.height, ROWS_PER_TASK)]
+    if workers == 1:
+        chunks = list(map(render_rows, tasks))
+    else:
+        with multiprocessing.Pool(workers) as pool:
+            chunks = pool.map(render_rows, tasks)
+    return [row for chunk in chunks for row in chunk]
+
+
+################################################################################
+###                                 Drawing                                  ###
+################################################################################
+
+
+def shade(counts: List[List[float]]) -> str:
+    """One character a point, darker the longer it took to escape (from the quickest in the picture to the
+    slowest), and darkest of all in the set."""
+    escaped = [count for row in counts for count in row if count != INSIDE]
+    low = min(escaped, default=0.0)
+    scale = math.log1p(max(escaped, default=0.0) - low) or 1.0
+    lines = []
+    for row in counts:
+        line = []
+        for count in row:
+            if count == INSIDE:
+                line.append(SHADES[-1])
+            else:
+                level = math.log1p(count - low) / scale
+                line.append(SHADES[min(len(SHADES) - 2, int(level * (len(SHADES) - 1)))])
+        lines.append("".join(line).rstrip())
+    return "\n".join(lines)
+
+
+def colour(count: float) -> Colour:
+    """Black in the set; outside, a cycle through blues, whites and oranges."""
+    if count == INSIDE:
+        return (0, 0, 0)
+    t = math.sqrt(max(count, 0.0)) / 4
+    return tuple(round(255 * (0.5 + 0.5 * math.cos(2 * math.pi * (t + phase)))) for phase in (0.6, 0.45, 0.3))
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def parse_complex(text: str) -> complex:
+    """A complex number written like -0.8+0.156i (or with j)."""
+    try:
+        return complex(text.replace(" ", "").replace("i", "j"))
+    except ValueError:
+        raise argparse.ArgumentTypeError(f"expected a complex number like -0.8+0.156i, not {text!r}") from None
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Draw the Mandelbrot set and Julia sets, in the terminal or a PNG")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--centre", type=parse_complex, default=None,
+                        help="the middle of the picture (default: -0.75 for Mandelbrot, 0 for Julia)")
+    common.add_argument("--zoom", type=float, default=1.0, help="at 1, the picture is 3.5 wide")
+    common.add_argument("--iterations", type=int, default=100, help="the squarings before a point is in the set")
+    common.add_argument("--width", type=int, default=79, help="in characters, or pixels for a PNG")
+    common.add_argument("--height", type=int, default=None, help="(default: in proportion to the width)")
+    common.add_argument("--workers", type=int, default=os.cpu_count() or 1, help="processes to draw with")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    mandelbrot_parser = subparsers.add_parser("mandelbrot", parents=[common], help="draw the Mandelbrot set")
+    julia_parser = subparsers.add_parser("julia", parents=[common], help="draw the Julia set of c")
+    julia_parser.add_argument("--c", type=parse_complex, default=complex(-0.8, 0.156))
+    for subparser in (mandelbrot_parser, julia_parser):
+        subparser.add_argument("--png", type=str, default=None, help="save a picture here instead")
+    bench_parser = subparsers.add_parser("bench", parents=[common], help="time the Mandelbrot set with more workers")
+    bench_parser.add_argument("--repeat", type=int, default=1, help="draw each this many times, and keep the best")
+    args = parser.parse_args()
+
+    if args.width < 1 or args.zoom <= 0 or args.iterations < 1 or args.workers < 1:
+        parser.exit(1, "error: --width, --zoom, --iterations and --workers must be positive\n")
+    kind = "julia" if args.command == "julia" else "mandelbrot"
+    png = getattr(args, "png", None)
+    aspect = 1.0 if png is not None or args.command == "bench" else 2.0
+    height = args.height if args.height is not None else max(1, round(args.width * 2 / 3 / aspect))
+    if height < 1:
+        parser.exit(1, "error: --height must be positive\n")
+    centre = args.centre if args.centre is not None else complex(-0.75 if kind == "mandelbrot" else 0.0, 0)
+    view = View(kind, getattr(args, "c", 0j), centre, args.zoom, args.width, height, aspect, args.iterations)
+
+    if args.command == "bench":
+        print(f"{args.width}x{height} points, {args.iterations} iterations")
+        counts = [1 << k for k in range(args.workers.bit_length()) if 1 << k < args.workers] + [args.workers]
+        first = None
+        for workers in counts:
+            best = math.inf
+            for _ in range(max(1, args.repeat)):
+                start = time.perf_counter()
+                render(view, workers)
+                best = min(best, time.perf_counter() - start)
+            first = first or best
+            print(f"{workers:>3} worker{'s' * (workers != 1)}: {best:.3f}s, {first / best:.2f}x")
+        return
+    counts = render(view, args.workers)
+    if png is None:
+        print(shade(counts))
+        return
+    try:
+        write_png(png, [[colour(count) for count in row] for row in counts])
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    print(f"{args.width}x{height} written to {png}")
+
+
+if __name__ == "__main__":
+    main()
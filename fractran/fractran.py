@@ -0,0 +1,166 @@
+"""
+# FRACTRAN
+
+John Conway's FRACTRAN: a program is a list of fractions, and the state
+is one positive integer n. At every step, n is multiplied by the first
+fraction f in the list for which n * f is an integer. When there is no
+such fraction, the program halts.
+
+It helps to think of n by its prime factorization: each prime is a
+register holding its exponent. Multiplying by 3/2 takes one from
+register 2 and adds one to register 3, and it only applies when
+register 2 is not empty. So `3/2` on 2^a 3^b adds the registers, leaving
+3^(a + b). `--trace` shows the registers at every step.
+
+Python's integers are unbounded, so n can grow as large as it likes.
+
+
+## Examples
+
+    add         3/2                                 2^a 3^b -> 3^(a + b)
+    multiply    455/33 11/13 1/11 3/7 11/2 1/3      2^a 3^b -> 5^(a b)
+    primegame   Conway's PRIMEGAME (below)          2 -> 2^2, 2^3, 2^5, 2^7, ...
+
+PRIMEGAME never halts. Starting from 2, the powers of 2 it passes
+through are exactly 2^p for the primes p, in order. `--powers-of 2`
+prints just those exponents.
+
+
+## Usage
+
+    python3 fractran.py --example add --input 72 --trace
+    python3 fractran.py --program "455/33 11/13 1/11 3/7 11/2 1/3" --input 2^3*3^4
+    python3 fractran.py --example primegame --input 2 --powers-of 2 --max-steps 1000000
+"""
+
+import argparse
+import os
+import re
+import sys
+from typing import Dict, Iterator, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from number_theory.number_theory import factorize
+
+
+DEFAULT_MAX_STEPS: int = 1_000_000
+Fraction = Tuple[int, int]
+
+EXAMPLES: Dict[str, str] = {
+    "add": "3/2",
+    "multiply": "455/33 11/13 1/11 3/7 11/2 1/3",
+    "primegame": "17/91 78/85 19/51 23/38 29/33 77/29 95/23 77/19 1/17 11/13 13/11 15/14 15/2 55/1",
+}
+
+
+def parse_program(text: str) -> List[Fraction]:
+    fractions: List[Fraction] = []
+    for token in re.split(r"[\s,]+", text.strip()):
+        match = re.fullmatch(r"(\d+)/(\d+)", token)
+        if match is None:
+            raise ValueError(f"'{token}' is not a fraction")
+        numerator, denominator = int(match.group(1)), int(match.group(2))
+        if numerator == 0 or denominator == 0:
+            raise ValueError(f"'{token}' must be positive")
+        fractions.append((numerator, denominator))
+    return fractions
+
+
+def parse_number(text: str) -> int:
+    """A positive integer, which may be written as a product of powers like 2^3*3^4."""
+    n = 1
+    for factor in text.replace(" ", "").split("*"):
+        match = re.fullmatch(r"(\d+)(?:\^(\d+))?", factor)
+        if match is None:
+            raise ValueError(f"cannot read the number '{text}'")
+        n *= int(match.group(1)) ** int(match.group(2) or 1)
+    if n < 1:
+        raise ValueError("the input must be positive")
+    return n
+
+
+def steps(program: List[Fraction], n: int) -> Iterator[int]:
+    """Yield each value of n after the first, until the program halts."""
+    while True:
+        for numerator, denominator in program:
+            if n % denominator == 0:
+                n = n // denominator * numerator
+                yield n
+                break
+        else:
+            return
+
+
+def prime_factors(n: int) -> List[int]:
+    return list(factorize(n)) if n > 0 else []
+
+
+def registers(program: List[Fraction], n: int) -> List[int]:
+    """Every prime that can ever divide n: those of the fractions and of the input."""
+    primes = set(prime_factors(n))
+    for numerator, denominator in program:
+        primes.update(prime_factors(numerator))
+        primes.update(prime_factors(denominator))
+    return sorted(primes)
+
+
+def format_registers(n: int, primes: List[int]) -> str:
+    shown: List[str] = []
+    for p in primes:
+        exponent = 0
+        while n % p == 0:
+            n //= p
+            exponent += 1
+        shown.append(f"{p}:{exponent}")
+    return " ".join(shown)
+
+
+def power_of(n: int, base: int) -> Optional[int]:
+    exponent = 0
+    while n % base == 0:
+        n //= base
+        exponent += 1
+    return exponent if n == 1 else None
+
+
+def main():
+    parser = argparse.ArgumentParser(description="FRACTRAN interpreter")
+    program_group = parser.add_mutually_exclusive_group(required=True)
+    program_group.add_argument("--program", type=str, help="the fractions, e.g. \"3/2 5/3\"")
+    program_group.add_argument("--example", choices=sorted(EXAMPLES), help="a bundled program")
+    parser.add_argument("--input", type=str, required=True, help="the starting n, e.g. 72 or 2^3*3^2")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="print the registers at every step")
+    parser.add_argument("--powers-of", type=int, default=None, metavar="B",
+                        help="only print the exponents of the powers of B that n passes through")
+    args = parser.parse_args()
+
+    try:
+        program = parse_program(args.program or EXAMPLES[args.example])
+        n = parse_number(args.input)
+    except ValueError as e:
+        parser.error(str(e))
+    primes = registers(program, n)
+
+    if args.trace:
+        print(f"{0:>8}  {format_registers(n, primes)}")
+    count, halted = 0, True
+    for count, n in enumerate(steps(program, n), start=1):
+        if args.trace:
+            print(f"{count:>8}  {format_registers(n, primes)}")
+        if args.powers_of is not None:
+            exponent = power_of(n, args.powers_of)
+            if exponent is not None:
+                print(f"{count:>8}  {args.powers_of}^{exponent}")
+        if count >= args.max_steps:
+            halted = False
+            break
+    print(f"Result: {'halt' if halted else 'step limit exceeded'}")
+    print(f"Steps: {count}")
+    print(f"Registers: {format_registers(n, primes)}")
+    if len(str(n)) <= 60:
+        print(f"n = {n}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,271 @@
+"""
+# Futoshiki
+
+Fill an n by n grid with 1 to n, each once in every row and column (a
+Latin square), so that each inequality between two neighbouring cells
+holds. Some cells may be given.
+
+
+## Solving
+
+By the shared CSP solver: `add_latin_square` (from `latin/latin.py`)
+gives a variable per cell and all-different on each row and column, a
+given cell has only its number in its domain, and each inequality is a
+binary constraint, which AC-3 keeps arc consistent all through the
+search: a cell less than another loses its biggest values, and more
+than one its smallest, as soon as the other's range narrows.
+
+
+## Generating
+
+A random Latin square (completed from an empty one by `latin.generate`)
+is the solution, and every clue it has, each inequality between
+neighbours and each cell's number, starts in the puzzle. In a random
+order, each clue is taken away if the puzzle still has one solution
+without it, so no clue left is needed for the others. `--givens` keeps
+only that many of the numbers to begin with; with too few, the
+inequalities may not be enough.
+
+
+## Rating
+
+`rate` solves the way a person would, with `rate_csp` on the scale of
+`difficulty/difficulty.py`: arc consistency alone (easy), then probing
+(medium, or hard with a lot of it), and the search when both are stuck.
+`generate --difficulty` keeps making puzzles until one rates at the
+level asked. Taking clues away for as long as the solution stays unique
+leaves puzzles that arc consistency and a little probing solve, so most
+are easy or medium; with fewer givens (`--givens 0`) they come out
+harder, up to fiendish.
+
+
+## The Format
+
+Rows of cells, 1 to n or . for empty, with < or > (or a space) between
+each pair across; between two rows, a line with ^ (the upper is less)
+or v (the upper is more) under each cell, or a space. Lines starting
+with # are comments.
+
+    . . .<.
+    ^     v
+    .>. . .
+    . . . .
+          ^
+    . 2 . .
+
+
+## Usage
+
+    python3 futoshiki.py solve puzzles/four.txt
+    python3 futoshiki.py solve puzzles/seven.txt --stats
+    python3 futoshiki.py solve puzzles/ambiguous.txt
+    python3 futoshiki.py generate --size 5 --seed 1
+    python3 futoshiki.py generate --size 6 --seed 1 --givens 0
+    python3 futoshiki.py generate --size 5 --seed 1 --difficulty medium
+    python3 futoshiki.py generate --size 5 --seed 1 --givens 0 --difficulty fiendish
+    python3 futoshiki.py rate puzzles/seven.txt
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.csp import CSP, Statistics, solutions
+from difficulty.difficulty import ARC_CONSISTENCY, LEVELS, PROBING, Scale, generate_rated, rate_csp
+from grid.grid import Grid, Position
+from latin.latin import add_latin_square, generate as generate_latin
+from rng.rng import add_seed_argument, make_rng
+
+
+# Typical uses: the values arc consistency crosses off in a 5 by 5, and a little probing
+SCALE = Scale([(ARC_CONSISTENCY, 100), (PROBING, 5)], guesses=5)
+ATTEMPTS: int = 200
+
+
+class Less(NamedTuple):
+    """The cell at smaller is less than the one at bigger, its neighbour."""
+    smaller: Position
+    bigger: Position
+
+
+class Puzzle(NamedTuple):
+    size: int
+    given: Dict[Position, int]
+    inequalities: List[Less]
+
+
+def to_csp(puzzle: Puzzle) -> CSP:
+    csp = CSP()
+    add_latin_square(csp, puzzle.size)
+    for position, number in puzzle.given.items():
+        csp.domains[position] = [number]
+    for less in puzzle.inequalities:
+        csp.add_constraint([less.smaller, less.bigger], lambda a, b: a < b)
+    return csp
+
+
+def solve(puzzle: Puzzle, limit: int = 2, statistics: Optional[Statistics] = None) -> List[Grid]:
+    found = []
+    for assignment in solutions(to_csp(puzzle), statistics=statistics):
+        found.append(Grid([[assignment[x, y] for x in range(puzzle.size)] for y in range(puzzle.size)]))
+        if len(found) >= limit:
+            break
+    return found
+
+
+################################################################################
+###                                Generating                                ###
+################################################################################
+
+
+def generate(size: int, rng: random.Random, givens: Optional[int] = None) -> Tuple[Puzzle, Grid]:
+    """A puzzle with one solution, and the solution."""
+    solution = Grid(generate_latin(size, rng))
+    clues: List[Tuple[str, Position, Position]] = [("given", position, position) for position in solution.positions()]
+    for x, y in solution.positions():
+        for neighbour in ((x + 1, y), (x, y + 1)):
+            if neighbour in solution:
+                a, b = sorted([(x, y), neighbour], key=lambda position: solution[position])
+                clues.append(("less", a, b))
+    rng.shuffle(clues)
+    if givens is not None:
+        allowed = [position for kind, position, _ in clues if kind == "given"][:givens]
+        clues = [clue for clue in clues if clue[0] == "less" or clue[1] in allowed]
+
+    def puzzle_of(chosen: List[Tuple[str, Position, Position]]) -> Puzzle:
+        return Puzzle(size, {a: solution[a] for kind, a, _ in chosen if kind == "given"},
+                      [Less(a, b) for kind, a, b in chosen if kind == "less"])
+
+    chosen = clues
+    for clue in clues:
+        rest = [other for other in chosen if other != clue]
+        if len(solve(puzzle_of(rest))) == 1:
+            chosen = rest
+    puzzle = puzzle_of(chosen)
+    if len(solve(puzzle)) != 1:     # only when there were too few givens allowed
+        raise ValueError(f"no puzzle with at most {givens} givens came out with one solution; try another seed")
+    return puzzle, solution
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    lines = [(number, line.rstrip()) for number, line in enumerate(text.split("\n"), start=1)
+             if line.strip() and not line.lstrip().startswith("#")]
+    if not lines:
+        raise ValueError("the puzzle is empty")
+    size = (len(lines[0][1]) + 1) // 2
+    given: Dict[Position, int] = {}
+    inequalities: List[Less] = []
+    y = 0
+    for number, line in lines:
+        if len(line) > 2 * size - 1:
+            raise ValueError(f"line {number}: {len(line)} characters, but the first row has {2 * size - 1}")
+        line = line.ljust(2 * size - 1)
+        if set(line) <= set("^v "):
+            if y == 0 or any(c != " " for c in line[1::2]):
+                raise ValueError(f"line {number}: expected ^ or v under the cells, between two rows")
+            for x, c in enumerate(line[::2]):
+                if c != " ":
+                    above, below = (x, y - 1), (x, y)
+                    inequalities.append(Less(above, below) if c == "^" else Less(below, above))
+            continue
+        if set(line[::2]) - set(".123456789") or set(line[1::2]) - set("<> "):
+            raise ValueError(f"line {number}: expected cells, 1 to 9 or ., with < or > between")
+        for x, c in enumerate(line[::2]):
+            if c != ".":
+                given[x, y] = int(c)
+        for x, c in enumerate(line[1::2]):
+            if c != " ":
+                left, right = (x, y), (x + 1, y)
+                inequalities.append(Less(left, right) if c == "<" else Less(right, left))
+        y += 1
+    if y != size:
+        raise ValueError(f"{y} rows of {size}: the grid is not square")
+    if any(max(less.smaller[1], less.bigger[1]) >= size for less in inequalities):
+        raise ValueError("a line of ^ and v after the last row")
+    if any(not 1 <= n <= size for n in given.values()):
+        raise ValueError(f"the numbers are 1 to {size}")
+    return Puzzle(size, given, inequalities)
+
+
+def render(puzzle: Puzzle, grid: Optional[Grid] = None) -> str:
+    """The puzzle, with the numbers of grid (or the givens) in the cells."""
+    across = {tuple(sorted(less)): "<" if less.smaller < less.bigger else ">" for less in puzzle.inequalities
+              if less.smaller[1] == less.bigger[1]}
+    down = {tuple(sorted(less, key=lambda p: p[1])): "^" if less.smaller[1] < less.bigger[1] else "v"
+            for less in puzzle.inequalities if less.smaller[0] == less.bigger[0]}
+    lines = []
+    for y in range(puzzle.size):
+        between = " ".join(down.get(((x, y - 1), (x, y)), " ") for x in range(puzzle.size)).rstrip()
+        if between:
+            lines.append(between)
+        cells = []
+        for x in range(puzzle.size):
+            number = grid[x, y] if grid is not None else puzzle.given.get((x, y))
+            cells.append("." if number is None else str(number))
+            if x + 1 < puzzle.size:
+                cells.append(across.get(((x, y), (x + 1, y)), " "))
+        lines.append("".join(cells).rstrip())
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve and generate Futoshiki")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a puzzle")
+    solve_parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    solve_parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    generate_parser = subparsers.add_parser("generate", help="make a puzzle with one solution")
+    generate_parser.add_argument("--size", type=int, default=5, choices=range(2, 10))
+    add_seed_argument(generate_parser)
+    generate_parser.add_argument("--givens", type=int, default=None, help="give at most this many numbers")
+    generate_parser.add_argument("--difficulty", choices=LEVELS, default=None,
+                                 help="only keep puzzles that rate at this level")
+    rate_parser = subparsers.add_parser("rate", help="rate how hard the puzzle is for a person")
+    rate_parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "generate":
+            rng = make_rng(args.seed)
+            (puzzle, solution), rating = generate_rated(lambda: generate(args.size, rng, args.givens),
+                                                        lambda made: rate_csp(to_csp(made[0])), SCALE, args.difficulty,
+                                                        ATTEMPTS)
+            print(f"# {SCALE.describe(rating)}")
+            print(render(puzzle))
+            print()
+            print(render(puzzle, solution))
+            return
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "rate":
+        found = solve(puzzle, limit=2)
+        print(SCALE.describe(rate_csp(to_csp(puzzle))) if len(found) == 1 else
+              "More than one solution, so no rating" if found else "No solution")
+        if not found:
+            parser.exit(1)
+        return
+    statistics = Statistics()
+    found = solve(puzzle, limit=2, statistics=statistics)
+    if not found:
+        print("No solution")
+    else:
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,350 @@
+"""
+# 2048
+
+A 4x4 grid of tiles, powers of two. Each move slides every tile as far
+as it goes left, right, up or down, and two tiles of the same value that
+meet become one of twice the value, which scores that much; a tile made
+by a merge does not merge again in the same move. After each move that
+changes anything, a 2 (nine times in ten) or a 4 appears in a random
+empty cell. The game is over when no move changes anything; the aim is
+a 2048 tile, and then as high a score as possible.
+
+
+## The Board
+
+A tile is stored as its exponent, 2^k as k, in 4 bits, so the board is
+one 64 bit integer, a row to each 16 bits. There are only 2^16 rows, so
+a move to the left is looked up for every row when the module is
+loaded, along with its score and its heuristic value (below); a move to
+the right is the row reversed, moved left and reversed back, and up and
+down are left and right on the board transposed, which is a few masks
+and shifts.
+
+
+## The Player
+
+Expectimax: a move is worth the best of what it leads to, and a new
+tile the average over the empty cells and the 2 or the 4, weighted by
+how likely each is. The search looks --depth moves ahead, and skips the
+tiles too unlikely to matter (a chance of less than 1 in 10,000 along
+the way there), where it takes the heuristic value of the board:
+
+    empty       270 for each empty cell
+    merges      700 for each pair of equal tiles side by side
+    monotonic   a row or column whose tiles go up or down from one end
+                to the other is good, so lose 47 for each k^4 of the
+                steps against the better of the two ways
+    sum         lose 11 for each k^3.5, so that big tiles are a cost
+                to be merged away
+
+summed over the rows and the columns. These are the weights of
+nneonneo's 2048-ai, tuned by many games of search. Boards met twice in
+one search are looked up in a transposition cache, with the depth they
+were searched to.
+
+
+## Usage
+
+    python3 game_2048.py play
+    python3 game_2048.py play --no-record
+    python3 game_2048.py watch --depth 1 --seed 1
+    python3 game_2048.py simulate --games 3 --depth 1 --seed 1
+
+At --depth 1, a game takes a couple of seconds and usually reaches
+2048; at the default of 2, it takes a minute or so, and gets to 4096 or
+8192.
+"""
+
+import argparse
+import os
+import random
+import sys
+import time
+from typing import Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from rng.rng import add_seed_argument, make_rng
+
+
+Board = int
+MOVES: List[str] = ["up", "down", "left", "right"]
+KEYS: Dict[str, str] = {"w": "up", "s": "down", "a": "left", "d": "right"}
+ROW_MASK: int = 0xFFFF
+# The chance of a new tile being a 4 rather than a 2
+FOUR: float = 0.1
+# Tiles less likely than this to appear, along the way, are not searched
+THRESHOLD: float = 0.0001
+# The heuristic weights (from nneonneo/2048-ai)
+LOST_PENALTY: float = 200000.0
+EMPTY_WEIGHT: float = 270.0
+MERGES_WEIGHT: float = 700.0
+MONOTONIC_POWER: float = 4.0
+MONOTONIC_WEIGHT: float = 47.0
+SUM_POWER: float = 3.5
+SUM_WEIGHT: float = 11.0
+
+
+def unpack(row: int) -> List[int]:
+    return [(row >> (4 * i)) & 0xF for i in range(4)]
+
+
+def pack(line: List[int]) -> int:
+    return sum(rank << (4 * i) for i, rank in enumerate(line))
+
+
+def reverse(row: int) -> int:
+    return pack(unpack(row)[::-1])
+
+
+def slide(line: List[int]) -> Tuple[List[int], int]:
+    """The line moved towards its start, and the points scored."""
+    tiles = [rank for rank in line if rank]
+    merged, score, i = [], 0, 0
+    while i < len(tiles):
+        if i + 1 < len(tiles) and tiles[i] == tiles[i + 1] and tiles[i] < 15:
+            merged.append(tiles[i] + 1)
+            score += 1 << (tiles[i] + 1)
+            i += 2
+        else:
+            merged.append(tiles[i])
+            i += 1
+    return merged + [0] * (4 - len(merged)), score
+
+
+def heuristic_line(line: List[int]) -> float:
+    empty = line.count(0)
+    merges, previous, counter = 0, 0, 0
+    for rank in line:
+        if rank == 0:
+            continue
+        if previous == rank:
+            counter += 1
+        elif counter > 0:
+            merges += 1 + counter
+            counter = 0
+        previous = rank
+    if counter > 0:
+        merges += 1 + counter
+    towards_start, towards_end = 0.0, 0.0
+    for a, b in zip(line, line[1:]):
+        if a > b:
+            towards_start += a ** MONOTONIC_POWER - b ** MONOTONIC_POWER
+        else:
+            towards_end += b ** MONOTONIC_POWER - a ** MONOTONIC_POWER
+    total = sum(rank ** SUM_POWER for rank in line)
+    return (LOST_PENALTY + EMPTY_WEIGHT * empty + MERGES_WEIGHT * merges
+            - MONOTONIC_WEIGHT * min(towards_start, towards_end) - SUM_WEIGHT * total)
+
+
+def tables() -> Tuple[List[int], List[int], List[int], List[float]]:
+    """For every row: moved left, moved right, the points scored, and its heuristic value."""
+    left, right, scores, heuristic = [0] * 65536, [0] * 65536, [0] * 65536, [0.0] * 65536
+    for row in range(65536):
+        line = unpack(row)
+        moved, score = slide(line)
+        left[row] = pack(moved)
+        scores[row] = score
+        heuristic[row] = heuristic_line(line)
+    for row in range(65536):
+        right[row] = reverse(left[reverse(row)])
+    return left, right, scores, heuristic
+
+
+LEFT, RIGHT, SCORES, HEURISTIC = tables()
+
+
+def transpose(board: Board) -> Board:
+    a1 = board & 0xF0F00F0FF0F00F0F
+    a2 = board & 0x0000F0F00000F0F0
+    a3 = board & 0x0F0F00000F0F0000
+    a = a1 | (a2 << 12) | (a3 >> 12)
+    b1 = a & 0xFF00FF0000FF00FF
+    b2 = a & 0x00FF00FF00000000
+    b3 = a & 0x00000000FF00FF00
+    return b1 | (b2 >> 24) | (b3 << 24)
+
+
+def rows(board: Board) -> List[int]:
+    return [(board >> (16 * r)) & ROW_MASK for r in range(4)]
+
+
+def move(board: Board, direction: str) -> Tuple[Board, int]:
+    """The board after the move, and the points scored."""
+    vertical = direction in ("up", "down")
+    if vertical:
+        board = transpose(board)
+    table = LEFT if direction in ("left", "up") else RIGHT
+    moved, score = 0, 0
+    for r, row in enumerate(rows(board)):
+        moved |= table[row] << (16 * r)
+        score += SCORES[row] if table is LEFT else SCORES[reverse(row)]
+    return (transpose(moved) if vertical else moved), score
+
+
+def empty_cells(board: Board) -> List[int]:
+    return [i for i in range(16) if not (board >> (4 * i)) & 0xF]
+
+
+def heuristic(board: Board) -> float:
+    return sum(HEURISTIC[row] for row in rows(board)) + sum(HEURISTIC[row] for row in rows(transpose(board)))
+
+
+def add_tile(board: Board, rng: random.Random) -> Board:
+    cell = rng.choice(empty_cells(board))
+    return board | ((2 if rng.random() < FOUR else 1) << (4 * cell))
+
+
+def highest(board: Board) -> int:
+    return 1 << max(rank for row in rows(board) for rank in unpack(row))
+
+
+################################################################################
+###                                Expectimax                                ###
+################################################################################
+
+
+class Player:
+    def __init__(self, depth: int = 2):
+        self.depth = depth
+        self.cache: Dict[Board, Tuple[int, float]] = {}
+
+    def moves_node(self, board: Board, depth: int, chance: float) -> float:
+        best = 0.0
+        for direction in MOVES:
+            after, _ = move(board, direction)
+            if after != board:
+                best = max(best, self.tiles_node(after, depth, chance))
+        return best
+
+    def tiles_node(self, board: Board, depth: int, chance: float) -> float:
+        if depth >= self.depth or chance < THRESHOLD:
+            return heuristic(board)
+        cached = self.cache.get(board)
+        if cached is not None and cached[0] <= depth:
+            return cached[1]
+        empty = empty_cells(board)
+        chance /= len(empty)
+        total = 0.0
+        for cell in empty:
+            total += (1 - FOUR) * self.moves_node(board | (1 << (4 * cell)), depth + 1, chance * (1 - FOUR))
+            total += FOUR * self.moves_node(board | (2 << (4 * cell)), depth + 1, chance * FOUR)
+        value = total / len(empty)
+        self.cache[board] = (depth, value)
+        return value
+
+    def choose(self, board: Board) -> Optional[str]:
+        """The best move, or None if no move changes anything."""
+        self.cache = {}
+        best, best_value = None, -1.0
+        for direction in MOVES:
+            after, _ = move(board, direction)
+            if after != board:
+                value = self.tiles_node(after, 0, 1.0)
+                if value > best_value:
+                    best, best_value = direction, value
+        return best
+
+
+################################################################################
+###                                  Playing                                 ###
+################################################################################
+
+
+def render(board: Board) -> str:
+    lines = []
+    for row in rows(board):
+        lines.append(" ".join(f"{1 << rank if rank else '.':>5}" for rank in unpack(row)))
+    return "\n".join(lines)
+
+
+def new_game(rng: random.Random) -> Board:
+    return add_tile(add_tile(0, rng), rng)
+
+
+def play_game(player: Player, rng: random.Random, show: bool = False) -> Tuple[int, int, int]:
+    """The score, the highest tile and the number of moves of one game."""
+    board, score, moves = new_game(rng), 0, 0
+    while True:
+        direction = player.choose(board)
+        if direction is None:
+            return score, highest(board), moves
+        board, points = move(board, direction)
+        score += points
+        moves += 1
+        board = add_tile(board, rng)
+        if show:
+            print(f"\n{moves}. {direction}, score {score}\n{render(board)}")
+
+
+def play(rng: random.Random, player: Player, instance: str = "random", record: bool = True):
+    """A game for a person at the keyboard: w a s d and Enter to move, h for a hint, q to quit."""
+    board, score = new_game(rng), 0
+    session = Session("game_2048", instance, record)
+    session.start()
+    while True:
+        print(f"\nScore {score}\n{render(board)}")
+        if all(move(board, direction)[0] == board for direction in MOVES):
+            print(f"Game over: score {score}, highest tile {highest(board)}")
+            session.finish("won" if highest(board) >= 2048 else "lost", score)
+            return
+        try:
+            key = input("Move (w a s d, h for a hint, q to quit)? ").strip().lower()
+        except EOFError:
+            print()
+            session.abandon(score)
+            return
+        if key == "q":
+            session.abandon(score)
+            return
+        if key == "h":
+            print(f"Hint: {player.choose(board)}")
+            continue
+        if key not in KEYS:
+            continue
+        after, points = move(board, KEYS[key])
+        if after == board:
+            print("That move changes nothing")
+            continue
+        board, score = add_tile(after, rng), score + points
+        session.move()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Play 2048, or watch expectimax play it")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--depth", type=int, default=2, help="the moves the player looks ahead (default 2)")
+    add_seed_argument(common)
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    play_parser = subparsers.add_parser("play", parents=[common], help="play a game yourself, with hints")
+    play_parser.add_argument("--no-record", action="store_true", help="leave the game out of the progress record")
+    subparsers.add_parser("watch", parents=[common], help="watch the player play a game")
+    simulate_parser = subparsers.add_parser("simulate", parents=[common],
+                                            help="play games and report the scores and tiles")
+    simulate_parser.add_argument("--games", type=int, default=10)
+    args = parser.parse_args()
+
+    rng = make_rng(args.seed)
+    player = Player(args.depth)
+    if args.command == "play":
+        play(rng, player, "random" if args.seed is None else f"seed {args.seed}", not args.no_record)
+    elif args.command == "watch":
+        score, tile, moves = play_game(player, rng, show=True)
+        print(f"\nGame over after {moves} moves: score {score}, highest tile {tile}")
+    else:
+        tiles: Dict[int, int] = {}
+        scores = []
+        for game in range(1, args.games + 1):
+            start = time.monotonic()
+            score, tile, moves = play_game(player, rng)
+            scores.append(score)
+            tiles[tile] = tiles.get(tile, 0) + 1
+            print(f"Game {game}: score {score}, highest tile {tile}, {moves} moves, "
+                  f"{time.monotonic() - start:.1f}s", file=sys.stderr)
+        print(f"Average score {sum(scores) / len(scores):.0f}, best {max(scores)}, worst {min(scores)}")
+        for tile, count in sorted(tiles.items(), reverse=True):
+            print(f"{tile:>6}: {count} game{'s' * (count != 1)} ({100 * count / len(scores):.0f}%)")
+
+
+if __name__ == "__main__":
+    main()
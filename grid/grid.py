@@ -0,0 +1,186 @@
+"""
+# Grid
+
+A rectangle of cells, for the puzzles that are played on one (mazes,
+Sudoku, Nonograms, ...), so each of them does not have to write its own
+parsing, neighbours, and printing. Positions are (x, y), with x the
+column and y the row, counting from the top left.
+
+    grid = Grid.parse("#.#\\n...\\n#.#")
+    grid[1, 0]                      '.'
+    grid.neighbours((1, 1))         [(1, 0), (2, 1), (1, 2), (0, 1)]
+    grid.find("#")                  [(0, 0), (2, 0), (0, 2), (2, 2)]
+    print(grid.map(lambda c: c == "#").render(lambda c: "X" if c else " "))
+
+
+## Edges
+
+    bounded     off the edge is outside the grid: a position there has no
+                cell, and is never a neighbour
+    torus       the edges wrap around, so every position has a cell
+
+`get(position, default)` reads off the edge of a bounded grid without
+raising, like the dead cells around a finite Game of Life.
+
+
+## Usage
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from grid.grid import Grid
+"""
+
+from typing import Callable, Generic, Iterator, List, Optional, Sequence, Tuple, TypeVar
+
+
+T = TypeVar("T")
+U = TypeVar("U")
+Position = Tuple[int, int]
+
+EDGES: List[str] = ["bounded", "torus"]
+ORTHOGONAL: List[Position] = [(0, -1), (1, 0), (0, 1), (-1, 0)]     # up, right, down, left
+DIAGONAL: List[Position] = [(1, -1), (1, 1), (-1, 1), (-1, -1)]
+ALL_DIRECTIONS: List[Position] = [
+    (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)
+]   # clockwise from up
+
+
+class Grid(Generic[T]):
+    def __init__(self, rows: Sequence[Sequence[T]], edges: str = "bounded"):
+        if edges not in EDGES:
+            raise ValueError(f"unknown edges '{edges}': expected one of {', '.join(EDGES)}")
+        self.cells: List[List[T]] = [list(row) for row in rows]
+        self.height = len(self.cells)
+        self.width = len(self.cells[0]) if self.cells else 0
+        if any(len(row) != self.width for row in self.cells):
+            raise ValueError("every row of a grid must be the same width")
+        self.edges = edges
+
+    @classmethod
+    def filled(cls, width: int, height: int, value: T, edges: str = "bounded") -> "Grid[T]":
+        return cls([[value] * width for _ in range(height)], edges)
+
+    @classmethod
+    def parse(cls, text: str, cell: Callable[[str], T] = lambda c: c, separator: Optional[str] = None,
+              edges: str = "bounded", pad: Optional[str] = None) -> "Grid[T]":
+        """
+        One row per line, ignoring blank lines at the start and end. Each
+        character is a cell, or with a separator (e.g. " " or ","), each word
+        is. With `pad`, short rows are padded with that, instead of an error.
+        """
+        lines = text.strip("\n").split("\n")
+        if separator is None:
+            words = [list(line.rstrip("\r")) for line in lines]
+        elif separator == " ":
+            words = [line.split() for line in lines]
+        else:
+            words = [[word.strip() for word in line.split(separator)] for line in lines]
+        if pad is not None:
+            width = max(len(row) for row in words)
+            words = [row + [pad] * (width - len(row)) for row in words]
+        for y, row in enumerate(words, start=1):
+            if len(row) != len(words[0]):
+                raise ValueError(f"line {y}: expected {len(words[0])} cells, not {len(row)}")
+        return cls([[cell(word) for word in row] for row in words], edges)
+
+    def normalize(self, position: Position) -> Optional[Position]:
+        """The position on the grid, after wrapping; None if it is off a bounded grid."""
+        x, y = position
+        if self.edges == "torus" and self.width and self.height:
+            return x % self.width, y % self.height
+        if 0 <= x < self.width and 0 <= y < self.height:
+            return x, y
+        return None
+
+    def __contains__(self, position: Position) -> bool:
+        return self.normalize(position) is not None
+
+    def __getitem__(self, position: Position) -> T:
+        normalized = self.normalize(position)
+        if normalized is None:
+            raise IndexError(f"{position} is off the {self.width}x{self.height} grid")
+        return self.cells[normalized[1]][normalized[0]]
+
+    def __setitem__(self, position: Position, value: T):
+        normalized = self.normalize(position)
+        if normalized is None:
+            raise IndexError(f"{position} is off the {self.width}x{self.height} grid")
+        self.cells[normalized[1]][normalized[0]] = value
+
+    def get(self, position: Position, default: Optional[T] = None) -> Optional[T]:
+        normalized = self.normalize(position)
+        return default if normalized is None else self.cells[normalized[1]][normalized[0]]
+
+    def __eq__(self, other: object) -> bool:
+        return isinstance(other, Grid) and self.cells == other.cells
+
+    def __hash__(self) -> int:
+        return hash(tuple(map(tuple, self.cells)))
+
+    def neighbours(self, position: Position, diagonal: bool = False) -> List[Position]:
+        """The positions next to this one (clockwise from up), leaving out any off the grid."""
+        directions = ALL_DIRECTIONS if diagonal else ORTHOGONAL
+        found: List[Position] = []
+        for dx, dy in directions:
+            neighbour = self.normalize((position[0] + dx, position[1] + dy))
+            if neighbour is not None:
+                found.append(neighbour)
+        return found
+
+    def positions(self) -> Iterator[Position]:
+        """Row by row, from the top left."""
+        for y in range(self.height):
+            for x in range(self.width):
+                yield x, y
+
+    def items(self) -> Iterator[Tuple[Position, T]]:
+        for y, row in enumerate(self.cells):
+            for x, value in enumerate(row):
+                yield (x, y), value
+
+    def find(self, value: T) -> List[Position]:
+        return [position for position, cell in self.items() if cell == value]
+
+    def row(self, y: int) -> List[T]:
+        return list(self.cells[y])
+
+    def column(self, x: int) -> List[T]:
+        return [row[x] for row in self.cells]
+
+    def rows(self) -> List[List[T]]:
+        return [list(row) for row in self.cells]
+
+    def columns(self) -> List[List[T]]:
+        return [self.column(x) for x in range(self.width)]
+
+    def count(self, value: T) -> int:
+        return sum(row.count(value) for row in self.cells)
+
+    def map(self, function: Callable[[T], U]) -> "Grid[U]":
+        return Grid([[function(cell) for cell in row] for row in self.cells], self.edges)
+
+    def copy(self) -> "Grid[T]":
+        return Grid(self.cells, self.edges)
+
+    def transpose(self) -> "Grid[T]":
+        return Grid(self.columns(), self.edges)
+
+    def rotate(self) -> "Grid[T]":
+        """A quarter turn clockwise."""
+        return Grid([list(reversed(column)) for column in self.columns()], self.edges)
+
+    def render(self, cell: Callable[[T], str] = str, separator: str = "") -> str:
+        """
+        One line per row. With a separator, the cells are padded to the same
+        width, so a grid of numbers lines up.
+        """
+        texts = [[cell(value) for value in row] for row in self.cells]
+        if separator:
+            width = max((len(text) for row in texts for text in row), default=0)
+            return "\n".join(separator.join(text.rjust(width) for text in row) for row in texts)
+        return "\n".join("".join(row) for row in texts)
+
+    def __str__(self) -> str:
+        return self.render()
+
+    def __repr__(self) -> str:
+        return f"Grid({self.width}x{self.height}, {self.edges})"
@@ -0,0 +1,242 @@
+"""
+# Hangman by Information
+
+Guess the letter that tells the most about the word. Of the words it
+could still be (the candidates), each letter splits them by where it
+would be revealed: nowhere (a wrong guess), or in each set of places
+the letter can fill. The best letter is the one whose split has the
+most entropy, the expected information in bits,
+
+    H(letter) = -sum over places p of share(p) log2 share(p)
+
+as in `wordle/entropy.py`. Ties, and the end, when one candidate is
+left and every letter tells nothing, go to the letter in the most
+candidates, the least likely to be wrong.
+
+That is not the same as the fewest wrong guesses: the most information
+is a letter in about half of the candidates, and so a wrong guess about
+half of the time, where the letter in the most candidates (`--strategy
+frequency`, what `hangman.py`'s guess does) is seldom wrong now but may
+leave more to guess later. `simulate` plays every word of the list
+(or a sample) both ways to compare them. The words are equally likely,
+as in `hangman.py`.
+
+A candidate has the revealed letters where the pattern shows them, and
+in the blanks, none of the letters guessed so far: each one right is
+revealed everywhere it is, and each one wrong is nowhere.
+
+
+## Usage
+
+    python3 entropy.py guess _a__e_
+    python3 entropy.py guess _a__ --wrong snlrt
+    python3 entropy.py guess _a__ --wrong snlrt --strategy frequency
+    python3 entropy.py play jazz
+    python3 entropy.py simulate --length 4 --limit 500 --seed 1
+    python3 entropy.py simulate --length 4 --limit 500 --seed 1 --strategy frequency
+"""
+
+import argparse
+import math
+import os
+import string
+import sys
+from typing import Dict, List, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+from search.search import Progress
+
+
+DEFAULT_WORDS: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "words.txt")
+STRATEGIES: List[str] = ["entropy", "frequency"]
+BLANK: str = "_"
+# A wrong guess draws a part of the hanged man: head, body, two arms, two legs
+NUM_WRONG: int = 6
+
+
+def places(letter: str, word: str) -> int:
+    """Where letter is in word, as a bit mask of positions; 0 if it is not."""
+    return sum(1 << i for i, c in enumerate(word) if c == letter)
+
+
+def split(letter: str, candidates: Sequence[str]) -> Dict[int, int]:
+    """How many of the candidates would reveal the letter at each set of places."""
+    counts: Dict[int, int] = {}
+    for word in candidates:
+        mask = places(letter, word)
+        counts[mask] = counts.get(mask, 0) + 1
+    return counts
+
+
+def entropy(counts: Dict[int, int]) -> float:
+    n = sum(counts.values())
+    return math.log2(n) - sum(c * math.log2(c) for c in counts.values()) / n
+
+
+def choose(candidates: Sequence[str], guessed: str, strategy: str = "entropy") -> Tuple[str, float]:
+    """The next letter, and its entropy in bits."""
+    best, best_rank, best_bits = "", None, 0.0
+    for letter in string.ascii_lowercase:
+        if letter in guessed:
+            continue
+        counts = split(letter, candidates)
+        bits = entropy(counts)
+        present = len(candidates) - counts.get(0, 0)
+        rank = (bits, present) if strategy == "entropy" else (present, bits)
+        if best_rank is None or rank > best_rank:
+            best, best_rank, best_bits = letter, rank, bits
+    return best, best_bits
+
+
+def matches(word: str, pattern: str, guessed: str) -> bool:
+    """Whether word could be behind the pattern, after these letters were guessed."""
+    return len(word) == len(pattern) and all(c == p if p != BLANK else c not in guessed
+                                             for c, p in zip(word, pattern))
+
+
+def reveal(word: str, guessed: str) -> str:
+    return "".join(c if c in guessed else BLANK for c in word)
+
+
+def play(words: Sequence[str], answer: str, strategy: str = "entropy") -> List[Tuple[str, bool, int]]:
+    """The letters guessed, each with whether it was in the word and the number of candidates before it."""
+    candidates = [word for word in words if len(word) == len(answer)]
+    guessed = ""
+    turns = []
+    while BLANK in reveal(answer, guessed):
+        letter, _ = choose(candidates, guessed, strategy)
+        turns.append((letter, letter in answer, len(candidates)))
+        guessed += letter
+        candidates = [word for word in candidates if places(letter, word) == places(letter, answer)]
+    return turns
+
+
+################################################################################
+###                                Simulating                                ###
+################################################################################
+
+
+class Tally:
+    def __init__(self):
+        self.games = 0
+        self.wrong: Dict[int, int] = {}     # number of wrong guesses -> games
+
+    def add(self, wrong: int):
+        self.games += 1
+        self.wrong[wrong] = self.wrong.get(wrong, 0) + 1
+
+    def __str__(self) -> str:
+        total = sum(wrong * games for wrong, games in self.wrong.items())
+        lost = sum(games for wrong, games in self.wrong.items() if wrong >= NUM_WRONG)
+        lines = [f"{wrong:>2}: {games}" for wrong, games in sorted(self.wrong.items())]
+        lines.append(f"Average: {total / self.games:.3f} wrong guesses over {self.games} words, "
+                     f"{lost} hanged ({NUM_WRONG} or more wrong)")
+        return "\n".join(lines)
+
+
+def simulate(words: Sequence[str], answers: Sequence[str], strategy: str = "entropy",
+             progress: Optional[Progress] = None) -> Tally:
+    """
+    Play every answer. The answers that have had the same letters revealed
+    so far get the same next guess, so this goes through the tree of
+    games, a choice per node, rather than word by word.
+    """
+    tally = Tally()
+
+    def walk(candidates: List[str], playing: List[str], guessed: str, wrong: int):
+        letter, _ = choose(candidates, guessed, strategy)
+        groups: Dict[int, List[str]] = {}
+        for word in candidates:
+            groups.setdefault(places(letter, word), []).append(word)
+        chosen = set(playing)
+        for mask, group in groups.items():
+            still = [answer for answer in group if answer in chosen]
+            if not still:
+                continue
+            finished = [answer for answer in still if BLANK not in reveal(answer, guessed + letter)]
+            for _ in finished:
+                tally.add(wrong + (mask == 0))
+            if len(finished) < len(still):
+                walk(group, [answer for answer in still if answer not in finished], guessed + letter,
+                     wrong + (mask == 0))
+        if progress is not None:
+            progress.update(words=tally.games)
+
+    for length in sorted({len(answer) for answer in answers}):
+        walk([word for word in words if len(word) == length], [answer for answer in answers if len(answer) == length],
+             "", 0)
+    return tally
+
+
+def read_words(path: str) -> List[str]:
+    with open(path) as f:
+        words = [line.strip().lower() for line in f]
+    return list(dict.fromkeys(word for word in words if word.isalpha() and word.isascii()))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Play Hangman by expected information")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--words", type=str, default=DEFAULT_WORDS, help="the words it could be, one a line")
+    common.add_argument("--strategy", choices=STRATEGIES, default="entropy",
+                        help="the most information, or the letter in the most words")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    guess_parser = subparsers.add_parser("guess", parents=[common], help="the next letter, given the pattern")
+    guess_parser.add_argument("pattern", type=str, help=f"the word so far, with {BLANK} for a blank, like _a__e_")
+    guess_parser.add_argument("--wrong", type=str, default="", help="the letters guessed wrong")
+    play_parser = subparsers.add_parser("play", parents=[common], help="play against a word")
+    play_parser.add_argument("answer", type=str)
+    simulate_parser = subparsers.add_parser("simulate", parents=[common],
+                                            help="play every word and count the wrong guesses")
+    simulate_parser.add_argument("--length", type=int, default=None, help="only the words of this length")
+    simulate_parser.add_argument("--limit", type=int, default=None, help="play a random sample of this many")
+    add_seed_argument(simulate_parser, help="seed the sample")
+    simulate_parser.add_argument("--quiet", action="store_true", help="do not report the progress")
+    args = parser.parse_args()
+
+    try:
+        words = read_words(args.words)
+        if args.command == "guess":
+            pattern, wrong = args.pattern.lower(), args.wrong.lower()
+            if not all(c == BLANK or c in string.ascii_lowercase for c in pattern):
+                raise ValueError(f"a pattern is letters and {BLANK}, not {args.pattern!r}")
+            if not all(c in string.ascii_lowercase for c in wrong) or set(wrong) & set(pattern):
+                raise ValueError("the wrong letters are letters not in the pattern")
+        elif args.command == "play":
+            if args.answer.lower() not in words:
+                raise ValueError(f"{args.answer!r} is not in the word list")
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    if args.command == "guess":
+        guessed = wrong + "".join(set(pattern) - {BLANK})
+        candidates = [word for word in words if matches(word, pattern, guessed)]
+        if not candidates:
+            parser.exit(1, "No word fits the pattern\n")
+        letter, bits = choose(candidates, guessed, args.strategy)
+        print(f"{len(candidates)} candidates" + (f": {' '.join(candidates)}" if len(candidates) <= 10 else ""))
+        if not letter:
+            print("Every letter has been guessed")
+            return
+        present = len(candidates) - split(letter, candidates).get(0, 0)
+        print(f"Guess: {letter} ({bits:.2f} bits, in {present} of {len(candidates)} candidates)")
+    elif args.command == "play":
+        answer = args.answer.lower()
+        guessed, wrong = "", 0
+        for letter, right, left in play(words, answer, args.strategy):
+            guessed += letter
+            wrong += not right
+            print(f"{letter}  {reveal(answer, guessed)}  {'right' if right else 'wrong'}  ({left} candidates)")
+        print(f"{wrong} wrong guess{'es' * (wrong != 1)}" + (", hanged" if wrong >= NUM_WRONG else ""))
+    else:
+        answers = [word for word in words if args.length is None or len(word) == args.length]
+        if args.limit is not None:
+            answers = make_rng(args.seed).sample(answers, k=min(args.limit, len(answers)))
+        if not answers:
+            parser.exit(1, "error: no words to play\n")
+        print(simulate(words, answers, args.strategy, None if args.quiet else Progress(label="simulate")))
+
+
+if __name__ == "__main__":
+    main()
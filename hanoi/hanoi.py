@@ -0,0 +1,224 @@
+"""
+# Tower of Hanoi
+
+Move a tower of n disks from the first peg to the last, one disk at a
+time, never putting a disk on a smaller one. A move is (from, to), with
+the pegs numbered from 0.
+
+    3 pegs      2^n - 1 moves, which `moves` makes without recursion: move
+                number i (from 1) takes the disk numbered one more than the
+                trailing zeros of i, from peg (i & (i - 1)) % 3 to peg
+                ((i | (i - 1)) + 1) % 3. That builds the tower on the last
+                peg for odd n, and on the middle one for even n, so for
+                even n those two pegs swap
+    4+ pegs     the Frame-Stewart algorithm: move the top k disks aside
+                using every peg, the other n - k to the target with one
+                peg fewer (they cannot land on the k), and the k back on
+                top of them. The best k comes from the recurrence
+                FS(n, p) = min over k of 2 FS(k, p) + FS(n - k, p - 1),
+                which for 4 pegs is optimal (proved by Bousch in 2014)
+
+`--test` checks each against the others: every move sequence is legal
+and ends with the tower moved, the iterative and recursive 3-peg
+sequences are the same, and the lengths match a breadth-first search of
+every arrangement of the disks, for the small n that it can do.
+
+
+## Usage
+
+    python3 hanoi.py 3
+    python3 hanoi.py 4 --show
+    python3 hanoi.py 10 --pegs 4 --count
+    python3 hanoi.py 5 --pegs 4 --step
+    python3 hanoi.py --test
+"""
+
+import argparse
+import functools
+import os
+import sys
+from typing import Iterator, List, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import bfs
+
+
+Move = Tuple[int, int]
+Pegs = List[List[int]]      # each peg's disks, from the bottom, numbered 1 (the smallest) to n
+
+
+def moves(n: int) -> Iterator[Move]:
+    """The 2^n - 1 moves taking n disks from peg 0 to peg 2, without recursion."""
+    swap = [0, 2, 1] if n % 2 == 0 else [0, 1, 2]
+    for i in range(1, 2 ** n):
+        yield swap[(i & (i - 1)) % 3], swap[((i | (i - 1)) + 1) % 3]
+
+
+def recursive_moves(n: int, source: int = 0, target: int = 2, spare: int = 1) -> Iterator[Move]:
+    if n == 0:
+        return
+    yield from recursive_moves(n - 1, source, spare, target)
+    yield source, target
+    yield from recursive_moves(n - 1, spare, target, source)
+
+
+@functools.lru_cache(maxsize=None)
+def frame_stewart(n: int, pegs: int) -> Tuple[int, int]:
+    """The fewest moves for n disks on that many pegs, and how many disks to move aside first."""
+    if n == 0:
+        return 0, 0
+    if pegs == 3 or n == 1:
+        return 2 ** n - 1, n - 1
+    return min((2 * frame_stewart(k, pegs)[0] + frame_stewart(n - k, pegs - 1)[0], k) for k in range(1, n))
+
+
+def multi_peg_moves(n: int, pegs: List[int]) -> Iterator[Move]:
+    """Frame-Stewart moves taking n disks from pegs[0] to pegs[-1], using the pegs between as spares."""
+    if n == 0:
+        return
+    source, target, spares = pegs[0], pegs[-1], pegs[1:-1]
+    if len(pegs) == 3:
+        yield from recursive_moves(n, source, target, spares[0])
+        return
+    if n == 1:
+        yield source, target
+        return
+    _, k = frame_stewart(n, len(pegs))
+    aside = spares[0]
+    yield from multi_peg_moves(k, [source] + spares[1:] + [target] + [aside])
+    yield from multi_peg_moves(n - k, [source] + spares[1:] + [target])
+    yield from multi_peg_moves(k, [aside] + [source] + spares[1:] + [target])
+
+
+def solve(n: int, pegs: int = 3) -> Iterator[Move]:
+    if pegs < 3:
+        raise ValueError("it takes at least 3 pegs")
+    return moves(n) if pegs == 3 else multi_peg_moves(n, list(range(pegs)))
+
+
+def apply(state: Pegs, move: Move):
+    """Make the move, raising ValueError if it is not allowed."""
+    source, target = move
+    if not state[source]:
+        raise ValueError(f"peg {source} is empty")
+    if state[target] and state[target][-1] < state[source][-1]:
+        raise ValueError(f"disk {state[source][-1]} cannot go on disk {state[target][-1]}")
+    state[target].append(state[source].pop())
+
+
+def start(n: int, pegs: int) -> Pegs:
+    return [list(range(n, 0, -1))] + [[] for _ in range(pegs - 1)]
+
+
+################################################################################
+###                               Checking                                   ###
+################################################################################
+
+
+def shortest(n: int, pegs: int) -> int:
+    """The fewest moves, by breadth-first search. A state is the peg of each disk, smallest first."""
+
+    def successors(state: Tuple[int, ...]) -> Iterator[Tuple[Move, Tuple[int, ...]]]:
+        tops = {}       # the smallest disk on each peg
+        for disk in reversed(range(n)):
+            tops[state[disk]] = disk
+        for source, disk in tops.items():
+            for target in range(pegs):
+                if target != source and (target not in tops or tops[target] > disk):
+                    yield (source, target), state[:disk] + (target,) + state[disk + 1:]
+
+    result = bfs((0,) * n, lambda state: state == (pegs - 1,) * n, successors)
+    assert result is not None
+    return len(result.moves)
+
+
+def check(n: int, pegs: int) -> List[str]:
+    problems = []
+    state = start(n, pegs)
+    sequence = list(solve(n, pegs))
+    try:
+        for move in sequence:
+            apply(state, move)
+    except ValueError as e:
+        problems.append(f"{n} disks, {pegs} pegs: an illegal move: {e}")
+    if state != [[] for _ in range(pegs - 1)] + [list(range(n, 0, -1))]:
+        problems.append(f"{n} disks, {pegs} pegs: the tower did not end up on the last peg")
+    if len(sequence) != frame_stewart(n, pegs)[0]:
+        problems.append(f"{n} disks, {pegs} pegs: {len(sequence)} moves, not {frame_stewart(n, pegs)[0]}")
+    if pegs == 3 and sequence != list(recursive_moves(n)):
+        problems.append(f"{n} disks: the iterative moves are not the recursive ones")
+    return problems
+
+
+def run_tests() -> bool:
+    problems = []
+    searched = {3: 8, 4: 7, 5: 5}       # the largest n to search, on that many pegs
+    for pegs in (3, 4, 5):
+        for n in range(0, 13):
+            problems += check(n, pegs)
+            if n <= searched[pegs] and shortest(n, pegs) != frame_stewart(n, pegs)[0]:
+                problems.append(f"{n} disks, {pegs} pegs: the search found {shortest(n, pegs)} moves, "
+                                f"not {frame_stewart(n, pegs)[0]}")
+    for problem in problems:
+        print(problem)
+    print("all tests ok" if not problems else f"{len(problems)} failed")
+    return not problems
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def render(state: Pegs, n: int) -> str:
+    width = 2 * n + 1
+    lines = []
+    for level in reversed(range(n)):
+        row = []
+        for peg in state:
+            if level < len(peg):
+                row.append(("=" * (2 * peg[level] - 1)).center(width))
+            else:
+                row.append("|".center(width))
+        lines.append(" ".join(row))
+    lines.append(" ".join("-" * width for _ in state))
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Tower of Hanoi")
+    parser.add_argument("n", type=int, nargs="?", default=3, help="the number of disks")
+    parser.add_argument("--pegs", type=int, default=3)
+    parser.add_argument("--count", action="store_true", help="only print how many moves it takes")
+    parser.add_argument("--show", action="store_true", help="draw the pegs after every move")
+    parser.add_argument("--step", action="store_true", help="draw the pegs, and wait for enter, after every move")
+    parser.add_argument("--test", action="store_true", help="check the solutions against each other and a search")
+    args = parser.parse_args()
+
+    if args.test:
+        if not run_tests():
+            parser.exit(1)
+        return
+    if args.n < 0 or args.pegs < 3:
+        parser.exit(1, "error: it takes at least 3 pegs, and a number of disks that is not negative\n")
+    if args.count:
+        print(f"{frame_stewart(args.n, args.pegs)[0]} moves")
+        return
+    state = start(args.n, args.pegs)
+    if args.show or args.step:
+        print(render(state, args.n))
+    for number, move in enumerate(solve(args.n, args.pegs), start=1):
+        apply(state, move)
+        print(f"{number}: disk {state[move[1]][-1]} from peg {move[0]} to peg {move[1]}")
+        if args.show or args.step:
+            print(render(state, args.n))
+            if args.step:
+                try:
+                    input()
+                except (EOFError, KeyboardInterrupt):
+                    print()
+                    break
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,305 @@
+"""
+# Hashiwokakero
+
+Also Bridges, or Hashi: islands on a grid, each with a number, to be
+joined by bridges. A bridge runs straight across or down, between two
+islands with only water between them, and does not cross another
+bridge. Two islands can have one bridge or two between them, but no
+more. Every island has as many bridges as its number, and they join all
+the islands into one.
+
+
+## Solving
+
+Each pair of islands that can see each other has a bridge count, known
+to lie between a least and a most (0 to 2 to begin with). The rules
+narrow those, over and over until none changes:
+
+    island      an island's bridges add up to its number, so each of its
+                counts is at least the number less the most the others
+                can give, and at most the number less the least they must
+    crossing    a bridge that is certainly there (a least of 1) rules out
+                the ones crossing it
+    isolation   two 1s joined would be cut off from the rest, and so
+                would two 2s joined twice: they can have at most 0 and 1
+                (unless they are the only islands)
+    connected   the islands joined by every bridge that could still be
+                there must be all of them, or there is no solution
+
+When the rules get stuck, the search picks the undecided count at the
+island with the fewest undecided, tries each value, most first, and
+narrows again. A solution must join every island, which the rules only
+check as far as "could still join". It stops after two, so it says
+whether the puzzle is proper.
+
+
+## The Format
+
+A grid of the islands' numbers (1 to 8), with . for water. Lines
+starting with # are comments.
+
+    2.3.2
+    .....
+    1.4.2
+
+
+## Usage
+
+    python3 hashi.py puzzles/small.txt
+    python3 hashi.py puzzles/medium.txt --stats
+    python3 hashi.py puzzles/ambiguous.txt
+    python3 hashi.py puzzles/large.txt --ascii
+"""
+
+import argparse
+import os
+import sys
+from typing import Dict, List, NamedTuple, Set
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+
+
+MAX_BRIDGES: int = 2
+# How a count of bridges is drawn, across and down, in Unicode and in ASCII
+UNICODE: Dict[bool, str] = {True: " ─═", False: " │║"}
+ASCII: Dict[bool, str] = {True: " -=", False: " |H"}
+
+
+class Island(NamedTuple):
+    position: Position
+    number: int
+
+
+class Link(NamedTuple):
+    """Two islands that can see each other, and the water between them."""
+    a: int
+    b: int
+    across: bool
+    water: List[Position]
+
+
+class Contradiction(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.narrowings = 0
+        self.guesses = 0
+
+    def __str__(self) -> str:
+        return f"{self.narrowings} counts narrowed, {self.guesses} guesses"
+
+
+class State:
+    """The least and the most bridges each link can still have."""
+
+    def __init__(self, least: List[int], most: List[int]):
+        self.least = least
+        self.most = most
+
+    def copy(self) -> "State":
+        return State(list(self.least), list(self.most))
+
+    def decided(self, link: int) -> bool:
+        return self.least[link] == self.most[link]
+
+
+class Puzzle:
+    def __init__(self, grid: Grid):
+        self.grid = grid
+        self.islands: List[Island] = [Island(position, int(c)) for position, c in grid.items() if c != "."]
+        index = {island.position: i for i, island in enumerate(self.islands)}
+        self.links: List[Link] = []
+        for i, island in enumerate(self.islands):
+            for dx, dy in ((1, 0), (0, 1)):
+                x, y = island.position
+                water = []
+                while (x + dx, y + dy) in grid:
+                    x, y = x + dx, y + dy
+                    if (x, y) in index:
+                        if water:
+                            self.links.append(Link(i, index[x, y], dy == 0, water))
+                        break
+                    water.append((x, y))
+        self.at: List[List[int]] = [[] for _ in self.islands]
+        for k, link in enumerate(self.links):
+            self.at[link.a].append(k)
+            self.at[link.b].append(k)
+        under: Dict[Position, List[int]] = {}
+        for k, link in enumerate(self.links):
+            for position in link.water:
+                under.setdefault(position, []).append(k)
+        self.crossing: List[Set[int]] = [set() for _ in self.links]
+        for links in under.values():
+            for k in links:
+                self.crossing[k].update(other for other in links if other != k)
+        for island, links in zip(self.islands, self.at):
+            if not 1 <= island.number <= MAX_BRIDGES * len(links):
+                raise ValueError(f"the island at {island.position} needs {island.number} bridges, but can have at "
+                                 f"most {MAX_BRIDGES * len(links)}")
+
+
+################################################################################
+###                                  Solving                                 ###
+################################################################################
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle):
+        self.puzzle = puzzle
+        self.statistics = Statistics()
+
+    def narrow(self, state: State, link: int, least: int, most: int) -> bool:
+        """Narrow a link's count to within least and most; whether it changed. Raises Contradiction if empty."""
+        least, most = max(least, state.least[link]), min(most, state.most[link])
+        if least > most:
+            raise Contradiction
+        if (least, most) == (state.least[link], state.most[link]):
+            return False
+        state.least[link], state.most[link] = least, most
+        self.statistics.narrowings += 1
+        return True
+
+    def propagate(self, state: State):
+        """Apply the rules until no count changes. Raises Contradiction if one has no value left."""
+        puzzle = self.puzzle
+        changed = True
+        while changed:
+            changed = False
+            for island, links in zip(puzzle.islands, puzzle.at):
+                least = sum(state.least[k] for k in links)
+                most = sum(state.most[k] for k in links)
+                if not least <= island.number <= most:
+                    raise Contradiction
+                for k in links:
+                    others_least, others_most = least - state.least[k], most - state.most[k]
+                    changed |= self.narrow(state, k, island.number - others_most, island.number - others_least)
+            for k, crossing in enumerate(puzzle.crossing):
+                if state.least[k] > 0:
+                    for other in crossing:
+                        changed |= self.narrow(state, other, 0, 0)
+        if not self.connected(state, lambda k: state.most[k] > 0):
+            raise Contradiction
+
+    def isolate(self, state: State):
+        """The isolation rule, which only depends on the numbers, so is applied once."""
+        puzzle = self.puzzle
+        if len(puzzle.islands) == 2:
+            return
+        for k, link in enumerate(puzzle.links):
+            a, b = puzzle.islands[link.a].number, puzzle.islands[link.b].number
+            if a == b and a <= MAX_BRIDGES:
+                self.narrow(state, k, 0, a - 1)
+
+    def connected(self, state: State, bridged) -> bool:
+        """Whether the links for which bridged(k) is true join every island."""
+        puzzle = self.puzzle
+        seen = {0}
+        stack = [0]
+        while stack:
+            i = stack.pop()
+            for k in puzzle.at[i]:
+                if bridged(k):
+                    link = puzzle.links[k]
+                    other = link.b if link.a == i else link.a
+                    if other not in seen:
+                        seen.add(other)
+                        stack.append(other)
+        return len(seen) == len(puzzle.islands)
+
+    def search(self, state: State, found: List[List[int]], limit: int):
+        undecided = {i: [k for k in links if not state.decided(k)] for i, links in enumerate(self.puzzle.at)}
+        undecided = {i: links for i, links in undecided.items() if links}
+        if not undecided:
+            if self.connected(state, lambda k: state.least[k] > 0):
+                found.append(state.least)
+            return
+        island = min(undecided, key=lambda i: len(undecided[i]))
+        link = undecided[island][0]
+        self.statistics.guesses += 1
+        for count in range(state.most[link], state.least[link] - 1, -1):
+            trial = state.copy()
+            try:
+                self.narrow(trial, link, count, count)
+                self.propagate(trial)
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, limit: int = 2) -> List[List[int]]:
+        """Up to limit solutions, each a count of bridges for every link."""
+        state = State([0] * len(self.puzzle.links), [MAX_BRIDGES] * len(self.puzzle.links))
+        found: List[List[int]] = []
+        try:
+            self.isolate(state)
+            self.propagate(state)
+        except Contradiction:
+            return found
+        self.search(state, found, limit)
+        return found
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    rows = []
+    for number, line in enumerate(text.split("\n"), start=1):
+        stripped = line.strip()
+        if not stripped or stripped.startswith("#"):
+            continue
+        if set(stripped) - set(".12345678"):
+            raise ValueError(f"line {number}: expected the islands' numbers, 1 to 8, and . for water")
+        if rows and len(stripped) != len(rows[0]):
+            raise ValueError(f"line {number}: {len(stripped)} cells, but the first row has {len(rows[0])}")
+        rows.append(stripped)
+    if not rows:
+        raise ValueError("the puzzle is empty")
+    puzzle = Puzzle(Grid(rows))
+    if not puzzle.islands:
+        raise ValueError("there are no islands")
+    return puzzle
+
+
+def render(puzzle: Puzzle, counts: List[int], symbols: Dict[bool, str] = UNICODE) -> str:
+    drawing = puzzle.grid.map(lambda c: " " if c == "." else c)
+    for link, count in zip(puzzle.links, counts):
+        for position in link.water:
+            if count:
+                drawing[position] = symbols[link.across][count]
+    return "\n".join(line.rstrip() for line in drawing.render().split("\n"))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Hashiwokakero (Bridges)")
+    parser.add_argument("file", type=str, help="the islands, in the format above")
+    parser.add_argument("--ascii", action="store_true", help="draw the bridges with - = | H")
+    parser.add_argument("--stats", action="store_true", help="print the counts narrowed and the guesses made")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    solver = Solver(puzzle)
+    found = solver.solutions(limit=2)
+    if not found:
+        print("No solution")
+    else:
+        print(render(puzzle, found[0], ASCII if args.ascii else UNICODE))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,368 @@
+"""
+# Hidato
+
+A grid with some numbers given. Fill in the rest, 1 up to the number of
+cells, so that each number is next to the one after it, across, down or
+diagonally: a path through every cell, like a king's tour.
+
+    .  33 35 .  .  #  #  #
+    .  .  24 22 .  #  #  #
+    .  .  .  21 .  .  #  #
+    .  26 .  13 40 11 #  #
+    27 .  .  .  9  .  1  #
+    #  #  .  .  18 .  .  #
+    #  #  #  #  .  7  .  .
+    #  #  #  #  #  #  5  .
+
+
+## Solving
+
+Backtracking, laying the numbers down in order: from the cell of k, the
+next, k + 1, goes in its given cell if it has one (which must be next
+to k's), or else in each empty cell next to it in turn, fewest empty
+neighbours first (Warnsdorff's rule, as for the knight's tour). Three
+things cut a branch short:
+
+    distance    the next given number m, at its cell, has to be reached
+                in m - (k + 1) steps, and a king needs at least the
+                larger of the rows and columns between them
+    dead ends   an empty cell needs two cells around it that could come
+                before and after it: empty ones, the last number laid
+                down, or given numbers whose own neighbours in the path
+                are not given (only one, if it could be the last)
+    joined      every empty cell must still be reachable from k + 1,
+                through empty cells and numbers still to come
+
+If 1 is not given, each empty cell is tried as its start. The search
+stops after two solutions, so it says whether the puzzle is proper.
+
+
+## Generating
+
+A path through every cell of a width x height board, by depth first
+search from a random corner with Warnsdorff's rule (which nearly always
+goes straight through); then, in random order, each number is taken away
+while the puzzle still has only one solution. 1 and the last number
+stay, and so does any number whose check takes more than --effort
+numbers placed, which keeps large boards from taking minutes.
+
+
+## The Format
+
+Rows of words separated by spaces: a number, . for a cell to fill, or #
+for a hole that is not part of the board. Lines starting with ; are
+comments. `check` takes a grid with every cell filled.
+
+
+## Usage
+
+    python3 hidato.py solve puzzles/classic.txt
+    python3 hidato.py solve puzzles/seven.txt --stats
+    python3 hidato.py solve puzzles/ambiguous.txt
+    python3 hidato.py check puzzles/solved.txt
+    python3 hidato.py generate --width 6 --height 6 --seed 1
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Dict, List, NamedTuple, Optional
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from rng.rng import add_seed_argument, make_rng
+
+
+EMPTY, HOLE = ".", "#"
+Solution = Dict[Position, int]
+
+
+class Puzzle(NamedTuple):
+    grid: Grid                  # each cell's word: a number, EMPTY or HOLE
+    cells: List[Position]       # every cell of the board, not the holes
+    given: Dict[Position, int]
+
+    @property
+    def last(self) -> int:
+        return len(self.cells)
+
+
+class OutOfEffort(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.placed = 0
+        self.pruned = 0
+
+    def __str__(self) -> str:
+        return f"{self.placed} numbers placed, {self.pruned} branches cut short"
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle, statistics: Optional[Statistics] = None, max_placed: Optional[int] = None):
+        self.puzzle = puzzle
+        self.max_placed = max_placed
+        self.statistics = statistics or Statistics()
+        index = {cell: i for i, cell in enumerate(puzzle.cells)}
+        self.positions = puzzle.cells
+        self.neighbours: List[List[int]] = [[index[n] for n in puzzle.grid.neighbours(cell, diagonal=True)
+                                             if n in index] for cell in puzzle.cells]
+        self.value: List[int] = [puzzle.given.get(cell, 0) for cell in puzzle.cells]
+        self.where: Dict[int, int] = {n: index[cell] for cell, n in puzzle.given.items()}
+        # For each number, the next number given after it, if any
+        self.next_given: List[Optional[int]] = [None] * (puzzle.last + 2)
+        upcoming = None
+        for n in range(puzzle.last, 0, -1):
+            self.next_given[n] = upcoming
+            if n in self.where:
+                upcoming = n
+        self.empty = self.value.count(0)
+        self.found: List[Solution] = []
+
+    def distance(self, a: int, b: int) -> int:
+        (ax, ay), (bx, by) = self.positions[a], self.positions[b]
+        return max(abs(ax - bx), abs(ay - by))
+
+    def could_link(self, n: int, k: int) -> bool:
+        """Whether the cell n could be next to an empty cell in the path, with k the last number placed."""
+        m = self.value[n]
+        if m == 0 or m == k:
+            return True
+        return m > k and (m - 1 > k and m - 1 not in self.where or m < self.puzzle.last and m + 1 not in self.where)
+
+    def dead_end(self, cell: int, k: int) -> bool:
+        """Whether an empty cell next to this one can no longer be filled: it needs two cells around it that
+        could come before and after it in the path (one, if it could be the last)."""
+        needed = 1 if self.puzzle.last not in self.where else 2
+        for empty in self.neighbours[cell]:
+            if self.value[empty] == 0 and sum(1 for n in self.neighbours[empty] if self.could_link(n, k)) < needed:
+                return True
+        return False
+
+    def cut_off(self, head: int, k: int) -> bool:
+        """Whether some empty cell cannot be reached from the head, through empty cells and numbers to come."""
+        seen = {head}
+        stack = [head]
+        reached = 0
+        while stack:
+            for n in self.neighbours[stack.pop()]:
+                if n not in seen and (self.value[n] == 0 or self.value[n] > k):
+                    seen.add(n)
+                    stack.append(n)
+                    reached += self.value[n] == 0
+        return reached < self.empty
+
+    def extend(self, cell: int, k: int, limit: int):
+        """Carry on from k, in the cell."""
+        if len(self.found) >= limit:
+            return
+        if k == self.puzzle.last:
+            self.found.append({self.positions[i]: v for i, v in enumerate(self.value)})
+            return
+        given = self.where.get(k + 1)
+        if given is not None:
+            if given in self.neighbours[cell]:
+                self.extend(given, k + 1, limit)
+            return
+        target = self.next_given[k + 1]
+        candidates = [n for n in self.neighbours[cell] if self.value[n] == 0]
+        candidates.sort(key=lambda n: sum(1 for m in self.neighbours[n] if self.value[m] == 0))
+        for n in candidates:
+            if target is not None and self.distance(n, self.where[target]) > target - (k + 1):
+                self.statistics.pruned += 1
+                continue
+            self.value[n] = k + 1
+            self.empty -= 1
+            self.statistics.placed += 1
+            if self.max_placed is not None and self.statistics.placed > self.max_placed:
+                raise OutOfEffort()
+            if self.dead_end(cell, k + 1) or self.dead_end(n, k + 1) or self.cut_off(n, k + 1):
+                self.statistics.pruned += 1
+            else:
+                self.extend(n, k + 1, limit)
+            self.value[n] = 0
+            self.empty += 1
+
+    def solutions(self, limit: int = 2) -> List[Solution]:
+        if 1 in self.where:
+            self.extend(self.where[1], 1, limit)
+        else:
+            for start in range(len(self.positions)):
+                if self.value[start] == 0:
+                    self.value[start] = 1
+                    self.empty -= 1
+                    self.extend(start, 1, limit)
+                    self.value[start] = 0
+                    self.empty += 1
+        return self.found
+
+
+def verify(puzzle: Puzzle, solution: Solution) -> List[str]:
+    """What is wrong with the filled grid, if anything."""
+    problems = []
+    where: Dict[int, Position] = {}
+    for cell in puzzle.cells:
+        n = solution.get(cell)
+        if n is None:
+            problems.append(f"{cell} is empty")
+        elif not 1 <= n <= puzzle.last:
+            problems.append(f"{n} at {cell} is not 1 to {puzzle.last}")
+        elif n in where:
+            problems.append(f"{n} is at {where[n]} and {cell}")
+        else:
+            where[n] = cell
+        if cell in puzzle.given and solution.get(cell) != puzzle.given[cell]:
+            problems.append(f"{cell} should be the given {puzzle.given[cell]}")
+    for n in range(1, puzzle.last):
+        if n in where and n + 1 in where:
+            (ax, ay), (bx, by) = where[n], where[n + 1]
+            if max(abs(ax - bx), abs(ay - by)) != 1:
+                problems.append(f"{n} at {where[n]} is not next to {n + 1} at {where[n + 1]}")
+    return problems
+
+
+################################################################################
+###                                Generating                                ###
+################################################################################
+
+
+def random_path(width: int, height: int, rng: random.Random) -> List[Position]:
+    """A king's path through every cell, from a random corner."""
+    board = Grid.filled(width, height, False)
+    x, y = rng.choice([(0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)])
+    path = [(x, y)]
+    board[x, y] = True
+
+    def free(cell: Position) -> List[Position]:
+        return [n for n in board.neighbours(cell, diagonal=True) if not board[n]]
+
+    def extend() -> bool:
+        if len(path) == width * height:
+            return True
+        options = free(path[-1])
+        rng.shuffle(options)
+        options.sort(key=lambda n: len(free(n)))
+        for n in options:
+            board[n] = True
+            path.append(n)
+            if extend():
+                return True
+            path.pop()
+            board[n] = False
+        return False
+
+    extend()
+    return path
+
+
+def generate(width: int, height: int, rng: random.Random, effort: int) -> Puzzle:
+    path = random_path(width, height, rng)
+    numbers = {cell: n for n, cell in enumerate(path, start=1)}
+    grid = Grid.filled(width, height, EMPTY)
+    cells = list(grid.positions())
+    given = dict(numbers)
+    order = path[1:-1]
+    rng.shuffle(order)
+    for cell in order:
+        rest = {c: n for c, n in given.items() if c != cell}
+        try:
+            if len(Solver(Puzzle(grid, cells, rest), max_placed=effort).solutions(limit=2)) == 1:
+                given = rest
+        except OutOfEffort:
+            pass        # too hard to tell in time, so the number stays
+
+    for cell, n in given.items():
+        grid[cell] = str(n)
+    return Puzzle(grid, cells, given)
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    lines = [line for line in text.split("\n") if line.strip() and not line.startswith(";")]
+    if not lines:
+        raise ValueError("the puzzle is empty")
+    grid = Grid.parse("\n".join(lines), separator=" ")
+    cells = [cell for cell, word in grid.items() if word != HOLE]
+    given: Dict[Position, int] = {}
+    for cell, word in grid.items():
+        if word in (EMPTY, HOLE):
+            continue
+        if not word.isdigit() or not 1 <= int(word) <= len(cells):
+            raise ValueError(f"{word!r} at {cell} is not {EMPTY}, {HOLE}, or a number from 1 to {len(cells)}")
+        if int(word) in given.values():
+            raise ValueError(f"{word} is given twice")
+        given[cell] = int(word)
+    return Puzzle(grid, cells, given)
+
+
+def render(puzzle: Puzzle, solution: Optional[Solution] = None) -> str:
+    width = len(str(puzzle.last))
+    shown = solution or puzzle.given
+    lines = []
+    for y in range(puzzle.grid.height):
+        words = []
+        for x in range(puzzle.grid.width):
+            word = puzzle.grid[x, y]
+            words.append(HOLE if word == HOLE else str(shown[x, y]) if (x, y) in shown else EMPTY)
+        lines.append(" ".join(f"{word:<{width}}" for word in words).rstrip())
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve, check and generate Hidato")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="fill in the numbers")
+    solve_parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    solve_parser.add_argument("--stats", action="store_true", help="print the numbers placed and the pruning")
+    check_parser = subparsers.add_parser("check", help="check a filled grid")
+    check_parser.add_argument("file", type=str, help="the grid, with every cell filled")
+    generate_parser = subparsers.add_parser("generate", help="make a puzzle with one solution")
+    generate_parser.add_argument("--width", type=int, default=6)
+    generate_parser.add_argument("--height", type=int, default=6)
+    add_seed_argument(generate_parser)
+    generate_parser.add_argument("--effort", type=int, default=20000,
+                                 help="the most numbers placed checking that a number can go; past it, it stays")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "generate":
+            if not 1 <= args.width <= 12 or not 1 <= args.height <= 12:
+                raise ValueError("a board is 1 to 12 cells each way")
+            puzzle = generate(args.width, args.height, make_rng(args.seed), args.effort)
+            print(render(puzzle))
+            print()
+            print(render(puzzle, Solver(puzzle).solutions(limit=1)[0]))
+            return
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "check":
+        problems = verify(puzzle, puzzle.given)
+        print("\n".join(problems) if problems else "Solved")
+        if problems:
+            parser.exit(1)
+        return
+    solver = Solver(puzzle)
+    found = solver.solutions(limit=2)
+    if not found:
+        print("No solution")
+    else:
+        assert not verify(puzzle, found[0])
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,282 @@
+"""
+# Hitori
+
+A grid of numbers. Shade some cells so that no number is left unshaded
+twice in a row or a column, no two shaded cells are side by side, and
+the unshaded cells are all joined, side to side, into one piece.
+
+
+## Solving
+
+Each cell is shaded, unshaded, or not known yet. A cell whose number is
+not repeated in its row or column is unshaded from the start, and the
+rules decide cells, over and over until none changes:
+
+    repeat      a cell left unshaded shades the cells with its number
+                in its row and column
+    beside      the cells beside a shaded cell are unshaded
+    joined      shading a cell must not cut the unshaded cells in two
+
+The joined rule is checked each time a cell is shaded, and only around
+it: before, every cell that is not shaded is joined to every other (it
+is the same for unknown cells, which may yet be unshaded), and shading
+a cell can only break that by separating the cells beside it, so it
+looks for a path between them that avoids the shaded cells. Most of the
+time, they are joined around a corner, in a few steps.
+
+When the rules get stuck, each unknown cell is tried both ways, and if
+the rules find one way impossible, it is the other (probing): that is
+what finds the patterns a person learns, like the middle of 2 1 2 being
+unshaded. When that gets stuck too, the search guesses an unknown cell,
+shaded and then unshaded. It stops after two solutions, so it says
+whether the puzzle is proper.
+
+
+## The Format
+
+Rows of numbers separated by spaces. Lines starting with # are
+comments.
+
+    1 4 2 4 3
+    4 3 1 1 4
+    3 1 4 2 5
+    2 2 1 4 4
+    2 4 2 5 1
+
+
+## Usage
+
+    python3 hitori.py puzzles/five.txt
+    python3 hitori.py puzzles/eight.txt --stats
+    python3 hitori.py puzzles/twelve.txt --stats
+    python3 hitori.py puzzles/ambiguous.txt
+"""
+
+import argparse
+import os
+import sys
+from collections import deque
+from typing import Dict, List
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+
+
+UNKNOWN, SHADED, UNSHADED = 0, 1, 2
+State = Dict[Position, int]
+
+
+class Contradiction(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.decisions = 0
+        self.probes = 0
+        self.guesses = 0
+
+    def __str__(self) -> str:
+        return f"{self.decisions} cells decided by the rules, {self.probes} by probing, {self.guesses} guesses"
+
+
+class Puzzle:
+    def __init__(self, grid: Grid):
+        self.grid = grid
+        # For each cell, the other cells in its row and column with the same number
+        self.same: Dict[Position, List[Position]] = {
+            (x, y): [(i, y) for i in range(grid.width) if i != x and grid[i, y] == n]
+            + [(x, j) for j in range(grid.height) if j != y and grid[x, j] == n]
+            for (x, y), n in grid.items()}
+
+
+################################################################################
+###                                  Solving                                 ###
+################################################################################
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle):
+        self.puzzle = puzzle
+        self.statistics = Statistics()
+
+    def decide(self, state: State, cell: Position, value: int) -> bool:
+        """
+        Set an unknown cell; whether it was unknown. Raises Contradiction if
+        it was the other value, or if shading it cuts the unshaded cells in two.
+        """
+        if state[cell] == value:
+            return False
+        if state[cell] != UNKNOWN:
+            raise Contradiction
+        state[cell] = value
+        self.statistics.decisions += 1
+        if value == SHADED and not self.still_joined(state, cell):
+            raise Contradiction
+        return True
+
+    def still_joined(self, state: State, shaded: Position) -> bool:
+        """Whether the cells beside a newly shaded cell that are not shaded are still joined to each other."""
+        grid = self.puzzle.grid
+        beside = [n for n in grid.neighbours(shaded) if state[n] != SHADED]
+        if len(beside) <= 1:
+            return True
+        wanted = set(beside[1:])
+        seen = {beside[0]}
+        queue = deque([beside[0]])
+        while queue and wanted:
+            cell = queue.popleft()
+            for n in grid.neighbours(cell):
+                if n not in seen and state[n] != SHADED:
+                    seen.add(n)
+                    wanted.discard(n)
+                    queue.append(n)
+        return not wanted
+
+    def propagate(self, state: State):
+        """Apply the rules until no cell changes. Raises Contradiction if a cell can be neither."""
+        grid = self.puzzle.grid
+        changed = True
+        while changed:
+            changed = False
+            for cell, value in list(state.items()):
+                if value == UNSHADED:
+                    for other in self.puzzle.same[cell]:
+                        changed |= self.decide(state, other, SHADED)
+                elif value == SHADED:
+                    for other in grid.neighbours(cell):
+                        changed |= self.decide(state, other, UNSHADED)
+
+    def probe(self, state: State):
+        """Try each unknown cell each way, and where one way leads the rules to a contradiction, decide it the
+        other, until that decides nothing more. Raises Contradiction if both ways do."""
+        changed = True
+        while changed:
+            changed = False
+            for cell in self.puzzle.grid.positions():
+                if state[cell] != UNKNOWN:
+                    continue
+                for value in (SHADED, UNSHADED):
+                    trial = dict(state)
+                    try:
+                        self.decide(trial, cell, value)
+                        self.propagate(trial)
+                    except Contradiction:
+                        self.statistics.probes += 1
+                        self.decide(state, cell, UNSHADED if value == SHADED else SHADED)
+                        self.propagate(state)
+                        changed = True
+                        break
+
+    def search(self, state: State, found: List[State], limit: int):
+        try:
+            self.probe(state)
+        except Contradiction:
+            return
+        cell = next((cell for cell, value in state.items() if value == UNKNOWN), None)
+        if cell is None:
+            found.append(state)
+            return
+        self.statistics.guesses += 1
+        for value in (SHADED, UNSHADED):
+            trial = dict(state)
+            try:
+                self.decide(trial, cell, value)
+                self.propagate(trial)
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, limit: int = 2) -> List[State]:
+        """Up to limit solutions, each SHADED or UNSHADED for every cell."""
+        state = {cell: UNKNOWN if same else UNSHADED for cell, same in self.puzzle.same.items()}
+        found: List[State] = []
+        try:
+            self.propagate(state)
+        except Contradiction:
+            return found
+        self.search(state, found, limit)
+        return found
+
+
+def verify(puzzle: Puzzle, state: State) -> List[str]:
+    """What is wrong with the shading as a solution, checked from scratch."""
+    grid = puzzle.grid
+    problems = []
+    for cell, value in state.items():
+        if value == UNSHADED and any(state[other] == UNSHADED for other in puzzle.same[cell] if other > cell):
+            problems.append(f"the {grid[cell]} at {cell} is repeated unshaded")
+        if value == SHADED and any(state[n] == SHADED for n in grid.neighbours(cell) if n > cell):
+            problems.append(f"the shaded cell at {cell} has a shaded cell beside it")
+    unshaded = [cell for cell, value in state.items() if value == UNSHADED]
+    seen = {unshaded[0]} if unshaded else set()
+    stack = list(seen)
+    while stack:
+        for n in grid.neighbours(stack.pop()):
+            if n not in seen and state[n] == UNSHADED:
+                seen.add(n)
+                stack.append(n)
+    if len(seen) != len(unshaded):
+        problems.append("the unshaded cells are not all joined")
+    return problems
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    rows = []
+    for number, line in enumerate(text.split("\n"), start=1):
+        words = line.split()
+        if not words or words[0].startswith("#"):
+            continue
+        if not all(word.isdigit() for word in words):
+            raise ValueError(f"line {number}: expected numbers separated by spaces")
+        if rows and len(words) != len(rows[0]):
+            raise ValueError(f"line {number}: {len(words)} numbers, but the first row has {len(rows[0])}")
+        rows.append([int(word) for word in words])
+    if not rows:
+        raise ValueError("the puzzle is empty")
+    return Puzzle(Grid(rows))
+
+
+def render(puzzle: Puzzle, state: State) -> str:
+    """The numbers left unshaded, with # for the shaded cells."""
+    shown = puzzle.grid.map(str)
+    for cell, value in state.items():
+        if value == SHADED:
+            shown[cell] = "#"
+    return shown.render(separator=" ")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Hitori")
+    parser.add_argument("file", type=str, help="the numbers, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the cells decided and the guesses made")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    solver = Solver(puzzle)
+    found = solver.solutions(limit=2)
+    if not found:
+        print("No solution")
+    else:
+        assert not verify(puzzle, found[0])
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,295 @@
+"""
+# Intcode
+
+The virtual machine from Advent of Code 2019. A program is a list of
+comma-separated integers, which is also the machine's memory. Memory
+beyond the program starts as 0, and may be read and written freely.
+
+    1  add a b c    c := a + b          5  jump-if-true a b     if a != 0, go to b
+    2  mul a b c    c := a * b          6  jump-if-false a b    if a == 0, go to b
+    3  in a         a := the next input 7  less-than a b c      c := 1 if a < b else 0
+    4  out a        output a            8  equals a b c         c := 1 if a == b else 0
+    9  arb a        add a to the relative base
+    99 halt
+
+An instruction's opcode is its value mod 100, and the digits above that
+are the parameter modes, from the first parameter up: 0 reads memory at
+the parameter (position mode), 1 uses the parameter itself (immediate
+mode), and 2 reads memory at the relative base plus the parameter
+(relative mode). A parameter that is written to is never immediate.
+
+
+## Input and Output
+
+The inputs and outputs are queues. When the machine needs an input and
+the queue is empty, `run` returns `Status.WAITING` instead of blocking,
+so the caller can send more with `send` and then continue with `run`.
+That is all it takes to connect several machines to each other, like
+the amplifiers of Day 7, in one thread (see `run_feedback_loop`).
+
+`--ascii` sends the input as characters and prints small outputs as
+characters, like the ASCII-capable programs of the later puzzles.
+
+
+## Programs
+
+    hello.ic        prints "Hello, World!" with a loop over relative mode
+    quine.ic        prints its own code (from Day 9)
+    compare8.ic     prints 999, 1000, or 1001 as the input is <, =, or > 8 (Day 5)
+    feedback.ic     an amplifier for a feedback loop (Day 7)
+
+
+## Usage
+
+    python3 intcode.py programs/hello.ic --ascii
+    python3 intcode.py programs/quine.ic
+    python3 intcode.py programs/compare8.ic --input 7 --trace
+    python3 intcode.py programs/feedback.ic --phases 9 8 7 6 5
+    python3 intcode.py programs/compare8.ic --disassemble
+"""
+
+import argparse
+import enum
+from collections import deque
+from typing import Deque, Dict, Iterable, List, NamedTuple, Optional, Tuple
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+
+POSITION, IMMEDIATE, RELATIVE = 0, 1, 2
+
+
+class Operation(NamedTuple):
+    name: str
+    parameters: int
+    writes: bool    # whether the last parameter is an address to write to
+
+
+OPERATIONS: Dict[int, Operation] = {
+    1: Operation("add", 3, True),
+    2: Operation("mul", 3, True),
+    3: Operation("in", 1, True),
+    4: Operation("out", 1, False),
+    5: Operation("jump-if-true", 2, False),
+    6: Operation("jump-if-false", 2, False),
+    7: Operation("less-than", 3, True),
+    8: Operation("equals", 3, True),
+    9: Operation("arb", 1, False),
+    99: Operation("halt", 0, False),
+}
+
+
+class Status(enum.Enum):
+    RUNNING = "running"
+    WAITING = "waiting for input"
+    HALTED = "halted"
+
+
+class MachineError(Exception):
+    """The program did something illegal while running."""
+
+
+def parse(text: str) -> List[int]:
+    try:
+        return [int(word) for word in text.replace("\n", "").split(",") if word.strip()]
+    except ValueError:
+        raise ValueError("an Intcode program is a list of comma-separated integers") from None
+
+
+def decode(memory: List[int], address: int) -> Tuple[Operation, List[int]]:
+    """The operation at an address, and its parameter modes."""
+    value = memory[address] if address < len(memory) else 0
+    operation = OPERATIONS.get(value % 100)
+    if operation is None or value < 0:
+        raise MachineError(f"address {address}: unknown opcode {value}")
+    modes = [value // 10**(2 + i) % 10 for i in range(operation.parameters)]
+    for i, mode in enumerate(modes):
+        if mode not in (POSITION, IMMEDIATE, RELATIVE):
+            raise MachineError(f"address {address}: unknown parameter mode {mode} in {value}")
+        if mode == IMMEDIATE and operation.writes and i == operation.parameters - 1:
+            raise MachineError(f"address {address}: {operation.name} cannot write to an immediate parameter")
+    return operation, modes
+
+
+def format_instruction(memory: List[int], address: int) -> str:
+    operation, modes = decode(memory, address)
+    parameters = memory[address + 1:address + 1 + operation.parameters]
+    prefixes = {POSITION: "[", IMMEDIATE: "", RELATIVE: "[rb"}
+    suffixes = {POSITION: "]", IMMEDIATE: "", RELATIVE: "]"}
+    shown = [
+        f"{prefixes[mode]}{'+' if mode == RELATIVE and p >= 0 else ''}{p}{suffixes[mode]}"
+        for mode, p in zip(modes, parameters)
+    ]
+    return " ".join([operation.name] + shown)
+
+
+def disassemble(memory: List[int]) -> str:
+    """A linear sweep, so data that happens to look like code comes out as code."""
+    lines: List[str] = []
+    address = 0
+    while address < len(memory):
+        try:
+            operation, _ = decode(memory, address)
+            text = format_instruction(memory, address)
+            size = 1 + operation.parameters
+        except MachineError:
+            text, size = f"data {memory[address]}", 1
+        lines.append(f"{address:>6}  {text}")
+        address += size
+    return "\n".join(lines)
+
+
+class Intcode:
+    def __init__(self, program: List[int], inputs: Iterable[int] = ()):
+        self.memory = list(program)
+        self.ip = 0
+        self.relative_base = 0
+        self.inputs: Deque[int] = deque(inputs)
+        self.outputs: Deque[int] = deque()
+        self.status = Status.RUNNING
+        self.steps = 0
+
+    def __getitem__(self, address: int) -> int:
+        if address < 0:
+            raise MachineError(f"address {self.ip}: there is no address {address}")
+        return self.memory[address] if address < len(self.memory) else 0
+
+    def __setitem__(self, address: int, value: int):
+        if address < 0:
+            raise MachineError(f"address {self.ip}: there is no address {address}")
+        if address >= len(self.memory):
+            self.memory.extend([0] * (address + 1 - len(self.memory)))
+        self.memory[address] = value
+
+    def send(self, *values: int):
+        self.inputs.extend(values)
+
+    def receive(self) -> List[int]:
+        """All the outputs since the last call."""
+        outputs = list(self.outputs)
+        self.outputs.clear()
+        return outputs
+
+    def address(self, mode: int, parameter: int) -> int:
+        return parameter + self.relative_base if mode == RELATIVE else parameter
+
+    def step(self):
+        operation, modes = decode(self.memory, self.ip)
+        if operation.name == "in" and not self.inputs:
+            self.status = Status.WAITING
+            return
+        self.status = Status.RUNNING
+        parameters = [self[self.ip + 1 + i] for i in range(operation.parameters)]
+        values = [p if mode == IMMEDIATE else self[self.address(mode, p)] for mode, p in zip(modes, parameters)]
+        target = self.address(modes[-1], parameters[-1]) if operation.writes else None
+        self.ip += 1 + operation.parameters
+        self.steps += 1
+
+        name = operation.name
+        if name == "add":
+            self[target] = values[0] + values[1]
+        elif name == "mul":
+            self[target] = values[0] * values[1]
+        elif name == "in":
+            self[target] = self.inputs.popleft()
+        elif name == "out":
+            self.outputs.append(values[0])
+        elif name == "jump-if-true":
+            if values[0] != 0:
+                self.ip = values[1]
+        elif name == "jump-if-false":
+            if values[0] == 0:
+                self.ip = values[1]
+        elif name == "less-than":
+            self[target] = int(values[0] < values[1])
+        elif name == "equals":
+            self[target] = int(values[0] == values[1])
+        elif name == "arb":
+            self.relative_base += values[0]
+        else:
+            self.ip -= 1
+            self.status = Status.HALTED
+
+    def run(self, max_steps: Optional[int] = None, trace: bool = False) -> Status:
+        """Run until the machine halts, needs an input, or reaches the step limit."""
+        if self.status == Status.WAITING and self.inputs:
+            self.status = Status.RUNNING
+        while self.status == Status.RUNNING and (max_steps is None or self.steps < max_steps):
+            if trace:
+                print(f"{self.steps:>8}  {self.ip:>6}  {format_instruction(self.memory, self.ip):<36} "
+                      f"rb={self.relative_base}")
+            self.step()
+        return self.status
+
+
+def run_feedback_loop(program: List[int], phases: List[int]) -> int:
+    """
+    Day 7: one machine per phase, each fed its phase and then the output of
+    the one before it, and the last one's output back into the first, until
+    they all halt. Return the last machine's final output.
+    """
+    machines = [Intcode(program, [phase]) for phase in phases]
+    machines[0].send(0)
+    last = 0
+    while any(machine.status != Status.HALTED for machine in machines):
+        for i, machine in enumerate(machines):
+            machine.run()
+            outputs = machine.receive()
+            if i == len(machines) - 1 and outputs:
+                last = outputs[-1]
+            machines[(i + 1) % len(machines)].send(*outputs)
+        if all(machine.status == Status.WAITING and not machine.inputs for machine in machines):
+            raise MachineError("every machine is waiting for input")
+    return last
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def format_outputs(outputs: List[int], ascii: bool) -> str:
+    if not ascii:
+        return "".join(f"{value}\n" for value in outputs)
+    return "".join(chr(value) if 0 <= value < 128 else f"{value}\n" for value in outputs)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Intcode virtual machine")
+    parser.add_argument("program", type=str, help="path to the program")
+    parser.add_argument("--input", type=str, nargs="*", default=[], help="integers (or text with --ascii) to send")
+    parser.add_argument("--ascii", action="store_true", help="send and print characters")
+    parser.add_argument("--phases", type=int, nargs="+", default=None, help="run a feedback loop of amplifiers")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="print every instruction as it runs")
+    parser.add_argument("--disassemble", action="store_true", help="print the program as instructions and stop")
+    args = parser.parse_args()
+
+    try:
+        with open(args.program) as f:
+            program = parse(f.read())
+        if args.disassemble:
+            print(disassemble(program))
+            return
+        if args.phases is not None:
+            print(run_feedback_loop(program, args.phases))
+            return
+        if args.ascii:
+            inputs = [ord(c) for c in "\n".join(args.input) + "\n"] if args.input else []
+        else:
+            inputs = [int(word) for word in args.input]
+        intcode = Intcode(program, inputs)
+        try:
+            intcode.run(args.max_steps, args.trace)
+        finally:
+            print(format_outputs(intcode.receive(), args.ascii), end="")
+    except (ValueError, MachineError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if intcode.status == Status.WAITING:
+        print(f"stopped at address {intcode.ip}, waiting for input")
+    elif intcode.status == Status.RUNNING:
+        print(f"stopped after {intcode.steps} steps")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,567 @@
+"""
+# Interpreters
+
+The interface shared by the step-at-a-time interpreters, and a runner
+and debugger that work for all of them. An `Interpreter` is a program
+being run: `step` carries out one instruction, `halted` says whether it
+has finished, `snapshot` describes where it is and what it holds (one
+line, for traces and the debugger), and input and output go through a
+`ProgramIO` (see esolangs/esolang_io.py), so the same program runs on
+the terminal or on a byte buffer. `run` is written once, here.
+
+    language        files           interpreter
+    Befunge-93      .bf93           esolangs/befunge.py
+    Whitespace      .ws, .wsa       esolangs/whitespace.py
+    Subleq          .sq             esolangs/subleq.py
+    Brainfuck       .bf             here
+    Thue            .t              esolangs/thue.py
+    Piet            .png, .gif      esolangs/piet.py
+    Turing machine  .tm             turing_machine/machine.py
+    Intcode         .ic             intcode/intcode.py
+
+The Turing machine and Intcode have their own ideas of input and output
+(a tape, and queues of numbers), so they are adapted here: a Turing
+machine reads its input tape (as for `tm.py run --tape`) and writes its
+result and tape when it stops, and Intcode reads and writes numbers,
+one to a line.
+
+Brain-Flak evaluates its nested brackets recursively, Forth and Deadfish
+interpret text a line at a time, and CHIP-8 runs by frames with a screen
+and keys, so they have no single step and are not included.
+
+Brainfuck is run here, a command a step, rather than compiled to Subleq
+(as `subleq.py run` does), so that its steps are its own commands. The
+cells wrap at 256, the tape grows to the right as far as the program
+goes, and `,` at the end of the input stores 0, as in the Subleq.
+
+
+## Halting
+
+`halts` runs a program on every input of up to k bytes (`--inputs
+exhaustive:k`, with `--alphabet` to draw them from fewer bytes than all
+256), or on none (`--inputs none`), for at most `--steps` steps each,
+and says which inputs it halts on, which it loops on, and which it is
+still running on when the steps run out. A run loops when it comes back
+to a state it was in before, with the same input still to read, which
+it finds by comparing each state with one saved at steps 1, 2, 4, 8,
+and so on (Brent's method): a loop is found within about twice the
+steps it takes to go round it once, without keeping every state. Only
+Brainfuck says what its state is (`state`), so the others can halt or
+run out of steps, but not loop. It is for vetting a program before
+publishing it: one that does not halt on every input exits with 1.
+
+    $ python3 interpreter.py halts ../esolangs/examples/wait.bf --inputs exhaustive:1 --steps 1000
+    257 inputs of up to 1 byte, at most 1000 steps each: 2 halt, 255 loop
+    loops on b'\\x01'
+    ...
+
+
+## Oracle Testing
+
+`oracle` runs a program on every input the same way, and prints what it
+does on each, a table of the input, halts, loops or runs on, and for a
+halt, the output. Given a second program, it runs that on them too, and
+prints the inputs that the two differ on instead: the check that a
+minified or optimised program does what the original did, on every
+input small enough to try. A loop's output is not compared (how much of
+it there is depends on where the loop is found), and nor is an input
+that either program is still running on when the steps run out; those
+are counted as undecided. A program that differs exits with 1.
+
+    $ python3 interpreter.py oracle ../esolangs/examples/cat.bf --inputs exhaustive:1 --alphabet ab
+    b''  halts  b''
+    b'a'  halts  b'a'
+    b'b'  halts  b'b'
+
+
+To check a program's output from Python:
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from interpreters.interpreter import output_of
+    assert output_of("esolangs/examples/factorial.bf93", b"5") == b"120 "
+
+
+## Usage
+
+    python3 interpreter.py run ../esolangs/examples/hello.ws
+    python3 interpreter.py run ../esolangs/examples/factorial.bf93 --input 5 --trace
+    python3 interpreter.py run ../turing_machine/machines/binary_increment.tm --input 1011
+    python3 interpreter.py debug ../intcode/programs/compare8.ic --input 7
+    python3 interpreter.py run ../esolangs/examples/hello.bf --trace --max-steps 20
+    python3 interpreter.py halts ../esolangs/examples/cat.bf --inputs exhaustive:2 --alphabet ab0
+    python3 interpreter.py halts ../esolangs/examples/wait.bf --inputs exhaustive:1
+    python3 interpreter.py oracle ../esolangs/examples/cat.bf --inputs exhaustive:2 --alphabet ab0
+    python3 interpreter.py oracle ../esolangs/examples/cat.bf ../esolangs/examples/copy.bf --inputs exhaustive:1
+    python3 interpreter.py oracle ../esolangs/examples/cat.bf ../esolangs/examples/wait.bf --inputs exhaustive:1
+
+In the debugger, a number takes that many steps (and an empty line
+one), and `:help` lists the commands.
+"""
+
+import argparse
+import itertools
+import os
+import sys
+from typing import Callable, Dict, Hashable, Iterator, List, Optional, Tuple
+
+ROOT: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "..")
+for directory in ("esolangs", "intcode", "turing_machine"):
+    sys.path.append(os.path.join(ROOT, directory))
+sys.path.append(ROOT)
+import intcode
+from esolang_io import BufferIO, ProgramIO, StreamIO
+from machine import MachineError, Simulation, format_configuration, format_tape, load_machine, parse_tape
+from repl.repl import Repl
+
+
+DEFAULT_MAX_STEPS: Optional[int] = None
+DEFAULT_HALTING_STEPS: int = 100_000
+
+
+class Interpreter:
+    """A program, run one step at a time, reading and writing through a ProgramIO."""
+
+    def __init__(self, io: ProgramIO):
+        self.io = io
+        self.halted = False
+        self.steps = 0
+
+    def step(self):
+        raise NotImplementedError
+
+    def snapshot(self) -> str:
+        """Where the program is and what it holds, on one line."""
+        raise NotImplementedError
+
+    def state(self) -> Optional[Hashable]:
+        """Everything the rest of the run depends on, other than the input still to read, or None if not known.
+        A run that comes back to a state never halts."""
+        return None
+
+    def run(self, max_steps: Optional[int] = None, trace: bool = False):
+        try:
+            while not self.halted and (max_steps is None or self.steps < max_steps):
+                if trace:
+                    print(f"{self.steps:>8}  {self.snapshot()}")
+                self.step()
+        finally:
+            self.io.flush()
+
+
+################################################################################
+###   BRAINFUCK   ###
+################################################################################
+
+
+class Brainfuck(Interpreter):
+    """Brainfuck, one command a step."""
+
+    def __init__(self, source: str, io: ProgramIO):
+        super().__init__(io)
+        self.program: List[str] = []
+        self.jumps: Dict[int, int] = {}     # each bracket's partner
+        opened = []
+        for number, line in enumerate(source.split("\n"), start=1):
+            for column, command in enumerate(line, start=1):
+                if command not in "+-<>[].,":
+                    continue
+                if command == "[":
+                    opened.append((len(self.program), number, column))
+                elif command == "]":
+                    if not opened:
+                        raise ValueError(f"line {number}, column {column}: a ] with no [")
+                    start = opened.pop()[0]
+                    self.jumps[start], self.jumps[len(self.program)] = len(self.program), start
+                self.program.append(command)
+        if opened:
+            raise ValueError(f"line {opened[-1][1]}, column {opened[-1][2]}: a [ with no ]")
+        self.tape = bytearray(1)
+        self.pointer = 0
+        self.pc = 0
+        self.read = 0       # the bytes read, which says what input is left
+        self.halted = not self.program
+
+    def step(self):
+        command, cell = self.program[self.pc], self.tape[self.pointer]
+        if command == "+":
+            self.tape[self.pointer] = (cell + 1) & 0xFF
+        elif command == "-":
+            self.tape[self.pointer] = (cell - 1) & 0xFF
+        elif command == ">":
+            self.pointer += 1
+            if self.pointer == len(self.tape):
+                self.tape.append(0)
+        elif command == "<":
+            if self.pointer == 0:
+                raise ValueError(f"command {self.pc + 1}: < from the first cell")
+            self.pointer -= 1
+        elif command == "[" and cell == 0 or command == "]" and cell != 0:
+            self.pc = self.jumps[self.pc]
+        elif command == ".":
+            self.io.write_byte(cell)
+        elif command == ",":
+            byte = self.io.read_byte()
+            self.read += byte is not None
+            self.tape[self.pointer] = 0 if byte is None else byte
+        self.pc += 1
+        self.steps += 1
+        self.halted = self.pc == len(self.program)
+
+    def snapshot(self) -> str:
+        first = max(0, self.pointer - 8)
+        cells = " ".join(f"[{cell}]" if first + i == self.pointer else str(cell)
+                         for i, cell in enumerate(self.tape[first:self.pointer + 9]))
+        return f"{self.pc:>6}  {self.program[self.pc]}  cell {self.pointer:<5} {cells}"
+
+    def state(self) -> Hashable:
+        return self.pc, self.pointer, bytes(self.tape).rstrip(b"\0"), self.read
+
+
+################################################################################
+###   ADAPTERS   ###
+################################################################################
+
+
+class TuringMachineInterpreter(Interpreter):
+    """A deterministic machine on the input tape, writing its result and tapes when it stops."""
+
+    def __init__(self, path: str, io: ProgramIO):
+        super().__init__(io)
+        text = bytearray()
+        byte = io.read_byte()
+        while byte is not None:
+            text.append(byte)
+            byte = io.read_byte()
+        try:
+            self.simulation = Simulation(load_machine(path), [parse_tape(text.decode().strip())])
+        except MachineError as e:
+            raise ValueError(str(e))
+        self.finish()
+
+    def finish(self):
+        sim = self.simulation
+        if sim.status is None:
+            return
+        self.halted = True
+        self.io.write_text(f"{sim.status}\n")
+        for i in range(sim.machine.num_tapes):
+            self.io.write_text(format_tape(sim.tape_symbols(i)) + "\n")
+
+    def step(self):
+        self.simulation.step()
+        self.steps = self.simulation.steps
+        self.finish()
+
+    def snapshot(self) -> str:
+        sim = self.simulation
+        return format_configuration(sim.machine, sim.state, sim.heads, sim.tapes)
+
+
+class IntcodeInterpreter(Interpreter):
+    """Intcode, reading a number when it needs an input and writing each output on its own line."""
+
+    def __init__(self, path: str, io: ProgramIO):
+        super().__init__(io)
+        with open(path) as f:
+            self.machine = intcode.Intcode(intcode.parse(f.read()))
+
+    def step(self):
+        machine = self.machine
+        try:
+            machine.step()
+        except intcode.MachineError as e:
+            raise ValueError(str(e))
+        if machine.status == intcode.Status.WAITING:
+            number = self.io.read_number()
+            if number is None:
+                raise ValueError(f"address {machine.ip}: the program needs an input and there is none")
+            machine.send(number)
+        for value in machine.receive():
+            self.io.write_text(f"{value}\n")
+        self.steps = machine.steps
+        self.halted = machine.status == intcode.Status.HALTED
+
+    def snapshot(self) -> str:
+        machine = self.machine
+        return f"{machine.ip:>6}  {intcode.format_instruction(machine.memory, machine.ip):<36} " \
+               f"rb={machine.relative_base}"
+
+
+################################################################################
+###   LOADING   ###
+################################################################################
+
+
+# The interpreters in esolangs/ import this module, so we import them only when needed
+
+
+def load_brainfuck(path: str, io: ProgramIO) -> Interpreter:
+    with open(path) as f:
+        return Brainfuck(f.read(), io)
+
+
+def load_befunge(path: str, io: ProgramIO) -> Interpreter:
+    import befunge
+
+    with open(path) as f:
+        return befunge.Befunge(f.read().rstrip("\n"), io)
+
+
+def load_whitespace(path: str, io: ProgramIO) -> Interpreter:
+    import whitespace
+
+    return whitespace.Whitespace(whitespace.load(path), io)
+
+
+def load_subleq(path: str, io: ProgramIO) -> Interpreter:
+    import subleq
+
+    return subleq.Subleq(subleq.load(path), io)
+
+
+def load_thue(path: str, io: ProgramIO) -> Interpreter:
+    import thue
+
+    with open(path) as f:
+        rules, state = thue.parse(f.read())
+    return thue.Thue(rules, state, io)
+
+
+def load_piet(path: str, io: ProgramIO) -> Interpreter:
+    import images
+    import piet
+
+    pixels = images.read_image(path)
+    return piet.Piet(piet.to_codels(pixels, piet.guess_codel_size(pixels), "white"), io)
+
+
+LOADERS: Dict[str, Callable[[str, ProgramIO], Interpreter]] = {
+    ".bf93": load_befunge,
+    ".ws": load_whitespace,
+    ".wsa": load_whitespace,
+    ".sq": load_subleq,
+    ".bf": load_brainfuck,
+    ".t": load_thue,
+    ".png": load_piet,
+    ".gif": load_piet,
+    ".tm": TuringMachineInterpreter,
+    ".ic": IntcodeInterpreter,
+}
+
+
+def load(path: str, io: ProgramIO) -> Interpreter:
+    """The program at `path`, in the language of its extension."""
+    extension = os.path.splitext(path)[1]
+    if extension not in LOADERS:
+        raise ValueError(f"{path}: unknown extension (try one of {', '.join(LOADERS)})")
+    return LOADERS[extension](path, io)
+
+
+def output_of(path: str, data: bytes = b"", max_steps: Optional[int] = DEFAULT_MAX_STEPS) -> bytes:
+    """Run the program on the input, and return everything it writes."""
+    io = BufferIO(data)
+    load(path, io).run(max_steps)
+    return bytes(io.output)
+
+
+################################################################################
+###   HALTING   ###
+################################################################################
+
+
+HALTS, LOOPS, RUNS_ON = "halts", "loops", "runs on"
+
+
+def outcome(interpreter: Interpreter, max_steps: int) -> str:
+    """Run the program for at most max_steps: whether it halts, loops (comes back to a state, by Brent's method),
+    or runs on past them."""
+    saved, power, distance = interpreter.state(), 1, 0
+    while not interpreter.halted and interpreter.steps < max_steps:
+        interpreter.step()
+        state = interpreter.state()
+        if state is not None and state == saved:
+            return LOOPS
+        distance += 1
+        if distance == power:
+            saved, power, distance = state, 2 * power, 0
+    interpreter.io.flush()
+    return HALTS if interpreter.halted else RUNS_ON
+
+
+def inputs(spec: str, alphabet: bytes) -> Iterator[bytes]:
+    """The inputs that `--inputs` names: none (one empty input), or exhaustive:k (every one of up to k bytes)."""
+    if spec == "none":
+        yield b""
+        return
+    kind, _, length = spec.partition(":")
+    if kind != "exhaustive" or not length.isdigit():
+        raise ValueError(f"'{spec}' is not none or exhaustive:k")
+    for size in range(int(length) + 1):
+        for data in itertools.product(alphabet, repeat=size):
+            yield bytes(data)
+
+
+def describe_inputs(spec: str, count: int) -> str:
+    if spec == "none":
+        return "no input"
+    length = int(spec.partition(":")[2])
+    return f"{count} input{'s' * (count != 1)} of up to {length} byte{'s' * (length != 1)}"
+
+
+def halts(path: str, spec: str, alphabet: bytes, max_steps: int, shown: int = 10) -> bool:
+    """Whether the program halts on every input, printing those it does not."""
+    counts = {HALTS: 0, LOOPS: 0, RUNS_ON: 0}
+    failed = []
+    for data in inputs(spec, alphabet):
+        result = outcome(load(path, BufferIO(data)), max_steps)
+        counts[result] += 1
+        if result != HALTS:
+            failed.append((result, data))
+    total = sum(counts.values())
+    print(f"{describe_inputs(spec, total)}, at most {max_steps} steps each: "
+          + ", ".join(f"{count} {'halt' if result == HALTS else 'loop' if result == LOOPS else 'run on'}"
+                      for result, count in counts.items() if count))
+    for result, data in failed[:shown]:
+        print(f"{result} on {data!r}")
+    if len(failed) > shown:
+        print(f"and {len(failed) - shown} more")
+    return not failed
+
+
+def run_on(path: str, data: bytes, max_steps: int) -> Tuple[str, bytes]:
+    """What the program does on the input, and what it writes."""
+    io = BufferIO(data)
+    return outcome(load(path, io), max_steps), bytes(io.output)
+
+
+def oracle(path: str, spec: str, alphabet: bytes, max_steps: int):
+    """Print what the program does on every input."""
+    for data in inputs(spec, alphabet):
+        result, output = run_on(path, data, max_steps)
+        print(f"{data!r}  {result}" + (f"  {output!r}" if result == HALTS else ""))
+
+
+def differ(first: Tuple[str, bytes], second: Tuple[str, bytes]) -> bool:
+    if RUNS_ON in (first[0], second[0]):
+        return False
+    return first[0] != second[0] or first[0] == HALTS and first[1] != second[1]
+
+
+def compare(path: str, other: str, spec: str, alphabet: bytes, max_steps: int, shown: int = 10) -> bool:
+    """Whether the two programs do the same on every input, printing those they do not."""
+    total, undecided, differences = 0, 0, []
+    for data in inputs(spec, alphabet):
+        first, second = run_on(path, data, max_steps), run_on(other, data, max_steps)
+        total += 1
+        undecided += RUNS_ON in (first[0], second[0])
+        if differ(first, second):
+            differences.append((data, first, second))
+    for data, first, second in differences[:shown]:
+        print(f"{data!r}: " + " but ".join(f"{result}" + (f" with {output!r}" if result == HALTS else "")
+                                           for result, output in (first, second)))
+    if len(differences) > shown:
+        print(f"and {len(differences) - shown} more")
+    print(f"{describe_inputs(spec, total)}, at most {max_steps} steps each: "
+          f"{len(differences) if differences else 'no'} difference{'s' * (len(differences) != 1)}"
+          + (f", {undecided} undecided" if undecided else ""))
+    return not differences
+
+
+################################################################################
+###   CLI   ###
+################################################################################
+
+
+def debug(interpreter: Interpreter):
+    io = interpreter.io
+    written = 0
+
+    def show():
+        nonlocal written
+        if isinstance(io, BufferIO) and len(io.output) > written:
+            print(f"output: {io.output[written:].decode(errors='replace')!r}")
+            written = len(io.output)
+        print("halted" if interpreter.halted else f"{interpreter.steps:>8}  {interpreter.snapshot()}")
+
+    def take(count: int):
+        for _ in range(count):
+            if interpreter.halted:
+                break
+            interpreter.step()
+        show()
+
+    def evaluate(line: str):
+        if not line.isdigit():
+            raise ValueError(f"'{line}' is not a number of steps (try :help)")
+        take(int(line))
+
+    repl = Repl("debug> ", evaluate)
+    repl.add_command("step", lambda argument: take(int(argument) if argument.isdigit() else 1),
+                     "take a step (or that many)")
+    repl.add_command("continue", lambda _: take(sys.maxsize), "run to the end")
+    repl.add_command("show", lambda _: show(), "show where the program is")
+    # An empty line is a step, as in most debuggers
+    handle = repl.handle
+    repl.handle = lambda line: handle(line if line.strip() else "1")
+    show()
+    repl.run()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Run or debug a program in any of the step-at-a-time languages")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    run_parser = subparsers.add_parser("run", help="run a program")
+    run_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    run_parser.add_argument("--trace", action="store_true", help="print every step")
+    debug_parser = subparsers.add_parser("debug", help="step through a program")
+    for p in (run_parser, debug_parser):
+        p.add_argument("program", type=str, help="the language is chosen by the extension")
+        p.add_argument("--input", type=str, default=None, help="use this as the input instead of stdin")
+    halts_parser = subparsers.add_parser("halts", help="check that a program halts on every small input")
+    oracle_parser = subparsers.add_parser("oracle", help="tabulate what a program does on every small input")
+    for p in (halts_parser, oracle_parser):
+        p.add_argument("program", type=str, help="the language is chosen by the extension")
+        p.add_argument("--steps", type=int, default=DEFAULT_HALTING_STEPS, help="the most for each input")
+        p.add_argument("--inputs", type=str, default="none", help="none, or exhaustive:k")
+        p.add_argument("--alphabet", type=str, default=None, help="the bytes the inputs are made of (default: all 256)")
+    oracle_parser.add_argument("against", type=str, nargs="?", default=None,
+                               help="another program, to print only the inputs the two differ on")
+    args = parser.parse_args()
+
+    if args.command in ("halts", "oracle"):
+        alphabet = args.alphabet.encode() if args.alphabet is not None else bytes(range(256))
+        try:
+            if args.command == "halts":
+                if not halts(args.program, args.inputs, alphabet, args.steps):
+                    parser.exit(1)
+            elif args.against is None:
+                oracle(args.program, args.inputs, alphabet, args.steps)
+            elif not compare(args.program, args.against, args.inputs, alphabet, args.steps):
+                parser.exit(1)
+        except (OSError, ValueError) as e:
+            parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+        return
+
+    # The debugger reads its commands from stdin, so the program cannot
+    data = args.input if args.input is not None or args.command == "run" else ""
+    io: ProgramIO = StreamIO() if data is None else BufferIO(data.encode())
+    try:
+        interpreter = load(args.program, io)
+        if args.command == "debug":
+            debug(interpreter)
+            return
+        try:
+            interpreter.run(args.max_steps, args.trace)
+        finally:
+            if isinstance(io, BufferIO):
+                sys.stdout.flush()
+                sys.stdout.buffer.write(io.output)
+                sys.stdout.buffer.flush()
+    except (OSError, ValueError) as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    if not interpreter.halted:
+        print(f"\nstopped after {interpreter.steps} steps")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,283 @@
+"""
+# Kakuro
+
+A crossword of digits: fill each white cell with a digit from 1 to 9 so
+that every run of white cells (across or down) adds up to its clue, with
+no digit twice in a run.
+
+
+## Solving
+
+Each run is a sum cage (`csp/cages.py`), as in Killer Sudoku: a hidden
+variable whose values are the sets of different digits that add up to
+its clue, from the partition table (a run of 2 adding to 3 is {1, 2},
+and of 3 adding to 23 is {6, 8, 9}). A white cell's digit must be in the
+sets of both its runs, and differ from the rest of each, and the shared
+CSP solver (`csp/csp.py`) does the searching, with AC-3 crossing off
+the sets and digits that no longer fit all the way through.
+
+`verify` checks a filled grid against the clues and names what is wrong.
+A puzzle file may also carry its solution, and `test` solves every file
+in `puzzles/` that has one and compares.
+
+
+## The Format
+
+A `grid` section of rows, cells separated by spaces:
+
+    X       a black cell with no clue
+    .       a white cell
+    23\     a clue: 23 down (in the cells below)
+    \16     16 across (in the cells to the right)
+    17\24   both
+
+and an optional `solution` section: the same rows, one character per
+cell, with the white cells' digits and . for the rest. Lines starting
+with # are comments.
+
+
+## Usage
+
+    python3 kakuro.py solve puzzles/small.txt
+    python3 kakuro.py solve puzzles/medium.txt --stats
+    python3 kakuro.py verify puzzles/medium.txt
+    python3 kakuro.py test
+"""
+
+import argparse
+import glob
+import os
+import sys
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.cages import DIGITS, PARTITIONS, add_sum_cage
+from csp.csp import CSP, Statistics, solutions
+from grid.grid import Grid, Position
+
+
+PUZZLE_DIRECTORY: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "puzzles")
+
+class Run(NamedTuple):
+    clue: Position      # the clue cell it starts after
+    direction: str      # "across" or "down"
+    cells: Tuple[Position, ...]
+    total: int
+
+    def __str__(self) -> str:
+        return f"{self.total} {self.direction} at row {self.clue[1] + 1}, column {self.clue[0] + 1}"
+
+
+class Puzzle:
+    def __init__(self, layout: Grid, solution: Optional[Grid] = None):
+        """layout's cells are "." for white, or (down, across) with either None."""
+        self.layout = layout
+        self.solution = solution
+        self.white = layout.find(".")
+        self.runs: List[Run] = []
+        for position, cell in layout.items():
+            if cell == ".":
+                continue
+            for direction, (dx, dy), total in (("down", (0, 1), cell[0]), ("across", (1, 0), cell[1])):
+                cells = []
+                x, y = position[0] + dx, position[1] + dy
+                while layout.get((x, y)) == ".":
+                    cells.append((x, y))
+                    x, y = x + dx, y + dy
+                if total is None and cells:
+                    raise ValueError(f"the white cells {direction} from row {position[1] + 1}, "
+                                     f"column {position[0] + 1} have no clue")
+                if total is not None:
+                    run = Run(position, direction, tuple(cells), total)
+                    if not cells:
+                        raise ValueError(f"the clue {run} has no white cells")
+                    if (len(cells), total) not in PARTITIONS:
+                        raise ValueError(f"the clue {run}: {len(cells)} different digits cannot add up to {total}")
+                    self.runs.append(run)
+        self.runs_of: Dict[Position, List[Run]] = {position: [] for position in self.white}
+        for run in self.runs:
+            for cell in run.cells:
+                self.runs_of[cell].append(run)
+        for position, runs in self.runs_of.items():
+            if len(runs) != 2:
+                raise ValueError(f"the white cell at row {position[1] + 1}, column {position[0] + 1} "
+                                 f"needs a run across and a run down")
+
+
+def to_csp(puzzle: Puzzle) -> CSP:
+    csp = CSP()
+    for cell in puzzle.white:
+        csp.add_variable(cell, DIGITS)
+    for run in puzzle.runs:
+        add_sum_cage(csp, run, run.cells, run.total)
+    return csp
+
+
+def solve(puzzle: Puzzle, limit: int = 2, statistics: Optional[Statistics] = None) -> List[Grid]:
+    """Up to `limit` solutions, as grids with the digits in the white cells and 0 elsewhere."""
+    found = []
+    for assignment in solutions(to_csp(puzzle), statistics=statistics):
+        grid = Grid.filled(puzzle.layout.width, puzzle.layout.height, 0)
+        for cell in puzzle.white:
+            grid[cell] = assignment[cell]
+        found.append(grid)
+        if len(found) >= limit:
+            break
+    return found
+
+
+def verify(puzzle: Puzzle, grid: Grid) -> List[str]:
+    """What is wrong with a filled grid, if anything."""
+    errors = []
+    for cell in puzzle.white:
+        if grid[cell] not in DIGITS:
+            errors.append(f"the white cell at row {cell[1] + 1}, column {cell[0] + 1} has no digit")
+    for run in puzzle.runs:
+        digits = [grid[cell] for cell in run.cells]
+        if len(set(digits)) != len(digits):
+            errors.append(f"the run {run} repeats a digit")
+        if sum(digits) != run.total:
+            errors.append(f"the run {run} adds up to {sum(digits)}")
+    return errors
+
+
+################################################################################
+###                              Reading and Writing                         ###
+################################################################################
+
+
+def parse_cell(word: str, line: int):
+    if word == ".":
+        return "."
+    if word.upper() == "X":
+        return None, None
+    down, backslash, across = word.partition("\\")
+    if not backslash or not (down or across) or not all(part.isdigit() for part in (down, across) if part):
+        raise ValueError(f"line {line}: a cell is X, ., or a clue like 23\\ or \\16 or 17\\24, not {word!r}")
+    return int(down) if down else None, int(across) if across else None
+
+
+def parse(text: str) -> Puzzle:
+    sections: Dict[str, List[Tuple[int, str]]] = {}
+    current: Optional[List[Tuple[int, str]]] = None
+    for number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        if line in ("grid", "solution"):
+            if line in sections:
+                raise ValueError(f"line {number}: a second {line} section")
+            current = sections.setdefault(line, [])
+        elif current is None:
+            raise ValueError(f"line {number}: expected 'grid' before the first row")
+        else:
+            current.append((number, line))
+    if not sections.get("grid"):
+        raise ValueError("a puzzle needs a 'grid' section")
+    rows = [[parse_cell(word, number) for word in line.split()] for number, line in sections["grid"]]
+    for (number, _), row in zip(sections["grid"], rows):
+        if len(row) != len(rows[0]):
+            raise ValueError(f"line {number}: expected {len(rows[0])} cells, not {len(row)}")
+    layout = Grid(rows)
+    solution = None
+    if "solution" in sections:
+        lines = sections["solution"]
+        if len(lines) != layout.height or any(len(line) != layout.width for _, line in lines):
+            raise ValueError(f"line {lines[0][0] if lines else 'end'}: the solution should be "
+                             f"{layout.height} rows of {layout.width} characters")
+        solution = Grid.parse("\n".join(line for _, line in lines), lambda c: int(c) if c.isdigit() else 0)
+    return Puzzle(layout, solution)
+
+
+def render(puzzle: Puzzle, grid: Optional[Grid] = None) -> str:
+    """The clues and the white cells, with the digits of grid if it is given."""
+    def text(position: Position) -> str:
+        cell = puzzle.layout[position]
+        if cell == ".":
+            return str(grid[position]) if grid is not None and grid[position] else "."
+        down, across = cell
+        if down is None and across is None:
+            return "X"
+        return f"{'' if down is None else down}\\{'' if across is None else across}"
+
+    return Grid([[text((x, y)) for x in range(puzzle.layout.width)]
+                 for y in range(puzzle.layout.height)]).render(separator=" ")
+
+
+def read_puzzle(path: str) -> Puzzle:
+    with open(path) as f:
+        return parse(f.read())
+
+
+def solve_command(path: str, stats: bool) -> bool:
+    puzzle = read_puzzle(path)
+    statistics = Statistics()
+    found = solve(puzzle, limit=2, statistics=statistics)
+    if not found:
+        print("No solution")
+    else:
+        errors = verify(puzzle, found[0])
+        if errors:
+            raise ValueError("the solver's grid is wrong: " + "; ".join(errors))
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if stats:
+        print(statistics)
+    return bool(found)
+
+
+def verify_command(path: str) -> bool:
+    puzzle = read_puzzle(path)
+    if puzzle.solution is None:
+        raise ValueError(f"{path} has no solution section to verify")
+    errors = verify(puzzle, puzzle.solution)
+    print("\n".join(errors) if errors else "The solution is right")
+    return not errors
+
+
+def test_command() -> bool:
+    passed = True
+    for path in sorted(glob.glob(os.path.join(PUZZLE_DIRECTORY, "*.txt"))):
+        puzzle = read_puzzle(path)
+        if puzzle.solution is None:
+            continue
+        found = solve(puzzle, limit=2)
+        if len(found) != 1:
+            verdict = "no solution" if not found else "more than one solution"
+        elif verify(puzzle, puzzle.solution):
+            verdict = "the recorded solution is wrong"
+        elif found[0] != puzzle.solution:
+            verdict = "a different solution from the recorded one"
+        else:
+            verdict = "ok"
+        passed &= verdict == "ok"
+        print(f"{os.path.basename(path)}: {verdict}")
+    return passed
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Kakuro")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a puzzle")
+    solve_parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    solve_parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    verify_parser = subparsers.add_parser("verify", help="check a puzzle's solution section against its clues")
+    verify_parser.add_argument("file", type=str)
+    subparsers.add_parser("test", help="solve every puzzle with a recorded solution, and compare")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "solve":
+            ok = solve_command(args.file, args.stats)
+        elif args.command == "verify":
+            ok = verify_command(args.file)
+        else:
+            ok = test_command()
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if not ok:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
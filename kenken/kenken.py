@@ -0,0 +1,190 @@
+"""
+# KenKen
+
+Also Calcudoku: fill an n by n grid with 1 to n so that each row and
+column has each number once (a Latin square), and the numbers in each
+cage make its target with its operation:
+
+    +       they add up to the target
+    -       the biggest, less the rest
+    x       they multiply to the target (* and × too)
+    /       the biggest, divided by the rest (÷ too)
+            (no operation) a cage of one cell, which is the target
+
+A number may repeat within a cage, if the two are in different rows and
+columns.
+
+
+## Solving
+
+The same way as a Killer Sudoku (`sudoku/variants.py`), by the shared
+CSP solver: a variable per cell and all-different on each row and
+column, from `add_latin_square` in `latin/latin.py`, and each cage a
+table cage from `csp/cages.py`, whose values are every way to fill it
+(with no number twice in a row or column of the cage) that makes the
+target. AC-3 propagates between the cages and the cells
+all through the search, so a cage's ways are crossed off as its cells
+lose values, and a value goes once no way left has it.
+
+
+## The Format
+
+n rows of n letters, each naming its cell's cage, then the cages'
+targets, separated by spaces: a=12+ b=2/ c=3. Lines starting with # are
+comments.
+
+    aab
+    cdb
+    cdd
+    a=3+ b=3x c=5+ d=6+
+
+
+## Usage
+
+    python3 kenken.py puzzles/four.txt
+    python3 kenken.py puzzles/six.txt --stats
+    python3 kenken.py puzzles/ambiguous.txt
+"""
+
+import argparse
+import functools
+import itertools
+import operator
+import os
+import sys
+from typing import Dict, List, NamedTuple, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.cages import add_table_cage
+from csp.csp import CSP, Statistics, solutions
+from grid.grid import Grid, Position
+from latin.latin import add_latin_square
+
+
+OPERATIONS: Dict[str, str] = {"+": "+", "-": "-", "−": "-", "x": "x", "*": "x", "×": "x",
+                              "/": "/", "÷": "/", "": ""}
+
+
+class Cage(NamedTuple):
+    name: str
+    cells: Tuple[Position, ...]
+    operation: str      # one of + - x /, or "" for a single given cell
+    target: int
+
+    def __str__(self) -> str:
+        return f"cage {self.name} ({self.target}{self.operation})"
+
+
+def makes(operation: str, target: int, values: Sequence[int]) -> bool:
+    if operation == "+":
+        return sum(values) == target
+    if operation == "x":
+        return functools.reduce(operator.mul, values, 1) == target
+    biggest = max(values)
+    rest = list(values)
+    rest.remove(biggest)
+    if operation == "-":
+        return biggest - sum(rest) == target
+    if operation == "/":
+        divisor = functools.reduce(operator.mul, rest, 1)
+        return biggest % divisor == 0 and biggest // divisor == target
+    return values[0] == target
+
+
+class Puzzle:
+    def __init__(self, size: int, cages: Sequence[Cage]):
+        self.size = size
+        self.cages = list(cages)
+        covered = sorted(cell for cage in self.cages for cell in cage.cells)
+        if covered != sorted((x, y) for x in range(size) for y in range(size)):
+            raise ValueError("every cell should be in exactly one cage")
+        for cage in self.cages:
+            if cage.operation == "" and len(cage.cells) != 1:
+                raise ValueError(f"{cage} has no operation, so it should be one cell")
+            if cage.operation in ("-", "/") and len(cage.cells) < 2:
+                raise ValueError(f"{cage} needs at least two cells")
+
+    def ways(self, cage: Cage) -> List[Tuple[int, ...]]:
+        """Every filling of the cage that makes its target, with no number twice in a row or column."""
+        clashes = [(i, j) for i, j in itertools.combinations(range(len(cage.cells)), 2)
+                   if cage.cells[i][0] == cage.cells[j][0] or cage.cells[i][1] == cage.cells[j][1]]
+        return [values for values in itertools.product(range(1, self.size + 1), repeat=len(cage.cells))
+                if all(values[i] != values[j] for i, j in clashes) and makes(cage.operation, cage.target, values)]
+
+
+def to_csp(puzzle: Puzzle) -> CSP:
+    csp = CSP()
+    add_latin_square(csp, puzzle.size)
+    for cage in puzzle.cages:
+        try:
+            add_table_cage(csp, cage.name, cage.cells, puzzle.ways(cage))
+        except ValueError:
+            raise ValueError(f"{cage} cannot be made with 1 to {puzzle.size}") from None
+    return csp
+
+
+def solve(puzzle: Puzzle, limit: int = 2, statistics: Optional[Statistics] = None) -> List[Grid]:
+    found = []
+    for assignment in solutions(to_csp(puzzle), statistics=statistics):
+        found.append(Grid([[assignment[x, y] for x in range(puzzle.size)] for y in range(puzzle.size)]))
+        if len(found) >= limit:
+            break
+    return found
+
+
+def parse(text: str) -> Puzzle:
+    lines = [(number, line.strip()) for number, line in enumerate(text.split("\n"), start=1)
+             if line.strip() and not line.strip().startswith("#")]
+    if not lines:
+        raise ValueError("the puzzle is empty")
+    size = len(lines[0][1])
+    if len(lines) < size or any(len(line) != size for _, line in lines[:size]):
+        raise ValueError(f"line {lines[0][0]}: expected {size} rows of {size} letters")
+    layout = Grid.parse("\n".join(line for _, line in lines[:size]))
+    targets: Dict[str, Tuple[str, int]] = {}
+    for number, line in lines[size:]:
+        for entry in line.split():
+            name, _, rule = entry.partition("=")
+            digits = rule.rstrip("".join(OPERATIONS))
+            symbol = rule[len(digits):]
+            if not digits.isdigit() or symbol not in OPERATIONS:
+                raise ValueError(f"line {number}: expected a cage's target, like a=12+ or b=2/, not {entry!r}")
+            targets[name] = (OPERATIONS[symbol], int(digits))
+    cages = []
+    names = sorted({cell for _, cell in layout.items()})
+    for name in names:
+        if name not in targets:
+            raise ValueError(f"cage {name} has no target")
+        cages.append(Cage(name, tuple(layout.find(name)), *targets[name]))
+    unused = sorted(set(targets) - set(names))
+    if unused:
+        raise ValueError(f"there is no cage {', '.join(unused)}")
+    return Puzzle(size, cages)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve KenKen")
+    parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+        statistics = Statistics()
+        found = solve(puzzle, limit=2, statistics=statistics)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if not found:
+        print("No solution")
+    else:
+        print(found[0].render(separator=" "))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,258 @@
+"""
+# Klotski
+
+Blocks of different shapes in a box, and one of them (in the classic
+layout, Huarong Dao or "Forget-me-not", the big 2x2 square) has to be
+slid out through the gap at the bottom. A block slides up, down, left or
+right into empty space; nothing is lifted, or turned.
+
+    A B B C         B, the 2x2, starts at the top in the middle and has
+    A B B C         to reach the bottom in the middle, above the gap
+    D E E F
+    D G H F
+    I . . J
+
+
+## Moves
+
+How a solution is counted varies, so there are two --metric options:
+
+    moves       a block going any distance, even around a corner, is one
+                move: the usual count, by which the classic layout takes
+                81 (the default)
+    steps       each step of one cell is a move
+
+Either way every move costs the same, so a breadth-first search (from
+`search/search.py`) finds the fewest. For moves, the successors of a
+layout are everything each block can reach by steps of its own, with the
+others still; the path it takes is printed with the move.
+
+
+## Congruent Blocks
+
+Blocks of the same shape are interchangeable, unless the goal names
+them: in the classic layout, the four 1x2 blocks standing up can be in
+any of 4! orders, and so can the four 1x1 soldiers, and the layouts
+that differ only by that are the same. Each layout is looked up by its
+canonical form, the positions of the blocks sorted within each shape,
+which leaves 25,955 reachable layouts, where telling the blocks apart
+would give up to 4! * 4! = 576 times as many.
+
+
+## The Format
+
+The layout, one character a cell: a letter or a digit for each cell of a
+block (the cells of a block joined side to side, one character to each
+block), . for an empty cell and # for a wall. Then a blank line, and the
+goal: the same grid, with the blocks that have a place to reach drawn
+there, and . everywhere else. Lines starting with ; are comments.
+
+    ABBC
+    ABBC
+    DEEF
+    DGHF
+    I..J
+
+    ....
+    ....
+    ....
+    .BB.
+    .BB.
+
+
+## Usage
+
+    python3 klotski.py puzzles/huarong.txt
+    python3 klotski.py puzzles/huarong.txt --metric steps --stats
+    python3 klotski.py puzzles/easy.txt --show
+    python3 klotski.py puzzles/easy.txt --animate --delay 0.3
+"""
+
+import argparse
+import os
+import sys
+import time
+from collections import deque
+from typing import Dict, FrozenSet, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from search.search import Progress, Result, bfs
+
+
+CLEAR_SCREEN: str = "\x1b[H\x1b[2J"
+METRICS: List[str] = ["moves", "steps"]
+DIRECTIONS: Dict[str, Position] = {"up": (0, -1), "down": (0, 1), "left": (-1, 0), "right": (1, 0)}
+EMPTY, WALL = ".", "#"
+State = Tuple[Position, ...]    # the top left corner of each block's bounding box, in the order of the names
+Move = Tuple[str, List[str]]    # a block, and the directions of its steps
+
+
+class Puzzle:
+    def __init__(self, layout: Grid, goal: Grid):
+        if (goal.width, goal.height) != (layout.width, layout.height):
+            raise ValueError(f"the goal is {goal.width}x{goal.height}, but the layout is "
+                             f"{layout.width}x{layout.height}")
+        self.width, self.height = layout.width, layout.height
+        self.walls = set(layout.find(WALL))
+        self.names, cells = blocks(layout)
+        self.shapes: List[FrozenSet[Position]] = []
+        corners = []
+        for name in self.names:
+            corner = (min(x for x, _ in cells[name]), min(y for _, y in cells[name]))
+            self.shapes.append(frozenset((x - corner[0], y - corner[1]) for x, y in cells[name]))
+            corners.append(corner)
+        self.start: State = tuple(corners)
+        self.goal: Dict[int, Position] = {}
+        goal_names, goal_cells = blocks(goal)
+        for name in goal_names:
+            if name not in self.names:
+                raise ValueError(f"the goal places {name}, which is not in the layout")
+            index = self.names.index(name)
+            corner = (min(x for x, _ in goal_cells[name]), min(y for _, y in goal_cells[name]))
+            if frozenset((x - corner[0], y - corner[1]) for x, y in goal_cells[name]) != self.shapes[index]:
+                raise ValueError(f"{name} has a different shape in the goal")
+            self.goal[index] = corner
+        if not self.goal:
+            raise ValueError("the goal places no block")
+        # Blocks of one shape share a kind, and are interchangeable, unless the goal places them
+        kinds: Dict[object, int] = {}
+        self.kinds: List[int] = [kinds.setdefault(("goal", i) if i in self.goal else shape, len(kinds))
+                                 for i, shape in enumerate(self.shapes)]
+
+    def cells(self, index: int, corner: Position) -> List[Position]:
+        return [(corner[0] + x, corner[1] + y) for x, y in self.shapes[index]]
+
+    def fits(self, index: int, corner: Position, occupied: Dict[Position, int]) -> bool:
+        return all(0 <= x < self.width and 0 <= y < self.height and (x, y) not in self.walls
+                   and occupied.get((x, y), index) == index for x, y in self.cells(index, corner))
+
+    def key(self, state: State) -> Tuple[Tuple[int, Position], ...]:
+        """The layout with the blocks of each kind in order, so that swapping congruent blocks changes nothing."""
+        return tuple(sorted(zip(self.kinds, state)))
+
+    def is_goal(self, state: State) -> bool:
+        return all(state[index] == corner for index, corner in self.goal.items())
+
+    def successors(self, state: State, metric: str) -> List[Tuple[Move, State]]:
+        occupied = {cell: i for i, corner in enumerate(state) for cell in self.cells(i, corner)}
+        found = []
+        for i, start in enumerate(state):
+            paths: Dict[Position, List[str]] = {start: []}
+            queue = deque([start])
+            while queue:
+                at = queue.popleft()
+                for direction, (dx, dy) in DIRECTIONS.items():
+                    to = (at[0] + dx, at[1] + dy)
+                    if to not in paths and self.fits(i, to, occupied):
+                        paths[to] = paths[at] + [direction]
+                        if metric == "moves":
+                            queue.append(to)
+            for to, path in paths.items():
+                if path:
+                    found.append(((self.names[i], path), state[:i] + (to,) + state[i + 1:]))
+        return found
+
+    def render(self, state: State) -> str:
+        shown = Grid.filled(self.width, self.height, EMPTY)
+        for cell in self.walls:
+            shown[cell] = WALL
+        for i, corner in enumerate(state):
+            for cell in self.cells(i, corner):
+                shown[cell] = self.names[i]
+        return shown.render(separator=" ")
+
+
+def blocks(grid: Grid) -> Tuple[List[str], Dict[str, List[Position]]]:
+    """The names of the blocks, in order, and each one's cells, checking that they are joined."""
+    cells: Dict[str, List[Position]] = {}
+    for position, c in grid.items():
+        if c not in (EMPTY, WALL):
+            if not c.isalnum():
+                raise ValueError(f"{c!r} at {position} is not a letter, a digit, {EMPTY} or {WALL}")
+            cells.setdefault(c, []).append(position)
+    for name, group in cells.items():
+        seen = {group[0]}
+        stack = [group[0]]
+        while stack:
+            for n in grid.neighbours(stack.pop()):
+                if n not in seen and grid[n] == name:
+                    seen.add(n)
+                    stack.append(n)
+        if len(seen) != len(group):
+            raise ValueError(f"the cells of {name} are not joined side to side")
+    return sorted(cells), cells
+
+
+def solve(puzzle: Puzzle, metric: str, progress: Optional[Progress] = None) -> Optional[Result]:
+    return bfs(puzzle.start, puzzle.is_goal, lambda state: puzzle.successors(state, metric),
+               key=puzzle.key, progress=progress)
+
+
+def describe(move: Move) -> str:
+    name, path = move
+    merged: List[Tuple[str, int]] = []
+    for direction in path:
+        if merged and merged[-1][0] == direction:
+            merged[-1] = (direction, merged[-1][1] + 1)
+        else:
+            merged.append((direction, 1))
+    return f"{name} " + ", ".join(direction + (f" {count}" if count > 1 else "") for direction, count in merged)
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    lines = [line.rstrip() for line in text.split("\n") if not line.startswith(";")]
+    sections: List[List[str]] = [[]]
+    for line in lines:
+        if line:
+            sections[-1].append(line)
+        elif sections[-1]:
+            sections.append([])
+    sections = [section for section in sections if section]
+    if len(sections) != 2:
+        raise ValueError(f"expected the layout and the goal, separated by a blank line, not {len(sections)} "
+                         f"grid{'s' * (len(sections) != 1)}")
+    layout, goal = (Grid.parse("\n".join(section)) for section in sections)
+    return Puzzle(layout, goal)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Klotski and other sliding block puzzles in the fewest moves")
+    parser.add_argument("file", type=str, help="the layout and the goal, in the format above")
+    parser.add_argument("--metric", choices=METRICS, default="moves", help="what counts as one move")
+    parser.add_argument("--show", action="store_true", help="draw the layout after every move")
+    parser.add_argument("--animate", action="store_true", help="play the solution back, in place")
+    parser.add_argument("--delay", type=float, default=0.5, help="the seconds between moves, animated")
+    parser.add_argument("--stats", action="store_true", help="print the layouts searched")
+    parser.add_argument("--quiet", action="store_true", help="do not report the progress of the search")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    result = solve(puzzle, args.metric, None if args.quiet else Progress(label="bfs"))
+    print(puzzle.render(puzzle.start))
+    if result is None:
+        print("No solution")
+        parser.exit(1)
+    if args.animate:
+        for i, (move, state) in enumerate(zip(result.moves, result.states[1:]), start=1):
+            time.sleep(args.delay)
+            print(CLEAR_SCREEN + f"{i}. {describe(move)}\n" + puzzle.render(state), flush=True)
+    for i, (move, state) in enumerate(zip(result.moves, result.states[1:]), start=1):
+        print(f"\n{i}. {describe(move)}\n{puzzle.render(state)}" if args.show else f"{i}. {describe(move)}")
+    count = len(result.moves)
+    print(f"{count} {args.metric if count != 1 else args.metric[:-1]}, the fewest")
+    if args.stats:
+        print(f"{result.expanded} layouts searched")
+
+
+if __name__ == "__main__":
+    main()
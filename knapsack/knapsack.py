@@ -0,0 +1,331 @@
+"""
+# Knapsack
+
+Items with weights and values, and a knapsack that holds so much weight:
+take the most value that fits.
+
+    01          each item at most once
+    bounded     each item up to its count
+    unbounded   each item as many times as fits
+
+
+## Methods
+
+    dp          dynamic programming over the capacity: the best value for
+                every weight up to it, item by item. An item taken up to k
+                times is split into parts of 1, 2, 4, ... of it (and the
+                rest), which are then taken or not, like 0/1 items: any
+                number up to k is a sum of some of them. Exact, in time
+                and memory the parts times the capacity.
+    bnb         branch and bound: depth-first over how many of each item
+                to take, best value per weight first, cutting off a
+                branch when even filling what is left with fractions of
+                the items at their best (Dantzig's bound) cannot beat the
+                best found. Exact, and fast when the bound is tight.
+    fptas       dynamic programming over value instead: the least weight
+                for every value, with the values rounded down to
+                multiples of epsilon * the biggest / the parts, so there
+                are few enough. The answer is within a factor (1 -
+                epsilon) of the best, in time polynomial in the parts and
+                1 / epsilon, whatever the capacity.
+
+`check` solves random instances by every method and variant, and checks
+that dp and bnb agree, that fptas comes within its factor, and that
+every answer fits and adds up.
+
+
+## The Format
+
+The capacity, then one item per line: its name, weight, value, and
+optionally its count (1 by default; the count is what bounded uses).
+Weights and values are whole numbers. Lines starting with # are
+comments.
+
+    capacity 50
+    gold 10 60
+    silver 20 100 2
+    bronze 30 120
+
+
+## Usage
+
+    python3 knapsack.py solve instances/camping.txt
+    python3 knapsack.py solve instances/camping.txt --variant bounded --method bnb --stats
+    python3 knapsack.py solve instances/camping.txt --variant unbounded --method fptas --epsilon 0.2
+    python3 knapsack.py check --instances 200 --seed 1
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import List, NamedTuple, Optional
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+VARIANTS: List[str] = ["01", "bounded", "unbounded"]
+METHODS: List[str] = ["dp", "bnb", "fptas"]
+
+
+class Item(NamedTuple):
+    name: str
+    weight: int
+    value: int
+    count: int
+
+
+class Instance(NamedTuple):
+    capacity: int
+    items: List[Item]
+
+
+class Part(NamedTuple):
+    """Some number of one item, taken or not as a whole."""
+    item: int
+    copies: int
+    weight: int
+    value: int
+
+
+class Solution(NamedTuple):
+    counts: List[int]   # how many of each item
+    weight: int
+    value: int
+
+
+class Statistics:
+    def __init__(self):
+        self.cells = 0      # of the dynamic programming tables
+        self.nodes = 0      # of the branch and bound search
+
+    def __str__(self) -> str:
+        return f"{self.nodes} nodes searched" if self.nodes else f"{self.cells} table cells filled"
+
+
+def parse(text: str) -> Instance:
+    capacity: Optional[int] = None
+    items: List[Item] = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        words = line.split()
+        if not words or words[0].startswith("#"):
+            continue
+        if words[0] == "capacity":
+            if len(words) != 2 or not words[1].isdigit():
+                raise ValueError(f"line {line_number}: expected 'capacity N'")
+            capacity = int(words[1])
+            continue
+        if len(words) not in (3, 4) or not all(word.isdigit() for word in words[1:]):
+            raise ValueError(f"line {line_number}: expected 'name weight value [count]', in whole numbers")
+        weight, value, count = int(words[1]), int(words[2]), int(words[3]) if len(words) == 4 else 1
+        if weight == 0:
+            raise ValueError(f"line {line_number}: {words[0]} weighs nothing")
+        items.append(Item(words[0], weight, value, count))
+    if capacity is None:
+        raise ValueError("no capacity (give 'capacity N')")
+    return Instance(capacity, items)
+
+
+def limits(instance: Instance, variant: str) -> List[int]:
+    """How many of each item may be taken, under the variant."""
+    most = [instance.capacity // item.weight for item in instance.items]
+    if variant == "01":
+        return [min(1, m) for m in most]
+    if variant == "bounded":
+        return [min(item.count, m) for item, m in zip(instance.items, most)]
+    return most
+
+
+def split(instance: Instance, variant: str) -> List[Part]:
+    parts = []
+    for i, (item, limit) in enumerate(zip(instance.items, limits(instance, variant))):
+        size = 1
+        while limit > 0:
+            copies = min(size, limit)
+            parts.append(Part(i, copies, copies * item.weight, copies * item.value))
+            limit -= copies
+            size *= 2
+    return parts
+
+
+def from_parts(instance: Instance, parts: List[Part], taken: List[int]) -> Solution:
+    counts = [0] * len(instance.items)
+    for p in taken:
+        counts[parts[p].item] += parts[p].copies
+    return total(instance, counts)
+
+
+def total(instance: Instance, counts: List[int]) -> Solution:
+    return Solution(counts, sum(c * item.weight for c, item in zip(counts, instance.items)),
+                    sum(c * item.value for c, item in zip(counts, instance.items)))
+
+
+################################################################################
+###                                 Methods                                  ###
+################################################################################
+
+
+def dynamic_programming(instance: Instance, variant: str, statistics: Statistics) -> Solution:
+    parts = split(instance, variant)
+    best = [0] * (instance.capacity + 1)        # the best value within each weight, with the parts so far
+    took: List[bytearray] = []                  # whether part p is in the best for each weight
+    for part in parts:
+        taking = bytearray(instance.capacity + 1)
+        for weight in range(instance.capacity, part.weight - 1, -1):
+            if best[weight - part.weight] + part.value > best[weight]:
+                best[weight] = best[weight - part.weight] + part.value
+                taking[weight] = 1
+        took.append(taking)
+        statistics.cells += instance.capacity + 1
+    taken, weight = [], instance.capacity
+    for p in reversed(range(len(parts))):
+        if took[p][weight]:
+            taken.append(p)
+            weight -= parts[p].weight
+    return from_parts(instance, parts, taken)
+
+
+def branch_and_bound(instance: Instance, variant: str, statistics: Statistics) -> Solution:
+    most = limits(instance, variant)
+    order = sorted((i for i, m in enumerate(most) if m > 0),
+                   key=lambda i: -instance.items[i].value / instance.items[i].weight)
+    counts = [0] * len(instance.items)
+    best = total(instance, list(counts))
+
+    def bound(k: int, room: int) -> float:
+        """The most the items from order[k] on could add, with fractions allowed."""
+        extra = 0.0
+        for i in order[k:]:
+            item = instance.items[i]
+            whole = min(most[i], room // item.weight)
+            extra += whole * item.value
+            room -= whole * item.weight
+            if whole < most[i]:
+                return extra + room * item.value / item.weight
+        return extra
+
+    def search(k: int, room: int, value: int):
+        nonlocal best
+        statistics.nodes += 1
+        if value > best.value:
+            best = total(instance, list(counts))
+        if k == len(order) or value + bound(k, room) <= best.value:
+            return
+        i = order[k]
+        for count in range(min(most[i], room // instance.items[i].weight), -1, -1):
+            counts[i] = count
+            search(k + 1, room - count * instance.items[i].weight, value + count * instance.items[i].value)
+        counts[i] = 0
+
+    search(0, instance.capacity, 0)
+    return best
+
+
+def fptas(instance: Instance, variant: str, epsilon: float, statistics: Statistics) -> Solution:
+    parts = [part for part in split(instance, variant) if part.value > 0]
+    if not parts:
+        return total(instance, [0] * len(instance.items))
+    scale = max(1.0, epsilon * max(part.value for part in parts) / len(parts))
+    values = [int(part.value / scale) for part in parts]
+    # lightest[v]: the least weight for a rounded value of exactly v, with the parts so far
+    top = sum(values)
+    lightest = [0] + [instance.capacity + 1] * top
+    took: List[bytearray] = []
+    for part, value in zip(parts, values):
+        taking = bytearray(top + 1)
+        for v in range(top, value - 1, -1):
+            if lightest[v - value] + part.weight < lightest[v]:
+                lightest[v] = lightest[v - value] + part.weight
+                taking[v] = 1
+        took.append(taking)
+        statistics.cells += top + 1
+    v = max(v for v in range(top + 1) if lightest[v] <= instance.capacity)
+    taken = []
+    for p in reversed(range(len(parts))):
+        if took[p][v]:
+            taken.append(p)
+            v -= values[p]
+    return from_parts(instance, parts, taken)
+
+
+def solve(instance: Instance, variant: str, method: str, epsilon: float, statistics: Statistics) -> Solution:
+    if method == "dp":
+        return dynamic_programming(instance, variant, statistics)
+    if method == "bnb":
+        return branch_and_bound(instance, variant, statistics)
+    return fptas(instance, variant, epsilon, statistics)
+
+
+def check(instances: int, items: int, epsilon: float, rng: random.Random) -> bool:
+    """Solve random instances by every method and variant, and compare."""
+    for number in range(1, instances + 1):
+        instance = Instance(rng.randint(10, 100), [
+            Item(f"item{i + 1}", rng.randint(1, 40), rng.randint(0, 60), rng.randint(1, 4)) for i in range(items)])
+        for variant in VARIANTS:
+            found = {method: solve(instance, variant, method, epsilon, Statistics()) for method in METHODS}
+            for method, solution in found.items():
+                problems = []
+                if solution != total(instance, solution.counts):
+                    problems.append("its totals do not add up")
+                if solution.weight > instance.capacity:
+                    problems.append(f"it weighs {solution.weight}, over {instance.capacity}")
+                if any(c > m for c, m in zip(solution.counts, limits(instance, variant))):
+                    problems.append("it takes an item too many times")
+                if method == "bnb" and solution.value != found["dp"].value:
+                    problems.append(f"it is worth {solution.value}, but dp found {found['dp'].value}")
+                if method == "fptas" and solution.value < (1 - epsilon) * found["dp"].value:
+                    problems.append(f"it is worth {solution.value}, under {1 - epsilon} of {found['dp'].value}")
+                if problems:
+                    print(f"instance {number}, {variant}, {method}: {'; '.join(problems)}")
+                    return False
+    print(f"All {instances} instances agree, in every variant")
+    return True
+
+
+def render(instance: Instance, solution: Solution) -> str:
+    rows = [("count", "item", "weight", "value")]
+    rows += [(str(c), item.name, str(c * item.weight), str(c * item.value))
+             for c, item in zip(solution.counts, instance.items) if c]
+    widths = [max(len(row[i]) for row in rows) for i in range(4)]
+    lines = [f"{row[0]:>{widths[0]}}  {row[1]:<{widths[1]}}  {row[2]:>{widths[2]}}  {row[3]:>{widths[3]}}"
+             for row in rows]
+    lines.append(f"weight {solution.weight} of {instance.capacity}, value {solution.value}")
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve knapsack problems")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve an instance")
+    solve_parser.add_argument("file", type=str, help="the instance, in the format above")
+    solve_parser.add_argument("--variant", choices=VARIANTS, default="01")
+    solve_parser.add_argument("--method", choices=METHODS, default="dp")
+    solve_parser.add_argument("--stats", action="store_true", help="print the work done")
+    check_parser = subparsers.add_parser("check", help="compare the methods on random instances")
+    check_parser.add_argument("--instances", type=int, default=100)
+    check_parser.add_argument("--items", type=int, default=8)
+    add_seed_argument(check_parser)
+    for p in (solve_parser, check_parser):
+        p.add_argument("--epsilon", type=float, default=0.1, help="fptas's allowed loss, as a fraction")
+    args = parser.parse_args()
+
+    if not 0 < args.epsilon < 1:
+        parser.exit(1, "error: --epsilon must be between 0 and 1\n")
+    if args.command == "check":
+        if not check(args.instances, args.items, args.epsilon, make_rng(args.seed)):
+            parser.exit(1)
+        return
+    try:
+        with open(args.file) as f:
+            instance = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    statistics = Statistics()
+    print(render(instance, solve(instance, args.variant, args.method, args.epsilon, statistics)))
+    if args.stats:
+        print(statistics)
+
+
+if __name__ == "__main__":
+    main()
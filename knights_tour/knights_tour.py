@@ -0,0 +1,189 @@
+"""
+# Knight's Tour
+
+Move a knight around a board so that it lands on every square exactly
+once. A tour is closed if the last square is a knight's move from the
+first, so it could go round again.
+
+    Warnsdorff  always move to the square with the fewest onward moves,
+                so the knight does not leave squares it cannot come back
+                to; ties go to the square farthest from the centre. This
+                is greedy, and on most boards finishes first time.
+    backtrack   if it gets stuck (or the tour must be closed, and is not),
+                a depth-first search, which tries the squares in the same
+                order, and for a closed tour also backs up as soon as the
+                start has no unvisited square left to come back from
+
+Schwenk's theorem says exactly which boards have a closed tour: an m by
+n board, with m <= n, has one unless m and n are both odd, m is 1, 2, or
+4, or m is 3 and n is 4, 6, or 8. And a knight changes colour with every
+move, so on a board with an odd number of squares, a tour has to start
+on the colour of the corners, which has one more square.
+
+
+## Usage
+
+    python3 knights_tour.py 8 8
+    python3 knights_tour.py 8 8 --start 3,4 --closed
+    python3 knights_tour.py 5 5
+    python3 knights_tour.py 5 5 --start 1,0
+    python3 knights_tour.py 3 4
+    python3 knights_tour.py 50 50 --quiet
+"""
+
+import argparse
+import os
+import sys
+from typing import List, Optional, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+
+
+KNIGHT_MOVES: List[Position] = [(1, -2), (2, -1), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2)]
+DEFAULT_MAX_NODES: int = 1_000_000
+
+
+class Board:
+    def __init__(self, width: int, height: int):
+        if width < 1 or height < 1:
+            raise ValueError("a board must be at least 1x1")
+        self.width, self.height = width, height
+        self.squares = width * height
+
+    def moves(self, position: Position) -> List[Position]:
+        x, y = position
+        return [(x + dx, y + dy) for dx, dy in KNIGHT_MOVES
+                if 0 <= x + dx < self.width and 0 <= y + dy < self.height]
+
+    def ordered(self, position: Position, visited: Set[Position]) -> List[Position]:
+        """The unvisited squares a move away, in Warnsdorff order."""
+        centre_x, centre_y = (self.width - 1) / 2, (self.height - 1) / 2
+
+        def rank(square: Position):
+            onward = sum(1 for other in self.moves(square) if other not in visited)
+            return onward, -((square[0] - centre_x) ** 2 + (square[1] - centre_y) ** 2)
+
+        return sorted((square for square in self.moves(position) if square not in visited), key=rank)
+
+    def is_closed(self, tour: List[Position]) -> bool:
+        return len(tour) == self.squares and self.squares > 1 and tour[0] in self.moves(tour[-1])
+
+
+def has_closed_tour(width: int, height: int) -> bool:
+    """By Schwenk's theorem."""
+    m, n = sorted((width, height))
+    if m % 2 == 1 and n % 2 == 1:
+        return False
+    return not (m in (1, 2, 4) or (m == 3 and n in (4, 6, 8)))
+
+
+def warnsdorff(board: Board, start: Position) -> List[Position]:
+    """The greedy tour, which is complete unless it got stuck."""
+    tour = [start]
+    visited = {start}
+    while len(tour) < board.squares:
+        options = board.ordered(tour[-1], visited)
+        if not options:
+            break
+        tour.append(options[0])
+        visited.add(options[0])
+    return tour
+
+
+class Backtracker:
+    def __init__(self, board: Board, closed: bool, max_nodes: int):
+        self.board = board
+        self.closed = closed
+        self.max_nodes = max_nodes
+        self.nodes = 0
+
+    def search(self, start: Position) -> Optional[List[Position]]:
+        """Depth-first, with a stack of the options left at each depth, so a big board does not recurse."""
+        board = self.board
+        tour = [start]
+        visited = {start}
+        options = [board.ordered(start, visited)]
+        while options:
+            if len(tour) == board.squares and (not self.closed or board.is_closed(tour)):
+                return tour
+            if not options[-1] or self.nodes >= self.max_nodes:
+                if self.nodes >= self.max_nodes:
+                    return None
+                options.pop()
+                visited.discard(tour.pop())
+                continue
+            square = options[-1].pop(0)
+            self.nodes += 1
+            tour.append(square)
+            visited.add(square)
+            if self.closed and len(tour) < board.squares and all(s in visited for s in board.moves(start)):
+                options.append([])      # nothing can come back to the start now
+            else:
+                options.append(board.ordered(square, visited))
+        return None
+
+
+def knights_tour(width: int, height: int, start: Position = (0, 0), closed: bool = False,
+                 max_nodes: int = DEFAULT_MAX_NODES) -> Tuple[Optional[List[Position]], int]:
+    """
+    A tour from the start, or None if there is none, and the nodes the
+    backtracking took (so if they reached `max_nodes`, it gave up).
+    """
+    board = Board(width, height)
+    if start not in Grid.filled(width, height, 0):
+        raise ValueError(f"the start {start} is off the {width}x{height} board")
+    if closed and not has_closed_tour(width, height):
+        return None, 0
+    if board.squares % 2 == 1 and sum(start) % 2 == 1:
+        return None, 0      # a knight changes colour every move, and there is one more of the corners' colour
+    tour = warnsdorff(board, start)
+    if len(tour) == board.squares and (not closed or board.is_closed(tour)):
+        return tour, 0
+    backtracker = Backtracker(board, closed, max_nodes)
+    return backtracker.search(start), backtracker.nodes
+
+
+def numbered(width: int, height: int, tour: List[Position]) -> Grid[int]:
+    """Each square's step in the tour, from 1."""
+    grid = Grid.filled(width, height, 0)
+    for step, square in enumerate(tour, start=1):
+        grid[square] = step
+    return grid
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Knight's tours")
+    parser.add_argument("width", type=int)
+    parser.add_argument("height", type=int)
+    parser.add_argument("--start", type=str, default="0,0", help="x,y from the top left")
+    parser.add_argument("--closed", action="store_true", help="find a tour that ends a move from its start")
+    parser.add_argument("--max-nodes", type=int, default=DEFAULT_MAX_NODES, help="when to give up backtracking")
+    parser.add_argument("--quiet", action="store_true", help="do not print the board")
+    args = parser.parse_args()
+
+    try:
+        x, _, y = args.start.partition(",")
+        if not x.strip().isdigit() or not y.strip().isdigit():
+            raise ValueError(f"the start must be x,y, not {args.start!r}")
+        tour, nodes = knights_tour(args.width, args.height, (int(x), int(y)), args.closed, args.max_nodes)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if tour is None:
+        if args.closed and not has_closed_tour(args.width, args.height):
+            print(f"No closed tour: a {args.width}x{args.height} board has none")
+        elif args.width * args.height % 2 == 1 and (int(x) + int(y)) % 2 == 1:
+            print(f"No tour from {args.start}: with an odd number of squares, a tour starts on a corner's colour")
+        elif nodes >= args.max_nodes:
+            print(f"No tour found in {nodes} nodes, so it gave up")
+        else:
+            print(f"No {'closed ' if args.closed else ''}tour from {args.start} ({nodes} nodes)")
+        parser.exit(1)
+    if not args.quiet:
+        print(numbered(args.width, args.height, tour).render(separator=" "))
+    kind = "Closed tour" if Board(args.width, args.height).is_closed(tour) else "Open tour"
+    print(f"{kind}{f' ({nodes} nodes of backtracking)' if nodes else ''}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,131 @@
+"""
+# Word Ladders
+
+Lewis Carroll's doublets: get from one word to another of the same
+length by changing one letter at a time, every step a word.
+
+    COLD
+    CORD
+    CARD
+    WARD
+    WARM
+
+
+## The Graph
+
+The words are the vertices, and two are joined if they differ in one
+letter. Comparing every pair is too slow for a big dictionary, so each
+word goes in a bucket for each of its letters blanked out: COLD is in
+_OLD, C_LD, CO_D and COL_, and its neighbours are the other words in
+those buckets. Only the words of the length asked for are bucketed, the
+first time that length is.
+
+The shortest ladder is a breadth-first search (`search/search.py`), and
+since a ladder can be climbed either way, the bidirectional search goes
+from both ends and meets in the middle, which expands far fewer words:
+a search of depth d with b neighbours a word looks at about b^d words
+one way, and 2 b^(d/2) both ways.
+
+
+## Usage
+
+    python3 ladder.py cold warm
+    python3 ladder.py ape man --stats
+    python3 ladder.py stone money --search bfs --stats
+    python3 ladder.py flour bread --words ../wordle/words_length_5.txt
+"""
+
+import argparse
+import os
+import sys
+from typing import Dict, List, Optional
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import Progress, Result, bfs, bidirectional
+
+
+SEARCHES: List[str] = ["bidirectional", "bfs"]
+DEFAULT_WORDS: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "..", "wordle", "words.txt")
+BLANK: str = "_"
+
+
+class WordGraph:
+    def __init__(self, words: List[str]):
+        self.words = list(dict.fromkeys(words))
+        self.known = set(self.words)
+        self.buckets: Dict[str, List[str]] = {}
+        self.bucketed = set()
+
+    def bucket(self, length: int):
+        if length in self.bucketed:
+            return
+        self.bucketed.add(length)
+        for word in self.words:
+            if len(word) == length:
+                for i in range(length):
+                    self.buckets.setdefault(word[:i] + BLANK + word[i + 1:], []).append(word)
+
+    def __contains__(self, word: str) -> bool:
+        return word in self.known
+
+    def neighbours(self, word: str) -> List[str]:
+        self.bucket(len(word))
+        found = []
+        for i in range(len(word)):
+            found += [other for other in self.buckets.get(word[:i] + BLANK + word[i + 1:], []) if other != word]
+        return found
+
+
+def ladder(graph: WordGraph, start: str, goal: str, search: str = "bidirectional",
+           progress: Optional[Progress] = None) -> Optional[Result]:
+    if len(start) != len(goal):
+        raise ValueError(f"{start} and {goal} are different lengths")
+    for word in (start, goal):
+        if word not in graph:
+            raise ValueError(f"{word} is not in the word list")
+
+    def successors(word: str):
+        return [(other, other) for other in graph.neighbours(word)]
+
+    def predecessors(word: str):
+        return [(word, other) for other in graph.neighbours(word)]
+
+    if search == "bfs":
+        return bfs(start, lambda word: word == goal, successors, progress=progress)
+    return bidirectional(start, goal, successors, predecessors, progress=progress)
+
+
+def read_words(path: str) -> List[str]:
+    with open(path) as f:
+        return [word for word in (line.strip().lower() for line in f) if word.isalpha() and word.isascii()]
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Find the shortest word ladder between two words")
+    parser.add_argument("start", type=str)
+    parser.add_argument("goal", type=str)
+    parser.add_argument("--words", type=str, default=DEFAULT_WORDS, help="the word list, one word a line")
+    parser.add_argument("--search", choices=SEARCHES, default="bidirectional")
+    parser.add_argument("--stats", action="store_true", help="print the words expanded")
+    parser.add_argument("--quiet", action="store_true", help="do not report the progress of the search")
+    args = parser.parse_args()
+
+    try:
+        graph = WordGraph(read_words(args.words))
+        result = ladder(graph, args.start.lower(), args.goal.lower(), args.search,
+                        None if args.quiet else Progress())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if result is None:
+        print(f"No ladder from {args.start} to {args.goal}")
+    else:
+        print("\n".join(word.upper() for word in result.states))
+        print(f"{len(result.moves)} steps")
+    if args.stats:
+        print(f"{result.expanded if result is not None else 'all reachable'} words expanded")
+    if result is None:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,299 @@
+"""
+# Combinator Calculus
+
+Combinatory logic is the lambda calculus without variables: everything
+is built by applying a few fixed functions (combinators) to each other.
+
+    I x      = x
+    K x y    = x
+    S x y z  = x z (y z)
+    B x y z  = x (y z)
+    C x y z  = x z y
+    W x y    = x y y
+
+S and K alone are enough for everything (even I is `S K K`), and so are
+B, C, K, and W. A term is a sequence of combinators and variables with
+the usual left-associative application, e.g. `S (K S) K x y`. Any word
+spelled only with the combinator letters is split into them, so `SKK` is
+`S K K`, and any other word is a variable (or a defined name, see below).
+Reduction always rewrites the leftmost, outermost combinator that has
+enough arguments.
+
+
+## From Lambda Terms
+
+`:lambda term` (or `--lambda`) translates a lambda term into combinators
+by bracket abstraction, which removes one variable at a time:
+
+    [x] x      = I
+    [x] e      = K e                  if x is not free in e
+    [x] f x    = f                    if x is not free in f
+    [x] f a    = B f ([x] a)          if x is only free in a
+    [x] f a    = C ([x] f) a          if x is only free in f
+    [x] f a    = S ([x] f) ([x] a)
+
+With `--basis ski`, B and C are not used. With `--basis bckw`, I is
+written `W K` and S is written in terms of B, C, and W. Lambda terms may
+use the lambda calculus prelude (e.g. `PLUS`, `MULT`).
+
+
+## Usage
+
+    python3 combinators.py                            # the REPL
+    python3 combinators.py --eval "S K K x"
+    python3 combinators.py --lambda "\\x y. y x" --basis ski
+    python3 combinators.py --eval "S (K (S I)) K a b" --trace
+
+In the REPL, `NAME = term` defines a name and `:help` lists the commands.
+"""
+
+import argparse
+import os
+import re
+import sys
+from typing import Dict, List, Optional, Tuple
+
+import lambda_calculus
+from lambda_calculus import App, Term, Var, free_variables
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from repl.repl import Repl
+
+
+DEFAULT_MAX_STEPS: int = 100_000
+ARITY: Dict[str, int] = {"I": 1, "K": 2, "S": 3, "B": 3, "C": 3, "W": 2}
+BASES: Tuple[str, ...] = ("ski", "skibc", "bckw")
+
+
+def parse(text: str, definitions: Dict[str, Term]) -> Term:
+    tokens = re.findall(r"\(|\)|[A-Za-z0-9_']+|\S", text)
+    position = 0
+
+    def atom() -> Term:
+        nonlocal position
+        if position >= len(tokens):
+            raise ValueError("unexpected end of input")
+        token = tokens[position]
+        position += 1
+        if token == "(":
+            term = sequence()
+            if position >= len(tokens) or tokens[position] != ")":
+                raise ValueError("missing ')'")
+            position += 1
+            return term
+        if token == ")" or not re.fullmatch(r"[A-Za-z0-9_']+", token):
+            raise ValueError(f"unexpected '{token}'")
+        if token in definitions:
+            return definitions[token]
+        if all(c in ARITY for c in token):
+            term: Term = Var(token[0])
+            for c in token[1:]:
+                term = App(term, Var(c))
+            return term
+        return Var(token)
+
+    def sequence() -> Term:
+        term = atom()
+        while position < len(tokens) and tokens[position] != ")":
+            term = App(term, atom())
+        return term
+
+    term = sequence()
+    if position != len(tokens):
+        raise ValueError(f"unexpected '{tokens[position]}'")
+    return term
+
+
+def format_term(term: Term) -> str:
+    if isinstance(term, Var):
+        return term.name
+    argument = format_term(term.argument)
+    if isinstance(term.argument, App):
+        argument = f"({argument})"
+    return f"{format_term(term.function)} {argument}"
+
+
+def unwind(term: Term) -> Tuple[Term, List[Term]]:
+    """Split `h a1 a2 ... an` into h and [a1, ..., an]."""
+    args: List[Term] = []
+    while isinstance(term, App):
+        args.append(term.argument)
+        term = term.function
+    return term, args[::-1]
+
+
+def rebuild(head: Term, args: List[Term]) -> Term:
+    for arg in args:
+        head = App(head, arg)
+    return head
+
+
+def contract(name: str, args: List[Term]) -> Term:
+    if name == "I":
+        x, = args
+        return x
+    if name == "K":
+        x, _ = args
+        return x
+    if name == "W":
+        x, y = args
+        return App(App(x, y), y)
+    x, y, z = args
+    if name == "S":
+        return App(App(x, z), App(y, z))
+    if name == "B":
+        return App(x, App(y, z))
+    return App(App(x, z), y)
+
+
+def step(term: Term) -> Optional[Term]:
+    """Do one reduction, or return None if the term is in normal form."""
+    head, args = unwind(term)
+    if isinstance(head, Var) and head.name in ARITY and len(args) >= ARITY[head.name]:
+        n = ARITY[head.name]
+        return rebuild(contract(head.name, args[:n]), args[n:])
+    for i, arg in enumerate(args):
+        reduced = step(arg)
+        if reduced is not None:
+            return rebuild(head, args[:i] + [reduced] + args[i + 1:])
+    return None
+
+
+################################################################################
+###                            Bracket Abstraction                           ###
+################################################################################
+
+
+def occurs(name: str, term: Term) -> bool:
+    if isinstance(term, Var):
+        return term.name == name
+    return occurs(name, term.function) or occurs(name, term.argument)
+
+
+def abstract(name: str, term: Term, basis: str) -> Term:
+    """Return a combinator term t such that `t x` reduces to `term`."""
+    if not occurs(name, term):
+        return App(Var("K"), term)
+    if term == Var(name):
+        return App(Var("W"), Var("K")) if basis == "bckw" else Var("I")
+    function, argument = term
+    if argument == Var(name) and not occurs(name, function):
+        return function
+    if basis != "ski" and not occurs(name, function):
+        return App(App(Var("B"), function), abstract(name, argument, basis))
+    if basis != "ski" and not occurs(name, argument):
+        return App(App(Var("C"), abstract(name, function, basis)), argument)
+    s = Var("S") if basis != "bckw" else parse("B (B (B W) C) (B B)", {})
+    return App(App(s, abstract(name, function, basis)), abstract(name, argument, basis))
+
+
+def from_lambda(term: Term, basis: str) -> Term:
+    if isinstance(term, Var):
+        return term
+    if isinstance(term, App):
+        return App(from_lambda(term.function, basis), from_lambda(term.argument, basis))
+    return abstract(term.param, from_lambda(term.body, basis), basis)
+
+
+################################################################################
+###                                Interpreter                               ###
+################################################################################
+
+
+class Interpreter:
+    def __init__(self, basis: str = "skibc", max_steps: int = DEFAULT_MAX_STEPS, trace: bool = False):
+        self.definitions: Dict[str, Term] = {}
+        self.basis = basis
+        self.max_steps = max_steps
+        self.trace = trace
+        self.lambdas = lambda_calculus.Interpreter()
+
+    def translate(self, text: str) -> Term:
+        term = self.lambdas.expand(lambda_calculus.parse(text))
+        clashes = free_variables(term) & set(ARITY)
+        if clashes:
+            raise ValueError(f"free variables {', '.join(sorted(clashes))} would be read as combinators")
+        return from_lambda(term, self.basis)
+
+    def normalize(self, term: Term) -> Optional[Term]:
+        if self.trace:
+            print(f"    {format_term(term)}")
+        for i in range(self.max_steps):
+            reduced = step(term)
+            if reduced is None:
+                return term
+            term = reduced
+            if self.trace:
+                print(f"--> {format_term(term)}")
+        return None
+
+    def evaluate(self, line: str):
+        match = re.fullmatch(r"\s*([A-Za-z_][A-Za-z0-9_']*)\s*=(.*)", line)
+        if match is not None:
+            name = match.group(1)
+            if all(c in ARITY for c in name):
+                raise ValueError(f"'{name}' would be read as combinators")
+            self.definitions[name] = parse(match.group(2), self.definitions)
+            return
+        result = self.normalize(parse(line, self.definitions))
+        if result is None:
+            print(f"no normal form within {self.max_steps} steps")
+        else:
+            print(format_term(result))
+
+
+def start_repl(interpreter: Interpreter):
+    repl = Repl("SKI> ", interpreter.evaluate)
+
+    def set_basis(argument: str):
+        if argument not in BASES:
+            raise ValueError(f"the basis must be one of {', '.join(BASES)}")
+        interpreter.basis = argument
+
+    def set_trace(argument: str):
+        interpreter.trace = argument != "off"
+
+    def set_steps(argument: str):
+        interpreter.max_steps = int(argument)
+
+    def translate(argument: str):
+        print(format_term(interpreter.translate(argument)))
+
+    def show_definitions(_: str):
+        for name, term in interpreter.definitions.items():
+            print(f"{name} = {format_term(term)}")
+
+    repl.add_command("lambda", translate, "translate a lambda term into combinators")
+    repl.add_command("basis", set_basis, f"combinators for :lambda to use ({', '.join(BASES)})")
+    repl.add_command("trace", set_trace, "'on' or 'off': show every reduction")
+    repl.add_command("steps", set_steps, "give up after this many reductions")
+    repl.add_command("defs", show_definitions, "list the definitions")
+    print("Combinator calculus. Type :help for the commands.")
+    repl.run()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="SKI and BCKW combinator calculus")
+    parser.add_argument("--eval", type=str, default=None, help="reduce this term and exit")
+    parser.add_argument("--lambda", dest="lambda_term", type=str, default=None,
+                        help="translate this lambda term into combinators and exit")
+    parser.add_argument("--basis", choices=BASES, default="skibc", help="combinators for --lambda")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="show every reduction")
+    args = parser.parse_args()
+
+    sys.setrecursionlimit(20_000)
+    interpreter = Interpreter(args.basis, args.max_steps, args.trace)
+    try:
+        if args.lambda_term is not None:
+            print(format_term(interpreter.translate(args.lambda_term)))
+        elif args.eval is not None:
+            interpreter.evaluate(args.eval)
+        else:
+            start_repl(interpreter)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
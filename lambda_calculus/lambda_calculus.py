@@ -0,0 +1,438 @@
+"""
+# Lambda Calculus
+
+The untyped lambda calculus has only variables, functions (abstractions),
+and applications, and yet it can express any computation:
+
+    x               a variable
+    \\x. body        a function of x (`λ` works too, and `\\x y. b` is `\\x. \\y. b`)
+    f a             apply f to a (application is left-associative)
+
+Evaluation is beta reduction: `(\\x. body) a` becomes body with `a`
+substituted for x. If `a` has a free variable that body binds, we rename
+the bound variable first (alpha conversion) so that the free variable
+does not get captured.
+
+
+## Reduction Strategies
+
+* Normal order always reduces the leftmost, outermost redex. It finds
+  the normal form whenever there is one.
+* Applicative order reduces the arguments before applying a function,
+  like most programming languages. It can loop forever on terms that
+  have a normal form, e.g. `K I Ω`, and on the Y combinator (use Z).
+
+
+## Definitions and Numerals
+
+`NAME = term` defines a name, and a free variable with a defined name is
+replaced by its definition. The prelude defines the usual Church
+encodings (see `PRELUDE`), and a number n stands for the Church numeral
+`\\f x. f (... (f x))` with n applications of f. Results that are Church
+numerals or booleans are labelled as such.
+
+
+## Usage
+
+    python3 lambda_calculus.py                       # the REPL
+    python3 lambda_calculus.py --eval "FACT 4"
+    python3 lambda_calculus.py --eval "PLUS 2 3" --strategy applicative --trace
+
+In the REPL, `:help` lists the commands.
+"""
+
+import argparse
+import os
+import re
+import sys
+from typing import Dict, Iterator, List, NamedTuple, Optional, Set, Union
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from repl.repl import Repl
+
+
+DEFAULT_MAX_STEPS: int = 100_000
+NORMAL: str = "normal"
+APPLICATIVE: str = "applicative"
+
+
+class Var(NamedTuple):
+    name: str
+
+
+class Abs(NamedTuple):
+    param: str
+    body: "Term"
+
+
+class App(NamedTuple):
+    function: "Term"
+    argument: "Term"
+
+
+Term = Union[Var, Abs, App]
+
+
+################################################################################
+###                                  Parsing                                 ###
+################################################################################
+
+
+TOKEN: str = r"\s*(?:(\\|λ)|(\.)|(\()|(\))|(=)|([A-Za-z_][A-Za-z0-9_']*|\d+)|(\S))"
+
+
+def tokenize(text: str) -> List[str]:
+    tokens: List[str] = []
+    for match in re.finditer(TOKEN, text):
+        if match.group(7) is not None:
+            raise ValueError(f"unexpected '{match.group(7)}'")
+        tokens.append(next(g for g in match.groups() if g is not None))
+    return [t if t != "λ" else "\\" for t in tokens]
+
+
+class Parser:
+    def __init__(self, text: str):
+        self.tokens = tokenize(text)
+        self.position = 0
+
+    def peek(self) -> Optional[str]:
+        return self.tokens[self.position] if self.position < len(self.tokens) else None
+
+    def take(self, expected: Optional[str] = None) -> str:
+        token = self.peek()
+        if token is None:
+            raise ValueError("unexpected end of input")
+        if expected is not None and token != expected:
+            raise ValueError(f"expected '{expected}' but found '{token}'")
+        self.position += 1
+        return token
+
+    def term(self) -> Term:
+        """term := '\\' names '.' term | atom+ [ '\\' ... ]"""
+        if self.peek() == "\\":
+            self.take()
+            params: List[str] = []
+            while self.peek() not in (".", None):
+                name = self.take()
+                if not is_name(name) or name.isdigit():
+                    raise ValueError(f"'{name}' cannot be a parameter")
+                params.append(name)
+            if not params:
+                raise ValueError("a function needs at least one parameter")
+            self.take(".")
+            body = self.term()
+            for param in reversed(params):
+                body = Abs(param, body)
+            return body
+        term = self.atom()
+        while self.peek() not in (")", None):
+            # A lambda in argument position extends as far right as possible
+            argument = self.term() if self.peek() == "\\" else self.atom()
+            term = App(term, argument)
+        return term
+
+    def atom(self) -> Term:
+        token = self.take()
+        if token == "(":
+            term = self.term()
+            self.take(")")
+            return term
+        if token.isdigit():
+            return church_numeral(int(token))
+        if not is_name(token):
+            raise ValueError(f"unexpected '{token}'")
+        return Var(token)
+
+    def done(self):
+        if self.peek() is not None:
+            raise ValueError(f"unexpected '{self.peek()}'")
+
+
+def is_name(token: str) -> bool:
+    return re.fullmatch(r"[A-Za-z_][A-Za-z0-9_']*|\d+", token) is not None
+
+
+def parse(text: str) -> Term:
+    parser = Parser(text)
+    term = parser.term()
+    parser.done()
+    return term
+
+
+def format_term(term: Term) -> str:
+    if isinstance(term, Var):
+        return term.name
+    if isinstance(term, Abs):
+        params: List[str] = []
+        while isinstance(term, Abs):
+            params.append(term.param)
+            term = term.body
+        return f"\\{' '.join(params)}. {format_term(term)}"
+    function = format_term(term.function)
+    if isinstance(term.function, Abs):
+        function = f"({function})"
+    argument = format_term(term.argument)
+    if not isinstance(term.argument, Var):
+        argument = f"({argument})"
+    return f"{function} {argument}"
+
+
+################################################################################
+###                                 Reduction                                ###
+################################################################################
+
+
+def free_variables(term: Term) -> Set[str]:
+    if isinstance(term, Var):
+        return {term.name}
+    if isinstance(term, Abs):
+        return free_variables(term.body) - {term.param}
+    return free_variables(term.function) | free_variables(term.argument)
+
+
+def fresh_name(name: str, avoid: Set[str]) -> str:
+    base = name.rstrip("0123456789")
+    i = 1
+    while f"{base}{i}" in avoid:
+        i += 1
+    return f"{base}{i}"
+
+
+def substitute(term: Term, name: str, value: Term) -> Term:
+    """Replace the free occurrences of `name` in `term` by `value`, avoiding capture."""
+    if isinstance(term, Var):
+        return value if term.name == name else term
+    if isinstance(term, App):
+        return App(substitute(term.function, name, value), substitute(term.argument, name, value))
+    if term.param == name:
+        return term
+    if term.param in free_variables(value) and name in free_variables(term.body):
+        # Alpha conversion: rename the parameter so it does not capture `value`
+        avoid = free_variables(value) | free_variables(term.body) | {name}
+        param = fresh_name(term.param, avoid)
+        body = substitute(term.body, term.param, Var(param))
+        return Abs(param, substitute(body, name, value))
+    return Abs(term.param, substitute(term.body, name, value))
+
+
+def step(term: Term, strategy: str) -> Optional[Term]:
+    """Do one beta reduction, or return None if the term is in normal form."""
+    if isinstance(term, Var):
+        return None
+    if isinstance(term, Abs):
+        body = step(term.body, strategy)
+        return None if body is None else Abs(term.param, body)
+    function, argument = term
+    if strategy == NORMAL and isinstance(function, Abs):
+        return substitute(function.body, function.param, argument)
+    reduced = step(function, strategy)
+    if reduced is not None:
+        return App(reduced, argument)
+    reduced = step(argument, strategy)
+    if reduced is not None:
+        return App(function, reduced)
+    if isinstance(function, Abs):
+        return substitute(function.body, function.param, argument)
+    return None
+
+
+def reductions(term: Term, strategy: str) -> Iterator[Term]:
+    """Yield every term on the way to the normal form (which may never come)."""
+    while True:
+        term = step(term, strategy)
+        if term is None:
+            return
+        yield term
+
+
+################################################################################
+###                              Church Encodings                            ###
+################################################################################
+
+
+def church_numeral(n: int) -> Term:
+    body: Term = Var("x")
+    for _ in range(n):
+        body = App(Var("f"), body)
+    return Abs("f", Abs("x", body))
+
+
+def from_church_numeral(term: Term) -> Optional[int]:
+    if not (isinstance(term, Abs) and isinstance(term.body, Abs)):
+        return None
+    f, x = term.param, term.body.param
+    if f == x:
+        return None
+    n, body = 0, term.body.body
+    while isinstance(body, App) and body.function == Var(f):
+        n, body = n + 1, body.argument
+    return n if body == Var(x) else None
+
+
+def from_church_boolean(term: Term) -> Optional[bool]:
+    if not (isinstance(term, Abs) and isinstance(term.body, Abs)) or term.param == term.body.param:
+        return None
+    if term.body.body == Var(term.param):
+        return True
+    if term.body.body == Var(term.body.param):
+        return False
+    return None
+
+
+PRELUDE: str = r"""
+I = \x. x
+K = \x y. x
+S = \x y z. x z (y z)
+OMEGA = (\x. x x) (\x. x x)
+Y = \f. (\x. f (x x)) (\x. f (x x))
+Z = \f. (\x. f (\v. x x v)) (\x. f (\v. x x v))
+
+TRUE = \a b. a
+FALSE = \a b. b
+AND = \p q. p q p
+OR = \p q. p p q
+NOT = \p. p FALSE TRUE
+IF = \p a b. p a b
+
+PAIR = \a b f. f a b
+FST = \p. p TRUE
+SND = \p. p FALSE
+
+SUCC = \n f x. f (n f x)
+PLUS = \m n f x. m f (n f x)
+MULT = \m n f. m (n f)
+POW = \b e. e b
+PRED = \n f x. n (\g h. h (g f)) (\u. x) (\u. u)
+SUB = \m n. n PRED m
+ISZERO = \n. n (\x. FALSE) TRUE
+LEQ = \m n. ISZERO (SUB m n)
+EQ = \m n. AND (LEQ m n) (LEQ n m)
+
+FACT = Y (\f n. ISZERO n 1 (MULT n (f (PRED n))))
+FIB = Y (\f n. LEQ n 1 n (PLUS (f (PRED n)) (f (PRED (PRED n)))))
+"""
+
+
+################################################################################
+###                                Interpreter                               ###
+################################################################################
+
+
+class Interpreter:
+    def __init__(self, strategy: str = NORMAL, max_steps: int = DEFAULT_MAX_STEPS, trace: bool = False):
+        self.definitions: Dict[str, Term] = {}
+        self.strategy = strategy
+        self.max_steps = max_steps
+        self.trace = trace
+        self.load_text(PRELUDE)
+
+    def expand(self, term: Term) -> Term:
+        """Replace the free variables that have definitions."""
+        for name in free_variables(term) & set(self.definitions):
+            term = substitute(term, name, self.definitions[name])
+        return term
+
+    def define(self, name: str, text: str):
+        if not is_name(name) or name.isdigit():
+            raise ValueError(f"'{name}' cannot be defined")
+        self.definitions[name] = self.expand(parse(text))
+
+    def load_text(self, text: str):
+        for line in text.split("\n"):
+            line = line.split("#", 1)[0].strip()
+            if line:
+                self.evaluate(line, quiet=True)
+
+    def normalize(self, term: Term) -> Optional[Term]:
+        """Reduce to normal form, or return None if it takes too many steps."""
+        term = self.expand(term)
+        if self.trace:
+            print(f"    {format_term(term)}")
+        for i, term in enumerate(reductions(term, self.strategy), start=1):
+            if self.trace:
+                print(f"--> {format_term(term)}")
+            if i >= self.max_steps:
+                return None
+        return term
+
+    def describe(self, term: Term) -> str:
+        labels: List[str] = []
+        number = from_church_numeral(term)
+        if number is not None:
+            labels.append(str(number))
+        boolean = from_church_boolean(term)
+        if boolean is not None:
+            labels.append("TRUE" if boolean else "FALSE")
+        text = format_term(term)
+        return f"{text}    (= {' = '.join(labels)})" if labels else text
+
+    def evaluate(self, line: str, quiet: bool = False):
+        match = re.fullmatch(r"\s*([A-Za-z_][A-Za-z0-9_']*)\s*=(.*)", line)
+        if match is not None:
+            self.define(match.group(1), match.group(2))
+            return
+        result = self.normalize(parse(line))
+        if quiet:
+            return
+        if result is None:
+            print(f"no normal form within {self.max_steps} steps")
+        else:
+            print(self.describe(result))
+
+
+def start_repl(interpreter: Interpreter):
+    repl = Repl("λ> ", interpreter.evaluate)
+
+    def set_strategy(argument: str):
+        if argument not in (NORMAL, APPLICATIVE):
+            raise ValueError(f"the strategy must be '{NORMAL}' or '{APPLICATIVE}'")
+        interpreter.strategy = argument
+
+    def set_trace(argument: str):
+        interpreter.trace = argument != "off"
+
+    def set_steps(argument: str):
+        interpreter.max_steps = int(argument)
+
+    def show_definitions(_: str):
+        for name, term in interpreter.definitions.items():
+            print(f"{name} = {format_term(term)}")
+
+    def load_file(argument: str):
+        with open(argument) as f:
+            interpreter.load_text(f.read())
+
+    repl.add_command("strategy", set_strategy, f"'{NORMAL}' or '{APPLICATIVE}' order")
+    repl.add_command("trace", set_trace, "'on' or 'off': show every reduction")
+    repl.add_command("steps", set_steps, "give up after this many reductions")
+    repl.add_command("defs", show_definitions, "list the definitions")
+    repl.add_command("load", load_file, "read definitions from a file")
+    print(f"Lambda calculus ({interpreter.strategy} order). Type :help for the commands.")
+    repl.run()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Untyped lambda calculus")
+    parser.add_argument("--eval", type=str, default=None, help="evaluate this term and exit")
+    parser.add_argument("--strategy", choices=(NORMAL, APPLICATIVE), default=NORMAL)
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="show every reduction")
+    parser.add_argument("--load", type=str, default=None, help="read definitions from a file")
+    args = parser.parse_args()
+
+    sys.setrecursionlimit(20_000)
+    interpreter = Interpreter(args.strategy, args.max_steps, args.trace)
+    if args.load is not None:
+        with open(args.load) as f:
+            interpreter.load_text(f.read())
+    if args.eval is None:
+        start_repl(interpreter)
+        return
+    try:
+        interpreter.evaluate(args.eval)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
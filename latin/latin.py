@@ -0,0 +1,316 @@
+"""
+# Latin Squares
+
+An n by n square of n symbols (here 1 to n), each once in every row and
+once in every column. A Sudoku is one with boxes, and a KenKen one with
+cages, and both build on this module: `add_latin_square` puts the rows
+and columns into a CSP, and `to_dlx` takes extra regions, like a
+Sudoku's boxes, that must also have every symbol once.
+
+
+## Completing
+
+A partial Latin square is completed as an exact cover problem, by the
+shared dancing links (`dlx/dlx.py`): an option is "symbol s in row r,
+column c", and covers the items "r,c is filled", "row r has s" and
+"column c has s" (and "region g has s", for any regions). `generate`
+completes an empty square with the options in a random order, so it can
+come out as any Latin square, not just the cyclic one.
+
+
+## Orthogonal Squares
+
+Two Latin squares of the same order are orthogonal if, laid on top of
+each other, every one of the n^2 pairs of symbols turns up exactly once:
+a Graeco-Latin square, the Latin symbols in one and the Greek in the
+other (here letters and numbers). `graeco` constructs a pair:
+
+    odd n           r + c and r + 2c (mod n), with the rows and columns
+                    numbered from 0
+    n = 2^k >= 4    a r + c and b r + c in the finite field of order n,
+                    for two different a, b other than 0
+    n = 2^k m       MacNeish's product of a pair of order 2^k and one of
+                    order m: each cell a pair of cells, one from each
+
+That covers every order but those 2 more than a multiple of 4. There is
+no pair for 2, or for 6 (Euler's 36 officers, settled by Tarry in 1900),
+but there is one for every other order: Bose, Shrikhande and Parker
+found them in 1959, by constructions not here.
+
+Squares are written one row a line, with the numbers separated by
+spaces; `.` is an empty cell. Lines starting with # are comments.
+
+
+## Usage
+
+    python3 latin.py generate 5 --seed 1
+    python3 latin.py complete squares/partial.txt --stats
+    python3 latin.py complete squares/open.txt --count
+    python3 latin.py graeco 4
+    python3 latin.py graeco 12 --check
+    python3 latin.py orthogonal squares/euler_a.txt squares/euler_b.txt
+    python3 latin.py orthogonal squares/euler_a.txt squares/not_orthogonal.txt
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Hashable, List, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.csp import CSP
+from dlx.dlx import DLX
+from rng.rng import add_seed_argument, make_rng
+
+
+Square = List[List[int]]
+Partial = List[List[Optional[int]]]
+# Irreducible polynomials over GF(2), one for each degree, as bits: x^2 + x + 1 is 0b111
+IRREDUCIBLE = {2: 0b111, 3: 0b1011, 4: 0b10011, 5: 0b100101, 6: 0b1000011, 7: 0b10000011, 8: 0b100011011}
+
+
+def is_latin(square: Partial) -> bool:
+    n = len(square)
+    symbols = list(range(1, n + 1))
+    return all(sorted(row) == symbols for row in square) and \
+        all(sorted(square[r][c] for r in range(n)) == symbols for c in range(n))
+
+
+def add_latin_square(csp: CSP, size: int):
+    """A variable (x, y) for every cell, with the domain 1 to size, and every row and column all different."""
+    for y in range(size):
+        for x in range(size):
+            csp.add_variable((x, y), range(1, size + 1))
+    for i in range(size):
+        csp.all_different([(x, i) for x in range(size)])
+        csp.all_different([(i, y) for y in range(size)])
+
+
+################################################################################
+###                                Completing                                ###
+################################################################################
+
+
+def to_dlx(given: Partial, regions: Sequence[Sequence[Tuple[int, int]]] = (),
+           rng: Optional[random.Random] = None) -> DLX:
+    """Option (r, c, s) is symbol s in row r, column c. Each region's cells, (r, c)s, must also have every symbol;
+    the options are shuffled if rng is given."""
+    n = len(given)
+    region_of = {cell: g for g, region in enumerate(regions) for cell in region}
+    items: List[Hashable] = [("cell", r, c) for r in range(n) for c in range(n)]
+    items += [(kind, i, s) for kind in ("row", "column") for i in range(n) for s in range(1, n + 1)]
+    items += [("region", g, s) for g in range(len(regions)) for s in range(1, n + 1)]
+    options = []
+    for r in range(n):
+        for c in range(n):
+            for s in [given[r][c]] if given[r][c] is not None else range(1, n + 1):
+                covers = [("cell", r, c), ("row", r, s), ("column", c, s)]
+                if (r, c) in region_of:
+                    covers.append(("region", region_of[r, c], s))
+                options.append(((r, c, s), covers))
+    if rng is not None:
+        rng.shuffle(options)
+    dlx = DLX(items)
+    for name, covers in options:
+        dlx.add_option(name, covers)
+    return dlx
+
+
+def from_cover(n: int, cover: List[Tuple[int, int, int]]) -> Square:
+    square = [[0] * n for _ in range(n)]
+    for r, c, s in cover:
+        square[r][c] = s
+    return square
+
+
+def complete(given: Partial, limit: int) -> Tuple[List[Square], DLX]:
+    """Up to limit completions, and the DLX that found them (for its statistics)."""
+    dlx = to_dlx(given)
+    found = []
+    for cover in dlx.solutions():
+        found.append(from_cover(len(given), cover))
+        if len(found) == limit:
+            break
+    return found, dlx
+
+
+def generate(n: int, rng: random.Random) -> Square:
+    cover = to_dlx([[None] * n for _ in range(n)], rng=rng).solve()
+    assert cover is not None
+    return from_cover(n, cover)
+
+
+################################################################################
+###                            Orthogonal Squares                            ###
+################################################################################
+
+
+def are_orthogonal(a: Square, b: Square) -> bool:
+    n = len(a)
+    return len({(a[r][c], b[r][c]) for r in range(n) for c in range(n)}) == n * n
+
+
+def multiply(x: int, y: int, k: int) -> int:
+    """x times y in the field of order 2^k, whose elements are polynomials over GF(2), as bits."""
+    product = 0
+    while y:
+        if y & 1:
+            product ^= x
+        y >>= 1
+        x <<= 1
+        if x >> k:
+            x ^= IRREDUCIBLE[k]
+    return product
+
+
+def odd_pair(n: int) -> Tuple[Square, Square]:
+    return ([[(r + c) % n + 1 for c in range(n)] for r in range(n)],
+            [[(r + 2 * c) % n + 1 for c in range(n)] for r in range(n)])
+
+
+def field_pair(k: int) -> Tuple[Square, Square]:
+    n = 1 << k
+    # In the field, r + c is r ^ c; a = 1 and b = x (the element 2)
+    return ([[(r ^ c) + 1 for c in range(n)] for r in range(n)],
+            [[(multiply(2, r, k) ^ c) + 1 for c in range(n)] for r in range(n)])
+
+
+def product(a: Square, b: Square) -> Square:
+    """The square whose cell ((r, r'), (c, c')) is the pair (a[r][c], b[r'][c']), numbered."""
+    m = len(b)
+    return [[(a[r // m][c // m] - 1) * m + b[r % m][c % m] for c in range(len(a) * m)] for r in range(len(a) * m)]
+
+
+def graeco_latin(n: int) -> Tuple[Square, Square]:
+    if n in (2, 6):
+        raise ValueError(f"there are no orthogonal Latin squares of order {n}")
+    if n % 4 == 2:
+        raise ValueError(f"there are orthogonal Latin squares of order {n}, but not by these constructions")
+    k, m = 0, n
+    while m % 2 == 0:
+        k, m = k + 1, m // 2
+    if k > max(IRREDUCIBLE):
+        raise ValueError(f"the order's power of 2, 2^{k}, is more than this module has a field for")
+    if n == 1:
+        return [[1]], [[1]]
+    if k == 0:
+        return odd_pair(n)
+    pair = field_pair(k)
+    if m == 1:
+        return pair
+    odd = odd_pair(m)
+    return product(pair[0], odd[0]), product(pair[1], odd[1])
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Partial:
+    rows: Partial = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        words = line.split()
+        if not words or words[0].startswith("#"):
+            continue
+        if not all(word.isdigit() or word == "." for word in words):
+            raise ValueError(f"line {line_number}: expected whole numbers, or . for an empty cell")
+        rows.append([int(word) if word != "." else None for word in words])
+        if len(rows[-1]) != len(rows[0]):
+            raise ValueError(f"line {line_number}: {len(rows[-1])} symbols, but the first row has {len(rows[0])}")
+    if not rows:
+        raise ValueError("no square")
+    n = len(rows)
+    if n != len(rows[0]):
+        raise ValueError(f"{n} rows of {len(rows[0])}: the square is not square")
+    if any(s is not None and not 1 <= s <= n for row in rows for s in row):
+        raise ValueError(f"the symbols are 1 to {n}")
+    return rows
+
+
+def read(path: str) -> Partial:
+    with open(path) as f:
+        return parse(f.read())
+
+
+def render(square: Partial) -> str:
+    width = len(str(len(square)))
+    return "\n".join(" ".join(".".rjust(width) if s is None else str(s).rjust(width) for s in row) for row in square)
+
+
+def render_pair(a: Square, b: Square) -> str:
+    """Each cell as a letter from a and a number from b."""
+    letters = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+    if len(a) > len(letters):
+        return render(a) + "\n\n" + render(b)
+    width = len(str(len(b)))
+    return "\n".join(" ".join(f"{letters[a[r][c] - 1]}{b[r][c]:<{width}}" for c in range(len(a))).rstrip()
+                     for r in range(len(a)))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Generate, complete and pair Latin squares")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    generate_parser = subparsers.add_parser("generate", help="a random Latin square")
+    generate_parser.add_argument("order", type=int)
+    add_seed_argument(generate_parser)
+    complete_parser = subparsers.add_parser("complete", help="fill in a partial Latin square")
+    complete_parser.add_argument("file", type=str)
+    complete_parser.add_argument("--count", action="store_true", help="count every completion")
+    complete_parser.add_argument("--stats", action="store_true", help="print the dancing links' updates")
+    graeco_parser = subparsers.add_parser("graeco", help="a pair of orthogonal Latin squares")
+    graeco_parser.add_argument("order", type=int)
+    graeco_parser.add_argument("--check", action="store_true", help="check that they are Latin and orthogonal")
+    orthogonal_parser = subparsers.add_parser("orthogonal", help="check whether two squares are orthogonal")
+    orthogonal_parser.add_argument("files", type=str, nargs=2)
+    args = parser.parse_args()
+
+    try:
+        if args.command == "generate":
+            if args.order < 1:
+                raise ValueError("the order must be at least 1")
+            print(render(generate(args.order, make_rng(args.seed))))
+        elif args.command == "graeco":
+            if args.order < 1:
+                raise ValueError("the order must be at least 1")
+            a, b = graeco_latin(args.order)
+            print(render_pair(a, b))
+            if args.check:
+                ok = is_latin(a) and is_latin(b) and are_orthogonal(a, b)
+                print("Latin and orthogonal" if ok else "NOT Latin and orthogonal")
+                if not ok:
+                    parser.exit(1)
+        elif args.command == "orthogonal":
+            a, b = (read(path) for path in args.files)
+            if len(a) != len(b):
+                raise ValueError(f"the squares are of orders {len(a)} and {len(b)}")
+            for path, square in zip(args.files, (a, b)):
+                if not is_latin(square):
+                    raise ValueError(f"{path} is not a (complete) Latin square")
+            if are_orthogonal(a, b):
+                print(render_pair(a, b))
+                print("Orthogonal")
+            else:
+                print("Not orthogonal")
+                parser.exit(1)
+        elif args.count:
+            dlx = to_dlx(read(args.file))
+            print(f"{dlx.count()} completions")
+        else:
+            found, dlx = complete(read(args.file), 2)
+            if not found:
+                print("No solution")
+            else:
+                print(render(found[0]))
+                print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+            if args.stats:
+                print(f"{dlx.updates} updates")
+            if not found:
+                parser.exit(1)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,215 @@
+"""
+# Lights Out
+
+A grid of lights, some on. Pressing a light toggles it and its
+orthogonal neighbours; the goal is to turn every light off. (`sat/
+puzzles.py` solves the same puzzle by reducing it to SAT.)
+
+
+## Linear Algebra
+
+Pressing twice undoes a press, and the order of presses does not
+matter, so a solution is a set of lights to press once, and the puzzle
+is linear over GF(2), the field of 0 and 1 with 1 + 1 = 0: with x the
+presses and b the lights that are on,
+
+    A x = b     where A's column for a light has a 1 at the light and at
+                each of its neighbours: the lights a press toggles
+
+Gaussian elimination solves it, each row a bit mask, so adding one row
+to another is an XOR. Not every board is solvable on every size: when
+A is singular, the lights that are on must be a combination of A's
+columns, and elimination finds a row that comes out 0 = 1 when they are
+not. Then the solutions, when there are any, are one solution plus any
+combination of the presses that change nothing (the null space of A,
+the "quiet patterns"): 5x5 has two, so four solutions, and `solve`
+tries every combination to find the fewest presses. `sizes` lists the
+quiet patterns of each square board, where 4x4, 5x5, 9x9 and 11x11
+stand out.
+
+`--torus` wraps the board around, so the edges are neighbours too.
+
+
+## Usage
+
+    python3 lights_out.py solve boards/cross.txt
+    python3 lights_out.py solve boards/unsolvable.txt
+    python3 lights_out.py solve "#####"
+    python3 lights_out.py solve boards/cross.txt --torus
+    python3 lights_out.py random 7x7 --seed 1
+    python3 lights_out.py sizes 12
+"""
+
+import argparse
+import os
+import sys
+from typing import List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid
+from rng.rng import add_seed_argument, make_rng
+
+
+# At most 2^MAX_NULLITY solutions are compared to find the fewest presses
+MAX_NULLITY: int = 20
+
+
+def toggles(board: Grid) -> List[int]:
+    """For each light, the lights a press on it toggles, as a bit mask over the board's positions."""
+    index = {position: i for i, position in enumerate(board.positions())}
+    masks = []
+    for position in board.positions():
+        mask = 1 << index[position]
+        for neighbour in board.neighbours(position):
+            mask ^= 1 << index[neighbour]      # on a narrow torus, a neighbour twice is no neighbour at all
+        masks.append(mask)
+    return masks
+
+
+def eliminate(columns: List[int], target: int) -> Tuple[Optional[int], List[int]]:
+    """
+    Solve A x = target over GF(2), where A is square and its column j is
+    columns[j] (a bit mask of rows). Returns one solution x (a bit mask of
+    columns), or None, and the null space of A, as bit masks of columns.
+    """
+    n = len(columns)
+    # Row i of the augmented matrix: the bits of A's row i, then target's bit i at position n
+    rows = [sum(1 << j for j, column in enumerate(columns) if column >> i & 1) | (target >> i & 1) << n
+            for i in range(n)]
+    pivots: List[int] = []      # the pivot column of each row of the echelon form, in order
+    rank = 0
+    for j in range(n):
+        pivot = next((i for i in range(rank, len(rows)) if rows[i] >> j & 1), None)
+        if pivot is None:
+            continue
+        rows[rank], rows[pivot] = rows[pivot], rows[rank]
+        for i in range(len(rows)):
+            if i != rank and rows[i] >> j & 1:
+                rows[i] ^= rows[rank]
+        pivots.append(j)
+        rank += 1
+    if any(row == 1 << n for row in rows[rank:]):
+        return None, []
+    solution = sum(1 << j for i, j in enumerate(pivots) if rows[i] >> n & 1)
+    free = [j for j in range(n) if j not in set(pivots)]
+    null_space = []
+    for f in free:
+        vector = 1 << f
+        for i, j in enumerate(pivots):
+            if rows[i] >> f & 1:
+                vector |= 1 << j
+        null_space.append(vector)
+    return solution, null_space
+
+
+def fewest(solution: int, null_space: List[int]) -> int:
+    """The solution plus the combination of the null space with the fewest presses."""
+    if len(null_space) > MAX_NULLITY:
+        raise ValueError(f"{2 ** len(null_space)} solutions are too many to compare")
+    best = solution
+    for k in range(1, 1 << len(null_space)):
+        candidate = solution
+        for i, vector in enumerate(null_space):
+            if k >> i & 1:
+                candidate ^= vector
+        if bin(candidate).count("1") < bin(best).count("1"):
+            best = candidate
+    return best
+
+
+def solve(board: Grid) -> Tuple[Optional[Grid], int]:
+    """The fewest presses that turn every light off, or None; and how many independent quiet patterns there are."""
+    positions = list(board.positions())
+    target = sum(1 << i for i, position in enumerate(positions) if board[position])
+    solution, null_space = eliminate(toggles(board), target)
+    if solution is None:
+        return None, len(eliminate(toggles(board), 0)[1])
+    best = fewest(solution, null_space)
+    presses = board.map(lambda _: False)
+    for i, position in enumerate(positions):
+        presses[position] = bool(best >> i & 1)
+    return presses, len(null_space)
+
+
+def press(board: Grid, presses: Grid) -> Grid:
+    result = board.copy()
+    for position, pressed in presses.items():
+        if pressed:
+            for toggled in [position] + board.neighbours(position):
+                result[toggled] = not result[toggled]
+    return result
+
+
+def nullity(size: int) -> int:
+    """How many independent quiet patterns the size x size board has."""
+    return len(eliminate(toggles(Grid.filled(size, size, False)), 0)[1])
+
+
+################################################################################
+###                                    CLI                                   ###
+################################################################################
+
+
+def read_board(argument: str, edges: str) -> Grid:
+    text = argument
+    if os.path.exists(argument):
+        with open(argument) as f:
+            text = "\n".join(line for line in f.read().split("\n") if not line.startswith("# "))
+    if set(text.strip()) - set("#.\n"):
+        raise ValueError("a board is rows of # (on) and . (off)")
+    return Grid.parse(text, cell=lambda c: c == "#", edges=edges)
+
+
+def report(board: Grid, parser: argparse.ArgumentParser):
+    presses, quiet = solve(board)
+    if presses is None:
+        print("No solution: these lights cannot all be turned off")
+        print(f"{quiet} independent quiet pattern{'s' * (quiet != 1)}, "
+              f"so only 1 in {2 ** quiet} boards of this size can be")
+        parser.exit(1)
+    assert not any(press(board, presses).cells[y][x] for x, y in board.positions())
+    count = presses.count(True)
+    print(presses.render(lambda p: "X" if p else "."))
+    print(f"{count} press{'es' * (count != 1)}, "
+          + (f"the fewest of {2 ** quiet} solutions" if quiet else "the only solution"))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Lights Out by linear algebra over GF(2)")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a board")
+    solve_parser.add_argument("board", type=str, help="a file, or the board itself: rows of # (on) and . (off)")
+    random_parser = subparsers.add_parser("random", help="solve a board with random lights on")
+    random_parser.add_argument("size", type=str, help="WxH")
+    add_seed_argument(random_parser)
+    for p in (solve_parser, random_parser):
+        p.add_argument("--torus", action="store_true", help="wrap the edges around")
+    sizes_parser = subparsers.add_parser("sizes", help="the quiet patterns of each square board")
+    sizes_parser.add_argument("up_to", type=int)
+    args = parser.parse_args()
+
+    if args.command == "sizes":
+        for size in range(1, args.up_to + 1):
+            quiet = nullity(size)
+            print(f"{size}x{size}: {quiet} independent quiet pattern{'s' * (quiet != 1)}"
+                  + (f", so 1 in {2 ** quiet} boards can be solved" if quiet else ", so every board can be solved"))
+        return
+    edges = "torus" if args.torus else "bounded"
+    try:
+        if args.command == "random":
+            width, x, height = args.size.partition("x")
+            if not x or not width.isdigit() or not height.isdigit() or int(width) < 1 or int(height) < 1:
+                raise ValueError("the size must be WxH, e.g. 5x5")
+            rng = make_rng(args.seed)
+            board = Grid([[rng.random() < 0.5 for _ in range(int(width))] for _ in range(int(height))], edges)
+            print(board.render(lambda on: "#" if on else "."))
+            print()
+        else:
+            board = read_board(args.board, edges)
+        report(board, parser)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,527 @@
+"""
+# Lisp
+
+A small Scheme-flavoured Lisp: integers (and exact fractions from `/`),
+strings, symbols, lists, and closures, with proper tail calls, so loops
+written as recursion run in constant space.
+
+    (define x 10)  (define (square n) (* n n))
+    (lambda (a b) (+ a b))
+    (if test then else)  (cond (test expr ...) ... (else expr ...))
+    (let ((a 1) (b 2)) body ...)  (let loop ((i 0)) ... (loop (+ i 1)))
+    (begin expr ...)  (set! x 11)
+    (and ...)  (or ...)  'expr  (quote expr)
+
+Only `#f` is false. The built-in procedures are
+
+    arithmetic  + - * / quotient remainder modulo abs min max expt
+    comparison  = < > <= >= not eq? equal?
+    lists       cons car cdr list length append list-ref null? pair? list?
+    types       number? symbol? string? procedure?
+    other       display newline apply error
+
+and the prelude (written in Lisp, in PRELUDE below) adds map, filter,
+fold-left, fold-right, reverse, range, and assoc. Lists are not built
+from cons cells, so `cons` onto something that is not a list is an
+error.
+
+
+## Programs
+
+`programs/` has some puzzles, each with its expected results in comments
+(`; test: (expression) => result`), which `--test` checks:
+
+    hanoi.lisp      the moves to solve the Towers of Hanoi
+    queens.lisp     the number of ways to place n queens
+    change.lisp     the number of ways to make change (from SICP)
+    collatz.lisp    the longest Collatz sequence below n
+    primes.lisp     a sieve of Eratosthenes
+
+
+## Usage
+
+    python3 lisp.py
+    python3 lisp.py programs/hanoi.lisp --eval "(hanoi 3 'a 'b 'c)"
+    python3 lisp.py programs/*.lisp --test
+"""
+
+import argparse
+import os
+import re
+import sys
+from fractions import Fraction
+from typing import Any, Callable, Dict, List, NamedTuple, Optional
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from repl.repl import Repl
+
+
+class Symbol(str):
+    pass
+
+
+class Environment:
+    def __init__(self, bindings: Dict[str, Any], outer: Optional["Environment"] = None):
+        self.bindings = bindings
+        self.outer = outer
+
+    def find(self, name: str) -> "Environment":
+        environment: Optional[Environment] = self
+        while environment is not None:
+            if name in environment.bindings:
+                return environment
+            environment = environment.outer
+        raise ValueError(f"'{name}' is not defined")
+
+
+class Procedure(NamedTuple):
+    name: str
+    parameters: List[Symbol]
+    rest: Optional[Symbol]     # for (lambda (a . rest) ...) or (lambda args ...)
+    body: List[Any]
+    environment: Environment
+
+
+PRELUDE: str = """
+(define (map f xs) (if (null? xs) '() (cons (f (car xs)) (map f (cdr xs)))))
+(define (filter keep? xs)
+  (cond ((null? xs) '())
+        ((keep? (car xs)) (cons (car xs) (filter keep? (cdr xs))))
+        (else (filter keep? (cdr xs)))))
+(define (fold-left f acc xs) (if (null? xs) acc (fold-left f (f acc (car xs)) (cdr xs))))
+(define (fold-right f acc xs) (if (null? xs) acc (f (car xs) (fold-right f acc (cdr xs)))))
+(define (reverse xs) (fold-left (lambda (acc x) (cons x acc)) '() xs))
+(define (range a b) (if (>= a b) '() (cons a (range (+ a 1) b))))
+(define (assoc key pairs)
+  (cond ((null? pairs) #f)
+        ((equal? key (car (car pairs))) (car pairs))
+        (else (assoc key (cdr pairs)))))
+"""
+
+
+################################################################################
+###   READER AND PRINTER   ###
+################################################################################
+
+
+TOKEN = re.compile(r"""\s*(?:;[^\n]*|([()']|"(?:\\.|[^"\\])*"|[^\s()';]+))""")
+
+
+def tokenize(text: str) -> List[str]:
+    return [token for token in TOKEN.findall(text) if token]
+
+
+def read_all(text: str) -> List[Any]:
+    tokens = tokenize(text)
+    tokens.reverse()
+    expressions: List[Any] = []
+    while tokens:
+        expressions.append(read(tokens))
+    return expressions
+
+
+def read(tokens: List[str]) -> Any:
+    """Read one expression from the (reversed) tokens."""
+    if not tokens:
+        raise ValueError("unexpected end of input")
+    token = tokens.pop()
+    if token == "(":
+        items: List[Any] = []
+        while tokens and tokens[-1] != ")":
+            items.append(read(tokens))
+        if not tokens:
+            raise ValueError("missing ')'")
+        tokens.pop()
+        return items
+    if token == ")":
+        raise ValueError("unexpected ')'")
+    if token == "'":
+        return [Symbol("quote"), read(tokens)]
+    if token.startswith('"'):
+        return token[1:-1].encode().decode("unicode_escape")
+    if token == "#t":
+        return True
+    if token == "#f":
+        return False
+    if re.fullmatch(r"-?\d+", token):
+        return int(token)
+    if re.fullmatch(r"-?\d+/\d+", token):
+        return Fraction(token)
+    return Symbol(token.lower())
+
+
+def to_string(value: Any, quote_strings: bool = True) -> str:
+    if value is True:
+        return "#t"
+    if value is False:
+        return "#f"
+    if isinstance(value, Symbol):
+        return value
+    if isinstance(value, str):
+        return '"' + value.replace("\\", "\\\\").replace('"', '\\"') + '"' if quote_strings else value
+    if isinstance(value, list):
+        if len(value) == 2 and value[0] == "quote":
+            return "'" + to_string(value[1], quote_strings)
+        return "(" + " ".join(to_string(v, quote_strings) for v in value) + ")"
+    if isinstance(value, Procedure):
+        return f"#<procedure {value.name}>"
+    if callable(value):
+        return "#<builtin>"
+    return str(value)
+
+
+################################################################################
+###   EVALUATOR   ###
+################################################################################
+
+
+def truthy(value: Any) -> bool:
+    return value is not False
+
+
+def make_procedure(name: str, parameters: Any, body: List[Any], environment: Environment) -> Procedure:
+    if isinstance(parameters, Symbol):
+        return Procedure(name, [], parameters, body, environment)
+    if not isinstance(parameters, list) or not all(isinstance(p, Symbol) for p in parameters):
+        raise ValueError(f"bad parameter list {to_string(parameters)}")
+    rest = None
+    if "." in parameters:
+        if parameters.index(".") != len(parameters) - 2:
+            raise ValueError(f"bad parameter list {to_string(parameters)}")
+        rest = parameters[-1]
+        parameters = parameters[:-2]
+    if not body:
+        raise ValueError(f"{name} has no body")
+    return Procedure(name, parameters, rest, body, environment)
+
+
+def bind(procedure: Procedure, arguments: List[Any]) -> Environment:
+    count = len(procedure.parameters)
+    if len(arguments) < count or procedure.rest is None and len(arguments) > count:
+        raise ValueError(f"{procedure.name} takes {count}{'+' if procedure.rest else ''} arguments, "
+                         f"not {len(arguments)}")
+    bindings = dict(zip(procedure.parameters, arguments))
+    if procedure.rest is not None:
+        bindings[procedure.rest] = arguments[count:]
+    return Environment(bindings, procedure.environment)
+
+
+def evaluate(expression: Any, environment: Environment) -> Any:
+    # The loop replaces the recursive call for every expression in tail position
+    while True:
+        if isinstance(expression, Symbol):
+            return environment.find(expression).bindings[expression]
+        if not isinstance(expression, list):
+            return expression
+        if not expression:
+            raise ValueError("cannot evaluate ()")
+        head, rest = expression[0], expression[1:]
+
+        if head == "quote":
+            return rest[0]
+        if head == "if":
+            if len(rest) not in (2, 3):
+                raise ValueError("if takes a test, a then, and an optional else")
+            if truthy(evaluate(rest[0], environment)):
+                expression = rest[1]
+            elif len(rest) == 3:
+                expression = rest[2]
+            else:
+                return False
+            continue
+        if head == "cond":
+            for clause in rest:
+                if clause[0] == "else" or truthy(evaluate(clause[0], environment)):
+                    break
+            else:
+                return False
+            for body_expression in clause[1:-1]:
+                evaluate(body_expression, environment)
+            expression = clause[-1]
+            continue
+        if head == "define":
+            if isinstance(rest[0], list):
+                name = rest[0][0]
+                environment.bindings[name] = make_procedure(name, rest[0][1:], rest[1:], environment)
+            else:
+                environment.bindings[rest[0]] = evaluate(rest[1], environment)
+            return Symbol(rest[0][0] if isinstance(rest[0], list) else rest[0])
+        if head == "set!":
+            environment.find(rest[0]).bindings[rest[0]] = evaluate(rest[1], environment)
+            return False
+        if head == "lambda":
+            return make_procedure("lambda", rest[0], rest[1:], environment)
+        if head == "let":
+            loop_name = None
+            if isinstance(rest[0], Symbol):   # a named let, which can call itself to loop
+                loop_name, rest = rest[0], rest[1:]
+            names = [binding[0] for binding in rest[0]]
+            values = [evaluate(binding[1], environment) for binding in rest[0]]
+            if loop_name is not None:
+                environment = Environment({}, environment)
+                environment.bindings[loop_name] = make_procedure(loop_name, names, rest[1:], environment)
+            environment = Environment(dict(zip(names, values)), environment)
+            for body_expression in rest[1:-1]:
+                evaluate(body_expression, environment)
+            expression = rest[-1]
+            continue
+        if head == "begin":
+            if not rest:
+                return False
+            for body_expression in rest[:-1]:
+                evaluate(body_expression, environment)
+            expression = rest[-1]
+            continue
+        if head in ("and", "or"):
+            if not rest:
+                return head == "and"
+            for body_expression in rest[:-1]:
+                value = evaluate(body_expression, environment)
+                if truthy(value) == (head == "or"):
+                    return value
+            expression = rest[-1]
+            continue
+
+        procedure = evaluate(head, environment)
+        arguments = [evaluate(argument, environment) for argument in rest]
+        if isinstance(procedure, Procedure):
+            environment = bind(procedure, arguments)
+            for body_expression in procedure.body[:-1]:
+                evaluate(body_expression, environment)
+            expression = procedure.body[-1]
+            continue
+        return apply(procedure, arguments)
+
+
+def apply(procedure: Any, arguments: List[Any]) -> Any:
+    if isinstance(procedure, Procedure):
+        environment = bind(procedure, arguments)
+        for body_expression in procedure.body[:-1]:
+            evaluate(body_expression, environment)
+        return evaluate(procedure.body[-1], environment)
+    if not callable(procedure):
+        raise ValueError(f"{to_string(procedure)} is not a procedure")
+    try:
+        return procedure(*arguments)
+    except TypeError:
+        raise ValueError(f"wrong number or type of arguments: {to_string(arguments)}") from None
+
+
+################################################################################
+###   BUILT-IN PROCEDURES   ###
+################################################################################
+
+
+def number(value: Any) -> Any:
+    if isinstance(value, bool) or not isinstance(value, (int, Fraction)):
+        raise ValueError(f"{to_string(value)} is not a number")
+    return value
+
+
+def divide(a: Any, *rest: Any) -> Any:
+    if not rest:
+        a, rest = 1, (a,)
+    result = Fraction(number(a))
+    for b in rest:
+        if number(b) == 0:
+            raise ValueError("division by zero")
+        result /= b
+    return result.numerator if result.denominator == 1 else result
+
+
+def product(*values: Any) -> Any:
+    result = 1
+    for value in values:
+        result *= number(value)
+    return result
+
+
+def integer_division(operation: Callable[[int, int], int]) -> Callable[[int, int], int]:
+    def run(a: int, b: int) -> int:
+        if number(b) == 0:
+            raise ValueError("division by zero")
+        return operation(number(a), b)
+    return run
+
+
+def truncate(a: int, b: int) -> int:
+    """a / b rounded towards 0, as `quotient` and `remainder` use."""
+    return abs(a) // abs(b) * (1 if (a < 0) == (b < 0) else -1)
+
+
+def chain(compare: Callable[[Any, Any], bool]) -> Callable[..., bool]:
+    def run(*values: Any) -> bool:
+        values = tuple(number(v) for v in values)
+        return all(compare(a, b) for a, b in zip(values, values[1:]))
+    return run
+
+
+def cons(head: Any, tail: Any) -> List[Any]:
+    if not isinstance(tail, list):
+        raise ValueError(f"cannot cons onto {to_string(tail)}, which is not a list")
+    return [head] + tail
+
+
+def first(name: str, index: int) -> Callable[[Any], Any]:
+    def run(pair: Any) -> Any:
+        if not isinstance(pair, list) or not pair:
+            raise ValueError(f"{name} of {to_string(pair)}")
+        return pair[0] if index == 0 else pair[1:]
+    return run
+
+
+def equal(a: Any, b: Any) -> bool:
+    return type(a) is type(b) and a == b if not isinstance(a, (int, Fraction)) \
+        else isinstance(b, (int, Fraction)) and not isinstance(b, bool) and a == b
+
+
+def display(value: Any) -> bool:
+    print(to_string(value, quote_strings=False), end="")
+    return False
+
+
+def lisp_error(*values: Any) -> None:
+    raise ValueError(" ".join(to_string(v, quote_strings=False) for v in values))
+
+
+def builtins() -> Dict[str, Any]:
+    return {
+        "+": lambda *values: sum(number(v) for v in values),
+        "-": lambda a, *rest: -number(a) if not rest else number(a) - sum(number(v) for v in rest),
+        "*": product,
+        "/": divide,
+        "quotient": integer_division(truncate),
+        "remainder": integer_division(lambda a, b: a - b * truncate(a, b)),
+        "modulo": integer_division(lambda a, b: a % b),
+        "abs": lambda a: abs(number(a)),
+        "min": lambda *values: min(number(v) for v in values),
+        "max": lambda *values: max(number(v) for v in values),
+        "expt": lambda a, b: number(a) ** number(b),
+        "=": chain(lambda a, b: a == b),
+        "<": chain(lambda a, b: a < b),
+        ">": chain(lambda a, b: a > b),
+        "<=": chain(lambda a, b: a <= b),
+        ">=": chain(lambda a, b: a >= b),
+        "not": lambda value: value is False,
+        "eq?": lambda a, b: a is b or not isinstance(a, list) and equal(a, b),
+        "equal?": lambda a, b: equal(a, b),
+        "cons": cons,
+        "car": first("car", 0),
+        "cdr": first("cdr", 1),
+        "list": lambda *values: list(values),
+        "length": lambda values: len(values),
+        "append": lambda *lists: [item for values in lists for item in values],
+        "list-ref": lambda values, index: values[index],
+        "null?": lambda value: value == [],
+        "pair?": lambda value: isinstance(value, list) and value != [],
+        "list?": lambda value: isinstance(value, list),
+        "number?": lambda value: isinstance(value, (int, Fraction)) and not isinstance(value, bool),
+        "symbol?": lambda value: isinstance(value, Symbol),
+        "string?": lambda value: isinstance(value, str) and not isinstance(value, Symbol),
+        "procedure?": lambda value: isinstance(value, Procedure) or callable(value),
+        "display": display,
+        "newline": lambda: display("\n"),
+        "apply": lambda procedure, arguments: apply(procedure, arguments),
+        "error": lisp_error,
+    }
+
+
+################################################################################
+###   INTERPRETER   ###
+################################################################################
+
+
+class Interpreter:
+    def __init__(self):
+        self.environment = Environment(builtins())
+        self.pending = ""
+        self.run(PRELUDE)
+
+    def run(self, text: str) -> Any:
+        result = None
+        for expression in read_all(text):
+            result = self.evaluate(expression)
+        return result
+
+    def evaluate(self, expression: Any) -> Any:
+        try:
+            return evaluate(expression, self.environment)
+        except (IndexError, TypeError, AttributeError):
+            raise ValueError(f"malformed expression {to_string(expression)}") from None
+
+    def feed(self, line: str):
+        """Evaluate a REPL line, waiting for more lines until the brackets balance."""
+        self.pending += line + "\n"
+        tokens = tokenize(self.pending)
+        if tokens.count("(") > tokens.count(")"):
+            return
+        text, self.pending = self.pending, ""
+        for expression in read_all(text):
+            print(to_string(self.evaluate(expression)))
+
+    def load(self, path: str):
+        with open(path) as f:
+            self.run(f.read())
+
+
+def run_tests(paths: List[str]) -> bool:
+    passed = True
+    for path in paths:
+        interpreter = Interpreter()
+        with open(path) as f:
+            text = f.read()
+        interpreter.run(text)
+        for match in re.finditer(r"^;+\s*test:\s*(.*?)\s*=>\s*(.*?)\s*$", text, re.MULTILINE):
+            try:
+                result = to_string(interpreter.run(match.group(1)))
+            except ValueError as e:
+                result = f"error: {e}"
+            ok = result == match.group(2)
+            passed &= ok
+            print(f"{'ok' if ok else 'FAIL':<6} {path}: {match.group(1)} => {result}"
+                  + ("" if ok else f" (expected {match.group(2)})"))
+    return passed
+
+
+def start_repl(interpreter: Interpreter):
+    def evaluate_line(line: str):
+        try:
+            interpreter.feed(line)
+        except ValueError:
+            interpreter.pending = ""
+            raise
+
+    repl = Repl("lisp> ", evaluate_line)
+    repl.add_command("load", interpreter.load, "run a file")
+    repl.add_command("env", lambda _: print(" ".join(sorted(interpreter.environment.bindings))),
+                     "list what is defined")
+    print("Lisp. Type :help for the commands.")
+    repl.run()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="A small Lisp")
+    parser.add_argument("files", type=str, nargs="*", help="source files to run in order")
+    parser.add_argument("--eval", type=str, default=None, help="evaluate this and print the result")
+    parser.add_argument("--test", action="store_true", help="check each file's `; test:` cases")
+    parser.add_argument("--interactive", action="store_true", help="start the REPL after the files")
+    args = parser.parse_args()
+
+    sys.setrecursionlimit(max(sys.getrecursionlimit(), 20_000))
+    try:
+        if args.test:
+            sys.exit(0 if run_tests(args.files) else 1)
+        interpreter = Interpreter()
+        for path in args.files:
+            interpreter.load(path)
+        if args.eval is not None:
+            print(to_string(interpreter.run(args.eval)))
+    except (ValueError, RecursionError) as e:
+        message = "too deeply nested" if isinstance(e, RecursionError) else str(e)
+        parser.exit(1, f"error: {message}\n")
+    if args.interactive or not args.files and args.eval is None:
+        start_repl(interpreter)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,11 @@
+; The number of ways to make change for an amount, from SICP 1.2.2.
+; test: (count-change 100) => 292
+; test: (count-change 11) => 4
+
+(define (count-change amount) (count amount '(50 25 10 5 1)))
+
+(define (count amount coins)
+  (cond ((= amount 0) 1)
+        ((or (< amount 0) (null? coins)) 0)
+        (else (+ (count amount (cdr coins))
+                 (count (- amount (car coins)) coins)))))
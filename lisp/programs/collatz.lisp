@@ -0,0 +1,20 @@
+; The number below n with the longest Collatz sequence (Project Euler 14).
+; The loops are tail calls, so they run in constant space.
+; test: (collatz-length 27) => 112
+; test: (longest-collatz 1000) => (871 179)
+
+(define (collatz-length n)
+  (let loop ((n n) (length 1))
+    (cond ((= n 1) length)
+          ((= (modulo n 2) 0) (loop (quotient n 2) (+ length 1)))
+          (else (loop (+ (* 3 n) 1) (+ length 1))))))
+
+(define (longest-collatz limit)
+  (define (search n best best-length)
+    (if (>= n limit)
+        (list best best-length)
+        (let ((length (collatz-length n)))
+          (if (> length best-length)
+              (search (+ n 1) n length)
+              (search (+ n 1) best best-length)))))
+  (search 1 1 1))
@@ -0,0 +1,10 @@
+; The Towers of Hanoi: the moves that take n discs from one peg to another.
+; test: (length (hanoi 10 'a 'b 'c)) => 1023
+; test: (hanoi 2 'a 'b 'c) => ((a c) (a b) (c b))
+
+(define (hanoi n from to spare)
+  (if (= n 0)
+      '()
+      (append (hanoi (- n 1) from spare to)
+              (list (list from to))
+              (hanoi (- n 1) spare to from))))
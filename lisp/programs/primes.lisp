@@ -0,0 +1,11 @@
+; The primes below n, by striking out the multiples of each prime in turn.
+; test: (primes 30) => (2 3 5 7 11 13 17 19 23 29)
+; test: (length (primes 1000)) => 168
+
+(define (primes n)
+  (define (sieve candidates)
+    (if (null? candidates)
+        '()
+        (let ((p (car candidates)))
+          (cons p (sieve (filter (lambda (x) (not (= (modulo x p) 0))) (cdr candidates)))))))
+  (sieve (range 2 n)))
@@ -0,0 +1,23 @@
+; The number of ways to place n queens on an n by n board, none attacking
+; another. A placement is the list of the columns of the queens so far.
+; test: (queens 4) => 2
+; test: (queens 6) => 4
+; test: (queens 8) => 92
+
+(define (safe? column placed)
+  (define (check rest distance)
+    (cond ((null? rest) #t)
+          ((= (car rest) column) #f)
+          ((= (abs (- (car rest) column)) distance) #f)
+          (else (check (cdr rest) (+ distance 1)))))
+  (check placed 1))
+
+(define (queens n)
+  (define (place row placed)
+    (if (= row n)
+        1
+        (fold-left + 0
+                   (map (lambda (column)
+                          (if (safe? column placed) (place (+ row 1) (cons column placed)) 0))
+                        (range 0 n)))))
+  (place 0 '()))
@@ -0,0 +1,286 @@
+"""
+# L-Systems
+
+Lindenmayer systems: a string, the axiom, and rules that rewrite every
+symbol of it at once, over and over. Read as instructions to a turtle,
+the strings draw fractals, and with brackets, plants.
+
+    axiom   F--F--F
+    F ->    F+F--F+F        the Koch snowflake, after 1 rewriting:
+                            F+F--F+F--F+F--F+F--F+F--F+F--F+F
+
+
+## The Turtle
+
+The turtle starts at the origin facing right (or --heading degrees
+anticlockwise from it), and reads each symbol:
+
+    F G ...     the drawing symbols (F and G, unless the system says):
+                step forward, drawing a line
+    f           step forward without drawing
+    + -         turn left or right by the angle
+    |           turn round
+    [ ]         save the turtle's place and heading, and go back to it
+    anything    nothing: symbols like X and Y are there for the rules
+    else
+
+The drawing is sized to fit: a terminal drawing is --width characters
+wide, in Braille, each character a 2x4 grid of dots; an SVG has --step
+units to each step, with a margin around it.
+
+
+## The Format
+
+One setting or rule a line; lines starting with # are comments.
+
+    # The Koch snowflake
+    axiom: F--F--F
+    angle: 60
+    F -> F+F--F+F
+
+Besides axiom and angle, `draw:` lists the drawing symbols, and
+`iterations:` the usual number of rewritings. The classics are built in
+(`list` shows them): koch, dragon, levy, sierpinski, arrowhead, hilbert,
+gosper, and plant.
+
+
+## Usage
+
+    python3 lsystem.py list
+    python3 lsystem.py expand koch --iterations 1
+    python3 lsystem.py draw koch --iterations 3
+    python3 lsystem.py draw dragon --iterations 10 --width 60
+    python3 lsystem.py draw plant --heading 90 --width 50
+    python3 lsystem.py draw systems/bush.txt --heading 90
+    python3 lsystem.py draw hilbert --iterations 5 --svg hilbert.svg --step 8
+    python3 lsystem.py draw koch --angle 85 --iterations 4
+"""
+
+import argparse
+import math
+import os
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+
+Point = Tuple[float, float]
+Segment = Tuple[Point, Point]
+MAX_LENGTH: int = 2_000_000
+BRAILLE_BASE: int = 0x2800
+# The bit of each dot of a Braille character, by its column (0 to 1) and row (0 to 3)
+BRAILLE_DOTS: List[List[int]] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]]
+SVG_MARGIN: float = 10.0
+
+
+class System(NamedTuple):
+    axiom: str
+    rules: Dict[str, str]
+    angle: float
+    draw: str = "FG"
+    iterations: int = 4
+
+
+CLASSICS: Dict[str, System] = {
+    "koch": System("F--F--F", {"F": "F+F--F+F"}, 60, iterations=3),
+    "dragon": System("FX", {"X": "X+YF+", "Y": "-FX-Y"}, 90, iterations=10),
+    "levy": System("F", {"F": "+F--F+"}, 45, iterations=10),
+    "sierpinski": System("F-G-G", {"F": "F-G+F+G-F", "G": "GG"}, 120, iterations=5),
+    "arrowhead": System("A", {"A": "B-A-B", "B": "A+B+A"}, 60, draw="AB", iterations=6),
+    "hilbert": System("A", {"A": "+BF-AFA-FB+", "B": "-AF+BFB+FA-"}, 90, iterations=4),
+    "gosper": System("A", {"A": "A-B--B+A++AA+B-", "B": "+A-BB--B-A++A+B"}, 60, draw="AB", iterations=3),
+    "plant": System("X", {"X": "F+[[X]-X]-F[-FX]+X", "F": "FF"}, 25, iterations=5),
+}
+
+
+def expand(system: System, iterations: int, limit: int = MAX_LENGTH) -> str:
+    """The axiom rewritten the given number of times; a ValueError if it grows longer than the limit."""
+    text = system.axiom
+    for i in range(1, iterations + 1):
+        text = "".join(system.rules.get(symbol, symbol) for symbol in text)
+        if len(text) > limit:
+            raise ValueError(f"after {i} rewritings the string is {len(text):,} symbols, more than {limit:,}")
+    return text
+
+
+def walk(system: System, text: str, heading: float = 0.0) -> List[Segment]:
+    """The lines the turtle draws, one step long each."""
+    x, y, angle = 0.0, 0.0, heading
+    stack: List[Tuple[float, float, float]] = []
+    segments: List[Segment] = []
+    for symbol in text:
+        if symbol in system.draw or symbol == "f":
+            nx, ny = x + math.cos(math.radians(angle)), y + math.sin(math.radians(angle))
+            if symbol != "f":
+                segments.append(((x, y), (nx, ny)))
+            x, y = nx, ny
+        elif symbol == "+":
+            angle += system.angle
+        elif symbol == "-":
+            angle -= system.angle
+        elif symbol == "|":
+            angle += 180
+        elif symbol == "[":
+            stack.append((x, y, angle))
+        elif symbol == "]":
+            if not stack:
+                raise ValueError("a ] with no [ before it")
+            x, y, angle = stack.pop()
+    return segments
+
+
+def bounds(segments: List[Segment]) -> Tuple[float, float, float, float]:
+    xs = [x for segment in segments for x, _ in segment]
+    ys = [y for segment in segments for _, y in segment]
+    return min(xs), min(ys), max(xs), max(ys)
+
+
+################################################################################
+###                                 Drawing                                  ###
+################################################################################
+
+
+def render_terminal(segments: List[Segment], width: int) -> str:
+    """The drawing in Braille, width characters wide, with y going up."""
+    left, bottom, right, top = bounds(segments)
+    dots_wide = 2 * width
+    scale = (dots_wide - 1) / max(right - left, top - bottom, 1e-9)
+    dots_high = int((top - bottom) * scale) + 1
+    height = (dots_high + 3) // 4
+    cells = [[0] * width for _ in range(height)]
+
+    def plot(px: int, py: int):
+        row = dots_high - 1 - py
+        cells[row // 4][px // 2] |= BRAILLE_DOTS[px % 2][row % 4]
+
+    for (x1, y1), (x2, y2) in segments:
+        a = (round((x1 - left) * scale), round((y1 - bottom) * scale))
+        b = (round((x2 - left) * scale), round((y2 - bottom) * scale))
+        steps = max(abs(b[0] - a[0]), abs(b[1] - a[1]), 1)
+        for i in range(steps + 1):
+            plot(a[0] + round((b[0] - a[0]) * i / steps), a[1] + round((b[1] - a[1]) * i / steps))
+    return "\n".join("".join(chr(BRAILLE_BASE + cell) for cell in row).rstrip(chr(BRAILLE_BASE)) for row in cells)
+
+
+def render_svg(segments: List[Segment], step: float) -> str:
+    """The drawing as an SVG path, step units to a step, y going up as on paper."""
+    left, bottom, right, top = bounds(segments)
+    width, height = (right - left) * step + 2 * SVG_MARGIN, (top - bottom) * step + 2 * SVG_MARGIN
+
+    def place(point: Point) -> str:
+        return f"{(point[0] - left) * step + SVG_MARGIN:.2f},{(top - point[1]) * step + SVG_MARGIN:.2f}"
+
+    path = []
+    at: Optional[Point] = None
+    for start, end in segments:
+        if at is None or math.dist(at, start) > 1e-6:
+            path.append(f"M{place(start)}")
+        path.append(f"L{place(end)}")
+        at = end
+    return (f'<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0f}" height="{height:.0f}" '
+            f'viewBox="0 0 {width:.2f} {height:.2f}">\n'
+            f'<rect width="100%" height="100%" fill="white"/>\n'
+            f'<path d="{" ".join(path)}" fill="none" stroke="black" stroke-width="1" stroke-linejoin="round"/>\n'
+            f'</svg>\n')
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> System:
+    axiom, angle, draw, iterations = None, None, "FG", 4
+    rules: Dict[str, str] = {}
+    for number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        if "->" in line:
+            symbol, _, replacement = line.partition("->")
+            symbol = symbol.strip()
+            if len(symbol) != 1:
+                raise ValueError(f"line {number}: a rule rewrites one symbol, not {symbol!r}")
+            if symbol in rules:
+                raise ValueError(f"line {number}: a second rule for {symbol}")
+            rules[symbol] = replacement.strip()
+            continue
+        name, colon, value = line.partition(":")
+        name, value = name.strip(), value.strip()
+        if not colon or name not in ("axiom", "angle", "draw", "iterations"):
+            raise ValueError(f"line {number}: expected a rule (F -> F+F) or axiom:, angle:, draw: or iterations:")
+        try:
+            if name == "axiom":
+                axiom = value
+            elif name == "angle":
+                angle = float(value)
+            elif name == "draw":
+                draw = value.replace(" ", "")
+            else:
+                iterations = int(value)
+        except ValueError:
+            raise ValueError(f"line {number}: {value!r} is not a number") from None
+    if not axiom:
+        raise ValueError("there is no axiom")
+    if angle is None:
+        raise ValueError("there is no angle")
+    return System(axiom, rules, angle, draw, iterations)
+
+
+def load(name: str) -> System:
+    if name in CLASSICS:
+        return CLASSICS[name]
+    if not os.path.exists(name):
+        raise ValueError(f"{name} is neither a file nor one of {', '.join(CLASSICS)}")
+    with open(name) as f:
+        return parse(f.read())
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Rewrite L-systems, and draw them with a turtle")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("system", type=str, help="a classic's name, or a file in the format above")
+    common.add_argument("--iterations", type=int, default=None, help="the rewritings (default: the system's own)")
+    common.add_argument("--angle", type=float, default=None, help="the turning angle, in degrees, instead")
+    common.add_argument("--max-length", type=int, default=MAX_LENGTH, help="the longest the string may grow")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    subparsers.add_parser("list", help="list the classic systems")
+    subparsers.add_parser("expand", parents=[common], help="print the rewritten string")
+    draw_parser = subparsers.add_parser("draw", parents=[common], help="draw it in the terminal, or as an SVG")
+    draw_parser.add_argument("--heading", type=float, default=0.0, help="the turtle's first heading, in degrees")
+    draw_parser.add_argument("--width", type=int, default=79, help="the terminal drawing's width, in characters")
+    draw_parser.add_argument("--svg", type=str, default=None, help="write an SVG here instead")
+    draw_parser.add_argument("--step", type=float, default=5.0, help="the length of a step in the SVG")
+    args = parser.parse_args()
+
+    if args.command == "list":
+        for name, system in CLASSICS.items():
+            rules = ", ".join(f"{symbol} -> {replacement}" for symbol, replacement in system.rules.items())
+            print(f"{name:<12}{system.axiom}; {rules}; angle {system.angle:g}")
+        return
+    try:
+        system = load(args.system)
+        if args.angle is not None:
+            system = system._replace(angle=args.angle)
+        iterations = system.iterations if args.iterations is None else args.iterations
+        if iterations < 0:
+            raise ValueError("the iterations cannot be negative")
+        text = expand(system, iterations, args.max_length)
+        if args.command == "expand":
+            print(text)
+            return
+        segments = walk(system, text, args.heading)
+        if not segments:
+            raise ValueError("the turtle draws nothing")
+        if args.svg is not None:
+            with open(args.svg, "w") as f:
+                f.write(render_svg(segments, args.step))
+            print(f"{len(segments):,} lines written to {args.svg}")
+        else:
+            if args.width < 1:
+                raise ValueError("the width must be at least 1")
+            print(render_terminal(segments, args.width))
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
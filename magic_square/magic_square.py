@@ -0,0 +1,266 @@
+"""
+# Magic Squares
+
+The numbers 1 to n^2 in an n by n square, so that every row, every
+column and both diagonals add up to the same number, the magic
+constant n (n^2 + 1) / 2: 15 for n = 3, 34 for n = 4.
+
+
+## Constructions
+
+There is a magic square of every order but 2, and a way to write one
+down for each kind of order:
+
+    odd             the Siamese method (de la Loubère's): 1 in the middle
+                    of the top row, then each number up and to the right
+                    of the one before, wrapping around the edges, or
+                    straight down if that square is taken
+    doubly even     (a multiple of 4) 1 to n^2 in order, then every
+                    number on one of the diagonals of each 4x4 block
+                    replaced by n^2 + 1 minus it
+    singly even     (2 more than a multiple of 4, n = 2m) Strachey's
+                    method: four Siamese squares of order m, with m^2,
+                    2 m^2 and 3 m^2 added to three of them, and some
+                    columns swapped between the quarters so that the
+                    rows and diagonals come right
+
+
+## Checking and Searching
+
+`verify` reports whether the square is magic, or only semi-magic (the
+rows and columns add up, the diagonals not), with the lines that do not
+add up, and whether it is normal (the numbers 1 to n^2). `search`
+completes a square with some cells given, by the shared CSP solver: a
+variable for every cell, all different, and each line adding up to the
+magic constant, which the solver checks as soon as all but one of its
+cells are known.
+
+Squares are written one row a line, with the numbers separated by
+spaces; `.` is an empty cell. Lines starting with # are comments.
+
+
+## Usage
+
+    python3 magic_square.py generate 3
+    python3 magic_square.py generate 6
+    python3 magic_square.py verify squares/durer.txt
+    python3 magic_square.py verify squares/not_magic.txt
+    python3 magic_square.py search squares/given.txt --stats
+    python3 magic_square.py check --up-to 30
+"""
+
+import argparse
+import os
+import sys
+from typing import List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.csp import CSP, Statistics, solutions
+
+
+Square = List[List[int]]
+Partial = List[List[Optional[int]]]
+
+
+def magic_constant(n: int) -> int:
+    return n * (n * n + 1) // 2
+
+
+################################################################################
+###                              Constructions                               ###
+################################################################################
+
+
+def siamese(n: int) -> Square:
+    square = [[0] * n for _ in range(n)]
+    row, column = 0, n // 2
+    for number in range(1, n * n + 1):
+        square[row][column] = number
+        up, right = (row - 1) % n, (column + 1) % n
+        if square[up][right]:
+            row = (row + 1) % n
+        else:
+            row, column = up, right
+    return square
+
+
+def doubly_even(n: int) -> Square:
+    square = [[row * n + column + 1 for column in range(n)] for row in range(n)]
+    for row in range(n):
+        for column in range(n):
+            if row % 4 == column % 4 or row % 4 + column % 4 == 3:
+                square[row][column] = n * n + 1 - square[row][column]
+    return square
+
+
+def singly_even(n: int) -> Square:
+    m, k = n // 2, (n - 2) // 4
+    quarter = siamese(m)
+    square = [[0] * n for _ in range(n)]
+    # top left, bottom right, top right, bottom left
+    for (top, left), offset in zip([(0, 0), (m, m), (0, m), (m, 0)], range(4)):
+        for row in range(m):
+            for column in range(m):
+                square[top + row][left + column] = quarter[row][column] + offset * m * m
+    for row in range(m):
+        # The leftmost k columns of the top left and bottom left quarters trade places, but in the middle
+        # row, the k columns starting one in
+        columns = range(1, k + 1) if row == m // 2 else range(k)
+        # and so do the rightmost k - 1 columns of the right quarters
+        for column in [*columns, *range(n - k + 1, n)]:
+            square[row][column], square[row + m][column] = square[row + m][column], square[row][column]
+    return square
+
+
+def generate(n: int) -> Square:
+    if n < 1 or n == 2:
+        raise ValueError(f"there is no magic square of order {n}")
+    if n % 2:
+        return siamese(n)
+    return doubly_even(n) if n % 4 == 0 else singly_even(n)
+
+
+################################################################################
+###                          Checking and Searching                          ###
+################################################################################
+
+
+def lines(n: int) -> List[List[Tuple[int, int]]]:
+    """The cells of every row, column and diagonal, in the order of line_names."""
+    return ([[(r, c) for c in range(n)] for r in range(n)] + [[(r, c) for r in range(n)] for c in range(n)]
+            + [[(i, i) for i in range(n)], [(i, n - 1 - i) for i in range(n)]])
+
+
+def line_names(n: int) -> List[str]:
+    return [f"row {r + 1}" for r in range(n)] + [f"column {c + 1}" for c in range(n)] + ["diagonal", "antidiagonal"]
+
+
+def describe(square: Square) -> List[str]:
+    """What kind of square it is, and which lines do not add up to what the first row does."""
+    n = len(square)
+    sums = [sum(square[r][c] for r, c in line) for line in lines(n)]
+    wrong = [f"{name} adds up to {total}" for name, total in zip(line_names(n), sums) if total != sums[0]]
+    if not wrong:
+        kind = f"magic: every line adds up to {sums[0]}"
+    elif all(total == sums[0] for total in sums[:2 * n]):
+        kind = f"semi-magic: the rows and columns add up to {sums[0]}, but not the diagonals"
+    else:
+        kind = f"not magic: row 1 adds up to {sums[0]}, but"
+    normal = sorted(number for row in square for number in row) == list(range(1, n * n + 1))
+    return [kind] + [f"  {line}" for line in wrong] + [f"{'' if normal else 'not '}normal: the numbers 1 to {n * n}"]
+
+
+def is_magic(square: Square) -> bool:
+    n = len(square)
+    return sorted(number for row in square for number in row) == list(range(1, n * n + 1)) \
+        and all(sum(square[r][c] for r, c in line) == magic_constant(n) for line in lines(n))
+
+
+def to_csp(given: Partial) -> CSP:
+    n = len(given)
+    csp = CSP()
+    cells = [(r, c) for r in range(n) for c in range(n)]
+    for r, c in cells:
+        csp.add_variable((r, c), [given[r][c]] if given[r][c] is not None else range(1, n * n + 1))
+    csp.all_different(cells)
+    for line in lines(n):
+        csp.add_constraint(line, lambda *values: sum(values) == magic_constant(n))
+    return csp
+
+
+def search(given: Partial, limit: int, statistics: Statistics) -> List[Square]:
+    """Up to limit completions of the square."""
+    n = len(given)
+    found = []
+    for assignment in solutions(to_csp(given), lcv=False, statistics=statistics):
+        found.append([[assignment[r, c] for c in range(n)] for r in range(n)])
+        if len(found) == limit:
+            break
+    return found
+
+
+def parse(text: str) -> Partial:
+    rows: Partial = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        words = line.split()
+        if not words or words[0].startswith("#"):
+            continue
+        if not all(word.isdigit() or word == "." for word in words):
+            raise ValueError(f"line {line_number}: expected whole numbers, or . for an empty cell")
+        rows.append([int(word) if word != "." else None for word in words])
+        if len(rows[-1]) != len(rows[0]):
+            raise ValueError(f"line {line_number}: {len(rows[-1])} numbers, but the first row has {len(rows[0])}")
+    if not rows:
+        raise ValueError("no square")
+    if len(rows) != len(rows[0]):
+        raise ValueError(f"{len(rows)} rows of {len(rows[0])}: the square is not square")
+    return rows
+
+
+def render(square: Partial) -> str:
+    width = len(str(len(square) ** 2))
+    return "\n".join(" ".join(".".rjust(width) if n is None else str(n).rjust(width) for n in row) for row in square)
+
+
+def check(up_to: int) -> bool:
+    """Generate every order up to up_to, and check each square is magic."""
+    for n in range(1, up_to + 1):
+        if n != 2 and not is_magic(generate(n)):
+            print(f"the square of order {n} is not magic:\n{render(generate(n))}")
+            return False
+    print(f"All {up_to - 1 if up_to >= 2 else up_to} orders up to {up_to} are magic")
+    return True
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Generate, verify and complete magic squares")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    generate_parser = subparsers.add_parser("generate", help="write down a magic square")
+    generate_parser.add_argument("order", type=int)
+    verify_parser = subparsers.add_parser("verify", help="check a square")
+    verify_parser.add_argument("file", type=str)
+    search_parser = subparsers.add_parser("search", help="complete a square with some cells given")
+    search_parser.add_argument("file", type=str)
+    search_parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    check_parser = subparsers.add_parser("check", help="check the constructions")
+    check_parser.add_argument("--up-to", type=int, default=20)
+    args = parser.parse_args()
+
+    if args.command == "check":
+        if not check(args.up_to):
+            parser.exit(1)
+        return
+    try:
+        if args.command == "generate":
+            square = generate(args.order)
+            print(render(square))
+            print(f"magic constant {magic_constant(args.order)}")
+            return
+        with open(args.file) as f:
+            given = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    if args.command == "verify":
+        if any(n is None for row in given for n in row):
+            parser.exit(1, "error: the square has empty cells (complete it with search)\n")
+        report = describe(given)
+        print("\n".join(report))
+        if not report[0].startswith("magic") or report[-1].startswith("not"):
+            parser.exit(1)
+        return
+    statistics = Statistics()
+    found = search(given, 2, statistics)
+    if not found:
+        print("No solution")
+    else:
+        print(render(found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
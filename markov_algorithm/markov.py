@@ -0,0 +1,160 @@
+"""
+# Markov Algorithms
+
+A Markov algorithm is an ordered list of rewrite rules applied to a
+string. At every step, the first rule whose left-hand side occurs in the
+string rewrites its leftmost occurrence, and we start again from the top
+of the list. The algorithm stops when no rule applies, or right after a
+terminating rule applies.
+
+
+## Rule Format
+
+One rule per line. Lines starting with `#` are comments, and blank
+lines are ignored.
+
+    pattern -> replacement      an ordinary rule
+    pattern ->. replacement     a terminating rule
+
+Either side may be empty, and an empty pattern matches at the start of
+the string. Spaces around the arrow are not part of the rule.
+
+
+## Examples
+
+`examples/` has some classics, each with the expected results in its
+comments (`# test: input => output`), which `--test` checks:
+
+    binary_to_unary.markov      101 => ||||| (the classic from Wikipedia)
+    unary_add.markov            ||+||| => |||||
+    unary_multiply.markov       ||*||| => ||||||
+    reverse.markov              abc => cba
+    sort.markov                 bacab => aabbc
+
+
+## Usage
+
+    python3 markov.py examples/binary_to_unary.markov --input 101 --trace
+    python3 markov.py examples/sort.markov --input cabbage
+    python3 markov.py examples/*.markov --test
+"""
+
+import argparse
+import re
+import sys
+from typing import Iterator, List, NamedTuple, Optional, Tuple
+
+
+DEFAULT_MAX_STEPS: int = 100_000
+ARROW = re.compile(r"\s*->(\.?)\s*")
+
+
+class Rule(NamedTuple):
+    pattern: str
+    replacement: str
+    terminating: bool
+
+
+class Result(NamedTuple):
+    status: str     # "halt", "terminated", or "step limit exceeded"
+    steps: int
+    output: str
+
+
+def parse_rules(text: str) -> Tuple[List[Rule], List[Tuple[str, str]]]:
+    """Return the rules, and the `# test:` cases from the comments."""
+    rules: List[Rule] = []
+    tests: List[Tuple[str, str]] = []
+    errors: List[str] = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        stripped = line.strip()
+        test = re.fullmatch(r"#\s*test:\s*(.*?)\s*=>\s*(.*?)\s*", stripped)
+        if test is not None:
+            tests.append((test.group(1), test.group(2)))
+            continue
+        if not stripped or stripped.startswith("#"):
+            continue
+        match = ARROW.search(stripped)
+        if match is None:
+            errors.append(f"line {line_number}: expected 'pattern -> replacement'")
+            continue
+        pattern, replacement = stripped[:match.start()], stripped[match.end():]
+        rules.append(Rule(pattern, replacement, match.group(1) == "."))
+    if errors:
+        raise ValueError("\n".join(errors))
+    return rules, tests
+
+
+def rewrites(rules: List[Rule], text: str) -> Iterator[Tuple[Rule, str]]:
+    """Yield each rule as it applies, along with the string it leaves."""
+    while True:
+        for rule in rules:
+            position = text.find(rule.pattern)
+            if position >= 0:
+                text = text[:position] + rule.replacement + text[position + len(rule.pattern):]
+                yield rule, text
+                if rule.terminating:
+                    return
+                break
+        else:
+            return
+
+
+def format_rule(rule: Rule) -> str:
+    return f"{rule.pattern} ->{'.' if rule.terminating else ''} {rule.replacement}".strip()
+
+
+def run(rules: List[Rule], text: str, max_steps: int, trace: bool = False) -> Result:
+    if trace:
+        print(f"{0:>8}  {'':<24} {text}")
+    steps = 0
+    rule: Optional[Rule] = None
+    for steps, (rule, text) in enumerate(rewrites(rules, text), start=1):
+        if trace:
+            print(f"{steps:>8}  {format_rule(rule):<24} {text}")
+        if steps >= max_steps:
+            return Result("step limit exceeded", steps, text)
+    status = "terminated" if rule is not None and rule.terminating else "halt"
+    return Result(status, steps, text)
+
+
+def run_tests(paths: List[str], max_steps: int) -> bool:
+    passed = True
+    for path in paths:
+        with open(path) as f:
+            rules, tests = parse_rules(f.read())
+        for text, expected in tests:
+            result = run(rules, text, max_steps)
+            ok = result.status != "step limit exceeded" and result.output == expected
+            passed &= ok
+            print(f"{'ok' if ok else 'FAIL':<6} {path}: {text} => {result.output}"
+                  + ("" if ok else f" (expected {expected})"))
+    return passed
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Markov algorithm interpreter")
+    parser.add_argument("rules", type=str, nargs="+", help="path to the rules (several with --test)")
+    parser.add_argument("--input", type=str, default="", help="the initial string")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="print every rewrite")
+    parser.add_argument("--test", action="store_true", help="check each file's `# test:` cases")
+    args = parser.parse_args()
+
+    try:
+        if args.test:
+            sys.exit(0 if run_tests(args.rules, args.max_steps) else 1)
+        if len(args.rules) != 1:
+            parser.error("give one rule file (or use --test)")
+        with open(args.rules[0]) as f:
+            rules, _ = parse_rules(f.read())
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    result = run(rules, args.input, args.max_steps, args.trace)
+    print(f"Result: {result.status}")
+    print(f"Steps: {result.steps}")
+    print(f"Output: {result.output}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,368 @@
+"""
+# Mastermind
+
+One player hides a code, a row of pegs (4 of them, each one of 6
+colours, in the usual game); the other guesses codes, and each guess is
+answered with a black peg for each peg of the right colour in the right
+place, and a white peg for each other peg of a colour in the code, as
+many times as the code has it left over.
+
+    code  1 2 2 3
+    guess 2 2 4 1       1 black (the second 2), 2 white (the 1, the first 2)
+
+
+## Strategies
+
+The codes that could still be the secret, having got every answer so
+far, are the candidates. Each possible guess splits them by the answer
+it would get, and the strategy picks the guess with the best split:
+
+    knuth       the smallest largest part, the fewest candidates that
+                could be left in the worst case (Knuth, 1977)
+    entropy     the most entropy, the expected information in bits, as
+                in `wordle/entropy.py`
+
+Ties go to a guess that could be the secret, then to the first code in
+order. A guess need not be a candidate: one that could not win can still
+split the candidates better. Knuth's is the best there is for the worst
+case of the 4 peg, 6 colour game: every secret in 5 guesses at most.
+
+Scoring every code against every candidate is slow past a few thousand
+codes, so --candidates-only guesses only the candidates, which is much
+quicker and only a little worse. The first guess never needs it: with
+nothing known yet, guesses that differ only by swapping colours or
+places split the candidates the same way, so it is enough to score one
+of each, the codes like 1122 and 1123 whose colours come in order.
+
+
+## Playing
+
+`play` is the game with the roles as above: you hide the code, and
+answer each guess. `guess` turns them round, for you to find a code the
+computer hides (at random) in `--guesses` guesses, at a prompt (the REPL
+of `repl/repl.py`). Each guess is answered with its pegs and the number
+of candidates left, and a guess that cannot be the code, given the
+answers so far, is allowed, but pointed out. `:hint` gives the
+strategy's guess, and lists the candidates when there are few. A code
+found is recorded as won, and one given up (`:reveal`) or not found in
+time as lost (see `progress/progress.py`).
+
+
+## Usage
+
+    python3 mastermind.py solve 3632
+    python3 mastermind.py solve 3632 --strategy entropy
+    python3 mastermind.py play
+    python3 mastermind.py guess --seed 1
+    python3 mastermind.py guess --pegs 3 --colours 4 --guesses 6 --no-record
+    python3 mastermind.py simulate
+    python3 mastermind.py simulate --strategy entropy
+    python3 mastermind.py simulate --pegs 5 --colours 5 --candidates-only --limit 200 --seed 1
+"""
+
+import argparse
+import itertools
+import math
+import operator
+import os
+import random
+import sys
+from typing import Dict, List, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from repl.repl import Repl
+from rng.rng import add_seed_argument, make_rng
+
+
+STRATEGIES: List[str] = ["knuth", "entropy"]
+SYMBOLS: str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+Code = Tuple[int, ...]
+
+
+class Game:
+    def __init__(self, pegs: int = 4, colours: int = 6):
+        if not 1 <= colours <= len(SYMBOLS):
+            raise ValueError(f"there are 1 to {len(SYMBOLS)} colours")
+        if pegs < 1:
+            raise ValueError("a code has at least one peg")
+        self.pegs = pegs
+        self.colours = colours
+        self.codes: List[Code] = list(itertools.product(range(colours), repeat=pegs))
+        self.counts = {code: tuple(code.count(colour) for colour in range(colours)) for code in self.codes}
+
+    def answer(self, guess: Code, code: Code) -> Tuple[int, int]:
+        """The black and white pegs for guess, when the secret is code."""
+        black = sum(map(operator.eq, guess, code))
+        common = sum(map(min, self.counts[guess], self.counts[code]))
+        return black, common - black
+
+    def parse(self, text: str) -> Code:
+        text = text.upper().replace(" ", "")
+        if len(text) != self.pegs or any(c not in SYMBOLS[:self.colours] for c in text):
+            raise ValueError(f"a code is {self.pegs} of {SYMBOLS[:self.colours]}, not {text!r}")
+        return tuple(SYMBOLS.index(c) for c in text)
+
+    def format(self, code: Code) -> str:
+        return "".join(SYMBOLS[colour] for colour in code)
+
+    def openings(self) -> List[Code]:
+        """One code of each shape, its colours in order: 1111, 1112, 1122, 1123, 1234, for 4 pegs."""
+        return [code for code in self.codes
+                if all(b in (a, a + 1) for a, b in zip(code, code[1:])) and code[0] == 0
+                and all(code.count(a) >= code.count(a + 1) for a in set(code))]
+
+
+################################################################################
+###                                 Guessing                                 ###
+################################################################################
+
+
+def split(game: Game, guess: Code, candidates: Sequence[Code]) -> Dict[Tuple[int, int], int]:
+    counts: Dict[Tuple[int, int], int] = {}
+    for code in candidates:
+        answer = game.answer(guess, code)
+        counts[answer] = counts.get(answer, 0) + 1
+    return counts
+
+
+def entropy(counts: Dict[Tuple[int, int], int]) -> float:
+    n = sum(counts.values())
+    return math.log2(n) - sum(c * math.log2(c) for c in counts.values()) / n
+
+
+def choose(game: Game, candidates: Sequence[Code], first: bool, strategy: str = "knuth",
+           candidates_only: bool = False) -> Code:
+    if len(candidates) <= 2:
+        return candidates[0]
+    if first:
+        guesses = game.openings()
+    else:
+        guesses = candidates if candidates_only else game.codes
+    possible = set(candidates)
+    best, best_rank = candidates[0], None
+    for guess in guesses:
+        counts = split(game, guess, candidates)
+        worth = -max(counts.values()) if strategy == "knuth" else entropy(counts)
+        rank = (worth, guess in possible)
+        if best_rank is None or rank > best_rank:
+            best, best_rank = guess, rank
+    return best
+
+
+def narrow(game: Game, candidates: Sequence[Code], guess: Code, answer: Tuple[int, int]) -> List[Code]:
+    return [code for code in candidates if game.answer(guess, code) == answer]
+
+
+def solve(game: Game, secret: Code, strategy: str = "knuth",
+          candidates_only: bool = False) -> List[Tuple[Code, Tuple[int, int], int]]:
+    """The guesses, each with its answer and the number of candidates before it."""
+    candidates = game.codes
+    turns = []
+    while True:
+        guess = choose(game, candidates, not turns, strategy, candidates_only)
+        answer = game.answer(guess, secret)
+        turns.append((guess, answer, len(candidates)))
+        if answer == (game.pegs, 0):
+            return turns
+        candidates = narrow(game, candidates, guess, answer)
+
+
+################################################################################
+###                                Simulating                                ###
+################################################################################
+
+
+class Tally:
+    def __init__(self):
+        self.games = 0
+        self.guesses: Dict[int, int] = {}   # number of guesses -> games
+
+    def add(self, turns: int):
+        self.games += 1
+        self.guesses[turns] = self.guesses.get(turns, 0) + 1
+
+    def __str__(self) -> str:
+        total = sum(turns * games for turns, games in self.guesses.items())
+        lines = [f"{turns:>2}: {games}" for turns, games in sorted(self.guesses.items())]
+        lines.append(f"Average: {total / self.games:.3f} guesses over {self.games} secrets "
+                     f"({total}/{self.games}), at most {max(self.guesses)}")
+        return "\n".join(lines)
+
+
+def simulate(game: Game, secrets: Sequence[Code], strategy: str = "knuth", candidates_only: bool = False) -> Tally:
+    """
+    Play every secret. The secrets that have had the same answers so far
+    get the same next guess, so this goes through the tree of games, a
+    choice per node, rather than game by game.
+    """
+    tally = Tally()
+
+    def walk(candidates: List[Code], playing: List[Code], turn: int):
+        guess = choose(game, candidates, turn == 1, strategy, candidates_only)
+        groups: Dict[Tuple[int, int], List[Code]] = {}
+        for code in candidates:
+            groups.setdefault(game.answer(guess, code), []).append(code)
+        chosen = set(playing)
+        for answer, group in groups.items():
+            still = [code for code in group if code in chosen]
+            if not still:
+                continue
+            if answer == (game.pegs, 0):
+                tally.add(turn)
+            else:
+                walk(group, still, turn + 1)
+
+    walk(game.codes, list(secrets), 1)
+    return tally
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def read_answer(game: Game, prompt: str) -> Optional[Tuple[int, int]]:
+    """The black and white pegs the player gives, like 2 1; None at the end of input."""
+    while True:
+        try:
+            text = input(prompt)
+        except EOFError:
+            return None
+        words = text.replace(",", " ").split()
+        if len(words) == 2 and all(word.isdigit() for word in words):
+            black, white = map(int, words)
+            if black + white <= game.pegs and (black, white) != (game.pegs - 1, 1):
+                return black, white
+        print(f"Answer with the black and the white pegs, e.g. 1 2, at most {game.pegs} in all")
+
+
+def play(game: Game, strategy: str, candidates_only: bool):
+    print(f"Think of a code of {game.pegs} pegs, each one of {SYMBOLS[:game.colours]}, "
+          f"and answer each guess with its black and white pegs.")
+    candidates = game.codes
+    turn = 1
+    while True:
+        guess = choose(game, candidates, turn == 1, strategy, candidates_only)
+        left = len(candidates)
+        prompt = f"{turn}. {game.format(guess)}  ({left} candidate{'s' * (left != 1)})  black white? "
+        answer = read_answer(game, prompt)
+        if answer is None:
+            print()
+            return
+        if answer == (game.pegs, 0):
+            print(f"Found in {turn} guess{'es' * (turn != 1)}")
+            return
+        candidates = narrow(game, candidates, guess, answer)
+        if not candidates:
+            print("No code gets all those answers: one of them must be wrong")
+            return
+        turn += 1
+
+
+def guess_code(game: Game, rng: random.Random, limit: int, strategy: str, candidates_only: bool, record: bool = True):
+    """Guess a code the computer hides, at the prompt, in at most `limit` guesses."""
+    session = Session("mastermind", f"{game.pegs} pegs, {game.colours} colours", record)
+    secret: List[Code] = []
+    turns: List[Tuple[Code, Tuple[int, int]]] = []
+    candidates: List[Code] = []
+
+    def describe(number: int, code: Code, answer: Tuple[int, int]) -> str:
+        return f"{number}. {game.format(code)}  {answer[0]} black, {answer[1]} white"
+
+    def take(text: str):
+        if session.finished:
+            raise ValueError("the game is over (:new for another)")
+        code = game.parse(text)
+        answer = game.answer(code, secret[0])
+        possible = code in candidates
+        turns.append((code, answer))
+        session.move()
+        candidates[:] = narrow(game, candidates, code, answer)
+        left = len(candidates)
+        print(describe(len(turns), code, answer) + f"  ({left} candidate{'s' * (left != 1)} left"
+              + ("" if possible else "; that guess could not have been the code") + ")")
+        if answer == (game.pegs, 0):
+            print(f"Found in {len(turns)} guess{'es' * (len(turns) != 1)}")
+            session.finish("won")
+        elif len(turns) >= limit:
+            print(f"Out of guesses: the code was {game.format(secret[0])}")
+            session.finish("lost")
+
+    def hint(_: str):
+        if session.finished:
+            return
+        best = choose(game, candidates, not turns, strategy, candidates_only)
+        print(f"{game.format(best)} ({strategy}), with {len(candidates)} candidate{'s' * (len(candidates) != 1)} left"
+              + (f": {' '.join(map(game.format, candidates))}" if len(candidates) <= 10 else ""))
+
+    def reveal(_: str):
+        if not session.finished:
+            print(f"The code was {game.format(secret[0])}")
+            session.finish("lost")
+
+    def new(_: str):
+        session.start()
+        secret[:] = [rng.choice(game.codes)]
+        turns.clear()
+        candidates[:] = game.codes
+        print(f"A code of {game.pegs} pegs, each one of {SYMBOLS[:game.colours]}: {limit} guesses to find it")
+
+    repl = Repl("guess> ", take)
+    repl.add_command("guesses", lambda _: print("\n".join(describe(number, code, answer) for number, (code, answer)
+                                                           in enumerate(turns, start=1))), "the guesses so far")
+    repl.add_command("hint", hint, "the strategy's next guess, and the codes still possible")
+    repl.add_command("reveal", reveal, "give up, and see the code")
+    repl.add_command("new", new, "start again with a new code")
+    print(f"A guess is a code like {game.format(game.openings()[-1])}, and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Play Mastermind by Knuth's minimax, or by information")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--pegs", type=int, default=4)
+    common.add_argument("--colours", type=int, default=6)
+    common.add_argument("--strategy", choices=STRATEGIES, default="knuth")
+    common.add_argument("--candidates-only", action="store_true", help="only guess codes that could be the secret")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", parents=[common], help="find a secret code")
+    solve_parser.add_argument("secret", type=str, help="the code, like 1234")
+    subparsers.add_parser("play", parents=[common], help="guess the code you are thinking of")
+    guess_parser = subparsers.add_parser("guess", parents=[common], help="find a code the computer hides, with hints")
+    guess_parser.add_argument("--guesses", type=int, default=10, help="how many you have")
+    add_seed_argument(guess_parser, help="for the codes")
+    guess_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    simulate_parser = subparsers.add_parser("simulate", parents=[common], help="play every code and count the guesses")
+    simulate_parser.add_argument("--limit", type=int, default=None, help="play a random sample of this many")
+    add_seed_argument(simulate_parser, help="seed the sample")
+    args = parser.parse_args()
+
+    try:
+        game = Game(args.pegs, args.colours)
+        secret = game.parse(args.secret) if args.command == "solve" else None
+        if args.command == "guess" and args.guesses < 1:
+            raise ValueError("it takes at least one guess")
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+    if args.command == "solve":
+        turns = solve(game, secret, args.strategy, args.candidates_only)
+        for number, (guess, (black, white), left) in enumerate(turns, start=1):
+            print(f"{number}. {game.format(guess)}  {black} black, {white} white  "
+                  f"({left} candidate{'s' * (left != 1)})")
+    elif args.command == "play":
+        play(game, args.strategy, args.candidates_only)
+    elif args.command == "guess":
+        guess_code(game, make_rng(args.seed), args.guesses, args.strategy, args.candidates_only, not args.no_record)
+    else:
+        secrets = game.codes
+        if args.limit is not None:
+            secrets = make_rng(args.seed).sample(secrets, k=min(args.limit, len(secrets)))
+        print(simulate(game, secrets, args.strategy, args.candidates_only))
+
+
+if __name__ == "__main__":
+    main()
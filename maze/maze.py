@@ -0,0 +1,310 @@
+"""
+# Mazes
+
+A maze is a grid of cells, each knowing which of its four sides are
+open, and the generators below carve passages until every cell is
+connected to every other by exactly one path (a perfect maze: a
+spanning tree of the grid).
+
+    backtracker     a random walk that backs up when it is boxed in: long
+                    winding corridors, and few dead ends
+    prim            Prim's algorithm, growing out from one cell through a
+                    random wall on its frontier: short, bushy dead ends
+    kruskal         Kruskal's algorithm, knocking down random walls that
+                    join two separate pieces: many short dead ends, evenly
+    wilson          loop-erased random walks from each cell to the maze so
+                    far: every spanning tree is equally likely, so it has
+                    no bias at all, but the first walks are slow
+
+Every generator takes a `random.Random`, so a seed names a maze. A
+perfect maze has one way through; `--braid` opens some of its dead ends
+into loops, so that there are others (and the solvers in `solve.py` have
+something to choose between).
+
+
+## Rendering
+
+    ascii       +--+ and |, which any terminal shows
+    unicode     box-drawing lines
+    blocks      a grid of wall and floor cells (2w + 1 by 2h + 1), which
+                is also what `--png` draws
+
+
+## Usage
+
+    python3 maze.py --width 20 --height 10 --seed 1
+    python3 maze.py --algorithm wilson --style unicode --seed 1
+    python3 maze.py --algorithm kruskal --width 60 --height 40 --png maze.png --scale 6
+    python3 maze.py --braid 0.5 --seed 1 > braided.txt
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Callable, Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from cellular_automata.png import scale, write_png
+from grid.grid import ORTHOGONAL, Grid, Position
+from rng.rng import add_seed_argument, make_rng
+
+
+UP, RIGHT, DOWN, LEFT = (1 << i for i in range(4))     # the bits of ORTHOGONAL's directions
+
+
+def opposite(side: int) -> int:
+    return {UP: DOWN, RIGHT: LEFT, DOWN: UP, LEFT: RIGHT}[side]
+
+
+class Maze:
+    def __init__(self, width: int, height: int):
+        if width < 1 or height < 1:
+            raise ValueError("a maze must be at least 1x1")
+        self.cells: Grid[int] = Grid.filled(width, height, 0)   # the open sides of each cell
+        self.width, self.height = width, height
+
+    def side(self, a: Position, b: Position) -> int:
+        """The side of a that b is on. They must be next to each other."""
+        return 1 << ORTHOGONAL.index((b[0] - a[0], b[1] - a[1]))
+
+    def carve(self, a: Position, b: Position):
+        side = self.side(a, b)
+        self.cells[a] |= side
+        self.cells[b] |= opposite(side)
+
+    def is_open(self, a: Position, b: Position) -> bool:
+        return bool(self.cells[a] & self.side(a, b))
+
+    def neighbours(self, position: Position) -> List[Position]:
+        """The cells next to this one, whether or not there is a wall between."""
+        return self.cells.neighbours(position)
+
+    def exits(self, position: Position) -> List[Position]:
+        """The cells that this one has a passage to."""
+        return [other for other in self.neighbours(position) if self.is_open(position, other)]
+
+    def positions(self) -> List[Position]:
+        return list(self.cells.positions())
+
+    def dead_ends(self) -> int:
+        return sum(1 for position in self.positions() if len(self.exits(position)) == 1)
+
+
+################################################################################
+###                                Generators                                ###
+################################################################################
+
+
+def backtracker(maze: Maze, rng: random.Random):
+    start = (rng.randrange(maze.width), rng.randrange(maze.height))
+    stack = [start]
+    visited = {start}
+    while stack:
+        here = stack[-1]
+        unvisited = [other for other in maze.neighbours(here) if other not in visited]
+        if not unvisited:
+            stack.pop()
+            continue
+        there = rng.choice(unvisited)
+        maze.carve(here, there)
+        visited.add(there)
+        stack.append(there)
+
+
+def prim(maze: Maze, rng: random.Random):
+    start = (rng.randrange(maze.width), rng.randrange(maze.height))
+    inside = {start}
+    frontier = [(start, other) for other in maze.neighbours(start)]
+    while frontier:
+        i = rng.randrange(len(frontier))
+        frontier[i], frontier[-1] = frontier[-1], frontier[i]
+        here, there = frontier.pop()
+        if there in inside:
+            continue
+        maze.carve(here, there)
+        inside.add(there)
+        frontier.extend((there, other) for other in maze.neighbours(there) if other not in inside)
+
+
+def kruskal(maze: Maze, rng: random.Random):
+    parent: Dict[Position, Position] = {position: position for position in maze.positions()}
+
+    def root(position: Position) -> Position:
+        while parent[position] != position:
+            parent[position] = parent[parent[position]]
+            position = parent[position]
+        return position
+
+    walls = [(a, b) for a in maze.positions() for b in maze.neighbours(a) if a < b]
+    rng.shuffle(walls)
+    for a, b in walls:
+        ra, rb = root(a), root(b)
+        if ra != rb:
+            parent[ra] = rb
+            maze.carve(a, b)
+
+
+def wilson(maze: Maze, rng: random.Random):
+    positions = maze.positions()
+    inside = {rng.choice(positions)}
+    for start in positions:
+        # Walk until the maze is hit, remembering only the last way out of
+        # each cell, which erases the loops.
+        way_out: Dict[Position, Position] = {}
+        here = start
+        while here not in inside:
+            way_out[here] = rng.choice(maze.neighbours(here))
+            here = way_out[here]
+        here = start
+        while here not in inside:
+            maze.carve(here, way_out[here])
+            inside.add(here)
+            here = way_out[here]
+
+
+ALGORITHMS: Dict[str, Callable[[Maze, random.Random], None]] = {
+    "backtracker": backtracker,
+    "prim": prim,
+    "kruskal": kruskal,
+    "wilson": wilson,
+}
+
+
+def braid(maze: Maze, fraction: float, rng: random.Random):
+    """Knock a wall out of that fraction of the dead ends, which makes loops, so there is more than one way."""
+    dead_ends = [position for position in maze.positions() if len(maze.exits(position)) == 1]
+    rng.shuffle(dead_ends)
+    for position in dead_ends[:round(fraction * len(dead_ends))]:
+        walled = [other for other in maze.neighbours(position) if not maze.is_open(position, other)]
+        if len(maze.exits(position)) == 1 and walled:
+            maze.carve(position, rng.choice(walled))
+
+
+def generate(width: int, height: int, algorithm: str = "backtracker", seed: Optional[int] = None,
+             braided: float = 0.0) -> Maze:
+    if algorithm not in ALGORITHMS:
+        raise ValueError(f"unknown algorithm '{algorithm}': expected one of {', '.join(ALGORITHMS)}")
+    maze = Maze(width, height)
+    rng = make_rng(seed)
+    ALGORITHMS[algorithm](maze, rng)
+    if braided:
+        braid(maze, braided, rng)
+    return maze
+
+
+################################################################################
+###                                Rendering                                 ###
+################################################################################
+
+
+def blocks(maze: Maze) -> Grid[bool]:
+    """True for a wall: cell (x, y) is at (2x + 1, 2y + 1), with its sides around it."""
+    walls = Grid.filled(2 * maze.width + 1, 2 * maze.height + 1, True)
+    for x, y in maze.positions():
+        walls[2 * x + 1, 2 * y + 1] = False
+        if maze.cells[x, y] & RIGHT:
+            walls[2 * x + 2, 2 * y + 1] = False
+        if maze.cells[x, y] & DOWN:
+            walls[2 * x + 1, 2 * y + 2] = False
+    return walls
+
+
+# A corner by which of its (up, right, down, left) arms are walls.
+BOX_CORNERS: Dict[Tuple[bool, bool, bool, bool], str] = {
+    (False, False, False, False): " ", (True, False, False, False): "╵", (False, True, False, False): "╶",
+    (False, False, True, False): "╷", (False, False, False, True): "╴", (True, False, True, False): "│",
+    (False, True, False, True): "─", (True, True, False, False): "└", (False, True, True, False): "┌",
+    (False, False, True, True): "┐", (True, False, False, True): "┘", (True, True, True, False): "├",
+    (False, True, True, True): "┬", (True, False, True, True): "┤", (True, True, False, True): "┴",
+    (True, True, True, True): "┼",
+}
+STYLES: List[str] = ["ascii", "unicode", "blocks"]
+
+
+def render(maze: Maze, style: str = "ascii", marks: Optional[Dict[Position, str]] = None) -> str:
+    """The maze as text. `marks` puts a character in some cells (a path, say)."""
+    marks = marks or {}
+    walls = blocks(maze)
+    if style == "blocks":
+        floor = {(2 * x + 1, 2 * y + 1): mark for (x, y), mark in marks.items()}
+        return "\n".join(
+            "".join("█" if walls[x, y] else floor.get((x, y), " ") for x in range(walls.width))
+            for y in range(walls.height)
+        )
+    lines = []
+    for y in range(walls.height):
+        line = []
+        for x in range(walls.width):
+            wall = walls[x, y]
+            if x % 2 == 0 and y % 2 == 0:
+                if style == "ascii":
+                    line.append("+")
+                else:
+                    arms = tuple(walls.get((x + dx, y + dy), False) for dx, dy in ORTHOGONAL)
+                    line.append(BOX_CORNERS[arms])
+            elif x % 2 == 0:
+                line.append(("|" if style == "ascii" else "│") if wall else " ")
+            elif y % 2 == 0:
+                line.append(("--" if style == "ascii" else "──") if wall else "  ")
+            else:
+                line.append(marks.get((x // 2, y // 2), " ").center(2))
+        lines.append("".join(line))
+    return "\n".join(lines)
+
+
+def parse(text: str) -> Maze:
+    """A maze printed in the ascii or unicode style: a side is open where its wall is a space."""
+    lines = text.strip("\n").split("\n")
+    width, height = (len(lines[0]) - 1) // 3, (len(lines) - 1) // 2
+    if width < 1 or height < 1 or any(len(line.rstrip()) > 3 * width + 1 for line in lines):
+        raise ValueError("a maze is rows of +--+ corners and walls, like the ascii style prints")
+    lines = [line.ljust(3 * width + 1) for line in lines]
+    maze = Maze(width, height)
+    for x, y in maze.positions():
+        if x + 1 < width and lines[2 * y + 1][3 * x + 3] == " ":
+            maze.carve((x, y), (x + 1, y))
+        if y + 1 < height and lines[2 * y + 2][3 * x + 1:3 * x + 3] == "  ":
+            maze.carve((x, y), (x, y + 1))
+    return maze
+
+
+PALETTE = [(255, 255, 255), (0, 0, 0)]
+
+
+def save_png(maze: Maze, path: str, factor: int):
+    write_png(path, scale(blocks(maze).map(int).rows(), PALETTE, factor))
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Generate mazes")
+    parser.add_argument("--algorithm", choices=list(ALGORITHMS), default="backtracker")
+    parser.add_argument("--width", type=int, default=20)
+    parser.add_argument("--height", type=int, default=10)
+    add_seed_argument(parser)
+    parser.add_argument("--style", choices=STYLES, default="ascii")
+    parser.add_argument("--png", type=str, default=None, help="save the maze as an image here too")
+    parser.add_argument("--scale", type=int, default=4, help="the pixels on a side of each block in the image")
+    parser.add_argument("--braid", type=float, default=0.0, help="the fraction of dead ends to open into loops")
+    parser.add_argument("--stats", action="store_true", help="print how many dead ends the maze has")
+    args = parser.parse_args()
+
+    try:
+        maze = generate(args.width, args.height, args.algorithm, args.seed, args.braid)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    print(render(maze, args.style))
+    if args.stats:
+        cells = args.width * args.height
+        print(f"{maze.dead_ends()} dead ends in {cells} cells ({100 * maze.dead_ends() / cells:.0f}%)")
+    if args.png is not None:
+        save_png(maze, args.png, args.scale)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,257 @@
+"""
+# Maze Solving
+
+Find the way from the top left cell of a maze to the bottom right, with
+the searches in `search/search.py`:
+
+    bfs         the fewest cells
+    dijkstra    the cheapest path, when each cell has a cost to enter
+                (`--weights` gives them random costs from 1 to 9)
+    astar       the cheapest path too, heading for the goal first: its
+                heuristic is the Manhattan distance left, times the
+                cheapest cell, which never overestimates
+
+A perfect maze has only one way through, so every search finds the same
+path, and the difference is in how much of the maze it looks at to find
+it; braid the maze (`--braid`) to give them a choice. `--compare` runs
+all three on the same maze.
+
+`--check` makes `--count` mazes of each kind from the seed and solves
+them: each search must find a path from start to goal through open
+walls, and in a perfect maze, which must have exactly one passage fewer
+than it has cells and reach every cell (so one way through), every
+search must find the same path. In a braided one the cheapest cost is
+the same for them all.
+
+
+## Animation
+
+`--animate` redraws the maze in the terminal as the search goes: `·`
+for a cell that has been expanded, `o` for one on the frontier (reached
+but not yet expanded), and at the end, `*` for the path (with
+`--weights`, the cells not yet reached show their cost). It records the
+search by wrapping the successors function, so the searches themselves
+know nothing about it.
+
+
+## Usage
+
+    python3 solve.py --width 30 --height 12 --seed 1
+    python3 solve.py --width 30 --height 12 --seed 1 --braid 0.5 --algorithm astar --animate
+    python3 solve.py --seed 2 --braid 1 --weights --algorithm dijkstra
+    python3 solve.py --width 60 --height 30 --seed 3 --braid 0.5 --compare
+    python3 solve.py --file braided.txt --algorithm bfs
+    python3 solve.py --check --count 10 --seed 1
+"""
+
+import argparse
+import os
+import random
+import sys
+import time
+from typing import Dict, Iterable, List, Optional, Set, Tuple
+
+from maze import ALGORITHMS, STYLES, Maze, generate, parse, render
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from rng.rng import add_seed_argument, make_rng
+from search.search import Result, astar, bfs, dijkstra
+
+
+SOLVERS: List[str] = ["bfs", "dijkstra", "astar"]
+
+
+class Recorder:
+    """Wraps successors to remember the states expanded and the states reached, in order."""
+
+    def __init__(self, maze: Maze, weights: Grid[int]):
+        self.maze = maze
+        self.weights = weights
+        self.expanded: List[Position] = []
+        self.reached: List[Tuple[int, Position]] = []    # (how many had been expanded, the position)
+
+    def weighted(self, position: Position) -> Iterable[Tuple[str, Position, float]]:
+        self.expanded.append(position)
+        for other in self.maze.exits(position):
+            self.reached.append((len(self.expanded), other))
+            yield other, other, self.weights[other]
+
+    def unweighted(self, position: Position) -> Iterable[Tuple[str, Position]]:
+        for move, other, _ in self.weighted(position):
+            yield move, other
+
+
+def solve(maze: Maze, algorithm: str, weights: Optional[Grid[int]] = None) -> Tuple[Optional[Result], Recorder]:
+    weights = weights if weights is not None else Grid.filled(maze.width, maze.height, 1)
+    start, goal = (0, 0), (maze.width - 1, maze.height - 1)
+    recorder = Recorder(maze, weights)
+
+    def is_goal(position: Position) -> bool:
+        return position == goal
+
+    if algorithm == "bfs":
+        result = bfs(start, is_goal, recorder.unweighted)
+    elif algorithm == "dijkstra":
+        result = dijkstra(start, is_goal, recorder.weighted)
+    elif algorithm == "astar":
+        cheapest = min(weight for _, weight in weights.items())
+        result = astar(start, is_goal, recorder.weighted,
+                       lambda p: cheapest * (abs(goal[0] - p[0]) + abs(goal[1] - p[1])))
+    else:
+        raise ValueError(f"unknown algorithm '{algorithm}': expected one of {', '.join(SOLVERS)}")
+    return result, recorder
+
+
+def path_cost(path: List[Position], weights: Optional[Grid[int]]) -> int:
+    """The cost of entering every cell after the first."""
+    return sum(weights[position] for position in path[1:]) if weights is not None else len(path) - 1
+
+
+def check_path(maze: Maze, path: List[Position]) -> List[str]:
+    problems = []
+    if not path or path[0] != (0, 0) or path[-1] != (maze.width - 1, maze.height - 1):
+        problems.append("the path does not go from the start to the goal")
+    for a, b in zip(path, path[1:]):
+        if b not in maze.exits(a):
+            problems.append(f"the path goes through a wall from {a} to {b}")
+            break
+    return problems
+
+
+def check(count: int, width: int, height: int, rng: random.Random) -> bool:
+    """Generate perfect and braided mazes with every algorithm, and solve them with every search."""
+    for algorithm in ALGORITHMS:
+        for braided in (0.0, 0.5):
+            for number in range(1, count + 1):
+                seed = rng.getrandbits(32)
+                maze = generate(width, height, algorithm, seed, braided)
+                weights = None
+                if braided:
+                    weights = Grid([[rng.randint(1, 9) for _ in range(width)] for _ in range(height)])
+                problems = []
+                if not braided:
+                    passages = sum(len(maze.exits(position)) for position in maze.positions()) // 2
+                    if passages != width * height - 1:
+                        problems.append(f"{passages} passages, not {width * height - 1}")
+                    reached, stack = {(0, 0)}, [(0, 0)]
+                    while stack:
+                        for other in maze.exits(stack.pop()):
+                            if other not in reached:
+                                reached.add(other)
+                                stack.append(other)
+                    if len(reached) != width * height:
+                        problems.append(f"{width * height - len(reached)} cells cannot be reached")
+                paths = {}
+                for solver in SOLVERS:
+                    result, _ = solve(maze, solver, weights)
+                    if result is None:
+                        problems.append(f"{solver} found no path")
+                        continue
+                    problems += [f"{solver}: {problem}" for problem in check_path(maze, result.states)]
+                    paths[solver] = result.states
+                if not braided and len({tuple(path) for path in paths.values()}) > 1:
+                    problems.append("the searches found different paths")
+                costs = {solver: path_cost(path, weights) for solver, path in paths.items() if solver != "bfs"}
+                if len(set(costs.values())) > 1:
+                    problems.append(f"the cheapest costs differ: {costs}")
+                if problems:
+                    print(f"{algorithm} maze {number}, {width}x{height}, seed {seed}"
+                          f"{', braided' if braided else ''}: {'; '.join(problems)}")
+                    return False
+    print(f"All {2 * count * len(ALGORITHMS)} generated {width}x{height} mazes are solved, "
+          f"with one path through each perfect one")
+    return True
+
+
+def frame(maze: Maze, recorder: Recorder, expanded: int, path: List[Position], style: str,
+          weights: Optional[Grid[int]]) -> str:
+    """The search after `expanded` expansions, with the path on top."""
+    marks: Dict[Position, str] = {}
+    if weights is not None:
+        marks.update((position, str(weight)) for position, weight in weights.items())
+    for count, position in recorder.reached:
+        if count > expanded:
+            break
+        marks[position] = "o"
+    done: Set[Position] = set(recorder.expanded[:expanded])
+    marks.update((position, "·") for position in done)
+    marks.update((position, "*") for position in path)
+    marks[(0, 0)], marks[(maze.width - 1, maze.height - 1)] = "S", "G"
+    return render(maze, style, marks)
+
+
+def animate(maze: Maze, recorder: Recorder, path: List[Position], style: str, weights: Optional[Grid[int]],
+            delay: float, frames: int):
+    """About `frames` frames of the search, and then the path."""
+    step = max(1, len(recorder.expanded) // frames)
+    for expanded in list(range(0, len(recorder.expanded), step)) + [len(recorder.expanded)]:
+        print("\x1b[H\x1b[2J" + frame(maze, recorder, expanded, [], style, weights), flush=True)
+        time.sleep(delay)
+    print("\x1b[H\x1b[2J" + frame(maze, recorder, len(recorder.expanded), path, style, weights))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve mazes")
+    parser.add_argument("--algorithm", choices=SOLVERS, default="bfs")
+    parser.add_argument("--file", type=str, default=None, help="a maze in the ascii style, instead of a new one")
+    parser.add_argument("--width", type=int, default=20)
+    parser.add_argument("--height", type=int, default=10)
+    add_seed_argument(parser)
+    parser.add_argument("--braid", type=float, default=0.0, help="the fraction of dead ends to open into loops")
+    parser.add_argument("--weights", action="store_true", help="give each cell a random cost from 1 to 9")
+    parser.add_argument("--style", choices=STYLES[:2], default="ascii")
+    parser.add_argument("--animate", action="store_true", help="show the search as it goes")
+    parser.add_argument("--delay", type=float, default=0.05, help="the seconds between frames")
+    parser.add_argument("--frames", type=int, default=100, help="about how many frames to show")
+    parser.add_argument("--compare", action="store_true", help="run every search, and compare them")
+    parser.add_argument("--check", action="store_true", help="generate mazes and check every search's paths")
+    parser.add_argument("--count", type=int, default=10, help="for --check, the mazes of each kind")
+    args = parser.parse_args()
+
+    if args.check:
+        if args.width < 1 or args.height < 1:
+            parser.exit(1, "error: a maze must be at least 1x1\n")
+        if not check(args.count, args.width, args.height, make_rng(args.seed)):
+            parser.exit(1)
+        return
+
+    try:
+        if args.file is not None:
+            with open(args.file) as f:
+                maze = parse(f.read())
+        else:
+            maze = generate(args.width, args.height, seed=args.seed, braided=args.braid)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    weights = None
+    if args.weights:
+        rng = make_rng(args.seed)
+        weights = Grid([[rng.randint(1, 9) for _ in range(maze.width)] for _ in range(maze.height)])
+
+    if args.compare:
+        print(f"{'search':<10} {'expanded':>9} {'cells':>6} {'cost':>6}")
+        for algorithm in SOLVERS:
+            result, recorder = solve(maze, algorithm, weights)
+            if result is None:
+                print(f"{algorithm:<10} {len(recorder.expanded):>9}  no path")
+            else:
+                print(f"{algorithm:<10} {len(recorder.expanded):>9} {len(result.states):>6} "
+                      f"{path_cost(result.states, weights):>6}")
+        return
+
+    result, recorder = solve(maze, args.algorithm, weights)
+    path = result.states if result is not None else []
+    if args.animate:
+        animate(maze, recorder, path, args.style, weights, args.delay, args.frames)
+    else:
+        print(frame(maze, recorder, len(recorder.expanded), path, args.style, weights))
+    if result is None:
+        print(f"No path ({len(recorder.expanded)} cells expanded)")
+    else:
+        print(f"{len(path)} cells, costing {path_cost(path, weights)}, "
+              f"with {len(recorder.expanded)} of {maze.width * maze.height} cells expanded")
+
+
+if __name__ == "__main__":
+    main()
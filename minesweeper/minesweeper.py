@@ -0,0 +1,443 @@
+"""
+# Minesweeper
+
+Clear every cell of a grid that is not a mine. A cleared cell shows how
+many of its eight neighbours are mines, and one showing 0 clears its
+neighbours too. The solver here plays a board it cannot see, only what
+it has cleared, and says why it makes each move.
+
+
+## Deductions
+
+Each cleared number with hidden neighbours is a constraint: its hidden,
+unflagged neighbours hold its number less its flagged ones. Cells are
+named rRcC, row R and column C, counting from 1.
+
+    count       a constraint with no mines left makes its cells safe,
+                and one with as many mines as cells makes them mines
+    subset      where one constraint's cells are all in another's, the
+                rest of the bigger one holds the difference: r2c3 r2c4
+                hold 1, and r2c3 r2c4 r2c5 hold 1, so r2c5 is safe
+    total       once every mine is flagged, the rest are safe, and once
+                the hidden cells are as many as the mines left, they are
+                all mines
+    enumeration every arrangement of mines that fits the constraints
+                agrees on the cell (see below)
+    guess       none of the above: clear the cell least likely to be a
+                mine
+
+## Probabilities
+
+The hidden cells next to a number (the frontier) split into components
+that share no constraint, and each is enumerated by backtracking: for
+each number k of mines in it, how many arrangements fit, and how many of
+those have a mine in each cell. The hidden cells away from the frontier
+are unconstrained, so an arrangement of the frontier with t mines stands
+for C(unconstrained, mines left - t) boards. Weighting by that, across
+the components, gives each cell's chance of being a mine: exactly, for
+a uniform board, given what is showing.
+
+
+## Boards Without Guessing
+
+`generate --no-guess` deals boards until one that the solver clears from
+the first click by deduction alone, without a guess. The first click, and
+its neighbours, are never mines, so it always opens up an area.
+
+
+## The Format
+
+Rows of cells, * for a mine and . for the rest. `generate` writes this,
+and `solve` reads it, and plays it from --start (by default, the first
+cell with no mines around it).
+
+
+## Usage
+
+    python3 minesweeper.py generate --width 9 --height 9 --mines 10 --seed 1
+    python3 minesweeper.py generate --width 16 --height 16 --mines 40 --seed 2 --no-guess
+    python3 minesweeper.py solve boards/beginner.txt --explain
+    python3 minesweeper.py solve boards/expert.txt --start 15,8
+    python3 minesweeper.py bench --width 16 --height 16 --mines 40 --games 100 --seed 1
+"""
+
+import argparse
+import math
+import os
+import random
+import sys
+from fractions import Fraction
+from typing import Dict, FrozenSet, List, NamedTuple, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+from rng.rng import add_seed_argument, make_rng
+
+
+MINE, SAFE = "*", "."
+HIDDEN, FLAG = "#", "F"
+RULES: List[str] = ["count", "subset", "total", "enumeration", "guess"]
+
+
+def name(cell: Position) -> str:
+    return f"r{cell[1] + 1}c{cell[0] + 1}"
+
+
+def names(cells) -> str:
+    return " ".join(name(cell) for cell in sorted(cells, key=lambda cell: (cell[1], cell[0])))
+
+
+def are(cells) -> str:
+    return f"{names(cells)} {'is' if len(cells) == 1 else 'are'}"
+
+
+class Board:
+    def __init__(self, mines: Grid):
+        """mines: True where there is a mine."""
+        self.mines = mines
+        self.width, self.height = mines.width, mines.height
+        self.total = mines.count(True)
+        self.numbers = Grid([[sum(mines[n] for n in mines.neighbours((x, y), diagonal=True))
+                              for x in range(self.width)] for y in range(self.height)])
+
+
+class Exploded(Exception):
+    pass
+
+
+class Game:
+    """What the player can see of a board."""
+
+    def __init__(self, board: Board):
+        self.board = board
+        self.shown: Grid = Grid.filled(board.width, board.height, None)   # the number, once cleared
+        self.hidden = board.width * board.height
+
+    def clear(self, cell: Position):
+        if self.board.mines[cell]:
+            raise Exploded(f"{name(cell)} was a mine")
+        stack = [cell]
+        while stack:
+            cell = stack.pop()
+            if self.shown[cell] is not None:
+                continue
+            self.shown[cell] = self.board.numbers[cell]
+            self.hidden -= 1
+            if self.shown[cell] == 0:
+                stack += [n for n in self.shown.neighbours(cell, diagonal=True) if self.shown[n] is None]
+
+    def won(self) -> bool:
+        return self.hidden == self.board.total
+
+
+class Step(NamedTuple):
+    rule: str                   # count, subset, total, enumeration, guess, or start
+    safe: FrozenSet[Position]
+    mines: FrozenSet[Position]
+    reason: str
+
+
+Constraint = Tuple[FrozenSet[Position], int]
+
+
+class Solver:
+    def __init__(self, game: Game):
+        self.game = game
+        self.flags: Set[Position] = set()
+
+    def hidden(self) -> List[Position]:
+        return [cell for cell, number in self.game.shown.items() if number is None and cell not in self.flags]
+
+    def constraints(self) -> Dict[Position, Constraint]:
+        shown = self.game.shown
+        found = {}
+        for cell, number in shown.items():
+            if not number:
+                continue
+            around = shown.neighbours(cell, diagonal=True)
+            cells = frozenset(n for n in around if shown[n] is None and n not in self.flags)
+            if cells:
+                found[cell] = (cells, number - sum(n in self.flags for n in around))
+        return found
+
+    def deduce(self) -> Step:
+        """The next move, and why: cells that are safe, or mines, or a guess."""
+        constraints = self.constraints()
+        for cell, (cells, mines) in constraints.items():
+            if mines == 0:
+                return Step("count", cells, frozenset(), f"{name(cell)} shows {self.game.shown[cell]} "
+                            f"and has all its mines flagged, so {are(cells)} safe")
+            if mines == len(cells):
+                return Step("count", frozenset(), cells, f"{name(cell)} shows {self.game.shown[cell]} "
+                            f"and has that many hidden neighbours left, "
+                            f"so {are(cells)} {'a mine' if len(cells) == 1 else 'mines'}")
+        for a, (cells_a, mines_a) in constraints.items():
+            for b, (cells_b, mines_b) in constraints.items():
+                if a == b or not cells_a < cells_b:
+                    continue
+                rest, mines = cells_b - cells_a, mines_b - mines_a
+                if mines == 0 or mines == len(rest):
+                    reason = (f"{names(cells_a)} hold {mines_a} (from {name(a)}), and {names(cells_b)} "
+                              f"hold {mines_b} (from {name(b)}), so {are(rest)} "
+                              f"{'safe' if mines == 0 else 'a mine' if len(rest) == 1 else 'mines'}")
+                    return Step("subset", rest if mines == 0 else frozenset(),
+                                rest if mines else frozenset(), reason)
+        hidden = frozenset(self.hidden())
+        left = self.game.board.total - len(self.flags)
+        if left == 0:
+            return Step("total", hidden, frozenset(), "every mine is flagged, so the rest are safe")
+        if left == len(hidden):
+            return Step("total", frozenset(), hidden, f"{left} mines are left in {left} hidden cells")
+        chances = probabilities(constraints, hidden, left)
+        safe = frozenset(cell for cell, p in chances.items() if p == 0)
+        mines = frozenset(cell for cell, p in chances.items() if p == 1)
+        if safe or mines:
+            parts = ([f"{names(safe)} safe"] if safe else []) + ([f"{names(mines)} mines"] if mines else [])
+            return Step("enumeration", safe, mines, f"every arrangement that fits has {' and '.join(parts)}")
+        best = min(sorted(chances, key=lambda cell: (cell[1], cell[0])), key=lambda cell: chances[cell])
+        return Step("guess", frozenset([best]), frozenset(),
+                    f"nothing is certain; {name(best)} is a mine with chance {float(chances[best]):.3f}, the least")
+
+    def apply(self, step: Step):
+        self.flags |= step.mines
+        for cell in step.safe:
+            self.game.clear(cell)
+
+
+def components(constraints: List[Constraint]) -> List[Tuple[List[Position], List[Constraint]]]:
+    """The frontier, split into groups of cells that share no constraint, each with its constraints."""
+    groups: List[Tuple[Set[Position], List[Constraint]]] = []
+    for constraint in constraints:
+        joined = [group for group in groups if group[0] & constraint[0]]
+        cells, members = set(constraint[0]), [constraint]
+        for group in joined:
+            groups.remove(group)
+            cells |= group[0]
+            members += group[1]
+        groups.append((cells, members))
+    return [(sorted(cells, key=lambda cell: (cell[1], cell[0])), members) for cells, members in groups]
+
+
+def enumerate_component(cells: List[Position], constraints: List[Constraint]):
+    """For each number of mines k: (arrangements, {cell: arrangements with a mine there})."""
+    index = {cell: i for i, cell in enumerate(cells)}
+    watching: List[List[int]] = [[] for _ in cells]
+    needs = []
+    for c, (members, mines) in enumerate(constraints):
+        needs.append([mines, len(members)])     # mines still to place, cells still open
+        for cell in members:
+            watching[index[cell]].append(c)
+    found: Dict[int, Tuple[int, List[int]]] = {}
+    placed: List[bool] = []
+
+    def place(i: int):
+        if i == len(cells):
+            k = sum(placed)
+            count, per_cell = found.setdefault(k, (0, [0] * len(cells)))
+            for j, mine in enumerate(placed):
+                per_cell[j] += mine
+            found[k] = (count + 1, per_cell)
+            return
+        for mine in (False, True):
+            if all(needs[c][0] - mine >= 0 and needs[c][0] - mine <= needs[c][1] - 1 for c in watching[i]):
+                for c in watching[i]:
+                    needs[c][0] -= mine
+                    needs[c][1] -= 1
+                placed.append(mine)
+                place(i + 1)
+                placed.pop()
+                for c in watching[i]:
+                    needs[c][0] += mine
+                    needs[c][1] += 1
+
+    place(0)
+    return {k: (count, dict(zip(cells, per_cell))) for k, (count, per_cell) in found.items()}
+
+
+def convolve(a: Dict[int, int], b: Dict[int, int]) -> Dict[int, int]:
+    result: Dict[int, int] = {}
+    for i, x in a.items():
+        for j, y in b.items():
+            result[i + j] = result.get(i + j, 0) + x * y
+    return result
+
+
+def probabilities(constraints: Dict[Position, Constraint], hidden: FrozenSet[Position],
+                  left: int) -> Dict[Position, Fraction]:
+    """Each hidden cell's chance of being a mine, with every board that fits what is shown equally likely."""
+    groups = [enumerate_component(cells, members) for cells, members in components(list(constraints.values()))]
+    frontier = set().union(*(constraint[0] for constraint in constraints.values()))
+    outside = len(hidden - frontier)
+
+    def boards(t: int) -> int:
+        """How many ways to put the other mines outside the frontier, with t on it."""
+        return math.comb(outside, left - t) if 0 <= left - t <= outside else 0
+
+    counts = [{k: count for k, (count, _) in group.items()} for group in groups]
+    chances: Dict[Position, Fraction] = {}
+    total = {0: 1}
+    for count in counts:
+        total = convolve(total, count)
+    weight = sum(ways * boards(t) for t, ways in total.items())
+    if weight == 0:
+        raise ValueError("no arrangement of mines fits what is shown")
+    for g, group in enumerate(groups):
+        others = {0: 1}
+        for h, count in enumerate(counts):
+            if h != g:
+                others = convolve(others, count)
+        mined: Dict[Position, int] = {}
+        for k, (_, per_cell) in group.items():
+            rest = sum(ways * boards(k + t) for t, ways in others.items())
+            for cell, arrangements in per_cell.items():
+                mined[cell] = mined.get(cell, 0) + arrangements * rest
+        for cell, ways in mined.items():
+            chances[cell] = Fraction(ways, weight)
+    if outside:
+        mined_outside = sum(ways * boards(t) * (left - t) for t, ways in total.items())
+        for cell in hidden - frontier:
+            chances[cell] = Fraction(mined_outside, outside * weight)
+    return chances
+
+
+class Outcome(NamedTuple):
+    won: bool
+    steps: List[Step]
+
+    def guesses(self) -> int:
+        return sum(step.rule == "guess" for step in self.steps)
+
+
+def play(board: Board, start: Position, explain: bool = False) -> Outcome:
+    game = Game(board)
+    solver = Solver(game)
+    steps = [Step("start", frozenset([start]), frozenset(), f"start at {name(start)}")]
+    try:
+        game.clear(start)
+        if explain:
+            print(steps[0].reason)
+        while not game.won():
+            step = solver.deduce()
+            steps.append(step)
+            if explain:
+                print(f"{step.rule}: {step.reason}")
+            solver.apply(step)
+    except Exploded as e:
+        if explain:
+            print(f"lost: {e}")
+        return Outcome(False, steps)
+    finally:
+        if explain:
+            print(render(game, solver.flags))
+    return Outcome(True, steps)
+
+
+def generate(width: int, height: int, mines: int, start: Position, rng: random.Random,
+             no_guess: bool = False, attempts: int = 1000) -> Board:
+    grid = Grid.filled(width, height, False)
+    keep_clear = set(grid.neighbours(start, diagonal=True)) | {start}
+    open_cells = [cell for cell in grid.positions() if cell not in keep_clear]
+    if mines > len(open_cells):
+        raise ValueError(f"{mines} mines do not fit in {width}x{height}, away from the first click")
+    for _ in range(attempts):
+        grid = Grid.filled(width, height, False)
+        for cell in rng.sample(open_cells, mines):
+            grid[cell] = True
+        board = Board(grid)
+        if not no_guess:
+            return board
+        outcome = play(board, start)
+        if outcome.won and outcome.guesses() == 0:
+            return board
+    raise ValueError(f"no board without guessing in {attempts} tries; try fewer mines")
+
+
+################################################################################
+###                              Reading and Writing                         ###
+################################################################################
+
+
+def parse(text: str) -> Board:
+    lines = [line.strip() for line in text.split("\n") if line.strip() and not line.strip().startswith("#")]
+    for number, line in enumerate(lines, start=1):
+        if len(line) != len(lines[0]) or any(c not in (MINE, SAFE) for c in line):
+            raise ValueError(f"row {number}: expected {len(lines[0])} cells of * and .")
+    if not lines:
+        raise ValueError("the board is empty")
+    return Board(Grid.parse("\n".join(lines), lambda c: c == MINE))
+
+
+def render_board(board: Board) -> str:
+    return board.mines.render(lambda mine: MINE if mine else SAFE)
+
+
+def render(game: Game, flags: Set[Position]) -> str:
+    def cell(position: Position) -> str:
+        number = game.shown[position]
+        if number is None:
+            return FLAG if position in flags else HIDDEN
+        return str(number) if number else SAFE
+
+    return Grid([[cell((x, y)) for x in range(game.board.width)]
+                 for y in range(game.board.height)]).render(separator=" ")
+
+
+def parse_cell(text: str) -> Position:
+    x, comma, y = text.partition(",")
+    if not comma or not x.isdigit() or not y.isdigit() or int(x) < 1 or int(y) < 1:
+        raise ValueError(f"a cell is its column and row, like 3,5, counting from 1, not {text!r}")
+    return int(x) - 1, int(y) - 1
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Generate and solve Minesweeper boards")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    generate_parser = subparsers.add_parser("generate", help="deal a random board")
+    bench_parser = subparsers.add_parser("bench", help="play random boards and count the wins")
+    for p in (generate_parser, bench_parser):
+        p.add_argument("--width", type=int, default=9)
+        p.add_argument("--height", type=int, default=9)
+        p.add_argument("--mines", type=int, default=10)
+        add_seed_argument(p)
+    generate_parser.add_argument("--start", type=str, default=None, help="the first click (default: the middle)")
+    generate_parser.add_argument("--no-guess", action="store_true", help="only a board that needs no guessing")
+    bench_parser.add_argument("--games", type=int, default=100)
+    solve_parser = subparsers.add_parser("solve", help="play a board, seeing only what is cleared")
+    solve_parser.add_argument("file", type=str, help="the board, in the format above")
+    solve_parser.add_argument("--start", type=str, default=None, help="the first click, as column,row")
+    solve_parser.add_argument("--explain", action="store_true", help="print each deduction, and the board")
+    args = parser.parse_args()
+
+    try:
+        if args.command == "solve":
+            with open(args.file) as f:
+                board = parse(f.read())
+            start = parse_cell(args.start) if args.start else next(
+                (cell for cell, number in board.numbers.items() if number == 0 and not board.mines[cell]), (0, 0))
+            if start not in board.mines:
+                raise ValueError(f"{name(start)} is not on the board")
+            outcome = play(board, start, args.explain)
+            counts = {rule: sum(step.rule == rule for step in outcome.steps) for rule in RULES}
+            print(("Cleared" if outcome.won else "Lost") + ": " +
+                  ", ".join(f"{count} {rule}" for rule, count in counts.items() if count))
+            if not outcome.won:
+                parser.exit(1)
+        elif args.command == "generate":
+            start = parse_cell(args.start) if args.start else (args.width // 2, args.height // 2)
+            board = generate(args.width, args.height, args.mines, start, make_rng(args.seed), args.no_guess)
+            print(render_board(board))
+        else:
+            rng = make_rng(args.seed)
+            start = (args.width // 2, args.height // 2)
+            won = guessed = 0
+            for _ in range(args.games):
+                outcome = play(generate(args.width, args.height, args.mines, start, rng), start)
+                won += outcome.won
+                guessed += outcome.guesses() > 0
+            print(f"Won {won} of {args.games} ({won / args.games:.1%}); {guessed} needed a guess")
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
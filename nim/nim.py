@@ -0,0 +1,337 @@
+"""
+# Nim and Other Heap Games
+
+Heaps of counters, and two players taking turns to make a move on one
+heap; whoever cannot move loses. In Nim a move takes any number of
+counters from one heap; other games allow only some moves, and some
+split what is left of a heap in two.
+
+    nim             take any number
+    subtract:1,3,4  a subtraction game: take 1, 3 or 4 (any set)
+    octal:0.77      an octal game, below: this one is Kayles
+    kayles          knock down 1 or 2 skittles from a row, which may
+                    leave two rows (0.77)
+    dawson          Dawson's chess (0.137)
+    grundy          Grundy's game: split a heap into two unequal heaps
+
+
+## Grundy Numbers
+
+Every such game is worth a heap in Nim: the Grundy number of a heap is
+the smallest number that is not the Grundy number of anything one move
+can leave, and two heaps side by side are worth the XOR of their
+numbers (the Sprague-Grundy theorem). So a position of many heaps is a
+win for the player to move exactly when the XOR of its heaps' numbers
+is not 0, and the winning moves are the ones that leave it 0. In Nim
+the number of a heap is its size.
+
+
+## Octal Games
+
+An octal code 0.d1d2d3... says, by the digit dk, what may be left after
+taking k counters from a heap: add 1 if nothing (the whole heap was k),
+2 if one heap, and 4 if two heaps. Kayles, 0.77, takes 1 or 2 from
+anywhere in the row; a subtraction game is every dk in its set 3.
+
+The Grundy numbers of a subtraction game repeat in the end, and so do
+those of many octal games with a last digit, t. `table` finds the
+shortest period p and the start s of the repeating; if the numbers are
+the same a period apart all the way from s to 2s + p + t, they repeat
+for ever, by the theorem of Guy and Smith, and `table` says that it is
+proved.
+
+
+## Usage
+
+    python3 nim.py analyse 3 4 5
+    python3 nim.py analyse 3 4 5 --game subtract:1,2,3
+    python3 nim.py analyse 10 7 --game kayles
+    python3 nim.py table --game dawson --up-to 180
+    python3 nim.py table --game grundy --up-to 40
+    python3 nim.py play 3 4 5 --game subtract:1,3,4 --first engine
+    python3 nim.py play 1 2 3 --no-record
+
+In `play`, a move is the heap (numbered from 1) and what is left of it:
+`2 5` takes heap 2 down to 5, `2 0` takes all of it, and `2 1 3` leaves
+heaps of 1 and 3 in its place.
+"""
+
+import argparse
+import os
+import re
+import sys
+from typing import Dict, Iterator, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from repl.repl import Repl
+
+
+PRESETS: Dict[str, str] = {"kayles": "octal:0.77", "dawson": "octal:0.137"}
+OCTAL = re.compile(r"0?\.([0-7]+)")
+LEAVE_NONE, LEAVE_ONE, LEAVE_TWO = 1, 2, 4
+Heaps = Tuple[int, ...]
+Move = Tuple[int, Heaps]    # a heap, by its index, and the heaps it leaves
+
+
+class Rules:
+    def __init__(self, name: str, digits: Optional[Dict[int, int]] = None, split: bool = False):
+        """The digits of an octal code, by the counters taken; None takes any number, as in Nim. Splitting is
+        Grundy's game."""
+        self.name = name
+        self.digits = digits
+        self.split = split
+        self.values: List[int] = []
+
+    @property
+    def last_digit(self) -> Optional[int]:
+        """The most a move takes, if there is a most."""
+        return max(self.digits) if self.digits and not self.split else None
+
+    def options(self, n: int) -> Iterator[Heaps]:
+        """What one move can leave of a heap of n, as the heaps left, smallest first."""
+        if self.split:
+            for a in range(1, (n + 1) // 2):
+                yield (a, n - a)
+            return
+        removals = self.digits.items() if self.digits is not None else ((k, LEAVE_NONE | LEAVE_ONE)
+                                                                          for k in range(1, n + 1))
+        for k, digit in removals:
+            rest = n - k
+            if rest == 0 and digit & LEAVE_NONE:
+                yield ()
+            if rest > 0 and digit & LEAVE_ONE:
+                yield (rest,)
+            if digit & LEAVE_TWO:
+                for a in range(1, rest // 2 + 1):
+                    yield (a, rest - a)
+
+    def grundy(self, n: int) -> int:
+        if self.digits is None and not self.split:
+            return n
+        while len(self.values) <= n:
+            m = len(self.values)
+            seen = {self.worth(after) for after in self.options(m)}
+            self.values.append(next(g for g in range(len(seen) + 1) if g not in seen))
+        return self.values[n]
+
+    def worth(self, heaps: Heaps) -> int:
+        """The Grundy number of heaps side by side: the XOR of theirs."""
+        total = 0
+        for n in heaps:
+            total ^= self.grundy(n)
+        return total
+
+    def moves(self, heaps: Heaps) -> List[Move]:
+        return [(i, after) for i, n in enumerate(heaps) for after in self.options(n)]
+
+    def winning_moves(self, heaps: Heaps) -> List[Move]:
+        total = self.worth(heaps)
+        return [(i, after) for i, after in self.moves(heaps)
+                if total ^ self.grundy(heaps[i]) ^ self.worth(after) == 0]
+
+
+def parse_rules(text: str) -> Rules:
+    spec = PRESETS.get(text, text)
+    kind, _, argument = spec.partition(":")
+    if kind == "nim" and not argument:
+        return Rules(text)
+    if kind == "grundy" and not argument:
+        return Rules(text, split=True)
+    if kind == "subtract":
+        try:
+            amounts = {int(k) for k in argument.split(",")}
+        except ValueError:
+            raise ValueError(f"expected the amounts to take, like subtract:1,3,4, not {text!r}") from None
+        if min(amounts) < 1:
+            raise ValueError("the amounts to take must be positive")
+        return Rules(text, {k: LEAVE_NONE | LEAVE_ONE for k in sorted(amounts)})
+    if kind == "octal":
+        match = OCTAL.fullmatch(argument)
+        if match is None:
+            raise ValueError(f"expected an octal code like octal:0.77, not {text!r}")
+        digits = {k: int(d) for k, d in enumerate(match.group(1), start=1) if d != "0"}
+        if not digits:
+            raise ValueError(f"{text} allows no moves")
+        return Rules(text, digits)
+    raise ValueError(f"expected nim, subtract:1,2,3, octal:0.77, {', '.join(PRESETS)} or grundy, not {text!r}")
+
+
+def period(values: List[int], last_digit: Optional[int]) -> Optional[Tuple[int, int, bool]]:
+    """The shortest period of the values, where it starts, and whether it is proved to go on for ever; a
+    period not proved must at least cover the later half of the values, twice over. None if there is none."""
+    found = []
+    for p in range(1, len(values) // 2 + 1):
+        start = len(values) - p
+        while start > 0 and values[start - 1] == values[start - 1 + p]:
+            start -= 1
+        proved = last_digit is not None and len(values) - p >= 2 * start + p + last_digit
+        if proved:
+            return start, p, True
+        if len(values) - start >= max(2 * p, len(values) / 2):
+            found.append((start, p, False))
+    return found[0] if found else None
+
+
+def describe(heaps: Heaps, move: Move) -> str:
+    i, after = move
+    return f"heap {i + 1}: {heaps[i]} -> {' + '.join(map(str, after)) or 'nothing'}"
+
+
+def play_move(heaps: Heaps, move: Move) -> Heaps:
+    i, after = move
+    return heaps[:i] + after + heaps[i + 1:]
+
+
+################################################################################
+###                                   Play                                   ###
+################################################################################
+
+
+def parse_move(rules: Rules, heaps: Heaps, text: str) -> Move:
+    try:
+        numbers = [int(word) for word in text.replace("->", " ").replace("+", " ").split()]
+    except ValueError:
+        numbers = []
+    if len(numbers) < 2:
+        raise ValueError("a move is a heap and what it leaves, like 2 5 (or 2 0 to take it all)")
+    if not 1 <= numbers[0] <= len(heaps):
+        raise ValueError(f"there are heaps 1 to {len(heaps)}")
+    i, after = numbers[0] - 1, tuple(sorted(n for n in numbers[1:] if n != 0))
+    if after not in set(rules.options(heaps[i])):
+        raise ValueError(f"{rules.name} cannot leave {' + '.join(map(str, after)) or 'nothing'} "
+                         f"of a heap of {heaps[i]}")
+    return i, after
+
+
+def engine_move(rules: Rules, heaps: Heaps) -> Tuple[Move, bool]:
+    """A winning move, if there is one, and otherwise the one that takes least from the biggest heap, to
+    last as long as it can; and whether it wins."""
+    winning = rules.winning_moves(heaps)
+    if winning:
+        return winning[0], True
+    return max(rules.moves(heaps), key=lambda move: (heaps[move[0]], sum(move[1]))), False
+
+
+def play(rules: Rules, start: Heaps, engine_first: bool, record: bool = True):
+    history: List[Heaps] = [start]
+    engine_turns = 0 if engine_first else 1
+    session = Session("nim", f"{rules.name} {' '.join(map(str, start))}", record)
+
+    def render() -> str:
+        return "heaps: " + (" ".join(map(str, history[-1])) or "none")
+
+    def over() -> bool:
+        if rules.moves(history[-1]):
+            return False
+        engine_lost = (len(history) - 1) % 2 == engine_turns
+        print("No moves left: " + ("you win" if engine_lost else "the engine wins"))
+        session.finish("won" if engine_lost else "lost")
+        return True
+
+    def engine_turn():
+        heaps = history[-1]
+        if (len(history) - 1) % 2 != engine_turns or over():
+            return
+        move, wins = engine_move(rules, heaps)
+        history.append(play_move(heaps, move))
+        print(f"The engine plays {describe(heaps, move)}" + (" (and will win)" if wins else ""))
+        print(render())
+        over()
+
+    def move(text: str):
+        heaps = history[-1]
+        if not rules.moves(heaps):
+            raise ValueError("the game is over (:new for another)")
+        history.append(play_move(heaps, parse_move(rules, heaps, text)))
+        session.move()
+        print(render())
+        engine_turn()
+
+    def hint(_: str):
+        heaps = history[-1]
+        winning = rules.winning_moves(heaps)
+        if winning:
+            print(f"{describe(heaps, winning[0])} wins")
+        elif rules.moves(heaps):
+            print(f"every move loses against perfect play (the heaps are worth {rules.worth(heaps)})")
+
+    def undo(_: str):
+        while len(history) > 1:
+            history.pop()
+            if (len(history) - 1) % 2 != engine_turns:
+                break
+        print(render())
+
+    def new(_: str):
+        del history[1:]
+        session.start()
+        print(render())
+        engine_turn()
+
+    repl = Repl("move> ", move)
+    repl.add_command("heaps", lambda _: print(render()), "print the heaps")
+    repl.add_command("hint", hint, "a winning move, if there is one")
+    repl.add_command("undo", undo, "take back your last move (and the engine's reply)")
+    repl.add_command("new", new, "start again")
+    print(f"{rules.name}: a move is a heap and what it leaves, like 2 5, and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Nim, subtraction games and octal games by Grundy numbers")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--game", type=str, default="nim", help="nim, subtract:1,2,3, octal:0.77, kayles, "
+                                                                "dawson or grundy")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    analyse_parser = subparsers.add_parser("analyse", parents=[common], help="who wins, and the winning moves")
+    table_parser = subparsers.add_parser("table", parents=[common], help="the Grundy numbers of single heaps")
+    table_parser.add_argument("--up-to", type=int, default=60, help="the biggest heap")
+    play_parser = subparsers.add_parser("play", parents=[common], help="play against perfect play")
+    play_parser.add_argument("--first", choices=["human", "engine"], default="human")
+    play_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    for p in (analyse_parser, play_parser):
+        p.add_argument("heaps", type=int, nargs="+", help="the sizes of the heaps")
+    args = parser.parse_args()
+
+    try:
+        rules = parse_rules(args.game)
+        if args.command != "table" and min(args.heaps) < 0:
+            raise ValueError("a heap cannot be negative")
+        if args.command == "table" and args.up_to < 0:
+            raise ValueError("--up-to cannot be negative")
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+
+    if args.command == "table":
+        values = [rules.grundy(n) for n in range(args.up_to + 1)]
+        width = len(str(args.up_to))
+        for row in range(0, len(values), 10):
+            print(f"{row:>{width}}: " + " ".join(f"{g:>2}" for g in values[row:row + 10]))
+        found = period(values, rules.last_digit) if rules.digits is not None and not rules.split else None
+        if found is not None:
+            start, p, proved = found
+            print(f"period {p} from {start}" + (", proved" if proved else ", as far as the table goes"))
+        return
+    heaps = tuple(n for n in args.heaps if n > 0)
+    if args.command == "play":
+        play(rules, heaps, args.first == "engine", not args.no_record)
+        return
+    print(f"{'heap':>4}  {'size':>4}  Grundy")
+    for i, n in enumerate(heaps, start=1):
+        print(f"{i:>4}  {n:>4}  {rules.grundy(n):>6}")
+    total = rules.worth(heaps)
+    if not rules.moves(heaps):
+        print("No moves: the player to move has lost")
+    elif total == 0:
+        print("XOR 0: the player to move loses against perfect play")
+    else:
+        print(f"XOR {total}: the player to move wins, by")
+        for move in rules.winning_moves(heaps):
+            print(f"  {describe(heaps, move)}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,479 @@
+"""
+# Nonograms
+
+Also Picross, or paint by numbers: fill in cells of a grid so that each
+row and column has runs of filled cells of the lengths its clue gives,
+in order, with at least one empty cell between them. A clue of 3 1 on a
+row of 7 could be ###.#.. or .###..#, among others.
+
+
+## Solving
+
+Each cell is filled, empty, or not known yet. The line solver takes one
+row or column and works out every cell that is the same in all the ways
+its clue fits what is known, by dynamic programming over (cell, runs
+placed so far): which of them can still be finished, and which can be
+reached. A row that changes puts its columns back on the queue to be
+looked at again, and the other way round, until nothing changes.
+
+Most published puzzles are solved by that alone. When it gets stuck the
+search guesses the first unknown cell, filled and then empty, and
+propagates again. It stops after two solutions, so it says whether the
+puzzle is proper (one solution), ambiguous, or contradictory (a line
+whose clue cannot fit, in which case it says which).
+
+
+## Rating and Generating
+
+`rate` solves the way a person does, for the shared scale of
+`difficulty/difficulty.py`: the lines as far as they go (easy, the
+more so the fewer lines fill anything in), then probing, trying a cell
+both ways to see if one leads the lines to a contradiction (medium to
+hard), and guessing only when that is stuck too. `generate` takes a
+random picture, keeps it if its clues have one solution, and with
+`--difficulty`, keeps going until one rates at the level asked. Most
+random pictures fall to the lines; about one in eight needs probing,
+and hardly any need guessing, so a fiendish one is not to be expected.
+`check` generates puzzles and solves each again from its clues written
+out and read back, which must give the picture and nothing else.
+
+
+## The Format
+
+The word `rows`, then one clue per line, top to bottom, then `columns`,
+then one per line, left to right. A clue is its run lengths, separated
+by spaces or commas, and 0 (or -) is an empty line. Lines starting with
+# are comments.
+
+This is most of the `.non` format that puzzles are shared in on the
+internet, which is read as it is: its other lines are a key and a value
+(`title "Heart"`, `width 9`), where the width and height must match the
+clues, and the rest (`catalogue`, `by`, `goal`, ...) are skipped.
+
+    rows
+    1 1
+    5
+    ...
+    columns
+    2
+    ...
+
+
+## Usage
+
+    python3 nonogram.py solve puzzles/heart.txt
+    python3 nonogram.py solve puzzles/duck.txt --stats
+    python3 nonogram.py solve puzzles/ambiguous.txt
+    python3 nonogram.py solve puzzles/contradiction.txt
+    python3 nonogram.py solve puzzles/heart.non
+    python3 nonogram.py rate puzzles/duck.txt
+    python3 nonogram.py generate --width 10 --height 10 --seed 1
+    python3 nonogram.py generate --width 12 --height 12 --difficulty medium --seed 1
+    python3 nonogram.py check --count 20 --seed 1
+"""
+
+import argparse
+import os
+import random
+import sys
+from collections import deque
+from typing import Dict, List, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from difficulty.difficulty import LEVELS, Rating, Scale, generate_rated
+from grid.grid import Grid
+from rng.rng import add_seed_argument, make_rng
+
+
+UNKNOWN, EMPTY, FILLED = -1, 0, 1
+# The generator's pictures: sparser ones are harder, but more often have more than one solution
+DENSITIES: Tuple[float, float] = (0.45, 0.65)
+ATTEMPTS: int = 1000
+Clue = List[int]
+Cells = List[int]
+
+
+class Puzzle:
+    def __init__(self, rows: Sequence[Clue], columns: Sequence[Clue]):
+        self.rows = [list(clue) for clue in rows]
+        self.columns = [list(clue) for clue in columns]
+        self.height, self.width = len(self.rows), len(self.columns)
+        for name, clues, length in (("row", self.rows, self.width), ("column", self.columns, self.height)):
+            for i, clue in enumerate(clues, start=1):
+                if any(run < 1 for run in clue):
+                    raise ValueError(f"{name} {i}: a run is at least one cell long")
+                if sum(clue) + len(clue) - 1 > length:
+                    raise ValueError(f"{name} {i}: the clue {' '.join(map(str, clue))} does not fit in {length} cells")
+        if sum(map(sum, self.rows)) != sum(map(sum, self.columns)):
+            raise ValueError(f"the rows fill {sum(map(sum, self.rows))} cells, but the columns "
+                             f"{sum(map(sum, self.columns))}")
+
+
+class Contradiction(Exception):
+    def __init__(self, line: str):
+        super().__init__(f"{line} cannot be filled in")
+        self.line = line
+
+
+class Statistics:
+    def __init__(self):
+        self.lines = 0
+        self.changed = 0    # the lines solved that filled in a cell
+        self.guesses = 0
+
+    def __str__(self) -> str:
+        return f"{self.lines} lines solved ({self.changed} filling in cells), {self.guesses} guesses"
+
+
+def solve_line(clue: Clue, cells: Cells) -> Optional[Cells]:
+    """The cells that every fit of the clue agrees on, or None if it does not fit at all."""
+    n, k = len(cells), len(clue)
+
+    def fits(i: int, j: int) -> bool:
+        """Whether run j can start at cell i."""
+        end = i + clue[j]
+        return end <= n and EMPTY not in cells[i:end] and (end == n or cells[end] != FILLED)
+
+    # finishable[i][j]: the cells from i on can hold the runs from j on
+    finishable = [[False] * (k + 1) for _ in range(n + 2)]
+    finishable[n][k] = finishable[n + 1][k] = True
+    for i in range(n - 1, -1, -1):
+        for j in range(k, -1, -1):
+            finishable[i][j] = ((cells[i] != FILLED and finishable[i + 1][j])
+                                or (j < k and fits(i, j) and finishable[i + clue[j] + 1][j + 1]))
+    if not finishable[0][0]:
+        return None
+    can_fill, can_empty = [False] * n, [False] * n
+    reached = [[False] * (k + 1) for _ in range(n + 2)]
+    reached[0][0] = True
+    for i in range(n):
+        for j in range(k + 1):
+            if not reached[i][j]:
+                continue
+            if cells[i] != FILLED and finishable[i + 1][j]:
+                can_empty[i] = True
+                reached[i + 1][j] = True
+            if j < k and fits(i, j) and finishable[i + clue[j] + 1][j + 1]:
+                end = i + clue[j]
+                for cell in range(i, end):
+                    can_fill[cell] = True
+                if end < n:
+                    can_empty[end] = True
+                reached[end + 1][j + 1] = True
+    return [UNKNOWN if fill and empty else FILLED if fill else EMPTY for fill, empty in zip(can_fill, can_empty)]
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle):
+        self.puzzle = puzzle
+        self.statistics = Statistics()
+
+    def propagate(self, grid: Grid, queue: deque):
+        """Solve lines until none changes. Raises Contradiction if one cannot be filled."""
+        queued = set(queue)
+        while queue:
+            line = queue.popleft()
+            queued.discard(line)
+            kind, index = line
+            if kind == "row":
+                positions = [(x, index) for x in range(self.puzzle.width)]
+                clue = self.puzzle.rows[index]
+            else:
+                positions = [(index, y) for y in range(self.puzzle.height)]
+                clue = self.puzzle.columns[index]
+            self.statistics.lines += 1
+            solved = solve_line(clue, [grid[position] for position in positions])
+            if solved is None:
+                raise Contradiction(f"{kind} {index + 1}")
+            self.statistics.changed += any(value != grid[position] for position, value in zip(positions, solved))
+            for (x, y), value in zip(positions, solved):
+                if value != grid[x, y]:
+                    grid[x, y] = value
+                    crossing = ("column", x) if kind == "row" else ("row", y)
+                    if crossing not in queued:
+                        queued.add(crossing)
+                        queue.append(crossing)
+
+    def search(self, grid: Grid, found: List[Grid], limit: int):
+        unknown = grid.find(UNKNOWN)
+        if not unknown:
+            found.append(grid)
+            return
+        x, y = unknown[0]
+        self.statistics.guesses += 1
+        for value in (FILLED, EMPTY):
+            trial = grid.copy()
+            trial[x, y] = value
+            try:
+                self.propagate(trial, deque([("row", y), ("column", x)]))
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, limit: int = 2) -> List[Grid]:
+        """
+        Up to `limit` solutions. Raises Contradiction, naming the line, if the
+        clues clash before any guess.
+        """
+        grid = Grid.filled(self.puzzle.width, self.puzzle.height, UNKNOWN)
+        lines = [("row", y) for y in range(self.puzzle.height)] + [("column", x) for x in range(self.puzzle.width)]
+        self.propagate(grid, deque(lines))
+        found: List[Grid] = []
+        self.search(grid, found, limit)
+        return found
+
+
+################################################################################
+###                                  Rating                                  ###
+################################################################################
+
+
+# Typical uses: a few dozen lines that fill in cells, and any probing at all is a step up
+SCALE = Scale([("lines", 30), ("probing", 3)], guesses=5)
+
+
+def probe(solver: Solver, grid: Grid) -> bool:
+    """Settle the first unknown cell that, tried one way, leads the lines to a contradiction."""
+    for x, y in grid.find(UNKNOWN):
+        for value, other in ((FILLED, EMPTY), (EMPTY, FILLED)):
+            trial = grid.copy()
+            trial[x, y] = value
+            try:
+                Solver(solver.puzzle).propagate(trial, deque([("row", y), ("column", x)]))
+            except Contradiction:
+                grid[x, y] = other
+                solver.propagate(grid, deque([("row", y), ("column", x)]))
+                return True
+    return False
+
+
+def rate(puzzle: Puzzle) -> Rating:
+    """
+    Solve the way a person does: every line as far as it goes, then a cell
+    probed both ways, and a guess only when that gets stuck too. The lines'
+    uses are those that filled in a cell. Raises Contradiction if the clues
+    clash.
+    """
+    solver = Solver(puzzle)
+    rating = Rating()
+    grid = Grid.filled(puzzle.width, puzzle.height, UNKNOWN)
+    lines = [("row", y) for y in range(puzzle.height)] + [("column", x) for x in range(puzzle.width)]
+    solver.propagate(grid, deque(lines))
+    while grid.count(UNKNOWN):
+        if not probe(solver, grid):
+            guesser = Solver(puzzle)
+            guesser.search(grid, [], 1)
+            rating.guess(max(1, guesser.statistics.guesses))
+            break
+        rating.use("probing")
+    if solver.statistics.changed:
+        rating.use("lines", solver.statistics.changed)
+    return rating
+
+
+################################################################################
+###                                Generating                                ###
+################################################################################
+
+
+def clues_of(picture: Grid) -> Puzzle:
+    def runs(cells: List[int]) -> Clue:
+        return [len(run) for run in "".join("#" if cell == FILLED else "." for cell in cells).split(".") if run]
+
+    return Puzzle([runs(row) for row in picture.rows()], [runs(column) for column in picture.columns()])
+
+
+def generate(width: int, height: int, density: float, rng: random.Random) -> Tuple[Puzzle, Grid]:
+    """The clues of a random picture, each cell filled with the chance `density`, that have one solution."""
+    while True:
+        picture = Grid([[FILLED if rng.random() < density else EMPTY for _ in range(width)] for _ in range(height)])
+        puzzle = clues_of(picture)
+        try:
+            if len(Solver(puzzle).solutions(limit=2)) == 1:
+                return puzzle, picture
+        except Contradiction:
+            pass
+
+
+def check(count: int, width: int, height: int, rng: random.Random) -> bool:
+    """Generate puzzles, write and read back their clues, and solve them again: each must have one solution,
+    the picture they came from."""
+    for number in range(1, count + 1):
+        puzzle, picture = generate(width, height, rng.uniform(*DENSITIES), rng)
+        problems = []
+        read = parse(to_text(puzzle))
+        if (read.rows, read.columns) != (puzzle.rows, puzzle.columns):
+            problems.append("the clues read back are not the ones written")
+        try:
+            found = Solver(read).solutions(limit=2)
+        except Contradiction as e:
+            found = []
+            problems.append(f"no solution: {e}")
+        if len(found) > 1:
+            problems.append("it has more than one solution")
+        elif found:
+            solved = clues_of(found[0])
+            if (solved.rows, solved.columns) != (puzzle.rows, puzzle.columns):
+                problems.append("the solution does not fit the clues")
+            if found[0] != picture:
+                problems.append("the solution is not the picture")
+        elif not problems:
+            problems.append("it has no solution")
+        if problems:
+            print(f"puzzle {number}, {width}x{height}: {'; '.join(problems)}")
+            print(render(puzzle, picture))
+            return False
+    print(f"All {count} generated {width}x{height} puzzles have one solution, and it is the picture")
+    return True
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse_clue(line: str, number: int) -> Clue:
+    words = line.replace(",", " ").split()
+    try:
+        clue = [int(word) for word in words if word != "-"]
+    except ValueError:
+        raise ValueError(f"line {number}: a clue is the lengths of its runs, not '{line.strip()}'") from None
+    return [run for run in clue if run != 0]
+
+
+def parse(text: str) -> Puzzle:
+    sections: List[List[Clue]] = []
+    sizes: Dict[str, int] = {}
+    for number, line in enumerate(text.split("\n"), start=1):
+        stripped = line.strip()
+        if not stripped or stripped.startswith("#"):
+            continue
+        words = stripped.split()
+        key = words[0].lower()
+        if key in ("rows", "columns"):
+            if len(sections) != ("rows", "columns").index(key):
+                raise ValueError(f"line {number}: expected the rows, then the columns")
+            sections.append([])
+        elif key.isalpha():
+            # A .non key: the width and height are checked, and the rest (title, goal, ...) skipped
+            if key in ("width", "height"):
+                if len(words) != 2 or not words[1].isdigit():
+                    raise ValueError(f"line {number}: the {key} is a number, not '{stripped}'")
+                sizes[key] = int(words[1])
+        elif not sections:
+            raise ValueError(f"line {number}: expected 'rows' before the first clue")
+        else:
+            sections[-1].append(parse_clue(stripped, number))
+    if len(sections) != 2 or not sections[0] or not sections[1]:
+        raise ValueError("a puzzle has a 'rows' section and a 'columns' section, each with its clues")
+    for key, clues in (("width", sections[1]), ("height", sections[0])):
+        if key in sizes and sizes[key] != len(clues):
+            raise ValueError(f"the {key} is {sizes[key]}, but there are {len(clues)} clues for it")
+    return Puzzle(sections[0], sections[1])
+
+
+def to_text(puzzle: Puzzle) -> str:
+    """The puzzle in the format above."""
+    clues = [" ".join(map(str, clue)) or "0" for clue in puzzle.rows + puzzle.columns]
+    return "\n".join(["rows"] + clues[:puzzle.height] + ["columns"] + clues[puzzle.height:])
+
+
+def render(puzzle: Puzzle, grid: Grid) -> str:
+    """The grid, with each row's clue on its right and the columns' clues below."""
+    width = max(len(str(run)) for clue in puzzle.columns for run in clue or [0]) + 1
+    symbols = {FILLED: "#", EMPTY: ".", UNKNOWN: "?"}
+    cells = grid.render(lambda value: symbols[value].ljust(width)).split("\n")
+    lines = [row + " " + " ".join(map(str, clue)) for row, clue in zip(cells, puzzle.rows)]
+    for i in range(max(len(clue) for clue in puzzle.columns)):
+        lines.append("".join(str(clue[i]).ljust(width) if i < len(clue) else " " * width
+                             for clue in puzzle.columns).rstrip())
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve, rate and generate nonograms")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="fill in the grid")
+    solve_parser.add_argument("file", type=str, help="the clues, in the format above")
+    solve_parser.add_argument("--stats", action="store_true", help="print the lines solved and the guesses made")
+    rate_parser = subparsers.add_parser("rate", help="rate how hard the puzzle is for a person")
+    rate_parser.add_argument("file", type=str, help="the clues, in the format above")
+    generate_parser = subparsers.add_parser("generate", help="make a puzzle with one solution")
+    generate_parser.add_argument("--width", type=int, default=10)
+    generate_parser.add_argument("--height", type=int, default=10)
+    generate_parser.add_argument("--density", type=float, default=None,
+                                 help="the share of the cells filled in (default: 0.45 to 0.65, at random)")
+    generate_parser.add_argument("--difficulty", choices=LEVELS, default=None,
+                                 help="only keep puzzles that rate at this level")
+    add_seed_argument(generate_parser)
+    check_parser = subparsers.add_parser("check", help="generate puzzles and check that each solves to its picture")
+    check_parser.add_argument("--count", type=int, default=20)
+    check_parser.add_argument("--width", type=int, default=10)
+    check_parser.add_argument("--height", type=int, default=10)
+    add_seed_argument(check_parser)
+    args = parser.parse_args()
+
+    if args.command == "check":
+        if not 1 <= args.width <= 40 or not 1 <= args.height <= 40:
+            parser.exit(1, "error: a grid is 1 to 40 cells each way\n")
+        if not check(args.count, args.width, args.height, make_rng(args.seed)):
+            parser.exit(1)
+        return
+    try:
+        if args.command == "generate":
+            if not 1 <= args.width <= 40 or not 1 <= args.height <= 40:
+                raise ValueError("a grid is 1 to 40 cells each way")
+            if args.density is not None and not 0 < args.density < 1:
+                raise ValueError("the density is between 0 and 1")
+            rng = make_rng(args.seed)
+
+            def make() -> Tuple[Puzzle, Grid]:
+                density = args.density if args.density is not None else rng.uniform(*DENSITIES)
+                return generate(args.width, args.height, density, rng)
+
+            (puzzle, picture), rating = generate_rated(make, lambda made: rate(made[0]), SCALE, args.difficulty,
+                                                       ATTEMPTS)
+            print(f"# {SCALE.describe(rating)}")
+            print(to_text(puzzle))
+            print()
+            print(render(puzzle, picture))
+            return
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "rate":
+        try:
+            found = Solver(puzzle).solutions(limit=2)
+        except Contradiction as e:
+            found = []
+            print(f"No solution: {e}")
+        if len(found) == 1:
+            print(SCALE.describe(rate(puzzle)))
+        elif found:
+            print("More than one solution, so no rating")
+        else:
+            parser.exit(1)
+        return
+    solver = Solver(puzzle)
+    try:
+        found = solver.solutions(limit=2)
+    except Contradiction as e:
+        print(f"No solution: {e}")
+        found = None
+    if found is not None:
+        if not found:
+            print("No solution: every guess led to a line that cannot be filled in")
+        else:
+            print(render(puzzle, found[0]))
+            print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,320 @@
+"""
+# Number Theory
+
+Primes, factors and modular arithmetic, shared by the puzzles that need
+them (`euler/euler.py`, `fractran/fractran.py`, ...).
+
+    primes_below(n)         the sieve of Eratosthenes
+    primes_between(lo, hi)  a segmented sieve: the primes in [lo, hi),
+                            crossing off multiples of the primes up to
+                            sqrt(hi) one window at a time, so the memory is
+                            the window's and not hi's
+    is_prime(n)             Miller-Rabin with the first thirteen primes as
+                            bases, which is never wrong below
+                            3317044064679887385961981 (3.3 * 10^24), and
+                            above it is wrong for no number anyone has found
+    factorize(n)            trial division by the small primes, then
+                            Pollard's rho (Brent's variant) on what is left,
+                            splitting it until every factor is prime
+    divisors(n), totient(n)
+    extended_gcd(a, b)      (g, x, y) with a x + b y = g = gcd(a, b)
+    mod_inverse(a, m)       x with a x = 1 (mod m)
+    crt(residues, moduli)   the Chinese remainder theorem: the x (mod the
+                            lcm of the moduli) with x = r (mod m) for each
+
+`check` tests each of these against a slow, obviously right version on
+random numbers: trial division for the sieves, primality and factors,
+and search for the inverses and remainders.
+
+
+## Usage
+
+    python3 number_theory.py factor 600851475143 2^67-1
+    python3 number_theory.py primes 1000000000 1000000100
+    python3 number_theory.py check --count 2000 --seed 1
+"""
+
+import argparse
+import math
+import os
+import random
+import re
+import sys
+from typing import Dict, Iterator, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+SMALL_PRIMES: List[int] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41]
+TRIAL_LIMIT: int = 1000
+SEGMENT: int = 1 << 16
+NUMBER = re.compile(r"(\d+)(?:\^(\d+))?([+-]\d+)?")
+
+
+################################################################################
+###                                  Primes                                  ###
+################################################################################
+
+
+def primes_below(n: int) -> List[int]:
+    """The sieve of Eratosthenes."""
+    if n < 3:
+        return []
+    sieve = bytearray([1]) * n
+    sieve[0] = sieve[1] = 0
+    for p in range(2, math.isqrt(n - 1) + 1):
+        if sieve[p]:
+            sieve[p * p::p] = bytes(len(range(p * p, n, p)))
+    return [i for i, is_prime in enumerate(sieve) if is_prime]
+
+
+def primes_between(low: int, high: int, segment: int = SEGMENT) -> Iterator[int]:
+    """The primes p with low <= p < high, by a segmented sieve."""
+    low = max(low, 2)
+    base = primes_below(math.isqrt(max(high - 1, 0)) + 1)
+    for start in range(low, high, segment):
+        end = min(start + segment, high)
+        sieve = bytearray([1]) * (end - start)
+        for p in base:
+            if p * p >= end:
+                break
+            first = max(p * p, (start + p - 1) // p * p)
+            sieve[first - start::p] = bytes(len(range(first, end, p)))
+        yield from (start + i for i, is_prime in enumerate(sieve) if is_prime)
+
+
+def is_prime(n: int) -> bool:
+    if n < 2:
+        return False
+    for p in SMALL_PRIMES:
+        if n % p == 0:
+            return n == p
+    d, s = n - 1, 0
+    while d % 2 == 0:
+        d, s = d // 2, s + 1
+    for a in SMALL_PRIMES:
+        x = pow(a, d, n)
+        if x in (1, n - 1):
+            continue
+        for _ in range(s - 1):
+            x = x * x % n
+            if x == n - 1:
+                break
+        else:
+            return False
+    return True
+
+
+################################################################################
+###                                 Factors                                  ###
+################################################################################
+
+
+def pollard_rho(n: int, rng: random.Random) -> int:
+    """A factor of the composite n, other than 1 and n, by Brent's variant of Pollard's rho."""
+    if n % 2 == 0:
+        return 2
+    while True:
+        c, y, m = rng.randrange(1, n), rng.randrange(0, n), 128
+        g, r, q = 1, 1, 1
+        while g == 1:
+            x = y
+            for _ in range(r):
+                y = (y * y + c) % n
+            k = 0
+            while k < r and g == 1:
+                saved = y
+                for _ in range(min(m, r - k)):
+                    y = (y * y + c) % n
+                    q = q * abs(x - y) % n
+                g = math.gcd(q, n)
+                k += m
+            r *= 2
+        if g == n:
+            # The batch overshot: redo it one step at a time
+            g = 1
+            while g == 1:
+                saved = (saved * saved + c) % n
+                g = math.gcd(abs(x - saved), n)
+        if g != n:
+            return g
+
+
+def factorize(n: int, rng: Optional[random.Random] = None) -> Dict[int, int]:
+    """Each prime factor of n, with its multiplicity."""
+    if n < 1:
+        raise ValueError(f"{n} has no prime factorization")
+    rng = rng or random.Random(0)
+    factors: Dict[int, int] = {}
+    for p in primes_below(TRIAL_LIMIT):
+        while n % p == 0:
+            factors[p] = factors.get(p, 0) + 1
+            n //= p
+    left = [n] if n > 1 else []
+    while left:
+        m = left.pop()
+        if is_prime(m):
+            factors[m] = factors.get(m, 0) + 1
+        else:
+            d = pollard_rho(m, rng)
+            left += [d, m // d]
+    return dict(sorted(factors.items()))
+
+
+def divisors(n: int) -> List[int]:
+    result = [1]
+    for p, k in factorize(n).items():
+        result = [d * p ** e for d in result for e in range(k + 1)]
+    return sorted(result)
+
+
+def totient(n: int) -> int:
+    result = n
+    for p in factorize(n):
+        result = result // p * (p - 1)
+    return result
+
+
+################################################################################
+###                           Modular Arithmetic                             ###
+################################################################################
+
+
+def extended_gcd(a: int, b: int) -> Tuple[int, int, int]:
+    x0, y0, x1, y1 = 1, 0, 0, 1
+    while b:
+        q = a // b
+        a, b = b, a - q * b
+        x0, x1 = x1, x0 - q * x1
+        y0, y1 = y1, y0 - q * y1
+    return (a, x0, y0) if a >= 0 else (-a, -x0, -y0)
+
+
+def mod_inverse(a: int, m: int) -> int:
+    g, x, _ = extended_gcd(a, m)
+    if g != 1:
+        raise ValueError(f"{a} has no inverse mod {m}: they share the factor {g}")
+    return x % m
+
+
+def crt(residues: List[int], moduli: List[int]) -> Optional[Tuple[int, int]]:
+    """(x, the lcm of the moduli), with x = r (mod m) for each r and m, or None if there is no such x."""
+    x, modulus = 0, 1
+    for r, m in zip(residues, moduli):
+        g, p, _ = extended_gcd(modulus, m)
+        if (r - x) % g:
+            return None
+        x += (r - x) // g * p % (m // g) * modulus
+        modulus = modulus // g * m
+        x %= modulus
+    return x, modulus
+
+
+################################################################################
+###                                  Checks                                  ###
+################################################################################
+
+
+def naive_is_prime(n: int) -> bool:
+    return n >= 2 and all(n % d for d in range(2, math.isqrt(n) + 1))
+
+
+def check(count: int, rng: random.Random) -> bool:
+    """Test everything above against slow versions, on random numbers."""
+    problems: List[str] = []
+
+    def expect(ok: bool, what: str):
+        if not ok and len(problems) < 10:
+            problems.append(what)
+
+    naive = [n for n in range(20_000) if naive_is_prime(n)]
+    expect(primes_below(20_000) == naive, "primes_below(20000) is not the primes by trial division")
+    for _ in range(count // 100 + 1):
+        low, high, segment = rng.randrange(20_000), rng.randrange(20_000), rng.randrange(1, 500)
+        expect(list(primes_between(low, high, segment)) == [p for p in naive if low <= p < high],
+               f"primes_between({low}, {high}, {segment}) is wrong")
+    for n in range(20_000):
+        expect(is_prime(n) == naive_is_prime(n), f"is_prime({n}) is wrong")
+    # Carmichael numbers, and strong pseudoprimes to all the bases up to 23, and up to 37
+    for n in [561, 1105, 1729, 2465, 3215031751, 3825123056546413051, 318665857834031151167461]:
+        expect(not is_prime(n), f"is_prime({n}) says the composite is prime")
+    for p in [2 ** 61 - 1, 2 ** 89 - 1, 2 ** 127 - 1]:
+        expect(is_prime(p), f"is_prime({p}) says the Mersenne prime is composite")
+
+    for _ in range(count):
+        n = rng.randrange(1, 10 ** rng.randint(1, 12))
+        factors = factorize(n, rng)
+        expect(math.prod(p ** k for p, k in factors.items()) == n and all(map(naive_is_prime, factors)),
+               f"factorize({n}) = {factors}")
+        if n < 10 ** 6:
+            expect(divisors(n) == [d for d in range(1, n + 1) if n % d == 0], f"divisors({n}) is wrong")
+        if n < 10 ** 4:
+            expect(totient(n) == sum(math.gcd(n, k) == 1 for k in range(1, n + 1)), f"totient({n}) is wrong")
+    for _ in range(count // 10):
+        p, q = rng.choice(naive[1000:]), rng.choice(naive[1000:])
+        expect(factorize(p * q) == dict(sorted(((p, 2),) if p == q else ((p, 1), (q, 1)))),
+               f"factorize({p * q}) misses {p} x {q}")
+
+    for _ in range(count):
+        a, m = rng.randrange(-1000, 1000), rng.randrange(1, 300)
+        g, x, y = extended_gcd(a, m)
+        expect(g == math.gcd(a, m) and a * x + m * y == g, f"extended_gcd({a}, {m}) = {(g, x, y)}")
+        inverses = [x for x in range(m) if a * x % m == 1 % m]
+        try:
+            expect([mod_inverse(a, m)] == inverses[:1], f"mod_inverse({a}, {m}) is wrong")
+        except ValueError:
+            expect(not inverses, f"mod_inverse({a}, {m}) found no inverse, but there is {inverses}")
+        moduli = [rng.randrange(1, 30) for _ in range(rng.randint(1, 3))]
+        residues = [rng.randrange(m) for m in moduli]
+        modulus = math.lcm(*moduli)
+        found = [x for x in range(modulus) if all(x % m == r for r, m in zip(residues, moduli))]
+        expect(crt(residues, moduli) == ((found[0], modulus) if found else None),
+               f"crt({residues}, {moduli}) = {crt(residues, moduli)}")
+
+    for problem in problems:
+        print(problem)
+    if not problems:
+        print(f"All {count} random cases agree with the naive versions")
+    return not problems
+
+
+def parse_number(text: str) -> int:
+    """A whole number, written out or as a power, like 2^67-1."""
+    match = NUMBER.fullmatch(text.replace(" ", ""))
+    if match is None:
+        raise argparse.ArgumentTypeError(f"expected a number like 91 or 2^67-1, not {text!r}")
+    base, exponent, offset = match.groups()
+    return int(base) ** int(exponent or 1) + int(offset or 0)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Primes, factors, and modular arithmetic")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    factor_parser = subparsers.add_parser("factor", help="factorize numbers")
+    factor_parser.add_argument("numbers", type=parse_number, nargs="+", help="like 91, or 2^67-1")
+    primes_parser = subparsers.add_parser("primes", help="list the primes in a range")
+    primes_parser.add_argument("low", type=parse_number)
+    primes_parser.add_argument("high", type=parse_number, help="exclusive")
+    check_parser = subparsers.add_parser("check", help="test against naive versions")
+    check_parser.add_argument("--count", type=int, default=1000, help="random cases of each kind")
+    add_seed_argument(check_parser)
+    args = parser.parse_args()
+
+    if args.command == "factor":
+        for n in args.numbers:
+            try:
+                factors = factorize(n)
+            except ValueError as e:
+                parser.exit(1, f"error: {e}\n")
+            terms = (str(p) if k == 1 else f"{p}^{k}" for p, k in factors.items())
+            print(f"{n} = {' x '.join(terms) or '1'}" + (" (prime)" if is_prime(n) else ""))
+    elif args.command == "primes":
+        for p in primes_between(args.low, args.high):
+            print(p)
+    elif not check(args.count, make_rng(args.seed)):
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
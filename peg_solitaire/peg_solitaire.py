@@ -0,0 +1,355 @@
+"""
+# Peg Solitaire
+
+A board of holes, all but one with a peg in. A peg jumps over a peg next
+to it (across or down, not diagonally) into an empty hole just beyond,
+and the peg jumped over is taken off. The aim is to finish with one peg,
+in the hole that was empty at the start.
+
+    English (33 holes)          European (37 holes)
+
+        o o o                       o o o
+        o o o                     o o o o o
+    o o o o o o o               o o o o o o o
+    o o o . o o o               o o o . o o o
+    o o o o o o o               o o o o o o o
+        o o o                     o o o o o
+        o o o                       o o o
+
+Holes are named by column letter and row number from the top left: the
+middle is d4. No game on the European board with one hole empty can end
+in that same hole, so it starts with c1 empty and finishes in e1.
+
+
+## The Rule of Three
+
+Colour the holes in three colours along the diagonals one way, so that
+every jump takes a peg from each of two colours and adds one to the
+third: then whether each count of pegs is odd or even flips all
+together, and the pattern of odd and even stays the same up to that
+flip. The same goes for the diagonals the other way. A finish whose
+patterns do not match the start's cannot be reached, and is turned down
+before searching.
+
+
+## Solving
+
+Depth-first over the jumps, with the board packed into a number, a bit a
+hole.
+
+    dead            Every position that has been shown to lead nowhere
+                    is kept in a set, so that it is not searched again by
+                    another order of the same jumps, and so are its
+                    rotations and reflections: each is stored in one form,
+                    the smallest number among its symmetric copies (under
+                    the symmetries that keep the finish where it is).
+    endings         The game played backwards from the finish, for
+                    --depth jumps: every position with that many pegs and
+                    one more that can be finished. A position with that
+                    many pegs is then looked up, not searched.
+    order           Jumps are tried by how little they give up of a pagoda
+                    function, a weight on the holes that no jump can
+                    raise: here the golden ratio to the power of minus the
+                    distance to the finish, so that pegs are kept near it.
+
+
+## The Format
+
+A custom board is rows of o (a peg), . (an empty hole), and - or a
+space (no hole). Lines starting with # are comments.
+
+
+## Usage
+
+    python3 peg_solitaire.py
+    python3 peg_solitaire.py --board european --stats
+    python3 peg_solitaire.py --empty c3 --finish f3
+    python3 peg_solitaire.py --empty d1 --finish any
+    python3 peg_solitaire.py --file boards/wiegleb.txt --stats
+    python3 peg_solitaire.py --animate --delay 0.2
+"""
+
+import argparse
+import time
+from typing import Dict, List, Optional, Set, Tuple
+
+
+CLEAR_SCREEN: str = "\x1b[H\x1b[2J"
+PEG, HOLE, NONE = "o", ".", "-"
+BOARDS: Dict[str, List[str]] = {
+    "english": ["--ooo--",
+                "--ooo--",
+                "ooooooo",
+                "ooo.ooo",
+                "ooooooo",
+                "--ooo--",
+                "--ooo--"],
+    "european": ["--.oo--",
+                 "-ooooo-",
+                 "ooooooo",
+                 "ooooooo",
+                 "ooooooo",
+                 "-ooooo-",
+                 "--ooo--"],
+}
+FINISHES: Dict[str, str] = {"english": "d4", "european": "e1"}
+GOLDEN: float = (5 ** 0.5 - 1) / 2
+Cell = Tuple[int, int]      # (column, row)
+Jump = Tuple[int, int, int]     # (from, over, to), as bits
+
+
+class Board:
+    def __init__(self, rows: List[str]):
+        self.width = max(len(row) for row in rows)
+        self.height = len(rows)
+        self.holes = 0
+        self.start = 0
+        for y, row in enumerate(rows):
+            for x, c in enumerate(row):
+                if c in (PEG, HOLE):
+                    self.holes |= self.bit(x, y)
+                if c == PEG:
+                    self.start |= self.bit(x, y)
+        if not self.holes:
+            raise ValueError("the board has no holes")
+        self.cells: List[Cell] = [(x, y) for y in range(self.height) for x in range(self.width)
+                                  if self.holes & self.bit(x, y)]
+        self.jumps: List[Jump] = []
+        for x, y in self.cells:
+            for dx, dy in ((1, 0), (-1, 0), (0, 1), (0, -1)):
+                over, to = self.bit(x + dx, y + dy), self.bit(x + 2 * dx, y + 2 * dy)
+                if self.holes & over and self.holes & to:
+                    self.jumps.append((self.bit(x, y), over, to))
+
+    def bit(self, x: int, y: int) -> int:
+        return 1 << (y * self.width + x) if 0 <= x < self.width and 0 <= y < self.height else 0
+
+    def cell(self, bit: int) -> Cell:
+        y, x = divmod(bit.bit_length() - 1, self.width)
+        return x, y
+
+    def name(self, bit: int) -> str:
+        x, y = self.cell(bit)
+        return f"{chr(ord('a') + x)}{y + 1}"
+
+    def parse_hole(self, text: str) -> int:
+        text = text.strip().lower()
+        bit = self.bit(ord(text[0]) - ord("a"), int(text[1:]) - 1) if len(text) >= 2 and text[1:].isdigit() else 0
+        if not self.holes & bit:
+            raise ValueError(f"{text!r} is not a hole on the board (holes are like d4)")
+        return bit
+
+    def symmetries(self) -> List[Dict[int, int]]:
+        """The rotations and reflections that map the holes onto themselves, as where each hole's bit goes."""
+        w, h = self.width - 1, self.height - 1
+        maps = [lambda x, y: (x, y), lambda x, y: (w - x, y), lambda x, y: (x, h - y), lambda x, y: (w - x, h - y),
+                lambda x, y: (y, x), lambda x, y: (h - y, x), lambda x, y: (y, w - x), lambda x, y: (h - y, w - x)]
+        found = []
+        for f in maps:
+            image = {self.bit(x, y): self.bit(*f(x, y)) for x, y in self.cells}
+            if all(self.holes & bit for bit in image.values()):
+                found.append(image)
+        return found
+
+    def position_class(self, pegs: int) -> Tuple[int, int]:
+        """Which counts of pegs on each colour of diagonal are odd, up to flipping all three, both ways."""
+        result = []
+        for sign in (1, -1):
+            odd = [0, 0, 0]
+            for x, y in self.cells:
+                if pegs & self.bit(x, y):
+                    odd[(x + sign * y) % 3] ^= 1
+            if odd[0]:
+                odd = [1 - o for o in odd]
+            result.append(2 * odd[1] + odd[2])
+        return result[0], result[1]
+
+    def render(self, pegs: int, highlight: int = 0) -> str:
+        rows = [[" "] * self.width for _ in range(self.height)]
+        for x, y in self.cells:
+            bit = self.bit(x, y)
+            rows[y][x] = "*" if highlight & bit else PEG if pegs & bit else HOLE
+        return "\n".join("    " + " ".join(row).rstrip() for row in rows)
+
+
+class Statistics:
+    def __init__(self):
+        self.positions = 0
+        self.pruned = 0
+        self.endings = 0
+
+    def __str__(self) -> str:
+        return (f"{self.positions} positions searched, {self.pruned} pruned as dead, "
+                f"{self.endings} endings run back from the finish")
+
+
+class Solver:
+    def __init__(self, board: Board, start: int, finish: Optional[int], depth: int):
+        """finish: the bit of the hole the last peg must be in, or None for anywhere."""
+        self.board = board
+        self.start = start
+        # the finishes that the rule of three allows
+        self.finishes = [board.bit(x, y) for x, y in board.cells if finish in (None, board.bit(x, y))
+                         and board.position_class(board.bit(x, y)) == board.position_class(start)]
+        symmetries = [s for s in board.symmetries() if all(s[f] == f for f in self.finishes)]
+        # for each symmetry, for each byte of the board, where the bits of that byte go
+        chunks = (board.holes.bit_length() + 7) // 8
+        self.tables = [[[sum(s.get(1 << (8 * chunk + b), 0) for b in range(8) if value >> b & 1)
+                         for value in range(256)] for chunk in range(chunks)] for s in symmetries]
+        weights = {board.bit(x, y): max((GOLDEN ** self.distance(board.bit(x, y), f) for f in self.finishes),
+                                        default=0) for x, y in board.cells}
+        self.order = sorted(board.jumps, key=lambda j: weights[j[0]] + weights[j[1]] - weights[j[2]])
+        self.depth = max(0, min(depth, bin(start).count("1") - 1))
+        self.endings: List[Dict[int, Optional[Jump]]] = []
+        self.dead: Set[int] = set()
+        self.statistics = Statistics()
+
+    def distance(self, a: int, b: int) -> int:
+        (ax, ay), (bx, by) = self.board.cell(a), self.board.cell(b)
+        return abs(ax - bx) + abs(ay - by)
+
+    def canonical(self, pegs: int) -> int:
+        best = pegs
+        for table in self.tables:
+            image, rest, chunk = 0, pegs, 0
+            while rest:
+                image |= table[chunk][rest & 0xFF]
+                rest >>= 8
+                chunk += 1
+            best = min(best, image)
+        return best
+
+    def run_back(self):
+        """The endings: for each number of jumps back from a finish, its positions, with the jump toward it."""
+        layer: Dict[int, Optional[Jump]] = {f: None for f in self.finishes}
+        self.endings = [layer]
+        for _ in range(self.depth):
+            before: Dict[int, Optional[Jump]] = {}
+            for pegs in layer:
+                for start, over, to in self.board.jumps:
+                    if pegs & to and not pegs & start and not pegs & over:
+                        before.setdefault(pegs ^ start ^ over ^ to, (start, over, to))
+            layer = before
+            self.endings.append(layer)
+            self.statistics.endings += len(layer)
+
+    def solve(self) -> Optional[List[Jump]]:
+        """The jumps from the start to a finish, or None."""
+        if not self.finishes:
+            return None
+        self.run_back()
+        meet = len(self.endings)    # the number of pegs in the last layer of endings
+        jumps: List[Jump] = []
+
+        def search(pegs: int, count: int) -> bool:
+            self.statistics.positions += 1
+            if count == meet:
+                return pegs in self.endings[-1]
+            key = self.canonical(pegs)
+            if key in self.dead:
+                self.statistics.pruned += 1
+                return False
+            for start, over, to in self.order:
+                if pegs & start and pegs & over and not pegs & to:
+                    jumps.append((start, over, to))
+                    if search(pegs ^ start ^ over ^ to, count - 1):
+                        return True
+                    jumps.pop()
+            self.dead.add(key)
+            return False
+
+        pegs = self.start
+        if not search(pegs, bin(pegs).count("1")):
+            return None
+        for start, over, to in jumps:
+            pegs ^= start | over | to
+        for layer in reversed(self.endings[1:]):
+            jump = layer[pegs]
+            jumps.append(jump)
+            pegs ^= jump[0] | jump[1] | jump[2]
+        return jumps
+
+
+def parse(text: str) -> Board:
+    rows = [line.rstrip().replace(" ", NONE) for line in text.split("\n")
+            if line.strip() and not line.strip().startswith("#")]
+    for number, row in enumerate(rows, start=1):
+        bad = set(row) - {PEG, HOLE, NONE}
+        if bad:
+            raise ValueError(f"row {number}: a cell is o, ., or -, not {sorted(bad)[0]!r}")
+    return Board(rows)
+
+
+def describe(board: Board, jumps: List[Jump]) -> str:
+    """The jumps, with a peg's jumps in a row counted as one move."""
+    moves: List[List[str]] = []
+    last = 0
+    for start, _, to in jumps:
+        if start == last:
+            moves[-1].append(board.name(to))
+        else:
+            moves.append([board.name(start), board.name(to)])
+        last = to
+    lines = [f"{len(jumps)} jumps in {len(moves)} moves:"]
+    lines += [f"{i:>3}. {'-'.join(move)}" for i, move in enumerate(moves, start=1)]
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve peg solitaire")
+    parser.add_argument("--board", choices=sorted(BOARDS), default="english")
+    parser.add_argument("--file", type=str, default=None, help="a custom board, in the format above")
+    parser.add_argument("--empty", type=str, default=None, help="the one hole to start empty, like d4")
+    parser.add_argument("--finish", type=str, default=None,
+                        help="the hole to finish in, or 'any' (default: the empty one)")
+    parser.add_argument("--depth", type=int, default=8, help="the jumps to run back from the finish")
+    parser.add_argument("--stats", action="store_true", help="print the positions searched")
+    parser.add_argument("--animate", action="store_true", help="show the board after each jump")
+    parser.add_argument("--delay", type=float, default=0.5, help="the seconds between jumps")
+    args = parser.parse_args()
+
+    try:
+        if args.file is not None:
+            with open(args.file) as f:
+                board = parse(f.read())
+        else:
+            board = Board(BOARDS[args.board])
+        pegs = board.start if args.empty is None else board.holes ^ board.parse_hole(args.empty)
+        if args.finish == "any":
+            finish = None
+        elif args.finish is not None:
+            finish = board.parse_hole(args.finish)
+        elif args.empty is None and args.file is None:
+            finish = board.parse_hole(FINISHES[args.board])
+        else:
+            holes = board.holes ^ pegs
+            if holes & (holes - 1):
+                raise ValueError("more than one hole starts empty, so give --finish")
+            finish = holes
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    print(board.render(pegs))
+    solver = Solver(board, pegs, finish, args.depth)
+    jumps = solver.solve()
+    where = f" finishing in {board.name(finish)}" if finish is not None else ""
+    if not solver.finishes:
+        print(f"No solution{where}, by the rule of three")
+    elif jumps is None:
+        print(f"No solution{where}")
+    else:
+        if args.animate:
+            for i, (start, over, to) in enumerate(jumps, start=1):
+                time.sleep(args.delay)
+                pegs ^= start | over | to
+                print(CLEAR_SCREEN + f"Jump {i}: {board.name(start)}-{board.name(to)}\n" + board.render(pegs, to),
+                      flush=True)
+        print(describe(board, jumps))
+    if args.stats:
+        print(solver.statistics)
+    if jumps is None:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
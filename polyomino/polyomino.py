@@ -0,0 +1,286 @@
+"""
+# Polyomino Tilings
+
+Cover a region of squares with a set of polyominoes (pieces made of
+squares joined edge to edge), every piece used once, every square
+covered once and nothing sticking out. The pentominoes are the classic
+set: twelve pieces of five squares, 60 squares in all, which tile 3x20,
+4x15, 5x12 and 6x10 rectangles, and the 8x8 board with a 2x2 hole in the
+middle (Dana Scott's problem, the first a computer solved, in 1958).
+
+
+## Solving
+
+As exact cover, by the shared dancing links (`dlx/dlx.py`): an option is
+a piece in one place and orientation, covering the piece's name and the
+region's squares under it. The pieces may be turned over and round,
+only turned round (`--one-sided`, for pieces cut from card coloured on
+one side), or neither (`--fixed`). With `--reuse`, a piece can be used
+any number of times, or not at all, and the pieces are not items, only
+the squares. With `--subset`, each piece is used at most once, and
+pieces may be left over: the pieces are secondary items.
+
+On a terminal, each piece is drawn in a colour, different from its
+neighbours' where the greedy choice manages it; otherwise, or with
+`--plain`, the pieces are letters with lines between them, which keeps
+copies of one piece apart.
+
+A count includes every tiling, so a tiling that is a rotation or
+reflection of another, when the region has that symmetry, is counted
+again: Scott's board has 65 tilings, counted 8 times each.
+
+
+## The Formats
+
+A region is WxH for a rectangle, or a file drawing it, with # for a
+square of the region and . for a hole:
+
+    ###.
+    ####
+    .###
+
+A pieces file draws the pieces, each with its own letter and . for
+empty; they can share lines and a piece's squares need not be written
+together, since each letter is one piece:
+
+    AAAA.BB
+    .....BB
+
+Lines starting with # are comments; in a region, where # is a square,
+only those starting with # and a space.
+
+
+## Usage
+
+    python3 polyomino.py 6x10
+    python3 polyomino.py regions/scott.txt --plain
+    python3 polyomino.py 3x20 --count --quiet
+    python3 polyomino.py 8x5 --pieces tetrominoes --reuse --one-sided --count --quiet
+    python3 polyomino.py regions/deficient.txt --pieces pieces/tromino.txt --reuse
+"""
+
+import argparse
+import os
+import sys
+from typing import Dict, List, Optional, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from dlx.dlx import DLX, PENTOMINOES
+from search.search import Progress
+
+
+Cell = Tuple[int, int]                      # (x, y), with y down
+Shape = Tuple[Cell, ...]
+Placement = Tuple[str, Shape]               # a piece's name, and the cells it covers
+TETROMINOES: Dict[str, List[str]] = {
+    "I": ["####"],
+    "L": ["###", "#.."],
+    "O": ["##", "##"],
+    "S": [".##", "##."],
+    "T": ["###", ".#."],
+}
+SETS: Dict[str, Dict[str, List[str]]] = {"pentominoes": PENTOMINOES, "tetrominoes": TETROMINOES}
+# Background colours, from the 256 of a terminal
+PALETTE: List[int] = [196, 33, 226, 46, 201, 208, 51, 93, 118, 214, 160, 27]
+
+
+def normalize(cells: List[Cell]) -> Shape:
+    """The cells moved to touch the top left, in order."""
+    x0, y0 = min(x for x, _ in cells), min(y for _, y in cells)
+    return tuple(sorted((x - x0, y - y0) for x, y in cells))
+
+
+def orientations(cells: List[Cell], rotate: bool = True, reflect: bool = True) -> Set[Shape]:
+    """The shape in every orientation allowed: turned round a quarter at a time, and turned over."""
+    found: Set[Shape] = set()
+    for _ in range(2 if reflect else 1):
+        for _ in range(4 if rotate else 1):
+            found.add(normalize(cells))
+            cells = [(-y, x) for x, y in cells]     # a quarter turn
+        cells = [(-x, y) for x, y in cells]         # a reflection
+    return found
+
+
+def to_dlx(region: Set[Cell], pieces: Dict[str, List[Cell]], rotate: bool = True, reflect: bool = True,
+           reuse: bool = False, subset: bool = False) -> DLX:
+    """Option (name, cells) is the piece covering those cells of the region."""
+    squares = sorted(region, key=lambda cell: (cell[1], cell[0]))
+    if reuse:
+        dlx = DLX(squares)
+    elif subset:
+        dlx = DLX(squares, list(pieces))
+    else:
+        dlx = DLX(list(pieces) + squares)
+    for name, cells in pieces.items():
+        for shape in orientations(cells, rotate, reflect):
+            for dx, dy in squares:
+                # Place the shape's first cell, the top row's leftmost, on each square
+                ox, oy = dx - shape[0][0], dy - shape[0][1]
+                placed = tuple((x + ox, y + oy) for x, y in shape)
+                if all(cell in region for cell in placed):
+                    dlx.add_option((name, placed), list(placed) if reuse else [name, *placed])
+    return dlx
+
+
+def check_areas(region: Set[Cell], pieces: Dict[str, List[Cell]], reuse: bool, subset: bool):
+    area = sum(len(cells) for cells in pieces.values())
+    if not reuse and not subset and area != len(region):
+        raise ValueError(f"the pieces cover {area} squares, but the region has {len(region)}")
+    if subset and area < len(region):
+        raise ValueError(f"the pieces cover only {area} squares, but the region has {len(region)}")
+
+
+################################################################################
+###                                 Rendering                                ###
+################################################################################
+
+
+def colours(tiling: List[Placement]) -> List[int]:
+    """A colour from the palette for each piece, different from its neighbours' where it can be (greedily)."""
+    owner = {cell: i for i, (_, cells) in enumerate(tiling) for cell in cells}
+    chosen: List[int] = []
+    for i, (_, cells) in enumerate(tiling):
+        taken = {chosen[owner[x + dx, y + dy]] for x, y in cells for dx, dy in ((1, 0), (-1, 0), (0, 1), (0, -1))
+                 if owner.get((x + dx, y + dy), i) < i}
+        chosen.append(next((c for c in range(len(PALETTE)) if c not in taken), i % len(PALETTE)))
+    return chosen
+
+
+def walls(tiling: List[Placement], region: Set[Cell]) -> str:
+    """Each square as its piece's letter, with lines between the pieces, so copies of a piece stand apart."""
+    owner = {cell: i for i, (_, cells) in enumerate(tiling) for cell in cells}
+    width = max(x for x, _ in region) + 1
+    height = max(y for _, y in region) + 1
+
+    def across(x: int, y: int) -> bool:     # a line above (x, y)
+        return owner.get((x, y - 1)) != owner.get((x, y))
+
+    def down(x: int, y: int) -> bool:       # a line left of (x, y)
+        return owner.get((x - 1, y)) != owner.get((x, y))
+
+    lines = []
+    for y in range(height + 1):
+        corners = ["+" if across(x - 1, y) or across(x, y) or down(x, y - 1) or down(x, y) else " "
+                   for x in range(width + 1)]
+        lines.append("".join(corners[x] + ("---" if across(x, y) else "   ") for x in range(width)) + corners[width])
+        if y < height:
+            lines.append("".join(("|" if down(x, y) else " ") + (f" {tiling[owner[x, y]][0]} " if (x, y) in owner
+                                                                  else "   ") for x in range(width + 1)))
+    return "\n".join(line.rstrip() for line in lines)
+
+
+def blocks(tiling: List[Placement], region: Set[Cell]) -> str:
+    """Each square as its piece's letter on the piece's colour, for a terminal."""
+    width = max(x for x, _ in region) + 1
+    height = max(y for _, y in region) + 1
+    grid: List[List[str]] = [["  "] * width for _ in range(height)]
+    for (name, cells), c in zip(tiling, colours(tiling)):
+        for x, y in cells:
+            grid[y][x] = f"\x1b[30;48;5;{PALETTE[c]}m{name:2}\x1b[0m"
+    return "\n".join("".join(row).rstrip() for row in grid)
+
+
+################################################################################
+###                                  Reading                                 ###
+################################################################################
+
+
+def parse_region(text: str) -> Set[Cell]:
+    rows = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        if not line.strip() or line.startswith("# "):
+            continue
+        if set(line.rstrip()) - set("#."):
+            raise ValueError(f"line {line_number}: expected # for a square and . for a hole")
+        rows.append(line.rstrip())
+    region = {(x, y) for y, row in enumerate(rows) for x, c in enumerate(row) if c == "#"}
+    if not region:
+        raise ValueError("the region has no squares")
+    return region
+
+
+def parse_pieces(text: str) -> Dict[str, List[Cell]]:
+    pieces: Dict[str, List[Cell]] = {}
+    rows = [line for line in text.split("\n") if not line.startswith("#")]
+    for y, row in enumerate(rows):
+        for x, c in enumerate(row):
+            if c not in ". ":
+                pieces.setdefault(c, []).append((x, y))
+    if not pieces:
+        raise ValueError("no pieces")
+    for name, cells in pieces.items():
+        if not connected(cells):
+            raise ValueError(f"the piece {name}'s squares are not joined edge to edge")
+    return {name: list(normalize(cells)) for name, cells in pieces.items()}
+
+
+def connected(cells: List[Cell]) -> bool:
+    left = set(cells)
+    stack = [left.pop()]
+    while stack:
+        x, y = stack.pop()
+        for neighbour in ((x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)):
+            if neighbour in left:
+                left.remove(neighbour)
+                stack.append(neighbour)
+    return not left
+
+
+def read_region(argument: str) -> Set[Cell]:
+    width, x, height = argument.partition("x")
+    if x and width.isdigit() and height.isdigit():
+        if int(width) < 1 or int(height) < 1:
+            raise ValueError("the rectangle must be at least 1x1")
+        return {(x, y) for y in range(int(height)) for x in range(int(width))}
+    with open(argument) as f:
+        return parse_region(f.read())
+
+
+def read_pieces(argument: str) -> Dict[str, List[Cell]]:
+    if argument in SETS:
+        return {name: [(x, y) for y, row in enumerate(rows) for x, c in enumerate(row) if c == "#"]
+                for name, rows in SETS[argument].items()}
+    with open(argument) as f:
+        return parse_pieces(f.read())
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Tile a region with polyominoes, by exact cover")
+    parser.add_argument("region", type=str, help="WxH for a rectangle, or a file drawing the region")
+    parser.add_argument("--pieces", type=str, default="pentominoes",
+                        help=f"{', '.join(SETS)}, or a file drawing the pieces (default pentominoes)")
+    turns = parser.add_mutually_exclusive_group()
+    turns.add_argument("--one-sided", action="store_true", help="turn the pieces round, but not over")
+    turns.add_argument("--fixed", action="store_true", help="neither turn the pieces round nor over")
+    uses = parser.add_mutually_exclusive_group()
+    uses.add_argument("--reuse", action="store_true", help="use each piece any number of times")
+    uses.add_argument("--subset", action="store_true", help="use each piece at most once")
+    parser.add_argument("--count", action="store_true", help="count the tilings instead of showing one")
+    parser.add_argument("--quiet", action="store_true", help="do not report the progress of --count")
+    parser.add_argument("--plain", action="store_true",
+                        help="lines between the pieces rather than colours, even on a terminal")
+    args = parser.parse_args()
+
+    try:
+        region = read_region(args.region)
+        pieces = read_pieces(args.pieces)
+        check_areas(region, pieces, args.reuse, args.subset)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    dlx = to_dlx(region, pieces, rotate=not args.fixed, reflect=not (args.fixed or args.one_sided),
+                 reuse=args.reuse, subset=args.subset)
+    if args.count:
+        count = dlx.count(None if args.quiet else Progress(label="polyomino"))
+        print(f"{count} tilings")
+    else:
+        tiling: Optional[List[Placement]] = dlx.solve()
+        render = blocks if sys.stdout.isatty() and not args.plain else walls
+        print("No tiling" if tiling is None else render(tiling, region))
+        count = 0 if tiling is None else 1
+    print(f"{dlx.updates} updates")
+    if not count:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,241 @@
+"""
+# Progress
+
+A record of the games played in the interactive modes (`play` in
+tictactoe, connect4, nim, dots_and_boxes, game_2048, sudoku and sliding,
+and `guess` in mastermind), kept in a JSON file between sessions, and a
+command to look back over it.
+
+A game is recorded once, as it first ends: won, lost or drawn, or
+abandoned if it was left (by `:new`, `:quit` or the end of input) after
+at least one move. Taking moves back after the end and playing on does
+not change the record. Each play records
+
+    game        the program, like connect4
+    instance    what was played: the board size, the heaps, the seed
+    result      won, lost, drawn or abandoned
+    moves       the player's moves, counting any taken back
+    seconds     from the start of the game to its end
+    score       where the game has one (2048)
+    started     the date and time, to the second
+
+`--no-record` on a `play` (or `guess`) command keeps its games out of the
+record.
+
+
+## The File
+
+`progress.json` in `$PUZZLES_DATA` if it is set, and otherwise in
+`$XDG_DATA_HOME/puzzles` (by default `~/.local/share/puzzles`). It is a
+list of the plays, oldest first, under a version number:
+
+    {
+        "plays": [
+            {
+                "game": "tictactoe",
+                "instance": "3,3,3",
+                "moves": 5,
+                "result": "drawn",
+                "score": null,
+                "seconds": 41.8,
+                "started": "2026-10-15T14:03:27"
+            }
+        ],
+        "version": 1
+    }
+
+
+## Usage
+
+    python3 progress.py stats
+    python3 progress.py stats --game connect4 --recent 20
+    python3 progress.py clear --game nim
+
+`stats` gives, for each game and instance, the games played and won,
+the fastest win, the win in fewest moves and the best score; and then
+the most recent plays.
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from progress.progress import Session
+"""
+
+import argparse
+import datetime
+import json
+import os
+import sys
+import time
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+
+VERSION: int = 1
+RESULTS: List[str] = ["won", "lost", "drawn", "abandoned"]
+
+
+class Play(NamedTuple):
+    game: str
+    instance: str
+    result: str
+    moves: int
+    seconds: float
+    score: Optional[int]
+    started: str
+
+
+def data_directory() -> str:
+    if os.environ.get("PUZZLES_DATA"):
+        return os.environ["PUZZLES_DATA"]
+    base = os.environ.get("XDG_DATA_HOME") or os.path.expanduser(os.path.join("~", ".local", "share"))
+    return os.path.join(base, "puzzles")
+
+
+def progress_path() -> str:
+    return os.path.join(data_directory(), "progress.json")
+
+
+def load(path: str) -> List[Play]:
+    """The plays in the file, oldest first; none if there is no file yet."""
+    if not os.path.exists(path):
+        return []
+    with open(path) as f:
+        try:
+            data = json.load(f)
+        except json.JSONDecodeError as e:
+            raise ValueError(f"{path} is not JSON: {e}") from None
+    if not isinstance(data, dict) or data.get("version") != VERSION or not isinstance(data.get("plays"), list):
+        raise ValueError(f"{path} is not a version {VERSION} progress file")
+    try:
+        return [Play(**play) for play in data["plays"]]
+    except TypeError:
+        raise ValueError(f"{path} has a play without the fields {', '.join(Play._fields)}") from None
+
+
+def save(path: str, plays: List[Play]):
+    """Write the plays to a new file and then move it over the old, so that a crash leaves one or the other."""
+    os.makedirs(os.path.dirname(path) or ".", exist_ok=True)
+    temporary = path + ".new"
+    with open(temporary, "w") as f:
+        json.dump({"version": VERSION, "plays": [play._asdict() for play in plays]}, f, indent=4, sort_keys=True)
+        f.write("\n")
+    os.replace(temporary, path)
+
+
+def record(play: Play, path: Optional[str] = None):
+    path = path or progress_path()
+    save(path, load(path) + [play])
+
+
+class Session:
+    """The games of one interactive mode, one at a time, each recorded as it ends."""
+
+    def __init__(self, game: str, instance: str, enabled: bool = True):
+        self.game, self.instance = game, instance
+        self.enabled = enabled
+        self.moves = 0
+        self.finished = True
+        self.started = datetime.datetime.now()
+        self.clock = time.monotonic()
+
+    def start(self):
+        """A new game, recording the last one as abandoned if it had begun and not ended."""
+        self.abandon()
+        self.moves = 0
+        self.finished = False
+        self.started = datetime.datetime.now()
+        self.clock = time.monotonic()
+
+    def move(self):
+        self.moves += 1
+
+    def finish(self, result: str, score: Optional[int] = None):
+        """Record the game, unless it has been already (or recording is off)."""
+        if self.finished:
+            return
+        self.finished = True
+        if not self.enabled:
+            return
+        play = Play(self.game, self.instance, result, self.moves, round(time.monotonic() - self.clock, 1), score,
+                    self.started.isoformat(timespec="seconds"))
+        try:
+            record(play)
+        except (OSError, ValueError) as e:
+            print(f"warning: the game was not recorded: {e}", file=sys.stderr)
+
+    def abandon(self, score: Optional[int] = None):
+        if not self.finished and self.moves:
+            self.finish("abandoned", score)
+        self.finished = True
+
+
+################################################################################
+###                                  Stats                                   ###
+################################################################################
+
+
+def duration(seconds: float) -> str:
+    minutes, seconds = divmod(int(round(seconds)), 60)
+    hours, minutes = divmod(minutes, 60)
+    return f"{hours}:{minutes:02}:{seconds:02}" if hours else f"{minutes}:{seconds:02}"
+
+
+def summary(plays: List[Play]) -> str:
+    """A line for each game and instance: the plays, the wins, and the best of them."""
+    groups: Dict[Tuple[str, str], List[Play]] = {}
+    for play in plays:
+        groups.setdefault((play.game, play.instance), []).append(play)
+    rows = [["game", "instance", "played", "won", "lost", "drawn", "fastest win", "fewest moves", "best score"]]
+    for (game, instance), group in sorted(groups.items()):
+        count = {result: sum(1 for play in group if play.result == result) for result in RESULTS}
+        wins = [play for play in group if play.result == "won"]
+        scores = [play.score for play in group if play.score is not None]
+        rows.append([game, instance or "-", str(len(group)), str(count["won"]), str(count["lost"]),
+                     str(count["drawn"]),
+                     duration(min(play.seconds for play in wins)) if wins else "-",
+                     str(min(play.moves for play in wins)) if wins else "-",
+                     str(max(scores)) if scores else "-"])
+    widths = [max(len(row[i]) for row in rows) for i in range(len(rows[0]))]
+    return "\n".join("  ".join(cell.ljust(width) if i < 2 else cell.rjust(width)
+                               for i, (cell, width) in enumerate(zip(row, widths))).rstrip()
+                     for row in rows)
+
+
+def describe(play: Play) -> str:
+    when = play.started.replace("T", " ")[:16]
+    score = f", score {play.score}" if play.score is not None else ""
+    return (f"{when}  {play.game} {play.instance}: {play.result}, {play.moves} move{'s' * (play.moves != 1)}, "
+            f"{duration(play.seconds)}{score}")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Look back over the games played in the interactive modes")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    stats_parser = subparsers.add_parser("stats", help="the plays, wins and bests of each game, and the latest")
+    stats_parser.add_argument("--recent", type=int, default=10, help="how many of the latest plays to list")
+    clear_parser = subparsers.add_parser("clear", help="forget the plays")
+    for p in (stats_parser, clear_parser):
+        p.add_argument("--game", type=str, default=None, help="only this game, like connect4")
+    args = parser.parse_args()
+
+    path = progress_path()
+    try:
+        plays = load(path)
+        chosen = [play for play in plays if args.game is None or play.game == args.game]
+        if args.command == "clear":
+            save(path, [play for play in plays if play not in chosen])
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "clear":
+        print(f"Forgot {len(chosen)} play{'s' * (len(chosen) != 1)}")
+        return
+    if not chosen:
+        print(f"No games{f' of {args.game}' if args.game else ''} recorded in {path}")
+        return
+    print(summary(chosen))
+    if args.recent > 0:
+        print("\nMost recent:")
+        for play in reversed(chosen[-args.recent:]):
+            print("    " + describe(play))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,269 @@
+"""
+# N Queens
+
+Place n queens on an n-by-n board so that no two share a row, a column,
+or a diagonal. A board is the row of the queen in each column.
+
+    count       bitmask backtracking, a row at a time: three integers hold
+                the columns and the two diagonals already attacked, so the
+                free squares of a row are one expression, and each is
+                taken off with `free & -free`. The first queen only tries
+                the left half of its row, and the count is doubled (plus
+                the middle, for odd n), since a mirror image of a solution
+                is a solution.
+    solve       min-conflicts: put each queen in the row of its column that
+                is attacked the least, and then, while any queen is
+                attacked, move a random attacked queen to the row of its
+                column that is attacked the least (starting again if it
+                gets stuck). The start does most of the work: each queen
+                tries a few random rows not yet taken, and takes the first
+                whose diagonals are free, so that even n = 10000 needs only
+                several hundred moves.
+
+Backtracking finds one solution quickly too, for small n, but its time
+grows exponentially (and unevenly), and counting is exponential however it is done.
+`benchmark` times both, for n from 4 up.
+
+
+## Usage
+
+    python3 queens.py solve 8
+    python3 queens.py solve 10000 --seed 1 --quiet
+    python3 queens.py count 12
+    python3 queens.py benchmark
+"""
+
+import argparse
+import os
+import random
+import sys
+import time
+from collections import Counter
+from typing import Iterator, List, Optional
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
+
+Board = List[int]
+SAMPLES: int = 8      # the rows tried for each queen when starting min-conflicts
+
+
+def attacks(board: Board) -> int:
+    """The number of pairs of queens that attack each other."""
+    lines = Counter()
+    for column, row in enumerate(board):
+        lines.update([("row", row), ("down", row - column), ("up", row + column)])
+    return sum(k * (k - 1) // 2 for k in lines.values())
+
+
+################################################################################
+###                              Backtracking                                ###
+################################################################################
+
+
+def boards(n: int) -> Iterator[Board]:
+    """Every solution, by bitmask backtracking (with the columns as the rows of the search)."""
+    full = (1 << n) - 1
+    board: Board = []
+
+    def place(rows: int, down: int, up: int) -> Iterator[Board]:
+        if rows == full:
+            yield list(board)
+            return
+        free = full & ~(rows | down | up)
+        while free:
+            bit = free & -free
+            free ^= bit
+            board.append(bit.bit_length() - 1)
+            yield from place(rows | bit, (down | bit) << 1 & full, (up | bit) >> 1)
+            board.pop()
+
+    yield from place(0, 0, 0)
+
+
+def backtrack(n: int) -> Optional[Board]:
+    return next(boards(n), None)
+
+
+def count(n: int) -> int:
+    full = (1 << n) - 1
+
+    def place(rows: int, down: int, up: int) -> int:
+        if rows == full:
+            return 1
+        total = 0
+        free = full & ~(rows | down | up)
+        while free:
+            bit = free & -free
+            free ^= bit
+            total += place(rows | bit, (down | bit) << 1 & full, (up | bit) >> 1)
+        return total
+
+    if n == 0:
+        return 1
+    total = 0
+    for row in range(n // 2):
+        bit = 1 << row
+        total += 2 * place(bit, bit << 1 & full, bit >> 1)
+    if n % 2:
+        bit = 1 << n // 2
+        total += place(bit, bit << 1 & full, bit >> 1)
+    return total
+
+
+################################################################################
+###                             Min-Conflicts                                ###
+################################################################################
+
+
+class MinConflicts:
+    """How many queens attack each row and diagonal, kept up to date as queens move."""
+
+    def __init__(self, n: int, rng: random.Random):
+        self.n = n
+        self.rng = rng
+        self.moves = 0
+        self.restarts = 0
+        self.start()
+
+    def start(self):
+        """
+        Each queen in the first of a few random rows not yet taken whose
+        diagonals are free too, or the least attacked of them.
+        """
+        n = self.n
+        self.rows = [0] * n
+        self.down = [0] * (2 * n)      # indexed by row - column + n
+        self.up = [0] * (2 * n)        # indexed by row + column
+        self.board: Board = []
+        untaken = list(range(n))
+        for column in range(n):
+            tries = [self.rng.randrange(len(untaken)) for _ in range(SAMPLES)]
+            free = [i for i in tries if self.conflicts(column, untaken[i]) == 0]
+            i = free[0] if free else min(tries, key=lambda i: self.conflicts(column, untaken[i]))
+            untaken[i], untaken[-1] = untaken[-1], untaken[i]
+            self.board.append(untaken.pop())
+            self.add(column, 1)
+
+    def add(self, column: int, delta: int):
+        row = self.board[column]
+        self.rows[row] += delta
+        self.down[row - column + self.n] += delta
+        self.up[row + column] += delta
+
+    def conflicts(self, column: int, row: int) -> int:
+        return self.rows[row] + self.down[row - column + self.n] + self.up[row + column]
+
+    def best_row(self, column: int) -> int:
+        """The least attacked row of the column, breaking ties at random."""
+        counts = [self.conflicts(column, row) for row in range(self.n)]
+        least = min(counts)
+        return self.rng.choice([row for row, k in enumerate(counts) if k == least])
+
+    def attacked(self) -> List[int]:
+        # A queen counts itself once in each of its three lines.
+        return [column for column, row in enumerate(self.board) if self.conflicts(column, row) > 3]
+
+    def solve(self, max_moves: int) -> Optional[Board]:
+        """Start again every 10n + 100 moves, since a small board can get stuck where no one move helps."""
+        since_start = 0
+        attacked = self.attacked()
+        while attacked:
+            if self.moves >= max_moves:
+                return None
+            if since_start > 10 * self.n + 100:
+                self.restarts += 1
+                self.start()
+                since_start = 0
+            column = self.rng.choice(attacked)
+            self.add(column, -1)
+            self.board[column] = self.best_row(column)
+            self.add(column, 1)
+            self.moves += 1
+            since_start += 1
+            attacked = self.attacked()
+        return list(self.board)
+
+
+def min_conflicts(n: int, seed: Optional[int] = None, max_moves: int = 100_000) -> Optional[Board]:
+    """A solution, or None if there is none (n is 2 or 3) or it has not found one in `max_moves`."""
+    if n in (2, 3):
+        return None
+    return MinConflicts(n, make_rng(seed)).solve(max_moves)
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def format_board(board: Board) -> str:
+    n = len(board)
+    return "\n".join(" ".join("Q" if board[c] == r else "." for c in range(n)) for r in range(n))
+
+
+def plural(count: int, noun: str) -> str:
+    return f"{count} {noun}{'' if count == 1 else 's'}"
+
+
+def timed(function, *args):
+    start = time.perf_counter()
+    result = function(*args)
+    return result, time.perf_counter() - start
+
+
+def benchmark(largest_count: int, sizes: List[int]):
+    print(f"{'n':>6}  {'backtrack':>12}  {'min-conflicts':>14}  {'count':>12}  {'solutions':>10}")
+    for n in sizes:
+        _, backtrack_time = timed(backtrack, n) if n <= 28 else (None, None)
+        _, conflicts_time = timed(min_conflicts, n, 0)
+        total, count_time = timed(count, n) if n <= largest_count else (None, None)
+        cells = [
+            "" if backtrack_time is None else f"{backtrack_time * 1000:.1f} ms",
+            f"{conflicts_time * 1000:.1f} ms",
+            "" if count_time is None else f"{count_time * 1000:.1f} ms",
+            "" if total is None else str(total),
+        ]
+        print(f"{n:>6}  {cells[0]:>12}  {cells[1]:>14}  {cells[2]:>12}  {cells[3]:>10}")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="N queens")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="find one solution")
+    solve_parser.add_argument("n", type=int)
+    solve_parser.add_argument("--backtrack", action="store_true", help="backtrack instead of min-conflicts")
+    add_seed_argument(solve_parser)
+    solve_parser.add_argument("--quiet", action="store_true", help="do not print the board")
+    count_parser = subparsers.add_parser("count", help="count every solution")
+    count_parser.add_argument("n", type=int)
+    benchmark_parser = subparsers.add_parser("benchmark", help="time the strategies against each other")
+    benchmark_parser.add_argument("--largest-count", type=int, default=12, help="the largest n to count")
+    args = parser.parse_args()
+
+    if getattr(args, "n", 0) < 0:
+        parser.exit(1, "error: n cannot be negative\n")
+    if args.command == "count":
+        total, elapsed = timed(count, args.n)
+        print(f"{plural(total, 'solution')} ({elapsed:.2f}s)")
+    elif args.command == "solve":
+        if args.backtrack:
+            board, elapsed = timed(backtrack, args.n)
+            how = ""
+        else:
+            solver = MinConflicts(args.n, make_rng(args.seed)) if args.n not in (2, 3) else None
+            board, elapsed = timed(solver.solve, 100_000) if solver else (None, 0.0)
+            how = f", {plural(solver.moves, 'move')}, {plural(solver.restarts, 'restart')}" if solver else ""
+        if board is None:
+            print("No solution")
+        elif not args.quiet:
+            print(format_board(board))
+        if board is not None:
+            print(f"Solved in {elapsed:.2f}s{how}; {attacks(board)} attacks")
+    else:
+        benchmark(args.largest_count, [4, 6, 8, 10, 12, 16, 20, 24, 28, 50, 100, 1000])
+
+
+if __name__ == "__main__":
+    main()
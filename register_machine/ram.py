@@ -0,0 +1,274 @@
+"""
+# Random-Access Machine
+
+The RAM model from algorithms textbooks: an unbounded array of registers
+r0, r1, ... holding arbitrary integers, where r0 is the accumulator, an
+input list, an output list, and a program of simple instructions.
+
+    LOAD x      r0 := x
+    STORE x     x := r0
+    ADD x       r0 := r0 + x
+    SUB x       r0 := r0 - x
+    MUL x       r0 := r0 * x
+    DIV x       r0 := r0 // x   (rounding towards negative infinity)
+    MOD x       r0 := r0 % x
+    READ x      x := the next input
+    WRITE x     output x
+    JUMP l      go to label l
+    JUMPZ l     go to label l if r0 = 0
+    JUMPGTZ l   go to label l if r0 > 0
+    JUMPLTZ l   go to label l if r0 < 0
+    HALT        stop
+
+An operand is written `=5` for the constant 5, `5` for register r5, or
+`*5` for the register whose number is in r5 (indirect addressing, which
+is what makes arrays possible). `STORE` and `READ` cannot take a
+constant.
+
+
+## Assembly Format
+
+One instruction per line, optionally after a `label:`. Instructions are
+case-insensitive, `;` or `#` starts a comment, and running off the end of
+the program halts. For example, this reads n and writes n!:
+
+            READ 1          ; r1 := n
+            LOAD =1
+            STORE 2         ; r2 := 1, the product so far
+    loop:   LOAD 1
+            JUMPZ done
+            MUL 2
+            STORE 2         ; r2 := r2 * r1
+            LOAD 1
+            SUB =1
+            STORE 1         ; r1 := r1 - 1
+            JUMP loop
+    done:   WRITE 2
+            HALT
+
+See `programs/` for more.
+
+
+## Usage
+
+    python3 ram.py programs/factorial.ram --input 10
+    python3 ram.py programs/sort.ram --input 4 3 9 1 4 --trace
+    python3 ram.py programs/sort.ram --listing
+"""
+
+import argparse
+import re
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+
+DEFAULT_MAX_STEPS: int = 1_000_000
+
+ARITHMETIC: Tuple[str, ...] = ("LOAD", "ADD", "SUB", "MUL", "DIV", "MOD", "WRITE")
+STORES: Tuple[str, ...] = ("STORE", "READ")
+JUMPS: Tuple[str, ...] = ("JUMP", "JUMPZ", "JUMPGTZ", "JUMPLTZ")
+OPCODES: Tuple[str, ...] = ARITHMETIC + STORES + JUMPS + ("HALT",)
+
+IMMEDIATE: str = "="
+DIRECT: str = ""
+INDIRECT: str = "*"
+
+
+class AssemblyError(Exception):
+    """The program could not be assembled."""
+
+
+class MachineError(Exception):
+    """The program did something illegal while running."""
+
+
+class Instruction(NamedTuple):
+    op: str
+    mode: Optional[str]     # IMMEDIATE, DIRECT, or INDIRECT (None for jumps and HALT)
+    operand: Optional[int]  # The value, register, or jump target
+    line: int
+
+
+class Program(NamedTuple):
+    instructions: List[Instruction]
+    labels: Dict[str, int]
+
+
+def assemble(text: str) -> Program:
+    """Two passes: collect the labels, then decode each instruction."""
+    lines: List[Tuple[int, str, List[str]]] = []
+    labels: Dict[str, int] = {}
+    errors: List[str] = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        line = re.split(r"[;#]", line, maxsplit=1)[0].strip()
+        while True:
+            match = re.match(r"([A-Za-z_]\w*)\s*:\s*", line)
+            if match is None:
+                break
+            if match.group(1) in labels:
+                errors.append(f"line {line_number}: duplicate label '{match.group(1)}'")
+            labels[match.group(1)] = len(lines)
+            line = line[match.end():]
+        if line:
+            words = line.split()
+            lines.append((line_number, words[0].upper(), words[1:]))
+
+    instructions: List[Instruction] = []
+    for line_number, op, operands in lines:
+        if op not in OPCODES:
+            errors.append(f"line {line_number}: unknown instruction '{op}'")
+            continue
+        expected = 0 if op == "HALT" else 1
+        if len(operands) != expected:
+            errors.append(f"line {line_number}: {op} takes {expected} operand{'s' * (expected != 1)}")
+            continue
+        if op == "HALT":
+            instructions.append(Instruction(op, None, None, line_number))
+        elif op in JUMPS:
+            if operands[0] not in labels:
+                errors.append(f"line {line_number}: unknown label '{operands[0]}'")
+                continue
+            instructions.append(Instruction(op, None, labels[operands[0]], line_number))
+        else:
+            match = re.fullmatch(r"([=*]?)(-?\d+)", operands[0])
+            if match is None:
+                errors.append(f"line {line_number}: bad operand '{operands[0]}'")
+                continue
+            mode, value = match.group(1), int(match.group(2))
+            if mode == IMMEDIATE and op in STORES:
+                errors.append(f"line {line_number}: {op} needs a register, not a constant")
+            elif mode != IMMEDIATE and value < 0:
+                errors.append(f"line {line_number}: there is no register {value}")
+            else:
+                instructions.append(Instruction(op, mode, value, line_number))
+    if errors:
+        raise AssemblyError("\n".join(errors))
+    return Program(instructions, labels)
+
+
+def format_instruction(instruction: Instruction, names: Dict[int, str]) -> str:
+    op, mode, operand, _ = instruction
+    if op == "HALT":
+        return op
+    if op in JUMPS:
+        return f"{op} {names.get(operand, operand)}"
+    return f"{op} {mode}{operand}"
+
+
+def listing(program: Program) -> str:
+    """The assembled program, one numbered instruction per line."""
+    names = {i: label for label, i in program.labels.items()}
+    lines: List[str] = []
+    for i, instruction in enumerate(program.instructions):
+        label = f"{names[i]}:" if i in names else ""
+        lines.append(f"{i:>4}  {label:<10} {format_instruction(instruction, names)}")
+    return "\n".join(lines)
+
+
+class RAM:
+    def __init__(self, program: Program, inputs: List[int]):
+        self.program = program
+        self.registers: Dict[int, int] = {}
+        self.inputs = list(inputs)
+        self.outputs: List[int] = []
+        self.pc = 0
+        self.steps = 0
+        self.halted = False
+
+    def __getitem__(self, register: int) -> int:
+        return self.registers.get(register, 0)
+
+    def address(self, instruction: Instruction) -> int:
+        if instruction.mode == INDIRECT:
+            register = self[instruction.operand]
+            if register < 0:
+                raise MachineError(f"line {instruction.line}: there is no register {register}")
+            return register
+        return instruction.operand
+
+    def value(self, instruction: Instruction) -> int:
+        if instruction.mode == IMMEDIATE:
+            return instruction.operand
+        return self[self.address(instruction)]
+
+    def step(self):
+        if self.pc >= len(self.program.instructions):
+            self.halted = True
+            return
+        instruction = self.program.instructions[self.pc]
+        op = instruction.op
+        self.pc += 1
+        self.steps += 1
+        acc = self[0]
+        if op == "HALT":
+            self.halted = True
+        elif op == "LOAD":
+            self.registers[0] = self.value(instruction)
+        elif op == "STORE":
+            self.registers[self.address(instruction)] = acc
+        elif op == "ADD":
+            self.registers[0] = acc + self.value(instruction)
+        elif op == "SUB":
+            self.registers[0] = acc - self.value(instruction)
+        elif op == "MUL":
+            self.registers[0] = acc * self.value(instruction)
+        elif op in ("DIV", "MOD"):
+            divisor = self.value(instruction)
+            if divisor == 0:
+                raise MachineError(f"line {instruction.line}: division by zero")
+            self.registers[0] = acc // divisor if op == "DIV" else acc % divisor
+        elif op == "READ":
+            if not self.inputs:
+                raise MachineError(f"line {instruction.line}: READ with no input left")
+            self.registers[self.address(instruction)] = self.inputs.pop(0)
+        elif op == "WRITE":
+            self.outputs.append(self.value(instruction))
+        elif (
+            op == "JUMP"
+            or (op == "JUMPZ" and acc == 0)
+            or (op == "JUMPGTZ" and acc > 0)
+            or (op == "JUMPLTZ" and acc < 0)
+        ):
+            self.pc = instruction.operand
+
+    def run(self, max_steps: int, trace: bool = False):
+        names = {i: label for label, i in self.program.labels.items()}
+        while not self.halted and self.steps < max_steps:
+            if trace and self.pc < len(self.program.instructions):
+                instruction = self.program.instructions[self.pc]
+                text = format_instruction(instruction, names)
+                print(f"{self.steps:>8}  {self.pc:>4}  {text:<16} r0={self[0]}")
+            self.step()
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Random-access register machine")
+    parser.add_argument("program", type=str, help="path to the assembly program")
+    parser.add_argument("--input", type=int, nargs="*", default=[], help="integers for READ")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--trace", action="store_true", help="print every instruction as it runs")
+    parser.add_argument("--listing", action="store_true", help="print the assembled program and stop")
+    args = parser.parse_args()
+
+    with open(args.program) as f:
+        text = f.read()
+    try:
+        program = assemble(text)
+        if args.listing:
+            print(listing(program))
+            return
+        ram = RAM(program, args.input)
+        ram.run(args.max_steps, args.trace)
+    except (AssemblyError, MachineError) as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    print(f"Result: {'halt' if ram.halted else 'step limit exceeded'}")
+    print(f"Steps: {ram.steps}")
+    print(f"Output: {' '.join(map(str, ram.outputs))}")
+
+
+if __name__ == "__main__":
+    main()
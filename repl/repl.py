@@ -0,0 +1,76 @@
+"""
+# REPL
+
+The read-eval-print loop shared by the interpreters (lambda calculus,
+combinators, Lisp, and Forth and Deadfish in esolangs/) and the `play`
+modes of the games. Lines starting with `:` are commands (`:help` lists
+them); everything else goes to the program's `evaluate`. A line ending
+in `\\` continues on the next one. Languages that need `:` themselves
+can pick another prefix.
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from repl.repl import Repl
+"""
+
+from typing import Callable, Dict, List, Tuple
+
+
+Command = Callable[[str], None]
+
+
+class Repl:
+    def __init__(self, prompt: str, evaluate: Callable[[str], None], command_prefix: str = ":"):
+        self.prompt = prompt
+        self.evaluate = evaluate
+        self.command_prefix = command_prefix
+        self.commands: Dict[str, Tuple[Command, str]] = {}
+        self.running = False
+        self.add_command("help", lambda _: self.print_help(), "list the commands")
+        self.add_command("quit", lambda _: self.stop(), "leave the REPL")
+
+    def add_command(self, name: str, command: Command, description: str):
+        self.commands[name] = (command, description)
+
+    def print_help(self):
+        width = max(len(name) for name in self.commands) + 1
+        for name, (_, description) in sorted(self.commands.items()):
+            print(f"    {self.command_prefix}{name:<{width}} {description}")
+
+    def stop(self):
+        self.running = False
+
+    def handle(self, line: str):
+        """Run one (complete) line, reporting errors instead of raising them."""
+        line = line.strip()
+        if not line:
+            return
+        try:
+            if line.startswith(self.command_prefix):
+                name, _, argument = line[len(self.command_prefix):].partition(" ")
+                matches: List[str] = [c for c in self.commands if c.startswith(name)]
+                if name in self.commands:
+                    matches = [name]
+                if len(matches) != 1:
+                    print(f"unknown command '{self.command_prefix}{name}' (try {self.command_prefix}help)")
+                    return
+                self.commands[matches[0]][0](argument.strip())
+            else:
+                self.evaluate(line)
+        except ValueError as e:
+            print(f"error: {e}")
+        except RecursionError:
+            print("error: too deeply nested")
+        except KeyboardInterrupt:
+            print("interrupted")
+
+    def run(self):
+        self.running = True
+        while self.running:
+            try:
+                line = input(self.prompt)
+                while line.endswith("\\"):
+                    line = line[:-1] + " " + input("... ")
+            except (EOFError, KeyboardInterrupt):
+                print()
+                break
+            self.handle(line)
@@ -0,0 +1,283 @@
+"""
+# River Crossings
+
+Everyone starts on the near bank and has to get to the far one, a boat
+load at a time, without the boat crossing empty and without ever
+leaving a bank in a state the puzzle forbids. The puzzles are written
+down as rules, not code, and solved by the shared searches
+(`search/search.py`): Dijkstra's algorithm for the cheapest plan, which
+with every crossing costing one is the plan with the fewest crossings.
+
+
+## The Format
+
+One rule per line. Lines starting with `#` are comments, and blank
+lines are ignored.
+
+    entity NAME [COUNT] [time T]
+                    who has to cross: COUNT of them (1 by default),
+                    alike and interchangeable, taking T to cross
+    boat CAPACITY   how many fit in the boat (or on the bridge)
+    rowers NAME...  the boat only crosses with one of these on board
+                    (anyone can row by default)
+    forbid CONDITION
+                    a state no bank may be left in, after a crossing
+    cost crossings | slowest | total
+                    what a crossing costs: one each (the default), the
+                    time of the slowest on board, or everyone's times
+                    added up
+
+A condition is comparisons joined by `and`, each between sums of names
+and numbers, where a name stands for how many of it are on the bank:
+
+    forbid missionary > 0 and cannibal > missionary
+    forbid wolf + goat == 2 and farmer == 0
+
+Comments of the form `# answer: COST` record the cheapest plan, which
+`--test` checks.
+
+
+## Puzzles
+
+    missionaries.txt    three missionaries and three cannibals, and a
+                        boat for two: never leave missionaries
+                        outnumbered (11 crossings)
+    wolf_goat_cabbage.txt
+                        the farmer rows, and the boat holds one more: the
+                        wolf eats the goat, and the goat the cabbage,
+                        when the farmer is not there (7 crossings)
+    bridge.txt          four people with one torch, and a bridge that
+                        holds two, who cross in 1, 2, 5 and 10 minutes:
+                        a pair goes at the slower one's pace (17 minutes)
+    jealous_husbands.txt
+                        three couples, and a boat for two: no wife may
+                        be with another man unless her husband is there
+                        too (11 crossings)
+
+
+## Usage
+
+    python3 river.py puzzles/missionaries.txt
+    python3 river.py puzzles/bridge.txt --stats
+    python3 river.py puzzles/*.txt --test
+"""
+
+import argparse
+import itertools
+import operator
+import os
+import re
+import sys
+from typing import Callable, Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import Result, dijkstra
+
+
+COMPARISONS: Dict[str, Callable[[int, int], bool]] = {
+    "<=": operator.le, ">=": operator.ge, "==": operator.eq, "!=": operator.ne, "<": operator.lt, ">": operator.gt,
+}
+COSTS: List[str] = ["crossings", "slowest", "total"]
+COMPARISON = re.compile(r"\s*(.*?)\s*(<=|>=|==|!=|<|>)\s*(.*?)\s*")
+Sum = List[Tuple[Optional[int], int]]       # (an entity's index, or None for a number; the number)
+State = Tuple[Tuple[int, ...], int]         # (how many of each entity are on the near bank, the boat's bank)
+
+
+class Entity(NamedTuple):
+    name: str
+    count: int
+    time: int
+
+
+class Comparison(NamedTuple):
+    left: Sum
+    compare: Callable[[int, int], bool]
+    right: Sum
+
+
+class Puzzle:
+    def __init__(self):
+        self.entities: List[Entity] = []
+        self.capacity = 0
+        self.rowers: List[int] = []
+        self.forbidden: List[List[Comparison]] = []
+        self.cost = "crossings"
+        self.answers: List[str] = []
+
+    def total(self, terms: Sum, bank: Tuple[int, ...]) -> int:
+        return sum(value if index is None else bank[index] for index, value in terms)
+
+    def allowed(self, bank: Tuple[int, ...]) -> bool:
+        return not any(all(c.compare(self.total(c.left, bank), self.total(c.right, bank)) for c in condition)
+                       for condition in self.forbidden)
+
+    def start(self) -> State:
+        return tuple(entity.count for entity in self.entities), 0
+
+    def is_goal(self, state: State) -> bool:
+        return not any(state[0])
+
+    def successors(self, state: State) -> List[Tuple[Tuple[int, ...], State, float]]:
+        """The boat loads that can cross from the boat's bank, each with the state after it and its cost."""
+        near, side = state
+        here = near if side == 0 else tuple(e.count - n for e, n in zip(self.entities, near))
+        children = []
+        for load in itertools.product(*(range(min(n, self.capacity) + 1) for n in here)):
+            if not 0 < sum(load) <= self.capacity:
+                continue
+            if self.rowers and not any(load[i] for i in self.rowers):
+                continue
+            sign = -1 if side == 0 else 1
+            after = tuple(n + sign * k for n, k in zip(near, load))
+            far = tuple(e.count - n for e, n in zip(self.entities, after))
+            if not (self.allowed(after) and self.allowed(far)):
+                continue
+            children.append((load, (after, 1 - side), self.crossing_cost(load)))
+        return children
+
+    def crossing_cost(self, load: Tuple[int, ...]) -> float:
+        if self.cost == "slowest":
+            return max(e.time for e, k in zip(self.entities, load) if k)
+        if self.cost == "total":
+            return sum(e.time * k for e, k in zip(self.entities, load))
+        return 1
+
+    def describe(self, counts: Tuple[int, ...]) -> str:
+        names = [e.name if k == 1 else f"{e.name} x{k}" for e, k in zip(self.entities, counts) if k]
+        return ", ".join(names) if names else "-"
+
+
+def parse_sum(text: str, names: Dict[str, int], line_number: int) -> Sum:
+    terms: Sum = []
+    for term in text.split("+"):
+        term = term.strip()
+        if term.isdigit():
+            terms.append((None, int(term)))
+        elif term in names:
+            terms.append((names[term], 1))
+        else:
+            raise ValueError(f"line {line_number}: {term!r} is neither a number nor an entity")
+    return terms
+
+
+def parse(text: str) -> Puzzle:
+    puzzle = Puzzle()
+    names: Dict[str, int] = {}
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        stripped = line.strip()
+        answer = re.fullmatch(r"#\s*answer:\s*(.*?)\s*", stripped)
+        if answer is not None:
+            puzzle.answers.append(answer.group(1))
+        if not stripped or stripped.startswith("#"):
+            continue
+        keyword, _, rest = stripped.partition(" ")
+        words = rest.split()
+        if keyword == "entity":
+            if not words or not re.fullmatch(r"[A-Za-z_]\w*", words[0]) or words[0] in names:
+                raise ValueError(f"line {line_number}: expected a new name, as 'entity NAME [COUNT] [time T]'")
+            count, time = 1, 1
+            if len(words) > 1 and words[1].isdigit():
+                count = int(words.pop(1))
+            if len(words) == 3 and words[1] == "time" and words[2].isdigit():
+                time = int(words[2])
+            elif len(words) != 1:
+                raise ValueError(f"line {line_number}: expected 'entity NAME [COUNT] [time T]'")
+            names[words[0]] = len(puzzle.entities)
+            puzzle.entities.append(Entity(words[0], count, time))
+        elif keyword == "boat":
+            if len(words) != 1 or not words[0].isdigit() or int(words[0]) < 1:
+                raise ValueError(f"line {line_number}: expected 'boat CAPACITY', at least 1")
+            puzzle.capacity = int(words[0])
+        elif keyword == "rowers":
+            for name in words:
+                if name not in names:
+                    raise ValueError(f"line {line_number}: {name!r} is not an entity (declare it first)")
+                puzzle.rowers.append(names[name])
+        elif keyword == "forbid":
+            condition = []
+            for part in re.split(r"\s+and\s+", rest):
+                match = COMPARISON.fullmatch(part)
+                if match is None or not match.group(1) or not match.group(3):
+                    raise ValueError(f"line {line_number}: expected a comparison, like 'goat > 0', not {part!r}")
+                condition.append(Comparison(parse_sum(match.group(1), names, line_number),
+                                            COMPARISONS[match.group(2)],
+                                            parse_sum(match.group(3), names, line_number)))
+            puzzle.forbidden.append(condition)
+        elif keyword == "cost":
+            if words not in ([cost] for cost in COSTS):
+                raise ValueError(f"line {line_number}: expected 'cost {' | '.join(COSTS)}'")
+            puzzle.cost = words[0]
+        else:
+            raise ValueError(f"line {line_number}: unknown rule {keyword!r}")
+    if not puzzle.entities:
+        raise ValueError("no entities")
+    if not puzzle.capacity:
+        raise ValueError("no boat (give 'boat CAPACITY')")
+    if not puzzle.allowed(puzzle.start()[0]):
+        raise ValueError("the start is forbidden")
+    return puzzle
+
+
+def solve(puzzle: Puzzle) -> Optional[Result]:
+    return dijkstra(puzzle.start(), puzzle.is_goal, puzzle.successors)
+
+
+def format_cost(cost: float) -> str:
+    return str(int(cost)) if cost == int(cost) else str(cost)
+
+
+def render(puzzle: Puzzle, result: Result) -> str:
+    width = max(len(puzzle.describe(near)) for near, _ in result.states)
+    lines = []
+    total = 0.0
+    for i, (near, side) in enumerate(result.states):
+        far = tuple(e.count - n for e, n in zip(puzzle.entities, near))
+        river = "[boat] ~~~~~~~~        " if side == 0 else "       ~~~~~~~~ [boat] "
+        lines.append(f"     {puzzle.describe(near):>{width}} {river}{puzzle.describe(far)}")
+        if i < len(result.moves):
+            load = result.moves[i]
+            total += puzzle.crossing_cost(load)
+            cost = f"  ({format_cost(total)} so far)" if puzzle.cost != "crossings" else ""
+            arrow = "-->" if side == 0 else "<--"
+            lines.append(f"{i + 1:>3}. {'':>{width}}      {arrow}  {puzzle.describe(load)}{cost}")
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve river crossing puzzles written as rules")
+    parser.add_argument("files", type=str, nargs="+", help="puzzles, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the states expanded")
+    parser.add_argument("--test", action="store_true", help="check the costs against the '# answer:' comments")
+    args = parser.parse_args()
+
+    failures = 0
+    for path in args.files:
+        try:
+            with open(path) as f:
+                puzzle = parse(f.read())
+        except (OSError, ValueError) as e:
+            parser.exit(1, f"error: {path}: {e}\n")
+        result = solve(puzzle)
+        cost = "none" if result is None else format_cost(result.cost)
+        if args.test:
+            ok = puzzle.answers == [cost]
+            failures += not ok
+            print(f"{'ok' if ok else 'FAIL'}  {path}: {cost}" + ("" if ok else f", expected {puzzle.answers}"))
+            continue
+        if len(args.files) > 1:
+            print(f"{path}:")
+        if result is None:
+            print("No solution")
+            failures += 1
+        else:
+            print(render(puzzle, result))
+            unit = "crossings" if puzzle.cost == "crossings" else "in all"
+            print(f"{len(result.moves)} crossings" + ("" if unit == "crossings" else f", {cost} {unit}"))
+        if args.stats and result is not None:
+            print(f"{result.expanded} states expanded")
+    if failures:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
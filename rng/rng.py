@@ -0,0 +1,54 @@
+"""
+# Random Numbers
+
+The seedable generator shared by everything here that does something at
+random: the generators (mazes, Sudoku, Latin squares, scrambles), the
+samples that the self-tests and simulations draw, and the interpreters
+with a random instruction (Befunge's `?`, Thue, CHIP-8's RND). Each
+takes `--seed`, and a seed names what comes out: the same seed gives
+the same maze, puzzle or sample every time.
+
+Without `--seed`, a seed is chosen anyway and the run uses it like any
+other, so nothing is left to chance that cannot be repeated. Set
+`$PUZZLES_SHOW_SEED` to have it printed (on stderr), for a bug report:
+
+    $ PUZZLES_SHOW_SEED=1 python3 maze/maze.py --width 8 --height 4
+    seed: 2838573042
+    ...
+    $ python3 maze/maze.py --width 8 --height 4 --seed 2838573042
+
+A program makes its generator with `make_rng` and its option with
+`add_seed_argument`:
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from rng.rng import add_seed_argument, make_rng
+
+    add_seed_argument(parser, help="for the shuffle")
+    ...
+    rng = make_rng(args.seed)
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Optional
+
+
+SEED_BITS: int = 32
+
+
+def choose_seed() -> int:
+    seed = random.SystemRandom().getrandbits(SEED_BITS)
+    if os.environ.get("PUZZLES_SHOW_SEED"):
+        print(f"seed: {seed}", file=sys.stderr)
+    return seed
+
+
+def make_rng(seed: Optional[int] = None) -> random.Random:
+    """A generator from the seed, or from a new one (see `$PUZZLES_SHOW_SEED`) if there is none."""
+    return random.Random(choose_seed() if seed is None else seed)
+
+
+def add_seed_argument(parser: argparse.ArgumentParser, help: str = "repeat a run (the same seed, the same output)"):
+    parser.add_argument("--seed", type=int, default=None, help=help)
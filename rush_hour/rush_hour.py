@@ -0,0 +1,324 @@
+"""
+# Rush Hour
+
+A 6x6 car park of cars (two cells long) and trucks (three), each parked
+lengthways in a row or a column, and only able to drive forwards and
+backwards along it. The red car, A, is in the third row, and the aim is
+to get it out through the exit at the right end of that row.
+
+    B B I J . .
+    . . I J C C
+    . . I A A M     A has to reach the right edge
+    G D D K . M
+    G H . K L .
+    G H F F L .
+
+
+## The Format
+
+The usual board strings (as in Michael Fogleman's database of every
+interesting position): the 36 cells, row by row, with o (or .) for an
+empty cell, x for a wall, and a letter for each cell of a vehicle, A
+for the red car.
+
+    BBIJ....IJCC..IAAMGDDK.MGH.KL.GHFFL.
+
+A move drives one vehicle any distance, and is written as the vehicle
+and how far it goes: A+2 is A two cells right (for a vehicle in a
+column, down), and M-1 is M one cell up (or left).
+
+
+## Solving
+
+A state is the position of each vehicle along its row or column, which
+is 0 to 4, packed 3 bits a vehicle into one integer: that is the whole
+state, so it is its own hash, and each vehicle's cells at each position
+are bit masks of the 36 cells, so finding the empty cells is a few ORs.
+
+    bfs         breadth first, from `search/search.py`: every move costs
+                the same, so the first solution is the shortest
+    astar       A*, on the moves so far plus a lower bound on the moves
+                left: 1 for A itself, and 1 for each vehicle in the way
+                between A and the exit, since each has to move at least
+                once (the default)
+
+
+## Generating
+
+Every position reachable from a board, its cluster, has the same
+vehicles in the same rows and columns, and moves can always be undone,
+so a breadth-first search back from all the solved positions in the
+cluster gives every position's fewest moves. The hardest position in
+the cluster is the one farthest from any solved one. `generate` parks
+--vehicles vehicles at random, --tries times, finds the hardest position
+of each cluster, and keeps the hardest of all.
+
+
+## Usage
+
+    python3 rush_hour.py solve BBIJ....IJCC..IAAMGDDK.MGH.KL.GHFFL.
+    python3 rush_hour.py solve GBBoLoGHIoLMGHIAAMCCCKoMooJKDDEEJFFo --stats
+    python3 rush_hour.py solve GBBoLoGHIoLMGHIAAMCCCKoMooJKDDEEJFFo --search bfs --stats
+    python3 rush_hour.py solve boards/boards.txt
+    python3 rush_hour.py solve ooooooooooooAAoooooooooooooooooooooo --show
+    python3 rush_hour.py generate --tries 50 --seed 1
+"""
+
+import argparse
+import os
+import random
+import sys
+from collections import deque
+from typing import Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+from search.search import Progress, Result, astar, bfs
+
+
+SIZE: int = 6
+EXIT_ROW: int = 2
+TARGET, EMPTY, WALL = "A", "o", "x"
+SEARCHES: List[str] = ["astar", "bfs"]
+BITS: int = 3
+State = int                 # each vehicle's position along its row or column, 3 bits each
+Move = Tuple[str, int]      # a vehicle, and how far it goes
+
+
+class Board:
+    def __init__(self, text: str):
+        text = text.replace(".", EMPTY)
+        if len(text) != SIZE * SIZE:
+            raise ValueError(f"a board is {SIZE * SIZE} cells, not {len(text)}")
+        cells: Dict[str, List[int]] = {}
+        self.walls = 0
+        for i, c in enumerate(text):
+            if c == WALL:
+                self.walls |= 1 << i
+            elif c.isalpha() and c.isupper():
+                cells.setdefault(c, []).append(i)
+            elif c != EMPTY:
+                raise ValueError(f"{c!r} is not a vehicle (a capital letter), {EMPTY} or {WALL}")
+        if TARGET not in cells:
+            raise ValueError(f"there is no {TARGET}, the red car")
+        self.names = [TARGET] + sorted(name for name in cells if name != TARGET)
+        self.horizontal: List[bool] = []
+        self.masks: List[List[int]] = []    # each vehicle's cells, at each position
+        start = []
+        for name in self.names:
+            places = cells[name]
+            rows, columns = {i // SIZE for i in places}, {i % SIZE for i in places}
+            if len(places) not in (2, 3) or (len(rows) != 1 and len(columns) != 1):
+                raise ValueError(f"{name} is not a straight line of 2 or 3 cells")
+            horizontal = len(rows) == 1
+            step = 1 if horizontal else SIZE
+            if places != list(range(places[0], places[0] + step * len(places), step)):
+                raise ValueError(f"the cells of {name} are not together")
+            first = places[0]
+            position = first % SIZE if horizontal else first // SIZE
+            origin = first - position * step
+            self.horizontal.append(horizontal)
+            self.masks.append([sum(1 << (origin + (p + k) * step) for k in range(len(places)))
+                               for p in range(SIZE - len(places) + 1)])
+            start.append(position)
+        if not self.horizontal[0] or cells[TARGET][0] // SIZE != EXIT_ROW:
+            raise ValueError(f"{TARGET} must lie along row {EXIT_ROW + 1}, the exit's")
+        self.start: State = pack(start)
+        self.goal = len(self.masks[0]) - 1
+        # The cells between each position of A and the exit
+        self.ahead: List[int] = [sum(1 << (EXIT_ROW * SIZE + x) for x in range(p + len(cells[TARGET]), SIZE))
+                                 for p in range(len(self.masks[0]))]
+
+    def positions(self, state: State) -> List[int]:
+        return [(state >> (BITS * i)) & ((1 << BITS) - 1) for i in range(len(self.names))]
+
+    def occupied(self, state: State) -> int:
+        mask = self.walls
+        for i, position in enumerate(self.positions(state)):
+            mask |= self.masks[i][position]
+        return mask
+
+    def is_goal(self, state: State) -> bool:
+        return state & ((1 << BITS) - 1) == self.goal
+
+    def successors(self, state: State) -> List[Tuple[Move, State]]:
+        occupied = self.occupied(state)
+        found = []
+        for i, position in enumerate(self.positions(state)):
+            masks = self.masks[i]
+            others = occupied & ~masks[position]
+            for direction in (-1, 1):
+                p = position + direction
+                while 0 <= p < len(masks) and not masks[p] & others:
+                    found.append(((self.names[i], p - position), state + ((p - position) << (BITS * i))))
+                    p += direction
+        return found
+
+    def heuristic(self, state: State) -> int:
+        """1 for A, and 1 for each vehicle between it and the exit: never more than the moves left."""
+        positions = self.positions(state)
+        if positions[0] == self.goal:
+            return 0
+        ahead = self.ahead[positions[0]]
+        return 1 + sum(1 for i in range(1, len(positions)) if self.masks[i][positions[i]] & ahead)
+
+    def format(self, state: State) -> str:
+        cells = [WALL if self.walls >> i & 1 else EMPTY for i in range(SIZE * SIZE)]
+        for i, position in enumerate(self.positions(state)):
+            for cell in range(SIZE * SIZE):
+                if self.masks[i][position] >> cell & 1:
+                    cells[cell] = self.names[i]
+        return "".join(cells)
+
+    def render(self, state: State) -> str:
+        text = self.format(state).replace(EMPTY, ".")
+        return "\n".join(" ".join(text[y * SIZE:(y + 1) * SIZE]) for y in range(SIZE))
+
+
+def pack(positions: List[int]) -> State:
+    return sum(position << (BITS * i) for i, position in enumerate(positions))
+
+
+def describe(move: Move) -> str:
+    name, distance = move
+    return f"{name}{distance:+d}"
+
+
+def solve(board: Board, search: str, progress: Optional[Progress] = None) -> Optional[Result]:
+    if search == "bfs":
+        return bfs(board.start, board.is_goal, board.successors, progress=progress)
+    return astar(board.start, board.is_goal,
+                 lambda state: [(move, after, 1) for move, after in board.successors(state)],
+                 board.heuristic, progress=progress)
+
+
+################################################################################
+###                                Generating                                ###
+################################################################################
+
+
+def hardest(board: Board) -> Optional[Tuple[State, int]]:
+    """The position of the start's cluster with the most moves to solve, and how many; None if none solves."""
+    seen = {board.start}
+    queue = deque([board.start])
+    solved = []
+    while queue:
+        state = queue.popleft()
+        if board.is_goal(state):
+            solved.append(state)
+        for _, after in board.successors(state):
+            if after not in seen:
+                seen.add(after)
+                queue.append(after)
+    if not solved:
+        return None
+    distance = {state: 0 for state in solved}
+    queue = deque(solved)
+    while queue:
+        state = queue.popleft()
+        for _, after in board.successors(state):
+            if after not in distance:
+                distance[after] = distance[state] + 1
+                queue.append(after)
+    far = max(distance, key=lambda state: (distance[state], -state))
+    return far, distance[far]
+
+
+def park(rng: random.Random, vehicles: int) -> Board:
+    """A at the left of its row, and up to the given count of others parked at random where they fit."""
+    cells = [EMPTY] * (SIZE * SIZE)
+    cells[EXIT_ROW * SIZE] = cells[EXIT_ROW * SIZE + 1] = TARGET
+    names = iter("BCDEFGHIJKLMNOPQRSTUVWXYZ")
+    for _ in range(vehicles):
+        for _ in range(100):
+            length = 3 if rng.random() < 0.25 else 2
+            horizontal = rng.random() < 0.5
+            step = 1 if horizontal else SIZE
+            x = rng.randrange(SIZE - length + 1 if horizontal else SIZE)
+            y = rng.randrange(SIZE if horizontal else SIZE - length + 1)
+            places = [y * SIZE + x + k * step for k in range(length)]
+            # Anything lying along the exit row would be in A's way for good
+            if all(cells[i] == EMPTY for i in places) and not (horizontal and y == EXIT_ROW):
+                name = next(names)
+                for i in places:
+                    cells[i] = name
+                break
+    return Board("".join(cells))
+
+
+def generate(rng: random.Random, tries: int, vehicles: int) -> Tuple[Board, State, int]:
+    best: Optional[Tuple[Board, State, int]] = None
+    for attempt in range(1, tries + 1):
+        board = park(rng, vehicles)
+        found = hardest(board)
+        if found is not None and (best is None or found[1] > best[2]):
+            best = (board, found[0], found[1])
+            print(f"try {attempt}: {board.format(found[0])}, {found[1]} moves", file=sys.stderr)
+    assert best is not None
+    return best
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def read_boards(text: str) -> List[str]:
+    """A board string, or a file of them, one a line (anything after it on the line is ignored)."""
+    if not os.path.exists(text):
+        return [text]
+    with open(text) as f:
+        return [line.split()[0] for line in f if line.strip() and not line.startswith("#")]
+
+
+def report(board: Board, result: Optional[Result], show: bool, stats: bool):
+    print(board.render(board.start))
+    if result is None:
+        print("No solution")
+        return
+    if show:
+        for move, state in zip(result.moves, result.states[1:]):
+            print(f"\n{describe(move)}\n{board.render(state)}")
+    count = len(result.moves)
+    print(f"{count} move{'s' * (count != 1)}, the fewest: {' '.join(map(describe, result.moves))}")
+    if stats:
+        print(f"{result.expanded} positions searched")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve and generate Rush Hour boards")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a board")
+    solve_parser.add_argument("board", type=str, help="a board string, or a file of them, one a line")
+    solve_parser.add_argument("--search", choices=SEARCHES, default="astar")
+    solve_parser.add_argument("--show", action="store_true", help="draw the board after every move")
+    solve_parser.add_argument("--stats", action="store_true", help="print the positions searched")
+    generate_parser = subparsers.add_parser("generate", help="search random boards for a hard position")
+    generate_parser.add_argument("--tries", type=int, default=100, help="the random boards to try")
+    generate_parser.add_argument("--vehicles", type=int, default=12, help="besides A, on each board")
+    add_seed_argument(generate_parser)
+    args = parser.parse_args()
+
+    if args.command == "generate":
+        board, state, moves = generate(make_rng(args.seed), args.tries, args.vehicles)
+        hard = Board(board.format(state))
+        print(hard.format(hard.start))
+        report(hard, solve(hard, "astar"), False, False)
+        return
+    try:
+        boards = [Board(text) for text in read_boards(args.board)]
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    unsolved = 0
+    for i, board in enumerate(boards):
+        if i:
+            print()
+        result = solve(board, args.search)
+        report(board, result, args.show, args.stats)
+        unsolved += result is None
+    if unsolved:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,203 @@
+"""
+# Puzzles as SAT
+
+Reductions from puzzles to CNF, so `sat.py` can solve them: build the
+clauses, solve them, and read the answer back out of the model.
+
+
+## Sudoku
+
+Variable 81r + 9c + d + 1 means "the cell in row r, column c is d + 1".
+Every cell has at least one digit, and no cell, row, column, or box has
+a digit twice; each given digit is a unit clause. (The at-most-once
+clauses for rows, columns, and boxes together with at-least-once for the
+cells are enough; there is no need to say each row has every digit.)
+
+A puzzle is 81 digits, with `.` or `0` for an empty cell, either in one
+line or as 9 rows.
+
+
+## Lights Out
+
+A grid of lights, where pressing a light toggles it and its orthogonal
+neighbours; the goal is to turn every light off. Pressing twice undoes a
+press, and the order does not matter, so a solution is the set of lights
+to press once. Variable v means "press light v", and each light must be
+toggled an odd number of times if it starts on, and even if it starts
+off: an XOR of its own press and its neighbours'. An XOR of k variables
+is the 2^(k-1) clauses that each rule out one assignment of the wrong
+parity.
+
+A grid is rows of `#` (on) and `.` (off). The answer marks the lights to
+press with `X`.
+
+
+## Usage
+
+    python3 puzzles.py sudoku examples/sudoku.txt
+    python3 puzzles.py sudoku 8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..
+    python3 puzzles.py lights-out examples/lights.txt
+    python3 puzzles.py lights-out examples/lights.txt --dimacs > lights.cnf
+"""
+
+import argparse
+import itertools
+import os
+import sys
+from typing import Dict, List, Optional, Sequence, Tuple
+
+from sat import CNF, Clause, Solver, format_dimacs
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid
+
+
+################################################################################
+###                                  Sudoku                                  ###
+################################################################################
+
+
+def sudoku_variable(row: int, column: int, digit: int) -> int:
+    """digit is 1 to 9."""
+    return 81 * row + 9 * column + digit
+
+
+def parse_sudoku(text: str) -> List[List[int]]:
+    digits = [c for c in text if not c.isspace()]
+    if len(digits) != 81 or any(c not in "0123456789." for c in digits):
+        raise ValueError("a Sudoku is 81 digits, with '.' or '0' for the empty cells")
+    values = [0 if c == "." else int(c) for c in digits]
+    return [values[9 * row:9 * row + 9] for row in range(9)]
+
+
+def sudoku_to_cnf(puzzle: List[List[int]]) -> CNF:
+    clauses: List[Clause] = []
+    cells = list(itertools.product(range(9), repeat=2))
+    for row, column in cells:
+        clauses.append([sudoku_variable(row, column, d) for d in range(1, 10)])
+    for row, column in cells:     # at most one digit per cell
+        for a, b in itertools.combinations(range(1, 10), 2):
+            clauses.append([-sudoku_variable(row, column, a), -sudoku_variable(row, column, b)])
+    groups = [[(row, column) for column in range(9)] for row in range(9)]
+    groups += [[(row, column) for row in range(9)] for column in range(9)]
+    groups += [
+        [(3 * box_row + i, 3 * box_column + j) for i in range(3) for j in range(3)]
+        for box_row in range(3) for box_column in range(3)
+    ]
+    for group in groups:
+        for digit in range(1, 10):
+            for (r1, c1), (r2, c2) in itertools.combinations(group, 2):
+                clauses.append([-sudoku_variable(r1, c1, digit), -sudoku_variable(r2, c2, digit)])
+    for row, column in cells:
+        if puzzle[row][column]:
+            clauses.append([sudoku_variable(row, column, puzzle[row][column])])
+    return CNF(729, clauses)
+
+
+def sudoku_from_model(model: Sequence[int]) -> List[List[int]]:
+    grid = [[0] * 9 for _ in range(9)]
+    for literal in model:
+        if literal > 0:
+            row, rest = divmod(literal - 1, 81)
+            column, digit = divmod(rest, 9)
+            grid[row][column] = digit + 1
+    return grid
+
+
+def format_sudoku(grid: List[List[int]]) -> str:
+    lines = []
+    for row in range(9):
+        if row and row % 3 == 0:
+            lines.append("------+-------+------")
+        cells = [str(d) if d else "." for d in grid[row]]
+        lines.append(" | ".join(" ".join(cells[i:i + 3]) for i in range(0, 9, 3)))
+    return "\n".join(lines)
+
+
+################################################################################
+###                                Lights Out                                ###
+################################################################################
+
+
+def xor_clauses(variables: Sequence[int], parity: bool) -> List[Clause]:
+    """The clauses for "an odd number of the variables are true" (or even, if not parity)."""
+    clauses: List[Clause] = []
+    for signs in itertools.product((1, -1), repeat=len(variables)):
+        trues = signs.count(-1)     # the clause is false exactly when the negated variables are true
+        if (trues % 2 == 1) != parity:
+            clauses.append([sign * variable for sign, variable in zip(signs, variables)])
+    return clauses
+
+
+def lights_out_to_cnf(lights: Grid) -> Tuple[CNF, Dict[Tuple[int, int], int]]:
+    variables = {position: i for i, position in enumerate(lights.positions(), start=1)}
+    clauses: List[Clause] = []
+    for position in lights.positions():
+        toggled_by = [variables[position]] + [variables[n] for n in lights.neighbours(position)]
+        clauses += xor_clauses(toggled_by, lights[position])
+    return CNF(len(variables), clauses), variables
+
+
+def press(lights: Grid, presses: Grid) -> Grid:
+    result = lights.copy()
+    for position, pressed in presses.items():
+        if pressed:
+            for toggled in [position] + lights.neighbours(position):
+                result[toggled] = not result[toggled]
+    return result
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def read_puzzle(argument: str) -> str:
+    if os.path.exists(argument):
+        with open(argument) as f:
+            return f.read()
+    return argument
+
+
+def solve_cnf(cnf: CNF) -> Optional[List[int]]:
+    return Solver(cnf.variables, cnf.clauses).solve()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve puzzles by reducing them to SAT")
+    parser.add_argument("puzzle", choices=["sudoku", "lights-out"])
+    parser.add_argument("instance", type=str, help="a file, or the puzzle itself")
+    parser.add_argument("--dimacs", action="store_true", help="print the CNF instead of solving it")
+    args = parser.parse_args()
+
+    text = read_puzzle(args.instance)
+    try:
+        if args.puzzle == "sudoku":
+            puzzle = parse_sudoku(text)
+            cnf = sudoku_to_cnf(puzzle)
+        else:
+            lights = Grid.parse(text, cell=lambda c: c == "#")
+            cnf, variables = lights_out_to_cnf(lights)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.dimacs:
+        print(format_dimacs(cnf, [f"{args.puzzle}, from {args.instance}"]), end="")
+        return
+
+    model = solve_cnf(cnf)
+    if model is None:
+        print("No solution")
+    elif args.puzzle == "sudoku":
+        print(format_sudoku(sudoku_from_model(model)))
+    else:
+        pressed = {abs(literal) for literal in model if literal > 0}
+        presses = lights.map(lambda _: False)
+        for position, variable in variables.items():
+            presses[position] = variable in pressed
+        assert not any(press(lights, presses).cells[y][x] for x, y in lights.positions())
+        print(presses.render(lambda p: "X" if p else "."))
+        print(f"{len(pressed)} press{'es' * (len(pressed) != 1)}")
+
+
+if __name__ == "__main__":
+    main()
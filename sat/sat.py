@@ -0,0 +1,362 @@
+"""
+# SAT
+
+A solver for Boolean satisfiability: given a formula in conjunctive
+normal form (an AND of clauses, each an OR of literals), find values for
+its variables that make it true, or show there are none. Variables are
+numbered from 1, and a literal is a variable or its negation, -v.
+
+    solve(3, [[1, 2], [-1, 3], [-2, -3]])     [-1, 2, -3]
+    solve(1, [[1], [-1]])                     None
+
+
+## The Algorithm
+
+By default, conflict-driven clause learning (CDCL):
+
+    decide      pick an unassigned variable, the one most involved in
+                recent conflicts (VSIDS), and give it its last value
+    propagate   a clause with every literal false but one forces that
+                one (unit propagation, with two watched literals per
+                clause, so only clauses that might have become unit are
+                looked at)
+    learn       when a clause becomes false, work back from it through the
+                implications to the first unique implication point, and
+                add the clause that rules out the cause; then jump back to
+                the level where that clause becomes unit
+
+`--dpll` turns learning off for plain DPLL: the same propagation, but on
+a conflict it undoes the latest decision that has not been flipped yet,
+and flips it.
+
+
+## DIMACS
+
+The input format of the SAT competitions: `c` lines are comments, the
+header `p cnf <variables> <clauses>` comes first, and each clause is its
+literals followed by 0. The answer is printed the same way, as
+`s SATISFIABLE` and `v` lines of the model, or `s UNSATISFIABLE`.
+
+`puzzles.py` turns a few puzzles into CNF, to solve them with this.
+
+
+## Examples
+
+    simple.cnf          3 variables, satisfiable
+    pigeonhole.cnf      5 pigeons in 4 holes, unsatisfiable
+
+
+## Usage
+
+    python3 sat.py examples/simple.cnf
+    python3 sat.py examples/pigeonhole.cnf --stats
+    python3 sat.py examples/pigeonhole.cnf --dpll --stats
+"""
+
+import argparse
+from typing import List, NamedTuple, Optional, Sequence, Tuple
+
+
+Clause = List[int]
+ACTIVITY_DECAY: float = 0.95
+
+
+class CNF(NamedTuple):
+    variables: int
+    clauses: List[Clause]
+
+
+def parse_dimacs(text: str) -> CNF:
+    errors: List[str] = []
+    header: Optional[Tuple[int, int]] = None
+    clauses: List[Clause] = []
+    clause: Clause = []
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        words = line.split()
+        if not words or words[0] == "c":
+            continue
+        if words[0] == "%":     # the end marker of the SATLIB benchmarks
+            break
+        if words[0] == "p":
+            if header is not None:
+                errors.append(f"line {line_number}: a second header")
+            elif len(words) != 4 or words[1] != "cnf" or not words[2].isdigit() or not words[3].isdigit():
+                errors.append(f"line {line_number}: expected 'p cnf <variables> <clauses>'")
+            else:
+                header = int(words[2]), int(words[3])
+            continue
+        if header is None:
+            errors.append(f"line {line_number}: a clause before the 'p cnf' header")
+            break
+        for word in words:
+            try:
+                literal = int(word)
+            except ValueError:
+                errors.append(f"line {line_number}: '{word}' is not a literal")
+                continue
+            if literal == 0:
+                clauses.append(clause)
+                clause = []
+            elif abs(literal) > header[0]:
+                errors.append(f"line {line_number}: variable {abs(literal)} is over the {header[0]} in the header")
+            else:
+                clause.append(literal)
+    if clause:
+        clauses.append(clause)      # a last clause without its 0
+    if header is None and not errors:
+        errors.append("there is no 'p cnf' header")
+    if errors:
+        raise ValueError("\n".join(errors))
+    return CNF(header[0], clauses)
+
+
+def format_dimacs(cnf: CNF, comments: Sequence[str] = ()) -> str:
+    lines = [f"c {comment}" for comment in comments]
+    lines.append(f"p cnf {cnf.variables} {len(cnf.clauses)}")
+    lines += [" ".join(map(str, clause + [0])) for clause in cnf.clauses]
+    return "\n".join(lines) + "\n"
+
+
+def format_model(model: Optional[List[int]]) -> str:
+    if model is None:
+        return "s UNSATISFIABLE"
+    lines = ["s SATISFIABLE"]
+    for start in range(0, len(model), 10):
+        lines.append("v " + " ".join(map(str, model[start:start + 10])))
+    lines.append("v 0")
+    return "\n".join(lines)
+
+
+def satisfies(model: Sequence[int], clauses: Sequence[Clause]) -> bool:
+    true = set(model)
+    return all(any(literal in true for literal in clause) for clause in clauses)
+
+
+class Solver:
+    def __init__(self, variables: int, clauses: Sequence[Clause], learn: bool = True):
+        self.variables = variables
+        self.learn = learn
+        self.clauses: List[Clause] = []
+        self.watches: List[List[int]] = [[] for _ in range(2 * variables + 2)]
+        self.values = [0] * (variables + 1)     # 1 true, -1 false, 0 unassigned
+        self.levels = [0] * (variables + 1)
+        self.reasons = [-1] * (variables + 1)   # the clause that forced the variable, or -1
+        self.phases = [-1] * (variables + 1)    # the last value, to try first next time
+        self.activity = [0.0] * (variables + 1)
+        self.bump = 1.0
+        self.trail: List[int] = []
+        self.decisions_at: List[int] = []       # where each decision level starts in the trail
+        self.flipped: List[bool] = []           # for DPLL: whether each decision has been flipped
+        self.head = 0                           # the trail up to here has been propagated
+        self.unsatisfiable = False
+        self.decisions = self.conflicts = self.propagations = self.learned = 0
+        for clause in clauses:
+            self.add_clause(list(dict.fromkeys(clause)))
+
+    @staticmethod
+    def index(literal: int) -> int:
+        return 2 * abs(literal) + (literal < 0)
+
+    def value(self, literal: int) -> int:
+        value = self.values[abs(literal)]
+        return value if literal > 0 else -value
+
+    def add_clause(self, clause: Clause):
+        if any(-literal in clause for literal in clause):
+            return      # always true
+        if not clause:
+            self.unsatisfiable = True
+        elif len(clause) == 1:
+            if self.value(clause[0]) == -1:
+                self.unsatisfiable = True
+            elif self.value(clause[0]) == 0:
+                self.assign(clause[0], -1)
+        else:
+            self.clauses.append(clause)
+            self.watches[self.index(clause[0])].append(len(self.clauses) - 1)
+            self.watches[self.index(clause[1])].append(len(self.clauses) - 1)
+
+    def assign(self, literal: int, reason: int):
+        variable = abs(literal)
+        self.values[variable] = 1 if literal > 0 else -1
+        self.levels[variable] = len(self.decisions_at)
+        self.reasons[variable] = reason
+        self.trail.append(literal)
+
+    def propagate(self) -> Optional[int]:
+        """Unit propagation. Return a clause that became false, if one did."""
+        while self.head < len(self.trail):
+            false_literal = -self.trail[self.head]
+            self.head += 1
+            self.propagations += 1
+            watching = self.watches[self.index(false_literal)]
+            kept: List[int] = []
+            for position, c in enumerate(watching):
+                clause = self.clauses[c]
+                if clause[0] == false_literal:
+                    clause[0], clause[1] = clause[1], clause[0]
+                if self.value(clause[0]) == 1:
+                    kept.append(c)
+                    continue
+                for other in range(2, len(clause)):
+                    if self.value(clause[other]) != -1:
+                        clause[1], clause[other] = clause[other], clause[1]
+                        self.watches[self.index(clause[1])].append(c)
+                        break
+                else:
+                    kept.append(c)
+                    if self.value(clause[0]) == -1:
+                        kept.extend(watching[position + 1:])
+                        self.watches[self.index(false_literal)] = kept
+                        return c
+                    self.assign(clause[0], c)
+            self.watches[self.index(false_literal)] = kept
+        return None
+
+    def backtrack(self, level: int):
+        if len(self.decisions_at) <= level:
+            return
+        start = self.decisions_at[level]
+        for literal in self.trail[start:]:
+            variable = abs(literal)
+            self.phases[variable] = self.values[variable]
+            self.values[variable] = 0
+            self.reasons[variable] = -1
+        del self.trail[start:]
+        del self.decisions_at[level:]
+        del self.flipped[level:]
+        self.head = len(self.trail)
+
+    def analyze(self, conflict: int) -> Tuple[Clause, int]:
+        """The first-UIP learned clause (asserting literal first), and the level to jump back to."""
+        level = len(self.decisions_at)
+        learned: Clause = [0]
+        seen = [False] * (self.variables + 1)
+        pending = 0
+        literal = 0
+        position = len(self.trail) - 1
+        clause = self.clauses[conflict]
+        while True:
+            for other in clause:
+                variable = abs(other)
+                if other == literal or seen[variable] or self.levels[variable] == 0:
+                    continue
+                seen[variable] = True
+                self.bump_activity(variable)
+                if self.levels[variable] == level:
+                    pending += 1
+                else:
+                    learned.append(other)
+            while not seen[abs(self.trail[position])]:
+                position -= 1
+            literal = self.trail[position]
+            position -= 1
+            pending -= 1
+            if pending == 0:
+                break
+            clause = self.clauses[self.reasons[abs(literal)]]
+        learned[0] = -literal
+        if len(learned) == 1:
+            return learned, 0
+        deepest = max(range(1, len(learned)), key=lambda i: self.levels[abs(learned[i])])
+        learned[1], learned[deepest] = learned[deepest], learned[1]    # watch the last to be unassigned
+        return learned, self.levels[abs(learned[1])]
+
+    def bump_activity(self, variable: int):
+        self.activity[variable] += self.bump
+        if self.activity[variable] > 1e100:
+            self.activity = [a * 1e-100 for a in self.activity]
+            self.bump *= 1e-100
+
+    def decide(self) -> bool:
+        """Make a decision, or return False if every variable is assigned."""
+        best, best_activity = 0, -1.0
+        for variable in range(1, self.variables + 1):
+            if self.values[variable] == 0 and self.activity[variable] > best_activity:
+                best, best_activity = variable, self.activity[variable]
+        if best == 0:
+            return False
+        self.decisions += 1
+        self.decisions_at.append(len(self.trail))
+        self.flipped.append(False)
+        self.assign(best if self.phases[best] == 1 else -best, -1)
+        return True
+
+    def resolve_conflict(self, conflict: int) -> bool:
+        """Return False if the formula turns out to be unsatisfiable."""
+        self.conflicts += 1
+        if not self.decisions_at:
+            return False
+        if not self.learn:
+            while self.flipped and self.flipped[-1]:
+                self.backtrack(len(self.decisions_at) - 1)
+            if not self.decisions_at:
+                return False
+            decision = self.trail[self.decisions_at[-1]]
+            self.backtrack(len(self.decisions_at) - 1)
+            self.decisions_at.append(len(self.trail))
+            self.flipped.append(True)
+            self.assign(-decision, -1)
+            return True
+        learned, level = self.analyze(conflict)
+        self.backtrack(level)
+        self.bump /= ACTIVITY_DECAY
+        self.learned += 1
+        if len(learned) == 1:
+            self.assign(learned[0], -1)
+        else:
+            self.clauses.append(learned)
+            self.watches[self.index(learned[0])].append(len(self.clauses) - 1)
+            self.watches[self.index(learned[1])].append(len(self.clauses) - 1)
+            self.assign(learned[0], len(self.clauses) - 1)
+        return True
+
+    def solve(self) -> Optional[List[int]]:
+        """A model, as the literal of each variable in order, or None if there is none."""
+        if self.unsatisfiable:
+            return None
+        while True:
+            conflict = self.propagate()
+            if conflict is not None:
+                if not self.resolve_conflict(conflict):
+                    return None
+            elif not self.decide():
+                return [v if self.values[v] == 1 else -v for v in range(1, self.variables + 1)]
+
+    def statistics(self) -> str:
+        return (f"c {self.decisions} decisions, {self.conflicts} conflicts, "
+                f"{self.propagations} propagations, {self.learned} learned clauses")
+
+
+def solve(variables: int, clauses: Sequence[Clause], learn: bool = True) -> Optional[List[int]]:
+    return Solver(variables, clauses, learn).solve()
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def main():
+    parser = argparse.ArgumentParser(description="CDCL and DPLL SAT solver")
+    parser.add_argument("cnf", type=str, help="path to a DIMACS CNF file")
+    parser.add_argument("--dpll", action="store_true", help="backtrack without learning clauses")
+    parser.add_argument("--stats", action="store_true", help="print the decisions, conflicts, and so on")
+    args = parser.parse_args()
+
+    try:
+        with open(args.cnf) as f:
+            cnf = parse_dimacs(f.read())
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    solver = Solver(cnf.variables, cnf.clauses, learn=not args.dpll)
+    model = solver.solve()
+    if args.stats:
+        print(solver.statistics())
+    print(format_model(model))
+    if model is not None and not satisfies(model, cnf.clauses):
+        parser.exit(2, "error: the model does not satisfy the formula (this is a bug)\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,321 @@
+"""
+# Search
+
+The graph searches that the puzzle solvers share, written once over any
+kind of state. A puzzle supplies its start state, a goal test, and its
+successors: for the unweighted searches, the (move, next state) pairs
+from a state, and for the weighted ones, (move, next state, cost).
+
+    bfs         fewest moves
+    bidirectional
+                fewest moves too, searching from the goal as well as the
+                start (so it needs the goal state, and the moves into a
+                state), and meeting in the middle
+    dfs         any path, depth-first (optionally to a maximum depth)
+    dijkstra    cheapest path
+    astar       cheapest path, guided by a heuristic that never
+                overestimates the cost left (an admissible heuristic)
+    ida_star    iterative-deepening A*, which needs memory only for the
+                current path, for when A* runs out of it
+
+Each returns a `Result` with the moves, the states (from the start to
+the goal), the cost, and how many states it expanded, or None if there
+is no path. States must be hashable, or pass `key` to hash something
+else instead, e.g. a canonical form so that symmetric states are only
+explored once.
+
+A long search can report how it is going: pass a `Progress`, and it
+prints a line to stderr every second or so with the states expanded,
+the size of the frontier, and the depth (for A*, the f-value, and for
+IDA*, the current bound). A solver's `--quiet` is then just not passing
+one.
+
+    def successors(n):
+        return [("double", n * 2), ("add one", n + 1)]
+
+    result = bfs(1, lambda n: n == 10, successors)
+    result.moves        ['double', 'double', 'add one', 'double']
+
+
+## Usage
+
+    sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+    from search.search import astar, bfs
+"""
+
+import heapq
+import itertools
+import sys
+import time
+from collections import deque
+from typing import Any, Callable, Dict, Hashable, Iterable, List, NamedTuple, Optional, TextIO, Tuple, TypeVar
+
+
+State = TypeVar("State")
+Move = Any
+
+
+class Result(NamedTuple):
+    moves: List[Move]
+    states: List[Any]       # from the start to the goal, one more than the moves
+    cost: float
+    expanded: int           # how many states had their successors generated
+
+
+class Progress:
+    """
+    Periodic status lines for a long search. `update` takes counts by name,
+    e.g. update(expanded=1000, depth=12), and at most once an interval
+    prints them, with the rate of the first.
+    """
+
+    def __init__(self, interval: float = 1.0, stream: TextIO = sys.stderr, label: str = "search"):
+        self.interval = interval
+        self.stream = stream
+        self.label = label
+        self.start = time.monotonic()
+        self.last = self.start
+
+    def update(self, **counts: float):
+        now = time.monotonic()
+        if now - self.last < self.interval:
+            return
+        self.last = now
+        elapsed = now - self.start
+        shown = [f"{name} {value}" for name, value in counts.items()]
+        if counts:
+            shown[0] += f" ({next(iter(counts.values())) / elapsed:.0f}/s)"
+        print(f"{self.label}: {elapsed:.0f}s, " + ", ".join(shown), file=self.stream, flush=True)
+
+
+def _identity(state: Any) -> Hashable:
+    return state
+
+
+def _reconstruct(goal_key: Hashable, goal: Any, parents: Dict[Hashable, Optional[Tuple[Hashable, Move, Any]]],
+                 cost: float, expanded: int) -> Result:
+    """Follow the parents back from the goal. parents maps a key to (parent key, move, state)."""
+    moves: List[Move] = []
+    states: List[Any] = [goal]
+    key = goal_key
+    while parents[key] is not None:
+        parent_key, move, parent = parents[key]
+        moves.append(move)
+        states.append(parent)
+        key = parent_key
+    return Result(moves[::-1], states[::-1], cost, expanded)
+
+
+def bfs(start: State, is_goal: Callable[[State], bool],
+        successors: Callable[[State], Iterable[Tuple[Move, State]]],
+        key: Callable[[State], Hashable] = _identity, max_expanded: Optional[int] = None,
+        progress: Optional[Progress] = None) -> Optional[Result]:
+    start_key = key(start)
+    parents: Dict[Hashable, Optional[Tuple[Hashable, Move, Any]]] = {start_key: None}
+    depths = {start_key: 0}
+    frontier = deque([start])
+    expanded = 0
+    while frontier:
+        state = frontier.popleft()
+        state_key = key(state)
+        if is_goal(state):
+            return _reconstruct(state_key, state, parents, depths[state_key], expanded)
+        if max_expanded is not None and expanded >= max_expanded:
+            return None
+        expanded += 1
+        if progress is not None:
+            progress.update(expanded=expanded, frontier=len(frontier), depth=depths[state_key])
+        for move, next_state in successors(state):
+            next_key = key(next_state)
+            if next_key not in parents:
+                parents[next_key] = (state_key, move, state)
+                depths[next_key] = depths[state_key] + 1
+                frontier.append(next_state)
+    return None
+
+
+def bidirectional(start: State, goal: State,
+                  successors: Callable[[State], Iterable[Tuple[Move, State]]],
+                  predecessors: Callable[[State], Iterable[Tuple[Move, State]]],
+                  key: Callable[[State], Hashable] = _identity,
+                  progress: Optional[Progress] = None) -> Optional[Result]:
+    """
+    Breadth-first from both ends, a whole layer at a time from whichever
+    side has the smaller frontier, until they meet. predecessors(state)
+    gives (move, previous state) pairs, where the move takes the previous
+    state to this one. Where the layer meets the other side in more than
+    one place, the shortest path through them is taken.
+    """
+    start_key, goal_key = key(start), key(goal)
+    # for each side: key -> (key of the next state toward its end, move, that state), and depths
+    links: List[Dict[Hashable, Optional[Tuple[Hashable, Move, Any]]]] = [{start_key: None}, {goal_key: None}]
+    depths: List[Dict[Hashable, int]] = [{start_key: 0}, {goal_key: 0}]
+    frontiers: List[List[State]] = [[start], [goal]]
+    states: Dict[Hashable, Any] = {start_key: start, goal_key: goal}
+    expanded = 0
+    meeting = start_key if start_key == goal_key else None
+    while meeting is None and frontiers[0] and frontiers[1]:
+        side = 0 if len(frontiers[0]) <= len(frontiers[1]) else 1
+        neighbours = successors if side == 0 else predecessors
+        best = None
+        layer = []
+        for state in frontiers[side]:
+            state_key = key(state)
+            expanded += 1
+            if progress is not None:
+                progress.update(expanded=expanded, frontier=len(frontiers[0]) + len(frontiers[1]))
+            for move, next_state in neighbours(state):
+                next_key = key(next_state)
+                if next_key in links[side]:
+                    continue
+                links[side][next_key] = (state_key, move, state)
+                depths[side][next_key] = depths[side][state_key] + 1
+                states[next_key] = next_state
+                layer.append(next_state)
+                if next_key in links[1 - side]:
+                    length = depths[0][next_key] + depths[1][next_key]
+                    if best is None or length < best[0]:
+                        best = (length, next_key)
+        frontiers[side] = layer
+        if best is not None:
+            meeting = best[1]
+    if meeting is None:
+        return None
+    forward = _reconstruct(meeting, states[meeting], links[0], 0, expanded)
+    moves, path = forward.moves, forward.states
+    key_here = meeting
+    while links[1][key_here] is not None:
+        key_here, move, state = links[1][key_here]
+        moves.append(move)
+        path.append(state)
+    return Result(moves, path, len(moves), expanded)
+
+
+def dfs(start: State, is_goal: Callable[[State], bool],
+        successors: Callable[[State], Iterable[Tuple[Move, State]]],
+        key: Callable[[State], Hashable] = _identity, max_depth: Optional[int] = None,
+        progress: Optional[Progress] = None) -> Optional[Result]:
+    """
+    Iterative, so a deep search does not hit Python's recursion limit. Each
+    state is visited once, so with max_depth a state first reached by a long
+    path is not explored again by a shorter one.
+    """
+    start_key = key(start)
+    parents: Dict[Hashable, Optional[Tuple[Hashable, Move, Any]]] = {start_key: None}
+    stack: List[Tuple[State, int]] = [(start, 0)]
+    expanded = 0
+    while stack:
+        state, depth = stack.pop()
+        state_key = key(state)
+        if is_goal(state):
+            return _reconstruct(state_key, state, parents, depth, expanded)
+        if max_depth is not None and depth >= max_depth:
+            continue
+        expanded += 1
+        if progress is not None:
+            progress.update(expanded=expanded, frontier=len(stack), depth=depth)
+        children = []
+        for move, next_state in successors(state):
+            next_key = key(next_state)
+            if next_key not in parents:
+                parents[next_key] = (state_key, move, state)
+                children.append((next_state, depth + 1))
+        stack.extend(reversed(children))    # so the first successor is explored first
+    return None
+
+
+def astar(start: State, is_goal: Callable[[State], bool],
+          successors: Callable[[State], Iterable[Tuple[Move, State, float]]],
+          heuristic: Callable[[State], float] = lambda state: 0,
+          key: Callable[[State], Hashable] = _identity, max_expanded: Optional[int] = None,
+          progress: Optional[Progress] = None) -> Optional[Result]:
+    start_key = key(start)
+    parents: Dict[Hashable, Optional[Tuple[Hashable, Move, Any]]] = {start_key: None}
+    costs: Dict[Hashable, float] = {start_key: 0}
+    closed = set()
+    counter = itertools.count()     # breaks ties, so states never need to be comparable
+    frontier = [(heuristic(start), next(counter), start)]
+    expanded = 0
+    while frontier:
+        f, _, state = heapq.heappop(frontier)
+        state_key = key(state)
+        if state_key in closed:
+            continue
+        if is_goal(state):
+            return _reconstruct(state_key, state, parents, costs[state_key], expanded)
+        if max_expanded is not None and expanded >= max_expanded:
+            return None
+        closed.add(state_key)
+        expanded += 1
+        if progress is not None:
+            progress.update(expanded=expanded, frontier=len(frontier), f=f)
+        for move, next_state, cost in successors(state):
+            next_key = key(next_state)
+            new_cost = costs[state_key] + cost
+            if next_key not in closed and new_cost < costs.get(next_key, float("inf")):
+                costs[next_key] = new_cost
+                parents[next_key] = (state_key, move, state)
+                heapq.heappush(frontier, (new_cost + heuristic(next_state), next(counter), next_state))
+    return None
+
+
+def dijkstra(start: State, is_goal: Callable[[State], bool],
+             successors: Callable[[State], Iterable[Tuple[Move, State, float]]],
+             key: Callable[[State], Hashable] = _identity, max_expanded: Optional[int] = None,
+             progress: Optional[Progress] = None) -> Optional[Result]:
+    return astar(start, is_goal, successors, lambda state: 0, key, max_expanded, progress)
+
+
+def ida_star(start: State, is_goal: Callable[[State], bool],
+             successors: Callable[[State], Iterable[Tuple[Move, State, float]]],
+             heuristic: Callable[[State], float],
+             key: Callable[[State], Hashable] = _identity, max_cost: float = float("inf"),
+             progress: Optional[Progress] = None) -> Optional[Result]:
+    """
+    Depth-first searches with a rising limit on cost + heuristic, starting at
+    the heuristic of the start, and raised each time to the smallest value
+    that went over. Only cycles on the current path are avoided.
+    """
+    moves: List[Move] = []
+    states: List[Any] = [start]
+    on_path = {key(start)}
+    expanded = 0
+
+    def search(cost: float, limit: float) -> Tuple[bool, float]:
+        """Whether the goal was found, or else the smallest f over the limit."""
+        nonlocal expanded
+        state = states[-1]
+        f = cost + heuristic(state)
+        if f > limit:
+            return False, f
+        if is_goal(state):
+            return True, cost
+        expanded += 1
+        if progress is not None:
+            progress.update(expanded=expanded, depth=len(moves), bound=limit)
+        smallest = float("inf")
+        for move, next_state, step_cost in successors(state):
+            next_key = key(next_state)
+            if next_key in on_path:
+                continue
+            moves.append(move)
+            states.append(next_state)
+            on_path.add(next_key)
+            found, value = search(cost + step_cost, limit)
+            if found:
+                return True, value
+            smallest = min(smallest, value)
+            moves.pop()
+            states.pop()
+            on_path.discard(next_key)
+        return False, smallest
+
+    limit = heuristic(start)
+    while limit <= max_cost:
+        found, value = search(0, limit)
+        if found:
+            return Result(list(moves), list(states), value, expanded)
+        if value == float("inf"):
+            return None
+        limit = value
+    return None
@@ -0,0 +1,265 @@
+"""
+# Skyscrapers
+
+Fill an n by n grid with buildings of heights 1 to n, each height once in
+every row and column (a Latin square). A clue outside the grid is how
+many buildings can be seen from there looking along its row or column,
+the taller hiding the shorter behind them: 1 2 4 3 seen from the left
+shows 1, 2 and 4, so 3. Some cells may be given.
+
+
+## Solving
+
+A row is a permutation of 1 to n, so each row and column starts with
+every permutation that its two clues allow, worked out once from a
+table of every permutation with how many it shows from each end. Each
+cell keeps the heights it can still have, and the lines narrow each
+other until nothing changes:
+
+    line        the line's permutations are those with a height each
+                cell can still have, and each cell can only have the
+                heights some permutation left puts there
+    crossing    a cell that loses a height sends the line crossing it
+                back to be narrowed again
+
+Most puzzles are solved by that alone. When it gets stuck, the search
+takes the line with the fewest permutations left (more than one), tries
+each in turn, and narrows again. It stops after two solutions, so it
+says whether the puzzle is proper.
+
+
+## The Format
+
+The top clues, then each row as its left clue, its cells and its right
+clue, then the bottom clues, separated by spaces, with . for no clue or
+an empty cell. Lines starting with # are comments.
+
+      . 2 . .
+    . . . . . 3
+    2 . . . . .
+    . . 3 . . .
+    . . . . . 1
+      . . 2 .
+
+
+## Usage
+
+    python3 skyscrapers.py puzzles/four.txt
+    python3 skyscrapers.py puzzles/six.txt --stats
+    python3 skyscrapers.py puzzles/seven.txt --stats
+    python3 skyscrapers.py puzzles/ambiguous.txt
+"""
+
+import argparse
+import functools
+import itertools
+import os
+import sys
+from collections import deque
+from typing import Dict, List, NamedTuple, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+
+
+Permutation = Tuple[int, ...]
+Line = Tuple[str, int]      # ("row", y) or ("column", x)
+
+
+class Puzzle(NamedTuple):
+    size: int
+    top: List[Optional[int]]
+    bottom: List[Optional[int]]
+    left: List[Optional[int]]
+    right: List[Optional[int]]
+    given: Dict[Position, int]
+
+
+class Contradiction(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.lines = 0
+        self.guesses = 0
+
+    def __str__(self) -> str:
+        return f"{self.lines} lines narrowed, {self.guesses} guesses"
+
+
+def visible(heights: Sequence[int]) -> int:
+    """How many buildings can be seen from the start of the line."""
+    count, tallest = 0, 0
+    for height in heights:
+        if height > tallest:
+            count, tallest = count + 1, height
+    return count
+
+
+@functools.lru_cache(maxsize=None)
+def permutations(size: int) -> List[Tuple[Permutation, int, int]]:
+    """Every permutation of 1 to size, with how many it shows from its start and from its end."""
+    return [(p, visible(p), visible(reversed(p))) for p in itertools.permutations(range(1, size + 1))]
+
+
+class State:
+    def __init__(self, candidates: Dict[Line, List[Permutation]], possible: Grid):
+        self.candidates = candidates    # each line's permutations left
+        self.possible = possible        # each cell's heights left, as a set
+
+    def copy(self) -> "State":
+        return State(dict(self.candidates), self.possible.map(set))
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle):
+        self.puzzle = puzzle
+        self.statistics = Statistics()
+
+    def cells(self, line: Line) -> List[Position]:
+        kind, i = line
+        return [(x, i) for x in range(self.puzzle.size)] if kind == "row" else \
+            [(i, y) for y in range(self.puzzle.size)]
+
+    def start(self) -> State:
+        puzzle = self.puzzle
+        candidates: Dict[Line, List[Permutation]] = {}
+        for kind, starts, ends in (("row", puzzle.left, puzzle.right), ("column", puzzle.top, puzzle.bottom)):
+            for i, (start, end) in enumerate(zip(starts, ends)):
+                candidates[kind, i] = [p for p, seen_start, seen_end in permutations(puzzle.size)
+                                       if start in (None, seen_start) and end in (None, seen_end)]
+        possible = Grid.filled(puzzle.size, puzzle.size, None).map(lambda _: set(range(1, puzzle.size + 1)))
+        for position, height in puzzle.given.items():
+            possible[position] = {height}
+        return State(candidates, possible)
+
+    def propagate(self, state: State, queue: deque):
+        """Narrow lines until none changes. Raises Contradiction if one has no permutation left."""
+        queued = set(queue)
+        while queue:
+            line = queue.popleft()
+            queued.discard(line)
+            cells = self.cells(line)
+            sets = [state.possible[cell] for cell in cells]
+            left = [p for p in state.candidates[line] if all(h in s for h, s in zip(p, sets))]
+            self.statistics.lines += 1
+            if not left:
+                raise Contradiction
+            state.candidates[line] = left
+            for i, cell in enumerate(cells):
+                heights = {p[i] for p in left}
+                if heights != sets[i]:
+                    state.possible[cell] = heights
+                    crossing = ("column", cell[0]) if line[0] == "row" else ("row", cell[1])
+                    if crossing not in queued:
+                        queued.add(crossing)
+                        queue.append(crossing)
+
+    def search(self, state: State, found: List[Grid], limit: int):
+        open_lines = [line for line, left in state.candidates.items() if len(left) > 1]
+        if not open_lines:
+            found.append(state.possible.map(lambda heights: next(iter(heights))))
+            return
+        line = min(open_lines, key=lambda line: len(state.candidates[line]))
+        self.statistics.guesses += 1
+        for permutation in state.candidates[line]:
+            trial = state.copy()
+            trial.candidates[line] = [permutation]
+            for cell, height in zip(self.cells(line), permutation):
+                trial.possible[cell] = {height}
+            try:
+                crossing = "column" if line[0] == "row" else "row"
+                self.propagate(trial, deque((crossing, i) for i in range(self.puzzle.size)))
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, limit: int = 2) -> List[Grid]:
+        state = self.start()
+        found: List[Grid] = []
+        try:
+            self.propagate(state, deque(state.candidates))
+        except Contradiction:
+            return found
+        self.search(state, found, limit)
+        return found
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    lines = [(number, line.split()) for number, line in enumerate(text.split("\n"), start=1)
+             if line.strip() and not line.lstrip().startswith("#")]
+    if len(lines) < 3:
+        raise ValueError("expected the top clues, the rows, and the bottom clues")
+    size = len(lines[0][1])
+    if len(lines) != size + 2:
+        raise ValueError(f"{size} top clues, so expected {size} rows, not {len(lines) - 2}")
+
+    def value(word: str, number: int) -> Optional[int]:
+        if word == ".":
+            return None
+        if not word.isdigit() or not 1 <= int(word) <= size:
+            raise ValueError(f"line {number}: expected 1 to {size}, or ., not {word!r}")
+        return int(word)
+
+    rows = lines[1:-1]
+    for number, words in [lines[0], lines[-1]]:
+        if len(words) != size:
+            raise ValueError(f"line {number}: expected {size} clues")
+    for number, words in rows:
+        if len(words) != size + 2:
+            raise ValueError(f"line {number}: expected a clue, {size} cells, and a clue")
+    given = {(x, y): value(word, number) for y, (number, words) in enumerate(rows)
+             for x, word in enumerate(words[1:-1]) if word != "."}
+    return Puzzle(size, [value(word, lines[0][0]) for word in lines[0][1]],
+                  [value(word, lines[-1][0]) for word in lines[-1][1]],
+                  [value(words[0], number) for number, words in rows],
+                  [value(words[-1], number) for number, words in rows], given)
+
+
+def render(puzzle: Puzzle, grid: Optional[Grid] = None) -> str:
+    """The clues around the grid's heights, or the givens."""
+    def text(value: Optional[int]) -> str:
+        return "." if value is None else str(value)
+
+    lines = ["  " + " ".join(map(text, puzzle.top))]
+    for y in range(puzzle.size):
+        cells = [grid[x, y] if grid is not None else puzzle.given.get((x, y)) for x in range(puzzle.size)]
+        lines.append(" ".join(map(text, [puzzle.left[y], *cells, puzzle.right[y]])))
+    lines.append("  " + " ".join(map(text, puzzle.bottom)))
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Skyscrapers")
+    parser.add_argument("file", type=str, help="the clues, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the lines narrowed and the guesses made")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    solver = Solver(puzzle)
+    found = solver.solutions(limit=2)
+    if not found:
+        print("No solution")
+    else:
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,428 @@
+"""
+# Sliding Puzzles
+
+The 8-puzzle (3x3) and the 15-puzzle (4x4): slide the tiles into the
+blank until they read 1, 2, 3, ... with the blank last, in the fewest
+moves. A board is a tuple of the tiles row by row, with 0 for the blank,
+and a move is the number of the tile that slides.
+
+Half of all boards cannot be solved: with the blank's row counted from
+the bottom, a move keeps (the inversions + that row) the same parity on
+a board of even width, and the inversions alone on odd width.
+
+
+## Heuristics
+
+The solver is IDA* (from `search/search.py`), which costs memory only for
+the current path, guided by one of
+
+    manhattan   the sum of each tile's distance from its goal
+    conflict    plus linear conflicts: two tiles in their goal row (or
+                column) in the wrong order, one of which has to step out
+                of the way and back, so two more moves per tile that has
+                to leave for the rest to be in order
+    pdb         additive pattern databases, for 4x4: the tiles are split
+                into three groups of five, and for every placement of a
+                group, a breadth-first search from the goal records the
+                fewest moves of those tiles it takes to get there (moves
+                of the other tiles are free). No move is counted by two
+                groups, so their sum still never overestimates. This is
+                taken with the linear conflicts, whichever is bigger.
+
+`play` is for sliding the tiles yourself, at a prompt (the REPL of
+`repl/repl.py`), on the board given or a scramble of `--moves` random
+moves: a move is the number of a tile next to the blank, or several, one
+after another (`7 8 4`), and one that is not next to the blank is
+refused. `:hint` solves from where you are, and gives the next tile on a
+shortest way and how far that is. The goal reached, the game is
+recorded as won (see `progress/progress.py`).
+
+The 3x3 needs nothing better than `conflict`. The databases for 4x4
+take a minute or two to build, the first time, and are saved in `pdb/`
+(which git ignores) for later runs.
+
+
+## Usage
+
+    python3 sliding.py solve "1 2 3 4 5 6 0 7 8"
+    python3 sliding.py solve "8 6 7 2 5 4 3 0 1" --show
+    python3 sliding.py solve examples/fifteen.txt --heuristic pdb
+    python3 sliding.py random --size 4 --moves 60 --seed 1 | python3 sliding.py solve - --heuristic pdb
+    python3 sliding.py build-pdb
+    python3 sliding.py play --size 3 --moves 20 --seed 1
+    python3 sliding.py play "1 2 3 4 5 6 0 7 8" --no-record
+"""
+
+import argparse
+import os
+import random
+import sys
+from typing import Callable, Iterator, List, Optional, Sequence, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from repl.repl import Repl
+from rng.rng import add_seed_argument, make_rng
+from search.search import Progress, Result, ida_star
+
+
+Board = Tuple[int, ...]
+HEURISTICS: List[str] = ["manhattan", "conflict", "pdb"]
+PDB_DIRECTORY: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "pdb")
+PDB_GROUPS: List[Tuple[int, ...]] = [(1, 2, 5, 6, 9), (3, 4, 7, 8, 12), (10, 11, 13, 14, 15)]
+
+
+def goal(size: int) -> Board:
+    return tuple(range(1, size * size)) + (0,)
+
+
+def size_of(board: Board) -> int:
+    size = int(round(len(board) ** 0.5))
+    if size * size != len(board) or size < 2:
+        raise ValueError(f"a board is a square of tiles, not {len(board)} of them")
+    return size
+
+
+def is_solvable(board: Board) -> bool:
+    size = size_of(board)
+    tiles = [tile for tile in board if tile]
+    inversions = sum(1 for i, a in enumerate(tiles) for b in tiles[i + 1:] if a > b)
+    if size % 2 == 1:
+        return inversions % 2 == 0
+    row_from_bottom = size - board.index(0) // size
+    return (inversions + row_from_bottom) % 2 == 1
+
+
+def successors(board: Board) -> Iterator[Tuple[int, Board, int]]:
+    size = size_of(board)
+    blank = board.index(0)
+    row, column = divmod(blank, size)
+    for dr, dc in ((-1, 0), (1, 0), (0, -1), (0, 1)):
+        r, c = row + dr, column + dc
+        if 0 <= r < size and 0 <= c < size:
+            tile = r * size + c
+            cells = list(board)
+            cells[blank], cells[tile] = cells[tile], 0
+            yield board[tile], tuple(cells), 1
+
+
+################################################################################
+###                                Heuristics                                ###
+################################################################################
+
+
+def manhattan(board: Board) -> int:
+    size = size_of(board)
+    total = 0
+    for cell, tile in enumerate(board):
+        if tile:
+            total += abs(cell // size - (tile - 1) // size) + abs(cell % size - (tile - 1) % size)
+    return total
+
+
+def _out_of_order(sequence: List[int]) -> int:
+    """How many items must leave so that the rest increase: the length less its longest increasing subsequence."""
+    longest: List[int] = []
+    for item in sequence:
+        i = next((i for i, last in enumerate(longest) if last > item), len(longest))
+        longest[i:i + 1] = [item]
+    return len(sequence) - len(longest)
+
+
+def linear_conflicts(board: Board) -> int:
+    size = size_of(board)
+    extra = 0
+    for line in range(size):
+        in_row = [(tile - 1) % size for tile in board[line * size:(line + 1) * size]
+                  if tile and (tile - 1) // size == line]
+        in_column = [(tile - 1) // size for tile in board[line::size] if tile and (tile - 1) % size == line]
+        extra += 2 * (_out_of_order(in_row) + _out_of_order(in_column))
+    return extra
+
+
+def conflict(board: Board) -> int:
+    return manhattan(board) + linear_conflicts(board)
+
+
+def _neighbours(cell: int, size: int) -> List[int]:
+    row, column = divmod(cell, size)
+    return [r * size + c for r, c in ((row - 1, column), (row + 1, column), (row, column - 1), (row, column + 1))
+            if 0 <= r < size and 0 <= c < size]
+
+
+def _region(taken: Set[int], cell: int, size: int) -> Set[int]:
+    """The cells reachable from this one without crossing a taken one."""
+    region = {cell}
+    stack = [cell]
+    while stack:
+        for neighbour in _neighbours(stack.pop(), size):
+            if neighbour not in taken and neighbour not in region:
+                region.add(neighbour)
+                stack.append(neighbour)
+    return region
+
+
+class PatternDatabase:
+    """The fewest moves of one group of tiles, for each placement of them, indexed by their cells in base 16."""
+
+    def __init__(self, group: Sequence[int], table: bytes):
+        self.group = tuple(group)
+        self.table = table
+
+    @classmethod
+    def build(cls, group: Sequence[int], size: int = 4) -> "PatternDatabase":
+        """
+        Breadth-first from the goal over (the group's cells, the region of
+        other cells the blank can reach without moving a group tile): within
+        a region the blank moves for free, and moving a group tile into it
+        costs one. A placement's entry is the first layer it turns up in.
+        """
+        cells = size * size
+        table = bytearray([255]) * cells ** len(group)
+        start = tuple(tile - 1 for tile in group)
+        region = _region(set(start), cells - 1, size)
+        seen = {(cls.index_of(start, cells), min(region))}
+        layer = [(start, region)]
+        distance = 0
+        while layer:
+            following = []
+            for placement, region in layer:
+                index = cls.index_of(placement, cells)
+                if table[index] == 255:
+                    table[index] = distance
+                for i, cell in enumerate(placement):
+                    for neighbour in _neighbours(cell, size):
+                        if neighbour in region:
+                            moved = placement[:i] + (neighbour,) + placement[i + 1:]
+                            moved_region = _region(set(moved), cell, size)
+                            key = (cls.index_of(moved, cells), min(moved_region))
+                            if key not in seen:
+                                seen.add(key)
+                                following.append((moved, moved_region))
+            layer = following
+            distance += 1
+        return cls(group, bytes(table))
+
+    @staticmethod
+    def index_of(placement: Sequence[int], cells: int) -> int:
+        index = 0
+        for cell in placement:
+            index = index * cells + cell
+        return index
+
+    def path(self) -> str:
+        return os.path.join(PDB_DIRECTORY, "-".join(map(str, self.group)) + ".pdb")
+
+    def lookup(self, where: Sequence[int]) -> int:
+        """With where[tile] the cell of each tile."""
+        index = 0
+        for tile in self.group:
+            index = index * 16 + where[tile]
+        return self.table[index]
+
+
+def load_databases(progress: bool = True) -> List[PatternDatabase]:
+    """The 4x4 databases, from `pdb/` if they are there, or else built and saved."""
+    databases = []
+    for group in PDB_GROUPS:
+        path = PatternDatabase(group, b"").path()
+        if os.path.exists(path):
+            with open(path, "rb") as f:
+                databases.append(PatternDatabase(group, f.read()))
+            continue
+        if progress:
+            print(f"building the pattern database for tiles {', '.join(map(str, group))} ...", file=sys.stderr)
+        database = PatternDatabase.build(group)
+        os.makedirs(PDB_DIRECTORY, exist_ok=True)
+        with open(path, "wb") as f:
+            f.write(database.table)
+        databases.append(database)
+    return databases
+
+
+def pdb_heuristic(databases: List[PatternDatabase]) -> Callable[[Board], int]:
+    def heuristic(board: Board) -> int:
+        where = [0] * 16
+        for cell, tile in enumerate(board):
+            where[tile] = cell
+        return max(sum(database.lookup(where) for database in databases), conflict(board))
+    return heuristic
+
+
+################################################################################
+###                                 Solving                                  ###
+################################################################################
+
+
+def solve(board: Board, heuristic: str = "conflict", progress: Optional[Progress] = None) -> Optional[Result]:
+    """The fewest moves, or None if the board cannot be solved."""
+    size = size_of(board)
+    if sorted(board) != list(range(size * size)):
+        raise ValueError(f"a {size}x{size} board has each of the tiles 0 to {size * size - 1} once")
+    if not is_solvable(board):
+        return None
+    if heuristic == "pdb":
+        if size != 4:
+            raise ValueError("the pattern databases are for 4x4 boards")
+        function = pdb_heuristic(load_databases())
+    else:
+        function = {"manhattan": manhattan, "conflict": conflict}[heuristic]
+    target = goal(size)
+    return ida_star(board, lambda state: state == target, successors, function, progress=progress)
+
+
+def parse(text: str) -> Board:
+    words = text.replace(",", " ").split()
+    try:
+        board = tuple(0 if word in (".", "_") else int(word) for word in words)
+    except ValueError:
+        raise ValueError("a board is its tiles, row by row, with 0 (or . or _) for the blank") from None
+    size_of(board)
+    return board
+
+
+def render(board: Board) -> str:
+    size = size_of(board)
+    width = len(str(len(board) - 1))
+    return "\n".join(" ".join(str(tile).rjust(width) if tile else "·".rjust(width)
+                              for tile in board[r * size:(r + 1) * size]) for r in range(size))
+
+
+def scramble(size: int, moves: int, rng: random.Random) -> Board:
+    """A random walk from the goal, never straight back."""
+    board, previous = goal(size), None
+    for _ in range(moves):
+        options = [(tile, after) for tile, after, _ in successors(board) if tile != previous]
+        previous, board = rng.choice(options)
+    return board
+
+
+def play(make: Callable[[], Board], instance: str, heuristic: str = "conflict", record: bool = True):
+    """Slide the tiles at the prompt; `make` gives the board, and a new one for each :new."""
+    session = Session("sliding", instance, record)
+    history: List[Board] = []
+
+    def show():
+        print(render(history[-1]))
+
+    def solved() -> bool:
+        return history[-1] == goal(size_of(history[-1]))
+
+    def slide(text: str):
+        if solved():
+            raise ValueError("the puzzle is solved (:new for another)")
+        words = text.replace(",", " ").split()
+        if not all(word.isdigit() for word in words):
+            raise ValueError(f"'{text}' is not the tiles to slide, like 7, or 7 8 4 for one after another")
+        boards = [history[-1]]
+        for word in words:
+            after = {tile: board for tile, board, _ in successors(boards[-1])}
+            if int(word) not in after:
+                raise ValueError(f"tile {word} is not next to the blank" + (" by then" if len(boards) > 1 else ""))
+            boards.append(after[int(word)])
+        for board in boards[1:]:
+            history.append(board)
+            session.move()
+        show()
+        if solved():
+            print(f"Solved, in {session.moves} move{'s' * (session.moves != 1)}")
+            session.finish("won")
+
+    def hint(_: str):
+        if solved():
+            return
+        result = solve(history[-1], heuristic)
+        assert result is not None
+        print(f"slide {result.moves[0]} ({len(result.moves)} move{'s' * (len(result.moves) != 1)} from the goal)")
+
+    def undo(_: str):
+        if len(history) > 1:
+            history.pop()
+        show()
+
+    def new(_: str):
+        board = make()
+        if sorted(board) != list(range(len(board))):
+            raise ValueError(f"a board has each of the tiles 0 to {len(board) - 1} once")
+        if not is_solvable(board):
+            raise ValueError("the tiles are an odd permutation away from the goal, so it cannot be solved")
+        session.start()
+        history[:] = [board]
+        show()
+
+    repl = Repl("move> ", slide)
+    repl.add_command("board", lambda _: show(), "print the board")
+    repl.add_command("hint", hint, "the next tile to slide on a shortest way to the goal")
+    repl.add_command("undo", undo, "take back your last move")
+    repl.add_command("new", new, "start again, on a new board unless one was given")
+    print("A move is the number of a tile next to the blank, which slides into it, and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve the 8- and 15-puzzles optimally")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a board")
+    solve_parser.add_argument("board", type=str, help="the tiles, a file of them, or - for stdin")
+    solve_parser.add_argument("--heuristic", choices=HEURISTICS, default="conflict")
+    solve_parser.add_argument("--show", action="store_true", help="draw the board after every move")
+    solve_parser.add_argument("--quiet", action="store_true", help="do not report the progress of the search")
+    random_parser = subparsers.add_parser("random", help="print a scrambled board")
+    play_parser = subparsers.add_parser("play", help="slide the tiles yourself, with hints")
+    play_parser.add_argument("board", type=str, nargs="?", default=None,
+                             help="the tiles, or a file of them; without it, a random board")
+    play_parser.add_argument("--heuristic", choices=HEURISTICS, default="conflict", help="for the hints")
+    play_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    for p in (random_parser, play_parser):
+        p.add_argument("--size", type=int, default=4)
+        p.add_argument("--moves", type=int, default=40, help="the random moves from the goal")
+        add_seed_argument(p)
+    subparsers.add_parser("build-pdb", help="build and save the 4x4 pattern databases")
+    args = parser.parse_args()
+
+    if args.command == "build-pdb":
+        load_databases()
+        return
+    if args.command == "random":
+        board = scramble(args.size, args.moves, make_rng(args.seed))
+        print(" ".join(map(str, board)))
+        return
+    try:
+        if args.command == "play" and args.board is None:
+            if args.size < 2 or args.moves < 0:
+                raise ValueError("a board is at least 2x2, scrambled by a number of moves that is not negative")
+            rng = make_rng(args.seed)
+            instance = f"{args.size}x{args.size}, {args.moves} moves, " + \
+                       ("random" if args.seed is None else f"seed {args.seed}")
+            play(lambda: scramble(args.size, args.moves, rng), instance, args.heuristic, not args.no_record)
+            return
+        if args.board == "-":
+            text = sys.stdin.read()
+        elif os.path.exists(args.board):
+            with open(args.board) as f:
+                text = f.read()
+        else:
+            text = args.board
+        board = parse(text)
+        if args.command == "play":
+            play(lambda: board, " ".join(map(str, board)), args.heuristic, not args.no_record)
+            return
+        result = solve(board, args.heuristic, None if args.quiet else Progress(label="ida*"))
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if result is None:
+        print("Unsolvable: the tiles are an odd permutation away from the goal")
+        parser.exit(1)
+    print(render(board))
+    if args.show:
+        for move, state in zip(result.moves, result.states[1:]):
+            print(f"\nslide {move}")
+            print(render(state))
+    print(f"{len(result.moves)} moves: {' '.join(map(str, result.moves))}")
+    print(f"{result.expanded} states expanded")
+
+
+if __name__ == "__main__":
+    main()
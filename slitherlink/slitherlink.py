@@ -0,0 +1,374 @@
+"""
+# Slitherlink
+
+Draw a single loop along the edges of a grid of dots, never crossing or
+touching itself, so that each number in a cell is how many of that
+cell's four edges the loop uses. Cells with no number can have any.
+
+
+## Solving
+
+Each edge is a line, a cross (not on the loop), or not known yet. The
+rules decide edges, over and over until none changes:
+
+    clue        a cell with as many lines as its number has the rest
+                crossed; one with as many lines and unknown edges as its
+                number has all of them lines
+    dot         the loop passes through a dot or misses it, so a dot has
+                two lines or none: with two, the rest are crossed; with
+                one, a last unknown edge is a line; with none, a last
+                unknown edge is crossed
+    loop        an edge that would join the two ends of a path is
+                crossed, unless the path is every line there is, since
+                the loop closed early would leave the others out
+
+    sides       each cell is inside the loop or outside, and off the grid
+                is outside; a line has different sides either side of it,
+                and a cross the same, so the edges decided so far tie
+                cells' sides together, and decide an edge between two
+                cells already tied
+
+When the rules get stuck, each unknown edge is tried both ways, and if
+the rules find one way impossible, it is the other (probing). When that
+gets stuck too, the search guesses an unknown edge, preferring
+one at a dot the loop has reached, a line and then a cross, and applies
+the rules again. A solution is every edge decided, with one loop and
+every clue met, which `verify` checks by walking the loop from one of
+its edges back to where it began. It stops after two, so it says
+whether the puzzle is proper.
+
+
+## The Format
+
+A grid of the clues, 0 to 3, with . for a cell with no clue. Lines
+starting with # are comments.
+
+    3..
+    .1.
+    ..2
+
+
+## Usage
+
+    python3 slitherlink.py puzzles/small.txt
+    python3 slitherlink.py puzzles/medium.txt --stats
+    python3 slitherlink.py puzzles/large.txt
+    python3 slitherlink.py puzzles/ambiguous.txt
+"""
+
+import argparse
+import os
+import sys
+from typing import Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+
+
+UNKNOWN, CROSS, LINE = -1, 0, 1
+Edge = Tuple[Position, Position]    # two dots, (x, y) with y down, the first above or left of the second
+
+
+class Contradiction(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.decisions = 0
+        self.probes = 0
+        self.guesses = 0
+
+    def __str__(self) -> str:
+        return f"{self.decisions} edges decided by the rules, {self.probes} by probing, {self.guesses} guesses"
+
+
+class Puzzle:
+    def __init__(self, clues: Grid):
+        self.clues = clues
+        self.width, self.height = clues.width, clues.height
+        self.edges: List[Edge] = [((x, y), (x + 1, y)) for y in range(self.height + 1) for x in range(self.width)]
+        self.edges += [((x, y), (x, y + 1)) for y in range(self.height) for x in range(self.width + 1)]
+        self.index: Dict[Edge, int] = {edge: i for i, edge in enumerate(self.edges)}
+        self.at: Dict[Position, List[int]] = {}
+        for i, (a, b) in enumerate(self.edges):
+            self.at.setdefault(a, []).append(i)
+            self.at.setdefault(b, []).append(i)
+        self.around: Dict[Position, List[int]] = {(x, y): self.sides(x, y) for x, y in clues.positions()}
+        # The cells on either side of each edge, above and below or left and right, None off the grid
+        self.between: List[Tuple[Optional[Position], Optional[Position]]] = [
+            (self.cell(x, y - 1) if y == y1 else self.cell(x - 1, y), self.cell(x, y))
+            for (x, y), (_, y1) in self.edges]
+
+    def cell(self, x: int, y: int) -> Optional[Position]:
+        return (x, y) if (x, y) in self.clues else None
+
+    def sides(self, x: int, y: int) -> List[int]:
+        """The edges of the cell at (x, y): above, below, left, right."""
+        return [self.index[(x, y), (x + 1, y)], self.index[(x, y + 1), (x + 1, y + 1)],
+                self.index[(x, y), (x, y + 1)], self.index[(x + 1, y), (x + 1, y + 1)]]
+
+
+################################################################################
+###                                  Solving                                 ###
+################################################################################
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle):
+        self.puzzle = puzzle
+        self.statistics = Statistics()
+
+    def decide(self, state: List[int], edge: int, value: int) -> bool:
+        """Set an unknown edge; whether it was unknown. Raises Contradiction if it was the other value."""
+        if state[edge] == value:
+            return False
+        if state[edge] != UNKNOWN:
+            raise Contradiction
+        state[edge] = value
+        self.statistics.decisions += 1
+        return True
+
+    def settle(self, state: List[int], edges: List[int], least: int, most: int) -> bool:
+        """Between least and most of the edges are lines: when that decides the unknown ones, decide them."""
+        lines = sum(state[e] == LINE for e in edges)
+        unknown = [e for e in edges if state[e] == UNKNOWN]
+        if lines > most or lines + len(unknown) < least:
+            raise Contradiction
+        if lines == most:
+            return any([self.decide(state, e, CROSS) for e in unknown])
+        if lines + len(unknown) == least:
+            return any([self.decide(state, e, LINE) for e in unknown])
+        return False
+
+    def propagate(self, state: List[int]):
+        """Apply the rules until no edge changes. Raises Contradiction if an edge can be neither."""
+        puzzle = self.puzzle
+        changed = True
+        while changed:
+            changed = False
+            for position, edges in puzzle.around.items():
+                clue = puzzle.clues[position]
+                if clue is not None:
+                    changed |= self.settle(state, edges, clue, clue)
+            for edges in puzzle.at.values():
+                lines = sum(state[e] == LINE for e in edges)
+                changed |= self.settle(state, edges, 2 if lines else 0, 2 if lines + sum(
+                    state[e] == UNKNOWN for e in edges) >= 2 else 0)
+            changed |= self.close_early(state)
+            changed |= self.inside_out(state)
+
+    def close_early(self, state: List[int]) -> bool:
+        """Cross each unknown edge that would close a path that is not every line."""
+        component: Dict[Position, int] = {}
+        sizes: List[int] = []
+        for start, _ in self.puzzle.at.items():
+            if start in component:
+                continue
+            component[start] = len(sizes)
+            stack, size = [start], 0
+            while stack:
+                dot = stack.pop()
+                for e in self.puzzle.at[dot]:
+                    if state[e] == LINE:
+                        size += 1
+                        a, b = self.puzzle.edges[e]
+                        other = b if a == dot else a
+                        if other not in component:
+                            component[other] = len(sizes)
+                            stack.append(other)
+            sizes.append(size // 2)
+        total = sum(sizes)
+        changed = False
+        for e, (a, b) in enumerate(self.puzzle.edges):
+            if state[e] == UNKNOWN and component[a] == component[b] and sizes[component[a]] < total:
+                changed |= self.decide(state, e, CROSS)
+        return changed
+
+    def inside_out(self, state: List[int]) -> bool:
+        """Decide each unknown edge between two cells already known to be on the same side of the loop, or on
+        different sides. Raises Contradiction if the decided edges disagree about a cell's side."""
+        parent: Dict[Optional[Position], Tuple[Optional[Position], int]] = {}
+
+        def find(cell: Optional[Position]) -> Tuple[Optional[Position], int]:
+            """The cell's class, and whether it is on the other side of the loop from the class's root."""
+            root, parity = cell, 0
+            while root in parent:
+                root, step = parent[root]
+                parity ^= step
+            return root, parity
+
+        for e, value in enumerate(state):
+            if value != UNKNOWN:
+                (a, p), (b, q) = (find(cell) for cell in self.puzzle.between[e])
+                if a != b:
+                    parent[a] = (b, p ^ q ^ value)
+                elif p ^ q != value:
+                    raise Contradiction
+        changed = False
+        for e, value in enumerate(state):
+            if value == UNKNOWN:
+                (a, p), (b, q) = (find(cell) for cell in self.puzzle.between[e])
+                if a == b:
+                    changed |= self.decide(state, e, LINE if p ^ q else CROSS)
+        return changed
+
+    def guess(self, state: List[int]) -> Optional[int]:
+        """An unknown edge at a dot the loop reaches, or any unknown edge, or None."""
+        for edges in self.puzzle.at.values():
+            if any(state[e] == LINE for e in edges):
+                for e in edges:
+                    if state[e] == UNKNOWN:
+                        return e
+        return next((e for e, value in enumerate(state) if value == UNKNOWN), None)
+
+    def probe(self, state: List[int]):
+        """Try each unknown edge each way, and where one way leads the rules to a contradiction, decide it the
+        other, until that decides nothing more. Raises Contradiction if both ways do."""
+        changed = True
+        while changed:
+            changed = False
+            for edge in range(len(state)):
+                if state[edge] != UNKNOWN:
+                    continue
+                for value in (LINE, CROSS):
+                    trial = list(state)
+                    try:
+                        self.decide(trial, edge, value)
+                        self.propagate(trial)
+                    except Contradiction:
+                        self.statistics.probes += 1
+                        self.decide(state, edge, CROSS if value == LINE else LINE)
+                        self.propagate(state)
+                        changed = True
+                        break
+
+    def search(self, state: List[int], found: List[List[int]], limit: int):
+        try:
+            self.probe(state)
+        except Contradiction:
+            return
+        edge = self.guess(state)
+        if edge is None:
+            if not verify(self.puzzle, state):
+                found.append(state)
+            return
+        self.statistics.guesses += 1
+        for value in (LINE, CROSS):
+            trial = list(state)
+            try:
+                self.decide(trial, edge, value)
+                self.propagate(trial)
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, limit: int = 2) -> List[List[int]]:
+        """Up to limit solutions, each LINE or CROSS for every edge."""
+        state = [UNKNOWN] * len(self.puzzle.edges)
+        found: List[List[int]] = []
+        try:
+            self.propagate(state)
+        except Contradiction:
+            return found
+        self.search(state, found, limit)
+        return found
+
+
+def verify(puzzle: Puzzle, state: List[int]) -> List[str]:
+    """What is wrong with the edges as a solution: walk the loop from one of its lines, then check the clues."""
+    lines = [e for e, value in enumerate(state) if value == LINE]
+    if not lines:
+        return ["there is no loop"]
+    start, dot = puzzle.edges[lines[0]]
+    walked, previous = 1, lines[0]
+    while dot != start:
+        onward = [e for e in puzzle.at[dot] if state[e] == LINE and e != previous]
+        if len(onward) != 1:
+            return [f"the loop {'ends' if not onward else 'branches'} at the dot {dot}"]
+        previous = onward[0]
+        a, b = puzzle.edges[previous]
+        dot = b if a == dot else a
+        walked += 1
+    problems = []
+    if walked != len(lines):
+        problems.append(f"the loop is {walked} edges long, but there are {len(lines)} lines: more than one loop")
+    for position, edges in puzzle.around.items():
+        clue = puzzle.clues[position]
+        count = sum(state[e] == LINE for e in edges)
+        if clue is not None and count != clue:
+            problems.append(f"the cell {position} has {count} lines, not {clue}")
+    return problems
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    rows = []
+    for number, line in enumerate(text.split("\n"), start=1):
+        stripped = line.strip()
+        if not stripped or stripped.startswith("#"):
+            continue
+        if set(stripped) - set(".0123"):
+            raise ValueError(f"line {number}: expected the clues, 0 to 3, and . for a cell with none")
+        if rows and len(stripped) != len(rows[0]):
+            raise ValueError(f"line {number}: {len(stripped)} cells, but the first row has {len(rows[0])}")
+        rows.append([None if c == "." else int(c) for c in stripped])
+    if not rows:
+        raise ValueError("the puzzle is empty")
+    return Puzzle(Grid(rows))
+
+
+def render(puzzle: Puzzle, state: List[int]) -> str:
+    """The loop over the clues: --- and | for its lines, + for the dots it passes through, . for the others."""
+    def line(a: Position, b: Position) -> bool:
+        return state[puzzle.index[a, b]] == LINE
+
+    lines = []
+    for y in range(puzzle.height + 1):
+        dots = ["+" if any(state[e] == LINE for e in puzzle.at[x, y]) else "." for x in range(puzzle.width + 1)]
+        lines.append("".join(dots[x] + ("---" if line((x, y), (x + 1, y)) else "   ") for x in range(puzzle.width))
+                     + dots[puzzle.width])
+        if y < puzzle.height:
+            cells = []
+            for x in range(puzzle.width + 1):
+                cells.append("|" if line((x, y), (x, y + 1)) else " ")
+                if x < puzzle.width:
+                    clue = puzzle.clues[x, y]
+                    cells.append(f" {clue if clue is not None else ' '} ")
+            lines.append("".join(cells))
+    return "\n".join(row.rstrip() for row in lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Slitherlink")
+    parser.add_argument("file", type=str, help="the clues, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the edges decided and the guesses made")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    solver = Solver(puzzle)
+    found = solver.solutions(limit=2)
+    if not found:
+        print("No solution")
+    else:
+        print(render(puzzle, found[0]))
+        print(f"A loop of {found[0].count(LINE)} edges, meeting every clue")
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,379 @@
+"""
+# Sokoban
+
+A warehouse keeper pushes boxes onto goals. The keeper walks up, down,
+left and right over the floor, and pushes a box by walking into it, if
+the square beyond is free: a box is never pulled, and never pushed two
+at a time.
+
+
+## The Format
+
+Levels are in XSB, the common text format, one or more to a file:
+
+    #       wall
+    @       the keeper
+    +       the keeper, on a goal
+    $       a box
+    *       a box, on a goal
+    .       a goal
+    space   floor (or - or _)
+
+Lines starting with ; are comments, and the comment line just before a
+level is its title. A solution is in LURD: the keeper's steps, l, u, r,
+d, with the pushes in capitals.
+
+
+## Solving
+
+A* over pushes (from `search/search.py`), so the solution has the fewest
+pushes; the walking between them is free. A state is the boxes and the
+squares the keeper can reach, which are the same for any keeper square
+within them, so the keeper is kept as the first of them.
+
+    heuristic       each box's pushes to the nearest goal, with the other
+                    boxes out of the way
+    dead squares    squares from which a box can never be pushed onto any
+                    goal, found by pulling boxes back from the goals: the
+                    corners, and the walls that have no goal along them.
+                    A box is never pushed onto one.
+    freeze          a box that cannot move along either axis (blocked by
+                    a wall, a dead square on both sides, or another box
+                    that is itself frozen) can never move again; if any
+                    frozen box is off a goal, the push is a deadlock
+    Zobrist         states are looked up in the transposition table by a
+                    hash of random 64-bit numbers, one for each square a
+                    box can be on and one for each square the keeper can
+                    be kept at, XORed together, and updated by a push in
+                    four XORs rather than rehashing all the boxes
+
+Every solution is replayed by the verifier before it is printed.
+
+
+## Usage
+
+    python3 sokoban.py solve levels/simple.xsb --level 7
+    python3 sokoban.py solve levels/simple.xsb --all --stats --quiet
+    python3 sokoban.py verify levels/simple.xsb RurDDulD --level 2
+"""
+
+import argparse
+import os
+import random
+import sys
+from collections import deque
+from typing import Dict, FrozenSet, List, NamedTuple, Optional, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import Progress, astar
+
+
+WALL, PLAYER, PLAYER_ON_GOAL, BOX, BOX_ON_GOAL, GOAL, FLOOR = "#", "@", "+", "$", "*", ".", " "
+DIRECTIONS: str = "lurd"
+ZOBRIST_SEED: int = 0
+Push = Tuple[int, str]     # (the square of the box, the direction)
+
+
+class State(NamedTuple):
+    boxes: FrozenSet[int]
+    player: int     # the first square the keeper can reach
+    hash: int
+
+
+class Level:
+    def __init__(self, rows: List[str], title: str = ""):
+        self.title = title
+        self.width = max(len(row) for row in rows) + 2      # a column of wall each side, so steps never wrap
+        self.height = len(rows)
+        text = [" " + row.ljust(self.width - 1) for row in rows]
+        self.steps: Dict[str, int] = {"l": -1, "u": -self.width, "r": 1, "d": self.width}
+        walls: Set[int] = set()
+        self.goals: Set[int] = set()
+        boxes: Set[int] = set()
+        players = []
+        for y, row in enumerate(text):
+            for x, c in enumerate(row):
+                square = y * self.width + x
+                if c == WALL:
+                    walls.add(square)
+                if c in (GOAL, BOX_ON_GOAL, PLAYER_ON_GOAL):
+                    self.goals.add(square)
+                if c in (BOX, BOX_ON_GOAL):
+                    boxes.add(square)
+                if c in (PLAYER, PLAYER_ON_GOAL):
+                    players.append(square)
+                if c not in "#@+$*. -_":
+                    raise ValueError(f"{title or 'the level'}: row {y + 1}: {c!r} is not an XSB square")
+        if len(players) != 1:
+            raise ValueError(f"{title or 'the level'}: {len(players)} keepers, not one")
+        if len(boxes) != len(self.goals) or not boxes:
+            raise ValueError(f"{title or 'the level'}: {len(boxes)} boxes and {len(self.goals)} goals")
+        # the inside is what the keeper could walk to with no boxes in the way
+        self.floor: Set[int] = set()
+        frontier = [players[0]]
+        while frontier:
+            square = frontier.pop()
+            if square in self.floor or square in walls:
+                continue
+            x, y = square % self.width, square // self.width
+            if x in (0, self.width - 1) or not 0 <= y < self.height:
+                raise ValueError(f"{title or 'the level'}: the walls do not close it in")
+            self.floor.add(square)
+            frontier.extend(square + step for step in self.steps.values())
+        if not boxes <= self.floor or not self.goals <= self.floor:
+            raise ValueError(f"{title or 'the level'}: a box or goal is outside the walls")
+        self.text = text
+        self.start_boxes = frozenset(boxes)
+        self.start_player = players[0]
+        self.distances = self.pull_distances()
+        self.dead = self.floor - set(self.distances)
+        zobrist = random.Random(ZOBRIST_SEED)
+        self.box_keys = {square: zobrist.getrandbits(64) for square in self.floor}
+        self.player_keys = {square: zobrist.getrandbits(64) for square in self.floor}
+
+    def pull_distances(self) -> Dict[int, int]:
+        """The fewest pushes from each square to the nearest goal, for a box alone: none for a dead square."""
+        distances = {goal: 0 for goal in self.goals}
+        frontier = deque(self.goals)
+        while frontier:
+            square = frontier.popleft()
+            for step in self.steps.values():
+                before, keeper = square - step, square - 2 * step
+                if before in self.floor and keeper in self.floor and before not in distances:
+                    distances[before] = distances[square] + 1
+                    frontier.append(before)
+        return distances
+
+    def reach(self, player: int, boxes: FrozenSet[int]) -> Set[int]:
+        seen = {player}
+        frontier = [player]
+        while frontier:
+            square = frontier.pop()
+            for step in self.steps.values():
+                near = square + step
+                if near in self.floor and near not in boxes and near not in seen:
+                    seen.add(near)
+                    frontier.append(near)
+        return seen
+
+    def walk(self, start: int, end: int, boxes: FrozenSet[int]) -> str:
+        """The keeper's steps from start to end around the boxes, in lower-case LURD."""
+        parents: Dict[int, Tuple[int, str]] = {}
+        frontier = deque([start])
+        seen = {start}
+        while frontier and end not in seen:
+            square = frontier.popleft()
+            for direction, step in self.steps.items():
+                near = square + step
+                if near in self.floor and near not in boxes and near not in seen:
+                    seen.add(near)
+                    parents[near] = (square, direction)
+                    frontier.append(near)
+        if end not in seen:
+            raise ValueError("the keeper cannot get there")
+        path = []
+        while end != start:
+            end, direction = parents[end]
+            path.append(direction)
+        return "".join(reversed(path))
+
+    def state(self, player: int, boxes: FrozenSet[int]) -> State:
+        key = 0
+        for box in boxes:
+            key ^= self.box_keys[box]
+        first = min(self.reach(player, boxes))
+        return State(boxes, first, key ^ self.player_keys[first])
+
+    def render(self, player: int, boxes: FrozenSet[int]) -> str:
+        rows = []
+        for y, row in enumerate(self.text):
+            line = []
+            for x, c in enumerate(row):
+                square = y * self.width + x
+                goal = square in self.goals
+                if square == player:
+                    line.append(PLAYER_ON_GOAL if goal else PLAYER)
+                elif square in boxes:
+                    line.append(BOX_ON_GOAL if goal else BOX)
+                else:
+                    line.append(GOAL if goal else WALL if c == WALL else FLOOR)
+            rows.append("".join(line[1:]).rstrip())
+        return "\n".join(rows)
+
+
+def parse(text: str) -> List[Level]:
+    levels = []
+    rows: List[str] = []
+    title = comment = ""
+    for line in text.split("\n") + [""]:
+        line = line.rstrip()
+        if line and set(line) <= set("#@+$*. -_") and WALL in line:
+            if not rows:
+                title, comment = comment, ""
+            rows.append(line)
+            continue
+        if rows:
+            levels.append(Level(rows, title or f"Level {len(levels) + 1}"))
+            rows = []
+        if line.startswith(";"):
+            comment = line[1:].strip()
+    if not levels:
+        raise ValueError("no levels in the file")
+    return levels
+
+
+################################################################################
+###                                 Solving                                  ###
+################################################################################
+
+
+class Statistics:
+    def __init__(self):
+        self.expanded = 0
+        self.generated = 0
+        self.dead_squares = 0
+        self.frozen = 0
+
+    def __str__(self) -> str:
+        return (f"{self.expanded} states expanded, {self.generated} pushes generated, "
+                f"{self.dead_squares} onto dead squares and {self.frozen} freezing left out")
+
+
+def frozen(level: Level, box: int, boxes: FrozenSet[int], walls: Set[int], found: Set[int]) -> bool:
+    """
+    Whether the box can never move again, taking the squares in walls as
+    walls too. found collects the boxes that are frozen along with it.
+    """
+    walls = walls | {box}
+    blocked = []
+    for first, second in (("l", "r"), ("u", "d")):
+        sides = [box + level.steps[first], box + level.steps[second]]
+        blocked.append(any(side not in level.floor or side in walls for side in sides)
+                       or all(side in level.dead for side in sides)
+                       or any(side in boxes and frozen(level, side, boxes, walls, found) for side in sides))
+    if all(blocked):
+        found.add(box)
+        return True
+    return False
+
+
+def successors(level: Level, state: State, statistics: Statistics) -> List[Tuple[Push, State, float]]:
+    statistics.expanded += 1
+    children = []
+    reach = level.reach(state.player, state.boxes)
+    for box in state.boxes:
+        for direction, step in level.steps.items():
+            target = box + step
+            if box - step not in reach or target not in level.floor or target in state.boxes:
+                continue
+            statistics.generated += 1
+            if target in level.dead:
+                statistics.dead_squares += 1
+                continue
+            boxes = state.boxes - {box} | {target}
+            group: Set[int] = set()
+            if frozen(level, target, boxes, set(), group) and not group <= level.goals:
+                statistics.frozen += 1
+                continue
+            first = min(level.reach(box, boxes))
+            key = (state.hash ^ level.box_keys[box] ^ level.box_keys[target]
+                   ^ level.player_keys[state.player] ^ level.player_keys[first])
+            children.append(((box, direction), State(boxes, first, key), 1))
+    return children
+
+
+def solve(level: Level, statistics: Statistics, max_expanded: Optional[int] = None,
+          progress: Optional[Progress] = None) -> Optional[str]:
+    """The solution in LURD, with the fewest pushes, or None."""
+    start = level.state(level.start_player, level.start_boxes)
+    result = astar(start, lambda state: state.boxes <= level.goals,
+                   lambda state: successors(level, state, statistics),
+                   lambda state: sum(level.distances[box] for box in state.boxes),
+                   key=lambda state: state.hash, max_expanded=max_expanded, progress=progress)
+    if result is None:
+        return None
+    lurd = []
+    player, boxes = level.start_player, level.start_boxes
+    for box, direction in result.moves:
+        lurd.append(level.walk(player, box - level.steps[direction], boxes) + direction.upper())
+        player, boxes = box, boxes - {box} | {box + level.steps[direction]}
+    return "".join(lurd)
+
+
+def replay(level: Level, lurd: str) -> Tuple[int, int]:
+    """Check that the solution is legal and leaves every box on a goal. Returns the moves and the pushes."""
+    player, boxes = level.start_player, set(level.start_boxes)
+    pushes = 0
+    for number, c in enumerate(lurd, start=1):
+        if c.lower() not in DIRECTIONS:
+            raise ValueError(f"move {number}: {c!r} is not one of lurdLURD")
+        step = level.steps[c.lower()]
+        ahead = player + step
+        if ahead not in level.floor:
+            raise ValueError(f"move {number}: the keeper walks into a wall")
+        if ahead in boxes:
+            if ahead + step not in level.floor or ahead + step in boxes:
+                raise ValueError(f"move {number}: the box cannot be pushed there")
+            if c.islower():
+                raise ValueError(f"move {number}: a push is written {c.upper()}, not {c}")
+            boxes.remove(ahead)
+            boxes.add(ahead + step)
+            pushes += 1
+        elif c.isupper():
+            raise ValueError(f"move {number}: {c} is a push, but there is no box to push")
+        player = ahead
+    left = len(boxes - level.goals)
+    if left:
+        raise ValueError(f"{left} {'box is' if left == 1 else 'boxes are'} left off the goals")
+    return len(lurd), pushes
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Sokoban levels in the fewest pushes")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve a level")
+    solve_parser.add_argument("file", type=str, help="a file of levels in XSB")
+    solve_parser.add_argument("--level", type=int, default=1, help="which level in the file, from 1")
+    solve_parser.add_argument("--all", action="store_true", help="solve every level in the file")
+    solve_parser.add_argument("--max-expanded", type=int, default=None, help="give up after this many states")
+    solve_parser.add_argument("--stats", action="store_true", help="print the states searched")
+    solve_parser.add_argument("--quiet", action="store_true", help="do not report the progress of the search")
+    verify_parser = subparsers.add_parser("verify", help="replay a solution")
+    verify_parser.add_argument("file", type=str, help="a file of levels in XSB")
+    verify_parser.add_argument("solution", type=str, help="the solution, in LURD")
+    verify_parser.add_argument("--level", type=int, default=1, help="which level in the file, from 1")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            levels = parse(f.read())
+        if not 1 <= args.level <= len(levels):
+            raise ValueError(f"there are {len(levels)} levels in the file, not {args.level}")
+        if args.command == "verify":
+            moves, pushes = replay(levels[args.level - 1], args.solution.strip())
+            print(f"Solved: {moves} moves, {pushes} pushes")
+            return
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    failed = False
+    for level in levels if args.all else [levels[args.level - 1]]:
+        print(level.title)
+        print(level.render(level.start_player, level.start_boxes))
+        statistics = Statistics()
+        lurd = solve(level, statistics, args.max_expanded, None if args.quiet else Progress(label="a*"))
+        if lurd is None:
+            print("No solution" if args.max_expanded is None else "No solution found")
+            failed = True
+        else:
+            moves, pushes = replay(level, lurd)
+            print(f"{pushes} push{'es' * (pushes != 1)}, {moves} moves: {lurd}")
+        if args.stats:
+            print(statistics)
+        print()
+    if failed:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,233 @@
+"""
+# Star Battle
+
+An n by n grid cut into n regions. Put stars in some cells so that each
+row, each column and each region has exactly the same number of stars
+(one in the small puzzles, two from about 10 by 10), and no two stars
+touch, not even at a corner.
+
+
+## Solving
+
+By the shared CSP solver (`csp/csp.py`): a variable per cell, 1 for a
+star and 0 for none, and each row, column and region a table cage from
+`csp/cages.py`, whose values are every way to put its stars in its
+cells with no two touching. Each pair of cells that touch, across, down
+or diagonally, has a binary constraint that they are not both stars.
+AC-3 propagates all through the search: once a region's ways all put a
+star in one cell, or none in another, the cell is decided, and that
+crosses off ways in its row and column, and the stars around it.
+
+A region is the usual reason the easy steps work (a region all in one
+row takes that row's stars), and the cages find those steps without
+being told. The search stops after two solutions, so it says whether
+the puzzle is proper.
+
+
+## The Format
+
+n rows of n letters, each naming its cell's region, and a line `stars
+2` if there is more than one star to a row. Lines starting with # are
+comments.
+
+    stars 1
+    eeedd
+    eeedb
+    eeabb
+    cccbb
+    ccbbb
+
+
+## Usage
+
+    python3 star_battle.py puzzles/five.txt
+    python3 star_battle.py puzzles/eight.txt --stats
+    python3 star_battle.py puzzles/ten.txt --stats
+    python3 star_battle.py puzzles/ambiguous.txt
+"""
+
+import argparse
+import itertools
+import os
+import sys
+from typing import List, NamedTuple, Optional, Sequence, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.cages import add_table_cage
+from csp.csp import CSP, Statistics, solutions
+from grid.grid import Grid, Position
+
+
+STAR, EMPTY = "*", "."
+
+
+class Puzzle(NamedTuple):
+    regions: Grid           # each cell's region's letter
+    stars: int              # in each row, column and region
+
+    @property
+    def size(self) -> int:
+        return self.regions.width
+
+
+def touching(a: Position, b: Position) -> bool:
+    return max(abs(a[0] - b[0]), abs(a[1] - b[1])) == 1
+
+
+def placements(cells: Sequence[Position], stars: int) -> List[Tuple[int, ...]]:
+    """Every way to put the stars in the cells, as a 1 or 0 for each cell, with no two stars touching."""
+    ways = []
+    for chosen in itertools.combinations(range(len(cells)), stars):
+        if not any(touching(cells[i], cells[j]) for i, j in itertools.combinations(chosen, 2)):
+            ways.append(tuple(int(i in chosen) for i in range(len(cells))))
+    return ways
+
+
+def _apart(a: int, b: int) -> bool:
+    return not (a and b)
+
+
+def units(puzzle: Puzzle) -> List[Tuple[str, List[Position]]]:
+    """Each row, column and region, named, with its cells."""
+    n = puzzle.size
+    found = [(f"row {y + 1}", [(x, y) for x in range(n)]) for y in range(n)]
+    found += [(f"column {x + 1}", [(x, y) for y in range(n)]) for x in range(n)]
+    for name in sorted({letter for _, letter in puzzle.regions.items()}):
+        found.append((f"region {name}", puzzle.regions.find(name)))
+    return found
+
+
+def to_csp(puzzle: Puzzle) -> CSP:
+    csp = CSP()
+    for cell in puzzle.regions.positions():
+        csp.add_variable(cell, [0, 1])
+    for name, cells in units(puzzle):
+        try:
+            add_table_cage(csp, name, cells, placements(cells, puzzle.stars))
+        except ValueError:
+            raise ValueError(f"{name} has no room for {puzzle.stars} star{'s' * (puzzle.stars != 1)} "
+                             f"apart from each other") from None
+    for cell in puzzle.regions.positions():
+        for other in puzzle.regions.neighbours(cell, diagonal=True):
+            if cell < other:
+                csp.add_constraint([cell, other], _apart)
+    return csp
+
+
+def solve(puzzle: Puzzle, limit: int = 2, statistics: Optional[Statistics] = None) -> List[Set[Position]]:
+    """The stars of each solution, up to the limit."""
+    found = []
+    for assignment in solutions(to_csp(puzzle), statistics=statistics):
+        found.append({cell for cell in puzzle.regions.positions() if assignment[cell]})
+        if len(found) >= limit:
+            break
+    return found
+
+
+def verify(puzzle: Puzzle, stars: Set[Position]) -> List[str]:
+    """What is wrong with the stars, if anything."""
+    problems = []
+    for name, cells in units(puzzle):
+        placed = sum(1 for cell in cells if cell in stars)
+        if placed != puzzle.stars:
+            problems.append(f"{name} has {placed} star{'s' * (placed != 1)}, not {puzzle.stars}")
+    for a, b in itertools.combinations(sorted(stars), 2):
+        if touching(a, b):
+            problems.append(f"the stars at {a} and {b} touch")
+    return problems
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def parse(text: str) -> Puzzle:
+    stars = 1
+    rows = []
+    for number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        words = line.split()
+        if words[0] == "stars":
+            if len(words) != 2 or not words[1].isdigit() or int(words[1]) < 1:
+                raise ValueError(f"line {number}: expected the stars to a row, like 'stars 2'")
+            stars = int(words[1])
+            continue
+        rows.append((number, line))
+    if not rows:
+        raise ValueError("the puzzle is empty")
+    size = len(rows)
+    for number, line in rows:
+        if len(line) != size:
+            raise ValueError(f"line {number}: expected {size} letters, one a column, as there are {size} rows")
+    regions = Grid.parse("\n".join(line for _, line in rows))
+    names = {letter for _, letter in regions.items()}
+    if len(names) != size:
+        raise ValueError(f"a {size} by {size} grid should have {size} regions, not {len(names)}")
+    for name in sorted(names):
+        cells = regions.find(name)
+        seen = {cells[0]}
+        stack = [cells[0]]
+        while stack:
+            for other in regions.neighbours(stack.pop()):
+                if other not in seen and regions[other] == name:
+                    seen.add(other)
+                    stack.append(other)
+        if len(seen) != len(cells):
+            raise ValueError(f"region {name} is in more than one piece")
+    return Puzzle(regions, stars)
+
+
+def render(puzzle: Puzzle, stars: Set[Position]) -> str:
+    """The regions, with lines between them, and a star or a dot in each cell."""
+    n = puzzle.size
+
+    def region(x: int, y: int) -> Optional[str]:
+        return puzzle.regions[x, y] if 0 <= x < n and 0 <= y < n else None
+
+    def across(x: int, y: int) -> bool:     # a line above (x, y)
+        return 0 <= x < n and region(x, y - 1) != region(x, y)
+
+    def down(x: int, y: int) -> bool:       # a line left of (x, y)
+        return 0 <= y < n and region(x - 1, y) != region(x, y)
+
+    lines = []
+    for y in range(n + 1):
+        corners = ["+" if across(x - 1, y) or across(x, y) or down(x, y - 1) or down(x, y) else " "
+                   for x in range(n + 1)]
+        lines.append("".join(corners[x] + ("---" if across(x, y) else "   ") for x in range(n)) + corners[n])
+        if y < n:
+            lines.append("".join(("|" if down(x, y) else " ") + f" {STAR if (x, y) in stars else EMPTY} "
+                                 for x in range(n)) + "|")
+    return "\n".join(line.rstrip() for line in lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Star Battle")
+    parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+        statistics = Statistics()
+        found = solve(puzzle, limit=2, statistics=statistics)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if not found:
+        print("No solution")
+    else:
+        assert not verify(puzzle, found[0])
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
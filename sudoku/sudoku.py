@@ -0,0 +1,664 @@
+"""
+# Sudoku
+
+Fill a 9x9 grid with the digits 1 to 9 so that every row, column, and
+3x3 box has each digit once. A grid is 81 digits, row by row, with 0 for
+an empty cell, and is read from either
+
+    an 81-character line    4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......
+    a 9-line grid           rows of digits and dots, with any |, -, and + lines between the boxes
+
+A file can hold many puzzles, one per line.
+
+
+## Solving
+
+Each cell keeps the digits it could still be (its candidates, as a
+bitmask), and two rules run to a fixed point after every placement:
+
+    naked single    a cell with one candidate left is that digit, which
+                    is removed from every other cell that shares a unit
+    hidden single   a digit with one place left in a unit goes there
+
+When they get stuck, the search guesses: it picks the cell with the
+fewest candidates, and tries each. `solutions(grid, limit=2)` therefore
+tells a proper puzzle (one solution) from one with several, or none.
+
+## Grading and Generating
+
+`grade` solves without guessing, the way a person does, and rates the
+puzzle on the shared scale of `difficulty/difficulty.py` by the hardest
+technique it needed, singles (easy), pairs (medium), x-wing (hard), or
+guessing (fiendish, when none of those get anywhere), and by how often.
+`generate` fills a random grid, then takes away givens for as long as
+the solution stays unique, and with `--difficulty` keeps going until a
+puzzle rates at the level asked. Its output, with a `#` line of rating
+and givens before each puzzle, is itself a puzzle file. `check`
+generates puzzles and solves them again, and fails unless each has one
+solution that keeps its givens and fills every unit.
+
+`play` is for filling in a puzzle yourself, at a prompt (the REPL of
+`repl/repl.py`): the one given, or a new one at the difficulty asked. An
+entry is a cell, its column letter and row number, and a digit: `e5 7`,
+or `e5 0` to empty it. An entry that changes a given, or repeats a digit
+in a row, column or box, is refused. `:hint` names the entries that are
+wrong, if there are any (they agree with the rest so far, but not with
+the solution), or else the empty cell with the fewest candidates left,
+and its digit. A grid filled in is solved, and recorded as won (see
+`progress/progress.py`).
+
+The units are a parameter, so the same solver works for any layout with
+81 cells and units of nine (the diagonals of X-Sudoku, or irregular
+boxes).
+
+
+## Usage
+
+    python3 sudoku.py solve 4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......
+    python3 sudoku.py solve puzzles/easy.txt
+    python3 sudoku.py solve puzzles/hard.txt --stats
+    python3 sudoku.py solve puzzles/multiple.txt --count
+    python3 sudoku.py grade puzzles/graded.txt
+    python3 sudoku.py generate --count 5 --difficulty medium --seed 1 > new.txt
+    python3 sudoku.py check --count 20 --seed 1
+    python3 sudoku.py play --difficulty easy --seed 1
+    python3 sudoku.py play puzzles/easy.txt --no-record
+"""
+
+import argparse
+import os
+import random
+import re
+import sys
+import time
+from typing import Callable, Dict, List, Optional, Sequence, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from difficulty.difficulty import LEVELS, Rating, Scale, generate_rated
+from progress.progress import Session
+from repl.repl import Repl
+from rng.rng import add_seed_argument, make_rng
+
+
+SIZE: int = 9
+CELLS: int = SIZE * SIZE
+ALL_DIGITS: int = 0b1111111110     # bit d set for each digit d in 1..9
+Grid = List[int]
+
+
+def digits_of(mask: int) -> List[int]:
+    return [d for d in range(1, SIZE + 1) if mask >> d & 1]
+
+
+class Layout:
+    """Which cells must hold different digits: a list of units, each nine cells."""
+
+    def __init__(self, units: Sequence[Sequence[int]]):
+        self.units = [list(unit) for unit in units]
+        for unit in self.units:
+            if len(unit) != SIZE or len(set(unit)) != SIZE:
+                raise ValueError(f"a unit must be nine different cells, not {unit}")
+        self.units_of: List[List[List[int]]] = [[] for _ in range(CELLS)]
+        for unit in self.units:
+            for cell in unit:
+                self.units_of[cell].append(unit)
+        self.peers: List[Set[int]] = [
+            {other for unit in self.units_of[cell] for other in unit} - {cell} for cell in range(CELLS)
+        ]
+
+
+ROWS = [[9 * r + c for c in range(9)] for r in range(9)]
+COLUMNS = [[9 * r + c for r in range(9)] for c in range(9)]
+BOXES = [[9 * (3 * br + i) + 3 * bc + j for i in range(3) for j in range(3)] for br in range(3) for bc in range(3)]
+STANDARD = Layout(ROWS + COLUMNS + BOXES)
+
+
+class Contradiction(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.guesses = 0
+        self.nodes = 0
+
+    def __str__(self) -> str:
+        return f"{self.nodes} nodes, {self.guesses} guesses"
+
+
+class Solver:
+    def __init__(self, layout: Layout = STANDARD, rng: Optional[random.Random] = None):
+        self.layout = layout
+        self.rng = rng      # tries the digits of a guess in a random order, if set
+        self.statistics = Statistics()
+
+    def assign(self, candidates: List[int], cell: int, digit: int):
+        for other in digits_of(candidates[cell] & ~(1 << digit)):
+            self.eliminate(candidates, cell, other)
+
+    def eliminate(self, candidates: List[int], cell: int, digit: int):
+        bit = 1 << digit
+        if not candidates[cell] & bit:
+            return
+        candidates[cell] &= ~bit
+        remaining = candidates[cell]
+        if remaining == 0:
+            raise Contradiction()
+        if remaining & (remaining - 1) == 0:     # a naked single
+            last = remaining.bit_length() - 1
+            for peer in self.layout.peers[cell]:
+                self.eliminate(candidates, peer, last)
+        for unit in self.layout.units_of[cell]:     # where can the digit go in this unit now?
+            places = [other for other in unit if candidates[other] & bit]
+            if not places:
+                raise Contradiction()
+            if len(places) == 1:    # a hidden single
+                self.assign(candidates, places[0], digit)
+
+    def candidates(self, grid: Grid) -> List[int]:
+        """The candidates after propagating the givens. Raises Contradiction if they clash."""
+        candidates = [ALL_DIGITS] * CELLS
+        for cell, digit in enumerate(grid):
+            if digit:
+                if not candidates[cell] & (1 << digit):
+                    raise Contradiction()
+                self.assign(candidates, cell, digit)
+        return candidates
+
+    def search(self, candidates: List[int], found: List[Grid], limit: int):
+        self.statistics.nodes += 1
+        open_cells = [cell for cell in range(CELLS) if candidates[cell] & (candidates[cell] - 1)]
+        if not open_cells:
+            found.append([mask.bit_length() - 1 for mask in candidates])
+            return
+        cell = min(open_cells, key=lambda c: bin(candidates[c]).count("1"))
+        options = digits_of(candidates[cell])
+        if self.rng is not None:
+            self.rng.shuffle(options)
+        self.statistics.guesses += len(options) - 1
+        for digit in options:
+            trial = list(candidates)
+            try:
+                self.assign(trial, cell, digit)
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, grid: Grid, limit: int = 2) -> List[Grid]:
+        """Up to `limit` solutions: none if the grid is unsolvable, two or more if it is not proper."""
+        try:
+            candidates = self.candidates(grid)
+        except Contradiction:
+            return []
+        found: List[Grid] = []
+        self.search(candidates, found, limit)
+        return found
+
+
+def solutions(grid: Grid, limit: int = 2, layout: Layout = STANDARD) -> List[Grid]:
+    return Solver(layout).solutions(grid, limit)
+
+
+def solve(grid: Grid, layout: Layout = STANDARD) -> Optional[Grid]:
+    found = solutions(grid, 1, layout)
+    return found[0] if found else None
+
+
+################################################################################
+###                                 Grading                                  ###
+################################################################################
+
+
+def locked_units(layout: Layout) -> List[Tuple[List[int], List[int]]]:
+    """Every pair of units that share at least two cells (a box and a row, say)."""
+    pairs = []
+    for a in layout.units:
+        for b in layout.units:
+            if a is not b and len(set(a) & set(b)) >= 2:
+                pairs.append((a, b))
+    return pairs
+
+
+class Grader:
+    """
+    Solve the way a person does, with the easiest technique that makes
+    progress, and count the uses of each kind:
+
+        singles     naked and hidden singles
+        pairs       naked and hidden pairs, and locked candidates (a
+                    digit confined to where a box meets a line)
+        x-wing      a digit with two places in each of two rows, in the
+                    same two columns, is in neither column elsewhere
+        guessing    none of the above get any further
+    """
+
+    def __init__(self, layout: Layout = STANDARD):
+        self.layout = layout
+        self.locked = locked_units(layout)
+        self.techniques = [
+            ("singles", self.naked_singles),
+            ("singles", self.hidden_singles),
+            ("pairs", self.naked_pairs),
+            ("pairs", self.hidden_pairs),
+            ("pairs", self.locked_candidates),
+            ("x-wing", self.x_wing),
+        ]
+
+    @staticmethod
+    def remove(candidates: List[int], cells: Sequence[int], mask: int) -> bool:
+        progress = False
+        for cell in cells:
+            if candidates[cell] & mask:
+                candidates[cell] &= ~mask
+                progress = True
+        return progress
+
+    def naked_singles(self, candidates: List[int]) -> bool:
+        progress = False
+        for cell, mask in enumerate(candidates):
+            if mask & (mask - 1) == 0:
+                progress |= self.remove(candidates, list(self.layout.peers[cell]), mask)
+        return progress
+
+    def hidden_singles(self, candidates: List[int]) -> bool:
+        for unit in self.layout.units:
+            for digit in range(1, SIZE + 1):
+                places = [cell for cell in unit if candidates[cell] >> digit & 1]
+                if len(places) == 1 and candidates[places[0]] != 1 << digit:
+                    candidates[places[0]] = 1 << digit
+                    return True
+        return False
+
+    def naked_pairs(self, candidates: List[int]) -> bool:
+        progress = False
+        for unit in self.layout.units:
+            pairs = [candidates[cell] for cell in unit if bin(candidates[cell]).count("1") == 2]
+            for mask in {mask for mask in pairs if pairs.count(mask) == 2}:
+                progress |= self.remove(candidates, [cell for cell in unit if candidates[cell] != mask], mask)
+        return progress
+
+    def hidden_pairs(self, candidates: List[int]) -> bool:
+        progress = False
+        for unit in self.layout.units:
+            places = {d: tuple(cell for cell in unit if candidates[cell] >> d & 1) for d in range(1, SIZE + 1)}
+            twos = [d for d in places if len(places[d]) == 2]
+            for i, a in enumerate(twos):
+                for b in twos[i + 1:]:
+                    if places[a] == places[b]:
+                        keep = 1 << a | 1 << b
+                        for cell in places[a]:
+                            if candidates[cell] != keep:
+                                candidates[cell] &= keep
+                                progress = True
+        return progress
+
+    def locked_candidates(self, candidates: List[int]) -> bool:
+        progress = False
+        for a, b in self.locked:
+            for digit in range(1, SIZE + 1):
+                places = [cell for cell in a if candidates[cell] >> digit & 1]
+                if len(places) >= 2 and all(cell in b for cell in places):
+                    progress |= self.remove(candidates, [cell for cell in b if cell not in a], 1 << digit)
+        return progress
+
+    def x_wing(self, candidates: List[int]) -> bool:
+        progress = False
+        for lines, crossing in ((ROWS, COLUMNS), (COLUMNS, ROWS)):
+            for digit in range(1, SIZE + 1):
+                spots = [tuple(i for i, cell in enumerate(line) if candidates[cell] >> digit & 1) for line in lines]
+                for first in range(SIZE):
+                    for second in range(first + 1, SIZE):
+                        if len(spots[first]) == 2 and spots[first] == spots[second]:
+                            for i in spots[first]:
+                                others = [cell for n, cell in enumerate(crossing[i]) if n not in (first, second)]
+                                progress |= self.remove(candidates, others, 1 << digit)
+        return progress
+
+    def rate(self, grid: Grid) -> Rating:
+        """How many times each kind of technique was used, and, if they got stuck, the guesses the search
+        made from there."""
+        candidates = [1 << digit if digit else ALL_DIGITS for digit in grid]
+        rating = Rating()
+        while not all(mask & (mask - 1) == 0 for mask in candidates):
+            for name, technique in self.techniques:
+                if technique(candidates):
+                    rating.use(name)
+                    break
+            else:
+                solver = Solver(self.layout)
+                solver.search(candidates, [], 1)
+                rating.guess(max(1, solver.statistics.guesses))
+                return rating
+        if 0 in candidates:
+            raise ValueError("the puzzle has no solution")
+        return rating
+
+
+# Typical uses: an easy puzzle takes a few dozen singles, but one pair or x-wing is already a step up
+SCALE = Scale([("singles", 50), ("pairs", 5), ("x-wing", 2)], guesses=10)
+
+
+################################################################################
+###                                Generating                                ###
+################################################################################
+
+
+def generate(rng: random.Random, symmetric: bool = True, layout: Layout = STANDARD) -> Grid:
+    """
+    A random solved grid, with givens taken away, in a random order, for
+    as long as the solution stays unique. With `symmetric`, they go in
+    pairs, so the givens are symmetric under a half turn.
+    """
+    puzzle = Solver(layout, rng).solutions([0] * CELLS, limit=1)[0]
+    order = list(range(CELLS))
+    rng.shuffle(order)
+    for cell in order:
+        removing = {cell, CELLS - 1 - cell} if symmetric else {cell}
+        if not all(puzzle[c] for c in removing):
+            continue
+        trial = [0 if c in removing else digit for c, digit in enumerate(puzzle)]
+        if len(solutions(trial, 2, layout)) == 1:
+            puzzle = trial
+    return puzzle
+
+
+def generate_graded(rng: random.Random, level: Optional[str], symmetric: bool = True,
+                    attempts: int = 1000) -> Tuple[Grid, Rating]:
+    """A puzzle of the level (or any, if it is None), giving up after `attempts` tries."""
+    return generate_rated(lambda: generate(rng, symmetric), Grader().rate, SCALE, level, attempts)
+
+
+def check(count: int, rng: random.Random, symmetric: bool = True) -> bool:
+    """Generate puzzles and solve them: each must have one solution, which keeps the givens and fills every unit."""
+    for number in range(1, count + 1):
+        puzzle = generate(rng, symmetric)
+        found = solutions(puzzle, 2)
+        problems = []
+        if len(found) != 1:
+            problems.append("it has no solution" if not found else "it has more than one solution")
+        else:
+            solution = found[0]
+            if any(given and given != digit for given, digit in zip(puzzle, solution)):
+                problems.append("the solution changes a given")
+            for unit in STANDARD.units:
+                if sorted(solution[cell] for cell in unit) != list(range(1, SIZE + 1)):
+                    problems.append(f"the unit {unit} does not hold each digit once")
+                    break
+        if problems:
+            print(f"puzzle {number}, {to_line(puzzle)}: {'; '.join(problems)}")
+            return False
+    print(f"All {count} generated puzzles have one solution, and it is valid")
+    return True
+
+
+################################################################################
+###                              Reading and Writing                         ###
+################################################################################
+
+
+def parse(text: str) -> Grid:
+    """One puzzle, as 81 characters or a 9-line grid, with . or 0 for the empty cells."""
+    cells = re.sub(r"[\s|+\-]", "", text)
+    if len(cells) != CELLS or any(c not in "0123456789." for c in cells):
+        raise ValueError(f"a Sudoku is {CELLS} digits, with '.' or '0' for the empty cells")
+    return [0 if c == "." else int(c) for c in cells]
+
+
+def parse_many(text: str) -> List[Grid]:
+    """Every puzzle: one per line if a line is 81 characters, or else the whole text as one grid."""
+    lines = [line.strip() for line in text.split("\n") if line.strip() and not line.startswith("#")]
+    if lines and all(len(re.sub(r"\s", "", line)) == CELLS for line in lines):
+        errors: List[str] = []
+        grids: List[Grid] = []
+        for number, line in enumerate(lines, start=1):
+            try:
+                grids.append(parse(line))
+            except ValueError as e:
+                errors.append(f"puzzle {number}: {e}")
+        if errors:
+            raise ValueError("\n".join(errors))
+        return grids
+    return [parse("\n".join(lines))]
+
+
+def to_line(grid: Grid) -> str:
+    return "".join(str(d) if d else "." for d in grid)
+
+
+def format_grid(grid: Grid) -> str:
+    lines = []
+    for r in range(SIZE):
+        if r and r % 3 == 0:
+            lines.append("------+-------+------")
+        cells = [str(d) if d else "." for d in grid[9 * r:9 * r + 9]]
+        lines.append(" | ".join(" ".join(cells[i:i + 3]) for i in range(0, SIZE, 3)))
+    return "\n".join(lines)
+
+
+################################################################################
+###                                   Play                                   ###
+################################################################################
+
+
+def cell_name(cell: int) -> str:
+    return f"{'abcdefghi'[cell % SIZE]}{cell // SIZE + 1}"
+
+
+def parse_entry(text: str) -> Tuple[int, int]:
+    """A cell and its digit, like e5 7, with 0 or . to empty it."""
+    words = text.replace(",", " ").split()
+    if len(words) == 1 and len(words[0]) == 3:
+        words = [words[0][:2], words[0][2]]
+    if (len(words) != 2 or len(words[0]) != 2 or words[0][0].lower() not in "abcdefghi"
+            or words[0][1] not in "123456789" or words[1] not in list("0123456789.")):
+        raise ValueError(f"'{text}' is not a cell and a digit, like e5 7 (or e5 0 to empty it)")
+    cell = SIZE * (int(words[0][1]) - 1) + "abcdefghi".index(words[0][0].lower())
+    return cell, 0 if words[1] == "." else int(words[1])
+
+
+def clash(grid: Grid, cell: int, digit: int) -> Optional[str]:
+    """Where the digit is already, if it cannot go in the cell."""
+    for kind, units in (("row", ROWS), ("column", COLUMNS), ("box", BOXES)):
+        for number, unit in enumerate(units, start=1):
+            if cell in unit and any(grid[other] == digit for other in unit if other != cell):
+                name = "abcdefghi"[number - 1] if kind == "column" else str(number)
+                return f"there is already a {digit} in {kind} {name}"
+    return None
+
+
+def play(make: Callable[[], Grid], instance: str, record: bool = True):
+    """Fill in a puzzle at the prompt; `make` gives the puzzle, and a new one for each :new."""
+    session = Session("sudoku", instance, record)
+    history: List[Grid] = []
+    solution: Grid = []
+
+    def show():
+        print(format_grid(history[-1]))
+
+    def solved() -> bool:
+        return 0 not in history[-1]
+
+    def enter(text: str):
+        if solved():
+            raise ValueError("the puzzle is solved (:new for another)")
+        cell, digit = parse_entry(text)
+        if history[0][cell]:
+            raise ValueError(f"{cell_name(cell)} is a given")
+        if digit and clash(history[-1], cell, digit):
+            raise ValueError(clash(history[-1], cell, digit))
+        grid = list(history[-1])
+        grid[cell] = digit
+        history.append(grid)
+        session.move()
+        show()
+        if solved():
+            print(f"Solved, in {session.moves} move{'s' * (session.moves != 1)}")
+            session.finish("won")
+
+    def hint(_: str):
+        grid = history[-1]
+        wrong = [cell_name(cell) for cell in range(CELLS) if grid[cell] and grid[cell] != solution[cell]]
+        if wrong:
+            print(f"{', '.join(wrong)} {'is' if len(wrong) == 1 else 'are'} wrong")
+            return
+        if solved():
+            return
+        candidates = Solver().candidates(grid)
+        cell = min((cell for cell in range(CELLS) if not grid[cell]), key=lambda c: len(digits_of(candidates[c])))
+        left = digits_of(candidates[cell])
+        if len(left) == 1:
+            print(f"{cell_name(cell)} can only be {solution[cell]}")
+        else:
+            print(f"{cell_name(cell)} is {solution[cell]} (of {' '.join(map(str, left))})")
+
+    def undo(_: str):
+        if len(history) > 1:
+            history.pop()
+        show()
+
+    def new(_: str):
+        puzzle = make()
+        found = solutions(puzzle, 2)
+        if len(found) != 1:
+            raise ValueError("the puzzle has no solution" if not found else "the puzzle has more than one solution")
+        session.start()
+        history[:] = [puzzle]
+        solution[:] = found[0]
+        show()
+
+    repl = Repl("move> ", enter)
+    repl.add_command("board", lambda _: show(), "print the grid")
+    repl.add_command("hint", hint, "the entries that are wrong, or else a cell to fill in")
+    repl.add_command("undo", undo, "take back your last entry")
+    repl.add_command("new", new, "start again, on a new puzzle unless one was given")
+    print("An entry is a cell and its digit, like e5 7 (e5 0 empties it), and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def read_puzzles(argument: str) -> List[Grid]:
+    if os.path.exists(argument):
+        with open(argument) as f:
+            return parse_many(f.read())
+    return [parse(argument)]
+
+
+def solve_command(grids: List[Grid], count: bool, stats: bool, layout: Layout = STANDARD):
+    totals: Dict[str, int] = {"solved": 0, "multiple": 0, "unsolvable": 0}
+    start = time.perf_counter()
+    for number, grid in enumerate(grids, start=1):
+        solver = Solver(layout)
+        found = solver.solutions(grid, limit=2)
+        if len(grids) > 1:
+            print(f"Puzzle {number}")
+        if not found:
+            totals["unsolvable"] += 1
+            print("No solution")
+        else:
+            totals["solved" if len(found) == 1 else "multiple"] += 1
+            if count:
+                print("One solution" if len(found) == 1 else "More than one solution")
+            else:
+                print(format_grid(found[0]))
+                if len(found) > 1:
+                    print("(more than one solution; this is the first)")
+        if stats:
+            print(solver.statistics)
+        if len(grids) > 1:
+            print()
+    if len(grids) > 1:
+        elapsed = time.perf_counter() - start
+        summary: Tuple[str, ...] = tuple(f"{n} {kind}" for kind, n in totals.items() if n)
+        print(f"{len(grids)} puzzles in {elapsed:.2f}s: {', '.join(summary)}")
+
+
+def grade_command(grids: List[Grid]):
+    grader = Grader()
+    for number, grid in enumerate(grids, start=1):
+        found = solutions(grid, 2)
+        if len(found) != 1:
+            verdict = "no solution" if not found else "more than one solution"
+        else:
+            verdict = SCALE.describe(grader.rate(grid))
+        print(f"Puzzle {number}: {verdict}")
+
+
+def generate_command(count: int, level: Optional[str], seed: Optional[int], symmetric: bool):
+    rng = make_rng(seed)
+    errors: List[str] = []
+    for _ in range(count):
+        try:
+            puzzle, rating = generate_graded(rng, level, symmetric)
+        except ValueError as e:
+            errors.append(str(e))
+            break
+        print(f"# {SCALE.describe(rating)}; {sum(1 for digit in puzzle if digit)} givens")
+        print(to_line(puzzle))
+    if errors:
+        raise ValueError("\n".join(errors))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Sudoku solver, grader, and generator")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="solve puzzles")
+    solve_parser.add_argument("puzzle", type=str, help="the 81 characters, or a file of puzzles")
+    solve_parser.add_argument("--count", action="store_true", help="say whether the solution is unique instead")
+    solve_parser.add_argument("--stats", action="store_true", help="print the nodes and guesses of each search")
+    grade_parser = subparsers.add_parser("grade", help="grade puzzles by the techniques they need")
+    grade_parser.add_argument("puzzle", type=str, help="the 81 characters, or a file of puzzles")
+    generate_parser = subparsers.add_parser("generate", help="generate puzzles with unique solutions")
+    generate_parser.add_argument("--count", type=int, default=1, help="how many puzzles")
+    generate_parser.add_argument("--difficulty", choices=LEVELS, default=None,
+                                 help="only keep puzzles that rate at this level")
+    add_seed_argument(generate_parser)
+    generate_parser.add_argument("--asymmetric", action="store_true", help="let the givens be anywhere")
+    check_parser = subparsers.add_parser("check", help="generate puzzles, and check that each has one valid solution")
+    check_parser.add_argument("--count", type=int, default=20, help="how many puzzles")
+    add_seed_argument(check_parser)
+    check_parser.add_argument("--asymmetric", action="store_true", help="let the givens be anywhere")
+    play_parser = subparsers.add_parser("play", help="fill in a puzzle yourself, with hints")
+    play_parser.add_argument("puzzle", type=str, nargs="?", default=None,
+                             help="the 81 characters, or a file of puzzles (the first is played); "
+                                  "without it, a new one")
+    play_parser.add_argument("--difficulty", choices=LEVELS, default=None, help="of the new puzzles")
+    add_seed_argument(play_parser)
+    play_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    args = parser.parse_args()
+
+    if args.command == "check":
+        if not check(args.count, make_rng(args.seed), not args.asymmetric):
+            parser.exit(1)
+        return
+    try:
+        if args.command == "generate":
+            generate_command(args.count, args.difficulty, args.seed, not args.asymmetric)
+            return
+        if args.command == "play":
+            if args.puzzle is not None:
+                puzzle = read_puzzles(args.puzzle)[0]
+                play(lambda: puzzle, to_line(puzzle), not args.no_record)
+            else:
+                rng = make_rng(args.seed)
+                instance = f"{args.difficulty or 'any'}, " + ("random" if args.seed is None else f"seed {args.seed}")
+                play(lambda: generate_graded(rng, args.difficulty)[0], instance, not args.no_record)
+            return
+        grids = read_puzzles(args.puzzle)
+    except ValueError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    if args.command == "grade":
+        grade_command(grids)
+    else:
+        solve_command(grids, args.count, args.stats)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,219 @@
+"""
+# Sudoku Variants
+
+Sudoku with extra or different rules, solved by `csp/csp.py`: each cell
+is a variable with domain 1 to 9, cells that share a unit must differ,
+and a variant adds the constraints of its own rules.
+
+    x           the two long diagonals are units too
+    regions     irregular (jigsaw) regions of nine cells replace the boxes
+    cages       a cage's digits differ and add up to its sum (Killer)
+
+The rules may be combined, as in `puzzles/killer_x.txt`. A cage is a
+sum cage from `csp/cages.py`, which keeps track of the sets of digits
+that could still fill it all through the search, so a Killer with no
+givens takes a few seconds.
+
+
+## The Format
+
+A line that is one of the keywords below starts a section, and lines
+starting with # are comments:
+
+    x           (no lines) the diagonals are units
+    givens      nine rows of digits, with . for the empty cells
+    regions     nine rows of nine letters, each naming its cell's region
+    cages       nine rows of letters naming each cell's cage, with . for
+                none, followed by one line with each cage's sum: a=13 b=7
+
+With no `givens` the grid starts empty (a Killer usually does), and with
+no `regions` the boxes are the usual 3x3.
+
+
+## Usage
+
+    python3 variants.py puzzles/x.txt
+    python3 variants.py puzzles/jigsaw.txt
+    python3 variants.py puzzles/killer.txt
+    python3 variants.py puzzles/killer_x.txt --count --stats
+"""
+
+import argparse
+import itertools
+import operator
+import os
+import sys
+from typing import Dict, List, Optional, Set, Tuple
+
+from sudoku import BOXES, CELLS, COLUMNS, ROWS, SIZE, Grid, Layout, format_grid
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.cages import add_sum_cage, cage_digits
+from csp.csp import CSP, Statistics, solutions
+
+
+DIAGONALS: List[List[int]] = [[10 * i for i in range(SIZE)], [8 * (i + 1) for i in range(SIZE)]]
+KEYWORDS: Set[str] = {"x", "givens", "regions", "cages"}
+
+
+class Variant:
+    def __init__(self, givens: Optional[Grid] = None, regions: Optional[List[List[int]]] = None,
+                 x: bool = False, cages: Optional[List[Tuple[List[int], int]]] = None):
+        self.givens = givens if givens is not None else [0] * CELLS
+        self.regions = regions
+        self.x = x
+        self.cages = cages or []
+        self.layout = Layout(ROWS + COLUMNS + (regions or BOXES) + (DIAGONALS if x else []))
+
+    def rules(self) -> str:
+        names = ["X" if self.x else "", "jigsaw" if self.regions else "", "killer" if self.cages else ""]
+        return " ".join(name for name in names if name) or "classic"
+
+
+def to_csp(variant: Variant) -> CSP:
+    csp = CSP()
+    for cell, given in enumerate(variant.givens):
+        csp.add_variable(cell, [given] if given else range(1, SIZE + 1))
+    different: Set[Tuple[int, int]] = set()
+    groups = variant.layout.units + [cells for cells, _ in variant.cages]
+    for group in groups:
+        different.update(itertools.combinations(sorted(group), 2))
+    for a, b in sorted(different):
+        csp.add_constraint([a, b], operator.ne)
+    for number, (cells, total) in enumerate(variant.cages):
+        add_sum_cage(csp, ("cage", number), cells, total, different=False)
+    return csp
+
+
+def solve(variant: Variant, limit: int = 2, statistics: Optional[Statistics] = None) -> List[Grid]:
+    """Up to `limit` solutions, as with `sudoku.solutions`."""
+    found = []
+    for assignment in solutions(to_csp(variant), statistics=statistics):
+        found.append([assignment[cell] for cell in range(CELLS)])
+        if len(found) >= limit:
+            break
+    return found
+
+
+################################################################################
+###                              Reading and Writing                         ###
+################################################################################
+
+
+def parse(text: str) -> Variant:
+    errors: List[str] = []
+    sections: Dict[str, List[Tuple[int, str]]] = {}
+    current: Optional[List[Tuple[int, str]]] = None
+    for number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        if line in KEYWORDS:
+            if line in sections:
+                errors.append(f"line {number}: a second {line} section")
+            current = sections.setdefault(line, [])
+        elif current is None:
+            errors.append(f"line {number}: expected one of {', '.join(sorted(KEYWORDS))}")
+        else:
+            current.append((number, line))
+
+    def rows_of(name: str, lines: List[Tuple[int, str]]) -> List[str]:
+        rows = [row.replace(" ", "") for _, row in lines[:SIZE]]
+        if len(rows) < SIZE or any(len(row) != SIZE for row in rows):
+            at = lines[0][0] if lines else "end"
+            errors.append(f"line {at}: the {name} should be {SIZE} rows of {SIZE}")
+            return []
+        return rows
+
+    if sections.get("x"):
+        errors.append(f"line {sections['x'][0][0]}: x takes no lines")
+    givens = None
+    if "givens" in sections:
+        rows = rows_of("givens", sections["givens"])
+        if rows:
+            cells = "".join(rows)
+            if any(c not in "0123456789." for c in cells):
+                errors.append(f"line {sections['givens'][0][0]}: the givens are digits, and . for empty cells")
+            else:
+                givens = [0 if c == "." else int(c) for c in cells]
+    regions = None
+    if "regions" in sections:
+        rows = rows_of("regions", sections["regions"])
+        if rows:
+            named: Dict[str, List[int]] = {}
+            for cell, name in enumerate("".join(rows)):
+                named.setdefault(name, []).append(cell)
+            regions = list(named.values())
+            for name, cells in named.items():
+                if len(cells) != SIZE:
+                    errors.append(f"line {sections['regions'][0][0]}: region {name} has {len(cells)} cells, not {SIZE}")
+    cages = None
+    if "cages" in sections:
+        lines = sections["cages"]
+        rows = rows_of("cages", lines)
+        if rows:
+            named = {}
+            for cell, name in enumerate("".join(rows)):
+                if name != ".":
+                    named.setdefault(name, []).append(cell)
+            sums: Dict[str, int] = {}
+            for number, line in lines[SIZE:]:
+                for entry in line.replace(",", " ").split():
+                    name, _, total = entry.partition("=")
+                    if not total.isdigit():
+                        errors.append(f"line {number}: expected a cage's sum, like a=13, not {entry!r}")
+                    else:
+                        sums[name] = int(total)
+            cages = []
+            for name, cells in named.items():
+                if name not in sums:
+                    errors.append(f"line {lines[0][0]}: cage {name} has no sum")
+                elif not cage_digits(len(cells), sums[name]):
+                    errors.append(f"line {lines[0][0]}: cage {name}'s {len(cells)} different digits "
+                                  f"cannot add up to {sums[name]}")
+                else:
+                    cages.append((cells, sums[name]))
+            for name in sorted(set(sums) - set(named)):
+                errors.append(f"line {lines[SIZE][0]}: there is no cage {name}")
+    if errors:
+        raise ValueError("\n".join(errors))
+    try:
+        return Variant(givens, regions, "x" in sections, cages)
+    except ValueError as e:
+        raise ValueError(f"line {sections['regions'][0][0]}: {e}") from None
+
+
+def format_solution(variant: Variant, grid: Grid) -> str:
+    """With the box lines if the boxes are the usual ones, or one row per line if not."""
+    if variant.regions is None:
+        return format_grid(grid)
+    return "\n".join(" ".join(map(str, grid[SIZE * r:SIZE * r + SIZE])) for r in range(SIZE))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Sudoku variants")
+    parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    parser.add_argument("--count", action="store_true", help="say whether the solution is unique too")
+    parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            variant = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+    statistics = Statistics()
+    found = solve(variant, limit=2 if args.count else 1, statistics=statistics)
+    print(f"{variant.rules()} Sudoku")
+    if not found:
+        print("No solution")
+    else:
+        print(format_solution(variant, found[0]))
+        if args.count:
+            print("One solution" if len(found) == 1 else "More than one solution")
+    if args.stats:
+        print(statistics)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,166 @@
+"""
+# Tag Systems
+
+Emil Post's tag systems are about as simple as a model of computation
+gets. A word is a string of symbols, and at every step an m-tag system
+(m is the deletion number)
+
+1. looks at the first symbol of the word,
+2. appends that symbol's production to the end of the word, and
+3. deletes the first m symbols.
+
+The system halts when the word is shorter than m, or when its first
+symbol is a halting symbol (one with no production). Even 2-tag systems
+are universal.
+
+
+## Collatz
+
+Liesbeth De Mol found this 2-tag system, which computes the Collatz
+function on a word of n `a`s:
+
+    a -> bc
+    b -> a
+    c -> aaa
+
+Starting from `a` * n, the word passes through `a` * (n / 2) if n is
+even, and `a` * (3n + 1) / 2 if n is odd, so the unary words in the trace
+are the (shortcut) Collatz sequence. Once n = 1 the word is a single
+`a`, which is shorter than m, so the system halts exactly when the
+sequence reaches 1.
+
+
+## Usage
+
+    python3 tag_system.py --deletion 2 --rule a:bc --rule b:a --rule c:aaa --word aaaaaaa
+    python3 tag_system.py --collatz 27 --quiet
+"""
+
+import argparse
+from typing import Dict, Iterator, List, NamedTuple, Optional, Set
+
+
+DEFAULT_MAX_STEPS: int = 1_000_000
+TRACE_WIDTH: int = 72
+COLLATZ_RULES: Dict[str, str] = {"a": "bc", "b": "a", "c": "aaa"}
+
+
+class TagSystem(NamedTuple):
+    deletion: int
+    productions: Dict[str, str]
+
+
+class Result(NamedTuple):
+    status: str     # "halt", "cycle", or "step limit exceeded"
+    steps: int
+    word: str
+
+
+def parse_rules(rules: List[str]) -> Dict[str, str]:
+    productions: Dict[str, str] = {}
+    for rule in rules:
+        symbol, colon, production = rule.partition(":")
+        if not colon or len(symbol) != 1:
+            raise ValueError(f"expected symbol:production, not '{rule}'")
+        if symbol in productions:
+            raise ValueError(f"'{symbol}' has two productions")
+        productions[symbol] = production
+    return productions
+
+
+def steps(system: TagSystem, word: str) -> Iterator[str]:
+    """Yield each word after the first, until the system halts."""
+    while len(word) >= system.deletion and word[0] in system.productions:
+        word = word[system.deletion:] + system.productions[word[0]]
+        yield word
+
+
+def run(
+    system: TagSystem,
+    word: str,
+    max_steps: int,
+    trace: bool = False,
+    detect_cycles: bool = True,
+) -> Result:
+    seen: Set[str] = {word}
+    if trace:
+        print(format_word(0, word))
+    count = 0
+    for count, word in enumerate(steps(system, word), start=1):
+        if trace:
+            print(format_word(count, word))
+        if detect_cycles:
+            if word in seen:
+                return Result("cycle", count, word)
+            seen.add(word)
+        if count >= max_steps:
+            return Result("step limit exceeded", count, word)
+    return Result("halt", count, word)
+
+
+def shorten(word: str) -> str:
+    return word if len(word) <= TRACE_WIDTH else word[:TRACE_WIDTH - 3] + "..."
+
+
+def format_word(step: int, word: str) -> str:
+    return f"{step:>8}  {shorten(word)}"
+
+
+def unary_length(word: str, symbol: str = "a") -> Optional[int]:
+    return len(word) if word and set(word) == {symbol} else None
+
+
+def collatz(n: int, max_steps: int, quiet: bool) -> Result:
+    """Run De Mol's system on a^n, printing the Collatz numbers it passes through."""
+    system = TagSystem(2, COLLATZ_RULES)
+    word = "a" * n
+    sequence = [n]
+    count = 0
+    for count, word in enumerate(steps(system, word), start=1):
+        m = unary_length(word)
+        if m is not None:
+            sequence.append(m)
+            if not quiet:
+                print(format_word(count, word))
+        if count >= max_steps:
+            print(f"Collatz: {' '.join(map(str, sequence))} ...")
+            return Result("step limit exceeded", count, word)
+    print(f"Collatz: {' '.join(map(str, sequence))}")
+    return Result("halt", count, word)
+
+
+################################################################################
+###                                   CLI                                    ###
+################################################################################
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Post tag system interpreter")
+    parser.add_argument("--deletion", type=int, default=2, help="symbols deleted per step (m)")
+    parser.add_argument("--rule", type=str, action="append", default=[], help="e.g. a:bc")
+    parser.add_argument("--word", type=str, default="", help="the initial word")
+    parser.add_argument("--collatz", type=int, default=None, help="run De Mol's Collatz system on n")
+    parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    parser.add_argument("--quiet", action="store_true", help="do not print the trace")
+    parser.add_argument("--no-detect", action="store_true", help="do not look for repeated words")
+    args = parser.parse_args()
+
+    if args.deletion < 1:
+        parser.error("the deletion number must be at least 1")
+    if args.collatz is not None:
+        if args.collatz < 1:
+            parser.error("Collatz needs a positive number")
+        result = collatz(args.collatz, args.max_steps, args.quiet)
+    else:
+        try:
+            system = TagSystem(args.deletion, parse_rules(args.rule))
+        except ValueError as e:
+            parser.error(str(e))
+        result = run(system, args.word, args.max_steps, not args.quiet, not args.no_detect)
+    print(f"Result: {result.status}")
+    print(f"Steps: {result.steps}")
+    print(f"Word: {shorten(result.word)}")
+
+
+if __name__ == "__main__":
+    main()
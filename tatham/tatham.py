@@ -0,0 +1,311 @@
+"""
+# Simon Tatham's Puzzles
+
+Simon Tatham's Portable Puzzle Collection has several of the puzzles
+solved here, under other names. Each of its puzzles can be given as a
+game ID, its parameters and its description separated by a colon
+(`5x5:2/1.3/...`), which the games show under "Game > Specific", and
+its save files carry the same two, among the moves. This reads either,
+and writes the puzzle in the format of the solver here:
+
+    Pattern     nonogram/nonogram.py    the clues, columns first, each
+                                        run separated by . and each
+                                        clue by /
+    Bridges     hashi/hashi.py          the islands as 1 to 9 and A up
+                                        (for 10 and more), and a letter
+                                        a to z for a run of 1 to 26
+                                        squares of water
+    Flip        lights_out/lights_out.py
+                                        in hex, which squares flip
+                                        which (a w*h by w*h matrix),
+                                        then a comma and the squares to
+                                        turn off; only the crosses kind,
+                                        which is Lights Out
+    Solo        sudoku/sudoku.py        the givens as numbers, a to z
+                                        for a run of 1 to 26 empty
+                                        cells, and _ between two
+                                        numbers; only plain 9x9
+    Loopy       slitherlink/slitherlink.py
+                                        the clues as digits, and a to z
+                                        for a run of cells without one;
+                                        only the square grid (t0)
+    Fifteen     sliding/sliding.py      the tiles separated by commas,
+                                        0 for the space
+    Singles     hitori/hitori.py        a character a cell, 1 to 9 and
+                                        then a to z for 10 and up
+
+Of the others, Slant (diagonals in every square, with numbered corners)
+has no solver here, and the descriptions of Mines are hidden on purpose,
+so that reading one does not give the mines away. The games do not
+check a description beyond its shape, and neither does this: whether
+the puzzle can be solved is for the solver to say.
+
+
+## Save Files
+
+A line `SAVEFILE:41:Simon Tatham's Portable Puzzle Collection`, then
+one line a field, each a key padded to 8 characters, the length of the
+value, and the value, separated by colons:
+
+    GAME    :7:Pattern
+    CPARAMS :3:5x5
+    DESC    :31:2/1.3/3.1/1.3/2/3/1.1.1/5/1.1/3
+
+GAME names the puzzle, CPARAMS has the parameters (PARAMS, if there is
+no CPARAMS, though that is the next game's) and DESC the description.
+
+
+## Usage
+
+    python3 tatham.py id pattern 5x5:2/1.3/3.1/1.3/2/3/1.1.1/5/1.1/3
+    python3 tatham.py id bridges 7x7m2:a3b3a4h6b3h4a2g1a2h2
+    python3 tatham.py id flip 3x3c:d074191345d1644c17058,aa8
+    python3 tatham.py id solo 3x3:b3a2a6b9b3a5b1b1_8a6_4d8_1a2_9b7g8b6_7a8_2d2_6a9_5b8b2a3b9b5a1a3b
+    python3 tatham.py id loopy 5x5t0:1a1d3a0c0a11a1e3
+    python3 tatham.py id fifteen 3x3:1,2,3,4,0,6,7,5,8
+    python3 tatham.py id singles 5x5:1424343114314252214424251
+    python3 tatham.py save saves/heart.sav
+    python3 tatham.py save saves/heart.sav --output heart.txt
+"""
+
+import argparse
+import re
+from typing import Callable, Dict, List, NamedTuple, Tuple
+
+
+SAVE_HEADER: str = "Simon Tatham's Portable Puzzle Collection"
+TOKENS = re.compile(r"[a-z]|_|\d+|.")     # a run of blanks, a separator, a number, or anything else
+
+
+class Imported(NamedTuple):
+    game: str           # the puzzle's name in the collection
+    solver: str         # the script here that solves it
+    text: str           # the puzzle, in that script's format
+
+
+def dimensions(params: str) -> Tuple[int, int, str]:
+    """The width and height at the start of the parameters, and the rest of them (a size alone is square)."""
+    match = re.match(r"(\d+)(?:x(\d+))?", params)
+    if not match:
+        raise ValueError(f"expected the parameters to start with a size like 5x5, not {params!r}")
+    width = int(match.group(1))
+    height = int(match.group(2) or width)
+    if width < 1 or height < 1:
+        raise ValueError(f"a {width}x{height} grid has no squares")
+    return width, height, params[match.end():]
+
+
+def expand(desc: str, digits: Callable[[str], List[int]]) -> List[int]:
+    """The cells of a run-length description: a letter a to z for 1 to 26 blanks (as 0), _ between two
+    numbers, and otherwise what `digits` makes of each token."""
+    cells: List[int] = []
+    for token in TOKENS.findall(desc):
+        if "a" <= token <= "z":
+            cells.extend([0] * (ord(token) - ord("a") + 1))
+        elif token != "_":
+            cells.extend(digits(token))
+    return cells
+
+
+def check_length(cells: List, width: int, height: int):
+    if len(cells) != width * height:
+        raise ValueError(f"the description has {len(cells)} squares, not the {width * height} of {width}x{height}")
+
+
+def rows(cells: List[str], width: int, separator: str = "") -> str:
+    return "\n".join(separator.join(cells[i:i + width]) for i in range(0, len(cells), width))
+
+
+################################################################################
+###                                  Games                                   ###
+################################################################################
+
+
+def pattern(params: str, desc: str) -> str:
+    width, height, _ = dimensions(params)
+    if "," in desc:
+        raise ValueError("squares filled in from the start are not part of a nonogram here")
+    clues = desc.split("/")
+    if len(clues) != width + height:
+        raise ValueError(f"a {width}x{height} Pattern has {width + height} clues, not {len(clues)}")
+    runs = []
+    for clue in clues:
+        if not re.fullmatch(r"(\d+(\.\d+)*)?", clue):
+            raise ValueError(f"expected a clue like 3.1.2, not {clue!r}")
+        runs.append(" ".join(clue.split(".")) if clue else "0")
+    return "\n".join(["rows"] + runs[width:] + ["columns"] + runs[:width])
+
+
+def bridges(params: str, desc: str) -> str:
+    width, height, rest = dimensions(params)
+    most = re.search(r"m(\d+)", rest)
+    if most and int(most.group(1)) != 2:
+        raise ValueError(f"islands joined by up to {most.group(1)} bridges, where the rules here allow 2")
+
+    def island(token: str) -> List[int]:
+        if token.isdigit():
+            return [int(c) for c in token]
+        if "A" <= token <= "Z":
+            return [ord(token) - ord("A") + 10]
+        raise ValueError(f"unexpected {token!r} in the description")
+
+    cells = expand(desc, island)
+    check_length(cells, width, height)
+    if any(n > 8 for n in cells):
+        raise ValueError("an island of more than 8 bridges needs more than 2 to a side")
+    return rows([str(n) if n else "." for n in cells], width)
+
+
+def hex_bits(text: str, count: int) -> List[bool]:
+    """The first count bits of the hex digits, the high bit of each first."""
+    if not re.fullmatch(r"[0-9a-fA-F]*", text) or len(text) != (count + 3) // 4:
+        raise ValueError(f"expected {(count + 3) // 4} hex digits for {count} squares, not {text!r}")
+    return [bool(int(text[i // 4], 16) & 8 >> i % 4) for i in range(count)]
+
+
+def flip(params: str, desc: str) -> str:
+    width, height, _ = dimensions(params)
+    area = width * height
+    matrix, comma, lights = desc.partition(",")
+    if not comma:
+        raise ValueError("expected the flips, a comma and the squares")
+    flips = hex_bits(matrix, area * area)
+    for i in range(area):
+        x, y = i % width, i // width
+        crosses = [abs(j % width - x) + abs(j // width - y) <= 1 for j in range(area)]
+        if flips[i * area:(i + 1) * area] != crosses:
+            raise ValueError("only the crosses kind of Flip is Lights Out; in this one, the square at "
+                             f"{(x, y)} flips another shape")
+    return rows(["#" if on else "." for on in hex_bits(lights, area)], width)
+
+
+def solo(params: str, desc: str) -> str:
+    columns, rows_, rest = dimensions(params)
+    if columns * rows_ != 9 or columns != rows_ or re.search(r"[xjk]", rest) or "," in desc:
+        raise ValueError("only a plain 9x9 Solo, without X, jigsaw or killer rules, is a Sudoku here")
+    cells = expand(desc, lambda token: [int(token)] if token.isdigit() else [-1])
+    if any(not 0 <= n <= 9 for n in cells):
+        raise ValueError("expected digits 1 to 9, runs of a to z, and _")
+    check_length(cells, 9, 9)
+    return "".join(str(n) if n else "." for n in cells)
+
+
+def loopy(params: str, desc: str) -> str:
+    width, height, rest = dimensions(params)
+    kind = re.match(r"t(\d+)", rest)
+    if kind and kind.group(1) != "0":
+        raise ValueError(f"only the square grid (t0) is a Slitherlink here, not t{kind.group(1)}")
+    cells = expand(desc, lambda token: [int(c) + 1 for c in token] if token.isdigit() else [-1])
+    if -1 in cells:
+        raise ValueError("expected clues 0 to 9 and runs of a to z")
+    check_length(cells, width, height)
+    return rows([str(n - 1) if n else "." for n in cells], width)
+
+
+def fifteen(params: str, desc: str) -> str:
+    width, height, _ = dimensions(params)
+    if not re.fullmatch(r"\d+(,\d+)*", desc):
+        raise ValueError("expected the tiles as numbers separated by commas")
+    tiles = [int(word) for word in desc.split(",")]
+    check_length(tiles, width, height)
+    if sorted(tiles) != list(range(width * height)):
+        raise ValueError(f"expected each tile from 0 to {width * height - 1} once")
+    size = len(str(width * height - 1))
+    return rows([str(tile).rjust(size) for tile in tiles], width, " ")
+
+
+def singles(params: str, desc: str) -> str:
+    width, height, _ = dimensions(params)
+    digits = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
+    if any(c not in digits for c in desc):
+        raise ValueError("expected a character a square: 1 to 9, then a to z, then A to Z")
+    check_length(list(desc), width, height)
+    return rows([str(digits.index(c)) for c in desc], width, " ")
+
+
+GAMES: Dict[str, Tuple[str, Callable[[str, str], str]]] = {
+    "pattern": ("nonogram/nonogram.py", pattern),
+    "bridges": ("hashi/hashi.py", bridges),
+    "flip": ("lights_out/lights_out.py", flip),
+    "solo": ("sudoku/sudoku.py", solo),
+    "loopy": ("slitherlink/slitherlink.py", loopy),
+    "fifteen": ("sliding/sliding.py", fifteen),
+    "singles": ("hitori/hitori.py", singles),
+}
+
+
+def import_id(game: str, game_id: str) -> Imported:
+    name = game.lower()
+    if name not in GAMES:
+        raise ValueError(f"there is nothing here for {game}, only for {', '.join(GAMES)}")
+    params, colon, desc = game_id.strip().partition(":")
+    if not colon:
+        raise ValueError("a game ID is the parameters and the description, separated by a colon")
+    solver, read = GAMES[name]
+    return Imported(name.capitalize(), solver, read(params, desc))
+
+
+################################################################################
+###                                Save Files                                ###
+################################################################################
+
+
+def read_save(text: str) -> Dict[str, str]:
+    """The fields of a save file, the first of each key."""
+    fields: Dict[str, str] = {}
+    at = 0
+    while at < len(text):
+        match = re.compile(r"([^:\n]{1,8}):(\d+):").match(text, at)
+        if not match:
+            line = text.count("\n", 0, at) + 1
+            raise ValueError(f"line {line}: expected a key, the length of its value and the value")
+        key = match.group(1).strip()
+        end = match.end() + int(match.group(2))
+        if end > len(text):
+            raise ValueError(f"the value of {key} is cut short")
+        fields.setdefault(key, text[match.end():end])
+        at = end + (text[end:end + 1] == "\n")
+    if fields.get("SAVEFILE") != SAVE_HEADER:
+        raise ValueError("this is not a save file from Simon Tatham's Portable Puzzle Collection")
+    return fields
+
+
+def import_save(text: str) -> Imported:
+    fields = read_save(text)
+    params = fields.get("CPARAMS", fields.get("PARAMS"))
+    for key, value in (("GAME", fields.get("GAME")), ("PARAMS", params), ("DESC", fields.get("DESC"))):
+        if value is None:
+            raise ValueError(f"the save file has no {key}")
+    return import_id(fields["GAME"], f"{params}:{fields['DESC']}")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Read puzzles from Simon Tatham's Portable Puzzle Collection")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--output", type=str, default=None, help="write the puzzle here instead")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    id_parser = subparsers.add_parser("id", parents=[common], help="read a game ID")
+    id_parser.add_argument("game", type=str, help=f"one of {', '.join(GAMES)}")
+    id_parser.add_argument("id", type=str, help="the parameters and description, like 5x5:2/1.3/...")
+    save_parser = subparsers.add_parser("save", parents=[common], help="read a save file")
+    save_parser.add_argument("file", type=str)
+    args = parser.parse_args()
+
+    try:
+        if args.command == "id":
+            imported = import_id(args.game, args.id)
+        else:
+            with open(args.file) as f:
+                imported = import_save(f.read())
+        if args.output is None:
+            print(imported.text)
+        else:
+            with open(args.output, "w") as f:
+                f.write(imported.text + "\n")
+            print(f"{imported.game} written to {args.output}, for {imported.solver}")
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,359 @@
+"""
+# Tents and Trees
+
+A grid with trees in some cells. Put a tent next to each tree, across or
+down from it, so that the trees and the tents pair off, each tree with a
+tent beside it; no two tents touch, not even at a corner; and each row
+and column has as many tents as the number beside it.
+
+
+## Solving
+
+Each cell without a tree is a tent, grass, or not known yet. A cell with
+no tree beside it is grass from the start, and the rules decide cells,
+over and over until none changes:
+
+    count       a row or column with as many tents as its number has the
+                rest grass; one with as many tents and unknown cells as
+                its number has all of them tents
+    touching    the eight cells around a tent are grass
+    tree        a tree with one cell beside it that is not grass has its
+                tent there
+    matching    the trees and the tents must pair off: there must be a
+                way to give every tree its own tent or unknown cell
+                beside it, and one to give every tent its own tree
+
+The matching rule finds each way by augmenting paths, and by a theorem of
+Mendelsohn and Dulmage, when both ways exist, so does one way doing
+both, so that is all it has to check.
+
+When the rules get stuck, each unknown cell is tried both ways, and if
+the rules find one way impossible, it is the other (probing). When that
+gets stuck too, the search guesses a cell beside the tree with the
+fewest left, a tent and then grass. A solution is every cell decided,
+which `verify` checks from scratch: the counts, no tents touching, and
+a pairing of the trees and tents. It stops after two, so it says whether
+the puzzle is proper.
+
+
+## The Format
+
+The column counts, then each row as its count and its cells, separated
+by spaces: T for a tree, . for a cell to fill in, and A for a tent
+(given, or for `check`, placed). Lines starting with # are comments.
+
+      1 1 0 2 0
+    2 . T . . .
+    0 . . . . T
+    1 . T . . .
+    1 T . . . .
+    0 . . . . .
+
+
+## Usage
+
+    python3 tents.py solve puzzles/small.txt
+    python3 tents.py solve puzzles/medium.txt --stats
+    python3 tents.py solve puzzles/large.txt --stats
+    python3 tents.py solve puzzles/ambiguous.txt
+    python3 tents.py check puzzles/solved.txt
+    python3 tents.py check puzzles/wrong.txt
+"""
+
+import argparse
+import os
+import sys
+from typing import Dict, List, Optional, Set
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import Grid, Position
+
+
+UNKNOWN, GRASS, TENT = ".", "-", "A"
+TREE: str = "T"
+State = Dict[Position, str]     # each cell without a tree: UNKNOWN, GRASS or TENT
+
+
+class Contradiction(Exception):
+    pass
+
+
+class Statistics:
+    def __init__(self):
+        self.decisions = 0
+        self.probes = 0
+        self.guesses = 0
+
+    def __str__(self) -> str:
+        return f"{self.decisions} cells decided by the rules, {self.probes} by probing, {self.guesses} guesses"
+
+
+class Puzzle:
+    def __init__(self, grid: Grid, rows: List[int], columns: List[int]):
+        self.grid = grid
+        self.rows = rows
+        self.columns = columns
+        self.trees: List[Position] = grid.find(TREE)
+        self.cells: List[Position] = [position for position, c in grid.items() if c != TREE]
+        self.beside: Dict[Position, List[Position]] = {      # tree -> cells beside it, cell -> trees beside it
+            position: [n for n in grid.neighbours(position) if (grid[n] == TREE) != (c == TREE)]
+            for position, c in grid.items()}
+        self.around: Dict[Position, List[Position]] = {
+            cell: [n for n in grid.neighbours(cell, diagonal=True) if grid[n] != TREE] for cell in self.cells}
+        self.lines: List[List[Position]] = [[(x, y) for x in range(grid.width) if grid[x, y] != TREE]
+                                            for y in range(grid.height)]
+        self.lines += [[(x, y) for y in range(grid.height) if grid[x, y] != TREE] for x in range(grid.width)]
+        self.counts: List[int] = rows + columns
+
+
+def match(left: List[Position], edges: Dict[Position, List[Position]]) -> Dict[Position, Position]:
+    """A largest matching of left to their edges, by augmenting paths, as right -> left."""
+    owner: Dict[Position, Position] = {}
+
+    def augment(node: Position, seen: Set[Position]) -> bool:
+        for other in edges[node]:
+            if other not in seen:
+                seen.add(other)
+                if other not in owner or augment(owner[other], seen):
+                    owner[other] = node
+                    return True
+        return False
+
+    for node in left:
+        augment(node, set())
+    return owner
+
+
+################################################################################
+###                                  Solving                                 ###
+################################################################################
+
+
+class Solver:
+    def __init__(self, puzzle: Puzzle):
+        self.puzzle = puzzle
+        self.statistics = Statistics()
+
+    def decide(self, state: State, cell: Position, value: str) -> bool:
+        """Set an unknown cell; whether it was unknown. Raises Contradiction if it was the other value."""
+        if state[cell] == value:
+            return False
+        if state[cell] != UNKNOWN:
+            raise Contradiction
+        state[cell] = value
+        self.statistics.decisions += 1
+        return True
+
+    def propagate(self, state: State):
+        """Apply the rules until no cell changes. Raises Contradiction if a cell can be neither."""
+        puzzle = self.puzzle
+        changed = True
+        while changed:
+            changed = False
+            for cells, count in zip(puzzle.lines, puzzle.counts):
+                tents = sum(state[cell] == TENT for cell in cells)
+                unknown = [cell for cell in cells if state[cell] == UNKNOWN]
+                if tents > count or tents + len(unknown) < count:
+                    raise Contradiction
+                if unknown and tents == count:
+                    changed |= any([self.decide(state, cell, GRASS) for cell in unknown])
+                elif unknown and tents + len(unknown) == count:
+                    changed |= any([self.decide(state, cell, TENT) for cell in unknown])
+            for cell in puzzle.cells:
+                if state[cell] == TENT:
+                    changed |= any([self.decide(state, other, GRASS) for other in puzzle.around[cell]])
+            for tree in puzzle.trees:
+                open_cells = [cell for cell in puzzle.beside[tree] if state[cell] != GRASS]
+                if not open_cells:
+                    raise Contradiction
+                if len(open_cells) == 1:
+                    changed |= self.decide(state, open_cells[0], TENT)
+            if not changed:
+                self.pair(state)
+
+    def pair(self, state: State):
+        """The matching rule. Raises Contradiction if the trees, or the tents, cannot each have their own."""
+        puzzle = self.puzzle
+        open_beside = {tree: [cell for cell in puzzle.beside[tree] if state[cell] != GRASS] for tree in puzzle.trees}
+        if len(match(puzzle.trees, open_beside)) < len(puzzle.trees):
+            raise Contradiction
+        tents = [cell for cell in puzzle.cells if state[cell] == TENT]
+        if len(match(tents, puzzle.beside)) < len(tents):
+            raise Contradiction
+
+    def probe(self, state: State):
+        """Try each unknown cell each way, and where one way leads the rules to a contradiction, decide it the
+        other, until that decides nothing more. Raises Contradiction if both ways do."""
+        changed = True
+        while changed:
+            changed = False
+            for cell in self.puzzle.cells:
+                if state[cell] != UNKNOWN:
+                    continue
+                for value in (TENT, GRASS):
+                    trial = dict(state)
+                    try:
+                        self.decide(trial, cell, value)
+                        self.propagate(trial)
+                    except Contradiction:
+                        self.statistics.probes += 1
+                        self.decide(state, cell, GRASS if value == TENT else TENT)
+                        self.propagate(state)
+                        changed = True
+                        break
+
+    def guess(self, state: State) -> Optional[Position]:
+        """An unknown cell beside the tree with the fewest, or None if no cell is unknown."""
+        choices = [[cell for cell in self.puzzle.beside[tree] if state[cell] == UNKNOWN] for tree in self.puzzle.trees]
+        choices = [cells for cells in choices if cells]
+        if choices:
+            return min(choices, key=len)[0]
+        return next((cell for cell in self.puzzle.cells if state[cell] == UNKNOWN), None)
+
+    def search(self, state: State, found: List[State], limit: int):
+        try:
+            self.probe(state)
+        except Contradiction:
+            return
+        cell = self.guess(state)
+        if cell is None:
+            if not verify(self.puzzle, state):
+                found.append(state)
+            return
+        self.statistics.guesses += 1
+        for value in (TENT, GRASS):
+            trial = dict(state)
+            try:
+                self.decide(trial, cell, value)
+                self.propagate(trial)
+            except Contradiction:
+                continue
+            self.search(trial, found, limit)
+            if len(found) >= limit:
+                return
+
+    def solutions(self, limit: int = 2) -> List[State]:
+        """Up to limit solutions, each TENT or GRASS for every cell without a tree."""
+        puzzle = self.puzzle
+        state = {cell: TENT if puzzle.grid[cell] == TENT else UNKNOWN for cell in puzzle.cells}
+        found: List[State] = []
+        try:
+            for cell in puzzle.cells:
+                if not puzzle.beside[cell]:
+                    self.decide(state, cell, GRASS)
+            self.propagate(state)
+        except Contradiction:
+            return found
+        self.search(state, found, limit)
+        return found
+
+
+def verify(puzzle: Puzzle, state: State) -> List[str]:
+    """What is wrong with the tents as a solution: the counts, tents touching, and pairing them with the trees."""
+    problems = []
+    tents = [cell for cell in puzzle.cells if state[cell] == TENT]
+    for y, count in enumerate(puzzle.rows):
+        placed = sum(state.get((x, y)) == TENT for x in range(puzzle.grid.width))
+        if placed != count:
+            problems.append(f"row {y + 1} has {placed} tent{'s' * (placed != 1)}, not {count}")
+    for x, count in enumerate(puzzle.columns):
+        placed = sum(state.get((x, y)) == TENT for y in range(puzzle.grid.height))
+        if placed != count:
+            problems.append(f"column {x + 1} has {placed} tent{'s' * (placed != 1)}, not {count}")
+    for tent in tents:
+        for other in puzzle.around[tent]:
+            if other > tent and state[other] == TENT:
+                problems.append(f"the tents at {cell_name(tent)} and {cell_name(other)} touch")
+    beside_tents = {tree: [cell for cell in puzzle.beside[tree] if state[cell] == TENT] for tree in puzzle.trees}
+    paired = match(puzzle.trees, beside_tents)
+    if len(paired) < len(puzzle.trees) or len(tents) != len(puzzle.trees):
+        problems.append(f"only {len(paired)} of the {len(puzzle.trees)} trees can each have a tent of their own, "
+                        f"with {len(tents)} tents")
+    return problems
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def cell_name(position: Position) -> str:
+    return f"row {position[1] + 1}, column {position[0] + 1}"
+
+
+def parse(text: str) -> Puzzle:
+    lines = [(number, line.split()) for number, line in enumerate(text.split("\n"), start=1)
+             if line.strip() and not line.lstrip().startswith("#")]
+    if len(lines) < 2:
+        raise ValueError("expected the column counts, then the rows")
+    width = len(lines[0][1])
+
+    def count(word: str, number: int) -> int:
+        if not word.isdigit():
+            raise ValueError(f"line {number}: expected a count, not {word!r}")
+        return int(word)
+
+    columns = [count(word, lines[0][0]) for word in lines[0][1]]
+    rows, cells = [], []
+    for number, words in lines[1:]:
+        if len(words) != width + 1:
+            raise ValueError(f"line {number}: expected a count and {width} cells")
+        if set(words[1:]) - {TREE, UNKNOWN, TENT}:
+            raise ValueError(f"line {number}: a cell is {TREE}, {UNKNOWN} or {TENT}")
+        rows.append(count(words[0], number))
+        cells.append(words[1:])
+    grid = Grid(cells)
+    trees = grid.count(TREE)
+    if sum(rows) != trees or sum(columns) != trees:
+        raise ValueError(f"{trees} trees, but the rows count {sum(rows)} tents and the columns {sum(columns)}")
+    return Puzzle(grid, rows, columns)
+
+
+def render(puzzle: Puzzle, state: Optional[State] = None) -> str:
+    """The puzzle in its format, with the tents of state and . for grass, or as it was given."""
+    grid = puzzle.grid.copy()
+    for cell in puzzle.cells:
+        if state is not None:
+            grid[cell] = TENT if state[cell] == TENT else UNKNOWN
+    lines = ["  " + " ".join(map(str, puzzle.columns))]
+    for y, count in enumerate(puzzle.rows):
+        lines.append(f"{count} " + " ".join(grid.row(y)))
+    return "\n".join(lines)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve and check Tents and Trees")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="place the tents")
+    solve_parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    solve_parser.add_argument("--stats", action="store_true", help="print the cells decided and the guesses made")
+    check_parser = subparsers.add_parser("check", help="check the tents placed in a puzzle")
+    check_parser.add_argument("file", type=str, help="the puzzle with every tent placed")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "check":
+        problems = verify(puzzle, {cell: puzzle.grid[cell] for cell in puzzle.cells})
+        print("\n".join(problems) if problems else "Solved")
+        if problems:
+            parser.exit(1)
+        return
+    solver = Solver(puzzle)
+    found = solver.solutions(limit=2)
+    if not found:
+        print("No solution")
+    else:
+        print(render(puzzle, found[0]))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(solver.statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
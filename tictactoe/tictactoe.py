@@ -0,0 +1,358 @@
+"""
+# Tic-Tac-Toe, and m,n,k-Games
+
+Two players take turns putting their mark (X, then O) in an empty cell
+of an m by n board; the first to get k in a row, across, down, or
+diagonally, wins. Tic-tac-toe is the 3,3,3-game, a draw; gomoku is the
+15,15,5-game.
+
+
+## Perfect Play
+
+Negamax (minimax, from the point of view of the player to move) with
+alpha-beta pruning. A win is worth more the sooner it comes, 1 plus the
+empty cells left when it is made, so the engine wins as fast as it can
+and loses as slowly.
+
+The same position comes up by many orders of moves, so a transposition
+table keeps what each has been worth: exactly, or only a bound, when a
+cut-off stopped the search before every move was tried. Positions that
+are rotations or reflections of each other are worth the same, so they
+share an entry, under the smallest of their forms. Moves nearest the
+middle are tried first, since they tend to be best, and a good move
+early makes for more cut-offs.
+
+The 3,3,3 and 4,3,3 games solve at once; 4,4,4 (a draw) takes a few
+seconds.
+
+
+## Play
+
+`play` plays against the engine in a prompt (the REPL shared with
+`lambda_calculus/`): a move is its column letter and row number, like
+b2, and :hint, :undo, :board and :new do what they say.
+
+
+## Usage
+
+    python3 tictactoe.py solve
+    python3 tictactoe.py solve --position "X.. .O. ..X"
+    python3 tictactoe.py solve --size 4,4,4 --stats
+    python3 tictactoe.py play --size 4,4,3 --first engine
+    python3 tictactoe.py play --no-record
+"""
+
+import argparse
+import os
+import sys
+from typing import Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from progress.progress import Session
+from repl.repl import Repl
+
+
+EMPTY: str = "."
+PLAYERS: str = "XO"
+EXACT, LOWER, UPPER = 0, 1, 2
+
+
+class Game:
+    def __init__(self, width: int = 3, height: int = 3, k: int = 3):
+        if min(width, height, k) < 1 or k > max(width, height):
+            raise ValueError(f"{k} in a row does not fit on a {width}x{height} board")
+        self.width, self.height, self.k = width, height, k
+        self.cells = width * height
+        # the lines through each cell: for each direction, the cells either side
+        self.lines: List[List[Tuple[List[int], List[int]]]] = []
+        for i in range(self.cells):
+            x, y = i % width, i // width
+            through = []
+            for dx, dy in ((1, 0), (0, 1), (1, 1), (1, -1)):
+                sides = []
+                for sign in (1, -1):
+                    side = []
+                    px, py = x + sign * dx, y + sign * dy
+                    while 0 <= px < width and 0 <= py < height and len(side) < k - 1:
+                        side.append(py * width + px)
+                        px, py = px + sign * dx, py + sign * dy
+                    sides.append(side)
+                through.append((sides[0], sides[1]))
+            self.lines.append(through)
+        middle_x, middle_y = (width - 1) / 2, (height - 1) / 2
+        self.order = sorted(range(self.cells), key=lambda i: abs(i % width - middle_x) + abs(i // width - middle_y))
+        self.symmetries = self._symmetries()
+
+    def _symmetries(self) -> List[List[int]]:
+        """Each symmetry of the board, as where each cell comes from."""
+        w, h = self.width, self.height
+        maps = [lambda x, y: (x, y), lambda x, y: (w - 1 - x, y),
+                lambda x, y: (x, h - 1 - y), lambda x, y: (w - 1 - x, h - 1 - y)]
+        if w == h:
+            maps += [lambda x, y: (y, x), lambda x, y: (w - 1 - y, x),
+                     lambda x, y: (y, h - 1 - x), lambda x, y: (w - 1 - y, h - 1 - x)]
+        return [[f(i % w, i // w)[1] * w + f(i % w, i // w)[0] for i in range(self.cells)] for f in maps]
+
+    def key(self, board: str) -> str:
+        return min("".join(board[j] for j in symmetry) for symmetry in self.symmetries)
+
+    def wins(self, board: str, i: int) -> bool:
+        """Whether the mark at i makes k in a row."""
+        mark = board[i]
+        for forward, backward in self.lines[i]:
+            run = 1
+            for side in (forward, backward):
+                for j in side:
+                    if board[j] != mark:
+                        break
+                    run += 1
+            if run >= self.k:
+                return True
+        return False
+
+    def to_move(self, board: str) -> str:
+        return PLAYERS[(self.cells - board.count(EMPTY)) % 2]
+
+    def winner(self, board: str) -> Optional[str]:
+        for i, mark in enumerate(board):
+            if mark != EMPTY and self.wins(board, i):
+                return mark
+        return None
+
+    def play(self, board: str, i: int) -> str:
+        return board[:i] + self.to_move(board) + board[i + 1:]
+
+    def name(self, i: int) -> str:
+        return f"{chr(ord('a') + i % self.width)}{i // self.width + 1}"
+
+    def parse_move(self, text: str) -> int:
+        text = text.strip().lower()
+        if len(text) < 2 or not text[0].isalpha() or not text[1:].isdigit():
+            raise ValueError(f"a move is a column letter and a row number, like b2, not {text!r}")
+        x, y = ord(text[0]) - ord("a"), int(text[1:]) - 1
+        if not (0 <= x < self.width and 0 <= y < self.height):
+            raise ValueError(f"{text} is off the board")
+        return y * self.width + x
+
+    def render(self, board: str) -> str:
+        header = "  " + " ".join(chr(ord("a") + x) for x in range(self.width))
+        rows = [f"{y + 1:<2}" + " ".join(board[y * self.width:(y + 1) * self.width]) for y in range(self.height)]
+        return "\n".join([header] + rows)
+
+
+class Statistics:
+    def __init__(self):
+        self.nodes = 0
+        self.hits = 0
+
+    def __str__(self) -> str:
+        return f"{self.nodes} positions searched, {self.hits} transposition table hits"
+
+
+class Engine:
+    def __init__(self, game: Game):
+        self.game = game
+        self.table: Dict[str, Tuple[int, int]] = {}     # key -> (value, EXACT or LOWER or UPPER)
+        self.statistics = Statistics()
+
+    def negamax(self, board: str, alpha: int, beta: int) -> int:
+        """The value of a position with no winner yet, to the player to move."""
+        self.statistics.nodes += 1
+        empty = board.count(EMPTY)
+        if empty == 0:
+            return 0
+        key = self.game.key(board)
+        entry = self.table.get(key)
+        if entry is not None:
+            value, flag = entry
+            if flag == EXACT or (flag == LOWER and value >= beta) or (flag == UPPER and value <= alpha):
+                self.statistics.hits += 1
+                return value
+        original_alpha = alpha
+        best = -self.game.cells - 1
+        for i in self.game.order:
+            if board[i] != EMPTY:
+                continue
+            after = self.game.play(board, i)
+            value = empty if self.game.wins(after, i) else -self.negamax(after, -beta, -alpha)
+            best = max(best, value)
+            alpha = max(alpha, value)
+            if alpha >= beta:
+                break
+        flag = UPPER if best <= original_alpha else LOWER if best >= beta else EXACT
+        self.table[key] = (best, flag)
+        return best
+
+    def evaluate(self, board: str) -> List[Tuple[int, int]]:
+        """Each legal move with its exact value to the player making it, best first."""
+        empty = board.count(EMPTY)
+        scored = []
+        for i in self.game.order:
+            if board[i] == EMPTY:
+                after = self.game.play(board, i)
+                scored.append((i, empty if self.game.wins(after, i) else -self.negamax(after, -self.game.cells - 1,
+                                                                                       self.game.cells + 1)))
+        return sorted(scored, key=lambda move: -move[1])
+
+    def best(self, board: str) -> Tuple[int, int]:
+        """The best move, and its value: positive for a win, zero for a draw."""
+        empty = board.count(EMPTY)
+        best_move, best_value = -1, -self.game.cells - 2
+        alpha = -self.game.cells - 1
+        for i in self.game.order:
+            if board[i] != EMPTY:
+                continue
+            after = self.game.play(board, i)
+            value = empty if self.game.wins(after, i) else -self.negamax(after, -self.game.cells - 1, -alpha)
+            if value > best_value:
+                best_move, best_value = i, value
+                alpha = max(alpha, value)
+        return best_move, best_value
+
+
+def describe(game: Game, board: str, value: int) -> str:
+    """A value to the player to move, in words."""
+    if value == 0:
+        return "a draw"
+    empty = board.count(EMPTY)
+    player = game.to_move(board)
+    winner = player if value > 0 else PLAYERS[1 - PLAYERS.index(player)]
+    plies = empty - abs(value) + 1      # the moves of both players, up to the winning one
+    moves = (plies + 1) // 2 if winner == player else plies // 2
+    return f"{winner} wins in {moves} move{'s' if moves > 1 else ''}"
+
+
+def parse_size(text: str) -> Tuple[int, int, int]:
+    parts = text.split(",")
+    if len(parts) != 3 or not all(part.strip().isdigit() for part in parts):
+        raise ValueError(f"a size is m,n,k: the width, the height, and how many in a row, not {text!r}")
+    m, n, k = (int(part) for part in parts)
+    return m, n, k
+
+
+def parse_position(game: Game, text: str) -> str:
+    board = "".join(text.split()).upper()
+    if len(board) != game.cells or any(c not in PLAYERS + EMPTY for c in board):
+        raise ValueError(f"a position is {game.cells} cells of X, O and ., row by row")
+    if board.count("X") - board.count("O") not in (0, 1):
+        raise ValueError("X moves first, so X has as many marks as O, or one more")
+    if game.winner(board) is not None:
+        raise ValueError(f"{game.winner(board)} has already won")
+    return board
+
+
+################################################################################
+###                                   Play                                   ###
+################################################################################
+
+
+def play(game: Game, engine_first: bool, record: bool = True):
+    engine = Engine(game)
+    history: List[str] = [EMPTY * game.cells]
+    engine_plays = PLAYERS[0] if engine_first else PLAYERS[1]
+    session = Session("tictactoe", f"{game.width},{game.height},{game.k}", record)
+
+    def over() -> bool:
+        board = history[-1]
+        winner = game.winner(board)
+        if winner is not None:
+            print(f"{winner} wins" + (" (the engine)" if winner == engine_plays else ""))
+            session.finish("lost" if winner == engine_plays else "won")
+            return True
+        if EMPTY not in board:
+            print("A draw")
+            session.finish("drawn")
+            return True
+        return False
+
+    def engine_moves():
+        board = history[-1]
+        if over() or game.to_move(board) != engine_plays:
+            return
+        move, value = engine.best(board)
+        history.append(game.play(board, move))
+        print(f"{engine_plays} plays {game.name(move)} ({describe(game, board, value)})")
+        print(game.render(history[-1]))
+        over()
+
+    def move(text: str):
+        board = history[-1]
+        if game.winner(board) is not None or EMPTY not in board:
+            raise ValueError("the game is over (:new for another)")
+        i = game.parse_move(text)
+        if board[i] != EMPTY:
+            raise ValueError(f"{game.name(i)} is taken")
+        history.append(game.play(board, i))
+        session.move()
+        print(game.render(history[-1]))
+        engine_moves()
+
+    def undo(_: str):
+        while len(history) > 1:
+            history.pop()
+            if game.to_move(history[-1]) != engine_plays:
+                break
+        print(game.render(history[-1]))
+
+    def hint(_: str):
+        board = history[-1]
+        if game.winner(board) is None and EMPTY in board:
+            i, value = engine.best(board)
+            print(f"{game.name(i)}: {describe(game, board, value)}")
+
+    def new(_: str):
+        del history[1:]
+        session.start()
+        print(game.render(history[-1]))
+        engine_moves()
+
+    repl = Repl("move> ", move)
+    repl.add_command("board", lambda _: print(game.render(history[-1])), "print the board")
+    repl.add_command("hint", hint, "the engine's move, and what it is worth")
+    repl.add_command("undo", undo, "take back your last move (and the engine's reply)")
+    repl.add_command("new", new, "start again")
+    print(f"{game.width},{game.height},{game.k}: you are {PLAYERS[1] if engine_first else PLAYERS[0]}; "
+          f"moves are like b2, and :help lists the rest")
+    new("")
+    repl.run()
+    session.abandon()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Play m,n,k-games, like tic-tac-toe, perfectly")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="the value of a position, and of each move")
+    play_parser = subparsers.add_parser("play", help="play against the engine")
+    for p in (solve_parser, play_parser):
+        p.add_argument("--size", type=str, default="3,3,3", help="m,n,k: the width, height, and how many in a row")
+    solve_parser.add_argument("--position", type=str, default=None, help="the cells row by row, X, O, or .")
+    solve_parser.add_argument("--stats", action="store_true", help="print the positions searched")
+    play_parser.add_argument("--first", choices=["human", "engine"], default="human")
+    play_parser.add_argument("--no-record", action="store_true", help="leave the games out of the progress record")
+    args = parser.parse_args()
+
+    try:
+        game = Game(*parse_size(args.size))
+        board = EMPTY * game.cells
+        if args.command == "solve" and args.position is not None:
+            board = parse_position(game, args.position)
+    except ValueError as e:
+        parser.exit(1, f"error: {e}\n")
+    if args.command == "play":
+        play(game, args.first == "engine", not args.no_record)
+        return
+    if EMPTY not in board:
+        print("The board is full: a draw")
+        return
+    engine = Engine(game)
+    moves = engine.evaluate(board)
+    print(game.render(board))
+    print(f"{game.to_move(board)} to move: {describe(game, board, moves[0][1])}")
+    for i, value in moves:
+        print(f"  {game.name(i)}: {describe(game, board, value)}")
+    if args.stats:
+        print(engine.statistics)
+
+
+if __name__ == "__main__":
+    main()
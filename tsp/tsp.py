@@ -0,0 +1,355 @@
+"""
+# Travelling Salesman
+
+The shortest tour through every city, back to where it started.
+
+
+## Methods
+
+    exact       Held-Karp: dynamic programming over subsets, the shortest
+                path from the first city through each set of cities to
+                each city in it. Time n^2 2^n, so for up to about 15
+                cities.
+    heuristic   nearest neighbour, from the first city always to the
+                nearest one not yet visited, then improved by local search
+                until neither move shortens it:
+                    2-opt       take out two edges and join the ends the
+                                other way, reversing the path between
+                    Or-opt      move a run of one to three cities (either
+                                way round) to between two others
+                The result is usually within a few percent of the best.
+    auto        exact for 12 cities or fewer, heuristic for more
+
+
+## The Format
+
+A subset of TSPLIB: `KEY: value` lines, where DIMENSION is the number of
+cities and EDGE_WEIGHT_TYPE is one of
+
+    EUC_2D      the distance rounded to the nearest whole number
+    CEIL_2D     rounded up
+    ATT         the pseudo-Euclidean distance of att48 and att532
+    EXPLICIT    given in an EDGE_WEIGHT_SECTION, in the EDGE_WEIGHT_FORMAT
+                FULL_MATRIX, UPPER_ROW, or LOWER_DIAG_ROW
+
+and the coordinates are in a NODE_COORD_SECTION (a number and x and y
+per line). The file may end with EOF.
+
+
+## Usage
+
+    python3 tsp.py solve instances/ten.tsp --method exact
+    python3 tsp.py solve instances/ten.tsp --method heuristic --show
+    python3 tsp.py random --cities 200 --seed 1 | python3 tsp.py solve - --png tour.png
+"""
+
+import argparse
+import math
+import os
+import random
+import sys
+from typing import Dict, List, NamedTuple, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from cellular_automata.png import write_png
+from rng.rng import add_seed_argument, make_rng
+
+
+METHODS: List[str] = ["auto", "exact", "heuristic"]
+AUTO_EXACT: int = 12
+EXACT_LIMIT: int = 16
+Point = Tuple[float, float]
+
+
+class Instance(NamedTuple):
+    name: str
+    distances: List[List[int]]
+    points: Optional[List[Point]]       # None if the distances were given explicitly
+
+
+def tsplib_distance(kind: str, a: Point, b: Point) -> int:
+    dx, dy = a[0] - b[0], a[1] - b[1]
+    if kind == "EUC_2D":
+        return int(math.sqrt(dx * dx + dy * dy) + 0.5)
+    if kind == "CEIL_2D":
+        return math.ceil(math.sqrt(dx * dx + dy * dy))
+    r = math.sqrt((dx * dx + dy * dy) / 10)       # ATT
+    t = int(r + 0.5)
+    return t + 1 if t < r else t
+
+
+def parse(text: str) -> Instance:
+    keys: Dict[str, str] = {}
+    points: List[Point] = []
+    weights: List[int] = []
+    section = None
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if not line or line == "EOF":
+            continue
+        if line.endswith("_SECTION"):
+            section = line
+            continue
+        if ":" in line and section is None:
+            key, _, value = line.partition(":")
+            keys[key.strip().upper()] = value.strip()
+            continue
+        try:
+            if section == "NODE_COORD_SECTION":
+                _, x, y = line.split()
+                points.append((float(x), float(y)))
+            elif section == "EDGE_WEIGHT_SECTION":
+                weights += [int(float(w)) for w in line.split()]
+            else:
+                raise ValueError
+        except ValueError:
+            raise ValueError(f"line {line_number}: cannot read {line!r}")
+    if keys.get("TYPE", "TSP").split()[0] != "TSP":
+        raise ValueError(f"only TYPE: TSP is supported, not {keys['TYPE']}")
+    if "DIMENSION" not in keys or not keys["DIMENSION"].isdigit():
+        raise ValueError("no DIMENSION")
+    n = int(keys["DIMENSION"])
+    kind = keys.get("EDGE_WEIGHT_TYPE", "EUC_2D")
+    if kind in ("EUC_2D", "CEIL_2D", "ATT"):
+        if len(points) != n:
+            raise ValueError(f"{len(points)} coordinates, but DIMENSION: {n}")
+        distances = [[tsplib_distance(kind, a, b) for b in points] for a in points]
+        return Instance(keys.get("NAME", ""), distances, points)
+    if kind != "EXPLICIT":
+        raise ValueError(f"EDGE_WEIGHT_TYPE {kind} is not supported")
+    distances = [[0] * n for _ in range(n)]
+    form = keys.get("EDGE_WEIGHT_FORMAT", "FULL_MATRIX")
+    if form == "FULL_MATRIX":
+        cells = [(i, j) for i in range(n) for j in range(n)]
+    elif form == "UPPER_ROW":
+        cells = [(i, j) for i in range(n) for j in range(i + 1, n)]
+    elif form == "LOWER_DIAG_ROW":
+        cells = [(i, j) for i in range(n) for j in range(i + 1)]
+    else:
+        raise ValueError(f"EDGE_WEIGHT_FORMAT {form} is not supported")
+    if len(weights) != len(cells):
+        raise ValueError(f"{len(weights)} edge weights, but {form} needs {len(cells)}")
+    for (i, j), w in zip(cells, weights):
+        distances[i][j] = w
+        if form != "FULL_MATRIX":
+            distances[j][i] = w
+    return Instance(keys.get("NAME", ""), distances, None)
+
+
+def length(distances: List[List[int]], tour: List[int]) -> int:
+    return sum(distances[a][b] for a, b in zip(tour, tour[1:] + tour[:1]))
+
+
+################################################################################
+###                                  Exact                                   ###
+################################################################################
+
+
+def held_karp(distances: List[List[int]]) -> List[int]:
+    n = len(distances)
+    if n <= 2:
+        return list(range(n))
+    # best[subset][last]: the shortest path from city 0 through the subset (of cities 1..n-1) ending at last
+    rest = n - 1
+    best: List[Dict[int, int]] = [{} for _ in range(1 << rest)]
+    parent: List[Dict[int, int]] = [{} for _ in range(1 << rest)]
+    for city in range(1, n):
+        best[1 << (city - 1)][city] = distances[0][city]
+    for subset in range(1, 1 << rest):
+        for last, cost in best[subset].items():
+            for city in range(1, n):
+                bit = 1 << (city - 1)
+                if subset & bit:
+                    continue
+                extended = subset | bit
+                new_cost = cost + distances[last][city]
+                if new_cost < best[extended].get(city, math.inf):
+                    best[extended][city] = new_cost
+                    parent[extended][city] = last
+    full = (1 << rest) - 1
+    last = min(best[full], key=lambda city: best[full][city] + distances[city][0])
+    tour, subset = [], full
+    while subset:
+        tour.append(last)
+        subset, last = subset & ~(1 << (last - 1)), parent[subset].get(last, 0)
+    return [0] + tour[::-1]
+
+
+################################################################################
+###                                Heuristic                                 ###
+################################################################################
+
+
+def nearest_neighbour(distances: List[List[int]], start: int = 0) -> List[int]:
+    unvisited = set(range(len(distances))) - {start}
+    tour = [start]
+    while unvisited:
+        here = tour[-1]
+        tour.append(min(unvisited, key=lambda city: distances[here][city]))
+        unvisited.remove(tour[-1])
+    return tour
+
+
+def two_opt(distances: List[List[int]], tour: List[int]) -> bool:
+    """Make every improving 2-opt move there is, in place. Returns whether any was made."""
+    n = len(tour)
+    improved = False
+    for i in range(n - 1):
+        for j in range(i + 2, n if i > 0 else n - 1):
+            a, b, c, d = tour[i], tour[i + 1], tour[j], tour[(j + 1) % n]
+            if distances[a][c] + distances[b][d] < distances[a][b] + distances[c][d]:
+                tour[i + 1:j + 1] = reversed(tour[i + 1:j + 1])
+                improved = True
+    return improved
+
+
+def or_opt(distances: List[List[int]], tour: List[int]) -> bool:
+    """Make every improving Or-opt move there is, in place. Returns whether any was made."""
+    n = len(tour)
+    improved = False
+    for size in (1, 2, 3):
+        i = 0
+        while i < n and n > size + 2:
+            # the run tour[i:i + size], between p and q
+            run = [tour[(i + k) % n] for k in range(size)]
+            p, q = tour[i - 1], tour[(i + size) % n]
+            removed = distances[p][run[0]] + distances[run[-1]][q] - distances[p][q]
+            rest = [tour[(i + size + k) % n] for k in range(n - size)]
+            best, where, flip = 0, None, False
+            for k in range(len(rest) - 1):
+                a, b = rest[k], rest[k + 1]
+                forward = distances[a][run[0]] + distances[run[-1]][b] - distances[a][b]
+                backward = distances[a][run[-1]] + distances[run[0]][b] - distances[a][b]
+                for added, reverse in ((forward, False), (backward, True)):
+                    if added - removed < best:
+                        best, where, flip = added - removed, k, reverse
+            if where is not None:
+                tour[:] = rest[:where + 1] + (run[::-1] if flip else run) + rest[where + 1:]
+                improved = True
+            i += 1
+    return improved
+
+
+def heuristic(distances: List[List[int]], report: Optional[List[Tuple[str, int]]] = None) -> List[int]:
+    tour = nearest_neighbour(distances)
+    if report is not None:
+        report.append(("nearest neighbour", length(distances, tour)))
+    if len(tour) > 3:
+        while two_opt(distances, tour) | or_opt(distances, tour):
+            pass
+    if report is not None:
+        report.append(("2-opt and Or-opt", length(distances, tour)))
+    first = tour.index(0)
+    return tour[first:] + tour[:first]
+
+
+################################################################################
+###                                 Drawing                                  ###
+################################################################################
+
+
+def line(a: Tuple[int, int], b: Tuple[int, int]) -> List[Tuple[int, int]]:
+    """The cells from a to b, by Bresenham's algorithm."""
+    (x0, y0), (x1, y1) = a, b
+    dx, dy = abs(x1 - x0), -abs(y1 - y0)
+    sx, sy = (1 if x1 > x0 else -1), (1 if y1 > y0 else -1)
+    error = dx + dy
+    cells = []
+    while True:
+        cells.append((x0, y0))
+        if (x0, y0) == (x1, y1):
+            return cells
+        doubled = 2 * error
+        if doubled >= dy:
+            error += dy
+            x0 += sx
+        if doubled <= dx:
+            error += dx
+            y0 += sy
+
+
+def place(points: List[Point], width: int, height: int) -> List[Tuple[int, int]]:
+    """The points scaled onto a width x height grid, y up."""
+    xs, ys = [p[0] for p in points], [p[1] for p in points]
+    span_x, span_y = (max(xs) - min(xs)) or 1, (max(ys) - min(ys)) or 1
+    return [(round((x - min(xs)) / span_x * (width - 1)), round((max(ys) - y) / span_y * (height - 1)))
+            for x, y in points]
+
+
+def render(points: List[Point], tour: List[int], width: int = 72, height: int = 30) -> str:
+    cells = place(points, width, height)
+    grid = [[" "] * width for _ in range(height)]
+    for a, b in zip(tour, tour[1:] + tour[:1]):
+        for x, y in line(cells[a], cells[b]):
+            grid[y][x] = "."
+    for city, (x, y) in enumerate(cells):
+        grid[y][x] = "@" if city == tour[0] else "o"
+    return "\n".join("".join(row).rstrip() for row in grid)
+
+
+def save_png(points: List[Point], tour: List[int], path: str, size: int = 600):
+    margin = 10
+    cells = [(x + margin, y + margin) for x, y in place(points, size, size)]
+    pixels = [[(255, 255, 255)] * (size + 2 * margin) for _ in range(size + 2 * margin)]
+    for a, b in zip(tour, tour[1:] + tour[:1]):
+        for x, y in line(cells[a], cells[b]):
+            pixels[y][x] = (40, 40, 40)
+    for x, y in cells:
+        for dx in (-1, 0, 1):
+            for dy in (-1, 0, 1):
+                pixels[y + dy][x + dx] = (200, 30, 30)
+    write_png(path, pixels)
+
+
+def random_instance(cities: int, rng: random.Random) -> str:
+    lines = [f"NAME: random{cities}", "TYPE: TSP", f"DIMENSION: {cities}", "EDGE_WEIGHT_TYPE: EUC_2D",
+             "NODE_COORD_SECTION"]
+    lines += [f"{i + 1} {rng.randint(0, 1000)} {rng.randint(0, 1000)}" for i in range(cities)]
+    return "\n".join(lines + ["EOF"])
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Find short travelling salesman tours")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="find a tour")
+    solve_parser.add_argument("file", type=str, help="the instance, in TSPLIB, or - for stdin")
+    solve_parser.add_argument("--method", choices=METHODS, default="auto")
+    solve_parser.add_argument("--show", action="store_true", help="draw the tour")
+    solve_parser.add_argument("--png", type=str, default=None, help="save a picture of the tour here")
+    random_parser = subparsers.add_parser("random", help="print random cities, in TSPLIB")
+    random_parser.add_argument("--cities", type=int, default=50)
+    add_seed_argument(random_parser)
+    args = parser.parse_args()
+
+    if args.command == "random":
+        print(random_instance(args.cities, make_rng(args.seed)))
+        return
+    try:
+        if args.file == "-":
+            instance = parse(sys.stdin.read())
+        else:
+            with open(args.file) as f:
+                instance = parse(f.read())
+        n = len(instance.distances)
+        method = args.method if args.method != "auto" else "exact" if n <= AUTO_EXACT else "heuristic"
+        if method == "exact" and n > EXACT_LIMIT:
+            raise ValueError(f"{n} cities are too many for the exact method (at most {EXACT_LIMIT})")
+        if (args.show or args.png) and instance.points is None:
+            raise ValueError("the instance has no coordinates to draw")
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+
+    report: List[Tuple[str, int]] = []
+    tour = held_karp(instance.distances) if method == "exact" else heuristic(instance.distances, report)
+    if args.show:
+        print(render(instance.points, tour))
+    for phase, value in report:
+        print(f"{phase}: {value}")
+    print(f"{'Shortest tour' if method == 'exact' else 'Tour'}: {length(instance.distances, tour)}")
+    print(" ".join(str(city + 1) for city in tour))
+    if args.png is not None:
+        save_png(instance.points, tour, args.png)
+
+
+if __name__ == "__main__":
+    main()
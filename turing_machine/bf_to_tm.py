@@ -0,0 +1,113 @@
+"""
+# Brainfuck to Turing Machine Compiler
+
+Brainfuck is Turing-complete, and the usual proof is a construction very
+much like this one: we translate a Brainfuck program into a Turing
+machine that does the same thing.
+
+* The BF memory is the first tape. Each cell is one tape symbol, so the
+  tape alphabet is every cell value (0 to 255 by default) and the blank
+  symbol is 0, just like a fresh BF cell. The data pointer is the head.
+* The instruction pointer is the finite control: there is one state per
+  instruction, named after its position in the program (e.g. `i12_+`),
+  plus the halting state `end`.
+* `+` and `-` need one transition per cell value, because a Turing
+  machine can only add one by looking up what to write next. `>` and `<`
+  are a single wildcard transition. `[` and `]` jump by choosing the
+  next state depending on whether the cell is 0.
+* `.` writes the cell to a second, output tape and moves right. Machines
+  without a `.` only have the one tape.
+
+A Turing machine has no input stream, so programs using `,` are not
+supported. Every character other than the eight commands is a comment.
+
+
+## Usage
+
+    python3 tm.py from-bf program.bf --output program.tm
+    python3 tm.py run program.tm
+
+The output tape holds the numeric values of the output bytes. As 0 is
+also the blank symbol, zero bytes at either end of the output do not
+show up when the tape is printed.
+"""
+
+from typing import Dict, List
+
+
+BF_COMMANDS: str = "+-<>[].,"
+HALT_STATE: str = "end"
+
+
+class CompileError(Exception):
+    """The Brainfuck program cannot be translated."""
+
+
+def match_brackets(program: str) -> Dict[int, int]:
+    """Map each bracket's position to the position of its partner."""
+    stack: List[int] = []
+    partner: Dict[int, int] = {}
+    for pc, op in enumerate(program):
+        if op == "[":
+            stack.append(pc)
+        elif op == "]":
+            if not stack:
+                raise CompileError(f"unmatched ']' at instruction {pc}")
+            start = stack.pop()
+            partner[start], partner[pc] = pc, start
+    if stack:
+        raise CompileError(f"unmatched '[' at instruction {stack[-1]}")
+    return partner
+
+
+def compile_bf(source: str, cell_size: int = 256) -> str:
+    """Return the text of a Turing machine that runs the BF program."""
+    program = "".join(c for c in source if c in BF_COMMANDS)
+    if "," in program:
+        raise CompileError("',' has no Turing machine equivalent (there is no input stream)")
+    if cell_size < 2:
+        raise CompileError("cells need at least two values")
+    partner = match_brackets(program)
+
+    def state(pc: int) -> str:
+        return HALT_STATE if pc >= len(program) else f"i{pc}_{program[pc]}"
+
+    has_output = "." in program
+    # The output tape's head always sits on a fresh (blank) cell
+    rest_read = " 0" if has_output else ""
+    rest_stay = (" 0", " S") if has_output else ("", "")
+    symbols = [str(v) for v in range(cell_size)]
+
+    lines: List[str] = [
+        "# Generated by bf_to_tm.py",
+        *(["tapes: 2"] if has_output else []),
+        f"states: {' '.join(state(pc) for pc in range(len(program) + 1))}",
+        f"alphabet: {' '.join(symbols)}",
+        "blank: 0",
+        f"start: {state(0)}",
+        f"halt: {HALT_STATE}",
+    ]
+    for pc, op in enumerate(program):
+        lines.append("")
+        lines.append(f"{state(pc)}:")
+        write, move = rest_stay
+        if op in "+-":
+            step = 1 if op == "+" else -1
+            for v in range(cell_size):
+                lines.append(
+                    f"    {v}{rest_read} -> {(v + step) % cell_size}{write} S{move} {state(pc + 1)}"
+                )
+        elif op in "<>":
+            lines.append(
+                f"    *{rest_read} -> *{write} {'L' if op == '<' else 'R'}{move} {state(pc + 1)}"
+            )
+        elif op == "[":
+            lines.append(f"    0{rest_read} -> 0{write} S{move} {state(partner[pc] + 1)}")
+            lines.append(f"    *{rest_read} -> *{write} S{move} {state(pc + 1)}")
+        elif op == "]":
+            lines.append(f"    0{rest_read} -> 0{write} S{move} {state(pc + 1)}")
+            lines.append(f"    *{rest_read} -> *{write} S{move} {state(partner[pc] + 1)}")
+        elif op == ".":
+            for v in range(cell_size):
+                lines.append(f"    {v} 0 -> {v} {v} S R {state(pc + 1)}")
+    return "\n".join(lines) + "\n"
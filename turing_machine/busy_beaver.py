@@ -0,0 +1,278 @@
+"""
+# Busy Beaver Search
+
+The busy beaver game asks: of all the n-state, 2-symbol Turing machines
+that eventually halt when started on a blank tape, which runs for the
+most steps, and which leaves the most 1s on the tape? The answers grow
+faster than any computable function, but they are known for small n:
+
+    states    most steps    most ones
+    1         1             1
+    2         6             4
+    3         21            6
+    4         107           13
+
+We find these by brute force, but it is a lot less brute than trying
+every machine:
+
+* We build machines in "tree normal form". A machine starts with no
+  transitions. We run it, and whenever it reads a (state, symbol) that
+  has no transition yet, we branch over every choice for it and carry
+  on from where it was. Transitions that are never used are never
+  chosen, so we never enumerate them.
+* A new transition may only go to a state that has already been used or
+  to the next unused state. This skips machines that are the same up to
+  renaming the states.
+* The first transition moves right. This skips the mirror image of
+  every machine.
+* The halting transition always writes a 1 and moves right. Nothing
+  after the halt matters, and writing a 1 never hurts.
+* When only one transition is undefined, the only way to halt is to
+  make that one halt, so we do not branch there at all.
+
+A machine that repeats an exact configuration (state, head, tape) will
+never halt, and neither will one that repeats a configuration further
+along the tape: in the same state, each time the head goes further than
+ever, with the same cells behind it as far back as it went last time.
+Every so often we also try the proofs that `tm.py run` uses
+(`find_non_halting_reason` in machine.py): the head running off into
+blank tape, and no way left to the halting state or to a missing
+transition (which could still become the halt). A machine that runs
+past the step limit is undecided; with the default limit, that is
+15,010 of the 858,909 machines with four states, in about 40 seconds.
+With a `Progress`, the search reports the machines so far (and their
+rate), the undecided ones, and the partial machines still to explore.
+"""
+
+import os
+import sys
+from typing import Dict, List, Optional, Tuple, Union
+
+from machine import HALT, Simulation, TuringMachine, find_non_halting_reason
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import Progress
+
+
+HALT_STATE: str = "H"
+STATE_NAMES: str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".replace(HALT_STATE, "")
+
+Rule = Tuple[str, str, str]   # (write, move, next state)
+
+
+class SearchResult:
+    def __init__(self):
+        self.num_machines: int = 0
+        self.num_halted: int = 0
+        self.num_cycles: int = 0
+        self.num_translated_cycles: int = 0
+        self.num_proved: int = 0
+        self.num_undecided: int = 0
+        self.most_steps: int = -1
+        self.most_steps_machine: Dict[Tuple[str, str], Rule] = {}
+        self.most_ones: int = -1
+        self.most_ones_machine: Dict[Tuple[str, str], Rule] = {}
+
+
+def search(num_states: int, max_steps: int, progress: Optional[Progress] = None) -> SearchResult:
+    if not 1 <= num_states <= len(STATE_NAMES):
+        raise ValueError(f"number of states must be between 1 and {len(STATE_NAMES)}")
+    states = STATE_NAMES[:num_states]
+    result = SearchResult()
+
+    def halted(rules: Dict[Tuple[str, str], Rule], tape: Dict[int, str], steps: int):
+        result.num_machines += 1
+        result.num_halted += 1
+        ones = sum(c == "1" for c in tape.values())
+        if steps > result.most_steps:
+            result.most_steps, result.most_steps_machine = steps, dict(rules)
+        if ones > result.most_ones:
+            result.most_ones, result.most_ones_machine = ones, dict(rules)
+
+    # Each entry is a partially-defined machine that is paused on a missing
+    # transition, along with its configuration at that moment.
+    stack: List[Tuple[Dict[Tuple[str, str], Rule], str, int, Dict[int, str], int]] = [
+        ({}, "A", 0, {}, 0)
+    ]
+    while stack:
+        if progress is not None:
+            progress.update(machines=result.num_machines, undecided=result.num_undecided, pending=len(stack))
+        rules, state, head, tape, steps = stack.pop()
+        key = (state, tape.get(head, "0"))
+        used = len({s for s, _ in rules} | {n for _, _, n in rules.values()} | {"A"})
+        choices: List[Rule] = [("1", "R", HALT_STATE)]
+        if len(rules) < 2 * num_states - 1:
+            next_states = states[:min(used + 1, num_states)]
+            moves = "R" if not rules else "LR"
+            choices += [(w, m, n) for w in "01" for m in moves for n in next_states]
+
+        for rule in choices:
+            new_rules = dict(rules)
+            new_rules[key] = rule
+            if rule[2] == HALT_STATE:
+                new_tape = dict(tape)
+                new_tape[head] = "1"
+                halted(new_rules, new_tape, steps + 1)
+                continue
+            paused = _run_until_missing(new_rules, num_states, state, head, dict(tape), steps, max_steps)
+            if paused == CYCLE:
+                result.num_machines += 1
+                result.num_cycles += 1
+            elif paused == TRANSLATED_CYCLE:
+                result.num_machines += 1
+                result.num_translated_cycles += 1
+            elif isinstance(paused, str):
+                result.num_machines += 1
+                result.num_proved += 1
+            elif paused[-1] > max_steps:
+                result.num_machines += 1
+                result.num_undecided += 1
+            else:
+                stack.append((new_rules, *paused))
+    return result
+
+
+CYCLE: str = "repeated configuration"
+TRANSLATED_CYCLE: str = "repeats further along the tape"
+
+
+class _Frontier:
+    """Brent's method again, on the times the head goes further one way than ever.
+
+    If the machine is in the same state at two of these times, and the
+    cells it went back over in between hold the same as before (shifted
+    along), then it will do the same again, forever, one shift further
+    along each time. Everything in front of the head is blank both times.
+    """
+
+    def __init__(self, head: int):
+        self.snapshot: Optional[Tuple[str, int, Dict[int, str]]] = None
+        # The furthest back the head has been since the snapshot
+        self.behind: int = head
+        self.count, self.power = 0, 1
+
+    def repeats(self, state: str, head: int, tape: Dict[int, str]) -> bool:
+        if self.snapshot is not None and self.snapshot[0] == state:
+            _, old_head, old_tape = self.snapshot
+            shift = head - old_head
+            lo, hi = min(self.behind, old_head), max(self.behind, old_head)
+            if all(old_tape.get(x, "0") == tape.get(x + shift, "0") for x in range(lo, hi + 1)):
+                return True
+        self.count += 1
+        if self.count == self.power:
+            self.snapshot, self.behind = (state, head, dict(tape)), head
+            self.count, self.power = 0, self.power * 2
+        return False
+
+
+def _run_until_missing(
+    rules: Dict[Tuple[str, str], Rule],
+    num_states: int,
+    state: str,
+    head: int,
+    tape: Dict[int, str],
+    steps: int,
+    max_steps: int,
+) -> Union[Tuple[str, int, Dict[int, str], int], str]:
+    """Run until the machine needs a missing transition.
+
+    Return the configuration at that point, or why the machine never
+    halts (CYCLE if it repeats a configuration, TRANSLATED_CYCLE if it
+    repeats one further along). If it runs past the step limit, the
+    returned step count is greater than max_steps.
+    """
+    # Brent's cycle detection: compare against a snapshot that we retake
+    # after every power of two steps, so that we never store more than one.
+    snapshot = (state, head, dict(tape))
+    since_snapshot, power = 0, 1
+    low, high = min([head, *tape]), max([head, *tape])
+    left, right = _Frontier(head), _Frontier(head)
+    # A missing transition is never needed, so it cannot stop the other proofs
+    sim = Simulation(to_machine(rules, num_states), [])
+    while steps <= max_steps:
+        key = (state, tape.get(head, "0"))
+        if key not in rules:
+            return state, head, tape, steps
+        write, move, state = rules[key]
+        if write == "0":
+            tape.pop(head, None)
+        else:
+            tape[head] = write
+        head += 1 if move == "R" else -1
+        steps += 1
+
+        if state == snapshot[0] and head == snapshot[1] and tape == snapshot[2]:
+            return CYCLE
+        if head > high:
+            high = head
+            if right.repeats(state, head, tape):
+                return TRANSLATED_CYCLE
+        elif head < low:
+            low = head
+            if left.repeats(state, head, tape):
+                return TRANSLATED_CYCLE
+        right.behind = min(right.behind, head)
+        left.behind = max(left.behind, head)
+        since_snapshot += 1
+        if since_snapshot == power:
+            sim.state, sim.heads, sim.tapes = state, [head], [tape]
+            reason = find_non_halting_reason(sim)
+            if reason is not None:
+                return reason
+            snapshot = (state, head, dict(tape))
+            since_snapshot, power = 0, power * 2
+    return state, head, tape, steps
+
+
+def to_machine(rules: Dict[Tuple[str, str], Rule], num_states: int) -> TuringMachine:
+    states = set(STATE_NAMES[:num_states]) | {HALT_STATE}
+    return TuringMachine(
+        states=states,
+        alphabet={"0", "1"},
+        blank="0",
+        start="A",
+        accept=set(),
+        reject=set(),
+        halt={HALT_STATE},
+        transitions={
+            (state, (read,)): [((write,), (move,), next_state)]
+            for (state, read), (write, move, next_state) in rules.items()
+        },
+    )
+
+
+def format_rules(rules: Dict[Tuple[str, str], Rule], num_states: int) -> str:
+    """The usual compact notation, e.g. `1RB 1LB, 1LA 1RH` for BB(2)."""
+    return ", ".join(
+        " ".join(
+            "".join(rules[(state, read)]) if (state, read) in rules else "---"
+            for read in "01"
+        )
+        for state in STATE_NAMES[:num_states]
+    )
+
+
+def format_machine(rules: Dict[Tuple[str, str], Rule], num_states: int) -> str:
+    """The machine in the format read by tm.py."""
+    lines = [
+        f"# Busy beaver candidate: {format_rules(rules, num_states)}",
+        f"states: {' '.join(STATE_NAMES[:num_states])} {HALT_STATE}",
+        "alphabet: 0 1",
+        "blank: 0",
+        "start: A",
+        f"halt: {HALT_STATE}",
+        "",
+    ]
+    lines += [
+        f"{state} {read} -> {write} {move} {next_state}"
+        for (state, read), (write, move, next_state) in sorted(rules.items())
+    ]
+    return "\n".join(lines) + "\n"
+
+
+def verify(rules: Dict[Tuple[str, str], Rule], num_states: int, max_steps: int) -> Tuple[int, int]:
+    """Re-run a champion on the general simulator. Return (steps, ones)."""
+    sim = Simulation(to_machine(rules, num_states), [])
+    status = sim.run(max_steps + 1)
+    assert status == HALT, f"champion did not halt: {status}"
+    return sim.steps, sum(c == "1" for c in sim.tape.values())
@@ -0,0 +1,614 @@
+"""
+# Turing Machines
+
+The machine definition, its parser and validator, and the simulators
+for deterministic and nondeterministic machines. The machine format is
+documented in `tm.py`, which is the command line interface to this.
+"""
+
+import itertools
+from typing import Dict, List, Optional, Set, Tuple
+
+
+LEFT: str = "L"
+RIGHT: str = "R"
+STAY: str = "S"
+MOVES: Dict[str, int] = {LEFT: -1, RIGHT: 1, STAY: 0}
+
+HEADER_KEYS: Tuple[str, ...] = (
+    "states", "alphabet", "blank", "start", "accept", "reject", "halt",
+    "nondeterministic", "tapes",
+)
+DEFAULT_MAX_STEPS: int = 10_000
+DEFAULT_MAX_CONFIGURATIONS: int = 1_000_000
+
+# Reasons that a simulation stopped
+ACCEPT: str = "accept"
+REJECT: str = "reject"
+HALT: str = "halt"
+NO_TRANSITION: str = "no transition"
+STEP_LIMIT: str = "step limit exceeded"
+CONFIGURATION_LIMIT: str = "configuration limit exceeded"
+NEVER_HALTS: str = "does not halt"
+
+
+class MachineError(Exception):
+    """The machine definition is malformed."""
+
+
+# One entry per tape in each of the tuples below
+Key = Tuple[str, Tuple[str, ...]]   # (state, symbols read)
+Transition = Tuple[Tuple[str, ...], Tuple[str, ...], str]   # (writes, moves, next state)
+
+
+class TuringMachine:
+    def __init__(
+        self,
+        states: Set[str],
+        alphabet: Set[str],
+        blank: str,
+        start: str,
+        accept: Set[str],
+        reject: Set[str],
+        halt: Set[str],
+        transitions: Dict[Key, List[Transition]],
+        nondeterministic: bool = False,
+        num_tapes: int = 1,
+    ):
+        self.states: Set[str] = states
+        self.alphabet: Set[str] = alphabet
+        self.blank: str = blank
+        self.start: str = start
+        self.accept: Set[str] = accept
+        self.reject: Set[str] = reject
+        self.halt: Set[str] = halt
+        # Deterministic machines have exactly one transition per key
+        self.transitions: Dict[Key, List[Transition]] = transitions
+        self.nondeterministic: bool = nondeterministic
+        self.num_tapes: int = num_tapes
+
+    @property
+    def final_states(self) -> Set[str]:
+        return self.accept | self.reject | self.halt
+
+
+WILDCARD: str = "*"
+
+
+def format_key(key: Key) -> str:
+    state, reads = key
+    return f"({state}, {' '.join(reads)})"
+
+
+def parse_machine(text: str) -> TuringMachine:
+    """Parse the machine format described in the module docstring.
+
+    Every problem in the definition is collected and reported together in
+    a single MachineError, rather than stopping at the first one.
+    """
+    errors: List[str] = []
+    header: Dict[str, List[str]] = {}
+    # (lineno, block, left of the arrow, right of the arrow). We need the
+    # number of tapes to split these up, which might not be known yet.
+    lines: List[Tuple[int, Optional[str], List[str], List[str]]] = []
+    block: Optional[str] = None
+
+    for lineno, line in enumerate(text.split("\n"), start=1):
+        line = line.split("#", 1)[0].strip()
+        if not line:
+            continue
+
+        key, colon, value = line.partition(":")
+        key = key.strip()
+        if colon and "->" not in line:
+            if key in HEADER_KEYS:
+                if key in header:
+                    errors.append(f"line {lineno}: duplicate header '{key}'")
+                header[key] = value.split()
+                block = None
+            elif len(key.split()) == 1 and not value.strip():
+                block = key
+            else:
+                errors.append(f"line {lineno}: unknown header '{key}'")
+            continue
+
+        lhs, arrow, rhs = line.partition("->")
+        if not arrow:
+            errors.append(f"line {lineno}: expected a header, a block, or a transition")
+            continue
+        lines.append((lineno, block, lhs.split(), rhs.split()))
+
+    header_errors: List[str] = []
+    for key in ("states", "alphabet", "blank", "start"):
+        if key not in header:
+            header_errors.append(f"missing header '{key}'")
+    for key in ("blank", "start", "tapes", "nondeterministic"):
+        if key in header and len(header[key]) != 1:
+            header_errors.append(f"header '{key}' takes exactly one value")
+    if header.get("nondeterministic", ["no"]) not in (["yes"], ["no"]):
+        header_errors.append("header 'nondeterministic' must be 'yes' or 'no'")
+    num_tapes = header.get("tapes", ["1"])[0]
+    if not num_tapes.isdigit() or int(num_tapes) < 1:
+        header_errors.append("header 'tapes' must be a positive integer")
+    if header_errors:
+        # Without the header, we cannot check the transitions
+        raise MachineError("\n".join(errors + header_errors))
+
+    states = set(header["states"])
+    alphabet = set(header["alphabet"])
+    nondeterministic = header.get("nondeterministic") == ["yes"]
+    k = int(num_tapes)
+    if WILDCARD in alphabet:
+        errors.append(f"'{WILDCARD}' is reserved for wildcards and cannot be a symbol")
+
+    # Group the rules by the pattern they read, which may contain wildcards
+    patterns: Dict[Key, List[Tuple[int, Transition]]] = {}
+    for lineno, block, lhs, rhs in lines:
+        if block is not None and len(lhs) == k:
+            lhs = [block] + lhs
+        if len(lhs) != k + 1 or len(rhs) != 2 * k + 1:
+            expected = "state read -> write move next" if k == 1 else (
+                f"state, {k} reads -> {k} writes, {k} moves, next"
+            )
+            errors.append(f"line {lineno}: expected '{expected}'")
+            continue
+        state, reads = lhs[0], lhs[1:]
+        writes, moves, next_state = tuple(rhs[:k]), tuple(rhs[k:2 * k]), rhs[-1]
+        if any(move not in MOVES for move in moves):
+            errors.append(f"line {lineno}: move must be one of {', '.join(MOVES)}")
+            continue
+        for s in (state, next_state):
+            if s not in states:
+                errors.append(f"line {lineno}: state '{s}' is not declared")
+        for c in [c for read in reads for c in read.split(",")] + list(writes):
+            if c != WILDCARD and c not in alphabet:
+                errors.append(f"line {lineno}: symbol '{c}' is not in the alphabet")
+
+        for pattern in itertools.product(*(read.split(",") for read in reads)):
+            rule = (writes, moves, next_state)
+            existing = patterns.setdefault((state, pattern), [])
+            if rule in [r for _, r in existing] or (existing and not nondeterministic):
+                if all(c == WILDCARD for c in pattern):
+                    errors.append(f"line {lineno}: duplicate wildcard transition for state '{state}'")
+                else:
+                    errors.append(
+                        f"line {lineno}: duplicate transition for {format_key((state, pattern))}"
+                    )
+                continue
+            existing.append((lineno, rule))
+
+    # Expand the wildcards. More specific patterns take priority, regardless
+    # of the order they were written in.
+    specificity: Dict[Key, int] = {}
+    sources: Dict[Key, Tuple[str, ...]] = {}
+    transitions: Dict[Key, List[Transition]] = {}
+    for (state, pattern), rules in patterns.items():
+        n = sum(c != WILDCARD for c in pattern)
+        symbols = sorted(alphabet)
+        for reads in itertools.product(*(symbols if c == WILDCARD else [c] for c in pattern)):
+            expanded = [
+                (
+                    tuple(r if w == WILDCARD else w for r, w in zip(reads, writes)),
+                    moves,
+                    next_state,
+                )
+                for _, (writes, moves, next_state) in rules
+            ]
+            key = (state, reads)
+            if key not in transitions or n > specificity[key]:
+                specificity[key] = n
+                sources[key] = pattern
+                transitions[key] = expanded
+            elif n == specificity[key]:
+                if not nondeterministic:
+                    errors.append(
+                        f"line {rules[0][0]}: pattern {format_key((state, pattern))} overlaps "
+                        f"{format_key((state, sources[key]))} equally"
+                    )
+                else:
+                    transitions[key] += expanded
+
+    machine = TuringMachine(
+        states=states,
+        alphabet=alphabet,
+        blank=header["blank"][0],
+        start=header["start"][0],
+        accept=set(header.get("accept", [])),
+        reject=set(header.get("reject", [])),
+        halt=set(header.get("halt", [])),
+        transitions=transitions,
+        nondeterministic=nondeterministic,
+        num_tapes=k,
+    )
+    if machine.blank not in alphabet:
+        errors.append(f"blank symbol '{machine.blank}' is not in the alphabet")
+    for state in sorted({machine.start} | machine.final_states):
+        if state not in states:
+            errors.append(f"state '{state}' is not declared")
+    if not errors:
+        # Anything wrong with the transitions has been reported line by line
+        errors += check_machine(machine)
+    if errors:
+        # Do not report the same problem once per expanded transition
+        raise MachineError("\n".join(dict.fromkeys(errors)))
+    return machine
+
+
+def check_machine(machine: TuringMachine) -> List[str]:
+    """Return a list of the undeclared states and symbols the machine uses."""
+    errors: List[str] = []
+    if machine.blank not in machine.alphabet:
+        errors.append(f"blank symbol '{machine.blank}' is not in the alphabet")
+    for state in sorted({machine.start} | machine.final_states):
+        if state not in machine.states:
+            errors.append(f"state '{state}' is not declared")
+    for (state, reads), rules in machine.transitions.items():
+        if len(rules) > 1 and not machine.nondeterministic:
+            errors.append(
+                f"deterministic machine has several transitions for {format_key((state, reads))}"
+            )
+        for writes, moves, next_state in rules:
+            if not len(reads) == len(writes) == len(moves) == machine.num_tapes:
+                errors.append(f"transition for {format_key((state, reads))} has the wrong number of tapes")
+            for s in (state, next_state):
+                if s not in machine.states:
+                    errors.append(f"state '{s}' is not declared")
+            for c in reads + writes:
+                if c not in machine.alphabet:
+                    errors.append(f"symbol '{c}' is not in the alphabet")
+    # Do not report the same undeclared name once per transition
+    return list(dict.fromkeys(errors))
+
+
+def find_warnings(machine: TuringMachine) -> List[str]:
+    """Return problems that do not stop the machine from running."""
+    warnings: List[str] = []
+
+    successors: Dict[str, Set[str]] = {}
+    for (state, _), rules in machine.transitions.items():
+        successors.setdefault(state, set()).update(next_state for _, _, next_state in rules)
+    reachable: Set[str] = {machine.start}
+    frontier: List[str] = [machine.start]
+    while frontier:
+        for next_state in successors.get(frontier.pop(), set()):
+            if next_state not in reachable:
+                reachable.add(next_state)
+                frontier.append(next_state)
+    for state in sorted(machine.states - reachable):
+        warnings.append(f"state '{state}' is unreachable from '{machine.start}'")
+
+    for state in sorted(machine.final_states & set(successors)):
+        warnings.append(f"final state '{state}' has transitions that will never be used")
+    return warnings
+
+
+def format_machine(machine: TuringMachine) -> str:
+    """Write a machine in our format, with one block per state."""
+    def names(items: Set[str]) -> str:
+        return " ".join(sorted(items))
+
+    lines = []
+    if machine.num_tapes != 1:
+        lines.append(f"tapes: {machine.num_tapes}")
+    if machine.nondeterministic:
+        lines.append("nondeterministic: yes")
+    lines += [
+        f"states: {names(machine.states)}",
+        f"alphabet: {names(machine.alphabet)}",
+        f"blank: {machine.blank}",
+        f"start: {machine.start}",
+    ]
+    for key, states in (("accept", machine.accept), ("reject", machine.reject), ("halt", machine.halt)):
+        if states:
+            lines.append(f"{key}: {names(states)}")
+    for state in sorted(machine.states):
+        rules = sorted((reads, rule) for (s, reads), rules in machine.transitions.items()
+                       if s == state for rule in rules)
+        if not rules:
+            continue
+        lines += ["", f"{state}:"]
+        for reads, (writes, moves, next_state) in rules:
+            lines.append(f"    {' '.join(reads)} -> {' '.join(writes)} {' '.join(moves)} {next_state}")
+    return "\n".join(lines) + "\n"
+
+
+def load_machine(path: str) -> TuringMachine:
+    with open(path) as f:
+        return parse_machine(f.read())
+
+
+def parse_tape(tape: str) -> List[str]:
+    """One symbol per character, unless the symbols are space-separated."""
+    return tape.split() if " " in tape.strip() else list(tape.strip())
+
+
+def initial_tapes(machine: TuringMachine, inputs: List[List[str]]) -> List[Dict[int, str]]:
+    """Lay the inputs out from cell 0 of each tape. Missing inputs are blank."""
+    if len(inputs) > machine.num_tapes:
+        raise MachineError(f"got {len(inputs)} input tapes for a {machine.num_tapes}-tape machine")
+    tapes: List[Dict[int, str]] = []
+    for i in range(machine.num_tapes):
+        tape = inputs[i] if i < len(inputs) else []
+        for c in tape:
+            if c not in machine.alphabet:
+                raise MachineError(f"input symbol '{c}' is not in the alphabet")
+        # Sparse tape: unwritten cells are blank
+        tapes.append({j: c for j, c in enumerate(tape) if c != machine.blank})
+    return tapes
+
+
+class Simulation:
+    """The configuration (state, tapes, heads) of a machine as it runs."""
+
+    def __init__(self, machine: TuringMachine, inputs: List[List[str]]):
+        if machine.nondeterministic:
+            raise MachineError("nondeterministic machines must be explored with explore()")
+        self.machine: TuringMachine = machine
+        self.state: str = machine.start
+        self.tapes: List[Dict[int, str]] = initial_tapes(machine, inputs)
+        self.heads: List[int] = [0] * machine.num_tapes
+        self.steps: int = 0
+        self.status: Optional[str] = self._final_status()
+        # Why we think the machine never halts, if it is NEVER_HALTS
+        self.reason: Optional[str] = None
+
+    # The first tape is the only tape of a single-tape machine
+    @property
+    def tape(self) -> Dict[int, str]:
+        return self.tapes[0]
+
+    @property
+    def head(self) -> int:
+        return self.heads[0]
+
+    def _final_status(self) -> Optional[str]:
+        if self.state in self.machine.accept:
+            return ACCEPT
+        elif self.state in self.machine.reject:
+            return REJECT
+        elif self.state in self.machine.halt:
+            return HALT
+        return None
+
+    def read(self) -> Tuple[str, ...]:
+        return tuple(
+            tape.get(head, self.machine.blank) for tape, head in zip(self.tapes, self.heads)
+        )
+
+    def step(self) -> Optional[Tuple[Key, Transition]]:
+        """Apply one transition. Return the rule applied, or None if stopped."""
+        if self.status is not None:
+            return None
+        key = (self.state, self.read())
+        if key not in self.machine.transitions:
+            self.status = NO_TRANSITION
+            return None
+        transition = self.machine.transitions[key][0]
+        writes, moves, next_state = transition
+        for i, (write, move) in enumerate(zip(writes, moves)):
+            if write == self.machine.blank:
+                self.tapes[i].pop(self.heads[i], None)
+            else:
+                self.tapes[i][self.heads[i]] = write
+            self.heads[i] += MOVES[move]
+        self.state = next_state
+        self.steps += 1
+        self.status = self._final_status()
+        return key, transition
+
+    def run(self, max_steps: int = DEFAULT_MAX_STEPS, detect_non_halting: bool = False) -> str:
+        """Run until the machine stops or we hit the step limit.
+
+        With `detect_non_halting`, we also stop with NEVER_HALTS (and set
+        `self.reason`) if we can prove that the machine will never stop.
+        """
+        # Brent's cycle detection: compare against a snapshot that we retake
+        # after every power of two steps, so that we never store more than one.
+        snapshot = self._snapshot()
+        since_snapshot, power = 0, 1
+        while self.status is None:
+            if self.steps >= max_steps:
+                return STEP_LIMIT
+            self.step()
+            if not detect_non_halting or self.status is not None:
+                continue
+            if (
+                self.state == snapshot[0]
+                and tuple(self.heads) == snapshot[1]
+                and self.tapes == snapshot[2]
+            ):
+                self.status = NEVER_HALTS
+                self.reason = f"the configuration repeats every {since_snapshot + 1} steps"
+                break
+            since_snapshot += 1
+            if since_snapshot == power:
+                # The other checks are slower, so only run them every so often
+                self.reason = find_non_halting_reason(self)
+                if self.reason is not None:
+                    self.status = NEVER_HALTS
+                    break
+                snapshot = self._snapshot()
+                since_snapshot, power = 0, power * 2
+        return self.status
+
+    def _snapshot(self) -> Tuple[str, Tuple[int, ...], List[Dict[int, str]]]:
+        return self.state, tuple(self.heads), [dict(tape) for tape in self.tapes]
+
+    def bounds(self, i: int = 0) -> Tuple[int, int]:
+        """The smallest range of cells holding every non-blank cell and the head."""
+        cells = list(self.tapes[i]) + [self.heads[i]]
+        return min(cells), max(cells)
+
+    def tape_symbols(self, i: int = 0) -> List[str]:
+        """The non-blank portion of a tape (trimmed of blanks on either end)."""
+        tape = self.tapes[i]
+        if not tape:
+            return []
+        lo, hi = min(tape), max(tape)
+        return [tape.get(j, self.machine.blank) for j in range(lo, hi + 1)]
+
+
+def format_tape(symbols: List[str]) -> str:
+    if all(len(c) == 1 for c in symbols):
+        return "".join(symbols)
+    return " ".join(symbols)
+
+
+def format_configuration(
+    machine: TuringMachine, state: str, heads: List[int], tapes: List[Dict[int, str]]
+) -> str:
+    """Show the non-blank part of each tape with the head's cell in brackets."""
+    shown: List[str] = []
+    for head, tape in zip(heads, tapes):
+        cells = list(tape) + [head]
+        shown.append(" ".join(
+            f"[{tape.get(i, machine.blank)}]" if i == head else tape.get(i, machine.blank)
+            for i in range(min(cells), max(cells) + 1)
+        ))
+    return f"{state}: {' | '.join(shown)}"
+
+
+# (state, heads, non-blank cells of each tape in order). Hashable, so we can
+# deduplicate.
+Configuration = Tuple[str, Tuple[int, ...], Tuple[Tuple[Tuple[int, str], ...], ...]]
+
+
+def explore(
+    machine: TuringMachine,
+    inputs: List[List[str]],
+    max_steps: int = DEFAULT_MAX_STEPS,
+    max_configurations: int = DEFAULT_MAX_CONFIGURATIONS,
+) -> Tuple[str, List[Configuration], int]:
+    """Explore every branch of a (nondeterministic) machine breadth-first.
+
+    Return the status, an accepting computation (empty if none was found),
+    and the number of distinct configurations visited. The status is
+    REJECT only if every branch stopped without accepting.
+    """
+    start: Configuration = (
+        machine.start,
+        (0,) * machine.num_tapes,
+        tuple(tuple(sorted(tape.items())) for tape in initial_tapes(machine, inputs)),
+    )
+    parents: Dict[Configuration, Optional[Configuration]] = {start: None}
+
+    def path_to(config: Configuration) -> List[Configuration]:
+        path = [config]
+        while parents[path[-1]] is not None:
+            path.append(parents[path[-1]])
+        return path[::-1]
+
+    if machine.start in machine.accept:
+        return ACCEPT, [start], 1
+
+    frontier: List[Configuration] = [start]
+    for _ in range(max_steps):
+        next_frontier: List[Configuration] = []
+        for config in frontier:
+            state, heads, cells = config
+            if state in machine.final_states:
+                continue    # This branch has stopped
+            tapes = [dict(c) for c in cells]
+            reads = tuple(tape.get(head, machine.blank) for tape, head in zip(tapes, heads))
+            for writes, moves, next_state in machine.transitions.get((state, reads), []):
+                new_tapes = [dict(tape) for tape in tapes]
+                for tape, head, write in zip(new_tapes, heads, writes):
+                    if write == machine.blank:
+                        tape.pop(head, None)
+                    else:
+                        tape[head] = write
+                new = (
+                    next_state,
+                    tuple(head + MOVES[move] for head, move in zip(heads, moves)),
+                    tuple(tuple(sorted(tape.items())) for tape in new_tapes),
+                )
+                if new in parents:
+                    continue
+                parents[new] = config
+                if next_state in machine.accept:
+                    return ACCEPT, path_to(new), len(parents)
+                if len(parents) >= max_configurations:
+                    return CONFIGURATION_LIMIT, [], len(parents)
+                next_frontier.append(new)
+        if not next_frontier:
+            return REJECT, [], len(parents)
+        frontier = next_frontier
+    return STEP_LIMIT, [], len(parents)
+
+
+def find_non_halting_reason(sim: Simulation) -> Optional[str]:
+    """Try to prove that a running machine will never stop.
+
+    Return a description of the proof, or None if we could not find one.
+    None does not mean that the machine halts! This catches two patterns:
+
+    * The machine can never reach a final state again, and it has a
+      transition for everything it could read from here on.
+    * There is nothing but blank tape in front of the head, and reading
+      blanks, the machine keeps moving that way while cycling through the
+      same states. Whatever it writes is left behind and never read again.
+      (This is the "head runs off to infinity" pattern.)
+    """
+    machine = sim.machine
+
+    reachable: Set[str] = {sim.state}
+    frontier: List[str] = [sim.state]
+    while frontier:
+        state = frontier.pop()
+        for (s, _), rules in machine.transitions.items():
+            if s != state:
+                continue
+            for _, _, next_state in rules:
+                if next_state not in reachable:
+                    reachable.add(next_state)
+                    frontier.append(next_state)
+    num_reads = len(machine.alphabet) ** machine.num_tapes
+    if not reachable & machine.final_states and all(
+        sum(s == state for s, _ in machine.transitions) == num_reads for state in reachable
+    ):
+        return "no final state is reachable and no transition is missing"
+
+    if machine.num_tapes != 1:
+        return None
+    for direction in (LEFT, RIGHT):
+        run_off = _find_run_off(sim, MOVES[direction])
+        if run_off is not None:
+            return f"the head runs off to the {'left' if direction == LEFT else 'right'} cycling through {run_off}"
+    return None
+
+
+def _find_run_off(sim: Simulation, direction: int) -> Optional[str]:
+    """See find_non_halting_reason(). `direction` is +1 or -1."""
+    machine = sim.machine
+    blank = machine.blank
+    # Every cell in front of the head (and the head's cell) must be blank
+    if any((position - sim.head) * direction >= 0 for position in sim.tape):
+        return None
+
+    state, cell = sim.state, blank
+    # Every state we pass through, and where in that list we were on a fresh cell
+    chain: List[str] = [state]
+    fresh: Dict[str, int] = {state: 0}
+    in_place: Set[Tuple[str, str]] = set()
+    while True:
+        rules = machine.transitions.get((state, (cell,)))
+        if rules is None or state in machine.final_states:
+            return None
+        (write,), (move,), state = rules[0]
+        if MOVES[move] == 0:
+            # We read back what we wrote. Going round in circles here is an
+            # exact cycle, which Simulation.run() finds by itself.
+            if (state, write) in in_place:
+                return None
+            in_place.add((state, write))
+            chain.append(state)
+            cell = write
+            continue
+        if MOVES[move] != direction:
+            return None
+        # Now on a fresh cell, which is blank
+        cell, in_place = blank, set()
+        if state in fresh:
+            return " ".join(chain[fresh[state]:])
+        fresh[state] = len(chain)
+        chain.append(state)
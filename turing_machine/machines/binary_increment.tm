@@ -0,0 +1,16 @@
+# Add one to a binary number. The head starts on the most significant bit.
+states: right carry done
+alphabet: 0 1 _
+blank: _
+start: right
+halt: done
+
+# Walk to the least significant bit
+right:
+    * -> * R right
+    _ -> _ L carry
+
+# Propagate the carry back towards the most significant bit
+carry:
+    1 -> 0 L carry
+    0,_ -> 1 S done
@@ -0,0 +1,13 @@
+# Nondeterministically guess where the substring "101" starts.
+nondeterministic: yes
+states: scan saw_1 saw_10 found
+alphabet: 0 1 _
+blank: _
+start: scan
+accept: found
+
+scan:
+    0,1 -> * R scan     # Not here, keep looking
+    1 -> 1 R saw_1      # Guess that "101" starts here
+saw_1 0 -> 0 R saw_10
+saw_10 1 -> 1 S found
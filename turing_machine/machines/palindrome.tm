@@ -0,0 +1,30 @@
+# Decide whether a string over {a, b} is a palindrome. Each pass erases the
+# first symbol, remembers it, and checks it against the last symbol.
+states: start have_a have_b check_a check_b back yes no
+alphabet: a b _
+blank: _
+start: start
+accept: yes
+reject: no
+
+start a -> _ R have_a
+start b -> _ R have_b
+start _ -> _ S yes
+
+have_a a -> a R have_a
+have_a b -> b R have_a
+have_a _ -> _ L check_a
+have_b a -> a R have_b
+have_b b -> b R have_b
+have_b _ -> _ L check_b
+
+check_a a -> _ L back
+check_a b -> b S no
+check_a _ -> _ S yes
+check_b b -> _ L back
+check_b a -> a S no
+check_b _ -> _ S yes
+
+back a -> a L back
+back b -> b L back
+back _ -> _ R start
@@ -0,0 +1,27 @@
+# Decide whether a string over {a, b} is a palindrome in linear time by
+# copying it to the second tape and comparing it against itself backwards.
+tapes: 2
+states: copy rewind compare yes no
+alphabet: a b _
+blank: _
+start: copy
+accept: yes
+reject: no
+
+copy:
+    a _ -> a a R R copy
+    b _ -> b b R R copy
+    _ _ -> _ _ L L rewind
+
+# Tape 1 goes back to the start; tape 2 stays on the last symbol
+rewind:
+    * * -> * * L S rewind
+    _ * -> _ * R S compare
+    _ _ -> _ _ S S yes
+
+compare:
+    a a -> a a R L compare
+    b b -> b b R L compare
+    a b -> a b S S no
+    b a -> b a S S no
+    _ _ -> _ _ S S yes
@@ -0,0 +1,30 @@
+# Double a unary number. Each 1 is marked as an `a` and pays for a `b`
+# on the right end; at the end, every `a` and `b` turns back into a 1.
+states: mark append back fix_right fix_left done
+alphabet: 1 a b _
+blank: _
+start: mark
+halt: done
+
+# At the leftmost unmarked 1 (or the first b once all are marked)
+mark:
+    1 -> a R append
+    b -> 1 R fix_right
+    _ -> _ S done
+
+append:
+    1,b -> * R append
+    _ -> b L back
+
+back:
+    1,b -> * L back
+    a -> a R mark
+
+fix_right:
+    b -> 1 R fix_right
+    _ -> _ L fix_left
+
+fix_left:
+    1 -> 1 L fix_left
+    a -> 1 L fix_left
+    _ -> _ R done
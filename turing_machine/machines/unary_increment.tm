@@ -0,0 +1,15 @@
+# Add one to a unary number (a run of 1s).
+states: right back done
+alphabet: 1 _
+blank: _
+start: right
+halt: done
+
+right:
+    1 -> 1 R right
+    _ -> 1 L back
+
+# Return the head to the start of the number
+back:
+    1 -> 1 L back
+    _ -> _ R done
@@ -0,0 +1,74 @@
+# A universal Turing machine: the textbook three-tape construction.
+#
+# Tape 1 holds the description of the simulated machine M, tape 2 holds
+# M's tape, and tape 3 holds M's current state in unary. Each transition
+# of M is written on tape 1 as
+#
+#     | x...x r w m x...x
+#
+# which is the state in unary, the symbol read, the symbol written, the
+# move, and the next state in unary. M's symbols are 0, 1, and the blank,
+# which is written as 'b' on tape 1 (tape 2 uses the real blank). See
+# utm.py for the encoder.
+#
+# We scan tape 1 for the transition matching tape 3 and the symbol under
+# tape 2's head, apply it, and start the scan over. If no transition
+# matches, M has halted and so do we.
+tapes: 3
+states: seek cmp cmp_sym skip_rewind skip_seek write move erase copy rewind_desc rewind_state done
+alphabet: | x 0 1 b L R S _
+blank: _
+start: seek
+halt: done
+
+seek:
+    | * * -> | * * R S S cmp
+    _ * * -> _ * * S S S done
+
+# Compare the transition's state with tape 3, one x at a time
+cmp:
+    x * x -> x * x R S R cmp
+    x * _ -> x * _ S S L skip_rewind
+    0,1,b * x -> * * * S S L skip_rewind
+    0,1,b * _ -> * * * S S S cmp_sym
+
+# Compare the transition's read symbol with tape 2
+cmp_sym:
+    0 0 * -> 0 0 * R S S write
+    1 1 * -> 1 1 * R S S write
+    b _ * -> b _ * R S S write
+    * * * -> * * * S S L skip_rewind
+
+# No match: put tape 3 back at the start and go to the next transition
+skip_rewind:
+    * * x -> * * x S S L skip_rewind
+    * * _ -> * * _ S S R skip_seek
+skip_seek:
+    * * * -> * * * R S S skip_seek
+    |,_ * * -> * * * S S S seek
+
+# Match: apply the transition to tape 2
+write:
+    0 * * -> 0 0 * R S S move
+    1 * * -> 1 1 * R S S move
+    b * * -> b _ * R S S move
+move:
+    L * * -> L * * R L L erase
+    R * * -> R * * R R L erase
+    S * * -> S * * R S L erase
+
+# Replace tape 3 with the next state
+erase:
+    * * x -> * * _ S S L erase
+    * * _ -> * * _ S S R copy
+copy:
+    x * _ -> x * x R S R copy
+    |,_ * _ -> * * _ L S L rewind_desc
+
+# Go back to the start of tapes 1 and 3
+rewind_desc:
+    * * * -> * * * L S S rewind_desc
+    _ * * -> _ * * R S S rewind_state
+rewind_state:
+    * * x -> * * x S S L rewind_state
+    * * _ -> * * _ S S R seek
@@ -0,0 +1,105 @@
+"""
+# Turing Machine Pipelines
+
+Compose machines like shell commands: the output of one machine is the
+input of the next.
+
+    python3 tm.py pipe machines/unary_increment.tm machines/unary_double.tm --tape "111"
+
+A machine's output is the non-blank part of its last tape (its only tape,
+for most machines), and it becomes the first tape of the next machine.
+The pipeline carries on while each machine halts or accepts, and stops
+at the first machine that does anything else (rejects, gets stuck, or
+runs out of steps). A nondeterministic machine passes on the tape of its
+accepting computation.
+
+## Alphabets
+
+Blanks inside the output become the next machine's blank, so the two
+machines may use different blank symbols. Every other symbol must be in
+the next machine's alphabet. Before running anything, we warn about
+symbols that one machine can write and the next does not know; these
+are often scratch marks that never survive to the output, so this is
+only an error if the symbol really does show up.
+"""
+
+from typing import Dict, List, NamedTuple
+
+from machine import (
+    ACCEPT,
+    HALT,
+    MachineError,
+    Simulation,
+    TuringMachine,
+    explore,
+)
+
+
+class Stage(NamedTuple):
+    status: str
+    steps: int
+    output: List[str]
+
+
+def written_symbols(machine: TuringMachine) -> set:
+    """The symbols a machine might leave on its last tape."""
+    last = machine.num_tapes - 1
+    symbols = {writes[last] for rules in machine.transitions.values() for writes, _, _ in rules}
+    if last == 0:
+        symbols |= machine.alphabet    # The input may pass through untouched
+    return symbols - {machine.blank}
+
+
+def find_incompatibilities(machines: List[TuringMachine], names: List[str]) -> List[str]:
+    warnings: List[str] = []
+    for i in range(len(machines) - 1):
+        unknown = written_symbols(machines[i]) - machines[i + 1].alphabet
+        if unknown:
+            warnings.append(
+                f"{names[i]} may output {', '.join(sorted(unknown))}, "
+                f"which {names[i + 1]} does not have in its alphabet"
+            )
+    return warnings
+
+
+def _trim(tape: Dict[int, str], blank: str) -> List[str]:
+    if not tape:
+        return []
+    lo, hi = min(tape), max(tape)
+    return [tape.get(j, blank) for j in range(lo, hi + 1)]
+
+
+def run_stage(machine: TuringMachine, tape: List[str], max_steps: int, max_configurations: int) -> Stage:
+    if machine.nondeterministic:
+        status, path, _ = explore(machine, [tape], max_steps, max_configurations)
+        if not path:
+            return Stage(status, 0, [])
+        _, _, cells = path[-1]
+        return Stage(status, len(path) - 1, _trim(dict(cells[-1]), machine.blank))
+    sim = Simulation(machine, [tape])
+    status = sim.run(max_steps, detect_non_halting=True)
+    return Stage(status, sim.steps, sim.tape_symbols(machine.num_tapes - 1))
+
+
+def run_pipeline(
+    machines: List[TuringMachine],
+    names: List[str],
+    tape: List[str],
+    max_steps: int,
+    max_configurations: int,
+) -> List[Stage]:
+    """Run each machine on the previous one's output, until one does not finish."""
+    stages: List[Stage] = []
+    previous_blank = None
+    for machine, name in zip(machines, names):
+        if previous_blank is not None:
+            tape = [machine.blank if c == previous_blank else c for c in tape]
+        for c in tape:
+            if c not in machine.alphabet:
+                raise MachineError(f"{name} cannot read '{c}', which is not in its alphabet")
+        stage = run_stage(machine, tape, max_steps, max_configurations)
+        stages.append(stage)
+        if stage.status not in (ACCEPT, HALT):
+            break
+        tape, previous_blank = stage.output, machine.blank
+    return stages
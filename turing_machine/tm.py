@@ -0,0 +1,470 @@
+"""
+# Turing Machine Simulator
+
+A single-tape Turing machine simulator. A machine is a finite set of
+states, a tape alphabet, and a transition table. At every step the
+machine reads the symbol under its head, and the transition for the
+pair (current state, symbol read) tells it which symbol to write, which
+way to move the head, and which state to enter next. The tape is
+unbounded in both directions and every cell that has not been written
+holds the blank symbol.
+
+
+## Machine Format
+
+Machines are plain text files. Blank lines are ignored and `#` starts a
+comment that runs to the end of the line. The header is a series of
+`key: value` lines, followed by one transition per line:
+
+    states: q0 q1 done
+    alphabet: 0 1 _
+    blank: _
+    start: q0
+    accept: done
+    reject:
+    halt:
+
+    # state read -> write move next
+    q0 1 -> 0 L q0
+    q0 0 -> 1 S done
+    q0 _ -> 1 S done
+
+* `states` and `alphabet` list every state and tape symbol.
+* `blank` is the symbol held by every unwritten cell. It must be in the
+  alphabet.
+* `start` is the initial state.
+* `accept`, `reject`, and `halt` are (possibly empty) lists of final
+  states. The machine stops as soon as it enters one. `halt` is for
+  machines that compute a function rather than decide a language.
+* The move is one of `L` (left), `R` (right), or `S` (stay).
+
+If the machine reaches a pair (state, symbol) without a transition, it
+stops and we report it as such. Traditionally, this counts as a reject.
+
+### Shorthands
+
+Writing out every transition gets tedious, so the format also has:
+
+* State blocks. A line `name:` (where `name` is not a header key) opens
+  a block, and the transitions inside it leave out the state:
+
+      carry:
+          1 -> 0 L carry
+          0 -> 1 S done
+
+  A block lasts until the next block or header line.
+* Several read symbols separated by commas (`0,1 -> ...`), which is one
+  rule per symbol.
+* A wildcard read `*`, which matches every symbol that the state has no
+  other rule for.
+* A wildcard write `*`, which writes back the symbol that was read.
+
+So the first state of the increment machine is just:
+
+    right:
+        * -> * R right
+        _ -> _ L carry
+
+### Validation
+
+Before we simulate anything, we check the whole definition and report
+every problem at once: duplicate transitions (including two wildcards
+for one state), references to undeclared states or symbols, and
+malformed lines. States that cannot be reached from the start state are
+reported as warnings because they are harmless, but usually a typo. Use
+`check` to only validate a machine.
+
+### Nondeterminism
+
+With the header `nondeterministic: yes`, a pair (state, symbol) may have
+several transitions (and a state may have several wildcards). Rather
+than following one computation, `run` then explores every branch
+breadth-first, one step of every branch at a time, up to the step limit.
+It reports whether any branch accepts and prints the first accepting
+computation it finds, which is therefore one of the shortest. Identical
+configurations reached along different branches are only explored once.
+
+### Multiple Tapes
+
+With the header `tapes: k`, the machine has k tapes, each with its own
+head. Every transition reads all k tapes at once, then writes to and
+moves each head independently, so a rule lists k symbols to read, k
+symbols to write, and k moves:
+
+    # state read_1 read_2 -> write_1 write_2 move_1 move_2 next
+    copy a _ -> a a R R copy
+
+Wildcards and comma lists work on each tape separately. When rules with
+wildcards overlap, the rule with the most non-wildcard reads wins, so
+`* _` is overridden by `_ _`. Rules that overlap equally (e.g. `* 0` and
+`0 *` for the symbols `0 0`) are an error in a deterministic machine.
+
+### Machines That Never Halt
+
+Rather than always running to the step limit, `run` tries to prove that
+a deterministic machine never halts, and reports "does not halt" with
+the reason if it can. It spots machines that repeat a configuration
+(state, heads, and tapes) exactly, machines that can never reach a final
+state and have no missing transitions, and single-tape machines whose
+head runs off into blank tape, writing a repeating pattern. It cannot
+spot everything (nothing can), so some machines that never halt still
+exceed the step limit. Use `--no-detect` to turn this off.
+
+### Brainfuck
+
+`from-bf` translates a Brainfuck program into a machine (see
+`bf_to_tm.py`), and `compile --target bf` goes the other way, turning a
+deterministic single-tape machine and its input into a Brainfuck program
+that prints the final state and tape (see `tm_to_bf.py`).
+
+### turingmachine.io
+
+Every command also takes machines in the YAML format used by
+[turingmachine.io](https://turingmachine.io) (any file ending in `.yaml`
+or `.yml`), and `run` uses the machine's own `input` if you do not give
+a `--tape`. See `turingmachine_io.py` for how they are translated, and
+use `from-yaml` to convert one to our format.
+
+### Pipelines
+
+`pipe` runs several machines one after the other, feeding each one's
+output tape to the next (see `pipeline.py`).
+
+### Turmites
+
+`turmite` runs Langton's ant and other 2D turmites (see `turmite.py`).
+
+
+## Usage
+
+    python3 tm.py run machines/binary_increment.tm --tape "1011"
+    python3 tm.py run machines/palindrome_2_tape.tm --tape "abba"
+    python3 tm.py check machines/palindrome.tm
+    python3 tm.py from-bf program.bf --output program.tm
+    python3 tm.py compile machines/binary_increment.tm --target bf --tape "1011" --output increment.bf
+    python3 tm.py busy-beaver --states 3
+    python3 tm.py universal machines/binary_increment.tm --tape "1011"
+    python3 tm.py visualize machines/palindrome.tm --tape "abba"
+    python3 tm.py from-yaml binary_increment.yaml --output binary_increment.tm
+    python3 tm.py pipe machines/unary_increment.tm machines/unary_double.tm --tape "111"
+    python3 tm.py turmite --rule RL --steps 11000
+
+The tape is given as one symbol per character. If your alphabet has
+symbols longer than one character, separate the symbols with spaces
+(e.g. `--tape "a1 a2 a1"`). Pass `--tape` once per tape to fill the
+tapes of a multi-tape machine in order; the rest start blank.
+"""
+
+import argparse
+import os
+import sys
+from typing import List, Optional, Tuple
+
+import bf_to_tm
+import busy_beaver
+import pipeline
+import tm_to_bf
+import turmite
+import turingmachine_io
+import utm
+import visualize
+from machine import (
+    DEFAULT_MAX_CONFIGURATIONS,
+    DEFAULT_MAX_STEPS,
+    MachineError,
+    Simulation,
+    TuringMachine,
+    explore,
+    find_warnings,
+    format_configuration,
+    format_machine,
+    format_tape,
+    load_machine,
+    parse_tape,
+)
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from search.search import Progress
+
+
+def load(path: str) -> Tuple[TuringMachine, Optional[List[str]]]:
+    """Load a machine in either format, with its example input (if any)."""
+    if os.path.splitext(path)[1] in (".yaml", ".yml"):
+        return turingmachine_io.load_turingmachine_io(path)
+    return load_machine(path), None
+
+
+def get_inputs(tapes: Optional[List[str]], example: Optional[List[str]]) -> List[List[str]]:
+    if tapes is None:
+        return [example or []]
+    return [parse_tape(tape) for tape in tapes]
+
+
+def print_warnings(machine: TuringMachine):
+    for warning in find_warnings(machine):
+        print(f"warning: {warning}", file=sys.stderr)
+
+
+def run_command(args):
+    machine, example = load(args.machine)
+    print_warnings(machine)
+    inputs = get_inputs(args.tape, example)
+    if machine.nondeterministic:
+        status, path, num_configurations = explore(
+            machine, inputs, args.max_steps, args.max_configurations
+        )
+        print(f"Result: {status}")
+        print(f"Configurations: {num_configurations}")
+        if path:
+            print(f"Steps: {len(path) - 1}")
+            print("Accepting computation:")
+            for state, heads, cells in path:
+                tapes = [dict(c) for c in cells]
+                print(f"    {format_configuration(machine, state, list(heads), tapes)}")
+        return
+    sim = Simulation(machine, inputs)
+    status = sim.run(args.max_steps, detect_non_halting=not args.no_detect)
+    print(f"Result: {status}" + (f" ({sim.reason})" if sim.reason else ""))
+    print(f"Steps: {sim.steps}")
+    print(f"State: {sim.state}")
+    if machine.num_tapes == 1:
+        print(f"Tape: {format_tape(sim.tape_symbols())}")
+    else:
+        for i in range(machine.num_tapes):
+            print(f"Tape {i + 1}: {format_tape(sim.tape_symbols(i))}")
+
+
+def check_command(args):
+    machine, _ = load(args.machine)
+    print_warnings(machine)
+    num_transitions = sum(len(rules) for rules in machine.transitions.values())
+    print(f"OK: {len(machine.states)} states, {num_transitions} transitions")
+
+
+def from_bf_command(args):
+    with open(args.program) as f:
+        source = f.read()
+    try:
+        text = bf_to_tm.compile_bf(source, args.cell_size)
+    except bf_to_tm.CompileError as e:
+        raise MachineError(str(e))
+    if args.output is None:
+        print(text, end="")
+    else:
+        with open(args.output, "w") as f:
+            f.write(text)
+
+
+def compile_command(args):
+    machine, example = load(args.machine)
+    print_warnings(machine)
+    (tape,) = get_inputs(None if args.tape is None else [args.tape], example)
+    try:
+        text = tm_to_bf.compile_machine(machine, tape)
+    except tm_to_bf.CompileError as e:
+        raise MachineError(str(e))
+    if args.output is None:
+        print(text, end="")
+    else:
+        with open(args.output, "w") as f:
+            f.write(text)
+
+
+def busy_beaver_command(args):
+    result = busy_beaver.search(args.states, args.max_steps, None if args.quiet else Progress(label="busy beaver"))
+    print(f"Machines: {result.num_machines}")
+    print(f"    Halted: {result.num_halted}")
+    print(f"    Never halt (repeated configuration): {result.num_cycles}")
+    print(f"    Never halt (repeated further along the tape): {result.num_translated_cycles}")
+    print(f"    Never halt (runs off into blank tape, or cannot halt): {result.num_proved}")
+    print(f"    Undecided (step limit exceeded): {result.num_undecided}")
+    for title, score, rules in (
+        ("Most steps", result.most_steps, result.most_steps_machine),
+        ("Most ones", result.most_ones, result.most_ones_machine),
+    ):
+        steps, ones = busy_beaver.verify(rules, args.states, args.max_steps)
+        print()
+        print(f"{title}: {score} ({steps} steps, {ones} ones)")
+        print(f"    {busy_beaver.format_rules(rules, args.states)}")
+    if args.output is not None:
+        with open(args.output, "w") as f:
+            f.write(busy_beaver.format_machine(result.most_steps_machine, args.states))
+
+
+def universal_command(args):
+    machine, example = load(args.machine)
+    tape = get_inputs(None if args.tape is None else [args.tape], example)[0]
+    status, state, final_tape, utm_steps = utm.run_universal(
+        machine, tape, args.max_steps * args.slowdown
+    )
+    sim = Simulation(machine, [tape])
+    direct_status = sim.run(args.max_steps)
+    print(f"Direct:    {direct_status} in state {sim.state} after {sim.steps} steps")
+    print(f"    Tape: {format_tape(sim.tape_symbols())}")
+    print(f"Universal: {status} in state {state} after {utm_steps} steps")
+    if final_tape:
+        lo, hi = min(final_tape), max(final_tape)
+        symbols = [final_tape.get(i, machine.blank) for i in range(lo, hi + 1)]
+        print(f"    Tape: {format_tape(symbols)}")
+    else:
+        print("    Tape: ")
+    same = (direct_status, sim.state, sim.tape) == (status, state, final_tape)
+    print(f"Match: {'yes' if same else 'no'}")
+    if not same:
+        sys.exit(1)
+
+
+def visualize_command(args):
+    machine, example = load(args.machine)
+    print_warnings(machine)
+    sim = Simulation(machine, get_inputs(args.tape, example))
+    visualize.visualize(sim, args.window, args.delay, args.auto)
+
+
+def from_yaml_command(args):
+    machine, example = turingmachine_io.load_turingmachine_io(args.machine)
+    text = format_machine(machine)
+    if example:
+        text = f"# Example input: {format_tape(example)}\n" + text
+    if args.output is None:
+        print(text, end="")
+    else:
+        with open(args.output, "w") as f:
+            f.write(text)
+
+
+def pipe_command(args):
+    loaded = [load(path) for path in args.machines]
+    machines = [machine for machine, _ in loaded]
+    names = [os.path.basename(path) for path in args.machines]
+    for machine in machines:
+        print_warnings(machine)
+    for warning in pipeline.find_incompatibilities(machines, names):
+        print(f"warning: {warning}", file=sys.stderr)
+    tape = parse_tape(args.tape) if args.tape is not None else loaded[0][1] or []
+    stages = pipeline.run_pipeline(machines, names, tape, args.max_steps, args.max_configurations)
+    for name, stage in zip(names, stages):
+        print(f"{name}: {stage.status} after {stage.steps} steps")
+        print(f"    Tape: {format_tape(stage.output)}")
+    if len(stages) < len(machines):
+        print(f"Stopped before {', '.join(names[len(stages):])}")
+        sys.exit(1)
+
+
+def turmite_command(args):
+    try:
+        ant = turmite.parse_rule(args.rule)
+    except ValueError as e:
+        raise MachineError(str(e))
+    if args.interactive:
+        turmite.interact(ant, args.era, args.width, args.height, args.delay)
+        return
+    ant.run(args.steps)
+    print(turmite.status_line(ant))
+    print(turmite.render(ant))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Turing machine simulator")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+
+    run_parser = subparsers.add_parser("run", help="run a machine on an input tape")
+    run_parser.add_argument("machine", type=str, help="path to the machine definition")
+    run_parser.add_argument(
+        "--tape", type=str, action="append",
+        help="initial tape contents (repeat for each tape of a multi-tape machine)",
+    )
+    run_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS)
+    run_parser.add_argument(
+        "--max-configurations", type=int, default=DEFAULT_MAX_CONFIGURATIONS,
+        help="nondeterministic machines only: stop after visiting this many configurations",
+    )
+    run_parser.add_argument(
+        "--no-detect", action="store_true",
+        help="do not try to prove that the machine never halts (just run to the step limit)",
+    )
+    run_parser.set_defaults(func=run_command)
+
+    check_parser = subparsers.add_parser("check", help="validate a machine without running it")
+    check_parser.add_argument("machine", type=str, help="path to the machine definition")
+    check_parser.set_defaults(func=check_command)
+
+    from_bf_parser = subparsers.add_parser("from-bf", help="translate a Brainfuck program into a machine")
+    from_bf_parser.add_argument("program", type=str, help="path to the Brainfuck program")
+    from_bf_parser.add_argument("--output", type=str, default=None, help="defaults to stdout")
+    from_bf_parser.add_argument("--cell-size", type=int, default=256, help="number of values per cell")
+    from_bf_parser.set_defaults(func=from_bf_command)
+
+    compile_parser = subparsers.add_parser("compile", help="translate a machine and its input into another language")
+    compile_parser.add_argument("machine", type=str, help="path to the machine")
+    compile_parser.add_argument("--target", choices=["bf"], required=True, help="the language to write")
+    compile_parser.add_argument("--tape", type=str, default=None, help="the input, fixed in the program")
+    compile_parser.add_argument("--output", type=str, default=None, help="defaults to stdout")
+    compile_parser.set_defaults(func=compile_command)
+
+    bb_parser = subparsers.add_parser("busy-beaver", help="search for n-state busy beavers")
+    bb_parser.add_argument("--states", type=int, required=True)
+    bb_parser.add_argument("--max-steps", type=int, default=1_000, help="give up on machines after this")
+    bb_parser.add_argument("--output", type=str, default=None, help="save the most-steps champion here")
+    bb_parser.add_argument("--quiet", action="store_true", help="do not report the progress of the search")
+    bb_parser.set_defaults(func=busy_beaver_command)
+
+    utm_parser = subparsers.add_parser(
+        "universal", help="run a machine on the universal machine and check it against direct simulation"
+    )
+    utm_parser.add_argument("machine", type=str, help="path to the machine definition")
+    utm_parser.add_argument("--tape", type=str, default=None, help="initial tape contents")
+    utm_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS, help="for the simulated machine")
+    utm_parser.add_argument(
+        "--slowdown", type=int, default=1_000,
+        help="give the universal machine this many steps per simulated step",
+    )
+    utm_parser.set_defaults(func=universal_command)
+
+    viz_parser = subparsers.add_parser("visualize", help="step through a run in the terminal")
+    viz_parser.add_argument("machine", type=str, help="path to the machine definition")
+    viz_parser.add_argument(
+        "--tape", type=str, action="append",
+        help="initial tape contents (repeat for each tape of a multi-tape machine)",
+    )
+    viz_parser.add_argument("--window", type=int, default=8, help="cells shown on either side of the head")
+    viz_parser.add_argument("--delay", type=float, default=0.2, help="seconds between auto-play steps")
+    viz_parser.add_argument("--auto", action="store_true", help="start in auto-play")
+    viz_parser.set_defaults(func=visualize_command)
+
+    from_yaml_parser = subparsers.add_parser(
+        "from-yaml", help="convert a turingmachine.io machine to our format"
+    )
+    from_yaml_parser.add_argument("machine", type=str, help="path to the YAML machine")
+    from_yaml_parser.add_argument("--output", type=str, default=None, help="defaults to stdout")
+    from_yaml_parser.set_defaults(func=from_yaml_command)
+
+    pipe_parser = subparsers.add_parser("pipe", help="run machines in sequence on each other's output")
+    pipe_parser.add_argument("machines", type=str, nargs="+", help="paths to the machines, in order")
+    pipe_parser.add_argument("--tape", type=str, default=None, help="input to the first machine")
+    pipe_parser.add_argument("--max-steps", type=int, default=DEFAULT_MAX_STEPS, help="for each machine")
+    pipe_parser.add_argument(
+        "--max-configurations", type=int, default=DEFAULT_MAX_CONFIGURATIONS,
+        help="nondeterministic machines only: stop after visiting this many configurations",
+    )
+    pipe_parser.set_defaults(func=pipe_command)
+
+    turmite_parser = subparsers.add_parser("turmite", help="run Langton's ant or another 2D turmite")
+    turmite_parser.add_argument("--rule", type=str, default="RL", help="e.g. RL, LLRR, or a turmite table")
+    turmite_parser.add_argument("--steps", type=int, default=11_000, help="steps to run before printing the grid")
+    turmite_parser.add_argument("--interactive", action="store_true", help="step through eras in the terminal")
+    turmite_parser.add_argument("--era", type=int, default=100, help="steps per era when interactive")
+    turmite_parser.add_argument("--width", type=int, default=79, help="columns shown when interactive")
+    turmite_parser.add_argument("--height", type=int, default=40, help="rows shown when interactive")
+    turmite_parser.add_argument("--delay", type=float, default=0.1, help="seconds between auto-play eras")
+    turmite_parser.set_defaults(func=turmite_command)
+
+    args = parser.parse_args()
+    try:
+        args.func(args)
+    except MachineError as e:
+        parser.exit(1, "".join(f"error: {line}\n" for line in str(e).split("\n")))
+
+
+if __name__ == "__main__":
+    main()
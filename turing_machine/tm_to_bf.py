@@ -0,0 +1,370 @@
+"""
+# Turing Machine to Brainfuck Compiler
+
+The other half of the proof that the two are equivalent (`bf_to_tm.py`
+is the first): we translate a deterministic, single-tape Turing machine
+into a Brainfuck program that runs it and prints where it stopped.
+
+    State: done
+    Tape: 10000
+
+The input tape is fixed when compiling (`--tape`), since Brainfuck's
+input conventions (what `,` gives at the end of input) differ between
+interpreters and the program does not read any input.
+
+## Layout
+
+Each Turing machine cell is a record of cells in BF memory. A record
+holds the symbol and state one-hot (one cell per symbol and per state,
+with the blank as no symbol at all, so fresh memory is blank tape),
+a few flags, and scratch cells. The data pointer stays on the head's
+record, which is the only one with a state.
+
+BF memory only goes one way and the tape goes both ways, so we fold the
+tape in two: cell `h` of the right half is record `2h`, and cell `-h` of
+the left half is record `2h - 1`. Moving the head is then two records
+one way or the other, except across cell 0, and which of these it is
+depends on the half the head is in, known only as the program runs.
+A BF loop cannot end in one of several places, so each move marks the
+records from two before the head up to its destination, goes back two
+records, and scans forward to the first record without a mark.
+
+Each step copies the state and symbol into scratch cells and runs the
+one transition whose state and symbol cells are both set, which writes
+the new symbol, puts the next state on the destination record and marks
+the way there. A step with no transition leaves everything in place and
+stops the machine, like reaching a final state.
+
+When the machine stops we print the state, then the non-blank part of
+the tape, as `tm.py run` does. The far ends of both halves are trimmed
+of blanks (and the blanks next to cell -1 too, if that leaves the right
+half empty); the left half is printed from its far end back to cell -1,
+and the right half from cell 0 onwards. Symbols are separated by spaces
+if any symbol in the alphabet is longer than one character.
+
+
+## Usage
+
+    python3 tm.py compile machines/binary_increment.tm --target bf --tape "1111" --output increment.bf
+    python3 ../esolangs/subleq.py run increment.bf
+"""
+
+from typing import Callable, Dict, List
+
+from machine import LEFT, RIGHT, MachineError, TuringMachine, initial_tapes
+
+
+# Cells of a record, before the symbol and state cells
+RUN: int = 0        # the head's record, while the machine runs
+PATH: int = 1       # marks the way to the head's next record
+VISITED: int = 2    # the head has been here (or it holds input)
+SIDE: int = 3       # the record is in the left half of the tape
+ORIGIN: int = 4     # the record is cell 0 or cell -1
+PAD: int = 5        # the first padding record, before cell 0
+FLAG: int = 6       # something has been printed
+SKIP: int = 7       # a blank at the end of the tape, by cell -1
+SCRATCH: List[int] = [8, 9, 10, 11]
+TEMPORARY: int = 12
+PRINT: int = 13     # holds each character as it is printed
+READ_BLANK: int = 14
+NUM_FIELDS: int = 15
+
+# Two records of padding come before cell 0, so that the scan for the
+# head's next record can always start two records back
+PADDING: int = 2
+
+
+def comment(text: str) -> str:
+    """BF ignores everything but its commands, so these must go."""
+    return "".join(c for c in text if c not in "+-<>[].,")
+
+
+class Emitter:
+    """Writes BF, keeping track of the data pointer relative to the start of the current record."""
+
+    def __init__(self, width: int):
+        self.width = width
+        self.code: List[str] = []
+        self.position = -PADDING * width
+
+    def note(self, text: str):
+        self.code.append(f"\n{comment(text)}\n")
+
+    def go(self, cell: int):
+        offset = cell - self.position
+        self.code.append(">" * offset if offset > 0 else "<" * -offset)
+        self.position = cell
+
+    def add(self, cell: int, amount: int):
+        self.go(cell)
+        self.code.append("+" * amount if amount > 0 else "-" * -amount)
+
+    def clear(self, cell: int):
+        self.go(cell)
+        self.code.append("[-]")
+
+    def loop(self, cell: int, body: Callable[[], None]):
+        """A loop that ends where it began, running once if `cell` is 1 (which it clears)."""
+        self.go(cell)
+        self.code.append("[")
+        self.add(cell, -1)
+        body()
+        self.go(cell)
+        self.code.append("]")
+
+    def walk(self, cell: int, records: int, body: Callable[[], None], then: Callable[[], None] = lambda: None):
+        """While `cell` is set, run the body and move `records` records on (and run `then` there)."""
+        self.go(cell)
+        self.code.append("[")
+        body()
+        self.go(records * self.width + cell)
+        self.position = cell
+        then()
+        self.go(cell)
+        self.code.append("]")
+
+    def scan(self, cell: int, records: int, decrement: bool = False):
+        """Move `records` records at a time until `cell` is 0 (clearing the marks on the way)."""
+        self.go(cell)
+        step = ">" * (records * self.width) if records > 0 else "<" * (-records * self.width)
+        self.code.append("[" + ("-" if decrement else "") + step + "]")
+        self.position = cell % self.width
+
+    def transfer(self, source: int, amounts: Dict[int, int]):
+        """Add `source` times each amount to its cell, leaving `source` 0."""
+        self.go(source)
+        self.code.append("[-")
+        for target, amount in amounts.items():
+            self.add(target, amount)
+        self.go(source)
+        self.code.append("]")
+
+    def move(self, source: int, *targets: int):
+        self.transfer(source, {target: 1 for target in targets})
+
+    def copy(self, source: int, target: int, sign: int = 1):
+        """Add `source` to `target` (or subtract it), using the temporary cell."""
+        self.transfer(source, {target: sign, TEMPORARY: 1})
+        self.move(TEMPORARY, source)
+
+    def branch(self, flag: int, then: Callable[[], None], otherwise: Callable[[], None], depth: int):
+        """Run one of the two, depending on whether `flag` is set (0 or 1, and kept)."""
+        on, off = SCRATCH[2 * depth], SCRATCH[2 * depth + 1]
+        self.move(flag, on, off)
+        self.move(off, flag)
+        self.add(off, 1)
+        self.loop(on, lambda: (self.add(off, -1), then()))
+        self.loop(off, otherwise)
+
+    def text(self, s: str):
+        value = 0
+        for c in s.encode():
+            self.add(PRINT, c - value)
+            self.code.append(".")
+            value = c
+        if value:
+            self.clear(PRINT)
+
+
+class CompileError(Exception):
+    """The machine cannot be translated."""
+
+
+def compile_machine(machine: TuringMachine, tape: List[str]) -> str:
+    """Return the text of a BF program that runs the machine on the tape."""
+    if machine.nondeterministic:
+        raise CompileError("only deterministic machines can be compiled")
+    if machine.num_tapes != 1:
+        raise CompileError("only single-tape machines can be compiled")
+    try:
+        (cells,) = initial_tapes(machine, [tape])
+    except MachineError as e:
+        raise CompileError(str(e))
+
+    states = sorted(machine.states)
+    symbols = sorted(machine.alphabet - {machine.blank})
+    symbol_cell: Dict[str, int] = {s: NUM_FIELDS + i for i, s in enumerate(symbols)}
+    read_cell: Dict[str, int] = {s: NUM_FIELDS + len(symbols) + i for i, s in enumerate(symbols)}
+    read_cell[machine.blank] = READ_BLANK
+    state_cell: Dict[str, int] = {q: NUM_FIELDS + 2 * len(symbols) + i for i, q in enumerate(states)}
+    current_cell: Dict[str, int] = {q: NUM_FIELDS + 2 * len(symbols) + len(states) + i for i, q in enumerate(states)}
+    width = NUM_FIELDS + 2 * len(symbols) + 2 * len(states)
+    separator = " " if any(len(s) > 1 for s in machine.alphabet) else ""
+    bf = Emitter(width)
+
+    def record(index: int) -> int:
+        return index * width
+
+    bf.note("Generated by tm_to_bf.py")
+    bf.add(record(-PADDING) + PAD, 1)
+    bf.add(record(0) + ORIGIN, 1)
+    bf.add(record(1) + ORIGIN, 1)
+    bf.add(record(1) + SIDE, 1)
+    for h in range(max(len(tape), 1)):
+        bf.add(record(2 * h) + VISITED, 1)
+        if h in cells:
+            bf.add(record(2 * h) + symbol_cell[cells[h]], 1)
+    bf.add(state_cell[machine.start], 1)
+    if machine.start not in machine.final_states:
+        bf.add(RUN, 1)
+
+    def arrive(offset: int, left_half: bool, state: str):
+        """Put the next state on the record `offset` records on, and mark the way there."""
+        destination = record(offset)
+        bf.add(destination + state_cell[state], 1)
+        if state not in machine.final_states:
+            bf.add(destination + RUN, 1)
+        if offset:
+            bf.clear(destination + VISITED)
+            bf.add(destination + VISITED, 1)
+            bf.clear(destination + SIDE)
+            bf.add(destination + SIDE, int(left_half))
+        # The two records before the head are marked already, for staying put
+        for index in range(0, offset):
+            bf.add(record(index) + PATH, 1)
+        for index in range(offset, 0):
+            bf.add(record(index) + PATH, -1)
+
+    def transition(state: str, read: str):
+        ((write,), (move,), next_state) = machine.transitions[(state, (read,))][0]
+        bf.note(f"{state} reads {read}: write {write}, move {move}, go to {next_state}")
+        bf.add(state_cell[state], -1)
+        if write != machine.blank:
+            bf.add(symbol_cell[write], 1)
+        # Away from the fold, a move is two records: out (up) or back in (down)
+        if move == RIGHT:
+            bf.branch(
+                SIDE,
+                lambda: bf.branch(ORIGIN, lambda: arrive(-1, False, next_state),
+                                  lambda: arrive(-2, True, next_state), 1),
+                lambda: arrive(2, False, next_state),
+                0,
+            )
+        elif move == LEFT:
+            bf.branch(
+                SIDE,
+                lambda: arrive(2, True, next_state),
+                lambda: bf.branch(ORIGIN, lambda: arrive(1, True, next_state),
+                                  lambda: arrive(-2, False, next_state), 1),
+                0,
+            )
+        else:
+            arrive(0, False, next_state)
+
+    def dispatch(state: str):
+        # Put the state back, for a transition to take away
+        bf.add(state_cell[state], 1)
+        for read in [machine.blank] + symbols:
+            if (state, (read,)) in machine.transitions:
+                bf.loop(read_cell[read], lambda: transition(state, read))
+
+    def step():
+        bf.add(RUN, -1)
+        bf.add(record(-2) + PATH, 1)
+        bf.add(record(-1) + PATH, 1)
+        bf.add(READ_BLANK, 1)
+        for s in symbols:
+            bf.move(symbol_cell[s], read_cell[s])
+            bf.copy(read_cell[s], READ_BLANK, sign=-1)
+        for q in states:
+            bf.move(state_cell[q], current_cell[q])
+        for q in states:
+            if q not in machine.final_states:
+                bf.loop(current_cell[q], lambda: dispatch(q))
+        # Without a transition, the symbol goes back and the machine stops
+        bf.clear(READ_BLANK)
+        for s in symbols:
+            bf.move(read_cell[s], symbol_cell[s])
+        bf.scan(record(-2) + PATH, 1, decrement=True)
+
+    bf.note("Run")
+    bf.walk(RUN, 0, step)
+
+    bf.note("Print the state")
+    bf.text("State: ")
+    for q in states:
+        bf.loop(state_cell[q], lambda: (bf.text(q), bf.add(SCRATCH[0], 1)))
+        bf.move(SCRATCH[0], state_cell[q])
+    bf.text("\n")
+
+    def to_fold():
+        """Go back along the half to the padding, and then to the record before cell 0."""
+        bf.scan(VISITED, -2)
+        bf.scan(PAD, 1)
+
+    def to_far_end(first: int):
+        """From the record before cell 0, go to the last visited record of the half from `first`."""
+        bf.go(record(first + 1) + VISITED)
+        bf.position = VISITED
+        bf.scan(VISITED, 2)
+        bf.go(record(-2) + VISITED)
+        bf.position = VISITED
+
+    def blank_end():
+        """Whether the record is visited and blank: the end of a half that can go."""
+        bf.copy(VISITED, SCRATCH[0])
+        for s in symbols:
+            bf.copy(symbol_cell[s], SCRATCH[0], sign=-1)
+
+    def trim():
+        """Forget the blank records at the far end of the half."""
+        blank_end()
+        bf.walk(SCRATCH[0], -2, lambda: (bf.add(SCRATCH[0], -1), bf.add(VISITED, -1)), blank_end)
+        to_fold()
+
+    def trim_near():
+        """Skip the blank records by the fold, which end the tape when the right half is empty."""
+        bf.go(record(2) + VISITED)
+        bf.position = VISITED
+        blank_end()
+        bf.walk(SCRATCH[0], 2, lambda: (bf.add(SCRATCH[0], -1), bf.add(SKIP, 1)), blank_end)
+        to_fold()
+
+    def separate():
+        if separator:
+            bf.copy(FLAG, SCRATCH[0])
+            bf.loop(SCRATCH[0], lambda: bf.text(separator))
+
+    def print_symbol():
+        separate()
+        for s in symbols:
+            bf.copy(symbol_cell[s], SCRATCH[2])
+            bf.loop(SCRATCH[2], lambda: bf.text(s))
+        bf.clear(FLAG)
+        bf.add(FLAG, 1)
+
+    def print_blank():
+        # Only between symbols: a blank before them is trimmed
+        bf.copy(FLAG, SCRATCH[2])
+        bf.loop(SCRATCH[2], lambda: (separate(), bf.text(machine.blank)))
+
+    def print_record():
+        nonblank, blank = SCRATCH[0], SCRATCH[1]
+        bf.add(blank, 1)
+        bf.copy(SKIP, blank, sign=-1)
+        for s in symbols:
+            bf.copy(symbol_cell[s], nonblank)
+            bf.copy(symbol_cell[s], blank, sign=-1)
+        bf.loop(nonblank, print_symbol)
+        bf.loop(blank, print_blank)
+
+    bf.note("Trim the tape")
+    to_fold()
+    to_far_end(0)
+    trim()
+    to_far_end(1)
+    trim()
+    bf.add(SCRATCH[1], 1)
+    bf.copy(record(1) + VISITED, SCRATCH[1], sign=-1)
+    bf.loop(SCRATCH[1], trim_near)
+
+    bf.note("Print the tape")
+    bf.text("Tape: ")
+    to_far_end(1)
+    bf.walk(VISITED, -2, lambda: (print_record(), bf.move(FLAG, record(-2) + FLAG)))
+    bf.move(FLAG, record(1) + FLAG)
+    bf.go(record(1) + VISITED)
+    bf.position = VISITED
+    bf.walk(VISITED, 2, lambda: (print_record(), bf.move(FLAG, record(2) + FLAG)))
+    bf.text("\n")
+    return "".join(bf.code).strip() + "\n"
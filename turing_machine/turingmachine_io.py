@@ -0,0 +1,148 @@
+"""
+# turingmachine.io Importer
+
+[turingmachine.io](https://turingmachine.io) is a popular online
+simulator with a large collection of published machines, written in
+YAML like this:
+
+    input: '1011'
+    blank: ' '
+    start state: right
+    table:
+      right:
+        [1, 0]: R
+        ' ': {L: carry}
+      carry:
+        1: {write: 0, L}
+        [0, ' ']: {write: 1, L: done}
+      done:
+
+Each state maps a symbol (or a list of symbols) to an action. The action
+is either a bare move, which keeps the symbol and the state, or a
+mapping with an optional `write` and a move, whose value is the next
+state (or empty to stay in the same state). The machine halts when
+there is no action for the symbol it reads.
+
+We translate these into our own machines. As our format separates
+symbols with spaces, a space symbol becomes `_` (or, if `_` is taken, the
+first of `.`, `~`, `^` that is free), and spaces in state names become
+underscores. States with no actions at all become halting states.
+"""
+
+from typing import Any, Dict, List, Optional, Tuple
+
+import yaml
+
+from machine import LEFT, RIGHT, Key, MachineError, Transition, TuringMachine, check_machine
+
+
+SPACE_REPLACEMENTS: str = "_.~^"
+
+
+class _Loader(yaml.SafeLoader):
+    """A YAML loader that allows lists of symbols as mapping keys."""
+
+    def construct_mapping(self, node, deep=False):
+        mapping = {}
+        for key_node, value_node in node.value:
+            key = self.construct_object(key_node, deep=True)
+            if isinstance(key, list):
+                key = tuple(key)
+            mapping[key] = self.construct_object(value_node, deep=deep)
+        return mapping
+
+
+_Loader.add_constructor(yaml.resolver.BaseResolver.DEFAULT_MAPPING_TAG, _Loader.construct_mapping)
+
+
+def _symbols(key: Any) -> List[str]:
+    keys = list(key) if isinstance(key, tuple) else [key]
+    for k in keys:
+        if isinstance(k, bool):
+            raise MachineError(f"symbol {k!r} was read by YAML as a boolean; quote it")
+    return [str(k) for k in keys]
+
+
+def parse_turingmachine_io(text: str) -> Tuple[TuringMachine, Optional[List[str]]]:
+    """Return the machine and its example input (if it has one)."""
+    try:
+        document = yaml.load(text, Loader=_Loader)
+    except yaml.YAMLError as e:
+        raise MachineError(f"invalid YAML: {e}")
+    if not isinstance(document, dict):
+        raise MachineError("expected a YAML mapping")
+    for key in ("blank", "start state", "table"):
+        if key not in document:
+            raise MachineError(f"missing key '{key}'")
+    table = document["table"]
+    if not isinstance(table, dict):
+        raise MachineError("'table' must map states to their actions")
+
+    symbols = {str(document["blank"])}
+    symbols |= set(str(document.get("input", "")))
+    for actions in table.values():
+        for key, action in (actions or {}).items():
+            symbols |= set(_symbols(key))
+            if isinstance(action, dict) and "write" in action:
+                symbols.add(str(action["write"]))
+    for c in symbols:
+        if len(c) != 1:
+            raise MachineError(f"symbol '{c}' is not a single character")
+    replacement = next((c for c in SPACE_REPLACEMENTS if c not in symbols), None)
+    if " " in symbols and replacement is None:
+        raise MachineError(f"cannot rename the space symbol, as {SPACE_REPLACEMENTS} are all in use")
+
+    def symbol(c: Any) -> str:
+        return replacement if str(c) == " " else str(c)
+
+    def state(name: Any) -> str:
+        return str(name).replace(" ", "_")
+
+    transitions: Dict[Key, List[Transition]] = {}
+    halt = set()
+    for name, actions in table.items():
+        if not actions:
+            halt.add(state(name))
+            continue
+        for key, action in actions.items():
+            if action in (LEFT, RIGHT):
+                write, move, next_state = None, action, name
+            elif isinstance(action, dict):
+                moves = [m for m in (LEFT, RIGHT) if m in action]
+                if len(moves) != 1 or set(action) - {"write", LEFT, RIGHT}:
+                    raise MachineError(f"state '{name}': expected one of L or R, and optionally write")
+                move = moves[0]
+                write = action.get("write")
+                next_state = name if action[move] is None else action[move]
+            else:
+                raise MachineError(f"state '{name}': unexpected action {action!r}")
+            for read in _symbols(key):
+                k = (state(name), (symbol(read),))
+                if k in transitions:
+                    raise MachineError(f"state '{name}': duplicate action for {read!r}")
+                transitions[k] = [(
+                    (symbol(read if write is None else write),),
+                    (move,),
+                    state(next_state),
+                )]
+
+    machine = TuringMachine(
+        states={state(name) for name in table},
+        alphabet={symbol(c) for c in symbols},
+        blank=symbol(document["blank"]),
+        start=state(document["start state"]),
+        accept=set(),
+        reject=set(),
+        halt=halt,
+        transitions=transitions,
+    )
+    errors = check_machine(machine)
+    if errors:
+        raise MachineError("\n".join(errors))
+    tape = [symbol(c) for c in str(document["input"])] if "input" in document else None
+    return machine, tape
+
+
+def load_turingmachine_io(path: str) -> Tuple[TuringMachine, Optional[List[str]]]:
+    with open(path) as f:
+        return parse_turingmachine_io(f.read())
@@ -0,0 +1,203 @@
+"""
+# Turmites
+
+A turmite is a Turing machine whose tape is a 2D grid. Every cell has a
+colour (0 to begin with), and the turmite has a state, a position, and a
+direction. At every step it looks up (state, colour), writes a new
+colour, turns, moves forward one cell, and enters the next state.
+
+## Langton's Ant
+
+The best-known turmite has one state and two colours: on colour 0 it
+turns right, on colour 1 it turns left, and it always flips the colour.
+After about 10,000 steps of chaos it starts building a "highway" that
+runs off forever. Generalized ants take a rule string with one letter
+per colour: on colour i the ant turns by the i-th letter and paints the
+cell colour i + 1 (wrapping around). Langton's ant is `RL`, and e.g.
+`LLRR` grows a symmetric blob. The turns are:
+
+    L    left
+    R    right
+    N    no turn
+    U    U-turn
+
+## Turmites With States
+
+Multi-state turmites use the usual notation: a list with one entry per
+state, each with one `{write, turn, next}` triple per colour. Turns are
+1 (no turn), 2 (right), 4 (U-turn), and 8 (left), and states are
+numbered from 0. Langton's ant is `{{{1,2,0},{0,8,0}}}`, and
+`{{{1,8,1},{1,8,1}},{{1,2,1},{0,1,0}}}` grows in a square spiral.
+
+## Usage
+
+    python3 tm.py turmite --rule RL --steps 11000
+    python3 tm.py turmite --rule "{{{1,8,1},{1,8,1}},{{1,2,1},{0,1,0}}}" --interactive
+
+At the interactive prompt, press Enter to run one era (`--era` steps), type
+a number to take that many steps, `a` to auto-play eras (Ctrl-C pauses),
+or `q` to quit.
+"""
+
+import re
+import time
+from typing import Dict, List, Optional, Tuple
+
+from visualize import CLEAR_SCREEN
+
+
+# Headings, clockwise from up. Rows grow downwards, as on the screen.
+DIRECTIONS: List[Tuple[int, int]] = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+ARROWS: str = "^>v<"
+ANT_TURNS: Dict[str, int] = {"N": 0, "R": 1, "U": 2, "L": 3}
+TABLE_TURNS: Dict[int, int] = {1: 0, 2: 1, 4: 2, 8: 3}
+COLOURS: str = " #o+*=%@&$"
+
+Rule = Tuple[int, int, int]   # (colour to write, turn clockwise by quarters, next state)
+
+
+class Turmite:
+    def __init__(self, table: Dict[Tuple[int, int], Rule], num_colours: int):
+        self.table = table
+        self.num_colours = num_colours
+        self.grid: Dict[Tuple[int, int], int] = {}
+        self.x, self.y = 0, 0
+        self.direction = 0
+        self.state = 0
+        self.steps = 0
+
+    def step(self):
+        colour = self.grid.get((self.x, self.y), 0)
+        write, turn, self.state = self.table[(self.state, colour)]
+        if write == 0:
+            self.grid.pop((self.x, self.y), None)
+        else:
+            self.grid[(self.x, self.y)] = write
+        self.direction = (self.direction + turn) % 4
+        dx, dy = DIRECTIONS[self.direction]
+        self.x, self.y = self.x + dx, self.y + dy
+        self.steps += 1
+
+    def run(self, num_steps: int):
+        for _ in range(num_steps):
+            self.step()
+
+
+def _parse_braces(text: str):
+    """Parse nested `{...}` lists of integers into Python lists."""
+    tokens = re.findall(r"\{|\}|,|\d+|\S", text)
+    position = 0
+
+    def parse():
+        nonlocal position
+        if position >= len(tokens):
+            raise ValueError("unexpected end of turmite table")
+        token = tokens[position]
+        position += 1
+        if token.isdigit():
+            return int(token)
+        if token != "{":
+            raise ValueError(f"unexpected '{token}' in turmite table")
+        items = []
+        while position < len(tokens) and tokens[position] != "}":
+            items.append(parse())
+            if position < len(tokens) and tokens[position] == ",":
+                position += 1
+        position += 1
+        return items
+
+    value = parse()
+    if position != len(tokens):
+        raise ValueError("unexpected text after the turmite table")
+    return value
+
+
+def parse_rule(rule: str) -> Turmite:
+    """Parse either an ant rule string (e.g. `RL`) or a turmite table."""
+    rule = rule.strip()
+    if rule and all(c in ANT_TURNS for c in rule.upper()):
+        n = len(rule)
+        table = {(0, c): ((c + 1) % n, ANT_TURNS[t], 0) for c, t in enumerate(rule.upper())}
+        return Turmite(table, n)
+
+    states = _parse_braces(rule)
+    if not isinstance(states, list) or not states or not all(isinstance(s, list) for s in states):
+        raise ValueError(f"'{rule}' is neither a rule string of L, R, N, U nor a turmite table")
+    table: Dict[Tuple[int, int], Rule] = {}
+    num_colours = None
+    for state, triples in enumerate(states):
+        if num_colours is None:
+            num_colours = len(triples)
+        elif len(triples) != num_colours:
+            raise ValueError(f"state {state} has {len(triples)} colours, but state 0 has {num_colours}")
+        for colour, triple in enumerate(triples):
+            if not isinstance(triple, list) or len(triple) != 3 or not all(isinstance(v, int) for v in triple):
+                raise ValueError(f"state {state}, colour {colour}: expected {{write, turn, next}}")
+            write, turn, next_state = triple
+            if turn not in TABLE_TURNS:
+                raise ValueError(f"state {state}, colour {colour}: turn must be 1, 2, 4, or 8")
+            table[(state, colour)] = (write, TABLE_TURNS[turn], next_state)
+    for (state, colour), (write, _, next_state) in table.items():
+        if write >= num_colours or next_state >= len(states):
+            raise ValueError(f"state {state}, colour {colour}: no such colour or state")
+    return Turmite(table, num_colours)
+
+
+def render(turmite: Turmite, width: Optional[int] = None, height: Optional[int] = None) -> str:
+    """Draw the grid, cropped to the painted cells (or a window around the turmite)."""
+    if width is None or height is None:
+        xs = [x for x, _ in turmite.grid] + [turmite.x]
+        ys = [y for _, y in turmite.grid] + [turmite.y]
+        left, right, top, bottom = min(xs), max(xs), min(ys), max(ys)
+    else:
+        left, top = turmite.x - width // 2, turmite.y - height // 2
+        right, bottom = left + width - 1, top + height - 1
+    symbols = COLOURS if turmite.num_colours <= len(COLOURS) else None
+    rows: List[str] = []
+    for y in range(top, bottom + 1):
+        row = []
+        for x in range(left, right + 1):
+            if (x, y) == (turmite.x, turmite.y):
+                row.append(ARROWS[turmite.direction])
+            else:
+                colour = turmite.grid.get((x, y), 0)
+                row.append(symbols[colour] if symbols else chr(ord("0") + colour))
+        rows.append("".join(row).rstrip())
+    return "\n".join(rows)
+
+
+def status_line(turmite: Turmite) -> str:
+    return f"Step {turmite.steps}    State: {turmite.state}    Painted: {len(turmite.grid)}"
+
+
+def interact(turmite: Turmite, era: int, width: int, height: int, delay: float):
+    def show():
+        print(CLEAR_SCREEN + status_line(turmite) + "\n" + render(turmite, width, height))
+
+    show()
+    while True:
+        try:
+            reply = input(f"[Enter] run {era} steps, [number] take that many steps, [a] auto-play, [q] quit\n>>> ")
+        except (EOFError, KeyboardInterrupt):
+            print()
+            break
+        reply = reply.strip()
+        if reply == "q":
+            break
+        elif reply == "a":
+            try:
+                while True:
+                    turmite.run(era)
+                    show()
+                    time.sleep(delay)
+            except KeyboardInterrupt:
+                pass
+            continue
+        elif reply.isdigit():
+            turmite.run(int(reply))
+        elif not reply:
+            turmite.run(era)
+        else:
+            print(f"unknown command '{reply}'")
+            continue
+        show()
@@ -0,0 +1,109 @@
+"""
+# Universal Turing Machine
+
+`machines/utm.tm` is a universal Turing machine: given the description
+of another machine M and an input w, it simulates M running on w. This
+module encodes M and w onto the universal machine's tapes, runs it, and
+decodes M's final state and tape afterwards.
+
+The record-holding small universal machines (e.g. Rogozhin's) simulate
+tag systems, and going from a Turing machine to a tag system and back
+makes for an enormous encoding. Instead, we use the textbook three-tape
+construction, described at the top of `machines/utm.tm`. It simulates
+deterministic single-tape machines with at most three symbols (the
+blank and two others). This loses no generality, because any machine
+can be rewritten to use a binary alphabet.
+"""
+
+import os
+from typing import Dict, List, Tuple
+
+from machine import (
+    MachineError,
+    Simulation,
+    TuringMachine,
+    load_machine,
+    ACCEPT,
+    HALT,
+    NO_TRANSITION,
+    REJECT,
+)
+
+
+UTM_PATH: str = os.path.join(os.path.dirname(os.path.abspath(__file__)), "machines", "utm.tm")
+UTM_BLANK_CODE: str = "b"   # How tape 1 writes M's blank
+UTM_SEPARATOR: str = "|"
+UTM_UNARY: str = "x"
+
+
+class Encoding:
+    """How M's states and symbols map onto the universal machine's."""
+
+    def __init__(self, machine: TuringMachine):
+        if machine.nondeterministic or machine.num_tapes != 1:
+            raise MachineError("the universal machine only simulates deterministic single-tape machines")
+        others = sorted(machine.alphabet - {machine.blank})
+        if len(others) > 2:
+            raise MachineError("the universal machine only simulates machines with at most 3 symbols")
+        # Keep 0 and 1 as they are if the machine already uses them
+        if not set(others) <= {"0", "1"}:
+            self.symbols: Dict[str, str] = {c: code for c, code in zip(others, "01")}
+        else:
+            self.symbols = {c: c for c in others}
+        self.symbols[machine.blank] = "_"
+        # The start state is 1 so that every state is a non-empty string of x's
+        names = [machine.start] + sorted(machine.states - {machine.start})
+        self.states: Dict[str, int] = {name: i + 1 for i, name in enumerate(names)}
+        self.state_names: Dict[int, str] = {i: name for name, i in self.states.items()}
+        self.symbol_names: Dict[str, str] = {code: c for c, code in self.symbols.items()}
+
+    def describe(self, machine: TuringMachine) -> List[str]:
+        """Tape 1: every transition out of a non-final state."""
+        description: List[str] = []
+        for (state, (read,)), [((write,), (move,), next_state)] in sorted(machine.transitions.items()):
+            if state in machine.final_states:
+                continue    # M would have stopped already
+            description += [UTM_SEPARATOR] + [UTM_UNARY] * self.states[state]
+            description += [
+                self.symbols[read].replace("_", UTM_BLANK_CODE),
+                self.symbols[write].replace("_", UTM_BLANK_CODE),
+                move,
+            ]
+            description += [UTM_UNARY] * self.states[next_state]
+        return description
+
+
+def run_universal(
+    machine: TuringMachine, tape: List[str], max_steps: int
+) -> Tuple[str, str, Dict[int, str], int]:
+    """Simulate M on the universal machine.
+
+    Return M's status (accept, reject, halt, or no transition, judged by
+    its final state), its final state and tape, and the number of steps
+    the universal machine took.
+    """
+    utm = load_machine(UTM_PATH)
+    encoding = Encoding(machine)
+    for c in tape:
+        if c not in machine.alphabet:
+            raise MachineError(f"input symbol '{c}' is not in the alphabet")
+    sim = Simulation(utm, [
+        encoding.describe(machine),
+        [encoding.symbols[c] for c in tape],
+        [UTM_UNARY] * encoding.states[machine.start],
+    ])
+    status = sim.run(max_steps)
+    if status != HALT:
+        return status, "", {}, sim.steps
+
+    state = encoding.state_names[len(sim.tapes[2])]
+    if state in machine.accept:
+        status = ACCEPT
+    elif state in machine.reject:
+        status = REJECT
+    elif state in machine.halt:
+        status = HALT
+    else:
+        status = NO_TRANSITION
+    final_tape = {i: encoding.symbol_names[c] for i, c in sim.tapes[1].items()}
+    return status, state, final_tape, sim.steps
@@ -0,0 +1,101 @@
+"""
+# Turing Machine Visualizer
+
+Watch a machine run in the terminal. Every frame shows a window of each
+tape centred on its head, the current state, and the rule that was just
+applied:
+
+    Step 3    State: carry
+    Rule: (right, _) -> _ L carry
+
+    Tape:  _  _  1  0 [1] _  _
+                       ^
+
+At the prompt, press Enter to take one step, type a number to take that
+many steps, `a` to auto-play (Ctrl-C pauses it again), or `q` to quit.
+"""
+
+import time
+from typing import List, Optional, Tuple
+
+from machine import Key, MachineError, Simulation, Transition, format_key
+
+
+CLEAR_SCREEN: str = "\x1b[H\x1b[2J"
+HELP: str = "[Enter] step, [number] take that many steps, [a] auto-play, [q] quit"
+
+
+def render_tape(sim: Simulation, i: int, window: int) -> Tuple[str, str]:
+    """Return the cells within `window` of the head, and a caret under the head."""
+    blank = sim.machine.blank
+    width = max(len(c) for c in sim.machine.alphabet) + 2
+    cells: List[str] = []
+    for position in range(sim.heads[i] - window, sim.heads[i] + window + 1):
+        c = sim.tapes[i].get(position, blank)
+        cells.append(f"[{c}]" if position == sim.heads[i] else f" {c} ")
+    tape = "".join(cell.center(width) for cell in cells).rstrip()
+    caret = " " * (width * window) + "^".center(width)
+    return tape, caret.rstrip()
+
+
+def render(sim: Simulation, rule: Optional[Tuple[Key, Transition]], window: int) -> str:
+    lines = [f"Step {sim.steps}    State: {sim.state}"]
+    if rule is None:
+        lines.append("Rule: (none yet)")
+    else:
+        key, (writes, moves, next_state) = rule
+        lines.append(f"Rule: {format_key(key)} -> {' '.join(writes)} {' '.join(moves)} {next_state}")
+    if sim.status is not None:
+        lines.append(f"Stopped: {sim.status}")
+    lines.append("")
+    for i in range(sim.machine.num_tapes):
+        label = "Tape:" if sim.machine.num_tapes == 1 else f"Tape {i + 1}:"
+        tape, caret = render_tape(sim, i, window)
+        lines.append(f"{label:<8}{tape}")
+        lines.append(f"{'':<8}{caret}")
+    return "\n".join(lines)
+
+
+def visualize(sim: Simulation, window: int = 8, delay: float = 0.2, auto: bool = False):
+    if sim.machine.nondeterministic:
+        raise MachineError("nondeterministic machines cannot be visualized")
+    rule: Optional[Tuple[Key, Transition]] = None
+
+    def show():
+        print(CLEAR_SCREEN + render(sim, rule, window))
+
+    show()
+    while True:
+        if auto:
+            try:
+                while sim.status is None:
+                    time.sleep(delay)
+                    rule = sim.step() or rule
+                    show()
+            except KeyboardInterrupt:
+                pass
+            auto = False
+
+        if sim.status is not None:
+            break
+        try:
+            reply = input(f"{HELP}\n>>> ").strip()
+        except (EOFError, KeyboardInterrupt):
+            print()
+            break
+        if reply == "q":
+            break
+        elif reply == "a":
+            auto = True
+            continue
+        elif reply.isdigit():
+            num_steps = int(reply)
+        elif not reply:
+            num_steps = 1
+        else:
+            print(f"unknown command '{reply}'")
+            continue
+        for _ in range(num_steps):
+            rule = sim.step() or rule
+        show()
+    print(f"Result: {sim.status or 'stopped by user'}")
@@ -0,0 +1,221 @@
+"""
+# The 24 Game
+
+Four numbers, usually dealt as cards from 1 to 13, to be made into 24
+with +, -, * and /, each number used exactly once. Fractions and
+negative numbers are allowed along the way, which is what makes some
+hands hard: 1 5 5 5 is 5 * (5 - 1/5), and 3 3 8 8 is 8 / (3 - 8/3).
+Any count of numbers and any target work the same way.
+
+All the arithmetic is exact, on the fractions of the shared expression
+module (`expression/expression.py`): in floating point, 8 / (3 - 8/3)
+comes out as 23.999999999999996, and a solver comparing with a tolerance
+instead would let through values that are only close.
+
+
+## Searching
+
+Take any two of the numbers left, replace them with what one of the six
+operations makes of them (a + b, a * b, a - b, b - a, a / b and b / a,
+without dividing by 0), and carry on until one number is left. Each
+number carries the normal form (below) of the expression that made it,
+and a list of forms already searched from, in any order, is not searched
+again.
+
+
+## Distinct Solutions
+
+Most solutions come in crowds that differ only in trivial ways, so each
+expression is also kept in a normal form, and only one expression of
+each form is printed:
+
+    order       a + b and b + a, a * b and b * a, are the same
+    grouping    (a + b) + c and a + (b + c) are the same, and so are
+                a - (b - c) and a - b + c, as a sum of the terms a and
+                c less the term b; products and quotients likewise
+    identity    adding 0 is subtracting it, and multiplying by 1 is
+                dividing by it
+
+The form is what is printed, written out with its numbers first,
+smallest first, and the terms taken away last: 4 * (3 + 2 + 1), from
+1 2 3 4, is printed as 4*(1 + 2 + 3). Nothing deeper is merged, so
+a * (b + c) and a * b + a * c are two solutions.
+
+
+## Usage
+
+    python3 twenty_four.py solve 1 2 3 4
+    python3 twenty_four.py solve 1 5 5 5
+    python3 twenty_four.py solve 3 3 8 8 --stats
+    python3 twenty_four.py solve 2 3 5 7 11 --target 100 --limit 10
+    python3 twenty_four.py hands
+    python3 twenty_four.py hands --max 8 --unique
+"""
+
+import argparse
+import itertools
+import os
+import sys
+from fractions import Fraction
+from typing import Dict, List, Set, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from expression.expression import Binary, Node, Number, apply, to_string
+
+
+# A normal form: ("n", value) for a number, ("+", terms added, terms subtracted) for a sum, and
+# ("*", factors multiplied, factors divided by) for a product, each list of terms sorted
+Form = tuple
+Item = Tuple[Fraction, Form]    # a value, and the normal form of how it was made
+KINDS: Dict[str, Tuple[str, bool]] = {"+": ("+", False), "-": ("+", True), "*": ("*", False), "/": ("*", True)}
+IDENTITY: Dict[str, Form] = {"+": ("n", Fraction(0)), "*": ("n", Fraction(1))}
+
+
+class Statistics:
+    def __init__(self):
+        self.states = 0
+
+    def __str__(self) -> str:
+        return f"{self.states} lists of numbers searched"
+
+
+def terms(form: Form, kind: str) -> Tuple[tuple, tuple]:
+    """The form as the terms on each side of a sum or product of the given kind."""
+    return (form[1], form[2]) if form[0] == kind else ((form,), ())
+
+
+def combine(operator: str, a: Form, b: Form) -> Form:
+    kind, inverse = KINDS[operator]
+    (a_up, a_down), (b_up, b_down) = terms(a, kind), terms(b, kind)
+    up, down = (a_up + b_down, a_down + b_up) if inverse else (a_up + b_up, a_down + b_down)
+    identity = IDENTITY[kind]
+    moved = tuple(term for term in down if term == identity)
+    down = tuple(term for term in down if term != identity)
+    return kind, tuple(sorted(up + moved)), tuple(sorted(down))
+
+
+def made(a: Item, b: Item) -> List[Item]:
+    """What the six operations make of a and b."""
+    found = []
+    for operator, (x, y) in itertools.product("+-*/", ((a, b), (b, a))):
+        if operator in "+*" and x is b or operator == "/" and y[0] == 0:
+            continue
+        found.append((apply(operator, x[0], y[0]), combine(operator, x[1], y[1])))
+    return found
+
+
+def tree(form: Form) -> Node:
+    """The form written out as an expression: numbers first, smallest first, and the terms taken away last."""
+    if form[0] == "n":
+        return Number(form[1])
+    kind, up, down = form
+    ordered = [tree(term) for term in sorted(up, key=order)] + [tree(term) for term in sorted(down, key=order)]
+    node = ordered[0]
+    for i, term in enumerate(ordered[1:], start=1):
+        node = Binary(kind if i < len(up) else {"+": "-", "*": "/"}[kind], node, term)
+    return node
+
+
+def order(form: Form) -> Tuple[bool, Fraction, str]:
+    return (False, form[1], "") if form[0] == "n" else (True, Fraction(0), to_string(tree(form)))
+
+
+def solve(numbers: List[int], target: Fraction, statistics: Statistics) -> List[Node]:
+    """One expression for each distinct way to make the target, shortest first."""
+    seen: Set[Tuple[Form, ...]] = set()
+    solutions: Set[Form] = set()
+
+    def search(items: List[Item]):
+        key = tuple(sorted(form for _, form in items))
+        if key in seen:
+            return
+        seen.add(key)
+        statistics.states += 1
+        if len(items) == 1:
+            value, form = items[0]
+            if value == target:
+                solutions.add(form)
+            return
+        for i in range(len(items)):
+            for j in range(i + 1, len(items)):
+                rest = items[:i] + items[i + 1:j] + items[j + 1:]
+                for item in made(items[i], items[j]):
+                    search(rest + [item])
+
+    search([(Fraction(n), ("n", Fraction(n))) for n in numbers])
+    written = [tree(form) for form in solutions]
+    return sorted(written, key=lambda node: (len(to_string(node)), to_string(node)))
+
+
+def solvable(numbers: List[int], target: Fraction) -> bool:
+    """Whether the target can be made at all: the same search on values alone, which is much quicker."""
+    seen: Set[Tuple[Fraction, ...]] = set()
+
+    def search(values: List[Fraction]) -> bool:
+        if len(values) == 1:
+            return values[0] == target
+        key = tuple(sorted(values))
+        if key in seen:
+            return False
+        seen.add(key)
+        for i in range(len(values)):
+            for j in range(i + 1, len(values)):
+                rest = values[:i] + values[i + 1:j] + values[j + 1:]
+                x, y = values[i], values[j]
+                results = [x + y, x - y, y - x, x * y]
+                results += [x / y] if y != 0 else []
+                results += [y / x] if x != 0 else []
+                if any(search(rest + [result]) for result in results):
+                    return True
+        return False
+
+    return search([Fraction(n) for n in numbers])
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Make 24 (or any target) from numbers with + - * /")
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument("--target", type=int, default=24)
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", parents=[common], help="find the distinct ways to make the target")
+    solve_parser.add_argument("numbers", type=int, nargs="+")
+    solve_parser.add_argument("--limit", type=int, default=None, help="print only this many of the solutions")
+    solve_parser.add_argument("--stats", action="store_true", help="print the lists of numbers searched")
+    hands_parser = subparsers.add_parser("hands", parents=[common], help="count the hands that can make the target")
+    hands_parser.add_argument("--count", type=int, default=4, help="the numbers in a hand (default 4)")
+    hands_parser.add_argument("--max", type=int, default=13, help="the numbers run from 1 to this (default 13)")
+    hands_parser.add_argument("--unique", action="store_true", help="list the hands with only one distinct solution")
+    args = parser.parse_args()
+
+    target = Fraction(args.target)
+    if args.command == "solve":
+        if len(args.numbers) > 6:
+            parser.exit(1, "error: at most 6 numbers; more take too long to search\n")
+        statistics = Statistics()
+        solutions = solve(args.numbers, target, statistics)
+        for tree in solutions[:args.limit]:
+            print(f"{to_string(tree)} = {args.target}")
+        if not solutions:
+            print("No solution")
+        elif args.limit is not None and len(solutions) > args.limit:
+            print(f"... {len(solutions)} distinct solutions in all")
+        else:
+            print(f"{len(solutions)} distinct solution{'s' * (len(solutions) != 1)}")
+        if args.stats:
+            print(statistics)
+        if not solutions:
+            parser.exit(1)
+    else:
+        hands = list(itertools.combinations_with_replacement(range(1, args.max + 1), args.count))
+        made_it = [hand for hand in hands if solvable(list(hand), target)]
+        if args.unique:
+            for hand in made_it:
+                solutions = solve(list(hand), target, Statistics())
+                if len(solutions) == 1:
+                    print(f"{' '.join(map(str, hand))}: {to_string(solutions[0])}")
+        print(f"{len(made_it)} of the {len(hands)} hands of {args.count} numbers from 1 to {args.max} "
+              f"make {args.target}")
+
+
+if __name__ == "__main__":
+    main()
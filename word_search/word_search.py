@@ -0,0 +1,286 @@
+"""
+# Word Search
+
+A grid of letters hiding a list of words, each in a straight line in
+one of the eight directions: across, down, diagonally, and any of those
+backwards.
+
+
+## Solving
+
+Every cell and direction is a place a word could start, and a word is
+there if its letters run on from it. A grid has only 8 w h places, so
+trying each for each word is quick. A word found in more than one place
+is reported at all of them; a palindrome read both ways is one place.
+
+
+## Generating
+
+The words are placed longest first, each at a random place where it
+fits: on the grid, and every cell it crosses empty or holding the same
+letter, so words can share letters. When a word fits nowhere, the
+search takes back the word before and tries its next place.
+
+Without --size, the grid is the smallest square where the words'
+letters cover at most --density of the cells (0.6 by default), grown
+by one until they can all be placed. The cells left over are filled
+with random letters, from --filler, or by default the letters of the
+words themselves, as often as they appear there, which hides the words
+better than the whole alphabet would. A filler that spells one of the
+words a second time is drawn again.
+
+--straight keeps to the four forward directions (right, down, and
+the two diagonals going down), for an easier puzzle.
+
+
+## The Format
+
+The grid, one row a line (any spaces between the letters are ignored),
+then a blank line, then the words, separated by spaces, commas or new
+lines. Lines starting with # are comments.
+
+    CATS
+    ODOG
+    WEEK
+
+    cat dog cow
+
+
+## Usage
+
+    python3 word_search.py solve puzzles/animals.txt
+    python3 word_search.py solve puzzles/twice.txt
+    python3 word_search.py generate words/planets.txt --seed 1
+    python3 word_search.py generate words/planets.txt --seed 1 --density 0.9 --straight
+    python3 word_search.py generate words/planets.txt --seed 2 --size 14x8 --filler XYZ
+"""
+
+import argparse
+import math
+import os
+import random
+import sys
+from typing import Dict, List, NamedTuple, Optional, Sequence, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from grid.grid import ALL_DIRECTIONS, Grid, Position
+from rng.rng import add_seed_argument, make_rng
+
+
+NAMES: Dict[Position, str] = dict(zip(ALL_DIRECTIONS, ["N", "NE", "E", "SE", "S", "SW", "W", "NW"]))
+STRAIGHT: List[Position] = [(1, 0), (1, 1), (0, 1), (-1, 1)]
+EMPTY: str = "."
+# How many times generate draws the filler letters before giving up on a grid
+FILLER_TRIES: int = 100
+
+
+class Place(NamedTuple):
+    start: Position
+    direction: Position
+
+    def cells(self, length: int) -> List[Position]:
+        (x, y), (dx, dy) = self.start, self.direction
+        return [(x + i * dx, y + i * dy) for i in range(length)]
+
+    def __str__(self) -> str:
+        return f"({self.start[0] + 1},{self.start[1] + 1}) {NAMES[self.direction]}"
+
+
+def places(grid: Grid, word: str, directions: Sequence[Position] = ALL_DIRECTIONS) -> List[Place]:
+    """Every place that spells word in the grid; a palindrome read both ways is counted once."""
+    found = []
+    cells = set()
+    for start in grid.positions():
+        if grid[start] != word[0]:
+            continue
+        for direction in directions:
+            place = Place(start, direction)
+            line = place.cells(len(word))
+            if all(cell in grid and grid[cell] == letter for cell, letter in zip(line, word)):
+                if frozenset(line) not in cells:
+                    cells.add(frozenset(line))
+                    found.append(place)
+    return found
+
+
+def solve(grid: Grid, words: List[str]) -> Dict[str, List[Place]]:
+    return {word: places(grid, word) for word in words}
+
+
+################################################################################
+###                                Generating                                ###
+################################################################################
+
+
+class Generator:
+    def __init__(self, words: List[str], width: int, height: int, rng: random.Random,
+                 directions: Sequence[Position] = ALL_DIRECTIONS):
+        self.words = sorted(words, key=len, reverse=True)
+        self.grid = Grid.filled(width, height, EMPTY)
+        self.rng = rng
+        self.directions = directions
+
+    def fits(self, word: str, place: Place) -> bool:
+        return all(cell in self.grid and self.grid[cell] in (EMPTY, letter)
+                   for cell, letter in zip(place.cells(len(word)), word))
+
+    def place(self, index: int, placed: List[Place]) -> bool:
+        """Place the words from index on, after those already placed; False if they cannot all be."""
+        if index == len(self.words):
+            return True
+        word = self.words[index]
+        options = [Place(start, direction) for start in self.grid.positions() for direction in self.directions]
+        self.rng.shuffle(options)
+        for place in options:
+            if not self.fits(word, place):
+                continue
+            line = place.cells(len(word))
+            before = [self.grid[cell] for cell in line]
+            for cell, letter in zip(line, word):
+                self.grid[cell] = letter
+            placed.append(place)
+            if self.place(index + 1, placed):
+                return True
+            placed.pop()
+            for cell, letter in zip(line, before):
+                self.grid[cell] = letter
+        return False
+
+    def fill(self, filler: str) -> bool:
+        """
+        Fill the empty cells from filler, so no word is in more places than
+        the words placed put it (one, unless it is in another word); False if
+        no draw managed that.
+        """
+        empty = self.grid.find(EMPTY)
+        counts = [len(places(self.grid, word)) for word in self.words]
+        for _ in range(FILLER_TRIES):
+            for cell in empty:
+                self.grid[cell] = self.rng.choice(filler)
+            if all(len(places(self.grid, word)) == count for word, count in zip(self.words, counts)):
+                return True
+        return False
+
+
+def smallest(words: List[str], density: float) -> int:
+    """The side of the smallest square where the words' letters cover at most density of the cells."""
+    letters = sum(len(word) for word in words)
+    return max(max(len(word) for word in words), math.ceil(math.sqrt(letters / density)))
+
+
+def generate(words: List[str], rng: random.Random, size: Optional[Tuple[int, int]] = None, density: float = 0.6,
+             filler: Optional[str] = None, straight: bool = False) -> Tuple[Grid, Dict[str, Place]]:
+    """A grid with each word in one place, and where each is."""
+    directions = STRAIGHT if straight else ALL_DIRECTIONS
+    filler = filler or "".join(words)
+    side = smallest(words, density)
+    while True:
+        width, height = size or (side, side)
+        generator = Generator(words, width, height, rng, directions)
+        placed: List[Place] = []
+        if generator.place(0, placed) and generator.fill(filler):
+            return generator.grid, dict(zip(generator.words, placed))
+        if size is not None:
+            raise ValueError(f"the words do not fit on a {width}x{height} grid")
+        side += 1
+
+
+################################################################################
+###                           Reading and Writing                            ###
+################################################################################
+
+
+def read_words(text: str) -> List[str]:
+    words = []
+    for line in text.split("\n"):
+        if line.lstrip().startswith("#"):
+            continue
+        for word in line.replace(",", " ").split():
+            if not word.isalpha() or not word.isascii():
+                raise ValueError(f"a word is letters only, not {word!r}")
+            if word.upper() not in words:
+                words.append(word.upper())
+    if not words:
+        raise ValueError("there are no words")
+    return words
+
+
+def parse(text: str) -> Tuple[Grid, List[str]]:
+    lines = [line for line in text.strip("\n").split("\n") if not line.lstrip().startswith("#")]
+    if "" not in [line.strip() for line in lines]:
+        raise ValueError("expected the grid, a blank line, and the words")
+    blank = [line.strip() for line in lines].index("")
+    rows = [line.replace(" ", "").upper() for line in lines[:blank]]
+    for number, row in enumerate(rows, start=1):
+        if not row.isalpha() or not row.isascii():
+            raise ValueError(f"row {number}: a grid is letters only")
+    return Grid.parse("\n".join(rows)), read_words("\n".join(lines[blank + 1:]))
+
+
+def render(grid: Grid, found: Dict[str, List[Place]], hide: bool = False) -> str:
+    """The grid with spaces between the letters; with hide, only the letters of the words found."""
+    shown = grid.copy()
+    if hide:
+        used = {cell for word, at in found.items() for place in at for cell in place.cells(len(word))}
+        shown = grid.map(lambda _: EMPTY)
+        for cell in used:
+            shown[cell] = grid[cell]
+    return shown.render(separator=" ")
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve and generate word searches")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    solve_parser = subparsers.add_parser("solve", help="find the words in a grid")
+    solve_parser.add_argument("file", type=str, help="the grid, a blank line, and the words")
+    generate_parser = subparsers.add_parser("generate", help="hide the words in a new grid")
+    generate_parser.add_argument("words", type=str, help="a file of the words")
+    generate_parser.add_argument("--size", type=str, default=None, help="WxH (by default, from --density)")
+    generate_parser.add_argument("--density", type=float, default=0.6,
+                                 help="the most of the cells the words should cover (default 0.6)")
+    generate_parser.add_argument("--filler", type=str, default=None, help="the letters to fill the rest with")
+    generate_parser.add_argument("--straight", action="store_true", help="no backwards or upwards words")
+    add_seed_argument(generate_parser)
+    args = parser.parse_args()
+
+    try:
+        if args.command == "generate":
+            with open(args.words) as f:
+                words = read_words(f.read())
+            size = None
+            if args.size is not None:
+                width, x, height = args.size.partition("x")
+                if not x or not width.isdigit() or not height.isdigit() or int(width) < 1 or int(height) < 1:
+                    raise ValueError("the size must be WxH, e.g. 12x12")
+                size = int(width), int(height)
+            if not 0 < args.density <= 1:
+                raise ValueError("the density is more than 0 and at most 1")
+            filler = args.filler.upper() if args.filler else None
+            if filler is not None and (not filler.isalpha() or not filler.isascii()):
+                raise ValueError("the filler is letters only")
+            grid, placed = generate(words, make_rng(args.seed), size, args.density, filler, args.straight)
+            print(render(grid, {}))
+            print()
+            print(render(grid, {word: [place] for word, place in placed.items()}, hide=True))
+            print()
+            for word in words:
+                print(f"{word:<{max(map(len, words))}}  {placed[word]}")
+            return
+        with open(args.file) as f:
+            grid, words = parse(f.read())
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    found = solve(grid, words)
+    print(render(grid, found, hide=True))
+    print()
+    width = max(map(len, words))
+    for word in words:
+        print(f"{word:<{width}}  " + (", ".join(map(str, found[word])) or "not found"))
+    missing = [word for word in words if not found[word]]
+    if missing:
+        print(f"{len(missing)} of {len(words)} words not found")
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()
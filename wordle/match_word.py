@@ -1,11 +1,16 @@
 #!/bin/python3
 
-import random
+import argparse
+import os
 import re
+import sys
 from typing import Dict, List, Set, Tuple
 
 import numpy as np
 
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from rng.rng import add_seed_argument, make_rng
+
 
 DICTIONARY_PATH: str = "words_length_5.txt"
 WORD_LENGTH: int = 5
@@ -152,15 +157,16 @@ def test_word(key: str, guess: str):
 ################################################################################
 
 
-def test(num: int = None):
+def test(num: int = None, seed: int = None):
     # Load key words
     with open(DICTIONARY_PATH) as f:
         all_text_words = f.read().split("\n")
     
+    # Sample with our own generator, so a seed gives the same words every time
     test_words_list = (
         all_text_words 
         if num is None
-        else random.sample(all_text_words, k=num)
+        else make_rng(seed).sample(all_text_words, k=num)
     )
     num_words = len(test_words_list)
 
@@ -212,7 +218,7 @@ def test_key_word(key_word: str) -> int:
 ################################################################################
 
 
-def main():
+def play():
     # Step 1: Load words (separated by "\n")
     with open(DICTIONARY_PATH) as f:
         all_text_words = f.read().split("\n")
@@ -254,8 +260,24 @@ def main():
     return True
     
 
+def main():
+    parser = argparse.ArgumentParser(description="Suggest guesses for Wordle, or see how well they do")
+    subparsers = parser.add_subparsers(dest="command")
+    subparsers.add_parser("play", help="suggest the guesses for a game, from its replies (the default)")
+    test_parser = subparsers.add_parser("test", help="solve the words of the dictionary, and score the guesses")
+    test_parser.add_argument("--num", type=int, default=None, help="a random sample of this many words, not all")
+    add_seed_argument(test_parser, help="seed the sample")
+    word_parser = subparsers.add_parser("word", help="solve one word, showing the guesses")
+    word_parser.add_argument("word", type=str)
+    args = parser.parse_args()
+
+    if args.command == "test":
+        test(args.num, args.seed)
+    elif args.command == "word":
+        test_key_word(args.word)
+    else:
+        play()
+
+
 if __name__ == "__main__":
-    # main()
-    # test(50)
-    test_key_word("gazer")
-    r = input("PRESS ANY KEY TO CONTINUE")
+    main()
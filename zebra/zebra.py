@@ -0,0 +1,194 @@
+"""
+# Zebra Puzzles
+
+Also Einstein's puzzle: a row of houses, each with one of every kind of
+thing (a colour, a nationality, a drink, ...), and clues about which go
+together and which live next door, from which to work out the lot.
+
+
+## The Format
+
+First the categories, one per line, each a name, a colon, and its
+values: as many values as there are houses, and no value in two
+categories. Then the clues, one per line:
+
+    A = B               A and B are in the same house
+    A != B              they are not
+    A at N              A is in house N, counting from 1 on the left
+    A not at N          it is not
+    A next-to B         A and B are next door to each other
+    A left-of B         A is just left of B (and right-of, just right)
+    A before B          A is somewhere left of B (and after, somewhere
+                        right)
+
+Then optionally questions, `? A`, which are answered with A's house and
+everything else in it. Lines starting with # are comments.
+
+    colour: red green blue
+    pet: cat dog fish
+    red left-of dog
+    ...
+    ? fish
+
+
+## Solving
+
+By the shared CSP solver: a variable for every value, whose domain is
+the houses, all-different among the values of a category, and a binary
+constraint for each clue (a clue at a house just narrows the domain).
+The search keeps going after the first solution to see whether there is
+a second, so it can say whether the clues settle everything.
+
+
+## Usage
+
+    python3 zebra.py puzzles/zebra.txt
+    python3 zebra.py puzzles/cottages.txt --stats
+    python3 zebra.py puzzles/ambiguous.txt
+"""
+
+import argparse
+import itertools
+import os
+import re
+import sys
+from typing import Callable, Dict, List, Optional, Tuple
+
+sys.path.append(os.path.join(os.path.dirname(os.path.abspath(__file__)), ".."))
+from csp.csp import CSP, Statistics, solutions
+
+
+RELATIONS: Dict[str, Callable[[int, int], bool]] = {
+    "=": lambda a, b: a == b,
+    "!=": lambda a, b: a != b,
+    "next-to": lambda a, b: abs(a - b) == 1,
+    "left-of": lambda a, b: a == b - 1,
+    "right-of": lambda a, b: a == b + 1,
+    "before": lambda a, b: a < b,
+    "after": lambda a, b: a > b,
+}
+CLUE = re.compile(r"(\S+)\s+(" + "|".join(map(re.escape, RELATIONS)) + r")\s+(\S+)")
+POSITION = re.compile(r"(\S+)\s+(not\s+)?at\s+(\d+)")
+
+
+class Puzzle:
+    def __init__(self):
+        self.categories: List[Tuple[str, List[str]]] = []
+        self.category_of: Dict[str, int] = {}
+        self.clues: List[Tuple[str, str, str]] = []
+        self.positions: List[Tuple[str, bool, int]] = []    # (value, whether it is there, house)
+        self.questions: List[str] = []
+
+    @property
+    def houses(self) -> int:
+        return len(self.categories[0][1])
+
+
+def parse(text: str) -> Puzzle:
+    puzzle = Puzzle()
+    for line_number, line in enumerate(text.split("\n"), start=1):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        if ":" in line and not puzzle.clues and not puzzle.positions:
+            name, _, values = line.partition(":")
+            values = values.split()
+            if puzzle.categories and len(values) != puzzle.houses:
+                raise ValueError(f"line {line_number}: {len(values)} values, but {puzzle.houses} houses")
+            for value in values:
+                if value in puzzle.category_of:
+                    raise ValueError(f"line {line_number}: {value!r} is in two categories")
+                puzzle.category_of[value] = len(puzzle.categories)
+            puzzle.categories.append((name.strip(), values))
+            continue
+        if not puzzle.categories:
+            raise ValueError(f"line {line_number}: the categories come first, as 'name: value value ...'")
+        clue, position = CLUE.fullmatch(line), POSITION.fullmatch(line)
+        if line.startswith("?"):
+            names = [line[1:].strip()]
+            puzzle.questions.append(names[0])
+        elif position is not None:
+            names = [position.group(1)]
+            house = int(position.group(3))
+            if not 1 <= house <= puzzle.houses:
+                raise ValueError(f"line {line_number}: there is no house {house}")
+            puzzle.positions.append((names[0], position.group(2) is None, house))
+        elif clue is not None:
+            names = [clue.group(1), clue.group(3)]
+            puzzle.clues.append((clue.group(1), clue.group(2), clue.group(3)))
+        else:
+            raise ValueError(f"line {line_number}: expected a clue, like 'A next-to B' or 'A at 3'")
+        for name in names:
+            if name not in puzzle.category_of:
+                raise ValueError(f"line {line_number}: {name!r} is not a value of any category")
+    if not puzzle.categories:
+        raise ValueError("no categories")
+    return puzzle
+
+
+def to_csp(puzzle: Puzzle) -> CSP:
+    csp = CSP()
+    for _, values in puzzle.categories:
+        for value in values:
+            csp.add_variable(value, range(1, puzzle.houses + 1))
+        csp.all_different(values)
+    for value, there, house in puzzle.positions:
+        csp.domains[value] = [h for h in csp.domains[value] if (h == house) == there]
+    for a, relation, b in puzzle.clues:
+        if a == b:
+            raise ValueError(f"the clue '{a} {relation} {b}' relates {a} to itself")
+        csp.add_constraint([a, b], RELATIONS[relation])
+    return csp
+
+
+def solve(puzzle: Puzzle, limit: int, statistics: Optional[Statistics] = None) -> List[Dict[str, int]]:
+    """Up to limit solutions, each the house of every value."""
+    return list(itertools.islice(solutions(to_csp(puzzle), statistics=statistics), limit))
+
+
+def render(puzzle: Puzzle, houses: Dict[str, int]) -> str:
+    rows = [["", *(f"{house}" for house in range(1, puzzle.houses + 1))]]
+    for name, values in puzzle.categories:
+        row = [name] + [""] * puzzle.houses
+        for value in values:
+            row[houses[value]] = value
+        rows.append(row)
+    widths = [max(len(row[i]) for row in rows) for i in range(len(rows[0]))]
+    return "\n".join("  ".join(cell.ljust(width) for cell, width in zip(row, widths)).rstrip() for row in rows)
+
+
+def answer(puzzle: Puzzle, houses: Dict[str, int], question: str) -> str:
+    house = houses[question]
+    others = [value for _, values in puzzle.categories for value in values
+              if houses[value] == house and value != question]
+    return f"{question}: house {house}, with {', '.join(others)}"
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Solve Zebra puzzles")
+    parser.add_argument("file", type=str, help="the puzzle, in the format above")
+    parser.add_argument("--stats", action="store_true", help="print the assignments the search tried")
+    args = parser.parse_args()
+
+    try:
+        with open(args.file) as f:
+            puzzle = parse(f.read())
+        statistics = Statistics()
+        found = solve(puzzle, limit=2, statistics=statistics)
+    except (OSError, ValueError) as e:
+        parser.exit(1, f"error: {e}\n")
+    if not found:
+        print("No solution")
+    else:
+        print(render(puzzle, found[0]))
+        for question in puzzle.questions:
+            print(answer(puzzle, found[0], question))
+        print("One solution" if len(found) == 1 else "More than one solution; this is the first")
+    if args.stats:
+        print(statistics)
+    if not found:
+        parser.exit(1)
+
+
+if __name__ == "__main__":
+    main()